@@ -5,6 +5,6 @@
 
 pub mod server;
 
-pub use server::LspServer;
 pub use server::formatting;
 pub use server::test_helpers;
+pub use server::{LspError, LspServer};
@@ -1,26 +1,50 @@
+use std::collections::HashMap;
 use std::ops::ControlFlow;
 use std::time::Duration;
 
 use async_lsp::client_monitor::ClientProcessMonitorLayer;
 use async_lsp::concurrency::ConcurrencyLayer;
-use async_lsp::lsp_types::request::GotoTypeDefinitionParams;
+use async_lsp::lsp_types::request::{GotoImplementationParams, GotoTypeDefinitionParams};
 use async_lsp::lsp_types::*;
 use async_lsp::panic::CatchUnwindLayer;
 use async_lsp::router::Router;
 use async_lsp::server::LifecycleLayer;
 use async_lsp::tracing::TracingLayer;
-use async_lsp::{ClientSocket, LanguageClient, LanguageServer, ResponseError};
+use async_lsp::{ClientSocket, ErrorCode, LanguageClient, LanguageServer, ResponseError};
 use futures::future::BoxFuture;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tower::ServiceBuilder;
 use tracing::{Level, info};
 
 mod server;
 use server::LspServer;
-use server::background_tasks::{debounce, events::ParseDocument};
+use server::background_tasks::events::{
+    ContinueValidation, IndexingComplete, ParseDocument, ReferenceCandidates,
+    ReferenceSearchFinished, ValidateProject,
+};
+use server::background_tasks::{debounce, indexing, reference_search};
+use server::client_support::{ClientSupport, GotoRequest};
+use server::configuration::ServerSettings;
 use server::diagram::GetDiagramRequest;
+use server::diagram_text::ExportDiagramTextRequest;
+use server::feature_support::GetFeatureSupportMatrixRequest;
+use server::health::HealthCheckRequest;
 use server::helpers::uri_to_path;
+use server::implicit_supertype::ImplicitSupertypeRequest;
+use server::inline_completion::InlineCompletionRequest;
+use server::memory_stats::GetMemoryStatsRequest;
+use server::model_export::ExportModelRequest;
+use server::package_graph::GetDependencyGraphRequest;
+use server::project_validation::VALIDATION_CHUNK_SIZE;
+use server::qualified_name::QualifiedNameAtRequest;
+use server::reference_search::{ReferencesProgress, ReferencesProgressParams};
+use server::requirement_status::RequirementDecorationsRequest;
+use server::requirement_trace::ValidateRequirementsRequest;
+use server::resolve_spans::ResolveSpansRequest;
+use server::snapshot::ExportWorkspaceSnapshotRequest;
 use server::type_info::TypeInfoRequest;
+use server::unused_elements::GetUnusedElementsRequest;
+use server::watched_files::WatchedFilesUpdate;
 
 /// Server state that owns the LspServer and client socket
 struct ServerState {
@@ -28,6 +52,25 @@ struct ServerState {
     server: LspServer,
     /// Channel to send parse requests to the debounce task
     parse_tx: mpsc::UnboundedSender<Url>,
+    /// Channel to the project validation debouncer, unless validation is off
+    validate_tx: Option<mpsc::UnboundedSender<()>>,
+    /// A `workspace/diagnostic` request waiting for diagnostics to change
+    held_workspace_diagnostics: Option<HeldWorkspaceDiagnostics>,
+    /// Replies to reference searches running in shards, by search id
+    reference_replies: HashMap<u64, ReferenceReply>,
+}
+
+/// A `workspace/diagnostic` request with nothing new to report yet
+struct HeldWorkspaceDiagnostics {
+    previous_result_ids: Vec<PreviousResultId>,
+    sender: oneshot::Sender<Result<WorkspaceDiagnosticReportResult, ResponseError>>,
+}
+
+/// Where the result of a sharded reference search goes
+struct ReferenceReply {
+    sender: oneshot::Sender<Result<Option<Vec<Location>>, ResponseError>>,
+    /// Set when the client asked for the references as partial results
+    partial_result_token: Option<ProgressToken>,
 }
 
 impl LanguageServer for ServerState {
@@ -38,10 +81,24 @@ impl LanguageServer for ServerState {
         &mut self,
         params: InitializeParams,
     ) -> BoxFuture<'static, Result<InitializeResult, Self::Error>> {
-        let (stdlib_enabled, stdlib_path) =
-            LspServer::parse_init_options(params.initialization_options);
+        let validation_delay =
+            ServerSettings::validation_delay(params.initialization_options.as_ref());
+        let settings = ServerSettings::from_options(params.initialization_options.as_ref());
+
+        self.server = LspServer::with_config(settings.stdlib_enabled, settings.stdlib_path.clone());
+        self.server.apply_settings(settings);
+        self.server
+            .set_stdlib_cache_dir(server::stdlib_cache::default_cache_dir());
 
-        self.server = LspServer::with_config(stdlib_enabled, stdlib_path);
+        // Spawn debounced project validation: waits for edits to pause for the configured delay
+        self.validate_tx = validation_delay.map(|delay| {
+            let (validate_tx, validate_rx) = mpsc::unbounded_channel::<()>();
+            let emit_client = self.client.clone();
+            debounce::spawn(delay, validate_rx, move |()| {
+                emit_client.emit(ValidateProject).is_ok()
+            });
+            validate_tx
+        });
 
         // Extract workspace folders from initialization params
         let mut folders = Vec::new();
@@ -66,10 +123,60 @@ impl LanguageServer for ServerState {
 
         self.server.set_workspace_folders(folders);
 
+        // Handlers only use what the client declared support for
+        self.server
+            .set_client_support(ClientSupport::from_capabilities(&params.capabilities));
+
         let result = LspServer::initialize_result();
         Box::pin(async move { Ok(result) })
     }
 
+    fn initialized(&mut self, _params: InitializedParams) -> Self::NotifyResult {
+        // Index the stdlib and workspace folders without blocking requests
+        if let Some(job) = self.server.begin_background_indexing() {
+            indexing::spawn(job, self.client.clone(), self.server.work_done_progress());
+        }
+
+        // Type hierarchy can only be advertised through dynamic registration
+        if let Some(registration) = self.server.type_hierarchy_registration() {
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                let params = RegistrationParams {
+                    registrations: vec![registration],
+                };
+                if let Err(err) = client.request::<request::RegisterCapability>(params).await {
+                    tracing::warn!("Failed to register type hierarchy: {err}");
+                }
+            });
+        }
+
+        if let Some(registration) = self.server.inline_completion_registration() {
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                let params = RegistrationParams {
+                    registrations: vec![registration],
+                };
+                if let Err(err) = client.request::<request::RegisterCapability>(params).await {
+                    tracing::warn!("Failed to register inline completion: {err}");
+                }
+            });
+        }
+
+        // Watch workspace files so changes made outside the editor are picked up
+        if let Some(registration) = self.server.watched_files_registration() {
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                let params = RegistrationParams {
+                    registrations: vec![registration],
+                };
+                if let Err(err) = client.request::<request::RegisterCapability>(params).await {
+                    tracing::warn!("Failed to register file watcher: {err}");
+                }
+            });
+        }
+        ControlFlow::Continue(())
+    }
+
     fn hover(
         &mut self,
         params: HoverParams,
@@ -89,7 +196,11 @@ impl LanguageServer for ServerState {
         let result = self
             .server
             .get_definition(&uri, position)
-            .map(GotoDefinitionResponse::Scalar);
+            .map(GotoDefinitionResponse::Scalar)
+            .map(|response| {
+                self.server
+                    .goto_response(GotoRequest::Definition, &uri, position, response)
+            });
         Box::pin(async move { Ok(result) })
     }
 
@@ -102,7 +213,26 @@ impl LanguageServer for ServerState {
         let result = self
             .server
             .get_type_definition(&uri, position)
-            .map(GotoDefinitionResponse::Scalar);
+            .map(GotoDefinitionResponse::Scalar)
+            .map(|response| {
+                self.server
+                    .goto_response(GotoRequest::TypeDefinition, &uri, position, response)
+            });
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn implementation(
+        &mut self,
+        params: GotoImplementationParams,
+    ) -> BoxFuture<'static, Result<Option<GotoDefinitionResponse>, Self::Error>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let locations = self.server.get_implementations(&uri, position);
+        let result = (!locations.is_empty()).then(|| {
+            let response = GotoDefinitionResponse::Array(locations);
+            self.server
+                .goto_response(GotoRequest::Implementation, &uri, position, response)
+        });
         Box::pin(async move { Ok(result) })
     }
 
@@ -113,12 +243,103 @@ impl LanguageServer for ServerState {
         let uri = params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
         let include_declaration = params.context.include_declaration;
+
+        // Large workspaces are searched file by file on the blocking pool
+        if self.server.parallel_reference_search()
+            && let Some(job) =
+                self.server
+                    .start_reference_search(&uri, position, include_declaration)
+        {
+            let (sender, receiver) = oneshot::channel();
+            self.reference_replies.insert(
+                job.id,
+                ReferenceReply {
+                    sender,
+                    partial_result_token: params.partial_result_params.partial_result_token,
+                },
+            );
+            // Cancelling the request drops the response, which stops the workers
+            let cancel_guard = job.cancel_on_drop();
+            reference_search::spawn(job, self.client.clone());
+            return Box::pin(async move {
+                let _cancel_guard = cancel_guard;
+                receiver.await.unwrap_or_else(|_| {
+                    Err(ResponseError::new(
+                        ErrorCode::REQUEST_FAILED,
+                        "Reference search was abandoned",
+                    ))
+                })
+            });
+        }
+
         let result = self
             .server
             .get_references(&uri, position, include_declaration);
         Box::pin(async move { Ok(result) })
     }
 
+    fn document_highlight(
+        &mut self,
+        params: DocumentHighlightParams,
+    ) -> BoxFuture<'static, Result<Option<Vec<DocumentHighlight>>, Self::Error>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let result = self.server.get_document_highlights(&uri, position);
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn prepare_call_hierarchy(
+        &mut self,
+        params: CallHierarchyPrepareParams,
+    ) -> BoxFuture<'static, Result<Option<Vec<CallHierarchyItem>>, Self::Error>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let result = self.server.prepare_call_hierarchy(&uri, position);
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn incoming_calls(
+        &mut self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> BoxFuture<'static, Result<Option<Vec<CallHierarchyIncomingCall>>, Self::Error>> {
+        let result = self.server.get_incoming_calls(&params.item);
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn outgoing_calls(
+        &mut self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> BoxFuture<'static, Result<Option<Vec<CallHierarchyOutgoingCall>>, Self::Error>> {
+        let result = self.server.get_outgoing_calls(&params.item);
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn prepare_type_hierarchy(
+        &mut self,
+        params: TypeHierarchyPrepareParams,
+    ) -> BoxFuture<'static, Result<Option<Vec<TypeHierarchyItem>>, Self::Error>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let result = self.server.prepare_type_hierarchy(&uri, position);
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn supertypes(
+        &mut self,
+        params: TypeHierarchySupertypesParams,
+    ) -> BoxFuture<'static, Result<Option<Vec<TypeHierarchyItem>>, Self::Error>> {
+        let result = self.server.get_supertypes(&params.item);
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn subtypes(
+        &mut self,
+        params: TypeHierarchySubtypesParams,
+    ) -> BoxFuture<'static, Result<Option<Vec<TypeHierarchyItem>>, Self::Error>> {
+        let result = self.server.get_subtypes(&params.item);
+        Box::pin(async move { Ok(result) })
+    }
+
     fn document_symbol(
         &mut self,
         params: DocumentSymbolParams,
@@ -145,6 +366,74 @@ impl LanguageServer for ServerState {
         Box::pin(async move { Ok(result) })
     }
 
+    fn semantic_tokens_full_delta(
+        &mut self,
+        params: SemanticTokensDeltaParams,
+    ) -> BoxFuture<'static, Result<Option<SemanticTokensFullDeltaResult>, Self::Error>> {
+        let uri = params.text_document.uri;
+        let result = self
+            .server
+            .get_semantic_tokens_delta(&uri, &params.previous_result_id);
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn semantic_tokens_range(
+        &mut self,
+        params: SemanticTokensRangeParams,
+    ) -> BoxFuture<'static, Result<Option<SemanticTokensRangeResult>, Self::Error>> {
+        let uri = params.text_document.uri;
+        let result = self.server.get_semantic_tokens_range(&uri, params.range);
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn document_diagnostic(
+        &mut self,
+        params: DocumentDiagnosticParams,
+    ) -> BoxFuture<'static, Result<DocumentDiagnosticReportResult, Self::Error>> {
+        let result = self.server.get_document_diagnostic_report(
+            &params.text_document.uri,
+            params.previous_result_id.as_deref(),
+        );
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn workspace_diagnostic(
+        &mut self,
+        params: WorkspaceDiagnosticParams,
+    ) -> BoxFuture<'static, Result<WorkspaceDiagnosticReportResult, Self::Error>> {
+        // Clients keep one pull open; an older one is answered as it stands
+        if let Some(held) = self.held_workspace_diagnostics.take()
+            && !held.sender.is_closed()
+        {
+            let result = self
+                .server
+                .get_workspace_diagnostic_report(&held.previous_result_ids);
+            let _ = held.sender.send(Ok(result));
+        }
+        if let Some(result) = self
+            .server
+            .changed_workspace_diagnostics(&params.previous_result_ids)
+        {
+            return Box::pin(async move { Ok(result) });
+        }
+
+        // Nothing changed: answer once some document's diagnostics do. A
+        // cancelled request drops the receiver, which closes the sender.
+        let (sender, receiver) = oneshot::channel();
+        self.held_workspace_diagnostics = Some(HeldWorkspaceDiagnostics {
+            previous_result_ids: params.previous_result_ids,
+            sender,
+        });
+        Box::pin(async move {
+            receiver.await.unwrap_or_else(|_| {
+                Err(ResponseError::new(
+                    ErrorCode::REQUEST_FAILED,
+                    "Workspace diagnostics request was abandoned",
+                ))
+            })
+        })
+    }
+
     fn completion(
         &mut self,
         params: CompletionParams,
@@ -165,6 +454,14 @@ impl LanguageServer for ServerState {
         let uri = params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
         let new_name = params.new_name;
+        if let Err(message) = self.server.validate_rename(&uri, position, &new_name) {
+            return Box::pin(async move {
+                Err(ResponseError::new(
+                    ErrorCode::REQUEST_FAILED,
+                    message.to_string(),
+                ))
+            });
+        }
         let result = self.server.get_rename_edits(&uri, position, &new_name);
         Box::pin(async move { Ok(result) })
     }
@@ -174,10 +471,10 @@ impl LanguageServer for ServerState {
         params: DocumentFormattingParams,
     ) -> BoxFuture<'static, Result<Option<Vec<TextEdit>>, Self::Error>> {
         let uri = params.text_document.uri;
-        let options = params.options;
+        let options = self.server.project_formatting_options(&uri, params.options);
 
-        // Snapshot the text synchronously - this is fast
-        let text_snapshot = self.server.get_document_text(&uri);
+        // Snapshot the text and its version synchronously - this is fast
+        let snapshot = self.server.get_document_snapshot(&uri);
 
         // Get the current cancellation token for this document.
         let cancel_token = uri
@@ -187,7 +484,7 @@ impl LanguageServer for ServerState {
             .unwrap_or_default();
 
         Box::pin(server::formatting::format_document(
-            text_snapshot,
+            snapshot,
             options,
             cancel_token,
         ))
@@ -198,10 +495,10 @@ impl LanguageServer for ServerState {
         params: DocumentRangeFormattingParams,
     ) -> BoxFuture<'static, Result<Option<Vec<TextEdit>>, Self::Error>> {
         let uri = params.text_document.uri;
-        let options = params.options;
+        let options = self.server.project_formatting_options(&uri, params.options);
         let range = params.range;
 
-        let text_snapshot = self.server.get_document_text(&uri);
+        let snapshot = self.server.get_document_snapshot(&uri);
 
         let cancel_token = uri
             .to_file_path()
@@ -210,13 +507,38 @@ impl LanguageServer for ServerState {
             .unwrap_or_default();
 
         Box::pin(server::formatting::format_range_document(
-            text_snapshot,
+            snapshot,
             options,
             cancel_token,
             range,
         ))
     }
 
+    fn on_type_formatting(
+        &mut self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> BoxFuture<'static, Result<Option<Vec<TextEdit>>, Self::Error>> {
+        let uri = params.text_document_position.text_document.uri;
+        let options = self.server.project_formatting_options(&uri, params.options);
+        let position = params.text_document_position.position;
+
+        let snapshot = self.server.get_document_snapshot(&uri);
+
+        let cancel_token = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| self.server.get_document_cancel_token(&path))
+            .unwrap_or_default();
+
+        Box::pin(server::formatting::format_on_type_document(
+            snapshot,
+            options,
+            cancel_token,
+            position,
+            params.ch,
+        ))
+    }
+
     fn prepare_rename(
         &mut self,
         params: TextDocumentPositionParams,
@@ -262,6 +584,26 @@ impl LanguageServer for ServerState {
         Box::pin(async move { Ok(result) })
     }
 
+    fn linked_editing_range(
+        &mut self,
+        params: LinkedEditingRangeParams,
+    ) -> BoxFuture<'static, Result<Option<LinkedEditingRanges>, Self::Error>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let result = self.server.get_linked_editing_ranges(&uri, position);
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn moniker(
+        &mut self,
+        params: MonikerParams,
+    ) -> BoxFuture<'static, Result<Option<Vec<Moniker>>, Self::Error>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let result = self.server.get_monikers(&uri, position);
+        Box::pin(async move { Ok(result) })
+    }
+
     fn inlay_hint(
         &mut self,
         params: InlayHintParams,
@@ -284,6 +626,27 @@ impl LanguageServer for ServerState {
         Box::pin(async move { Ok(result) })
     }
 
+    fn code_lens_resolve(
+        &mut self,
+        params: CodeLens,
+    ) -> BoxFuture<'static, Result<CodeLens, Self::Error>> {
+        let result = self.server.resolve_code_lens(params);
+        Box::pin(async move { Ok(result) })
+    }
+
+    fn code_action(
+        &mut self,
+        params: CodeActionParams,
+    ) -> BoxFuture<'static, Result<Option<CodeActionResponse>, Self::Error>> {
+        let actions = self.server.get_code_actions(&params);
+        let result = if actions.is_empty() {
+            None
+        } else {
+            Some(actions)
+        };
+        Box::pin(async move { Ok(result) })
+    }
+
     fn document_link(
         &mut self,
         params: DocumentLinkParams,
@@ -313,17 +676,21 @@ impl LanguageServer for ServerState {
         let uri = params.text_document.uri.clone();
         let text = params.text_document.text;
         info!("did_open: {}", uri);
+        self.server
+            .set_document_version(&uri, params.text_document.version);
 
         match self.server.open_document(&uri, &text) {
             Ok(_) => {
-                let diagnostics = self.server.get_diagnostics(&uri);
-                let _ = self.client.publish_diagnostics(PublishDiagnosticsParams {
-                    uri,
-                    diagnostics,
-                    version: None,
-                });
+                if let Some(diagnostics) = self.server.diagnostics_to_publish(&uri) {
+                    let _ = self.client.publish_diagnostics(PublishDiagnosticsParams {
+                        uri,
+                        diagnostics,
+                        version: None,
+                    });
+                }
             }
             Err(e) => {
+                self.server.record_document_error();
                 let _ = self.client.log_message(LogMessageParams {
                     typ: MessageType::ERROR,
                     message: format!("Failed to open document {uri}: {e}"),
@@ -346,10 +713,14 @@ impl LanguageServer for ServerState {
         if let Ok(path) = uri.to_file_path() {
             self.server.cancel_document_operations(&path);
         }
+        // Formatting computed against an older version is now stale
+        self.server
+            .set_document_version(&uri, params.text_document.version);
 
         // Apply text changes only (fast - just string manipulation)
         for change in params.content_changes {
             if let Err(e) = self.server.apply_text_change_only(&uri, &change) {
+                self.server.record_document_error();
                 let _ = self.client.log_message(LogMessageParams {
                     typ: MessageType::ERROR,
                     message: format!("Failed to apply change to {uri}: {e}"),
@@ -367,6 +738,7 @@ impl LanguageServer for ServerState {
     fn did_close(&mut self, params: DidCloseTextDocumentParams) -> Self::NotifyResult {
         let uri = params.text_document.uri;
         if let Err(e) = self.server.close_document(&uri) {
+            self.server.record_document_error();
             let _ = self.client.log_message(LogMessageParams {
                 typ: MessageType::ERROR,
                 message: format!("Failed to close document {uri}: {e}"),
@@ -381,15 +753,191 @@ impl LanguageServer for ServerState {
 
     fn did_change_watched_files(
         &mut self,
-        _params: DidChangeWatchedFilesParams,
+        params: DidChangeWatchedFilesParams,
     ) -> Self::NotifyResult {
-        // Currently we don't need to react to file system changes
-        // The workspace is updated when files are opened/changed via the editor
+        let update = self.server.apply_watched_file_changes(&params.changes);
+        self.publish_file_changes(update);
+        ControlFlow::Continue(())
+    }
+
+    fn did_change_configuration(
+        &mut self,
+        params: DidChangeConfigurationParams,
+    ) -> Self::NotifyResult {
+        // Clients pulling settings with workspace/configuration send no values
+        let Some(settings) = ServerSettings::from_change(&params.settings) else {
+            return ControlFlow::Continue(());
+        };
+        info!("Applying changed configuration");
+        let update = self.server.apply_settings(settings);
+        self.publish_file_changes(update);
+        ControlFlow::Continue(())
+    }
+
+    fn did_change_workspace_folders(
+        &mut self,
+        params: DidChangeWorkspaceFoldersParams,
+    ) -> Self::NotifyResult {
+        let paths = |folders: Vec<WorkspaceFolder>| {
+            folders
+                .into_iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect()
+        };
+        let update = self
+            .server
+            .change_workspace_folders(paths(params.event.added), paths(params.event.removed));
+        self.publish_file_changes(update);
         ControlFlow::Continue(())
     }
 }
 
 impl ServerState {
+    /// Publish the diagnostics of documents loaded, changed or unloaded
+    /// outside the editor
+    fn publish_file_changes(&mut self, update: WatchedFilesUpdate) {
+        // Unloaded files keep no diagnostics
+        for uri in update.removed {
+            let _ = self.client.publish_diagnostics(PublishDiagnosticsParams {
+                uri,
+                diagnostics: Vec::new(),
+                version: None,
+            });
+        }
+        for uri in update.changed {
+            if let Some(diagnostics) = self.server.diagnostics_to_publish(&uri) {
+                let _ = self.client.publish_diagnostics(PublishDiagnosticsParams {
+                    uri,
+                    diagnostics,
+                    version: None,
+                });
+            }
+        }
+        // Changes on disk can affect the diagnostics of open documents
+        self.refresh_diagnostics();
+        self.answer_held_workspace_diagnostics();
+        self.schedule_validation();
+    }
+
+    /// Have a pulling client request diagnostics again
+    fn refresh_diagnostics(&self) {
+        if self.server.diagnostic_refresh_support() {
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                if let Err(err) = client
+                    .request::<request::WorkspaceDiagnosticRefresh>(())
+                    .await
+                {
+                    tracing::warn!("Failed to refresh diagnostics: {err}");
+                }
+            });
+        }
+    }
+
+    /// Answer a held `workspace/diagnostic` request once diagnostics have
+    /// changed, dropping it if the client cancelled it meanwhile
+    fn answer_held_workspace_diagnostics(&mut self) {
+        let Some(held) = self.held_workspace_diagnostics.take() else {
+            return;
+        };
+        if held.sender.is_closed() {
+            return;
+        }
+        match self
+            .server
+            .changed_workspace_diagnostics(&held.previous_result_ids)
+        {
+            Some(result) => {
+                let _ = held.sender.send(Ok(result));
+            }
+            None => self.held_workspace_diagnostics = Some(held),
+        }
+    }
+
+    /// Validate the whole project once edits pause, restarting the delay
+    fn schedule_validation(&self) {
+        if let Some(validate_tx) = &self.validate_tx {
+            let _ = validate_tx.send(());
+        }
+    }
+
+    /// Keep the references a search found in one file, streaming them to a
+    /// client that asked for partial results
+    fn continue_reference_search(&mut self, event: ReferenceCandidates) {
+        let Some(reply) = self.reference_replies.get(&event.search) else {
+            return;
+        };
+        // The request was cancelled: stop the workers
+        if reply.sender.is_closed() {
+            self.server.cancel_reference_search(event.search);
+            self.reference_replies.remove(&event.search);
+            return;
+        }
+        if !self
+            .server
+            .accept_reference_candidates(event.search, &event.path, &event.entries)
+        {
+            return;
+        }
+        if let Some(token) = reply.partial_result_token.clone() {
+            self.stream_references(event.search, token);
+        }
+    }
+
+    /// Send the references found since the last batch as a partial result
+    fn stream_references(&mut self, search: u64, token: ProgressToken) {
+        let value = self.server.take_unreported_references(search);
+        if !value.is_empty() {
+            let _ = self
+                .client
+                .notify::<ReferencesProgress>(ReferencesProgressParams { token, value });
+        }
+    }
+
+    /// Answer a reference search once all of its shards have been scanned
+    fn finish_reference_search(&mut self, search: u64) {
+        let Some(reply) = self.reference_replies.remove(&search) else {
+            return;
+        };
+        if let Some(token) = reply.partial_result_token.clone() {
+            self.stream_references(search, token);
+        }
+        let result = match self.server.finish_reference_search(search) {
+            // Everything was streamed as partial results, so the response is empty
+            Some(_) if reply.partial_result_token.is_some() => Ok(Some(Vec::new())),
+            Some(locations) => Ok(Some(locations)),
+            None => Err(ResponseError::new(
+                ErrorCode::CONTENT_MODIFIED,
+                "Documents changed during the reference search",
+            )),
+        };
+        let _ = reply.sender.send(result);
+    }
+
+    /// Publish the diagnostics of the next files of a validation run, and
+    /// queue the rest behind any requests that arrived meanwhile
+    fn continue_validation(&mut self, generation: u64) {
+        let Some(step) = self
+            .server
+            .continue_project_validation(generation, VALIDATION_CHUNK_SIZE)
+        else {
+            return;
+        };
+        for (uri, diagnostics) in step.diagnostics {
+            let _ = self.client.publish_diagnostics(PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version: None,
+            });
+        }
+        self.answer_held_workspace_diagnostics();
+        if step.finished {
+            self.refresh_diagnostics();
+        } else {
+            let _ = self.client.emit(ContinueValidation { generation });
+        }
+    }
+
     fn new_router(client: ClientSocket) -> Router<Self> {
         let (parse_tx, parse_rx) = mpsc::unbounded_channel::<Url>();
 
@@ -405,18 +953,93 @@ impl ServerState {
             client,
             server: LspServer::new(),
             parse_tx,
+            validate_tx: None,
+            held_workspace_diagnostics: None,
+            reference_replies: HashMap::new(),
         });
 
         // Handle ParseDocument events
         router.event(|state: &mut ServerState, event: ParseDocument| {
             state.server.parse_document(&event.uri);
+            let dependents = state.server.take_dependent_documents();
 
-            let diagnostics = state.server.get_diagnostics(&event.uri);
-            let _ = state.client.publish_diagnostics(PublishDiagnosticsParams {
-                uri: event.uri,
-                diagnostics,
-                version: None,
-            });
+            // Unchanged diagnostics aren't resent, so clients don't redraw them
+            for uri in std::iter::once(event.uri).chain(dependents.iter().cloned()) {
+                if let Some(diagnostics) = state.server.diagnostics_to_publish(&uri) {
+                    let _ = state.client.publish_diagnostics(PublishDiagnosticsParams {
+                        uri,
+                        diagnostics,
+                        version: None,
+                    });
+                }
+            }
+            // Pulling clients ask again for the dependents' diagnostics
+            if !dependents.is_empty() {
+                state.refresh_diagnostics();
+            }
+            state.answer_held_workspace_diagnostics();
+            state.schedule_validation();
+            ControlFlow::Continue(())
+        });
+
+        // Handle ValidateProject events: start a run once edits have paused
+        router.event(|state: &mut ServerState, _: ValidateProject| {
+            let generation = state.server.start_project_validation();
+            state.continue_validation(generation);
+            ControlFlow::Continue(())
+        });
+
+        // Handle ContinueValidation events: the next files of a run, after queued requests
+        router.event(|state: &mut ServerState, event: ContinueValidation| {
+            state.continue_validation(event.generation);
+            ControlFlow::Continue(())
+        });
+
+        // Handle ReferenceCandidates events: verify and stream a shard's references
+        router.event(|state: &mut ServerState, event: ReferenceCandidates| {
+            state.continue_reference_search(event);
+            ControlFlow::Continue(())
+        });
+
+        // Handle ReferenceSearchFinished events: answer the references request
+        router.event(|state: &mut ServerState, event: ReferenceSearchFinished| {
+            state.finish_reference_search(event.search);
+            ControlFlow::Continue(())
+        });
+
+        // Handle IndexingComplete events: merge loaded files and refresh diagnostics
+        router.event(|state: &mut ServerState, event: IndexingComplete| {
+            for uri in state.server.finish_background_indexing(event.host) {
+                if let Some(diagnostics) = state.server.diagnostics_to_publish(&uri) {
+                    let _ = state.client.publish_diagnostics(PublishDiagnosticsParams {
+                        uri,
+                        diagnostics,
+                        version: None,
+                    });
+                }
+            }
+
+            // Tokens and hints computed before the stdlib and workspace were
+            // loaded show references as unresolved; have the client re-request them
+            if state.server.semantic_tokens_refresh_support() {
+                let client = state.client.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = client.request::<request::SemanticTokensRefresh>(()).await {
+                        tracing::warn!("Failed to refresh semantic tokens: {err}");
+                    }
+                });
+            }
+            if state.server.inlay_hint_refresh_support() {
+                let client = state.client.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = client.request::<request::InlayHintRefreshRequest>(()).await {
+                        tracing::warn!("Failed to refresh inlay hints: {err}");
+                    }
+                });
+            }
+            state.refresh_diagnostics();
+            state.answer_held_workspace_diagnostics();
+            state.schedule_validation();
             ControlFlow::Continue(())
         });
 
@@ -429,7 +1052,82 @@ impl ServerState {
                 .and_then(|uri| Url::parse(uri).ok())
                 .and_then(|url| url.to_file_path().ok());
             let view_type = &params.view_type;
-            let result = state.server.get_diagram(file_path.as_deref(), view_type);
+            let result = state.server.get_diagram(
+                file_path.as_deref(),
+                params.element.as_deref(),
+                params.depth,
+                view_type,
+            );
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/exportDiagramText
+        // Renders diagram data as PlantUML or Mermaid text
+        router.request::<ExportDiagramTextRequest, _>(|state, params| {
+            let file_path = params
+                .uri
+                .as_ref()
+                .and_then(|uri| Url::parse(uri).ok())
+                .and_then(|url| url.to_file_path().ok());
+            let result = state.server.export_diagram_text(
+                file_path.as_deref(),
+                params.element.as_deref(),
+                &params.view_type,
+                params.format,
+            );
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/exportModel
+        // Returns the resolved model of a file or the workspace as JSON
+        router.request::<ExportModelRequest, _>(|state, params| {
+            let file_path = params
+                .uri
+                .as_ref()
+                .and_then(|uri| Url::parse(uri).ok())
+                .and_then(|url| url.to_file_path().ok());
+            let result = state
+                .server
+                .export_model(file_path.as_deref(), params.include_stdlib);
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/getDependencyGraph
+        // Returns the package-level import graph
+        router.request::<GetDependencyGraphRequest, _>(|state, params| {
+            let result = state.server.get_dependency_graph(params.include_stdlib);
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/unusedElements
+        // Lists definitions nothing outside their own file refers to
+        router.request::<GetUnusedElementsRequest, _>(|state, params| {
+            let path = params
+                .uri
+                .and_then(|uri| Url::parse(&uri).ok())
+                .and_then(|uri| uri_to_path(&uri));
+            let result = state.server.get_unused_elements(path.as_deref());
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/validateRequirements
+        // Reports what satisfies, verifies and derives from each requirement
+        router.request::<ValidateRequirementsRequest, _>(|state, params| {
+            let path = params
+                .uri
+                .and_then(|uri| Url::parse(&uri).ok())
+                .and_then(|uri| uri_to_path(&uri));
+            let result = state.server.validate_requirements(path.as_deref());
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/requirementDecorations
+        // Reports whether each requirement of a document is satisfied or verified
+        router.request::<RequirementDecorationsRequest, _>(|state, params| {
+            let path = Url::parse(&params.uri)
+                .ok()
+                .and_then(|uri| uri_to_path(&uri));
+            let result = path.map(|path| state.server.requirement_decorations(&path));
             Box::pin(async move { Ok(result) })
         });
 
@@ -441,6 +1139,68 @@ impl ServerState {
             Box::pin(async move { Ok(result) })
         });
 
+        // LSP 3.18 request: textDocument/inlineCompletion
+        // Suggests the skeleton of an element's body after its opening brace
+        router.request::<InlineCompletionRequest, _>(|state, params| {
+            let result = state
+                .server
+                .get_inline_completions(&params.text_document.uri, params.position);
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/getQualifiedNameAt
+        // Returns the qualified name of the element declared or referenced at a position
+        router.request::<QualifiedNameAtRequest, _>(|state, params| {
+            let uri = Url::parse(&params.uri).ok();
+            let result = uri.and_then(|u| state.server.get_qualified_name_at(&u, params.position));
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/getImplicitSupertype
+        // Returns the library element the element at a position implicitly specializes
+        router.request::<ImplicitSupertypeRequest, _>(|state, params| {
+            let uri = Url::parse(&params.uri).ok();
+            let result = uri.and_then(|u| state.server.get_implicit_supertype(&u, params.position));
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/getMemoryStats
+        // Returns approximate memory usage per structure, optionally trimming caches
+        router.request::<GetMemoryStatsRequest, _>(|state, params| {
+            let result = state.server.get_memory_stats(params.trim_caches);
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/exportWorkspaceSnapshot
+        // Returns open documents and configuration for attaching to bug reports
+        router.request::<ExportWorkspaceSnapshotRequest, _>(|state, params| {
+            let result = state
+                .server
+                .export_workspace_snapshot(params.include_file_hashes);
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/healthCheck
+        // Returns readiness, pending parses and error counts while the server starts
+        router.request::<HealthCheckRequest, _>(|state, _| {
+            let result = state.server.health_check();
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/resolveSpans
+        // Converts element names and byte spans to LSP locations in bulk
+        router.request::<ResolveSpansRequest, _>(|state, params| {
+            let result = state.server.resolve_spans(&params);
+            Box::pin(async move { Ok(result) })
+        });
+
+        // Custom request: syster/getFeatureSupportMatrix
+        // Returns which constructs hover, definition, tokens and diagnostics support
+        router.request::<GetFeatureSupportMatrixRequest, _>(|_, _| {
+            let result = LspServer::feature_support_matrix();
+            Box::pin(async move { Ok(result) })
+        });
+
         router
     }
 }
@@ -18,6 +18,9 @@ fn create_test_server_state() -> (ServerState, mpsc::UnboundedReceiver<Url>) {
         client,
         server: LspServer::with_config(false, None),
         parse_tx,
+        validate_tx: None,
+        held_workspace_diagnostics: None,
+        reference_replies: HashMap::new(),
     };
 
     (state, parse_rx)
@@ -128,16 +131,29 @@ async fn test_initialize_capabilities() {
     assert!(caps.hover_provider.is_some());
     assert!(caps.definition_provider.is_some());
     assert!(caps.references_provider.is_some());
+    assert!(caps.document_highlight_provider.is_some());
+    assert!(caps.call_hierarchy_provider.is_some());
     assert!(caps.document_symbol_provider.is_some());
     assert!(caps.rename_provider.is_some());
     assert!(caps.document_formatting_provider.is_some());
     assert!(caps.document_range_formatting_provider.is_some());
+    let on_type = caps.document_on_type_formatting_provider.unwrap();
+    assert_eq!(on_type.first_trigger_character, "}");
+    assert_eq!(on_type.more_trigger_character, Some(vec![";".to_string()]));
     assert!(caps.completion_provider.is_some());
     assert!(caps.folding_range_provider.is_some());
     assert!(caps.selection_range_provider.is_some());
+    assert!(caps.linked_editing_range_provider.is_some());
+    assert!(caps.moniker_provider.is_some());
     assert!(caps.inlay_hint_provider.is_some());
     assert!(caps.semantic_tokens_provider.is_some());
     assert!(caps.workspace_symbol_provider.is_some());
+    let workspace_folders = caps.workspace.unwrap().workspace_folders.unwrap();
+    assert_eq!(workspace_folders.supported, Some(true));
+    assert_eq!(
+        workspace_folders.change_notifications,
+        Some(OneOf::Left(true))
+    );
 }
 
 #[tokio::test]
@@ -157,6 +173,78 @@ async fn test_initialize_completion_trigger_characters() {
     }
 }
 
+#[tokio::test]
+async fn test_initialize_reads_refresh_support() {
+    let (mut state, _parse_rx) = create_test_server_state();
+    state.initialize(InitializeParams::default()).await.unwrap();
+    assert!(!state.server.semantic_tokens_refresh_support());
+    assert!(!state.server.inlay_hint_refresh_support());
+
+    let params = InitializeParams {
+        capabilities: ClientCapabilities {
+            workspace: Some(WorkspaceClientCapabilities {
+                semantic_tokens: Some(SemanticTokensWorkspaceClientCapabilities {
+                    refresh_support: Some(true),
+                }),
+                inlay_hint: Some(InlayHintWorkspaceClientCapabilities {
+                    refresh_support: Some(true),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    state.initialize(params).await.unwrap();
+    assert!(state.server.semantic_tokens_refresh_support());
+    assert!(state.server.inlay_hint_refresh_support());
+}
+
+#[tokio::test]
+async fn test_initialize_reads_client_support() {
+    let (mut state, _parse_rx) = create_test_server_state();
+    state.initialize(InitializeParams::default()).await.unwrap();
+    assert_eq!(
+        state.server.client_support(),
+        &ClientSupport::from_capabilities(&ClientCapabilities::default())
+    );
+
+    let params = InitializeParams {
+        capabilities: ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                definition: Some(GotoCapability {
+                    link_support: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    state.initialize(params).await.unwrap();
+    assert!(state.server.client_support().definition_links);
+    assert!(!state.server.client_support().implementation_links);
+    assert!(!state.server.client_support().markdown_hover);
+}
+
+#[tokio::test]
+async fn test_initialized_starts_background_indexing() {
+    let (mut state, _parse_rx) = create_test_server_state();
+
+    let result = state.initialized(InitializedParams {});
+    assert!(matches!(result, ControlFlow::Continue(())));
+    assert!(state.server.is_indexing());
+
+    // Requests are still answered while indexing
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    state
+        .server
+        .open_document(&uri, "part def Vehicle;")
+        .unwrap();
+    assert_eq!(state.server.file_count(), 1);
+}
+
 #[test]
 fn test_did_open_valid_document() {
     let (mut state, _parse_rx) = create_test_server_state();
@@ -390,6 +478,25 @@ fn test_did_save() {
     assert!(matches!(result, ControlFlow::Continue(())));
 }
 
+#[test]
+fn test_did_change_configuration() {
+    let (mut state, _parse_rx) = create_test_server_state();
+
+    let params = DidChangeConfigurationParams {
+        settings: serde_json::json!({ "syster": { "stdlibEnabled": false, "hoverMaxDepth": 2 } }),
+    };
+    let result = state.did_change_configuration(params);
+    assert!(matches!(result, ControlFlow::Continue(())));
+    assert_eq!(state.server.settings().hover_max_depth, 2);
+
+    // Settings for other servers leave ours alone
+    let params = DidChangeConfigurationParams {
+        settings: serde_json::json!({ "editor": { "tabSize": 2 } }),
+    };
+    let _ = state.did_change_configuration(params);
+    assert_eq!(state.server.settings().hover_max_depth, 2);
+}
+
 #[tokio::test]
 async fn test_hover_on_valid_symbol() {
     let (mut state, _parse_rx) = create_test_server_state();
@@ -1739,3 +1846,285 @@ fn test_did_change_watched_files_multiple_changes() {
     let result = state.did_change_watched_files(params);
     assert!(matches!(result, ControlFlow::Continue(())));
 }
+
+#[tokio::test]
+async fn test_formatting_rejects_edits_for_outdated_version() {
+    let (mut state, _parse_rx) = create_test_server_state();
+
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let _ = state.did_open(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "sysml".to_string(),
+            version: 1,
+            text: "part   def   Vehicle;".to_string(),
+        },
+    });
+
+    let params = DocumentFormattingParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        options: FormattingOptions {
+            tab_size: 4,
+            insert_spaces: true,
+            ..Default::default()
+        },
+        work_done_progress_params: WorkDoneProgressParams::default(),
+    };
+
+    // Formatting snapshots version 1, then the user keeps typing
+    let format_future = state.formatting(params);
+    let _ = state.did_change(DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier {
+            uri: uri.clone(),
+            version: 2,
+        },
+        content_changes: vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "part   def   Car;".to_string(),
+        }],
+    });
+
+    let error = format_future
+        .await
+        .expect_err("edits for version 1 must not be applied to version 2");
+    assert_eq!(error.code, ErrorCode::CONTENT_MODIFIED);
+}
+
+#[tokio::test]
+async fn test_formatting_current_version_returns_edits() {
+    let (mut state, _parse_rx) = create_test_server_state();
+
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let _ = state.did_open(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "sysml".to_string(),
+            version: 3,
+            text: "part   def   Vehicle;".to_string(),
+        },
+    });
+
+    let snapshot = state.server.get_document_snapshot(&uri).unwrap();
+    assert_eq!(snapshot.version, 3);
+    assert!(!snapshot.is_stale());
+
+    let params = DocumentFormattingParams {
+        text_document: TextDocumentIdentifier { uri },
+        options: FormattingOptions {
+            tab_size: 4,
+            insert_spaces: true,
+            ..Default::default()
+        },
+        work_done_progress_params: WorkDoneProgressParams::default(),
+    };
+    let result = state.formatting(params).await.unwrap();
+    assert!(result.is_some());
+}
+
+fn format_params(uri: &Url, tab_size: u32) -> DocumentFormattingParams {
+    DocumentFormattingParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        options: FormattingOptions {
+            tab_size,
+            insert_spaces: true,
+            ..Default::default()
+        },
+        work_done_progress_params: WorkDoneProgressParams::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_formatting_formatted_document_is_skipped() {
+    let (mut state, _parse_rx) = create_test_server_state();
+
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let _ = state.did_open(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "sysml".to_string(),
+            version: 1,
+            text: "part   def   Vehicle {\npart   engine;\n}".to_string(),
+        },
+    });
+
+    let edits = state
+        .formatting(format_params(&uri, 4))
+        .await
+        .unwrap()
+        .expect("the document needs formatting");
+    let formatted = edits[0].new_text.clone();
+
+    // Formatting again before the edits are applied still returns them
+    let again = state.formatting(format_params(&uri, 4)).await.unwrap();
+    assert_eq!(again, Some(edits));
+
+    // The client applies the edits, then saves and formats again
+    let _ = state.did_change(DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier {
+            uri: uri.clone(),
+            version: 2,
+        },
+        content_changes: vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: formatted.clone(),
+        }],
+    });
+    let snapshot = state.server.get_document_snapshot(&uri).unwrap();
+    assert!(
+        snapshot
+            .formatted
+            .is_formatted(&formatted, &format_params(&uri, 4).options)
+    );
+    let result = state.formatting(format_params(&uri, 4)).await.unwrap();
+    assert_eq!(result, None);
+
+    // Other options format the document differently
+    let result = state.formatting(format_params(&uri, 2)).await.unwrap();
+    assert!(result.is_some());
+}
+
+#[tokio::test]
+async fn test_formatting_cache_is_dropped_on_close() {
+    let (mut state, _parse_rx) = create_test_server_state();
+
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let open = |version: i32, text: &str| DidOpenTextDocumentParams {
+        text_document: TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "sysml".to_string(),
+            version,
+            text: text.to_string(),
+        },
+    };
+    let _ = state.did_open(open(1, "part   def   Vehicle;"));
+    let options = format_params(&uri, 4).options;
+    let edits = state
+        .formatting(format_params(&uri, 4))
+        .await
+        .unwrap()
+        .unwrap();
+    let formatted = edits[0].new_text.clone();
+    let snapshot = state.server.get_document_snapshot(&uri).unwrap();
+    assert!(snapshot.formatted.is_formatted(&formatted, &options));
+
+    let _ = state.did_close(DidCloseTextDocumentParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+    });
+    let _ = state.did_open(open(1, &formatted));
+
+    let snapshot = state.server.get_document_snapshot(&uri).unwrap();
+    assert!(!snapshot.formatted.is_formatted(&formatted, &options));
+}
+
+#[tokio::test]
+async fn test_initialize_schedules_project_validation_by_default() {
+    let (mut state, _parse_rx) = create_test_server_state();
+
+    let params = InitializeParams::default();
+    state.initialize(params).await.unwrap();
+
+    assert!(state.validate_tx.is_some());
+}
+
+#[tokio::test]
+async fn test_initialize_zero_validation_delay_turns_validation_off() {
+    let (mut state, _parse_rx) = create_test_server_state();
+
+    let mut opts = serde_json::Map::new();
+    opts.insert("validationDelayMs".to_string(), Value::from(0));
+
+    let params = InitializeParams {
+        initialization_options: Some(Value::Object(opts)),
+        ..Default::default()
+    };
+    state.initialize(params).await.unwrap();
+
+    assert!(state.validate_tx.is_none());
+}
+
+fn workspace_diagnostic_params(
+    previous_result_ids: Vec<PreviousResultId>,
+) -> WorkspaceDiagnosticParams {
+    WorkspaceDiagnosticParams {
+        identifier: None,
+        previous_result_ids,
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    }
+}
+
+/// Open a document and pull its diagnostics once, returning the result ID
+async fn pull_initial_workspace_diagnostics(
+    state: &mut ServerState,
+    uri: &Url,
+) -> PreviousResultId {
+    state.server.open_document(uri, "part def A;").unwrap();
+    let result = state
+        .workspace_diagnostic(workspace_diagnostic_params(Vec::new()))
+        .await
+        .unwrap();
+    let WorkspaceDiagnosticReportResult::Report(report) = result else {
+        panic!("expected a report");
+    };
+    match &report.items[..] {
+        [WorkspaceDocumentDiagnosticReport::Full(full)] => PreviousResultId {
+            uri: uri.clone(),
+            value: full
+                .full_document_diagnostic_report
+                .result_id
+                .clone()
+                .unwrap(),
+        },
+        other => panic!("expected one full report, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_workspace_diagnostic_is_held_until_diagnostics_change() {
+    let (mut state, _parse_rx) = create_test_server_state();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let previous = pull_initial_workspace_diagnostics(&mut state, &uri).await;
+
+    let pending = state.workspace_diagnostic(workspace_diagnostic_params(vec![previous]));
+    assert!(state.held_workspace_diagnostics.is_some());
+
+    // Reparsing without a change leaves the request held
+    state.server.parse_document(&uri);
+    state.answer_held_workspace_diagnostics();
+    assert!(state.held_workspace_diagnostics.is_some());
+
+    let change = TextDocumentContentChangeEvent {
+        range: None,
+        range_length: None,
+        text: "part def A {".to_string(),
+    };
+    state.server.apply_text_change_only(&uri, &change).unwrap();
+    state.server.parse_document(&uri);
+    state.answer_held_workspace_diagnostics();
+    assert!(state.held_workspace_diagnostics.is_none());
+
+    let WorkspaceDiagnosticReportResult::Report(report) = pending.await.unwrap() else {
+        panic!("expected a report");
+    };
+    assert!(matches!(
+        &report.items[..],
+        [WorkspaceDocumentDiagnosticReport::Full(full)]
+            if !full.full_document_diagnostic_report.items.is_empty()
+    ));
+}
+
+#[tokio::test]
+async fn test_cancelled_workspace_diagnostic_is_dropped() {
+    let (mut state, _parse_rx) = create_test_server_state();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let previous = pull_initial_workspace_diagnostics(&mut state, &uri).await;
+
+    // Cancelling a request drops its future
+    drop(state.workspace_diagnostic(workspace_diagnostic_params(vec![previous])));
+    assert!(state.held_workspace_diagnostics.is_some());
+
+    state.answer_held_workspace_diagnostics();
+    assert!(state.held_workspace_diagnostics.is_none());
+}
@@ -1,29 +1,91 @@
+mod call_hierarchy;
+pub mod client_support;
+mod code_actions;
 mod code_lens;
 mod completion;
+mod completion_context;
+mod completion_ranking;
+pub mod configuration;
+mod connection_check;
+mod connector_ends;
+mod constant_eval;
 mod core;
+pub mod corpus;
 mod definition;
+mod dependency_graph;
 mod diagnostics;
+mod diagnostics_store;
 pub mod diagram;
+pub mod diagram_sequence;
+pub mod diagram_text;
+mod direction_check;
 mod document;
+mod document_highlight;
 mod document_links;
 mod document_symbols;
+pub mod environment;
+pub mod error;
+pub mod feature_support;
 mod folding_ranges;
 pub mod formatting;
+pub mod health;
 pub mod helpers;
 mod hover;
+mod implementation;
+pub mod implicit_supertype;
+mod incremental_parse;
 mod inlay_hints;
+pub mod inline_completion;
+mod keyword_hover;
+mod language;
+mod linked_editing;
+pub mod memory_stats;
+pub mod model_export;
+mod moniker;
+mod naming_check;
+mod organize_imports;
+pub mod package_graph;
+mod parallel_parse;
+mod parse_cache;
 mod position;
+mod pragmas;
+pub mod project_manifest;
+pub mod project_validation;
+pub mod qualified_name;
+mod reference_index;
+pub mod reference_search;
 mod references;
+mod relationship_check;
+mod relationship_duplicates;
 mod rename;
+pub mod requirement_status;
+pub mod requirement_trace;
+pub mod resolve_spans;
+pub mod schema;
+mod scope_check;
 mod selection_range;
 mod semantic_tokens;
+pub mod session;
+pub mod snapshot;
+mod snippet_completions;
+pub mod stdlib_cache;
+mod symbol_lookup;
+mod tokens;
 mod type_definition;
+mod type_hierarchy;
 pub mod type_info;
+mod unit_resolution;
+pub mod unused_elements;
+mod unused_imports;
+pub mod watched_files;
+mod workspace_filter;
+mod workspace_roots;
 mod workspace_symbols;
 
 pub mod background_tasks;
 
 pub use core::LspServer;
+pub use error::LspError;
 
 #[cfg(test)]
 mod tests;
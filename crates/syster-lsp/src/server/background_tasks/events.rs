@@ -3,9 +3,39 @@
 //! Events are used to communicate between async tasks and the main LSP loop.
 //! They are emitted via `ClientSocket::emit()` and handled by `Router::event()`.
 
+use std::path::PathBuf;
+
 use async_lsp::lsp_types::Url;
+use syster::ide::AnalysisHost;
+
+use crate::server::reference_index::ReferenceEntry;
 
 /// Trigger document parsing after debounce delay
 pub struct ParseDocument {
     pub uri: Url,
 }
+
+/// Edits have paused long enough to validate the whole project
+pub struct ValidateProject;
+
+/// Re-validate the next files of a project validation run
+pub struct ContinueValidation {
+    pub generation: u64,
+}
+
+/// Background indexing finished loading the stdlib and workspace folders
+pub struct IndexingComplete {
+    pub host: AnalysisHost,
+}
+
+/// References in one file that may be to the element a search is looking for
+pub struct ReferenceCandidates {
+    pub search: u64,
+    pub path: PathBuf,
+    pub entries: Vec<ReferenceEntry>,
+}
+
+/// Every shard of a reference search has been scanned, or the search stopped
+pub struct ReferenceSearchFinished {
+    pub search: u64,
+}
@@ -0,0 +1,176 @@
+//! Background workspace indexing
+//!
+//! Loads the stdlib and workspace folders into a separate `AnalysisHost` on a
+//! blocking task, reporting progress via `window/workDoneProgress`. The loaded
+//! files are handed back to the main loop with an `IndexingComplete` event, so
+//! requests keep being answered (with partial results) while indexing runs.
+
+use std::path::PathBuf;
+
+use async_lsp::ClientSocket;
+use async_lsp::lsp_types::notification::Progress;
+use async_lsp::lsp_types::request::WorkDoneProgressCreate;
+use async_lsp::lsp_types::{
+    NumberOrString, ProgressParams, ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+use syster::ide::AnalysisHost;
+use syster::project::{StdLibLoader, WorkspaceLoader};
+
+use super::events::IndexingComplete;
+use crate::server::parallel_parse;
+use crate::server::workspace_filter::WorkspaceFilter;
+
+/// Progress token used for the indexing work-done progress
+pub const INDEXING_PROGRESS_TOKEN: &str = "syster/indexing";
+
+/// A snapshot of what needs to be loaded, taken from the server before indexing starts
+#[derive(Debug, Clone)]
+pub struct IndexingJob {
+    /// Stdlib location (`None` lets the loader discover it), or skip the stdlib entirely
+    pub stdlib: Option<Option<PathBuf>>,
+    /// Workspace folders to scan for SysML/KerML files
+    pub folders: Vec<PathBuf>,
+    /// Globs excluded from the scan, on top of each folder's ignore files
+    pub exclude_globs: Vec<String>,
+}
+
+/// Progress of an indexing run
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexingProgress {
+    pub message: String,
+    pub percentage: u32,
+}
+
+impl IndexingJob {
+    /// Load everything into a fresh host, calling `report` as files are loaded.
+    ///
+    /// Files that fail to load are logged and skipped.
+    pub fn run<F>(self, mut report: F) -> AnalysisHost
+    where
+        F: FnMut(IndexingProgress),
+    {
+        let mut host = AnalysisHost::new();
+
+        // The stdlib counts as the first half of the work when it is loaded
+        let base = if self.stdlib.is_some() { 50 } else { 0 };
+
+        if let Some(stdlib_path) = self.stdlib {
+            report(IndexingProgress {
+                message: "Loading standard library".to_string(),
+                percentage: 0,
+            });
+            let loaded = match parallel_parse::stdlib_dir(stdlib_path.as_deref()) {
+                Some(dir) => parallel_parse::load_stdlib(&dir, &mut host).map(|_| ()),
+                None => StdLibLoader::new()
+                    .load_into_host(&mut host)
+                    .map_err(|err| err.to_string()),
+            };
+            if let Err(err) = loaded {
+                tracing::warn!("Failed to load stdlib: {err}");
+            }
+        }
+
+        let paths: Vec<PathBuf> = self
+            .folders
+            .iter()
+            .filter_map(|folder| {
+                match WorkspaceFilter::for_folder(folder, &self.exclude_globs).collect_files() {
+                    Ok(paths) => Some(paths),
+                    Err(err) => {
+                        tracing::warn!(folder = %folder.display(), "Failed to scan folder: {err}");
+                        None
+                    }
+                }
+            })
+            .flatten()
+            .collect();
+
+        let loader = WorkspaceLoader::new();
+        let total = paths.len().max(1);
+        for (i, path) in paths.into_iter().enumerate() {
+            report(IndexingProgress {
+                message: format!("Indexing {}", path.display()),
+                percentage: (base + i * (100 - base) / total) as u32,
+            });
+            if let Err(err) = loader.load_file_into_host(&path, &mut host) {
+                tracing::warn!("Some files failed to parse: {err}");
+            }
+        }
+
+        host
+    }
+}
+
+/// Run an indexing job on a blocking task and emit `IndexingComplete` when done.
+///
+/// When `report_progress` is set, progress is reported to the client through a
+/// work-done progress created by the server.
+pub fn spawn(job: IndexingJob, client: ClientSocket, report_progress: bool) {
+    tokio::spawn(async move {
+        let token = NumberOrString::String(INDEXING_PROGRESS_TOKEN.to_string());
+        let report_progress = report_progress
+            && client
+                .request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                    token: token.clone(),
+                })
+                .await
+                .is_ok();
+
+        if report_progress {
+            notify_progress(
+                &client,
+                &token,
+                WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                    title: "Indexing SysML workspace".to_string(),
+                    cancellable: Some(false),
+                    message: None,
+                    percentage: Some(0),
+                }),
+            );
+        }
+
+        let progress_client = client.clone();
+        let progress_token = token.clone();
+        let host = tokio::task::spawn_blocking(move || {
+            job.run(|progress| {
+                if report_progress {
+                    notify_progress(
+                        &progress_client,
+                        &progress_token,
+                        WorkDoneProgress::Report(WorkDoneProgressReport {
+                            cancellable: Some(false),
+                            message: Some(progress.message),
+                            percentage: Some(progress.percentage),
+                        }),
+                    );
+                }
+            })
+        })
+        .await;
+
+        if report_progress {
+            notify_progress(
+                &client,
+                &token,
+                WorkDoneProgress::End(WorkDoneProgressEnd {
+                    message: Some("Indexing complete".to_string()),
+                }),
+            );
+        }
+
+        match host {
+            Ok(host) => {
+                let _ = client.emit(IndexingComplete { host });
+            }
+            Err(err) => tracing::error!("Indexing task failed: {err}"),
+        }
+    });
+}
+
+fn notify_progress(client: &ClientSocket, token: &NumberOrString, progress: WorkDoneProgress) {
+    let _ = client.notify::<Progress>(ProgressParams {
+        token: token.clone(),
+        value: ProgressParamsValue::WorkDone(progress),
+    });
+}
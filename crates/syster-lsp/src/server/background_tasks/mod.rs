@@ -2,6 +2,8 @@
 
 pub mod debounce;
 pub mod events;
+pub mod indexing;
+pub mod reference_search;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,87 @@
+//! Background scanning of reference search shards
+//!
+//! A search's shards are scanned on the blocking pool by up to one worker
+//! per core. Workers share a queue instead of a fixed split of the shards:
+//! whichever worker is free takes the next shard, so one large file doesn't
+//! hold up the files assigned after it. Candidates of each shard are emitted
+//! to the main loop as soon as the shard is scanned, and `ReferenceSearchFinished`
+//! once every worker has stopped.
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_lsp::ClientSocket;
+use tokio::task::JoinSet;
+
+use super::events::{ReferenceCandidates, ReferenceSearchFinished};
+use crate::server::reference_search::{ReferenceSearchJob, ReferenceShard};
+
+/// Shards taken in order by whichever worker asks next
+#[derive(Debug)]
+pub struct ShardQueue {
+    shards: Vec<ReferenceShard>,
+    next: AtomicUsize,
+}
+
+impl ShardQueue {
+    pub fn new(shards: Vec<ReferenceShard>) -> Self {
+        Self {
+            shards,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// The next shard nobody has taken yet
+    pub fn take(&self) -> Option<&ReferenceShard> {
+        self.shards.get(self.next.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Scan a search's shards on the blocking pool, emitting the candidates of
+/// each shard and `ReferenceSearchFinished` when done or cancelled
+pub fn spawn(mut job: ReferenceSearchJob, client: ClientSocket) {
+    let workers = std::thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(job.shards.len())
+        .max(1);
+    let id = job.id;
+    let names: Arc<[Arc<str>]> = std::mem::take(&mut job.names).into();
+    let queue = Arc::new(ShardQueue::new(std::mem::take(&mut job.shards)));
+    let job = Arc::new(job);
+
+    tokio::spawn(async move {
+        let mut tasks = JoinSet::new();
+        for _ in 0..workers {
+            let job = job.clone();
+            let queue = queue.clone();
+            let names = names.clone();
+            let client = client.clone();
+            tasks.spawn_blocking(move || {
+                while let Some(shard) = queue.take() {
+                    if job.is_cancelled() {
+                        return;
+                    }
+                    let entries = shard.candidates(&names);
+                    if !entries.is_empty()
+                        && client
+                            .emit(ReferenceCandidates {
+                                search: id,
+                                path: shard.path.clone(),
+                                entries,
+                            })
+                            .is_err()
+                    {
+                        return;
+                    }
+                }
+            });
+        }
+        while let Some(result) = tasks.join_next().await {
+            if let Err(err) = result {
+                tracing::error!("Reference search worker failed: {err}");
+            }
+        }
+        let _ = client.emit(ReferenceSearchFinished { search: id });
+    });
+}
@@ -6,7 +6,11 @@ use std::time::Duration;
 
 use tokio::sync::mpsc;
 
+use crate::server::LspServer;
 use crate::server::background_tasks::debounce;
+use crate::server::background_tasks::indexing::IndexingJob;
+use crate::server::background_tasks::reference_search::ShardQueue;
+use crate::server::reference_search::ReferenceShard;
 
 /// Test that debounce waits before emitting
 #[tokio::test]
@@ -35,6 +39,31 @@ async fn test_debounce_waits_before_emit() {
     assert_eq!(emitted.load(Ordering::SeqCst), 1, "Should emit after delay");
 }
 
+/// Test debouncing deterministically on tokio's paused clock
+#[tokio::test(start_paused = true)]
+async fn test_debounce_on_paused_clock() {
+    let (tx, rx) = mpsc::unbounded_channel::<String>();
+    let emitted = Arc::new(AtomicUsize::new(0));
+    let emitted_clone = emitted.clone();
+
+    debounce::spawn(Duration::from_millis(150), rx, move |_key| {
+        emitted_clone.fetch_add(1, Ordering::SeqCst);
+        true
+    });
+
+    tx.send("doc1".to_string()).unwrap();
+    tokio::time::sleep(Duration::from_millis(149)).await;
+    assert_eq!(emitted.load(Ordering::SeqCst), 0);
+
+    // Another edit restarts the quiet period
+    tx.send("doc1".to_string()).unwrap();
+    tokio::time::sleep(Duration::from_millis(149)).await;
+    assert_eq!(emitted.load(Ordering::SeqCst), 0);
+
+    tokio::time::sleep(Duration::from_millis(2)).await;
+    assert_eq!(emitted.load(Ordering::SeqCst), 1);
+}
+
 /// Test that rapid changes reset the debounce timer
 #[tokio::test]
 async fn test_debounce_resets_on_same_key() {
@@ -142,3 +171,210 @@ async fn test_debounce_stops_on_emit_false() {
     }
     // If send failed, that's also correct - task stopped and dropped receiver
 }
+
+/// Create an empty scratch directory for indexing tests
+fn scratch_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("syster-indexing-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Test that an indexing job loads workspace files and reports progress
+#[test]
+fn test_indexing_job_loads_folders_with_progress() {
+    let dir = scratch_dir("folders");
+    std::fs::write(dir.join("a.sysml"), "part def A;").unwrap();
+    std::fs::write(dir.join("b.sysml"), "part def B;").unwrap();
+
+    let job = IndexingJob {
+        stdlib: None,
+        folders: vec![dir.clone()],
+        exclude_globs: Vec::new(),
+    };
+    let mut reports = Vec::new();
+    let host = job.run(|progress| reports.push(progress));
+
+    assert_eq!(host.file_count(), 2);
+    assert_eq!(reports.len(), 2, "One report per workspace file");
+    assert_eq!(reports[0].percentage, 0);
+    assert!(
+        reports
+            .windows(2)
+            .all(|w| w[0].percentage <= w[1].percentage)
+    );
+    assert!(reports.iter().all(|r| r.percentage <= 100));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Test that an indexing job skips excluded directories
+#[test]
+fn test_indexing_job_skips_excluded_paths() {
+    let dir = scratch_dir("excluded");
+    std::fs::create_dir_all(dir.join("vendor")).unwrap();
+    std::fs::write(dir.join("a.sysml"), "part def A;").unwrap();
+    std::fs::write(dir.join("vendor/v.sysml"), "part def V;").unwrap();
+    std::fs::write(dir.join(".systerignore"), "vendor/\n").unwrap();
+
+    let job = IndexingJob {
+        stdlib: None,
+        folders: vec![dir.clone()],
+        exclude_globs: vec!["*.draft.sysml".to_string()],
+    };
+    std::fs::write(dir.join("b.draft.sysml"), "part def B;").unwrap();
+    let host = job.run(|_| {});
+
+    assert_eq!(host.file_count(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Test that the server hands out a single indexing job and answers requests meanwhile
+#[test]
+fn test_background_indexing_lifecycle() {
+    let dir = scratch_dir("lifecycle");
+    let disk_path = dir.join("disk.sysml");
+    let open_path = dir.join("open.sysml");
+    std::fs::write(&disk_path, "part def OnDisk;").unwrap();
+    std::fs::write(&open_path, "part def Stale;").unwrap();
+
+    let mut server = LspServer::with_config(false, None);
+    server.set_workspace_folders(vec![dir.clone()]);
+
+    let job = server
+        .begin_background_indexing()
+        .expect("job should start");
+    assert!(job.stdlib.is_none(), "Stdlib disabled");
+    assert!(server.is_indexing());
+    assert!(server.begin_background_indexing().is_none(), "Only one job");
+
+    // Requests don't block on loading while indexing runs
+    let open_uri = async_lsp::lsp_types::Url::from_file_path(&open_path).unwrap();
+    server.open_document(&open_uri, "part def Open;").unwrap();
+    assert_eq!(server.file_count(), 1, "Only the opened document so far");
+
+    let host = job.run(|_| {});
+    server.finish_background_indexing(host);
+
+    assert!(!server.is_indexing());
+    assert_eq!(server.file_count(), 2);
+    assert_eq!(
+        server.get_document_text(&open_uri).as_deref(),
+        Some("part def Open;"),
+        "Editor text is kept over the indexed version"
+    );
+    assert!(
+        server.begin_background_indexing().is_none(),
+        "Already loaded"
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Test that unresolved references aren't reported until indexing finishes
+#[test]
+fn test_unresolved_references_suppressed_while_indexing() {
+    let dir = scratch_dir("unresolved");
+    let lib_path = dir.join("lib.sysml");
+    let open_path = dir.join("open.sysml");
+    std::fs::write(&lib_path, "package Lib { part def Engine; }").unwrap();
+    let text = "package App {\n    private import Lib::*;\n    part e : Engine;\n}";
+    std::fs::write(&open_path, text).unwrap();
+
+    let mut server = LspServer::with_config(false, None);
+    server.set_workspace_folders(vec![dir.clone()]);
+    let job = server
+        .begin_background_indexing()
+        .expect("job should start");
+
+    let open_uri = async_lsp::lsp_types::Url::from_file_path(&open_path).unwrap();
+    server.open_document(&open_uri, text).unwrap();
+    assert!(
+        server
+            .get_diagnostics(&open_uri)
+            .iter()
+            .all(|d| !d.message.starts_with("unresolved reference")),
+        "Imported library isn't loaded yet"
+    );
+
+    let host = job.run(|_| {});
+    server.finish_background_indexing(host);
+    assert!(server.get_diagnostics(&open_uri).is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Test that the edited file is served from its own symbols while indexing runs
+#[test]
+fn test_requests_answered_locally_while_indexing() {
+    let dir = scratch_dir("partial");
+    let open_path = dir.join("open.sysml");
+    std::fs::write(dir.join("lib.sysml"), "package Lib { part def Wheel; }").unwrap();
+    let text = "package App {\n    part def Engine;\n    part e : Engine;\n}";
+    std::fs::write(&open_path, text).unwrap();
+
+    let mut server = LspServer::with_config(false, None);
+    server.set_workspace_folders(vec![dir.clone()]);
+    let job = server
+        .begin_background_indexing()
+        .expect("job should start");
+
+    let open_uri = async_lsp::lsp_types::Url::from_file_path(&open_path).unwrap();
+    server.open_document(&open_uri, text).unwrap();
+    let position = async_lsp::lsp_types::Position::new(2, 14);
+
+    let definition = server.get_definition(&open_uri, position).unwrap();
+    assert_eq!(definition.range.start.line, 1);
+    assert!(server.get_semantic_tokens(&open_uri).is_some());
+
+    let hover_text = |server: &mut LspServer| match server.get_hover(&open_uri, position) {
+        Some(async_lsp::lsp_types::Hover {
+            contents: async_lsp::lsp_types::HoverContents::Markup(markup),
+            ..
+        }) => markup.value,
+        other => panic!("Expected a markdown hover, got {other:?}"),
+    };
+    assert!(hover_text(&mut server).contains("Partial results"));
+
+    let host = job.run(|_| {});
+    server.finish_background_indexing(host);
+    assert!(!hover_text(&mut server).contains("Partial results"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Test that workers sharing a shard queue take every shard exactly once
+#[test]
+fn test_shard_queue_hands_out_each_shard_once() {
+    let shards: Vec<ReferenceShard> = (0..100)
+        .map(|i| ReferenceShard {
+            path: std::path::PathBuf::from(format!("/{i}.sysml")),
+            entries: Arc::from([]),
+        })
+        .collect();
+    let queue = Arc::new(ShardQueue::new(shards));
+
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let queue = queue.clone();
+            std::thread::spawn(move || {
+                let mut taken = Vec::new();
+                while let Some(shard) = queue.take() {
+                    taken.push(shard.path.clone());
+                }
+                taken
+            })
+        })
+        .collect();
+
+    let mut taken: Vec<_> = workers
+        .into_iter()
+        .flat_map(|worker| worker.join().unwrap())
+        .collect();
+    taken.sort();
+    assert_eq!(taken.len(), 100);
+    taken.dedup();
+    assert_eq!(taken.len(), 100);
+    assert!(queue.take().is_none());
+}
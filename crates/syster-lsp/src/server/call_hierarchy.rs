@@ -0,0 +1,253 @@
+//! Call hierarchy for action and calculation invocations
+//!
+//! A "call" is an action or calc usage (including `perform`) typed by, or
+//! subsetting, an action or calculation. The caller is the nearest named
+//! element that owns the usage.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::LspServer;
+use super::document_symbols::convert_symbol_kind;
+use super::helpers::{item_qualified_name, uri_to_path};
+use async_lsp::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Position, Range, Url,
+};
+use syster::hir::{HirSymbol, SymbolIndex, SymbolKind as HirSymbolKind, TypeRefKind};
+use syster::ide::Analysis;
+
+/// A usage that invokes an action or calculation
+struct CallSite<'a> {
+    /// Qualified name of the element making the call
+    caller: &'a str,
+    /// Qualified name of the invoked action or calculation
+    target: &'a str,
+    /// Span of the reference to the target
+    range: Range,
+}
+
+impl LspServer {
+    /// Resolve the action or calculation at the given position to a call hierarchy item
+    pub fn prepare_call_hierarchy(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> Option<Vec<CallHierarchyItem>> {
+        let path = uri_to_path(uri)?;
+        let (name, _) = self.find_symbol_at_position(&path, position)?;
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+        let symbol = resolve_name(index, &name, &path, &analysis)?;
+
+        // A usage typed by an action stands for the action it invokes
+        let symbol = if is_invocation(symbol) {
+            call_target(index, symbol)
+                .and_then(|(target, _)| index.lookup_qualified(target))
+                .unwrap_or(symbol)
+        } else {
+            symbol
+        };
+
+        if !is_callable(symbol.kind) {
+            return None;
+        }
+        Some(vec![call_hierarchy_item(&analysis, symbol)?])
+    }
+
+    /// Find the elements that invoke the given action or calculation
+    pub fn get_incoming_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+    ) -> Option<Vec<CallHierarchyIncomingCall>> {
+        let _ = self.ensure_workspace_loaded();
+        let qualified_name = item_qualified_name(item.data.as_ref(), item.detail.as_ref())?;
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+
+        let mut by_caller: HashMap<&str, Vec<Range>> = HashMap::new();
+        for call in call_sites(index).filter(|call| call.target == qualified_name) {
+            by_caller.entry(call.caller).or_default().push(call.range);
+        }
+
+        let mut calls: Vec<CallHierarchyIncomingCall> = by_caller
+            .into_iter()
+            .filter_map(|(caller, mut from_ranges)| {
+                from_ranges.sort_by_key(|range| (range.start.line, range.start.character));
+                let symbol = index.lookup_qualified(caller)?;
+                Some(CallHierarchyIncomingCall {
+                    from: call_hierarchy_item(&analysis, symbol)?,
+                    from_ranges,
+                })
+            })
+            .collect();
+        calls.sort_by(|a, b| a.from.name.cmp(&b.from.name));
+
+        Some(calls)
+    }
+
+    /// Find the actions and calculations invoked by the given element
+    pub fn get_outgoing_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+    ) -> Option<Vec<CallHierarchyOutgoingCall>> {
+        let _ = self.ensure_workspace_loaded();
+        let qualified_name = item_qualified_name(item.data.as_ref(), item.detail.as_ref())?;
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+
+        let mut by_target: HashMap<&str, Vec<Range>> = HashMap::new();
+        for call in call_sites(index).filter(|call| call.caller == qualified_name) {
+            by_target.entry(call.target).or_default().push(call.range);
+        }
+
+        let mut calls: Vec<CallHierarchyOutgoingCall> = by_target
+            .into_iter()
+            .filter_map(|(target, mut from_ranges)| {
+                from_ranges.sort_by_key(|range| (range.start.line, range.start.character));
+                let symbol = index.lookup_qualified(target)?;
+                Some(CallHierarchyOutgoingCall {
+                    to: call_hierarchy_item(&analysis, symbol)?,
+                    from_ranges,
+                })
+            })
+            .collect();
+        calls.sort_by(|a, b| a.to.name.cmp(&b.to.name));
+
+        Some(calls)
+    }
+}
+
+/// Look up the symbol named at the cursor, trying the qualified name first
+fn resolve_name<'a>(
+    index: &'a SymbolIndex,
+    name: &str,
+    path: &Path,
+    analysis: &Analysis<'_>,
+) -> Option<&'a HirSymbol> {
+    if let Some(symbol) = index.lookup_qualified(name) {
+        return Some(symbol);
+    }
+    // Type reference targets are written as in source; prefer definitions,
+    // then matches in this file
+    let file_id = analysis.get_file_id(&path.to_string_lossy());
+    let candidates = index.lookup_simple(name);
+    candidates
+        .iter()
+        .max_by_key(|sym| (sym.kind.is_definition(), Some(sym.file) == file_id))
+        .copied()
+}
+
+/// Actions and calculations, and usages that own calls
+fn is_callable(kind: HirSymbolKind) -> bool {
+    matches!(
+        kind,
+        HirSymbolKind::ActionDef
+            | HirSymbolKind::CalculationDef
+            | HirSymbolKind::ActionUsage
+            | HirSymbolKind::CalculationUsage
+            | HirSymbolKind::PartDef
+            | HirSymbolKind::PartUsage
+    )
+}
+
+fn is_invocation(symbol: &HirSymbol) -> bool {
+    matches!(
+        symbol.kind,
+        HirSymbolKind::ActionUsage | HirSymbolKind::CalculationUsage
+    )
+}
+
+fn is_anonymous(qualified_name: &str) -> bool {
+    qualified_name
+        .rsplit("::")
+        .next()
+        .is_some_and(|name| name.starts_with('<'))
+}
+
+/// The action or calculation a usage invokes, with the span of the reference
+fn call_target<'a>(index: &'a SymbolIndex, symbol: &'a HirSymbol) -> Option<(&'a str, Range)> {
+    symbol
+        .type_refs
+        .iter()
+        .filter_map(|trk| match trk {
+            TypeRefKind::Simple(type_ref) => Some(type_ref),
+            // For chains, the last part is the invoked element
+            TypeRefKind::Chain(chain) => chain.parts.last(),
+        })
+        .find_map(|type_ref| {
+            let target = type_ref.resolved_target.as_deref()?;
+            let target_symbol = index.lookup_qualified(target)?;
+            matches!(
+                target_symbol.kind,
+                HirSymbolKind::ActionDef
+                    | HirSymbolKind::CalculationDef
+                    | HirSymbolKind::ActionUsage
+                    | HirSymbolKind::CalculationUsage
+            )
+            .then(|| {
+                (
+                    target_symbol.qualified_name.as_ref(),
+                    Range {
+                        start: Position::new(type_ref.start_line, type_ref.start_col),
+                        end: Position::new(type_ref.end_line, type_ref.end_col),
+                    },
+                )
+            })
+        })
+}
+
+/// The nearest named element owning `symbol`
+fn owner<'a>(index: &'a SymbolIndex, symbol: &HirSymbol) -> Option<&'a HirSymbol> {
+    let mut qualified_name = symbol.qualified_name.as_ref();
+    while let Some((parent, _)) = qualified_name.rsplit_once("::") {
+        qualified_name = parent;
+        if is_anonymous(parent) {
+            continue;
+        }
+        if let Some(parent_symbol) = index.lookup_qualified(parent)
+            && parent_symbol.kind != HirSymbolKind::Package
+        {
+            return Some(parent_symbol);
+        }
+        return None;
+    }
+    None
+}
+
+/// All invocations in the index
+fn call_sites(index: &SymbolIndex) -> impl Iterator<Item = CallSite<'_>> {
+    index
+        .all_symbols()
+        .filter(|symbol| is_invocation(symbol))
+        .filter_map(move |symbol| {
+            let (target, range) = call_target(index, symbol)?;
+            let caller = owner(index, symbol)?;
+            Some(CallSite {
+                caller: caller.qualified_name.as_ref(),
+                target,
+                range,
+            })
+        })
+}
+
+fn call_hierarchy_item(analysis: &Analysis<'_>, symbol: &HirSymbol) -> Option<CallHierarchyItem> {
+    let path = analysis.get_file_path(symbol.file)?;
+    let uri = Url::from_file_path(path).ok()?;
+    let range = Range {
+        start: Position::new(symbol.start_line, symbol.start_col),
+        end: Position::new(symbol.end_line, symbol.end_col),
+    };
+    Some(CallHierarchyItem {
+        name: symbol.name.to_string(),
+        kind: convert_symbol_kind(symbol.kind),
+        tags: None,
+        detail: Some(symbol.qualified_name.to_string()),
+        uri,
+        range,
+        selection_range: range,
+        data: Some(serde_json::Value::String(symbol.qualified_name.to_string())),
+    })
+}
@@ -0,0 +1,297 @@
+//! What the client declared support for at initialize
+//!
+//! Anything the server sends beyond the base protocol depends on a client
+//! capability: work-done progress, dynamic registrations, refresh requests,
+//! `LocationLink`s, markdown and snippets. The capabilities are read once at
+//! `initialize` into a [`ClientSupport`] that every handler consults, so a
+//! minimal client only gets what it asked for. Partial results need no entry:
+//! a client opts into them per request by sending a `partialResultToken`.
+
+use async_lsp::lsp_types::{
+    ClientCapabilities, GotoDefinitionResponse, Location, LocationLink, MarkupKind, Position,
+    Range, Url,
+};
+
+use super::LspServer;
+use super::helpers::uri_to_path;
+use super::inline_completion::INLINE_COMPLETION_CAPABILITY;
+use super::tokens::{TokenKind, tokenize};
+
+/// Client capabilities the server's behavior depends on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSupport {
+    /// `window.workDoneProgress`: the server may create progress tokens
+    pub work_done_progress: bool,
+    /// `textDocument.typeHierarchy.dynamicRegistration`
+    pub type_hierarchy_registration: bool,
+    /// `workspace.didChangeWatchedFiles.dynamicRegistration`
+    pub watched_files_registration: bool,
+    /// `workspace.semanticTokens.refreshSupport`
+    pub semantic_tokens_refresh: bool,
+    /// `workspace.inlayHint.refreshSupport`
+    pub inlay_hint_refresh: bool,
+    /// The experimental `inlineCompletion` capability
+    pub inline_completion: bool,
+    /// `textDocument.diagnostic`: diagnostics are pulled, not pushed
+    pub pull_diagnostics: bool,
+    /// `workspace.diagnostic.refreshSupport`
+    pub diagnostic_refresh: bool,
+    /// `textDocument.definition.linkSupport`: go to definition may answer
+    /// with `LocationLink`s
+    pub definition_links: bool,
+    /// `textDocument.typeDefinition.linkSupport`
+    pub type_definition_links: bool,
+    /// `textDocument.implementation.linkSupport`
+    pub implementation_links: bool,
+    /// `textDocument.hover.contentFormat` includes markdown
+    pub markdown_hover: bool,
+    /// `textDocument.completion.completionItem.snippetSupport`
+    pub snippets: bool,
+}
+
+impl Default for ClientSupport {
+    /// Before a client initializes, hovers and completions are formatted for
+    /// a full editor; nothing that needs a request to the client is used
+    fn default() -> Self {
+        Self {
+            work_done_progress: false,
+            type_hierarchy_registration: false,
+            watched_files_registration: false,
+            semantic_tokens_refresh: false,
+            inlay_hint_refresh: false,
+            inline_completion: false,
+            pull_diagnostics: false,
+            diagnostic_refresh: false,
+            definition_links: false,
+            type_definition_links: false,
+            implementation_links: false,
+            markdown_hover: true,
+            snippets: true,
+        }
+    }
+}
+
+impl ClientSupport {
+    /// Read what the client declared in its `initialize` request
+    pub fn from_capabilities(capabilities: &ClientCapabilities) -> Self {
+        let text_document = capabilities.text_document.as_ref();
+        let workspace = capabilities.workspace.as_ref();
+
+        Self {
+            work_done_progress: capabilities
+                .window
+                .as_ref()
+                .and_then(|window| window.work_done_progress)
+                .unwrap_or(false),
+            type_hierarchy_registration: text_document
+                .and_then(|text_document| text_document.type_hierarchy.as_ref())
+                .and_then(|type_hierarchy| type_hierarchy.dynamic_registration)
+                .unwrap_or(false),
+            watched_files_registration: workspace
+                .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+                .and_then(|watched_files| watched_files.dynamic_registration)
+                .unwrap_or(false),
+            semantic_tokens_refresh: workspace
+                .and_then(|workspace| workspace.semantic_tokens.as_ref())
+                .and_then(|semantic_tokens| semantic_tokens.refresh_support)
+                .unwrap_or(false),
+            inlay_hint_refresh: workspace
+                .and_then(|workspace| workspace.inlay_hint.as_ref())
+                .and_then(|inlay_hint| inlay_hint.refresh_support)
+                .unwrap_or(false),
+            // Inline completion predates the protocol types, so clients opt in
+            // through an experimental capability
+            inline_completion: capabilities
+                .experimental
+                .as_ref()
+                .and_then(|experimental| experimental.get(INLINE_COMPLETION_CAPABILITY))
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false),
+            pull_diagnostics: text_document
+                .is_some_and(|text_document| text_document.diagnostic.is_some()),
+            diagnostic_refresh: workspace
+                .and_then(|workspace| workspace.diagnostic.as_ref())
+                .and_then(|diagnostic| diagnostic.refresh_support)
+                .unwrap_or(false),
+            definition_links: text_document
+                .and_then(|text_document| text_document.definition.as_ref())
+                .and_then(|definition| definition.link_support)
+                .unwrap_or(false),
+            type_definition_links: text_document
+                .and_then(|text_document| text_document.type_definition.as_ref())
+                .and_then(|type_definition| type_definition.link_support)
+                .unwrap_or(false),
+            implementation_links: text_document
+                .and_then(|text_document| text_document.implementation.as_ref())
+                .and_then(|implementation| implementation.link_support)
+                .unwrap_or(false),
+            markdown_hover: text_document
+                .and_then(|text_document| text_document.hover.as_ref())
+                .and_then(|hover| hover.content_format.as_ref())
+                .is_some_and(|formats| formats.contains(&MarkupKind::Markdown)),
+            snippets: text_document
+                .and_then(|text_document| text_document.completion.as_ref())
+                .and_then(|completion| completion.completion_item.as_ref())
+                .and_then(|item| item.snippet_support)
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// The go to requests that may answer with `LocationLink`s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GotoRequest {
+    Definition,
+    TypeDefinition,
+    Implementation,
+}
+
+impl LspServer {
+    /// Use what the client declared support for at initialize
+    pub fn set_client_support(&mut self, support: ClientSupport) {
+        self.client_support = support;
+    }
+
+    /// What the client declared support for
+    pub fn client_support(&self) -> &ClientSupport {
+        &self.client_support
+    }
+
+    /// A go to response for a request at `position`, as links from the name
+    /// under the cursor if the client supports them for that request
+    pub fn goto_response(
+        &self,
+        request: GotoRequest,
+        uri: &Url,
+        position: Position,
+        response: GotoDefinitionResponse,
+    ) -> GotoDefinitionResponse {
+        let link_support = match request {
+            GotoRequest::Definition => self.client_support.definition_links,
+            GotoRequest::TypeDefinition => self.client_support.type_definition_links,
+            GotoRequest::Implementation => self.client_support.implementation_links,
+        };
+        if !link_support {
+            return response;
+        }
+        let locations = match response {
+            GotoDefinitionResponse::Scalar(location) => vec![location],
+            GotoDefinitionResponse::Array(locations) => locations,
+            links @ GotoDefinitionResponse::Link(_) => return links,
+        };
+
+        let origin = self.name_range_at(uri, position);
+        GotoDefinitionResponse::Link(
+            locations
+                .into_iter()
+                .map(|Location { uri, range }| LocationLink {
+                    origin_selection_range: origin,
+                    target_uri: uri,
+                    target_range: range,
+                    target_selection_range: range,
+                })
+                .collect(),
+        )
+    }
+
+    /// Range of the name at `position` in an open document
+    fn name_range_at(&self, uri: &Url, position: Position) -> Option<Range> {
+        let text = self.document_texts.get(&uri_to_path(uri)?)?;
+        let cursor = (position.line, position.character);
+        tokenize(text)
+            .into_iter()
+            .find(|token| {
+                token.kind == TokenKind::Word
+                    && (token.start.line, token.start.character) <= cursor
+                    && cursor <= (token.end.line, token.end.character)
+            })
+            .map(|token| Range::new(token.start, token.end))
+    }
+}
+
+/// Plain text of hover markdown, for clients that can't render it
+///
+/// Drops emphasis, heading markers, rules and code fences, unwraps code
+/// spans and keeps only the text of links.
+pub(super) fn markdown_to_plain_text(markdown: &str) -> String {
+    let mut plain = String::with_capacity(markdown.len());
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed == "---" {
+            continue;
+        }
+        let line = match line.trim_start_matches('#') {
+            heading if heading.len() < line.len() && heading.starts_with(' ') => &heading[1..],
+            _ => line,
+        };
+        let line = match line.strip_prefix('_').and_then(|l| l.strip_suffix('_')) {
+            Some(emphasized) if !emphasized.is_empty() => emphasized,
+            _ => line,
+        };
+
+        let mut rest = line;
+        while !rest.is_empty() {
+            // `[text](target)` keeps its text
+            if let Some(after) = rest.strip_prefix('[')
+                && let Some(close) = after.find("](")
+                && let Some(end) = after[close + 2..].find(')')
+            {
+                plain.push_str(&markdown_to_plain_text(&after[..close]));
+                rest = &after[close + 2 + end + 1..];
+            } else if let Some(after) = rest.strip_prefix('`')
+                && let Some(end) = after.find('`')
+            {
+                // Code spans are kept verbatim, `*` and all
+                plain.push_str(&after[..end]);
+                rest = &after[end + 1..];
+            } else if let Some(after) = rest.strip_prefix("**") {
+                rest = after;
+            } else {
+                let mut chars = rest.chars();
+                plain.extend(chars.next());
+                rest = chars.as_str();
+            }
+        }
+        plain.push('\n');
+    }
+    plain.truncate(plain.trim_end().len());
+    plain
+}
+
+/// Plain insert text of a snippet, for clients without snippet support
+///
+/// Placeholders keep their default text, tab stops are dropped and escapes
+/// are resolved.
+pub(super) fn snippet_to_plain_text(snippet: &str) -> String {
+    let mut plain = String::with_capacity(snippet.len());
+    let mut chars = snippet.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => plain.extend(chars.next()),
+            '$' if chars.peek().is_some_and(char::is_ascii_digit) => {
+                while chars.next_if(char::is_ascii_digit).is_some() {}
+            }
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                while chars.next_if(char::is_ascii_digit).is_some() {}
+                // `${1:default}` keeps `default`, `${1}` nothing
+                if chars.next_if_eq(&':').is_some() {
+                    let mut depth = 0;
+                    for c in chars.by_ref() {
+                        match c {
+                            '{' => depth += 1,
+                            '}' if depth == 0 => break,
+                            '}' => depth -= 1,
+                            _ => {}
+                        }
+                        plain.push(c);
+                    }
+                } else {
+                    chars.next_if_eq(&'}');
+                }
+            }
+            _ => plain.push(c),
+        }
+    }
+    plain
+}
@@ -0,0 +1,357 @@
+//! Code actions for LSP.
+//!
+//! Quick fixes are built from the diagnostics the client sends back in the
+//! request context. Diagnostics that can be fixed carry what the fix needs in
+//! their `data`:
+//! - relationship keywords are replaced with the one their target calls for
+//! - relationships an element already declares are deleted
+//! - unresolved references get an import for each matching symbol elsewhere
+//!   in the workspace, and a rename to the closest visible names
+//! - references resolved only by the global fallback get an import of the
+//!   definition they resolved to
+//! - names breaking the naming convention are renamed, with their references
+//!   across the workspace, to the conventional spelling
+//! - imports nothing refers to are deleted
+//!
+//! Source actions that organize the document's imports are offered alongside.
+
+use super::LspServer;
+use super::completion_context::{self, CompletionContext};
+use super::diagnostics::UNDEFINED_REFERENCE_CODE;
+use super::helpers::uri_to_path;
+use super::naming_check::NAMING_CONVENTION_CODE;
+use super::qualified_name::minimal_name;
+use super::relationship_check::RELATIONSHIP_KIND_CODE;
+use super::relationship_duplicates::DUPLICATE_RELATIONSHIP_CODE;
+use super::scope_check::FALLBACK_RESOLUTION_CODE;
+use super::symbol_lookup::symbol;
+use super::unused_imports::UNUSED_IMPORT_CODE;
+use async_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic, NumberOrString,
+    Range, TextEdit, Url, WorkspaceEdit,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use syster::hir::{HirSymbol, SymbolKind};
+
+/// Most rename suggestions offered for an unresolved reference
+const MAX_RENAME_SUGGESTIONS: usize = 3;
+
+/// `data` of a relationship keyword diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelationshipFix {
+    /// The operator as written
+    pub operator: String,
+    /// The operator to use instead
+    pub replacement: String,
+}
+
+/// `data` of a duplicate relationship diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateRelationshipFix {
+    /// The repeated target as written
+    pub target: String,
+    /// Span deleting the repeated relationship
+    pub removal: Range,
+}
+
+/// `data` of an unresolved reference diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedReference {
+    /// The reference as written
+    pub name: String,
+}
+
+/// `data` of a naming convention diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamingFix {
+    /// The name as declared
+    pub name: String,
+    /// The name in the conventional case
+    pub replacement: String,
+    /// The case's label (e.g. "UpperCamelCase")
+    pub case: String,
+}
+
+/// `data` of an unused import diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnusedImportFix {
+    /// The imported path as written
+    pub path: String,
+    /// Lines deleting the import statement
+    pub removal: Range,
+}
+
+/// `data` of a fallback resolution diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackImport {
+    /// Qualified name of the definition the reference resolved to
+    pub target: String,
+}
+
+impl LspServer {
+    /// Get the code actions for a range of a document
+    pub fn get_code_actions(&mut self, params: &CodeActionParams) -> Vec<CodeActionOrCommand> {
+        let uri = &params.text_document.uri;
+        let mut actions = Vec::new();
+        for diagnostic in &params.context.diagnostics {
+            actions.extend(relationship_quick_fix(uri, diagnostic));
+            actions.extend(duplicate_relationship_fix(uri, diagnostic));
+            actions.extend(unused_import_fix(uri, diagnostic));
+            actions.extend(self.unresolved_reference_fixes(uri, diagnostic));
+            actions.extend(self.fallback_import_fix(uri, diagnostic));
+            actions.extend(self.naming_fix(uri, diagnostic));
+        }
+        actions.extend(self.organize_imports_actions(params));
+        actions
+            .into_iter()
+            .map(CodeActionOrCommand::CodeAction)
+            .collect()
+    }
+
+    /// Imports and renames that make an unresolved reference resolve
+    fn unresolved_reference_fixes(
+        &mut self,
+        uri: &Url,
+        diagnostic: &Diagnostic,
+    ) -> Vec<CodeAction> {
+        if diagnostic.code != Some(NumberOrString::String(UNDEFINED_REFERENCE_CODE.to_string())) {
+            return Vec::new();
+        }
+        let Some(reference) = diagnostic
+            .data
+            .clone()
+            .and_then(|data| serde_json::from_value::<UnresolvedReference>(data).ok())
+        else {
+            return Vec::new();
+        };
+        let Some(text) = uri_to_path(uri).and_then(|path| self.document_texts.get(&path)) else {
+            return Vec::new();
+        };
+        let site = completion_context::analyze(text, diagnostic.range.start);
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+        // A type reference can only be fixed with a definition
+        let accepts = |sym: &HirSymbol| {
+            !sym.name.starts_with('<')
+                && sym.kind != SymbolKind::Import
+                && (site.context != CompletionContext::TypedBy
+                    || sym.kind.is_definition() && sym.kind != SymbolKind::Package)
+        };
+
+        let mut actions = Vec::new();
+
+        // The last segment of a qualified reference is the name to look for
+        let simple_name = reference
+            .name
+            .rsplit("::")
+            .next()
+            .unwrap_or(&reference.name);
+        self.symbol_lookup.sync(&analysis);
+        let mut matches: Vec<&HirSymbol> = self
+            .symbol_lookup
+            .named(simple_name)
+            .filter_map(|(path, entry)| {
+                symbol(index, analysis.get_file_id(&path.to_string_lossy())?, entry)
+            })
+            .filter(|sym| sym.name.as_ref() == simple_name && accepts(sym))
+            .filter(|sym| !completion_context::is_visible(index, &site.scope, sym))
+            .collect();
+        matches.sort_by(|a, b| a.qualified_name.cmp(&b.qualified_name));
+        for sym in matches {
+            let path = minimal_name(index, sym, &site.import_site.package);
+            let position = site.import_site.position;
+            let edit = TextEdit {
+                range: Range::new(position, position),
+                new_text: format!("{}private import {path};\n", site.import_site.indent),
+            };
+            actions.push(quick_fix(
+                format!("Import '{path}'"),
+                uri,
+                diagnostic,
+                vec![edit],
+                actions.is_empty(),
+            ));
+        }
+
+        // Visible names within a small edit distance
+        let max_distance = (reference.name.chars().count() / 3).max(1);
+        let mut candidates: Vec<(usize, String)> = index
+            .all_symbols()
+            .filter(|sym| accepts(sym) && sym.name.as_ref() != reference.name)
+            .filter_map(|sym| {
+                let distance = edit_distance(&reference.name, &sym.name);
+                (distance <= max_distance
+                    && completion_context::is_visible(index, &site.scope, sym))
+                .then(|| (distance, sym.name.to_string()))
+            })
+            .collect();
+        candidates.sort();
+        candidates.dedup_by(|a, b| a.1 == b.1);
+        for (_, name) in candidates.into_iter().take(MAX_RENAME_SUGGESTIONS) {
+            let edit = TextEdit {
+                range: diagnostic.range,
+                new_text: name.clone(),
+            };
+            actions.push(quick_fix(
+                format!("Change to '{name}'"),
+                uri,
+                diagnostic,
+                vec![edit],
+                actions.is_empty(),
+            ));
+        }
+
+        actions
+    }
+
+    /// Import the definition a reference resolved to through the global fallback
+    fn fallback_import_fix(&mut self, uri: &Url, diagnostic: &Diagnostic) -> Option<CodeAction> {
+        if diagnostic.code != Some(NumberOrString::String(FALLBACK_RESOLUTION_CODE.to_string())) {
+            return None;
+        }
+        let fix: FallbackImport = serde_json::from_value(diagnostic.data.clone()?).ok()?;
+        let text = self.document_texts.get(&uri_to_path(uri)?)?;
+        let site = completion_context::analyze(text, diagnostic.range.start);
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+        let target = index.lookup_qualified(&fix.target)?;
+        let path = minimal_name(index, target, &site.import_site.package);
+        let position = site.import_site.position;
+        let edit = TextEdit {
+            range: Range::new(position, position),
+            new_text: format!("{}private import {path};\n", site.import_site.indent),
+        };
+        Some(quick_fix(
+            format!("Import '{path}'"),
+            uri,
+            diagnostic,
+            vec![edit],
+            true,
+        ))
+    }
+
+    /// Rename a name breaking the naming convention, and every reference to it
+    fn naming_fix(&mut self, uri: &Url, diagnostic: &Diagnostic) -> Option<CodeAction> {
+        if diagnostic.code != Some(NumberOrString::String(NAMING_CONVENTION_CODE.to_string())) {
+            return None;
+        }
+        let fix: NamingFix = serde_json::from_value(diagnostic.data.clone()?).ok()?;
+        // No fix when the new name is taken in the same scope
+        let edit = self.get_rename_edits(uri, diagnostic.range.start, &fix.replacement)?;
+
+        Some(CodeAction {
+            title: format!(
+                "Convert '{}' to {} ('{}')",
+                fix.name, fix.case, fix.replacement
+            ),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(edit),
+            is_preferred: Some(true),
+            ..Default::default()
+        })
+    }
+}
+
+/// Replace a relationship keyword with the one its target calls for
+fn relationship_quick_fix(uri: &Url, diagnostic: &Diagnostic) -> Option<CodeAction> {
+    if diagnostic.code != Some(NumberOrString::String(RELATIONSHIP_KIND_CODE.to_string())) {
+        return None;
+    }
+    let fix: RelationshipFix = serde_json::from_value(diagnostic.data.clone()?).ok()?;
+
+    let edit = TextEdit {
+        range: diagnostic.range,
+        new_text: fix.replacement.clone(),
+    };
+    Some(quick_fix(
+        format!("Replace '{}' with '{}'", fix.operator, fix.replacement),
+        uri,
+        diagnostic,
+        vec![edit],
+        true,
+    ))
+}
+
+/// Delete a relationship the element already declares
+fn duplicate_relationship_fix(uri: &Url, diagnostic: &Diagnostic) -> Option<CodeAction> {
+    if diagnostic.code
+        != Some(NumberOrString::String(
+            DUPLICATE_RELATIONSHIP_CODE.to_string(),
+        ))
+    {
+        return None;
+    }
+    let fix: DuplicateRelationshipFix = serde_json::from_value(diagnostic.data.clone()?).ok()?;
+
+    let edit = TextEdit {
+        range: fix.removal,
+        new_text: String::new(),
+    };
+    Some(quick_fix(
+        format!("Remove duplicate '{}'", fix.target),
+        uri,
+        diagnostic,
+        vec![edit],
+        true,
+    ))
+}
+
+/// Delete an import nothing refers to
+fn unused_import_fix(uri: &Url, diagnostic: &Diagnostic) -> Option<CodeAction> {
+    if diagnostic.code != Some(NumberOrString::String(UNUSED_IMPORT_CODE.to_string())) {
+        return None;
+    }
+    let fix: UnusedImportFix = serde_json::from_value(diagnostic.data.clone()?).ok()?;
+
+    let edit = TextEdit {
+        range: fix.removal,
+        new_text: String::new(),
+    };
+    Some(quick_fix(
+        format!("Remove unused import '{}'", fix.path),
+        uri,
+        diagnostic,
+        vec![edit],
+        true,
+    ))
+}
+
+/// A quick fix applying `edits` to the document
+fn quick_fix(
+    title: String,
+    uri: &Url,
+    diagnostic: &Diagnostic,
+    edits: Vec<TextEdit>,
+    is_preferred: bool,
+) -> CodeAction {
+    CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), edits)])),
+            ..Default::default()
+        }),
+        is_preferred: Some(is_preferred),
+        ..Default::default()
+    }
+}
+
+/// Levenshtein distance between two names, in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -1,19 +1,36 @@
 use super::LspServer;
+use super::diagram::GetDiagramParams;
 use super::helpers::uri_to_path;
+use super::reference_index::ReferenceIndex;
 use async_lsp::lsp_types::{CodeLens, Command, Location, Position, Range, Url};
-use syster::hir::SymbolKind;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use syster::hir::{HirSymbol, SymbolIndex, SymbolKind};
+
+/// `data` of a code lens whose command is filled in by `codeLens/resolve`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CodeLensData {
+    /// "Open diagram" scoped to a definition
+    #[serde(rename_all = "camelCase")]
+    Diagram { uri: String, qualified_name: String },
+}
 
 impl LspServer {
     /// Get code lenses for a document
     ///
     /// Shows inline commands above definitions:
     /// - "N references" - clickable to show all references
+    /// - "Open diagram" above definitions - resolved lazily, opens `syster/getDiagram`
+    ///   scoped to the definition
+    /// - "expands to N visible names" above wildcard imports - clickable to list the names
     pub fn get_code_lenses(&mut self, uri: &Url) -> Vec<CodeLens> {
         let Some(path) = uri_to_path(uri) else {
             return Vec::new();
         };
 
         let analysis = self.analysis_host.analysis();
+        self.reference_index.sync(&analysis);
         let path_str = path.to_string_lossy();
 
         let Some(file_id) = analysis.get_file_id(&path_str) else {
@@ -24,6 +41,15 @@ impl LspServer {
 
         // Get symbols in this file from the SymbolIndex
         for symbol in analysis.symbol_index().symbols_in_file(file_id) {
+            if symbol.kind == SymbolKind::Import {
+                lenses.extend(Self::wildcard_import_lens(
+                    analysis.symbol_index(),
+                    symbol,
+                    uri,
+                ));
+                continue;
+            }
+
             // Only show code lens for definitions
             if !symbol.kind.is_definition() && !matches!(symbol.kind, SymbolKind::Package) {
                 continue;
@@ -40,10 +66,11 @@ impl LspServer {
                 },
             };
 
-            // Count references using type_refs
-            let qualified_name = symbol.qualified_name.as_ref();
-            let references =
-                Self::collect_reference_locations_from_analysis(&analysis, qualified_name);
+            // Count references from the reference index
+            let references = Self::collect_reference_locations(
+                &self.reference_index,
+                symbol.qualified_name.as_ref(),
+            );
             let reference_count = references.len();
 
             // Only show code lens if there are references
@@ -74,42 +101,180 @@ impl LspServer {
                 };
                 lenses.push(lens);
             }
+
+            if symbol.kind.is_definition() {
+                let data = CodeLensData::Diagram {
+                    uri: uri.to_string(),
+                    qualified_name: symbol.qualified_name.to_string(),
+                };
+                lenses.push(CodeLens {
+                    range,
+                    command: None,
+                    data: serde_json::to_value(data).ok(),
+                });
+            }
         }
 
         lenses
     }
 
+    /// Fill in the command of a code lens returned without one
+    pub fn resolve_code_lens(&mut self, mut lens: CodeLens) -> CodeLens {
+        if lens.command.is_some() {
+            return lens;
+        }
+        let Some(data) = lens
+            .data
+            .clone()
+            .and_then(|data| serde_json::from_value::<CodeLensData>(data).ok())
+        else {
+            return lens;
+        };
+
+        match data {
+            CodeLensData::Diagram {
+                uri,
+                qualified_name,
+            } => {
+                // The argument is the syster/getDiagram request to send
+                let params = GetDiagramParams {
+                    uri: Some(uri),
+                    element: Some(qualified_name),
+                    depth: None,
+                    view_type: "GeneralView".to_string(),
+                };
+                lens.command = Some(Command {
+                    title: "Open diagram".to_string(),
+                    command: "syster.showDiagram".to_string(),
+                    arguments: serde_json::to_value(params).ok().map(|params| vec![params]),
+                });
+            }
+        }
+        lens
+    }
+
+    /// Code lens previewing the names a wildcard (`::*`) or recursive (`::**`) import brings in
+    fn wildcard_import_lens(
+        index: &SymbolIndex,
+        import: &HirSymbol,
+        uri: &Url,
+    ) -> Option<CodeLens> {
+        let names = wildcard_import_names(index, import)?;
+
+        let range = Range {
+            start: Position::new(import.start_line, import.start_col),
+            end: Position::new(import.end_line, import.end_col),
+        };
+        let names_value = serde_json::Value::Array(
+            names
+                .into_iter()
+                .map(|(name, qualified_name)| {
+                    serde_json::json!({ "name": name, "qualifiedName": qualified_name })
+                })
+                .collect(),
+        );
+        let count = names_value.as_array().map_or(0, Vec::len);
+
+        Some(CodeLens {
+            range,
+            command: Some(Command {
+                title: format!(
+                    "expands to {} visible name{}",
+                    count,
+                    if count == 1 { "" } else { "s" }
+                ),
+                command: "syster.showImportedNames".to_string(),
+                arguments: Some(vec![
+                    serde_json::Value::String(uri.to_string()),
+                    serde_json::to_value(range.start).ok()?,
+                    names_value,
+                ]),
+            }),
+            data: None,
+        })
+    }
+
     /// Collect all reference locations for a qualified name
-    fn collect_reference_locations_from_analysis(
-        analysis: &syster::ide::Analysis<'_>,
+    fn collect_reference_locations(
+        reference_index: &ReferenceIndex,
         qualified_name: &str,
     ) -> Vec<Location> {
-        analysis
-            .symbol_index()
-            .all_symbols()
-            .flat_map(|sym| {
-                sym.type_refs
-                    .iter()
-                    .flat_map(|trk| trk.as_refs())
-                    .filter(|tr| tr.target.as_ref() == qualified_name)
-                    .filter_map(|tr| {
-                        let path = analysis.get_file_path(sym.file)?;
-                        let uri = Url::from_file_path(path).ok()?;
-                        Some(Location {
-                            uri,
-                            range: Range {
-                                start: Position {
-                                    line: tr.start_line,
-                                    character: tr.start_col,
-                                },
-                                end: Position {
-                                    line: tr.end_line,
-                                    character: tr.end_col,
-                                },
-                            },
-                        })
-                    })
+        let mut locations: Vec<Location> = reference_index
+            .references_to(qualified_name)
+            .filter_map(|(path, entry)| {
+                let uri = Url::from_file_path(path).ok()?;
+                Some(Location {
+                    uri,
+                    range: entry.range,
+                })
             })
-            .collect()
+            .collect();
+        locations.sort_by(|a, b| {
+            (a.uri.as_str(), a.range.start.line, a.range.start.character).cmp(&(
+                b.uri.as_str(),
+                b.range.start.line,
+                b.range.start.character,
+            ))
+        });
+        locations
+    }
+}
+
+/// Names made visible by a wildcard or recursive import, keyed by simple name
+///
+/// Returns `None` for membership imports such as `import Pkg::Part;`.
+pub(super) fn wildcard_import_names(
+    index: &SymbolIndex,
+    import: &HirSymbol,
+) -> Option<BTreeMap<String, String>> {
+    let path = import.name.as_ref();
+    let (target, recursive) = if let Some(target) = path.strip_suffix("::**") {
+        (target, true)
+    } else {
+        (path.strip_suffix("::*")?, false)
+    };
+
+    // Import symbols are named `<scope>::import:<path>`
+    let qualified_name = import.qualified_name.as_ref();
+    let scope = qualified_name
+        .find("::import:")
+        .map_or("", |idx| &qualified_name[..idx]);
+    let namespace = index
+        .resolver_for_scope(scope)
+        .resolve(target)
+        .symbol()
+        .map(|sym| sym.qualified_name.to_string())
+        .unwrap_or_else(|| target.to_string());
+
+    let mut names = namespace_names(index, &namespace);
+    if recursive {
+        let prefix = format!("{namespace}::");
+        for sym in index
+            .all_symbols()
+            .filter(|sym| sym.kind != SymbolKind::Import && sym.qualified_name.starts_with(&prefix))
+        {
+            names
+                .entry(sym.name.to_string())
+                .or_insert_with(|| sym.qualified_name.to_string());
+        }
+    }
+    // Anonymous elements can't be referred to by name
+    names.retain(|name, _| !name.is_empty() && !name.starts_with('<'));
+
+    Some(names)
+}
+
+/// Names a namespace makes visible to qualified references - its members and
+/// what it imports - keyed by simple name
+pub(super) fn namespace_names(index: &SymbolIndex, namespace: &str) -> BTreeMap<String, String> {
+    let mut names = BTreeMap::new();
+    if let Some(visibility) = index.visibility_for_scope(namespace) {
+        for (name, qualified_name) in visibility.direct_defs().chain(visibility.imports()) {
+            names
+                .entry(name.to_string())
+                .or_insert_with(|| qualified_name.to_string());
+        }
     }
+    names.retain(|name, _| !name.is_empty() && !name.starts_with('<'));
+    names
 }
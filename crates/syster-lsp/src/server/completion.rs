@@ -1,8 +1,17 @@
+use super::client_support::snippet_to_plain_text;
+use super::code_lens::namespace_names;
+use super::completion_context::{self, CompletionContext, CompletionSite};
+use super::connector_ends;
+use super::parse_cache::CachedParse;
+use super::qualified_name::shortest_name;
+use super::snippet_completions::add_snippet_completions;
 use crate::server::core::LspServer;
 use async_lsp::lsp_types::{
-    CompletionItem, CompletionItemKind, CompletionResponse, Documentation, InsertTextFormat,
-    Position,
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionResponse,
+    Documentation, InsertTextFormat, Position, Range, TextEdit,
 };
+use std::collections::{HashMap, HashSet};
+use syster::hir::{HirSymbol, SymbolIndex, SymbolKind as HirSymbolKind};
 
 impl LspServer {
     /// Get completion items at a position
@@ -13,8 +22,24 @@ impl LspServer {
         path: &std::path::Path,
         position: Position,
     ) -> CompletionResponse {
+        // Type and relationship targets are limited to what's valid and visible
+        let site = self
+            .document_texts
+            .get(path)
+            .map(|text| completion_context::analyze(text, position));
+        if let Some(site) = &site
+            && site.context != CompletionContext::General
+        {
+            let mut items = self.get_context_completions(path, position, site);
+            self.rank_completions(path, position, site, &mut items);
+            return CompletionResponse::Array(items);
+        }
+
         let path_str = path.to_string_lossy();
-        let analysis = self.analysis_host.analysis();
+        let analysis = self
+            .root_libraries
+            .host(path, &mut self.analysis_host)
+            .analysis();
 
         // Get file ID for the new HIR layer
         let file_id = match analysis.get_file_id(&path_str) {
@@ -34,12 +59,15 @@ impl LspServer {
             }
         });
 
+        // Clients without snippet support get the placeholders' plain text
+        let snippets = self.client_support.snippets;
+
         // Use the Analysis completions method
         let ide_completions =
             analysis.completions(file_id, position.line, position.character, trigger);
 
         // Convert to LSP CompletionItems
-        let items: Vec<CompletionItem> = ide_completions
+        let mut items: Vec<CompletionItem> = ide_completions
             .into_iter()
             .map(|item| {
                 // Convert u32 kind to LSP CompletionItemKind
@@ -51,7 +79,6 @@ impl LspServer {
                     15 => CompletionItemKind::SNIPPET, // Snippet
                     _ => CompletionItemKind::TEXT,
                 };
-                let has_insert_text = item.insert_text.is_some();
                 CompletionItem {
                     label: item.label.to_string(),
                     kind: Some(lsp_kind),
@@ -59,18 +86,436 @@ impl LspServer {
                     documentation: item
                         .documentation
                         .map(|s| Documentation::String(s.to_string())),
-                    insert_text: item.insert_text.map(|s| s.to_string()),
-                    insert_text_format: if has_insert_text {
-                        Some(InsertTextFormat::SNIPPET)
+                    insert_text_format: item.insert_text.is_some().then_some(if snippets {
+                        InsertTextFormat::SNIPPET
                     } else {
-                        None
-                    },
+                        InsertTextFormat::PLAIN_TEXT
+                    }),
+                    insert_text: item.insert_text.map(|text| {
+                        if snippets {
+                            text.to_string()
+                        } else {
+                            snippet_to_plain_text(&text)
+                        }
+                    }),
                     sort_text: Some(format!("{:03}_{}", item.sort_priority, item.label)),
                     ..Default::default()
                 }
             })
             .collect();
 
+        // Aliases aren't definitions, so the IDE layer only offers those in this file
+        if trigger != Some('.') {
+            let types_only = trigger == Some(':');
+            add_alias_completions(&mut items, analysis.symbol_index(), file_id, types_only);
+        }
+
+        // Skeletons of whole elements, where a new statement can begin
+        if snippets && site.as_ref().is_some_and(|site| site.at_statement_start) {
+            add_snippet_completions(&mut items);
+        }
+
+        // Candidates near the cursor come before the library's
+        if let Some(site) = &site {
+            self.rank_completions(path, position, site, &mut items);
+        }
         CompletionResponse::Array(items)
     }
+
+    /// Completions for a type, supertype or redefined feature reference
+    ///
+    /// An incomplete statement usually stops the document from parsing, which
+    /// leaves its declarations and imports out of the index. In that case the
+    /// statement is completed with a placeholder and the repaired text is
+    /// indexed while the completions are computed, then replaced by the
+    /// file the host had before (or removed, if it had none).
+    fn get_context_completions(
+        &mut self,
+        path: &std::path::Path,
+        position: Position,
+        site: &CompletionSite,
+    ) -> Vec<CompletionItem> {
+        let has_errors = self
+            .parse_errors
+            .get(path)
+            .is_some_and(|errors| !errors.is_empty());
+        let repaired = if has_errors {
+            self.document_texts
+                .get(path)
+                .and_then(|text| completion_context::repair(text, position))
+                .map(|text| CachedParse::parse(path, &text))
+                .filter(|parse| parse.errors.is_empty())
+                .and_then(|parse| parse.content)
+        } else {
+            None
+        };
+        let host = self.root_libraries.host(path, &mut self.analysis_host);
+        let original = repaired.map(|file| {
+            let original = host.files().get(path).cloned();
+            host.set_file(path.to_path_buf(), file);
+            original
+        });
+
+        let analysis = host.analysis();
+        let file_id = analysis.get_file_id(&path.to_string_lossy());
+        let mut items = match &site.context {
+            CompletionContext::ConnectorEnd { first_end, prefix } => {
+                connector_ends::connector_end_completions(
+                    analysis.symbol_index(),
+                    &site.scope,
+                    first_end,
+                    prefix,
+                    &|sym| connector_ends::is_conjugated(&analysis, &self.document_texts, sym),
+                )
+            }
+            CompletionContext::MemberAccess { chain } => {
+                member_completions(analysis.symbol_index(), &site.scope, chain)
+            }
+            CompletionContext::QualifiedName { prefix, within } => {
+                qualified_completions(analysis.symbol_index(), &site.scope, prefix, within)
+            }
+            _ => context_completions(analysis.symbol_index(), site),
+        };
+        if site.context == CompletionContext::TypedBy
+            && let Some(file_id) = file_id
+        {
+            add_alias_completions(&mut items, analysis.symbol_index(), file_id, true);
+        }
+
+        let host = self.root_libraries.host(path, &mut self.analysis_host);
+        match original {
+            Some(Some(file)) => host.set_file(path.to_path_buf(), file),
+            Some(None) => host.remove_file(path),
+            None => {}
+        }
+        items
+    }
+}
+
+/// Completions for a type, supertype or redefined feature reference
+fn context_completions(index: &SymbolIndex, site: &CompletionSite) -> Vec<CompletionItem> {
+    let accepts = |sym: &HirSymbol| accepts(&site.context, sym);
+
+    // The element being declared can't be its own type or supertype
+    let declared = site.declared_name.as_ref().map(|name| {
+        if site.scope.is_empty() {
+            name.clone()
+        } else {
+            format!("{}::{name}", site.scope)
+        }
+    });
+
+    // Features are usually specialized or redefined from the owner's supertypes
+    let inherited = match site.context {
+        CompletionContext::Specializes { .. } | CompletionContext::Redefines { .. } => {
+            completion_context::inherited_features(index, &site.scope)
+        }
+        _ => Vec::new(),
+    };
+
+    // One resolver and one set of offered labels serve every candidate
+    let resolver = index.resolver_for_scope(&site.scope);
+    let visible = index.all_symbols().filter(|sym| {
+        accepts(sym)
+            && !sym.name.starts_with('<')
+            && resolver
+                .resolve(&sym.name)
+                .symbol()
+                .is_some_and(|found| found.qualified_name == sym.qualified_name)
+    });
+
+    let mut items: Vec<CompletionItem> = Vec::new();
+    let mut offered: HashSet<String> = HashSet::new();
+    for sym in inherited
+        .into_iter()
+        .filter(|sym| accepts(sym))
+        .chain(visible)
+    {
+        let label = sym.name.to_string();
+        if declared.as_deref() == Some(sym.qualified_name.as_ref())
+            || !offered.insert(label.clone())
+        {
+            continue;
+        }
+        items.push(CompletionItem {
+            label: label.clone(),
+            kind: Some(if sym.kind.is_definition() {
+                CompletionItemKind::CLASS
+            } else {
+                CompletionItemKind::FIELD
+            }),
+            detail: Some(if sym.supertypes.is_empty() {
+                sym.kind.display().to_string()
+            } else {
+                format!(": {}", sym.supertypes.join(", "))
+            }),
+            documentation: sym
+                .doc
+                .as_ref()
+                .map(|doc| Documentation::String(doc.to_string())),
+            sort_text: Some(format!("010_{label}")),
+            ..Default::default()
+        });
+    }
+
+    // Definitions that aren't visible yet are offered with an import
+    if matches!(
+        site.context,
+        CompletionContext::TypedBy | CompletionContext::Specializes { .. }
+    ) {
+        let import_site = &site.import_site;
+        let package_resolver = index.resolver_for_scope(&import_site.package);
+        let mut hidden: Vec<&HirSymbol> = index
+            .all_definitions()
+            .filter(|sym| {
+                accepts(sym)
+                    && sym.kind.is_definition()
+                    && !sym.qualified_name.contains('<')
+                    && declared.as_deref() != Some(sym.qualified_name.as_ref())
+            })
+            .collect();
+        hidden.sort_by(|a, b| a.qualified_name.cmp(&b.qualified_name));
+
+        for sym in hidden {
+            let label = sym.name.to_string();
+            if !offered.insert(label.clone()) {
+                continue;
+            }
+            let path = shortest_name(&package_resolver, sym);
+            items.push(CompletionItem {
+                label: label.clone(),
+                kind: Some(CompletionItemKind::CLASS),
+                detail: Some(format!("import {path}")),
+                label_details: Some(CompletionItemLabelDetails {
+                    detail: None,
+                    description: Some(path.clone()),
+                }),
+                documentation: sym
+                    .doc
+                    .as_ref()
+                    .map(|doc| Documentation::String(doc.to_string())),
+                sort_text: Some(format!("060_{label}")),
+                additional_text_edits: Some(vec![TextEdit {
+                    range: Range::new(import_site.position, import_site.position),
+                    new_text: format!("{}private import {path};\n", import_site.indent),
+                }]),
+                ..Default::default()
+            });
+        }
+    }
+    items
+}
+
+/// Completions for the members of the feature `chain` names from `scope`
+///
+/// The feature's own members come first, then those of its types and their
+/// supertypes; a member redefined along the way is offered once.
+fn member_completions(index: &SymbolIndex, scope: &str, chain: &[String]) -> Vec<CompletionItem> {
+    let Some(feature) = connector_ends::resolve_chain(index, scope, chain) else {
+        return Vec::new();
+    };
+    let mut seen = HashSet::new();
+    connector_ends::features_of_scope(index, &feature.qualified_name)
+        .into_iter()
+        .filter(|sym| !sym.name.starts_with('<') && seen.insert(sym.name.clone()))
+        .map(|sym| {
+            let label = sym.name.to_string();
+            CompletionItem {
+                label: label.clone(),
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(if sym.supertypes.is_empty() {
+                    sym.kind.display().to_string()
+                } else {
+                    format!(": {}", sym.supertypes.join(", "))
+                }),
+                documentation: sym
+                    .doc
+                    .as_ref()
+                    .map(|doc| Documentation::String(doc.to_string())),
+                sort_text: Some(format!("010_{label}")),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Whether `sym` is a valid reference target in `context`
+fn accepts(context: &CompletionContext, sym: &HirSymbol) -> bool {
+    match context {
+        CompletionContext::TypedBy => {
+            sym.kind.is_definition() && sym.kind != HirSymbolKind::Package
+        }
+        CompletionContext::Specializes { kind: Some(kind) } => sym.kind == *kind,
+        CompletionContext::Specializes { kind: None } => {
+            sym.kind.is_definition() && sym.kind != HirSymbolKind::Package || sym.kind.is_usage()
+        }
+        CompletionContext::Redefines { kind } => {
+            sym.kind.is_usage() && kind.is_none_or(|kind| sym.kind == kind)
+        }
+        CompletionContext::ConnectorEnd { .. }
+        | CompletionContext::MemberAccess { .. }
+        | CompletionContext::QualifiedName { .. }
+        | CompletionContext::General => false,
+    }
+}
+
+/// Completions for the next segment of a qualified name like `ISQ::`
+///
+/// The namespace's own members are offered along with the names it
+/// re-exports through public imports. Packages always are, since the name
+/// can go on through them; other members only if they are valid `within`
+/// the reference being typed.
+fn qualified_completions(
+    index: &SymbolIndex,
+    scope: &str,
+    prefix: &[String],
+    within: &CompletionContext,
+) -> Vec<CompletionItem> {
+    let path = prefix.join("::");
+    let Some(namespace) = index
+        .resolver_for_scope(scope)
+        .resolve(&path)
+        .symbol()
+        .map(|sym| sym.qualified_name.to_string())
+        .or_else(|| {
+            index
+                .lookup_qualified(&path)
+                .map(|sym| sym.qualified_name.to_string())
+        })
+    else {
+        return Vec::new();
+    };
+
+    let mut items = Vec::new();
+    for (name, qualified_name) in namespace_names(index, &namespace) {
+        let Some(sym) = index.lookup_qualified(&qualified_name) else {
+            continue;
+        };
+        let target = match sym.kind {
+            HirSymbolKind::Alias => alias_target(index, sym),
+            _ => Some(sym.clone()),
+        };
+        let Some(target) = target else {
+            continue;
+        };
+        let valid = match within {
+            CompletionContext::General | CompletionContext::MemberAccess { .. } => {
+                target.kind != HirSymbolKind::Import
+            }
+            context => target.kind == HirSymbolKind::Package || accepts(context, &target),
+        };
+        if !valid {
+            continue;
+        }
+        items.push(CompletionItem {
+            label: name.clone(),
+            kind: Some(item_kind(&target)),
+            detail: Some(if sym.kind == HirSymbolKind::Alias {
+                format!("alias for {}", target.qualified_name)
+            } else if sym.supertypes.is_empty() {
+                sym.kind.display().to_string()
+            } else {
+                format!(": {}", sym.supertypes.join(", "))
+            }),
+            documentation: target
+                .doc
+                .as_ref()
+                .map(|doc| Documentation::String(doc.to_string())),
+            sort_text: Some(format!("010_{name}")),
+            ..Default::default()
+        });
+    }
+    items
+}
+
+/// Resolve the target of an alias from the alias's own scope
+pub(super) fn alias_target(index: &SymbolIndex, alias: &HirSymbol) -> Option<HirSymbol> {
+    let target = alias.supertypes.first()?;
+    let scope = alias
+        .qualified_name
+        .rsplit_once("::")
+        .map_or("", |(scope, _)| scope);
+    index
+        .resolver_for_scope(scope)
+        .resolve(target)
+        .symbol()
+        .cloned()
+        .or_else(|| index.lookup_qualified(target).cloned())
+}
+
+/// Offer aliases alongside their targets.
+///
+/// Each alias is labelled with its own name and a detail naming the target.
+/// An alias whose label is already offered (e.g. it has the same name as its
+/// target) would insert the same reference, so it's skipped. With `types_only`,
+/// only aliases of definitions are offered.
+fn add_alias_completions(
+    items: &mut Vec<CompletionItem>,
+    index: &SymbolIndex,
+    file: syster::base::FileId,
+    types_only: bool,
+) {
+    let mut aliases: Vec<&HirSymbol> = index
+        .all_symbols()
+        .filter(|sym| sym.kind == HirSymbolKind::Alias)
+        .collect();
+    // Prefer aliases in this file, then the shortest qualified name
+    aliases.sort_by_key(|sym| (sym.file != file, sym.qualified_name.len()));
+
+    let mut offered: HashMap<String, usize> = items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| (item.label.clone(), idx))
+        .collect();
+    for alias in aliases {
+        let Some(target) = alias_target(index, alias) else {
+            continue;
+        };
+        if types_only && !target.kind.is_definition() {
+            continue;
+        }
+
+        let label = alias.name.to_string();
+        let existing = offered.get(&label).copied();
+        let detail = format!("alias for {}", target.qualified_name);
+
+        if let Some(idx) = existing {
+            // The IDE layer lists same-file aliases like any symbol; add the target
+            let is_this_alias = items[idx].detail.as_deref()
+                == Some(format!(": {}", alias.supertypes.join(", ")).as_str());
+            if is_this_alias {
+                items[idx].detail = Some(detail);
+                items[idx].kind = Some(item_kind(&target));
+            }
+            continue;
+        }
+        let priority = match (types_only, alias.file == file) {
+            (true, _) => 10,
+            (false, true) => 20,
+            (false, false) => 50,
+        };
+        offered.insert(label.clone(), items.len());
+        items.push(CompletionItem {
+            label: label.clone(),
+            kind: Some(item_kind(&target)),
+            detail: Some(detail),
+            documentation: target
+                .doc
+                .as_ref()
+                .map(|doc| Documentation::String(doc.to_string())),
+            sort_text: Some(format!("{priority:03}_{label}")),
+            ..Default::default()
+        });
+    }
+}
+
+/// Completion kind for a symbol, or for an alias of it
+fn item_kind(target: &HirSymbol) -> CompletionItemKind {
+    if target.kind == HirSymbolKind::Package {
+        CompletionItemKind::MODULE
+    } else if target.kind.is_definition() {
+        CompletionItemKind::CLASS
+    } else {
+        CompletionItemKind::FIELD
+    }
 }
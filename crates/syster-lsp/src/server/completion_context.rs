@@ -0,0 +1,535 @@
+//! Completion context analysis
+//!
+//! Works out what is expected at the cursor from the tokens before it - a type
+//! after `:`, a supertype after `:>`, a redefined feature after `:>>`, a member
+//! after `.` - and which namespace the cursor is in, so completions can be
+//! limited to what is valid and visible there.
+
+use std::collections::HashSet;
+
+use async_lsp::lsp_types::Position;
+use syster::hir::{HirSymbol, RefKind, SymbolIndex, SymbolKind};
+
+use super::helpers::position_to_byte_offset;
+use super::tokens::{Token, TokenKind, tokenize, unquote};
+
+/// What kind of reference is expected at the cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum CompletionContext {
+    /// After `:`, `defined by` or `typed by`: any definition usable as a type
+    TypedBy,
+    /// After `:>`, `specializes` or `subsets`: an element of the declared kind
+    Specializes { kind: Option<SymbolKind> },
+    /// After `:>>` or `redefines`: a feature of the declared kind
+    Redefines { kind: Option<SymbolKind> },
+    /// After `connect <first end> to`: a feature that can be connected to the
+    /// first end, below the features named by `prefix` (`engine.` so far)
+    ConnectorEnd {
+        first_end: Vec<String>,
+        prefix: Vec<String>,
+    },
+    /// After `driver.` or `vehicle.engine.`: a member of the feature the
+    /// chain names, owned by it or by its types
+    MemberAccess { chain: Vec<String> },
+    /// After `ISQ::` or `Vehicles::Parts::`: a member of the namespace
+    /// `prefix` names, valid for the reference the qualified name is `within`
+    QualifiedName {
+        prefix: Vec<String>,
+        within: Box<CompletionContext>,
+    },
+    /// Anywhere else
+    General,
+}
+
+/// The statement being completed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CompletionSite {
+    pub(super) context: CompletionContext,
+    /// Name declared by the statement, if it has one yet
+    pub(super) declared_name: Option<String>,
+    /// Nothing but the word being typed is before the cursor in the statement
+    pub(super) at_statement_start: bool,
+    /// Qualified name of the innermost named namespace containing the cursor
+    pub(super) scope: String,
+    /// Where an import for a symbol that isn't visible would go
+    pub(super) import_site: ImportSite,
+}
+
+/// Insertion point for a new import in the package enclosing the cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ImportSite {
+    /// Qualified name of the package, empty for the root namespace
+    pub(super) package: String,
+    /// Start of the line after the package's last import, or after its `{`
+    pub(super) position: Position,
+    /// Indentation of the package's members
+    pub(super) indent: String,
+}
+
+/// A `{` that is still open at the cursor
+struct OpenBrace {
+    /// Name declared by the brace's header, `None` if anonymous
+    name: Option<String>,
+    is_package: bool,
+    /// Index of the brace in the token list
+    token: usize,
+}
+
+/// Analyze the tokens before `position` in `text`
+pub(super) fn analyze(text: &str, position: Position) -> CompletionSite {
+    let cursor = (position.line, position.character);
+    let tokens = tokenize(text);
+    let mut before: Vec<&Token<'_>> = tokens
+        .iter()
+        .take_while(|token| (token.end.line, token.end.character) <= cursor)
+        .collect();
+
+    // The identifier being typed isn't part of the context
+    if before
+        .last()
+        .is_some_and(|token| token.kind == TokenKind::Word && token.end == position)
+    {
+        before.pop();
+    }
+
+    let statement_start = before
+        .iter()
+        .rposition(|token| {
+            matches!(
+                token.kind,
+                TokenKind::LBrace | TokenKind::RBrace | TokenKind::Semi
+            )
+        })
+        .map_or(0, |idx| idx + 1);
+    let statement = &before[statement_start..];
+
+    let open = open_braces(&before);
+    CompletionSite {
+        context: context_of(statement),
+        declared_name: declared_name(statement),
+        at_statement_start: statement.is_empty(),
+        scope: scope_of(&open),
+        import_site: import_site(text, &tokens, &open),
+    }
+}
+
+fn context_of(statement: &[&Token<'_>]) -> CompletionContext {
+    if let Some(connector_end) = connector_end(statement) {
+        return connector_end;
+    }
+    if let Some(member_access) = member_access(statement) {
+        return member_access;
+    }
+    if let Some(qualified_name) = qualified_name(statement) {
+        return qualified_name;
+    }
+
+    let mut end = statement.len();
+
+    // In a list like `:> A, B, `, the operator is before the first entry
+    if statement.last().is_some_and(|token| token.text == ",") {
+        while end > 0
+            && (statement[end - 1].kind == TokenKind::Word
+                || matches!(statement[end - 1].text, "," | "::"))
+        {
+            end -= 1;
+        }
+    }
+    let head = &statement[..end];
+    let texts: Vec<&str> = head.iter().map(|token| token.text).collect();
+    let kind = declared_kind(head);
+
+    match texts.as_slice() {
+        [.., ":>", ">"] | [.., "redefines"] => CompletionContext::Redefines { kind },
+        [.., ":>"] | [.., "specializes"] | [.., "subsets"] => {
+            CompletionContext::Specializes { kind }
+        }
+        [.., ":"] | [.., "defined" | "typed", "by"] => CompletionContext::TypedBy,
+        _ => CompletionContext::General,
+    }
+}
+
+/// The second end of `connect a.b to c.` being typed, if the cursor is there
+fn connector_end(statement: &[&Token<'_>]) -> Option<CompletionContext> {
+    let connect = statement
+        .iter()
+        .rposition(|token| token.text == "connect")?;
+    let ends = &statement[connect + 1..];
+    let to = ends.iter().position(|token| token.text == "to")?;
+
+    let first_end = feature_chain(&ends[..to], false)?;
+    let prefix = feature_chain(&ends[to + 1..], true)?;
+    Some(CompletionContext::ConnectorEnd { first_end, prefix })
+}
+
+/// The feature chain ending the statement with a `.`, like `driver.` in
+/// `perform driver.`
+fn member_access(statement: &[&Token<'_>]) -> Option<CompletionContext> {
+    if statement.last()?.text != "." {
+        return None;
+    }
+    let start = chain_start(statement, &[".", "::"]);
+    let chain = feature_chain(&statement[start..], true)?;
+    // A number like `1.` isn't a feature
+    let named = chain
+        .first()
+        .is_some_and(|name| !name.starts_with(|c: char| c.is_ascii_digit()));
+    named.then_some(CompletionContext::MemberAccess { chain })
+}
+
+/// The qualified name prefix ending the statement with a `::`, like `ISQ::`
+/// in `attribute mass : ISQ::`
+fn qualified_name(statement: &[&Token<'_>]) -> Option<CompletionContext> {
+    if statement.last()?.text != "::" {
+        return None;
+    }
+    let start = chain_start(statement, &["::"]);
+    let prefix = feature_chain(&statement[start..], true)?;
+    Some(CompletionContext::QualifiedName {
+        prefix,
+        within: Box::new(context_of(&statement[..start])),
+    })
+}
+
+/// Where the names and `separators` alternating at the end of `statement` begin
+fn chain_start(statement: &[&Token<'_>], separators: &[&str]) -> usize {
+    let mut start = statement.len();
+    while start > 0 {
+        let token = statement[start - 1];
+        let expects_name = (statement.len() - start) % 2 == 1;
+        let continues = if expects_name {
+            token.kind == TokenKind::Word
+        } else {
+            separators.contains(&token.text)
+        };
+        if !continues {
+            break;
+        }
+        start -= 1;
+    }
+    start
+}
+
+/// The names of a feature chain like `engine.fuelIn`, separated by `.` or
+/// `::`; an `open` chain ends with a separator (`engine.`) or is empty
+pub(super) fn feature_chain(tokens: &[&Token<'_>], open: bool) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+    for (idx, token) in tokens.iter().enumerate() {
+        let expects_name = idx % 2 == 0;
+        match token.kind {
+            TokenKind::Word if expects_name => names.push(unquote(token.text).to_string()),
+            TokenKind::Punct if !expects_name && matches!(token.text, "." | "::") => {}
+            _ => return None,
+        }
+    }
+    let complete = tokens.len() % 2 == 1;
+    (complete != open).then_some(names)
+}
+
+/// Symbol kind declared by a statement, from its leading keywords
+fn declared_kind(statement: &[&Token<'_>]) -> Option<SymbolKind> {
+    let words: Vec<&str> = statement
+        .iter()
+        .take_while(|token| token.kind == TokenKind::Word)
+        .map(|token| token.text)
+        .collect();
+    let is_definition = words.contains(&"def");
+
+    words.iter().find_map(|&word| {
+        let (definition, usage) = match word {
+            "part" => (SymbolKind::PartDef, SymbolKind::PartUsage),
+            "item" => (SymbolKind::ItemDef, SymbolKind::ItemUsage),
+            "action" => (SymbolKind::ActionDef, SymbolKind::ActionUsage),
+            "port" => (SymbolKind::PortDef, SymbolKind::PortUsage),
+            "attribute" => (SymbolKind::AttributeDef, SymbolKind::AttributeUsage),
+            "connection" => (SymbolKind::ConnectionDef, SymbolKind::ConnectionUsage),
+            "interface" => (SymbolKind::InterfaceDef, SymbolKind::InterfaceUsage),
+            "allocation" => (SymbolKind::AllocationDef, SymbolKind::AllocationUsage),
+            "requirement" => (SymbolKind::RequirementDef, SymbolKind::RequirementUsage),
+            "constraint" => (SymbolKind::ConstraintDef, SymbolKind::ConstraintUsage),
+            "state" => (SymbolKind::StateDef, SymbolKind::StateUsage),
+            "calc" => (SymbolKind::CalculationDef, SymbolKind::CalculationUsage),
+            "enum" if is_definition => (SymbolKind::EnumerationDef, SymbolKind::AttributeUsage),
+            "use" if is_definition => (SymbolKind::UseCaseDef, SymbolKind::Other),
+            "analysis" if is_definition => (SymbolKind::AnalysisCaseDef, SymbolKind::Other),
+            "concern" if is_definition => (SymbolKind::ConcernDef, SymbolKind::Other),
+            "view" if is_definition => (SymbolKind::ViewDef, SymbolKind::Other),
+            "viewpoint" if is_definition => (SymbolKind::ViewpointDef, SymbolKind::Other),
+            "rendering" if is_definition => (SymbolKind::RenderingDef, SymbolKind::Other),
+            "occurrence" if !is_definition => (SymbolKind::Other, SymbolKind::OccurrenceUsage),
+            "ref" if !is_definition => (SymbolKind::Other, SymbolKind::ReferenceUsage),
+            _ => return None,
+        };
+        Some(if is_definition { definition } else { usage })
+    })
+}
+
+/// First word after the SysML or KerML keywords and modifiers
+pub(super) fn declared_name(statement: &[&Token<'_>]) -> Option<String> {
+    const KEYWORDS: &[&str] = &[
+        "abstract",
+        "action",
+        "allocation",
+        "analysis",
+        "assoc",
+        "attribute",
+        "behavior",
+        "binding",
+        "calc",
+        "case",
+        "class",
+        "classifier",
+        "composite",
+        "concern",
+        "connection",
+        "connector",
+        "const",
+        "constraint",
+        "datatype",
+        "def",
+        "derived",
+        "end",
+        "enum",
+        "expr",
+        "feature",
+        "function",
+        "in",
+        "individual",
+        "inout",
+        "interaction",
+        "interface",
+        "item",
+        "library",
+        "member",
+        "metaclass",
+        "namespace",
+        "occurrence",
+        "out",
+        "package",
+        "part",
+        "perform",
+        "port",
+        "portion",
+        "predicate",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "ref",
+        "rendering",
+        "requirement",
+        "standard",
+        "state",
+        "step",
+        "struct",
+        "succession",
+        "type",
+        "use",
+        "var",
+        "variation",
+        "view",
+        "viewpoint",
+    ];
+    statement
+        .iter()
+        .take_while(|token| token.kind == TokenKind::Word)
+        .find(|token| !KEYWORDS.contains(&token.text))
+        .map(|token| unquote(token.text).to_string())
+}
+
+/// The braces enclosing the cursor, outermost first
+fn open_braces(before: &[&Token<'_>]) -> Vec<OpenBrace> {
+    let mut open = Vec::new();
+    let mut statement_start = 0;
+    for (idx, token) in before.iter().enumerate() {
+        match token.kind {
+            TokenKind::LBrace => {
+                let header = &before[statement_start..idx];
+                open.push(OpenBrace {
+                    name: declared_name(header),
+                    is_package: header
+                        .iter()
+                        .any(|token| matches!(token.text, "package" | "namespace")),
+                    token: idx,
+                });
+                statement_start = idx + 1;
+            }
+            TokenKind::RBrace => {
+                open.pop();
+                statement_start = idx + 1;
+            }
+            TokenKind::Semi => statement_start = idx + 1,
+            _ => {}
+        }
+    }
+    open
+}
+
+/// Qualified name of the innermost named namespace whose body contains the cursor
+///
+/// Built from the names declared by the headers of the open braces, so it
+/// doesn't depend on the (possibly incomplete) document having parsed.
+fn scope_of(open: &[OpenBrace]) -> String {
+    // Anonymous elements can't be named, so stop at the first one
+    open.iter()
+        .map_while(|brace| brace.name.as_deref())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Find where an import goes in the innermost package enclosing the cursor
+fn import_site(text: &str, tokens: &[Token<'_>], open: &[OpenBrace]) -> ImportSite {
+    let named = open
+        .iter()
+        .position(|brace| brace.name.is_none())
+        .unwrap_or(open.len());
+    let package = open[..named].iter().rposition(|brace| brace.is_package);
+
+    let (body_start, brace) = match package {
+        Some(idx) => {
+            let brace = &tokens[open[idx].token];
+            (open[idx].token + 1, Some(brace))
+        }
+        None => (0, None),
+    };
+
+    // Walk the package body's own statements
+    let mut depth = 0usize;
+    let mut statement_start = body_start;
+    let mut first_member: Option<&Token<'_>> = None;
+    let mut last_import: Option<&Token<'_>> = None;
+    for (idx, token) in tokens.iter().enumerate().skip(body_start) {
+        match token.kind {
+            TokenKind::LBrace => depth += 1,
+            TokenKind::RBrace if depth == 0 => break,
+            TokenKind::RBrace => {
+                depth -= 1;
+                if depth == 0 {
+                    statement_start = idx + 1;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if depth > 0 {
+            continue;
+        }
+        if idx == statement_start {
+            first_member.get_or_insert(token);
+        }
+        if token.kind == TokenKind::Semi {
+            let is_import = tokens[statement_start..idx]
+                .iter()
+                .find(|token| !matches!(token.text, "private" | "public" | "protected"))
+                .is_some_and(|token| token.text == "import");
+            if is_import {
+                last_import = Some(token);
+            }
+            statement_start = idx + 1;
+        }
+    }
+
+    let line_indent = |line: u32| -> String {
+        text.lines()
+            .nth(line as usize)
+            .map(|line| line.chars().take_while(|c| c.is_whitespace()).collect())
+            .unwrap_or_default()
+    };
+    let indent = match (first_member, brace) {
+        (Some(member), Some(brace)) if member.start.line > brace.start.line => {
+            line_indent(member.start.line)
+        }
+        (_, Some(brace)) => format!("{}    ", line_indent(brace.start.line)),
+        (_, None) => String::new(),
+    };
+    let position = match (last_import, brace) {
+        (Some(import), _) => Position::new(import.end.line + 1, 0),
+        (None, Some(brace)) => Position::new(brace.end.line + 1, 0),
+        (None, None) => Position::new(0, 0),
+    };
+
+    ImportSite {
+        package: package.map_or_else(String::new, |idx| scope_of(&open[..=idx])),
+        position,
+        indent,
+    }
+}
+
+/// Complete the statement at the cursor so an incomplete document parses
+///
+/// A placeholder name is inserted if nothing has been typed yet, and the
+/// statement is terminated unless something already follows it.
+pub(super) fn repair(text: &str, position: Position) -> Option<String> {
+    let offset = position_to_byte_offset(text, position).ok()?;
+    let (head, tail) = text.split_at(offset);
+
+    let typing = head
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let placeholder = if typing { "" } else { "completionPlaceholder" };
+    let terminated = tail.trim_start().starts_with([';', '{', ',', '[', '=']);
+    let terminator = if terminated { "" } else { ";" };
+
+    Some(format!("{head}{placeholder}{terminator}{tail}"))
+}
+
+/// Whether `symbol` is reachable by its simple name from `scope`
+pub(super) fn is_visible(index: &SymbolIndex, scope: &str, symbol: &HirSymbol) -> bool {
+    index
+        .resolver_for_scope(scope)
+        .resolve(&symbol.name)
+        .symbol()
+        .is_some_and(|found| found.qualified_name == symbol.qualified_name)
+}
+
+/// Features owned by the supertypes and types of `scope`, transitively
+pub(super) fn inherited_features<'a>(index: &'a SymbolIndex, scope: &str) -> Vec<&'a HirSymbol> {
+    let Some(owner) = index.lookup_qualified(scope) else {
+        return Vec::new();
+    };
+
+    let mut visited = HashSet::new();
+    let mut pending = vec![owner];
+    let mut supertypes: Vec<&HirSymbol> = Vec::new();
+    while let Some(symbol) = pending.pop() {
+        for type_ref in symbol.type_refs.iter().flat_map(|trk| trk.as_refs()) {
+            if !matches!(
+                type_ref.kind,
+                RefKind::TypedBy | RefKind::Specializes | RefKind::Subsets | RefKind::Redefines
+            ) {
+                continue;
+            }
+            let Some(target) = type_ref
+                .resolved_target
+                .as_deref()
+                .and_then(|target| index.lookup_qualified(target))
+            else {
+                continue;
+            };
+            if visited.insert(target.qualified_name.clone()) {
+                supertypes.push(target);
+                pending.push(target);
+            }
+        }
+    }
+
+    supertypes
+        .into_iter()
+        .flat_map(|supertype| owned_features(index, supertype))
+        .collect()
+}
+
+/// The features `owner` declares, found among the symbols of its file
+pub(super) fn owned_features<'a>(index: &'a SymbolIndex, owner: &HirSymbol) -> Vec<&'a HirSymbol> {
+    index
+        .symbols_in_file(owner.file)
+        .into_iter()
+        .filter(|sym| sym.kind.is_usage())
+        .filter(|sym| {
+            sym.qualified_name
+                .rsplit_once("::")
+                .is_some_and(|(parent, _)| parent == owner.qualified_name.as_ref())
+        })
+        .collect()
+}
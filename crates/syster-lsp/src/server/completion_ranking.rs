@@ -0,0 +1,204 @@
+//! Ranking of completion candidates
+//!
+//! With the stdlib loaded, the hundreds of library names crowd out the few
+//! the user most likely wants. Each candidate is scored, most significant
+//! first, by:
+//!
+//! - scope proximity: declared in the current body (or inherited into it),
+//!   elsewhere in the current package, elsewhere in the project (imports and
+//!   keywords), or in the stdlib
+//! - prefix match quality: how well the label matches the word being typed
+//! - recent usage: whether the name is written near the cursor, elsewhere in
+//!   the file, or not at all
+//!
+//! The scores prefix each item's own `sortText`, so candidates scoring the
+//! same keep the order they had.
+
+use super::LspServer;
+use super::completion_context::{CompletionSite, inherited_features};
+use async_lsp::lsp_types::{CompletionItem, Position};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use syster::hir::HirSymbol;
+
+/// Lines around the cursor within which a use of a name counts as recent
+const RECENT_LINES: u32 = 20;
+
+/// How close to the cursor a candidate is declared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Proximity {
+    Body,
+    Package,
+    Project,
+    Stdlib,
+}
+
+impl LspServer {
+    /// Prefix the `sortText` of each item with its score
+    pub(super) fn rank_completions(
+        &mut self,
+        path: &Path,
+        position: Position,
+        site: &CompletionSite,
+        items: &mut [CompletionItem],
+    ) {
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+
+        let labels: HashSet<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        let inherited: HashSet<&str> = inherited_features(index, &site.scope)
+            .into_iter()
+            .map(|sym| sym.name.as_ref())
+            .collect();
+        let candidates: Vec<(String, Option<PathBuf>, Proximity)> = index
+            .all_symbols()
+            .filter(|sym| labels.contains(sym.name.as_ref()))
+            .map(|sym| {
+                let proximity = if inherited.contains(sym.name.as_ref()) {
+                    Proximity::Body
+                } else {
+                    proximity_of(sym, &site.scope, &site.import_site.package)
+                };
+                let path = analysis.get_file_path(sym.file).map(PathBuf::from);
+                (sym.name.to_string(), path, proximity)
+            })
+            .collect();
+
+        let mut proximity: HashMap<String, Proximity> = HashMap::new();
+        for (name, path, rank) in candidates {
+            let rank = match path {
+                Some(path) if self.is_stdlib_path(&path) => Proximity::Stdlib,
+                _ => rank,
+            };
+            proximity
+                .entry(name)
+                .and_modify(|best| *best = (*best).min(rank))
+                .or_insert(rank);
+        }
+
+        let Some(text) = self.document_texts.get(path) else {
+            return;
+        };
+        let prefix = word_before(text, position);
+        let uses = uses_by_line(text, position);
+        for item in items {
+            let proximity = proximity
+                .get(&item.label)
+                .copied()
+                .unwrap_or(Proximity::Project);
+            let recency = match uses.get(item.label.as_str()) {
+                Some(distance) if *distance <= RECENT_LINES => 0,
+                Some(_) => 1,
+                None => 2,
+            };
+            let own = item.sort_text.take().unwrap_or_else(|| item.label.clone());
+            item.sort_text = Some(format!(
+                "{}{}{recency}_{own}",
+                proximity as u8,
+                match_quality(&item.label, prefix)
+            ));
+        }
+    }
+}
+
+/// Proximity of a project symbol to the body at `scope`, in `package`
+pub(super) fn proximity_of(sym: &HirSymbol, scope: &str, package: &str) -> Proximity {
+    let owner = sym
+        .qualified_name
+        .rsplit_once("::")
+        .map_or("", |(owner, _)| owner);
+    let within =
+        |outer: &str, inner: &str| inner == outer || inner.starts_with(&format!("{outer}::"));
+    // The body and the elements enclosing it, up to the package
+    let in_body = within(owner, scope) && owner.len() > package.len();
+    if !scope.is_empty() && in_body {
+        Proximity::Body
+    } else if !package.is_empty() && within(package, owner) {
+        Proximity::Package
+    } else {
+        Proximity::Project
+    }
+}
+
+/// How well `label` matches the typed `prefix`: 0 for a prefix, 1 for a
+/// prefix of another case, 2 for camel-case initials or a substring, and 3
+/// otherwise
+pub(super) fn match_quality(label: &str, prefix: &str) -> u8 {
+    let (lower_label, lower_prefix) = (label.to_lowercase(), prefix.to_lowercase());
+    if label.starts_with(prefix) {
+        0
+    } else if lower_label.starts_with(&lower_prefix) {
+        1
+    } else if initials(label).starts_with(&lower_prefix) || lower_label.contains(&lower_prefix) {
+        2
+    } else {
+        3
+    }
+}
+
+/// Lowercase initials of the words of a camel-case name: `dpo` for
+/// `drivePowerOutput`
+fn initials(label: &str) -> String {
+    let mut initials = String::new();
+    let mut previous: Option<char> = None;
+    for ch in label.chars() {
+        let starts_word = match previous {
+            None => true,
+            Some(previous) => {
+                (ch.is_uppercase() && !previous.is_uppercase()) || (previous == '_' && ch != '_')
+            }
+        };
+        if starts_word && ch != '_' {
+            initials.extend(ch.to_lowercase());
+        }
+        previous = Some(ch);
+    }
+    initials
+}
+
+/// The part of a name typed before the cursor
+fn word_before(text: &str, position: Position) -> &str {
+    let Some(line) = text.lines().nth(position.line as usize) else {
+        return "";
+    };
+    let end = line
+        .char_indices()
+        .nth(position.character as usize)
+        .map_or(line.len(), |(idx, _)| idx);
+    let before = &line[..end];
+    let start = before
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |idx| {
+            idx + before[idx..].chars().next().map_or(1, char::len_utf8)
+        });
+    &before[start..]
+}
+
+/// Names written in the text, with the line distance of the use nearest the
+/// cursor, leaving out the word being typed
+fn uses_by_line(text: &str, position: Position) -> HashMap<&str, u32> {
+    let mut uses: HashMap<&str, u32> = HashMap::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_idx = line_idx as u32;
+        let mut start = None;
+        for (idx, ch) in line.char_indices().chain([(line.len(), ' ')]) {
+            let is_word = ch.is_alphanumeric() || ch == '_';
+            match (start, is_word) {
+                (None, true) => start = Some(idx),
+                (Some(word_start), false) => {
+                    start = None;
+                    let column = line[..idx].chars().count() as u32;
+                    if line_idx == position.line && column == position.character {
+                        continue;
+                    }
+                    let distance = line_idx.abs_diff(position.line);
+                    uses.entry(&line[word_start..idx])
+                        .and_modify(|nearest| *nearest = (*nearest).min(distance))
+                        .or_insert(distance);
+                }
+                _ => {}
+            }
+        }
+    }
+    uses
+}
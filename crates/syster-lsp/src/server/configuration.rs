@@ -0,0 +1,213 @@
+//! Live server configuration
+//!
+//! The settings read from `initializationOptions` can be changed later with
+//! `workspace/didChangeConfiguration`, without restarting the server. The
+//! notification carries the whole settings object, either as is or under a
+//! `syster` section; options it leaves out go back to their defaults.
+//!
+//! A change to the stdlib or the exclude globs reloads the workspace, keeping
+//! the documents open in the editor. A change to the reported diagnostics
+//! republishes those of the open documents and validates the project again.
+//! Everything else applies to the next request. The project validation delay
+//! is only read at startup.
+
+use super::LspServer;
+use super::watched_files::WatchedFilesUpdate;
+use async_lsp::lsp_types::Url;
+use std::path::PathBuf;
+use std::time::Duration;
+use syster::core::constants::{OPT_STDLIB_ENABLED, OPT_STDLIB_PATH};
+
+/// Section of the client settings holding the server's options
+pub const SETTINGS_SECTION: &str = "syster";
+
+/// Initialization option controlling whether references skip stdlib files
+pub const OPT_REFERENCES_EXCLUDE_STDLIB: &str = "referencesExcludeStdlib";
+
+/// Initialization option listing gitignore-style globs excluded from workspace indexing
+pub const OPT_EXCLUDE_GLOBS: &str = "excludeGlobs";
+
+/// Initialization option limiting how many levels of transitive relationships hovers show
+pub const OPT_HOVER_MAX_DEPTH: &str = "hoverMaxDepth";
+
+/// Initialization option enabling naming convention diagnostics
+pub const OPT_NAMING_CONVENTIONS: &str = "namingConventions";
+
+/// Initialization option enabling diagnostics for definitions nothing outside
+/// their file refers to
+pub const OPT_UNUSED_ELEMENTS: &str = "unusedElements";
+
+/// Initialization option making rename include the features that redefine or
+/// subset the renamed one under the same name
+pub const OPT_RENAME_REDEFINITIONS: &str = "renameRedefinitions";
+
+/// Initialization option listing qualified names of elements (and their
+/// members) used from outside the workspace, never reported as unused
+pub const OPT_ENTRY_POINTS: &str = "entryPoints";
+
+/// Levels of transitive relationships shown in hovers by default
+pub const DEFAULT_HOVER_MAX_DEPTH: usize = 5;
+
+/// Initialization option setting how long edits must pause before the whole
+/// project is validated (0 turns project validation off)
+pub const OPT_VALIDATION_DELAY_MS: &str = "validationDelayMs";
+
+/// Pause after the last edit before the whole project is validated by default
+pub const DEFAULT_VALIDATION_DELAY_MS: u64 = 1000;
+
+/// The options a client can set
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerSettings {
+    pub stdlib_enabled: bool,
+    pub stdlib_path: Option<PathBuf>,
+    pub exclude_globs: Vec<String>,
+    pub references_exclude_stdlib: bool,
+    pub hover_max_depth: usize,
+    pub naming_conventions: bool,
+    pub unused_elements: bool,
+    pub entry_points: Vec<String>,
+    pub rename_redefinitions: bool,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self::from_options(None)
+    }
+}
+
+impl ServerSettings {
+    /// Settings from initialization options, defaulting the ones not given
+    pub fn from_options(options: Option<&serde_json::Value>) -> Self {
+        let option = |name: &str| options.and_then(|opts| opts.get(name));
+        let flag = |name: &str| option(name).and_then(|v| v.as_bool()).unwrap_or(false);
+        Self {
+            stdlib_enabled: option(OPT_STDLIB_ENABLED)
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+            stdlib_path: option(OPT_STDLIB_PATH)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(PathBuf::from),
+            exclude_globs: strings(option(OPT_EXCLUDE_GLOBS)),
+            references_exclude_stdlib: flag(OPT_REFERENCES_EXCLUDE_STDLIB),
+            hover_max_depth: option(OPT_HOVER_MAX_DEPTH)
+                .and_then(|v| v.as_u64())
+                .map_or(DEFAULT_HOVER_MAX_DEPTH, |depth| depth as usize),
+            naming_conventions: flag(OPT_NAMING_CONVENTIONS),
+            unused_elements: flag(OPT_UNUSED_ELEMENTS),
+            entry_points: strings(option(OPT_ENTRY_POINTS))
+                .into_iter()
+                .filter(|name| !name.is_empty())
+                .collect(),
+            rename_redefinitions: flag(OPT_RENAME_REDEFINITIONS),
+        }
+    }
+
+    /// How long edits must pause before the project is validated, or `None`
+    /// if project validation is off.
+    ///
+    /// Only initialization options set it, since the debouncer is started once.
+    pub fn validation_delay(options: Option<&serde_json::Value>) -> Option<Duration> {
+        let delay = options
+            .and_then(|opts| opts.get(OPT_VALIDATION_DELAY_MS))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_VALIDATION_DELAY_MS);
+        (delay > 0).then(|| Duration::from_millis(delay))
+    }
+
+    /// Settings from a `workspace/didChangeConfiguration` payload, or `None`
+    /// if it has none of the server's options
+    pub fn from_change(settings: &serde_json::Value) -> Option<Self> {
+        const OPTIONS: &[&str] = &[
+            OPT_STDLIB_ENABLED,
+            OPT_STDLIB_PATH,
+            OPT_EXCLUDE_GLOBS,
+            OPT_REFERENCES_EXCLUDE_STDLIB,
+            OPT_HOVER_MAX_DEPTH,
+            OPT_NAMING_CONVENTIONS,
+            OPT_UNUSED_ELEMENTS,
+            OPT_ENTRY_POINTS,
+            OPT_RENAME_REDEFINITIONS,
+        ];
+        let options = settings.get(SETTINGS_SECTION).unwrap_or(settings);
+        let object = options.as_object()?;
+        OPTIONS
+            .iter()
+            .any(|option| object.contains_key(*option))
+            .then(|| Self::from_options(Some(options)))
+    }
+}
+
+impl LspServer {
+    /// The options currently in effect
+    pub fn settings(&self) -> ServerSettings {
+        let (stdlib_enabled, stdlib_path) = self.configured_stdlib.clone();
+        ServerSettings {
+            stdlib_enabled,
+            stdlib_path,
+            exclude_globs: self.exclude_globs.clone(),
+            references_exclude_stdlib: self.references_exclude_stdlib,
+            hover_max_depth: self.hover_max_depth,
+            naming_conventions: self.naming_conventions,
+            unused_elements: self.unused_elements,
+            entry_points: self.entry_points.clone(),
+            rename_redefinitions: self.rename_redefinitions,
+        }
+    }
+
+    /// Switch to new settings, redoing the work they affect.
+    ///
+    /// Before the workspace is loaded this only records them. Returns the
+    /// documents whose diagnostics should be republished.
+    pub fn apply_settings(&mut self, settings: ServerSettings) -> WatchedFilesUpdate {
+        let old = self.settings();
+        let reload = (settings.stdlib_enabled, &settings.stdlib_path)
+            != (old.stdlib_enabled, &old.stdlib_path)
+            || settings.exclude_globs != old.exclude_globs;
+        let diagnostics = reload
+            || settings.naming_conventions != old.naming_conventions
+            || settings.unused_elements != old.unused_elements
+            || settings.entry_points != old.entry_points;
+
+        self.configured_stdlib = (settings.stdlib_enabled, settings.stdlib_path);
+        self.set_exclude_globs(settings.exclude_globs);
+        self.set_references_exclude_stdlib(settings.references_exclude_stdlib);
+        self.set_hover_max_depth(settings.hover_max_depth);
+        self.set_naming_conventions(settings.naming_conventions);
+        self.set_unused_elements(settings.unused_elements);
+        self.set_entry_points(settings.entry_points);
+        self.set_rename_redefinitions(settings.rename_redefinitions);
+
+        let mut update = WatchedFilesUpdate::default();
+        if !self.is_workspace_loaded() {
+            return update;
+        }
+        if reload {
+            tracing::info!("Configuration changed the library or excludes, reloading workspace");
+            self.reload_workspace();
+        }
+        if diagnostics {
+            update.changed = self
+                .document_versions
+                .keys()
+                .filter_map(|path| Url::from_file_path(path).ok())
+                .collect();
+            update.changed.sort();
+        }
+        update
+    }
+}
+
+/// The strings of an array option, skipping other values
+fn strings(value: Option<&serde_json::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
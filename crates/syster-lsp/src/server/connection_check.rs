@@ -0,0 +1,131 @@
+//! Port conjugation checks for connections
+//!
+//! A connection between the ports of two nested parts has to pair a port with
+//! its conjugate (`FuelPort` with `~FuelPort`), so what one end sends the
+//! other receives. A port of the enclosing element instead delegates to a
+//! nested port of the same direction. The AST doesn't keep `connect` ends, so
+//! this scans the document text for `connect <end> to <end>` and resolves the
+//! ends like connector end completion does.
+
+use async_lsp::lsp_types::{DiagnosticSeverity, Range};
+use syster::hir::{HirSymbol, SymbolIndex};
+
+use super::completion_context::{declared_name, feature_chain};
+use super::connector_ends::{EndType, conforms, end_type, is_compatible, is_port, resolve_chain};
+use super::tokens::{Token, TokenKind, tokenize};
+
+/// Diagnostic code for a connection between ports that don't fit together
+pub const PORT_CONJUGATION_CODE: &str = "port-conjugation";
+
+/// A connection whose port ends don't fit together
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionIssue {
+    /// Span of the `connect` clause
+    pub range: Range,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+/// Check every `connect` between ports in `text`
+pub fn check_connections(
+    index: &SymbolIndex,
+    text: &str,
+    is_conjugated: &dyn Fn(&HirSymbol) -> bool,
+) -> Vec<ConnectionIssue> {
+    let tokens = tokenize(text);
+    let mut issues = Vec::new();
+    // Names declared by the open braces, `None` for anonymous ones
+    let mut open: Vec<Option<String>> = Vec::new();
+    let mut statement_start = 0;
+
+    for (idx, token) in tokens.iter().enumerate() {
+        if !matches!(
+            token.kind,
+            TokenKind::LBrace | TokenKind::RBrace | TokenKind::Semi
+        ) {
+            continue;
+        }
+        let statement: Vec<&Token<'_>> = tokens[statement_start..idx].iter().collect();
+        statement_start = idx + 1;
+        if token.kind == TokenKind::RBrace {
+            open.pop();
+            continue;
+        }
+
+        let scope = open
+            .iter()
+            .map_while(|name| name.as_deref())
+            .collect::<Vec<_>>()
+            .join("::");
+        issues.extend(check_connect(index, &scope, &statement, is_conjugated));
+        if token.kind == TokenKind::LBrace {
+            open.push(declared_name(&statement));
+        }
+    }
+    issues
+}
+
+/// Check the `connect` clause of a statement, if it has one between two ports
+fn check_connect(
+    index: &SymbolIndex,
+    scope: &str,
+    statement: &[&Token<'_>],
+    is_conjugated: &dyn Fn(&HirSymbol) -> bool,
+) -> Option<ConnectionIssue> {
+    let connect = statement.iter().position(|token| token.text == "connect")?;
+    let ends = &statement[connect + 1..];
+    let to = ends.iter().position(|token| token.text == "to")?;
+    let first_chain = feature_chain(&ends[..to], false)?;
+    let second_chain = feature_chain(&ends[to + 1..], false)?;
+
+    let end = |chain: &[String]| -> Option<(String, EndType)> {
+        let feature = resolve_chain(index, scope, chain)?;
+        let end_type = end_type(index, feature, is_conjugated)?;
+        is_port(index, &end_type).then(|| (chain.join("."), end_type))
+    };
+    let (first_name, first) = end(&first_chain)?;
+    let (second_name, second) = end(&second_chain)?;
+
+    // One end on the enclosing element's boundary, the other inside it
+    let delegation = (first_chain.len() == 1) != (second_chain.len() == 1);
+    if is_compatible(index, &first, &second, delegation) {
+        return None;
+    }
+
+    let type_name = |end: &EndType| {
+        let name = end.definition.rsplit("::").next().unwrap_or_default();
+        if end.conjugated {
+            format!("~{name}")
+        } else {
+            name.to_string()
+        }
+    };
+    let pair = format!(
+        "'{first_name}' ({}) and '{second_name}' ({})",
+        type_name(&first),
+        type_name(&second)
+    );
+    let (severity, message) = if !conforms(index, &first, &second) {
+        (
+            DiagnosticSeverity::ERROR,
+            format!("{pair} have incompatible port types"),
+        )
+    } else if delegation {
+        (
+            DiagnosticSeverity::WARNING,
+            format!("{pair} have opposite directions; a delegating connection keeps the direction"),
+        )
+    } else {
+        (
+            DiagnosticSeverity::WARNING,
+            format!("{pair} have the same direction; connect a port to its conjugate"),
+        )
+    };
+
+    let last = statement.last()?;
+    Some(ConnectionIssue {
+        range: Range::new(statement[connect].start, last.end),
+        severity,
+        message,
+    })
+}
@@ -0,0 +1,277 @@
+//! Type-aware completion of connector ends
+//!
+//! In `connect engine.fuelIn to `, only features whose types conform to the
+//! type of the first end are useful as the second end. Ports also have to
+//! agree on conjugation: two ports of nested parts connect a port to its
+//! conjugate (`FuelPort` to `~FuelPort`), while a port of the enclosing
+//! element delegates to a nested port of the same direction.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_lsp::lsp_types::{CompletionItem, CompletionItemKind, Documentation, Position};
+use syster::hir::{HirSymbol, RefKind, SymbolIndex, SymbolKind};
+use syster::ide::Analysis;
+
+use super::completion_context;
+use super::helpers::position_to_byte_offset;
+
+/// The type of a connector end
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct EndType {
+    /// Qualified name of the definition typing the end
+    pub(super) definition: Arc<str>,
+    /// Typed by the conjugate of a port definition (`~FuelPort`)
+    pub(super) conjugated: bool,
+}
+
+/// Resolve a feature chain like `engine.fuelIn` from `scope`
+///
+/// The first name is resolved from `scope` and each following one from the
+/// feature before it, where only that feature's members count.
+pub(super) fn resolve_chain<'a>(
+    index: &'a SymbolIndex,
+    scope: &str,
+    chain: &[String],
+) -> Option<&'a HirSymbol> {
+    let (first, rest) = chain.split_first()?;
+    let mut feature = resolve_from(index, scope, first)?;
+    for name in rest {
+        let member = resolve_from(index, &feature.qualified_name, name)?;
+        // A name found in a namespace enclosing the feature isn't its member
+        let (owner, _) = member.qualified_name.rsplit_once("::")?;
+        if feature.qualified_name.starts_with(&format!("{owner}::")) {
+            return None;
+        }
+        feature = member;
+    }
+    Some(feature)
+}
+
+/// The symbol `name` refers to from `scope`
+fn resolve_from<'a>(index: &'a SymbolIndex, scope: &str, name: &str) -> Option<&'a HirSymbol> {
+    index
+        .resolver_for_scope(scope)
+        .resolve(name)
+        .symbol()
+        .and_then(|sym| index.lookup_qualified(&sym.qualified_name))
+}
+
+/// Features owned by `scope` and inherited from its types and supertypes
+pub(super) fn features_of_scope<'a>(index: &'a SymbolIndex, scope: &str) -> Vec<&'a HirSymbol> {
+    let mut features: Vec<&HirSymbol> = index
+        .lookup_qualified(scope)
+        .map(|owner| completion_context::owned_features(index, owner))
+        .unwrap_or_default();
+    features.retain(|sym| !sym.name.starts_with('<'));
+    features.sort_by_key(|sym| (sym.start_line, sym.start_col));
+
+    // Owned features shadow the inherited ones they redefine
+    for inherited in completion_context::inherited_features(index, scope) {
+        if !features.iter().any(|sym| sym.name == inherited.name) {
+            features.push(inherited);
+        }
+    }
+    features
+}
+
+/// Whether a feature is typed by the conjugate of a port definition
+/// (`: ~FuelPort`), read from the feature's file among `texts`
+pub(super) fn is_conjugated(
+    analysis: &Analysis<'_>,
+    texts: &HashMap<PathBuf, String>,
+    feature: &HirSymbol,
+) -> bool {
+    analysis
+        .get_file_path(feature.file)
+        .and_then(|path| texts.get(&PathBuf::from(path)))
+        .is_some_and(|text| typed_by_conjugate(text, feature))
+}
+
+/// Whether the typing reference of `feature` in `text` is conjugated. The HIR
+/// records the reference but not whether it is conjugated, so only the `~`
+/// at the start of the reference's span, or just before it, is read.
+pub(super) fn typed_by_conjugate(text: &str, feature: &HirSymbol) -> bool {
+    feature
+        .type_refs
+        .iter()
+        .flat_map(|trk| trk.as_refs())
+        .filter(|type_ref| matches!(type_ref.kind, RefKind::TypedBy))
+        .any(|type_ref| {
+            let start = Position::new(type_ref.start_line, type_ref.start_col);
+            position_to_byte_offset(text, start)
+                .ok()
+                .filter(|&offset| offset <= text.len())
+                .is_some_and(|offset| {
+                    text[..offset].trim_end().ends_with('~') || text[offset..].starts_with('~')
+                })
+        })
+}
+
+/// The definition typing a feature, following subsetted and redefined features
+/// to theirs if it isn't typed itself
+pub(super) fn end_type(
+    index: &SymbolIndex,
+    feature: &HirSymbol,
+    is_conjugated: &dyn Fn(&HirSymbol) -> bool,
+) -> Option<EndType> {
+    let mut visited = HashSet::new();
+    let mut current = feature;
+    while visited.insert(current.qualified_name.clone()) {
+        let refs: Vec<_> = current
+            .type_refs
+            .iter()
+            .flat_map(|trk| trk.as_refs())
+            .collect();
+        let resolved = |accepts: fn(&RefKind) -> bool| {
+            refs.iter()
+                .filter(|type_ref| accepts(&type_ref.kind))
+                .find_map(|type_ref| type_ref.resolved_target.as_deref())
+                .and_then(|target| index.lookup_qualified(target))
+        };
+        if let Some(definition) = resolved(|kind| matches!(kind, RefKind::TypedBy)) {
+            return Some(EndType {
+                definition: definition.qualified_name.clone(),
+                conjugated: is_conjugated(current),
+            });
+        }
+        current = resolved(|kind| matches!(kind, RefKind::Redefines | RefKind::Subsets))?;
+    }
+    None
+}
+
+/// Whether `definition` is `other` or specializes it, directly or not
+pub(super) fn specializes(index: &SymbolIndex, definition: &str, other: &str) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending = vec![definition.to_string()];
+    while let Some(name) = pending.pop() {
+        if name == other {
+            return true;
+        }
+        if !visited.insert(name.clone()) {
+            continue;
+        }
+        let Some(symbol) = index.lookup_qualified(&name) else {
+            continue;
+        };
+        pending.extend(
+            symbol
+                .type_refs
+                .iter()
+                .flat_map(|trk| trk.as_refs())
+                .filter(|type_ref| matches!(type_ref.kind, RefKind::Specializes))
+                .filter_map(|type_ref| type_ref.resolved_target.as_deref())
+                .map(str::to_string),
+        );
+    }
+    false
+}
+
+/// Whether the types of two ends conform, one way or the other
+pub(super) fn conforms(index: &SymbolIndex, first: &EndType, second: &EndType) -> bool {
+    specializes(index, &first.definition, &second.definition)
+        || specializes(index, &second.definition, &first.definition)
+}
+
+/// Whether an end is typed by a port definition
+pub(super) fn is_port(index: &SymbolIndex, end: &EndType) -> bool {
+    index
+        .lookup_qualified(&end.definition)
+        .is_some_and(|sym| sym.kind == SymbolKind::PortDef)
+}
+
+/// Whether two ends can be connected
+///
+/// Their types have to conform. Port ends of nested parts must be conjugates
+/// of each other; with `delegation` (one end is a port of the enclosing
+/// element itself) they must have the same direction.
+pub(super) fn is_compatible(
+    index: &SymbolIndex,
+    first: &EndType,
+    second: &EndType,
+    delegation: bool,
+) -> bool {
+    if !conforms(index, first, second) {
+        return false;
+    }
+    if !(is_port(index, first) && is_port(index, second)) {
+        return true;
+    }
+    (first.conjugated == second.conjugated) == delegation
+}
+
+/// Completions for the second end of `connect <first_end> to <prefix>`
+pub(super) fn connector_end_completions(
+    index: &SymbolIndex,
+    scope: &str,
+    first_end: &[String],
+    prefix: &[String],
+    is_conjugated: &dyn Fn(&HirSymbol) -> bool,
+) -> Vec<CompletionItem> {
+    let first = resolve_chain(index, scope, first_end);
+    let first_type = first.and_then(|feature| end_type(index, feature, is_conjugated));
+
+    let owner = if prefix.is_empty() {
+        scope.to_string()
+    } else {
+        match resolve_chain(index, scope, prefix) {
+            Some(feature) => feature.qualified_name.to_string(),
+            None => return Vec::new(),
+        }
+    };
+
+    // Candidates named from the owner, with the chain that reaches them
+    let mut candidates: Vec<(String, &HirSymbol)> = Vec::new();
+    for feature in features_of_scope(index, &owner) {
+        candidates.push((feature.name.to_string(), feature));
+        // Without a prefix, the ports of nested parts are offered as `part.port`
+        if prefix.is_empty() {
+            for nested in features_of_scope(index, &feature.qualified_name) {
+                candidates.push((format!("{}.{}", feature.name, nested.name), nested));
+            }
+        }
+    }
+
+    let first_is_delegating = first_end.len() == 1;
+    let mut items: Vec<CompletionItem> = Vec::new();
+    for (label, feature) in candidates {
+        if first.is_some_and(|first| first.qualified_name == feature.qualified_name)
+            || items.iter().any(|item| item.label == label)
+        {
+            continue;
+        }
+        let second_type = end_type(index, feature, is_conjugated);
+        if let Some(first_type) = &first_type {
+            let Some(second_type) = &second_type else {
+                continue;
+            };
+            let second_is_delegating = prefix.is_empty() && !label.contains('.');
+            let delegation = first_is_delegating != second_is_delegating;
+            if !is_compatible(index, first_type, second_type, delegation) {
+                continue;
+            }
+        }
+
+        let detail = match &second_type {
+            Some(end) => {
+                let name = end.definition.rsplit("::").next().unwrap_or_default();
+                let conjugate = if end.conjugated { "~" } else { "" };
+                format!(": {conjugate}{name}")
+            }
+            None => feature.kind.display().to_string(),
+        };
+        items.push(CompletionItem {
+            label: label.clone(),
+            kind: Some(CompletionItemKind::FIELD),
+            detail: Some(detail),
+            documentation: feature
+                .doc
+                .as_ref()
+                .map(|doc| Documentation::String(doc.to_string())),
+            sort_text: Some(format!("010_{label}")),
+            ..Default::default()
+        });
+    }
+    items
+}
@@ -0,0 +1,409 @@
+//! Evaluation of constant value expressions
+//!
+//! A feature bound to arithmetic on literals, like
+//! `attribute wheelDiameter = 0.5 [m] * 2;`, has a value that can be worked
+//! out without a simulator. Hover shows it, and an inlay hint after the
+//! expression shows it where the expression is more than a single literal.
+//!
+//! The AST keeps only the references inside an expression, not the
+//! expression itself, so the declaration is split with the shared tokenizer,
+//! which skips comments and strings, and the expression after the binding
+//! `=` is evaluated from its text, comments and line breaks included.
+//! Numbers, `+ - * /`, `**` or `^` with a plain number as exponent,
+//! parentheses and units in brackets are understood; units multiply and
+//! divide, and only quantities of the same unit add up. Anything else - a
+//! reference to another feature, a function call - leaves the value unknown.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use async_lsp::lsp_types::Position;
+use syster::hir::HirSymbol;
+
+use super::helpers::position_to_byte_offset;
+use super::tokens::{Token, TokenKind, tokenize};
+
+/// A number with an optional unit, such as `1 [m]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    /// Exponent of each unit symbol: `m/s^2` is `{m: 1, s: -2}`
+    pub unit: BTreeMap<String, i32>,
+}
+
+impl Quantity {
+    fn number(value: f64) -> Self {
+        Self {
+            value,
+            unit: BTreeMap::new(),
+        }
+    }
+
+    fn combine(mut self, other: &Self, sign: i32) -> Self {
+        for (symbol, exponent) in &other.unit {
+            *self.unit.entry(symbol.clone()).or_default() += sign * exponent;
+        }
+        self.unit.retain(|_, exponent| *exponent != 0);
+        self
+    }
+
+    /// The unit as written in brackets, e.g. `m/s^2`, or `None` if dimensionless
+    pub fn unit_text(&self) -> Option<String> {
+        let symbols = |positive: bool| -> Vec<String> {
+            self.unit
+                .iter()
+                .filter(|(_, exponent)| (**exponent > 0) == positive)
+                .map(|(symbol, exponent)| match exponent.abs() {
+                    1 => symbol.clone(),
+                    n => format!("{symbol}^{n}"),
+                })
+                .collect()
+        };
+        let (numerator, denominator) = (symbols(true), symbols(false));
+        let numerator = match numerator.is_empty() {
+            true if denominator.is_empty() => return None,
+            true => "1".to_string(),
+            false => numerator.join("*"),
+        };
+        Some(match denominator.as_slice() {
+            [] => numerator,
+            [single] => format!("{numerator}/{single}"),
+            _ => format!("{numerator}/({})", denominator.join("*")),
+        })
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Round away binary noise like 0.30000000000000004
+        let value = (self.value * 1e12).round() / 1e12;
+        write!(f, "{value}")?;
+        if let Some(unit) = self.unit_text() {
+            write!(f, " [{unit}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// A token of a value expression, lexed from its text
+#[derive(Debug, Clone, PartialEq)]
+enum ExprToken {
+    Number(f64),
+    /// Contents of a `[...]` unit
+    Unit(String),
+    /// A name, only understood in unit expressions
+    Name(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+fn lex(text: &str) -> Option<Vec<ExprToken>> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(ch) = rest.chars().next() {
+        // Comments can sit anywhere in an expression spanning several lines
+        if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment
+                .find('\n')
+                .map_or("", |end| &comment[end..])
+                .trim_start();
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment[comment.find("*/")? + 2..].trim_start();
+            continue;
+        }
+        let (token, len) = match ch {
+            '0'..='9' | '.' => {
+                let mut len = rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                    .unwrap_or(rest.len());
+                // Exponent, as in 1.5e-3
+                if let Some(exponent) = rest[len..].strip_prefix(['e', 'E']) {
+                    let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+                    let count = digits
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(digits.len());
+                    if count > 0 {
+                        len += 1 + (exponent.len() - digits.len()) + count;
+                    }
+                }
+                (ExprToken::Number(rest[..len].parse().ok()?), len)
+            }
+            '[' => {
+                let close = rest.find(']')?;
+                (
+                    ExprToken::Unit(rest[1..close].trim().to_string()),
+                    close + 1,
+                )
+            }
+            ch if ch.is_alphabetic() || ch == '_' => {
+                let len = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (ExprToken::Name(rest[..len].to_string()), len)
+            }
+            '*' if rest.starts_with("**") => (ExprToken::Op("^"), 2),
+            '+' => (ExprToken::Op("+"), 1),
+            '-' => (ExprToken::Op("-"), 1),
+            '*' => (ExprToken::Op("*"), 1),
+            '/' => (ExprToken::Op("/"), 1),
+            '^' => (ExprToken::Op("^"), 1),
+            '(' => (ExprToken::Open, 1),
+            ')' => (ExprToken::Close, 1),
+            _ => return None,
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+    Some(tokens)
+}
+
+/// Parse a unit like `m/s^2` or `kg*m`
+fn parse_unit(text: &str) -> Option<BTreeMap<String, i32>> {
+    let mut unit = BTreeMap::new();
+    let mut sign = 1;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest.find(['*', '/', ' ', '·']).unwrap_or(rest.len());
+        let (symbol, exponent) = match rest[..end].split_once('^') {
+            Some((symbol, exponent)) => (symbol, exponent.parse::<i32>().ok()?),
+            None => (&rest[..end], 1),
+        };
+        if symbol.is_empty() || !symbol.chars().all(|c| c.is_alphanumeric() || c == '°') {
+            return None;
+        }
+        *unit.entry(symbol.to_string()).or_default() += sign * exponent;
+
+        let separator = rest[end..].chars().next();
+        sign = if separator == Some('/') { -1 } else { 1 };
+        rest = rest[end..]
+            .strip_prefix(|c: char| matches!(c, '*' | '/' | ' ' | '·'))
+            .unwrap_or("");
+    }
+    unit.retain(|_, exponent| *exponent != 0);
+    Some(unit)
+}
+
+/// Recursive descent over the tokens, lowest precedence first
+struct Parser {
+    tokens: Vec<ExprToken>,
+    next: usize,
+    /// Read names as unit symbols, as in `kg*m/s^2`
+    names_as_units: bool,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&ExprToken> {
+        self.tokens.get(self.next)
+    }
+
+    fn eat(&mut self, op: &'static str) -> bool {
+        let matches = self.peek() == Some(&ExprToken::Op(op));
+        if matches {
+            self.next += 1;
+        }
+        matches
+    }
+
+    fn sum(&mut self) -> Option<Quantity> {
+        let mut value = self.product()?;
+        loop {
+            let sign = if self.eat("+") {
+                1.0
+            } else if self.eat("-") {
+                -1.0
+            } else {
+                return Some(value);
+            };
+            let rhs = self.product()?;
+            if rhs.unit != value.unit {
+                return None;
+            }
+            value.value += sign * rhs.value;
+        }
+    }
+
+    fn product(&mut self) -> Option<Quantity> {
+        let mut value = self.power()?;
+        loop {
+            if self.eat("*") {
+                let rhs = self.power()?;
+                value = Quantity {
+                    value: value.value * rhs.value,
+                    ..value
+                }
+                .combine(&rhs, 1);
+            } else if self.eat("/") {
+                let rhs = self.power()?;
+                if rhs.value == 0.0 {
+                    return None;
+                }
+                value = Quantity {
+                    value: value.value / rhs.value,
+                    ..value
+                }
+                .combine(&rhs, -1);
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn power(&mut self) -> Option<Quantity> {
+        let base = self.unary()?;
+        if !self.eat("^") {
+            return Some(base);
+        }
+        let exponent = self.power()?;
+        if !exponent.unit.is_empty() {
+            return None;
+        }
+        // Units can only be raised to whole powers
+        let whole = exponent.value.fract() == 0.0;
+        if !base.unit.is_empty() && !whole {
+            return None;
+        }
+        let unit = base
+            .unit
+            .iter()
+            .map(|(symbol, power)| (symbol.clone(), power * exponent.value as i32))
+            .collect();
+        let value = base.value.powf(exponent.value);
+        value.is_finite().then_some(Quantity { value, unit })
+    }
+
+    fn unary(&mut self) -> Option<Quantity> {
+        if self.eat("-") {
+            let mut value = self.unary()?;
+            value.value = -value.value;
+            return Some(value);
+        }
+        if self.eat("+") {
+            return self.unary();
+        }
+        self.quantity()
+    }
+
+    /// A number or parenthesized expression, with an optional unit
+    fn quantity(&mut self) -> Option<Quantity> {
+        let value = match self.peek()? {
+            ExprToken::Number(value) => {
+                let value = *value;
+                self.next += 1;
+                Quantity::number(value)
+            }
+            ExprToken::Name(symbol) if self.names_as_units => {
+                let value = Quantity {
+                    value: 1.0,
+                    unit: BTreeMap::from([(symbol.clone(), 1)]),
+                };
+                self.next += 1;
+                value
+            }
+            ExprToken::Open => {
+                self.next += 1;
+                let value = self.sum()?;
+                if self.peek() != Some(&ExprToken::Close) {
+                    return None;
+                }
+                self.next += 1;
+                value
+            }
+            _ => return None,
+        };
+        if let Some(ExprToken::Unit(unit)) = self.peek() {
+            let unit = Quantity {
+                value: 1.0,
+                unit: parse_unit(unit)?,
+            };
+            self.next += 1;
+            return Some(value.combine(&unit, 1));
+        }
+        Some(value)
+    }
+}
+
+/// Evaluate a constant expression, or `None` if it isn't one
+pub fn evaluate(expression: &str) -> Option<Quantity> {
+    parse(expression, false)
+}
+
+/// Evaluate a unit expression, like the `kg*m/s^2` newton is bound to or
+/// the `1000 * m` of a scaled unit, reading names as unit symbols
+pub fn evaluate_unit_expression(expression: &str) -> Option<Quantity> {
+    parse(expression, true)
+}
+
+fn parse(expression: &str, names_as_units: bool) -> Option<Quantity> {
+    let mut parser = Parser {
+        tokens: lex(expression)?,
+        next: 0,
+        names_as_units,
+    };
+    let value = parser.sum()?;
+    (parser.next == parser.tokens.len() && value.value.is_finite()).then_some(value)
+}
+
+/// The value expression bound to a feature (`= expr`, `:= expr`,
+/// `default = expr`) in `text`, with the position after it
+pub(super) fn value_expression<'a>(
+    text: &'a str,
+    symbol: &HirSymbol,
+) -> Option<(&'a str, Position)> {
+    if !symbol.kind.is_usage() {
+        return None;
+    }
+    let name_end = Position::new(symbol.end_line, symbol.end_col);
+    let rest = text.get(position_to_byte_offset(text, name_end).ok()?..)?;
+    let tokens = tokenize(rest);
+    let declaration_end = tokens
+        .iter()
+        .position(|token| matches!(token.kind, TokenKind::Semi | TokenKind::LBrace))
+        .unwrap_or(tokens.len());
+    let declaration = &tokens[..declaration_end];
+
+    // The binding `=` (also ending `:=`), not part of `==`, `<=`, `>=` or `!=`
+    let equals = (0..declaration.len()).find(|&idx| {
+        matches!(declaration[idx].text, "=" | ":=")
+            && declaration.get(idx + 1).is_none_or(|next| next.text != "=")
+            && (idx == 0 || !matches!(declaration[idx - 1].text, "=" | "<" | ">" | "!"))
+    })?;
+    let (first, last) = match &declaration[equals + 1..] {
+        [] => return None,
+        [only] => (only, only),
+        [first, .., last] => (first, last),
+    };
+
+    // Token positions are relative to the end of the name
+    let absolute = |token_position: Position| {
+        if token_position.line == 0 {
+            Position::new(name_end.line, name_end.character + token_position.character)
+        } else {
+            Position::new(
+                name_end.line + token_position.line,
+                token_position.character,
+            )
+        }
+    };
+    let offset = |token: &Token<'_>, position: Position| {
+        position_to_byte_offset(text, absolute(position))
+            .ok()
+            .filter(|offset| {
+                text.get(*offset..)
+                    .is_some_and(|rest| rest.starts_with(token.text))
+            })
+    };
+    let start = offset(first, first.start)?;
+    let end = offset(last, last.start)? + last.text.len();
+    Some((&text[start..end], absolute(last.end)))
+}
+
+/// Whether an expression is a single number, with or without a unit
+pub(super) fn is_literal(expression: &str) -> bool {
+    matches!(
+        lex(expression).as_deref(),
+        Some([ExprToken::Number(_)] | [ExprToken::Number(_), ExprToken::Unit(_)])
+    )
+}
@@ -1,9 +1,32 @@
+use super::background_tasks::indexing::IndexingJob;
+use super::client_support::ClientSupport;
+use super::configuration::DEFAULT_HOVER_MAX_DEPTH;
+use super::dependency_graph::DependencyGraph;
+use super::diagnostics_store::DiagnosticsStore;
+use super::environment::{FileSystem, RealFileSystem};
+use super::error::LspError;
+use super::formatting::{DocumentVersion, FormattedHash};
+use super::health::HealthTracker;
+use super::incremental_parse::IncrementalParser;
+use super::organize_imports::ORGANIZE_IMPORTS_EXPAND_WILDCARDS;
+use super::parallel_parse;
+use super::parse_cache::ParseCache;
+use super::project_manifest::ProjectManifest;
+use super::project_validation::ProjectValidation;
+use super::reference_index::ReferenceIndex;
+use super::reference_search::ReferenceSearches;
+use super::semantic_tokens::SemanticTokensCache;
+use super::session::DocumentOverlays;
+use super::symbol_lookup::SymbolLookup;
+use super::workspace_filter::WorkspaceFilter;
+use super::workspace_roots::RootLibraries;
 use async_lsp::lsp_types::*;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use syster::core::ParseError;
 use syster::core::constants::{
-    COMPLETION_TRIGGERS, LSP_SERVER_NAME, LSP_SERVER_VERSION, OPT_STDLIB_ENABLED, OPT_STDLIB_PATH,
+    COMPLETION_TRIGGERS, LSP_SERVER_NAME, LSP_SERVER_VERSION, STDLIB_DIR,
 };
 use syster::ide::AnalysisHost;
 use syster::project::{StdLibLoader, WorkspaceLoader};
@@ -13,20 +36,76 @@ use tokio_util::sync::CancellationToken;
 pub struct LspServer {
     /// Unified analysis host - holds workspace, symbol index, and file maps
     pub(super) analysis_host: AnalysisHost,
+    /// Indices of the roots whose manifest configures another library
+    pub(super) root_libraries: RootLibraries,
     /// Track parse errors for each file (keyed by file path)
     pub(super) parse_errors: HashMap<PathBuf, Vec<ParseError>>,
     /// Track document text for hover and other features (keyed by file path)
     pub(super) document_texts: HashMap<PathBuf, String>,
+    /// Editor versions of open documents, shared with in-flight formatting
+    pub(super) document_versions: HashMap<PathBuf, DocumentVersion>,
+    /// What open documents were last formatted to, shared with in-flight formatting
+    pub(super) formatted_hashes: HashMap<PathBuf, FormattedHash>,
+    /// Per-session text of open documents when the workspace is shared
+    pub(super) document_overlays: DocumentOverlays,
     /// Stdlib loader for lazy loading
     pub(super) stdlib_loader: StdLibLoader,
+    /// Parse results keyed by content hash, reused when identical text is parsed again
+    pub(super) parse_cache: ParseCache,
+    /// Member-level parse results reused when re-parsing edited documents
+    pub(super) incremental_parser: IncrementalParser,
+    /// References per file, updated span by span as documents are edited
+    pub(super) reference_index: ReferenceIndex,
+    /// Find-references searches waiting for their shards to be scanned
+    pub(super) reference_searches: ReferenceSearches,
+    /// Symbols per file by position and by name, updated per edited file
+    pub(super) symbol_lookup: SymbolLookup,
+    /// Definitions of edited documents and the dependents to re-validate
+    pub(super) dependency_graph: DependencyGraph,
+    /// Semantic tokens last sent per document, for answering delta requests
+    pub(super) semantic_tokens_cache: SemanticTokensCache,
+    /// Diagnostics last published per document, so unchanged ones aren't resent
+    pub(super) diagnostics_store: DiagnosticsStore,
+    /// Cross-file findings and the project validation run in progress
+    pub(super) project_validation: ProjectValidation,
     /// Whether stdlib loading is enabled
-    stdlib_enabled: bool,
+    pub(super) stdlib_enabled: bool,
+    /// Custom stdlib location, if one was configured
+    pub(super) stdlib_path: Option<PathBuf>,
+    /// Stdlib settings from the client, before project manifests override them
+    pub(super) configured_stdlib: (bool, Option<PathBuf>),
+    /// `syster.toml` manifests read from the workspace folders
+    pub(super) project_manifests: Vec<ProjectManifest>,
+    /// Directory of the on-disk stdlib reference cache (`None` disables it)
+    pub(super) stdlib_cache_dir: Option<PathBuf>,
+    /// Whether Find All References should skip locations inside the stdlib
+    pub(super) references_exclude_stdlib: bool,
+    /// Levels of transitive relationships (e.g. the inheritance chain) shown in hovers
+    pub(super) hover_max_depth: usize,
+    /// Whether names breaking the naming convention are reported
+    pub(super) naming_conventions: bool,
+    /// Whether definitions unreferenced outside their file are reported
+    pub(super) unused_elements: bool,
+    /// Qualified names of elements that are used even when nothing refers to them
+    pub(super) entry_points: Vec<String>,
+    /// Whether rename follows redefinitions and subsettings of the renamed feature
+    pub(super) rename_redefinitions: bool,
     /// Cancellation tokens per document - cancelled when document changes
-    document_cancel_tokens: HashMap<PathBuf, CancellationToken>,
+    pub(super) document_cancel_tokens: HashMap<PathBuf, CancellationToken>,
     /// Whether workspace has been fully initialized
     workspace_initialized: bool,
     /// Workspace folders to scan for SysML/KerML files
-    workspace_folders: Vec<PathBuf>,
+    pub(super) workspace_folders: Vec<PathBuf>,
+    /// Globs excluded when scanning workspace folders, on top of their ignore files
+    pub(super) exclude_globs: Vec<String>,
+    /// Whether a background indexing job is currently loading the workspace
+    indexing_in_progress: bool,
+    /// What the client declared support for at initialize
+    pub(super) client_support: ClientSupport,
+    /// Pending documents and error counts reported by the health check
+    pub(super) health: HealthTracker,
+    /// File system the server reads documents, manifests and caches from
+    pub(super) fs: Arc<dyn FileSystem>,
 }
 
 impl Default for LspServer {
@@ -53,7 +132,10 @@ impl LspServer {
             hover_provider: Some(HoverProviderCapability::Simple(true)),
             definition_provider: Some(OneOf::Left(true)),
             type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
+            implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
             references_provider: Some(OneOf::Left(true)),
+            document_highlight_provider: Some(OneOf::Left(true)),
+            call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
             document_symbol_provider: Some(OneOf::Left(true)),
             rename_provider: Some(OneOf::Right(RenameOptions {
                 prepare_provider: Some(true),
@@ -61,6 +143,10 @@ impl LspServer {
             })),
             document_formatting_provider: Some(OneOf::Left(true)),
             document_range_formatting_provider: Some(OneOf::Left(true)),
+            document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                first_trigger_character: "}".to_string(),
+                more_trigger_character: Some(vec![";".to_string()]),
+            }),
             completion_provider: Some(CompletionOptions {
                 resolve_provider: Some(false),
                 trigger_characters: Some(
@@ -70,15 +156,25 @@ impl LspServer {
             }),
             folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
             selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+            linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(true)),
+            moniker_provider: Some(OneOf::Left(true)),
             inlay_hint_provider: Some(OneOf::Left(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                code_action_kinds: Some(vec![
+                    CodeActionKind::QUICKFIX,
+                    CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                    CodeActionKind::new(ORGANIZE_IMPORTS_EXPAND_WILDCARDS),
+                ]),
+                ..Default::default()
+            })),
             code_lens_provider: Some(CodeLensOptions {
-                resolve_provider: Some(false),
+                resolve_provider: Some(true),
             }),
             semantic_tokens_provider: Some(
                 SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
                     legend: Self::semantic_tokens_legend(),
-                    full: Some(SemanticTokensFullOptions::Bool(true)),
-                    range: None,
+                    full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                    range: Some(true),
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 }),
             ),
@@ -87,8 +183,17 @@ impl LspServer {
                 work_done_progress_options: WorkDoneProgressOptions::default(),
             }),
             workspace_symbol_provider: Some(OneOf::Left(true)),
+            diagnostic_provider: Some(DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                identifier: Some(LSP_SERVER_NAME.to_string()),
+                inter_file_dependencies: true,
+                workspace_diagnostics: true,
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            })),
             workspace: Some(WorkspaceServerCapabilities {
-                workspace_folders: None,
+                workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                    supported: Some(true),
+                    change_notifications: Some(OneOf::Left(true)),
+                }),
                 file_operations: None,
             }),
             ..Default::default()
@@ -106,24 +211,6 @@ impl LspServer {
         }
     }
 
-    /// Parse initialization options from the client
-    pub fn parse_init_options(options: Option<serde_json::Value>) -> (bool, Option<PathBuf>) {
-        if let Some(serde_json::Value::Object(opts)) = options {
-            let enabled = opts
-                .get(OPT_STDLIB_ENABLED)
-                .and_then(|v| v.as_bool())
-                .unwrap_or(true);
-            let path = opts
-                .get(OPT_STDLIB_PATH)
-                .and_then(|v| v.as_str())
-                .filter(|s| !s.is_empty())
-                .map(PathBuf::from);
-            (enabled, path)
-        } else {
-            (true, None)
-        }
-    }
-
     pub fn new() -> Self {
         Self::with_config(true, None)
     }
@@ -131,20 +218,48 @@ impl LspServer {
     /// Create a new LspServer with custom configuration
     pub fn with_config(stdlib_enabled: bool, custom_stdlib_path: Option<PathBuf>) -> Self {
         // Use custom path or let StdLibLoader discover it automatically
-        let stdlib_loader = match custom_stdlib_path {
-            Some(path) => StdLibLoader::with_path(path),
+        let stdlib_loader = match &custom_stdlib_path {
+            Some(path) => StdLibLoader::with_path(path.clone()),
             None => StdLibLoader::new(),
         };
 
         Self {
             analysis_host: AnalysisHost::new(),
+            root_libraries: RootLibraries::default(),
             parse_errors: HashMap::new(),
             document_texts: HashMap::new(),
+            document_versions: HashMap::new(),
+            formatted_hashes: HashMap::new(),
+            document_overlays: DocumentOverlays::default(),
             stdlib_loader,
+            parse_cache: ParseCache::default(),
+            incremental_parser: IncrementalParser::default(),
+            reference_index: ReferenceIndex::default(),
+            reference_searches: ReferenceSearches::default(),
+            symbol_lookup: SymbolLookup::default(),
+            dependency_graph: DependencyGraph::default(),
+            semantic_tokens_cache: SemanticTokensCache::default(),
+            diagnostics_store: DiagnosticsStore::default(),
+            project_validation: ProjectValidation::default(),
             stdlib_enabled,
+            configured_stdlib: (stdlib_enabled, custom_stdlib_path.clone()),
+            project_manifests: Vec::new(),
+            stdlib_path: custom_stdlib_path,
+            stdlib_cache_dir: None,
+            references_exclude_stdlib: false,
+            hover_max_depth: DEFAULT_HOVER_MAX_DEPTH,
+            naming_conventions: false,
+            unused_elements: false,
+            entry_points: Vec::new(),
+            rename_redefinitions: false,
             document_cancel_tokens: HashMap::new(),
             workspace_initialized: false,
             workspace_folders: Vec::new(),
+            exclude_globs: Vec::new(),
+            indexing_in_progress: false,
+            client_support: ClientSupport::default(),
+            health: HealthTracker::default(),
+            fs: Arc::new(RealFileSystem),
         }
     }
 
@@ -153,45 +268,378 @@ impl LspServer {
         self.workspace_folders = folders;
     }
 
+    /// Set whether the client supports `window/workDoneProgress`
+    pub fn set_work_done_progress(&mut self, supported: bool) {
+        self.client_support.work_done_progress = supported;
+    }
+
+    /// Whether indexing progress should be reported to the client
+    pub fn work_done_progress(&self) -> bool {
+        self.client_support.work_done_progress
+    }
+
+    /// Set whether the client supports dynamic registration of type hierarchy
+    pub fn set_type_hierarchy_dynamic_registration(&mut self, supported: bool) {
+        self.client_support.type_hierarchy_registration = supported;
+    }
+
+    /// Set whether the client supports dynamic registration of file watchers
+    pub fn set_watched_files_dynamic_registration(&mut self, supported: bool) {
+        self.client_support.watched_files_registration = supported;
+    }
+
+    /// Set whether the client can be asked to re-request semantic tokens
+    pub fn set_semantic_tokens_refresh_support(&mut self, supported: bool) {
+        self.client_support.semantic_tokens_refresh = supported;
+    }
+
+    /// Whether the client can be asked to re-request semantic tokens
+    pub fn semantic_tokens_refresh_support(&self) -> bool {
+        self.client_support.semantic_tokens_refresh
+    }
+
+    /// Set whether the client can be asked to re-request inlay hints
+    pub fn set_inlay_hint_refresh_support(&mut self, supported: bool) {
+        self.client_support.inlay_hint_refresh = supported;
+    }
+
+    /// Whether the client can be asked to re-request inlay hints
+    pub fn inlay_hint_refresh_support(&self) -> bool {
+        self.client_support.inlay_hint_refresh
+    }
+
+    /// Set whether the client pulls diagnostics with `textDocument/diagnostic`
+    pub fn set_pull_diagnostics(&mut self, enabled: bool) {
+        self.client_support.pull_diagnostics = enabled;
+    }
+
+    /// Set whether the client can be asked to pull diagnostics again
+    pub fn set_diagnostic_refresh_support(&mut self, supported: bool) {
+        self.client_support.diagnostic_refresh = supported;
+    }
+
+    /// Whether the client should be asked to pull diagnostics again after
+    /// changes that can affect other documents
+    pub fn diagnostic_refresh_support(&self) -> bool {
+        self.client_support.pull_diagnostics && self.client_support.diagnostic_refresh
+    }
+
+    /// Replace the file system the server reads from (e.g. with an in-memory one in tests)
+    pub fn set_file_system(&mut self, fs: Arc<dyn FileSystem>) {
+        self.fs = fs;
+    }
+
+    /// Set the globs excluded from workspace indexing
+    pub fn set_exclude_globs(&mut self, globs: Vec<String>) {
+        self.exclude_globs = globs;
+    }
+
+    /// Set whether Find All References should skip stdlib locations
+    pub fn set_references_exclude_stdlib(&mut self, exclude: bool) {
+        self.references_exclude_stdlib = exclude;
+    }
+
+    /// Set how many levels of transitive relationships hovers show
+    pub fn set_hover_max_depth(&mut self, depth: usize) {
+        self.hover_max_depth = depth;
+    }
+
+    /// Set whether names breaking the naming convention are reported
+    pub fn set_naming_conventions(&mut self, enabled: bool) {
+        self.naming_conventions = enabled;
+    }
+
+    /// Set whether definitions unreferenced outside their file are reported
+    pub fn set_unused_elements(&mut self, enabled: bool) {
+        self.unused_elements = enabled;
+    }
+
+    /// Set whether rename includes the features redefining or subsetting the
+    /// renamed one
+    pub fn set_rename_redefinitions(&mut self, enabled: bool) {
+        self.rename_redefinitions = enabled;
+    }
+
+    /// Set the elements never reported as unused
+    pub fn set_entry_points(&mut self, entry_points: Vec<String>) {
+        self.entry_points = entry_points;
+    }
+
+    /// Whether Find All References skips stdlib locations by default
+    pub fn references_exclude_stdlib(&self) -> bool {
+        self.references_exclude_stdlib
+    }
+
+    /// Check whether a path belongs to the standard library.
+    ///
+    /// Matches files under the configured stdlib path or the stdlib of any
+    /// workspace folder's manifest, or any path containing a `sysml.library`
+    /// directory component when the path was auto-discovered.
+    pub fn is_stdlib_path(&self, path: &Path) -> bool {
+        if let Some(stdlib_path) = &self.stdlib_path
+            && path.starts_with(stdlib_path)
+        {
+            return true;
+        }
+        if self
+            .project_manifests
+            .iter()
+            .filter_map(|manifest| manifest.stdlib_path.as_ref())
+            .any(|stdlib_path| path.starts_with(stdlib_path))
+        {
+            return true;
+        }
+        path.components()
+            .any(|component| component.as_os_str() == STDLIB_DIR)
+    }
+
+    /// Check whether a path is under a source folder and not excluded from indexing
+    pub fn is_workspace_file(&self, path: &Path) -> bool {
+        let exclude_globs = self.source_exclude_globs();
+        self.source_folders().iter().any(|folder| {
+            path.starts_with(folder)
+                && !WorkspaceFilter::for_folder(folder, &exclude_globs).excludes_file(path)
+        })
+    }
+
+    /// Documents of the project: opened by the client or under a source
+    /// folder, and not part of the standard library
+    pub(super) fn project_files(&self) -> HashSet<PathBuf> {
+        self.document_texts
+            .keys()
+            .filter(|path| {
+                (self.document_versions.contains_key(*path) || self.is_workspace_file(path))
+                    && !self.is_stdlib_path(path)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Folders scanned for model files: the source directories of each
+    /// folder's project manifest, or the workspace folder itself
+    fn source_folders(&self) -> Vec<PathBuf> {
+        self.workspace_folders
+            .iter()
+            .flat_map(|folder| self.root_source_folders(folder))
+            .collect()
+    }
+
+    /// The configured exclude globs and those of every project manifest
+    fn source_exclude_globs(&self) -> Vec<String> {
+        self.exclude_globs
+            .iter()
+            .chain(self.project_manifests.iter().flat_map(|m| &m.exclude))
+            .cloned()
+            .collect()
+    }
+
+    /// Read the `syster.toml` at the root of each workspace folder.
+    ///
+    /// Manifests that fail to parse are logged and ignored.
+    pub(super) fn read_project_manifests(&self) -> Vec<ProjectManifest> {
+        self.workspace_folders
+            .iter()
+            .filter_map(|folder| match ProjectManifest::find(self.fs.as_ref(), folder)? {
+                Ok(manifest) => Some(manifest),
+                Err(err) => {
+                    tracing::warn!(folder = %folder.display(), "Invalid project manifest: {err}");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Read the project manifests and apply their stdlib settings
+    fn load_project_manifests(&mut self) {
+        self.project_manifests = self.read_project_manifests();
+
+        let (enabled, path) = self.library_stdlib();
+        // A fresh loader, so a reload puts the stdlib into the new analysis host
+        self.stdlib_loader = match &path {
+            Some(path) => StdLibLoader::with_path(path.clone()),
+            None => StdLibLoader::new(),
+        };
+        self.stdlib_enabled = enabled;
+        self.stdlib_path = path;
+    }
+
+    /// Re-read the project manifests and reload the workspace if they changed.
+    ///
+    /// Documents open in the editor keep their text. Returns whether the
+    /// workspace was reloaded.
+    pub fn reload_project_manifests(&mut self) -> bool {
+        if !self.workspace_initialized || self.read_project_manifests() == self.project_manifests {
+            return false;
+        }
+        self.reload_workspace();
+        true
+    }
+
+    /// Load the workspace again from scratch, with the current workspace
+    /// folders and manifests.
+    ///
+    /// Documents open in the editor keep their text.
+    pub(super) fn reload_workspace(&mut self) {
+        let open: Vec<(PathBuf, String)> = self
+            .document_versions
+            .keys()
+            .filter_map(|path| Some((path.clone(), self.document_texts.get(path)?.clone())))
+            .collect();
+
+        self.analysis_host = AnalysisHost::new();
+        self.document_texts.clear();
+        self.parse_errors.clear();
+        self.reference_index = ReferenceIndex::default();
+        self.symbol_lookup = SymbolLookup::default();
+        self.dependency_graph = DependencyGraph::default();
+        self.workspace_initialized = false;
+        if let Err(err) = self.ensure_workspace_loaded() {
+            tracing::warn!("Failed to reload workspace: {err}");
+        }
+
+        for (path, text) in open {
+            if let Ok(uri) = Url::from_file_path(&path)
+                && let Err(err) = self.open_document(&uri, &text)
+            {
+                tracing::warn!(path = %path.display(), "Failed to reopen document: {err}");
+            }
+        }
+    }
+
     /// Ensure workspace is fully initialized (stdlib loaded, symbols populated, texts synced).
     /// Only runs once on first call, subsequent calls are no-ops.
     ///
     /// Parse errors in individual files are logged but don't block workspace loading.
     /// Valid files are still loaded and functional even when some files have parse errors.
-    pub fn ensure_workspace_loaded(&mut self) -> Result<(), String> {
-        if self.workspace_initialized {
+    pub fn ensure_workspace_loaded(&mut self) -> Result<(), LspError> {
+        // While background indexing runs, requests work on what is loaded so far
+        if self.workspace_initialized || self.indexing_in_progress {
             return Ok(());
         }
 
-        // Load stdlib if enabled
+        // Project manifests can change the stdlib and the folders to scan
+        self.load_project_manifests();
+
+        // Load stdlib if enabled, parsing its files in parallel when its location is known
         if self.stdlib_enabled {
-            self.stdlib_loader
-                .ensure_loaded_into_host(&mut self.analysis_host)?;
+            match parallel_parse::stdlib_dir(self.stdlib_path.as_deref()) {
+                Some(dir) => {
+                    parallel_parse::load_stdlib(&dir, &mut self.analysis_host)
+                        .map_err(LspError::StdlibUnavailable)?;
+                }
+                None => self
+                    .stdlib_loader
+                    .ensure_loaded_into_host(&mut self.analysis_host)
+                    .map_err(|err| LspError::StdlibUnavailable(err.to_string()))?,
+            }
+            self.restore_stdlib_index();
         }
 
         // Load all SysML/KerML files from workspace folders
-        // Parse errors are collected but don't block loading of valid files
-        let loader = WorkspaceLoader::new();
-        for folder in self.workspace_folders.clone() {
-            if let Err(err) = loader.load_directory_into_host(&folder, &mut self.analysis_host) {
-                // Log parse errors but continue - valid files are already loaded
-                tracing::warn!(
-                    folder = %folder.display().to_string(),
-                    "Some files failed to parse: {err}"
-                );
-            }
-        }
+        let folders = self.source_folders();
+        self.load_folders(&folders);
 
         // Sync document texts for hover/features on loaded files
         self.sync_document_texts_from_files();
 
         // Mark dirty so index is rebuilt on next analysis() call
         self.analysis_host.mark_dirty();
+        self.load_root_libraries();
 
         self.workspace_initialized = true;
         Ok(())
     }
 
+    /// Load the SysML/KerML files under source folders into the workspace.
+    ///
+    /// Parse errors are collected but don't block loading of valid files.
+    /// Documents open in the editor keep their text.
+    pub(super) fn load_folders(&mut self, folders: &[PathBuf]) {
+        let loader = WorkspaceLoader::new();
+        let exclude_globs = self.source_exclude_globs();
+        for folder in folders {
+            let paths = match WorkspaceFilter::for_folder(folder, &exclude_globs).collect_files() {
+                Ok(paths) => paths,
+                Err(err) => {
+                    tracing::warn!(folder = %folder.display(), "Failed to scan folder: {err}");
+                    continue;
+                }
+            };
+            for path in paths {
+                if self.document_versions.contains_key(&path) {
+                    continue;
+                }
+                if let Err(err) = loader.load_file_into_host(&path, &mut self.analysis_host) {
+                    // Log parse errors but continue - valid files are already loaded
+                    tracing::warn!(
+                        folder = %folder.display().to_string(),
+                        "Some files failed to parse: {err}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Start background indexing of the stdlib and workspace folders.
+    ///
+    /// Returns the job to run, or `None` if the workspace is already loaded or
+    /// being indexed. Until `finish_background_indexing` is called, requests are
+    /// answered with partial results from the documents opened so far.
+    pub fn begin_background_indexing(&mut self) -> Option<IndexingJob> {
+        if self.workspace_initialized || self.indexing_in_progress {
+            return None;
+        }
+        self.indexing_in_progress = true;
+        self.load_project_manifests();
+        Some(IndexingJob {
+            stdlib: self.stdlib_enabled.then(|| self.stdlib_path.clone()),
+            folders: self.source_folders(),
+            exclude_globs: self.source_exclude_globs(),
+        })
+    }
+
+    /// Merge the files loaded by a background indexing job into the workspace.
+    ///
+    /// Files already present (e.g. opened in the editor while indexing) are kept.
+    /// Returns the documents whose diagnostics should be republished.
+    pub fn finish_background_indexing(&mut self, host: AnalysisHost) -> Vec<Url> {
+        for (path, file) in host.files() {
+            if !self.analysis_host.has_file_path(path) {
+                self.analysis_host.set_file(path.clone(), file.clone());
+            }
+        }
+
+        self.sync_document_texts_from_files();
+        self.analysis_host.mark_dirty();
+        if self.stdlib_enabled {
+            self.restore_stdlib_index();
+        }
+        self.load_root_libraries();
+
+        self.indexing_in_progress = false;
+        self.workspace_initialized = true;
+
+        self.parse_errors
+            .keys()
+            .filter_map(|path| Url::from_file_path(path).ok())
+            .collect()
+    }
+
+    /// Whether a background indexing job is still running
+    pub fn is_indexing(&self) -> bool {
+        self.indexing_in_progress
+    }
+
+    /// Whether the stdlib and workspace folders have been loaded
+    pub fn is_workspace_loaded(&self) -> bool {
+        self.workspace_initialized
+    }
+
+    /// Whether the stdlib is enabled and has been loaded
+    pub fn is_stdlib_loaded(&self) -> bool {
+        self.stdlib_enabled && self.workspace_initialized
+    }
+
     /// Cancel any in-flight operations for a document and return a new token.
     /// Call this at the start of didChange to cancel previous operations.
     pub fn cancel_document_operations(&mut self, path: &PathBuf) -> CancellationToken {
@@ -213,11 +661,11 @@ impl LspServer {
 
     /// Sync document_texts with all files currently loaded
     /// This ensures hover and other features work on all files without disk reads
-    fn sync_document_texts_from_files(&mut self) {
+    pub(super) fn sync_document_texts_from_files(&mut self) {
         for path in self.analysis_host.files().keys() {
             // Only load if not already tracked (avoid overwriting editor versions)
             if !self.document_texts.contains_key(path)
-                && let Ok(text) = std::fs::read_to_string(path)
+                && let Ok(text) = self.fs.read_to_string(path)
             {
                 self.document_texts.insert(path.clone(), text);
             }
@@ -0,0 +1,406 @@
+//! Regression runs over an on-disk model corpus
+//!
+//! A corpus is a directory of SysML/KerML files - the spec's Annex A examples,
+//! or a user's own models - with an expectations file at its root recording
+//! what the server made of it: the number of parse errors in each file, and
+//! the hover and definition answered at chosen positions. [`run_corpus`]
+//! loads every file, repeats those probes and reports where the answers
+//! differ, so a server upgrade can be checked against real models before it
+//! is rolled out. [`LspServer::record_corpus`] writes the expectations from
+//! the current server, probing every reference in the corpus.
+//!
+//! Paths in the expectations are relative to the corpus, with `/` separators.
+//! Definitions outside the corpus (in the standard library) are recorded by
+//! file name only, as the library's location differs between machines. The
+//! same goes for the links in recorded hovers.
+
+use super::LspServer;
+use super::error::LspError;
+use super::workspace_filter::WorkspaceFilter;
+use async_lsp::lsp_types::{Hover, HoverContents, MarkedString, Position, Range, Url};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Expectations file at the root of a corpus
+pub const EXPECTATIONS_FILE: &str = "corpus.expected.json";
+
+/// What the server is expected to make of a corpus
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorpusExpectations {
+    /// Expectations per file, ordered by path
+    pub files: Vec<FileExpectations>,
+}
+
+/// What the server is expected to make of one corpus file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileExpectations {
+    /// Path relative to the corpus
+    pub path: String,
+    #[serde(default)]
+    pub parse_errors: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub probes: Vec<Probe>,
+}
+
+/// Hover and definition expected at a position; `None` expects no answer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Probe {
+    pub position: Position,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hover: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub definition: Option<ProbeLocation>,
+}
+
+/// A definition location, by corpus-relative path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeLocation {
+    pub path: String,
+    pub range: Range,
+}
+
+impl fmt::Display for ProbeLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let start = self.range.start;
+        write!(
+            f,
+            "{}:{}:{}",
+            self.path,
+            start.line + 1,
+            start.character + 1
+        )
+    }
+}
+
+/// What a corpus check compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CorpusCheck {
+    /// A file with expectations is no longer in the corpus
+    MissingFile,
+    ParseErrors,
+    Hover,
+    Definition,
+}
+
+/// An answer that differs from the expectations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorpusRegression {
+    pub path: String,
+    /// Position of the probe, for hover and definition checks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+    pub check: CorpusCheck,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for CorpusRegression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)?;
+        if let Some(position) = self.position {
+            write!(f, ":{}:{}", position.line + 1, position.character + 1)?;
+        }
+        write!(
+            f,
+            ": {:?} expected {:?}, got {:?}",
+            self.check, self.expected, self.actual
+        )
+    }
+}
+
+/// Outcome of checking a corpus against its expectations
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorpusReport {
+    /// Files loaded from the corpus
+    pub files: usize,
+    /// Probes repeated
+    pub probes: usize,
+    pub regressions: Vec<CorpusRegression>,
+    /// Corpus files without expectations, which were loaded but not checked
+    pub unrecorded_files: Vec<String>,
+}
+
+impl CorpusReport {
+    /// Whether every expectation was met
+    pub fn passed(&self) -> bool {
+        self.regressions.is_empty()
+    }
+}
+
+/// Check the corpus in `dir` against its expectations, with the standard
+/// library loaded
+pub fn run_corpus(dir: &Path) -> Result<CorpusReport, LspError> {
+    LspServer::new().check_corpus(dir)
+}
+
+impl CorpusExpectations {
+    /// Read the expectations of the corpus in `dir`; a corpus without an
+    /// expectations file has none
+    pub fn load(dir: &Path) -> Result<Self, LspError> {
+        let path = dir.join(EXPECTATIONS_FILE);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(LspError::InvalidCorpus(format!(
+                    "failed to read {}: {err}",
+                    path.display()
+                )));
+            }
+        };
+        serde_json::from_str(&text)
+            .map_err(|err| LspError::InvalidCorpus(format!("{}: {err}", path.display())))
+    }
+
+    /// Write the expectations to the root of the corpus in `dir`
+    pub fn save(&self, dir: &Path) -> Result<(), LspError> {
+        let path = dir.join(EXPECTATIONS_FILE);
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| LspError::InvalidCorpus(err.to_string()))?;
+        std::fs::write(&path, json + "\n").map_err(|err| {
+            LspError::InvalidCorpus(format!("failed to write {}: {err}", path.display()))
+        })
+    }
+}
+
+impl LspServer {
+    /// Load the corpus in `dir` into this server and check it against its
+    /// expectations
+    pub fn check_corpus(&mut self, dir: &Path) -> Result<CorpusReport, LspError> {
+        let expectations = CorpusExpectations::load(dir)?;
+        let dir = corpus_root(dir)?;
+        let files = self.load_corpus(&dir)?;
+        let mut report = CorpusReport {
+            files: files.len(),
+            ..CorpusReport::default()
+        };
+
+        for expected in &expectations.files {
+            let Some((path, uri)) = files.iter().find(|(path, _)| *path == expected.path) else {
+                report.regressions.push(CorpusRegression {
+                    path: expected.path.clone(),
+                    position: None,
+                    check: CorpusCheck::MissingFile,
+                    expected: "file in corpus".to_string(),
+                    actual: "no file".to_string(),
+                });
+                continue;
+            };
+
+            let parse_errors = self.corpus_parse_errors(uri);
+            if parse_errors != expected.parse_errors {
+                report.regressions.push(CorpusRegression {
+                    path: path.clone(),
+                    position: None,
+                    check: CorpusCheck::ParseErrors,
+                    expected: expected.parse_errors.to_string(),
+                    actual: parse_errors.to_string(),
+                });
+            }
+
+            for probe in &expected.probes {
+                report.probes += 1;
+                let actual = self.probe(&dir, uri, probe.position);
+                let mut regression = |check, expected: String, actual: String| {
+                    report.regressions.push(CorpusRegression {
+                        path: path.clone(),
+                        position: Some(probe.position),
+                        check,
+                        expected,
+                        actual,
+                    });
+                };
+                if actual.hover != probe.hover {
+                    regression(
+                        CorpusCheck::Hover,
+                        probe.hover.clone().unwrap_or_default(),
+                        actual.hover.unwrap_or_default(),
+                    );
+                }
+                if actual.definition != probe.definition {
+                    let location = |location: Option<&ProbeLocation>| {
+                        location.map(ToString::to_string).unwrap_or_default()
+                    };
+                    regression(
+                        CorpusCheck::Definition,
+                        location(probe.definition.as_ref()),
+                        location(actual.definition.as_ref()),
+                    );
+                }
+            }
+        }
+
+        report.unrecorded_files = files
+            .into_iter()
+            .map(|(path, _)| path)
+            .filter(|path| !expectations.files.iter().any(|file| file.path == *path))
+            .collect();
+        Ok(report)
+    }
+
+    /// Load the corpus in `dir` into this server and record what it makes of
+    /// it, probing every reference
+    pub fn record_corpus(&mut self, dir: &Path) -> Result<CorpusExpectations, LspError> {
+        let dir = corpus_root(dir)?;
+        let files = self.load_corpus(&dir)?;
+        let mut expectations = CorpusExpectations::default();
+
+        for (path, uri) in files {
+            let positions = self.reference_starts(&uri);
+            let probes = positions
+                .into_iter()
+                .map(|position| self.probe(&dir, &uri, position))
+                .collect();
+            expectations.files.push(FileExpectations {
+                path,
+                parse_errors: self.corpus_parse_errors(&uri),
+                probes,
+            });
+        }
+        Ok(expectations)
+    }
+
+    /// Open every file of the corpus, returning their relative paths and URIs
+    /// ordered by path
+    fn load_corpus(&mut self, dir: &Path) -> Result<Vec<(String, Url)>, LspError> {
+        let paths = WorkspaceFilter::for_folder(dir, &[])
+            .collect_files()
+            .map_err(LspError::InvalidCorpus)?;
+
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let text = self.fs.read_to_string(&path).map_err(|err| {
+                LspError::InvalidCorpus(format!("failed to read {}: {err}", path.display()))
+            })?;
+            let uri = Url::from_file_path(&path).map_err(|_| {
+                LspError::InvalidCorpus(format!("not a file path: {}", path.display()))
+            })?;
+            self.open_document(&uri, &text)?;
+            files.push((relative_path(dir, &path), uri));
+        }
+        Ok(files)
+    }
+
+    fn corpus_parse_errors(&self, uri: &Url) -> usize {
+        uri.to_file_path()
+            .ok()
+            .and_then(|path| self.parse_errors.get(&path))
+            .map_or(0, Vec::len)
+    }
+
+    /// Hover and definition answered at a position
+    fn probe(&mut self, dir: &Path, uri: &Url, position: Position) -> Probe {
+        let hover = self
+            .get_hover(uri, position)
+            .map(|hover| relative_links(dir, &hover_text(hover)));
+        let definition = self.get_definition(uri, position).and_then(|location| {
+            let path = location.uri.to_file_path().ok()?;
+            Some(ProbeLocation {
+                path: relative_path(dir, &path),
+                range: location.range,
+            })
+        });
+        Probe {
+            position,
+            hover,
+            definition,
+        }
+    }
+
+    /// Start of every reference in a file, in source order
+    fn reference_starts(&mut self, uri: &Url) -> Vec<Position> {
+        let Ok(path) = uri.to_file_path() else {
+            return Vec::new();
+        };
+        let analysis = self.analysis_host.analysis();
+        let Some(file_id) = analysis.get_file_id(&path.to_string_lossy()) else {
+            return Vec::new();
+        };
+        let mut starts: Vec<Position> = analysis
+            .symbol_index()
+            .symbols_in_file(file_id)
+            .into_iter()
+            .flat_map(|symbol| symbol.type_refs.iter().flat_map(|trk| trk.as_refs()))
+            .map(|type_ref| Position::new(type_ref.start_line, type_ref.start_col))
+            .collect();
+        starts.sort_by_key(|position| (position.line, position.character));
+        starts.dedup();
+        starts
+    }
+}
+
+/// The corpus directory as an absolute path without symlinks, as files are
+/// loaded from it
+fn corpus_root(dir: &Path) -> Result<PathBuf, LspError> {
+    dir.canonicalize()
+        .map_err(|err| LspError::InvalidCorpus(format!("{}: {err}", dir.display())))
+}
+
+/// Path relative to the corpus with `/` separators, or the file name for
+/// paths outside it
+fn relative_path(dir: &Path, path: &Path) -> String {
+    match path.strip_prefix(dir) {
+        Ok(relative) => relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string_lossy().into_owned()),
+    }
+}
+
+/// Markdown with its `file://` link targets rewritten as corpus-relative
+/// paths, keeping their `#L` fragment
+fn relative_links(dir: &Path, text: &str) -> String {
+    const LINK: &str = "](file://";
+    let mut rewritten = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(LINK) {
+        let target_start = start + 2;
+        let Some(len) = rest[target_start..].find(')') else {
+            break;
+        };
+        let target = &rest[target_start..target_start + len];
+        let (url, fragment) = match target.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (target, None),
+        };
+        rewritten.push_str(&rest[..target_start]);
+        match Url::parse(url).ok().and_then(|url| url.to_file_path().ok()) {
+            Some(path) => {
+                rewritten.push_str(&relative_path(dir, &path));
+                if let Some(fragment) = fragment {
+                    rewritten.push('#');
+                    rewritten.push_str(fragment);
+                }
+            }
+            None => rewritten.push_str(target),
+        }
+        rest = &rest[target_start + len..];
+    }
+    rewritten.push_str(rest);
+    rewritten
+}
+
+fn hover_text(hover: Hover) -> String {
+    let marked = |marked: MarkedString| match marked {
+        MarkedString::String(text) => text,
+        MarkedString::LanguageString(code) => code.value,
+    };
+    match hover.contents {
+        HoverContents::Markup(markup) => markup.value,
+        HoverContents::Scalar(text) => marked(text),
+        HoverContents::Array(texts) => texts.into_iter().map(marked).collect::<Vec<_>>().join("\n"),
+    }
+}
@@ -1,6 +1,9 @@
 use super::LspServer;
+use super::connector_ends::resolve_chain;
 use super::helpers::uri_to_path;
 use async_lsp::lsp_types::{Location, Position, Range, Url};
+use syster::base::FileId;
+use syster::hir::{SymbolIndex, TypeRefKind};
 
 impl LspServer {
     /// Get the definition location for a symbol at the given position
@@ -10,11 +13,30 @@ impl LspServer {
         let path = uri_to_path(uri)?;
         let path_str = path.to_string_lossy();
 
-        let analysis = self.analysis_host.analysis();
+        let analysis = self
+            .root_libraries
+            .host(&path, &mut self.analysis_host)
+            .analysis();
 
         // Get file ID for the new HIR layer
         let file_id = analysis.get_file_id(&path_str)?;
 
+        // Each segment of a feature chain like `driver.p1` goes to the
+        // feature it names, following typing and subsetting along the chain
+        if let Some((scope, chain)) = chain_at(analysis.symbol_index(), file_id, position)
+            && let Some(feature) = resolve_chain(analysis.symbol_index(), &scope, &chain)
+            && let Some(def_path) = analysis.get_file_path(feature.file)
+            && let Ok(def_uri) = Url::from_file_path(def_path)
+        {
+            return Some(Location {
+                uri: def_uri,
+                range: Range {
+                    start: Position::new(feature.start_line, feature.start_col),
+                    end: Position::new(feature.end_line, feature.end_col),
+                },
+            });
+        }
+
         // Use the Analysis goto_definition method
         let result = analysis.goto_definition(file_id, position.line, position.character);
 
@@ -40,3 +62,35 @@ impl LspServer {
         })
     }
 }
+
+/// The feature chain reference with a segment at `position`, up to that
+/// segment, and the scope its first segment is named from
+fn chain_at(
+    index: &SymbolIndex,
+    file_id: FileId,
+    position: Position,
+) -> Option<(String, Vec<String>)> {
+    index
+        .symbols_in_file(file_id)
+        .into_iter()
+        .find_map(|symbol| {
+            symbol.type_refs.iter().find_map(|trk| {
+                let TypeRefKind::Chain(chain) = trk else {
+                    return None;
+                };
+                let at = chain.parts.iter().position(|part| {
+                    Position::new(part.start_line, part.start_col) <= position
+                        && position <= Position::new(part.end_line, part.end_col)
+                })?;
+                let scope = symbol
+                    .qualified_name
+                    .rsplit_once("::")
+                    .map_or("", |(owner, _)| owner);
+                let names = chain.parts[..=at]
+                    .iter()
+                    .map(|part| part.target.to_string())
+                    .collect();
+                Some((scope.to_string(), names))
+            })
+        })
+}
@@ -0,0 +1,135 @@
+//! Cross-file dependencies between documents
+//!
+//! A file depends on another when one of its references names something the
+//! other defines. Editing a definition (renaming `part def Vehicle`, removing
+//! it, turning it into an attribute def) changes what those references resolve
+//! to, so `parse_document` schedules the dependent files for re-validation and
+//! the caller publishes their diagnostics again. Dependents are found through
+//! the reference index, which records names as written: a reference to a
+//! renamed definition still matches its old name.
+
+use super::LspServer;
+use async_lsp::lsp_types::Url;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use syster::hir::SymbolKind as HirSymbolKind;
+
+/// Simple name and kind of a definition, keyed by qualified name
+type Definitions = HashMap<Arc<str>, (Arc<str>, HirSymbolKind)>;
+
+/// Definitions of edited files and the dependents waiting to be re-validated
+#[derive(Debug, Default)]
+pub(super) struct DependencyGraph {
+    /// What each edited file defined when it was last parsed
+    definitions: HashMap<PathBuf, Definitions>,
+    /// Files whose diagnostics may have changed with another file's definitions
+    pending: BTreeSet<PathBuf>,
+}
+
+impl DependencyGraph {
+    /// Whether the definitions of a file have been recorded
+    pub(super) fn is_tracked(&self, path: &Path) -> bool {
+        self.definitions.contains_key(path)
+    }
+
+    /// Record the definitions of a file, returning the simple names of those
+    /// added, removed or changed in kind since the last record
+    pub(super) fn update(&mut self, path: &Path, definitions: Definitions) -> HashSet<Arc<str>> {
+        let previous = self
+            .definitions
+            .insert(path.to_path_buf(), definitions)
+            .unwrap_or_default();
+        let current = &self.definitions[path];
+
+        let removed_or_changed = previous
+            .iter()
+            .filter(|(qname, (_, kind))| current.get(*qname).is_none_or(|(_, k)| k != kind));
+        let added = current
+            .iter()
+            .filter(|(qname, _)| !previous.contains_key(*qname));
+        removed_or_changed
+            .chain(added)
+            .map(|(_, (name, _))| name.clone())
+            .collect()
+    }
+
+    /// Stop tracking a file, e.g. when it is deleted
+    pub(super) fn remove(&mut self, path: &Path) {
+        self.definitions.remove(path);
+        self.pending.remove(path);
+    }
+}
+
+impl LspServer {
+    /// Qualified name, simple name and kind of every definition in a file
+    fn file_definitions(&mut self, path: &Path) -> Definitions {
+        let path_str = path.to_string_lossy();
+        let analysis = self.analysis_host.analysis();
+        let Some(file_id) = analysis.get_file_id(&path_str) else {
+            return Definitions::new();
+        };
+        analysis
+            .symbol_index()
+            .symbols_in_file(file_id)
+            .into_iter()
+            .filter(|sym| !matches!(sym.kind, HirSymbolKind::Import | HirSymbolKind::Comment))
+            .map(|sym| {
+                (
+                    sym.qualified_name.clone(),
+                    (Arc::from(sym.name.as_ref()), sym.kind),
+                )
+            })
+            .collect()
+    }
+
+    /// Record what a file defines before an edit is stored, so the next
+    /// [`schedule_dependents`](Self::schedule_dependents) can tell what changed
+    pub(super) fn track_definitions(&mut self, path: &Path) {
+        if !self.dependency_graph.is_tracked(path) {
+            let definitions = self.file_definitions(path);
+            self.dependency_graph.update(path, definitions);
+        }
+    }
+
+    /// Schedule re-validation of the files referencing a definition that the
+    /// last parse of `path` added, removed or changed
+    pub(super) fn schedule_dependents(&mut self, path: &Path) {
+        let definitions = self.file_definitions(path);
+        let changed = self.dependency_graph.update(path, definitions);
+        if changed.is_empty() {
+            return;
+        }
+
+        let analysis = self.analysis_host.analysis();
+        self.reference_index.sync(&analysis);
+        let dependents: Vec<PathBuf> = self
+            .document_texts
+            .keys()
+            .filter(|dependent| {
+                dependent.as_path() != path
+                    && (self.document_versions.contains_key(*dependent)
+                        || self.workspace_root(dependent).is_some())
+            })
+            .filter(|dependent| {
+                self.reference_index.entries(dependent).iter().any(|entry| {
+                    entry
+                        .target
+                        .split("::")
+                        .any(|segment| changed.contains(segment))
+                })
+            })
+            .cloned()
+            .collect();
+        self.dependency_graph.pending.extend(dependents);
+    }
+
+    /// Take the documents scheduled for re-validation by edits to the files
+    /// they depend on, in path order
+    pub fn take_dependent_documents(&mut self) -> Vec<Url> {
+        std::mem::take(&mut self.dependency_graph.pending)
+            .into_iter()
+            .filter_map(|path| Url::from_file_path(path).ok())
+            .collect()
+    }
+}
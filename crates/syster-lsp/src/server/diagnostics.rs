@@ -1,7 +1,28 @@
 use super::LspServer;
+use super::code_actions::{
+    DuplicateRelationshipFix, FallbackImport, NamingFix, RelationshipFix, UnresolvedReference,
+    UnusedImportFix,
+};
+use super::connection_check::{PORT_CONJUGATION_CODE, check_connections};
+use super::connector_ends::is_conjugated;
+use super::diagnostics_store::DiagnosticSet;
+use super::direction_check::{DIRECTION_MISUSE_CODE, check_directions};
 use super::helpers::{position_to_lsp_position, uri_to_path};
-use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range, Url};
-use syster::hir::{Severity as HirSeverity, check_file};
+use super::language::Language;
+use super::naming_check::{NAMING_CONVENTION_CODE, check_naming};
+use super::pragmas::LintSuppression;
+use super::relationship_check::{RELATIONSHIP_KIND_CODE, check_relationships};
+use super::relationship_duplicates::{DUPLICATE_RELATIONSHIP_CODE, check_duplicate_relationships};
+use super::scope_check::{FALLBACK_RESOLUTION_CODE, check_scopes, fallback_resolutions};
+use super::unused_imports::{UNUSED_IMPORT_CODE, check_unused_imports};
+use async_lsp::lsp_types::{
+    Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString, Position, Range, Url,
+};
+use std::collections::HashMap;
+use syster::hir::{HirSymbol, Severity as HirSeverity, SymbolIndex, check_file};
+
+/// Code of the semantic checker's undefined reference diagnostics
+pub(super) const UNDEFINED_REFERENCE_CODE: &str = "E0001";
 
 impl LspServer {
     /// Get LSP diagnostics for a given file (parse errors + semantic errors)
@@ -34,38 +55,250 @@ impl LspServer {
 
         // 2. Add semantic diagnostics (only if no parse errors - semantic checks need valid AST)
         if diagnostics.is_empty() {
-            let analysis = self.analysis_host.analysis();
+            // Imports may still be missing while the workspace is indexed
+            let indexing = self.is_indexing();
+            // The reference index holds every root's files, so it follows the shared index
+            if !indexing {
+                let shared = self.analysis_host.analysis();
+                self.reference_index.sync(&shared);
+            }
+            let analysis = self
+                .root_libraries
+                .host(&path, &mut self.analysis_host)
+                .analysis();
             let path_str = path.to_string_lossy();
             if let Some(file_id) = analysis.get_file_id(&path_str) {
-                let semantic_diags = check_file(analysis.symbol_index(), file_id);
+                let index = analysis.symbol_index();
+                let semantic_diags = check_file(index, file_id);
+                // Undefined references reported per element, which the checker lists in order
+                let mut reported: HashMap<Position, usize> = HashMap::new();
                 for diag in semantic_diags {
-                    diagnostics.push(Diagnostic {
-                        range: Range {
-                            start: Position {
-                                line: diag.start_line,
-                                character: diag.start_col,
-                            },
-                            end: Position {
-                                line: diag.end_line,
-                                character: diag.end_col,
-                            },
+                    let mut range = Range {
+                        start: Position {
+                            line: diag.start_line,
+                            character: diag.start_col,
+                        },
+                        end: Position {
+                            line: diag.end_line,
+                            character: diag.end_col,
                         },
+                    };
+                    let mut message = diag.message.to_string();
+                    let mut data = None;
+
+                    if diag.code.as_deref() == Some(UNDEFINED_REFERENCE_CODE) {
+                        if indexing {
+                            continue;
+                        }
+                        // Point at the reference itself rather than the referencing element
+                        let nth = reported.entry(range.start).or_default();
+                        if let Some((name, reference)) =
+                            unresolved_references(index, file_id, range.start).nth(*nth)
+                        {
+                            message = format!("unresolved reference '{name}'");
+                            range = reference;
+                            data = serde_json::to_value(UnresolvedReference {
+                                name: name.to_string(),
+                            })
+                            .ok();
+                        }
+                        *nth += 1;
+                    }
+
+                    diagnostics.push(Diagnostic {
+                        range,
                         severity: Some(hir_severity_to_lsp(diag.severity)),
-                        code: diag
-                            .code
-                            .map(|c| async_lsp::lsp_types::NumberOrString::String(c.to_string())),
-                        message: diag.message.to_string(),
+                        code: diag.code.map(|c| NumberOrString::String(c.to_string())),
+                        message,
                         source: Some("syster-semantic".to_string()),
+                        data,
                         ..Default::default()
                     });
                 }
+
+                // Duplicate names and redefinitions of features that aren't inherited
+                for issue in check_scopes(index, file_id) {
+                    diagnostics.push(semantic_diagnostic(
+                        issue.range,
+                        DiagnosticSeverity::ERROR,
+                        issue.code,
+                        issue.message,
+                    ));
+                }
+
+                // Private imports nothing in their package refers to
+                if !indexing {
+                    if let Some(text) = self.document_texts.get(&path) {
+                        let entries = self.reference_index.entries(&path);
+                        for unused in check_unused_imports(index, file_id, text, entries) {
+                            let fix = unused.removal.map(|removal| UnusedImportFix {
+                                path: unused.path,
+                                removal,
+                            });
+                            diagnostics.push(Diagnostic {
+                                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                                data: fix.and_then(|fix| serde_json::to_value(fix).ok()),
+                                ..semantic_diagnostic(
+                                    unused.range,
+                                    DiagnosticSeverity::HINT,
+                                    UNUSED_IMPORT_CODE,
+                                    unused.message,
+                                )
+                            });
+                        }
+                    }
+                }
+
+                // References that only resolve through the global name search
+                if !indexing {
+                    for resolution in fallback_resolutions(index, file_id) {
+                        let fix = FallbackImport {
+                            target: resolution.target.clone(),
+                        };
+                        let message = format!(
+                            "'{}' resolved by fallback to '{}'; add an import to make this explicit",
+                            resolution.name, resolution.target
+                        );
+                        diagnostics.push(Diagnostic {
+                            data: serde_json::to_value(fix).ok(),
+                            ..semantic_diagnostic(
+                                resolution.range,
+                                DiagnosticSeverity::INFORMATION,
+                                FALLBACK_RESOLUTION_CODE,
+                                message,
+                            )
+                        });
+                    }
+                }
+
+                // Relationship keywords that don't fit their target
+                if let Some(text) = self.document_texts.get(&path) {
+                    for issue in check_relationships(index, file_id, text) {
+                        let fix = RelationshipFix {
+                            operator: issue.operator,
+                            replacement: issue.replacement,
+                        };
+                        diagnostics.push(Diagnostic {
+                            data: serde_json::to_value(fix).ok(),
+                            ..semantic_diagnostic(
+                                issue.range,
+                                DiagnosticSeverity::WARNING,
+                                RELATIONSHIP_KIND_CODE,
+                                issue.message,
+                            )
+                        });
+                    }
+                }
+
+                // Relationships an element declares more than once
+                if let Some(text) = self.document_texts.get(&path) {
+                    for duplicate in check_duplicate_relationships(index, file_id, text) {
+                        let fix = DuplicateRelationshipFix {
+                            target: duplicate.target,
+                            removal: duplicate.removal,
+                        };
+                        diagnostics.push(Diagnostic {
+                            data: serde_json::to_value(fix).ok(),
+                            ..semantic_diagnostic(
+                                duplicate.range,
+                                DiagnosticSeverity::WARNING,
+                                DUPLICATE_RELATIONSHIP_CODE,
+                                duplicate.message,
+                            )
+                        });
+                    }
+                }
+
+                // Connections between ports that don't fit together
+                if let Some(text) = self.document_texts.get(&path) {
+                    let is_conjugated =
+                        |sym: &HirSymbol| is_conjugated(&analysis, &self.document_texts, sym);
+                    for issue in check_connections(index, text, &is_conjugated) {
+                        diagnostics.push(semantic_diagnostic(
+                            issue.range,
+                            issue.severity,
+                            PORT_CONJUGATION_CODE,
+                            issue.message,
+                        ));
+                    }
+                }
+
+                // Names breaking the naming convention, when enabled
+                if self.naming_conventions {
+                    for issue in check_naming(index, file_id) {
+                        let fix = NamingFix {
+                            name: issue.name,
+                            replacement: issue.replacement,
+                            case: issue.case.label().to_string(),
+                        };
+                        diagnostics.push(Diagnostic {
+                            data: serde_json::to_value(fix).ok(),
+                            ..semantic_diagnostic(
+                                issue.range,
+                                DiagnosticSeverity::INFORMATION,
+                                NAMING_CONVENTION_CODE,
+                                issue.message,
+                            )
+                        });
+                    }
+                }
+            }
+
+            // 3. Parameter direction misuse in action bodies
+            let is_sysml = Language::of(&path) == Some(Language::SysML);
+            if is_sysml && let Some(text) = self.document_texts.get(&path) {
+                for issue in check_directions(analysis.symbol_index(), text) {
+                    diagnostics.push(semantic_diagnostic(
+                        issue.range,
+                        issue.severity,
+                        DIRECTION_MISUSE_CODE,
+                        issue.message,
+                    ));
+                }
             }
         }
 
+        // 4. Cross-file findings of the last project validation
+        diagnostics.extend(self.project_validation.findings(&path).iter().cloned());
+
+        // 5. Rules turned off by `// syster:lint off` pragmas
+        if let Some(text) = self.document_texts.get(&path)
+            && let Some(suppression) = LintSuppression::from_text(text)
+        {
+            diagnostics.retain(|diagnostic| !suppression.suppresses(diagnostic));
+        }
+
+        // Overlapping passes can report the same finding; keep one, in range order
         diagnostics
+            .into_iter()
+            .collect::<DiagnosticSet>()
+            .into_vec()
     }
 }
 
+/// The references of the symbol declared at `start` that didn't resolve,
+/// with their spans
+fn unresolved_references<'a>(
+    index: &'a SymbolIndex,
+    file: syster::base::FileId,
+    start: Position,
+) -> impl Iterator<Item = (&'a str, Range)> {
+    index
+        .symbols_in_file(file)
+        .into_iter()
+        .find(|sym| sym.start_line == start.line && sym.start_col == start.character)
+        .into_iter()
+        .flat_map(|symbol: &HirSymbol| symbol.type_refs.iter().flat_map(|trk| trk.as_refs()))
+        .filter(|type_ref| type_ref.resolved_target.is_none())
+        .map(|type_ref| {
+            let range = Range {
+                start: Position::new(type_ref.start_line, type_ref.start_col),
+                end: Position::new(type_ref.end_line, type_ref.end_col),
+            };
+            (type_ref.target.as_ref(), range)
+        })
+}
+
 /// Convert HIR severity to LSP severity
 fn hir_severity_to_lsp(severity: HirSeverity) -> DiagnosticSeverity {
     match severity {
@@ -0,0 +1,299 @@
+//! Deduplicated, ordered diagnostics per document
+//!
+//! Several passes can report the same finding (e.g. the semantic checker and
+//! a text-based check both flagging one reference), and the order of their
+//! output isn't stable. Diagnostics are collected into a [`DiagnosticSet`],
+//! which keeps one copy of each finding and orders them by range, then
+//! severity, so consecutive publishes of an unchanged document are identical.
+//! The [`DiagnosticsStore`] remembers what was last published for each
+//! document, so unchanged diagnostics aren't sent again.
+//!
+//! Clients that pull diagnostics (`textDocument/diagnostic` and
+//! `workspace/diagnostic`) get a result ID with each report. The ID changes
+//! only when the diagnostics do, so a pull with the current ID is answered
+//! with an unchanged report, and nothing is pushed to such clients. A
+//! `workspace/diagnostic` pull that would only get unchanged reports is held
+//! until the diagnostics of some document change.
+
+use super::LspServer;
+use async_lsp::lsp_types::{
+    Diagnostic, DiagnosticSeverity, DocumentDiagnosticReport, DocumentDiagnosticReportKind,
+    DocumentDiagnosticReportResult, FullDocumentDiagnosticReport, NumberOrString, PreviousResultId,
+    RelatedFullDocumentDiagnosticReport, RelatedUnchangedDocumentDiagnosticReport,
+    UnchangedDocumentDiagnosticReport, Url, WorkspaceDiagnosticReport,
+    WorkspaceDiagnosticReportResult, WorkspaceDocumentDiagnosticReport,
+    WorkspaceFullDocumentDiagnosticReport, WorkspaceUnchangedDocumentDiagnosticReport,
+};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Diagnostics with set semantics: one per code, range and message, sorted
+/// by range, then severity (most severe first), code and message
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiagnosticSet {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticSet {
+    /// Add a diagnostic, returning false if the set already has the finding.
+    ///
+    /// A duplicate reported with a higher severity replaces the earlier copy.
+    pub fn insert(&mut self, diagnostic: Diagnostic) -> bool {
+        let range = (diagnostic.range.start, diagnostic.range.end);
+        let same_range = self
+            .diagnostics
+            .partition_point(|existing| (existing.range.start, existing.range.end) < range);
+        let duplicate = self.diagnostics[same_range..]
+            .iter()
+            .take_while(|existing| existing.range == diagnostic.range)
+            .position(|existing| {
+                existing.code == diagnostic.code && existing.message == diagnostic.message
+            })
+            .map(|idx| same_range + idx);
+
+        if let Some(idx) = duplicate {
+            if severity_rank(&diagnostic) >= severity_rank(&self.diagnostics[idx]) {
+                return false;
+            }
+            self.diagnostics.remove(idx);
+        }
+        let idx = self
+            .diagnostics
+            .partition_point(|existing| compare(existing, &diagnostic) == Ordering::Less);
+        self.diagnostics.insert(idx, diagnostic);
+        duplicate.is_none()
+    }
+
+    pub fn as_slice(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+}
+
+impl FromIterator<Diagnostic> for DiagnosticSet {
+    fn from_iter<I: IntoIterator<Item = Diagnostic>>(iter: I) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Diagnostic> for DiagnosticSet {
+    fn extend<I: IntoIterator<Item = Diagnostic>>(&mut self, iter: I) {
+        for diagnostic in iter {
+            self.insert(diagnostic);
+        }
+    }
+}
+
+/// Diagnostics last published for each document
+#[derive(Debug, Default)]
+pub struct DiagnosticsStore {
+    published: HashMap<PathBuf, DiagnosticSet>,
+    /// Diagnostics last reported to a pulling client, with their result ID
+    reported: HashMap<PathBuf, (String, DiagnosticSet)>,
+    next_result_id: u64,
+}
+
+impl DiagnosticsStore {
+    /// Record the diagnostics of a document, returning them if they differ
+    /// from those last recorded
+    pub fn update(&mut self, path: PathBuf, diagnostics: DiagnosticSet) -> Option<Vec<Diagnostic>> {
+        if self.published.get(&path) == Some(&diagnostics) {
+            return None;
+        }
+        let published = diagnostics.as_slice().to_vec();
+        self.published.insert(path, diagnostics);
+        Some(published)
+    }
+
+    /// Report the diagnostics of a document to a pulling client: unchanged if
+    /// they are the ones reported with `previous_result_id`, in full otherwise
+    pub fn report(
+        &mut self,
+        path: PathBuf,
+        diagnostics: DiagnosticSet,
+        previous_result_id: Option<&str>,
+    ) -> DocumentDiagnosticReportKind {
+        let result_id = match self.reported.get(&path) {
+            Some((result_id, reported)) if *reported == diagnostics => result_id.clone(),
+            _ => {
+                self.next_result_id += 1;
+                let result_id = self.next_result_id.to_string();
+                self.reported
+                    .insert(path, (result_id.clone(), diagnostics.clone()));
+                result_id
+            }
+        };
+        if previous_result_id == Some(result_id.as_str()) {
+            DocumentDiagnosticReportKind::Unchanged(UnchangedDocumentDiagnosticReport { result_id })
+        } else {
+            DocumentDiagnosticReportKind::Full(FullDocumentDiagnosticReport {
+                result_id: Some(result_id),
+                items: diagnostics.into_vec(),
+            })
+        }
+    }
+
+    /// Forget a document's diagnostics, so the next update publishes them
+    pub fn remove(&mut self, path: &Path) {
+        self.published.remove(path);
+        self.reported.remove(path);
+    }
+}
+
+impl LspServer {
+    /// Diagnostics to publish for a document, or None if they are the ones
+    /// last published for it or the client pulls diagnostics instead
+    pub fn diagnostics_to_publish(&mut self, uri: &Url) -> Option<Vec<Diagnostic>> {
+        if self.client_support.pull_diagnostics {
+            return None;
+        }
+        let diagnostics: DiagnosticSet = self.get_diagnostics(uri).into_iter().collect();
+        let Ok(path) = uri.to_file_path() else {
+            return Some(diagnostics.into_vec());
+        };
+        self.diagnostics_store.update(path, diagnostics)
+    }
+
+    /// Answer `textDocument/diagnostic` for a document
+    pub fn get_document_diagnostic_report(
+        &mut self,
+        uri: &Url,
+        previous_result_id: Option<&str>,
+    ) -> DocumentDiagnosticReportResult {
+        let report = match self.diagnostic_report(uri, previous_result_id) {
+            DocumentDiagnosticReportKind::Full(report) => {
+                DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                    related_documents: None,
+                    full_document_diagnostic_report: report,
+                })
+            }
+            DocumentDiagnosticReportKind::Unchanged(report) => {
+                DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                    related_documents: None,
+                    unchanged_document_diagnostic_report: report,
+                })
+            }
+        };
+        DocumentDiagnosticReportResult::Report(report)
+    }
+
+    /// Answer `workspace/diagnostic` with a report for every open document and
+    /// every document in a workspace folder
+    pub fn get_workspace_diagnostic_report(
+        &mut self,
+        previous_result_ids: &[PreviousResultId],
+    ) -> WorkspaceDiagnosticReportResult {
+        let mut paths: Vec<PathBuf> = self
+            .document_texts
+            .keys()
+            .filter(|path| {
+                self.document_versions.contains_key(*path) || self.workspace_root(path).is_some()
+            })
+            .cloned()
+            .collect();
+        paths.sort();
+
+        let mut items = Vec::with_capacity(paths.len());
+        for path in paths {
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            let previous = previous_result_ids
+                .iter()
+                .find(|previous| previous.uri == uri)
+                .map(|previous| previous.value.as_str());
+            let version = self
+                .document_versions
+                .get(&path)
+                .map(|version| version.get() as i64);
+            items.push(match self.diagnostic_report(&uri, previous) {
+                DocumentDiagnosticReportKind::Full(report) => {
+                    WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                        uri,
+                        version,
+                        full_document_diagnostic_report: report,
+                    })
+                }
+                DocumentDiagnosticReportKind::Unchanged(report) => {
+                    WorkspaceDocumentDiagnosticReport::Unchanged(
+                        WorkspaceUnchangedDocumentDiagnosticReport {
+                            uri,
+                            version,
+                            unchanged_document_diagnostic_report: report,
+                        },
+                    )
+                }
+            });
+        }
+        WorkspaceDiagnosticReportResult::Report(WorkspaceDiagnosticReport { items })
+    }
+
+    /// Answer `workspace/diagnostic` if the diagnostics of some document
+    /// differ from those reported with `previous_result_ids`, or None so the
+    /// request can be held until they do
+    pub fn changed_workspace_diagnostics(
+        &mut self,
+        previous_result_ids: &[PreviousResultId],
+    ) -> Option<WorkspaceDiagnosticReportResult> {
+        let result = self.get_workspace_diagnostic_report(previous_result_ids);
+        let changed = match &result {
+            WorkspaceDiagnosticReportResult::Report(report) => {
+                previous_result_ids.is_empty()
+                    || report
+                        .items
+                        .iter()
+                        .any(|item| matches!(item, WorkspaceDocumentDiagnosticReport::Full(_)))
+            }
+            WorkspaceDiagnosticReportResult::Partial(_) => true,
+        };
+        changed.then_some(result)
+    }
+
+    /// Diagnostics of a document as a pull report, parsing edits that are
+    /// still waiting for the debounce so the report is current
+    fn diagnostic_report(
+        &mut self,
+        uri: &Url,
+        previous_result_id: Option<&str>,
+    ) -> DocumentDiagnosticReportKind {
+        let Ok(path) = uri.to_file_path() else {
+            return DocumentDiagnosticReportKind::Full(FullDocumentDiagnosticReport {
+                result_id: None,
+                items: self.get_diagnostics(uri),
+            });
+        };
+        if self.health.is_pending(&path) {
+            self.parse_document(uri);
+        }
+        let diagnostics: DiagnosticSet = self.get_diagnostics(uri).into_iter().collect();
+        self.diagnostics_store
+            .report(path, diagnostics, previous_result_id)
+    }
+}
+
+/// Errors first; diagnostics without a severity last
+fn severity_rank(diagnostic: &Diagnostic) -> (bool, Option<DiagnosticSeverity>) {
+    (diagnostic.severity.is_none(), diagnostic.severity)
+}
+
+fn code_key(code: &Option<NumberOrString>) -> String {
+    match code {
+        Some(NumberOrString::String(code)) => code.clone(),
+        Some(NumberOrString::Number(code)) => code.to_string(),
+        None => String::new(),
+    }
+}
+
+fn compare(a: &Diagnostic, b: &Diagnostic) -> Ordering {
+    (a.range.start, a.range.end)
+        .cmp(&(b.range.start, b.range.end))
+        .then_with(|| severity_rank(a).cmp(&severity_rank(b)))
+        .then_with(|| code_key(&a.code).cmp(&code_key(&b.code)))
+        .then_with(|| a.message.cmp(&b.message))
+}
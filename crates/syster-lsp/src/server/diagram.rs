@@ -4,14 +4,30 @@
 //! by the diagram-core TypeScript package.
 //!
 //! IMPORTANT: The `node_type` field MUST match the NODE_TYPES values in
-//! packages/diagram-core/src/sysml-nodes.ts. If they don't match, nodes
-//! won't render in the diagram.
+//! packages/diagram-core/src/sysml-nodes.ts, and the relationships' `type`
+//! the EDGE_TYPES values. If they don't match, nodes and edges won't render
+//! in the diagram.
 
 use super::LspServer;
+use super::diagram_sequence::{SEQUENCE_VIEW, SequenceDiagram};
+use super::schema::SCHEMA_VERSION;
+use super::type_hierarchy::redefined_feature;
 use async_lsp::lsp_types::request::Request;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::Path;
-use syster::hir::{HirSymbol, SymbolKind};
+use syster::hir::{HirSymbol, RefKind, SymbolIndex, SymbolKind};
+
+/// Edge from a usage to its type (`:`)
+pub const TYPING_EDGE: &str = "typing";
+/// Edge from an element to one it specializes (`:>` on definitions)
+pub const SPECIALIZATION_EDGE: &str = "specialization";
+/// Edge from a feature to one it redefines (`:>>`)
+pub const REDEFINITION_EDGE: &str = "redefinition";
+/// Edge from a feature to one it subsets (`:>` on usages)
+pub const SUBSETTING_EDGE: &str = "subsetting";
+/// Edge from an element to a feature it owns
+pub const COMPOSITION_EDGE: &str = "composition";
 
 /// Custom LSP request: syster/getDiagram
 pub enum GetDiagramRequest {}
@@ -29,13 +45,27 @@ pub struct GetDiagramParams {
     /// URI of the file to get diagram for (optional - if None, returns whole workspace)
     pub uri: Option<String>,
 
+    /// Qualified name of an element to scope the diagram to (optional - the
+    /// element and everything it owns). Also accepted as `rootQualifiedName`.
+    #[serde(
+        default,
+        alias = "rootQualifiedName",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub element: Option<String>,
+
+    /// Levels of owned members to show below `element` (optional - all of
+    /// them if None); 1 shows only its direct members
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+
     /// View type to use for rendering (from StandardViewDefinitions)
     /// Defaults to "GeneralView" if not specified
     #[serde(default = "default_view_type")]
     pub view_type: String,
 }
 
-fn default_view_type() -> String {
+pub(super) fn default_view_type() -> String {
     "GeneralView".to_string()
 }
 
@@ -89,15 +119,28 @@ pub struct DiagramRelationship {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DiagramData {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
     pub symbols: Vec<DiagramSymbol>,
     pub relationships: Vec<DiagramRelationship>,
     pub view_type: String,
+    /// Lifelines, ordered messages and activations, for the "SequenceView" view type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<SequenceDiagram>,
 }
 
 impl LspServer {
-    /// Get diagram data for the workspace or a specific file.
+    /// Get diagram data for the workspace or a specific file, optionally
+    /// scoped to one element and its owned members up to `depth` levels down.
     /// Returns raw symbol data - presentation logic belongs in the frontend.
-    pub fn get_diagram(&mut self, file_path: Option<&Path>, view_type: &str) -> DiagramData {
+    pub fn get_diagram(
+        &mut self,
+        file_path: Option<&Path>,
+        element: Option<&str>,
+        depth: Option<usize>,
+        view_type: &str,
+    ) -> DiagramData {
         let mut symbols = Vec::new();
         let mut relationships = Vec::new();
 
@@ -115,27 +158,101 @@ impl LspServer {
             Box::new(analysis.symbol_index().all_symbols())
         };
 
+        let in_scope = |symbol: &HirSymbol| {
+            element.is_none_or(|element| {
+                symbol.qualified_name.as_ref() == element
+                    || symbol
+                        .qualified_name
+                        .strip_prefix(element)
+                        .and_then(|rest| rest.strip_prefix("::"))
+                        .is_some_and(|member| {
+                            depth.is_none_or(|depth| member.split("::").count() <= depth)
+                        })
+            })
+        };
+
         // Convert all symbols - frontend decides how to display them
-        for symbol in symbol_iter {
+        for symbol in symbol_iter.filter(|symbol| in_scope(symbol)) {
             if let Some(diagram_symbol) = convert_symbol_to_diagram(symbol) {
-                // Extract typing relationship from the symbol itself
-                if let Some(ref typed_by) = diagram_symbol.typed_by {
-                    relationships.push(DiagramRelationship {
-                        rel_type: "typing".to_string(),
-                        source: diagram_symbol.qualified_name.clone(),
-                        target: typed_by.clone(),
-                    });
-                }
+                relationships.extend(relationship_edges(analysis.symbol_index(), symbol));
                 symbols.push(diagram_symbol);
             }
         }
 
+        // Features owned by definitions and usages; package members are
+        // shown nested through `parent` instead
+        let owners: HashSet<&str> = symbols
+            .iter()
+            .filter(|symbol| symbol.node_type != "Package")
+            .map(|symbol| symbol.qualified_name.as_str())
+            .collect();
+        let composition: Vec<DiagramRelationship> = symbols
+            .iter()
+            .filter_map(|symbol| {
+                let parent = symbol.parent.as_deref()?;
+                owners.contains(parent).then(|| DiagramRelationship {
+                    rel_type: COMPOSITION_EDGE.to_string(),
+                    source: parent.to_string(),
+                    target: symbol.qualified_name.clone(),
+                })
+            })
+            .collect();
+        relationships.extend(composition);
+
+        let sequence = (view_type == SEQUENCE_VIEW)
+            .then(|| self.sequence_diagram(file_path, element, &symbols));
+
         DiagramData {
+            schema_version: SCHEMA_VERSION,
             symbols,
             relationships,
             view_type: view_type.to_string(),
+            sequence,
+        }
+    }
+}
+
+/// Typing, specialization, redefinition and subsetting edges of a symbol, to
+/// the resolved target where there is one
+fn relationship_edges(index: &SymbolIndex, symbol: &HirSymbol) -> Vec<DiagramRelationship> {
+    let mut edges: Vec<DiagramRelationship> = Vec::new();
+    // A feature chain's edge goes to the feature at its end
+    for type_ref in symbol
+        .type_refs
+        .iter()
+        .filter_map(|trk| trk.as_refs().last().copied())
+    {
+        let rel_type = match type_ref.kind {
+            RefKind::TypedBy => TYPING_EDGE,
+            RefKind::Specializes => SPECIALIZATION_EDGE,
+            RefKind::Redefines => REDEFINITION_EDGE,
+            RefKind::Subsets => SUBSETTING_EDGE,
+            _ => continue,
+        };
+        // A same-named `:>> x` resolves to the redefining feature itself
+        let redefined = matches!(type_ref.kind, RefKind::Redefines)
+            .then(|| redefined_feature(index, symbol, &type_ref.target))
+            .flatten();
+        let target = match redefined {
+            Some(redefined) => &*redefined.qualified_name,
+            None => type_ref
+                .resolved_target
+                .as_deref()
+                .unwrap_or(&*type_ref.target),
+        };
+        if target != &*symbol.qualified_name
+            && !edges
+                .iter()
+                .any(|edge| edge.rel_type == rel_type && edge.target == target)
+        {
+            edges.push(DiagramRelationship {
+                rel_type: rel_type.to_string(),
+                source: symbol.qualified_name.to_string(),
+                target: target.to_string(),
+            });
         }
     }
+    edges
 }
 
 /// Convert a HirSymbol to DiagramSymbol
@@ -266,6 +383,7 @@ mod tests {
     #[test]
     fn test_diagram_data_serialization() {
         let data = DiagramData {
+            schema_version: SCHEMA_VERSION,
             symbols: vec![DiagramSymbol {
                 name: "Test".to_string(),
                 qualified_name: "Pkg::Test".to_string(),
@@ -281,6 +399,7 @@ mod tests {
                 target: "Pkg::B".to_string(),
             }],
             view_type: "GeneralView".to_string(),
+            sequence: None,
         };
 
         let json = serde_json::to_string(&data).unwrap();
@@ -293,6 +412,11 @@ mod tests {
             "Should include viewType in camelCase: {}",
             json
         );
+        assert!(
+            !json.contains("\"sequence\""),
+            "Only sequence views: {}",
+            json
+        );
     }
 
     /// Test that GetDiagramParams deserializes with default view_type
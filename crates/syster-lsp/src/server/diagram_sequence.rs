@@ -0,0 +1,354 @@
+//! Sequence data for the `SequenceView` diagram view.
+//!
+//! Messages between parts (`message of cmd : Cmd from driver.turnOn to
+//! vehicle.trigger;`) become ordered edges between lifelines - the parts at the
+//! ends of each message - so clients can draw sequence diagrams. Messages are
+//! ordered as declared, adjusted by successions between named messages
+//! (`first sendCmd then sendStatus;`). A lifeline's activation is a run of
+//! consecutive messages it sends or receives.
+//!
+//! The HIR doesn't record message endpoints or successions, so they are read
+//! from the source text of the diagram's scope.
+
+use super::LspServer;
+use super::diagram::DiagramSymbol;
+use super::tokens::{Token, TokenKind, tokenize};
+use async_lsp::lsp_types::Position;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// View type that gets sequence data
+pub const SEQUENCE_VIEW: &str = "SequenceView";
+
+/// Lifelines, ordered messages and activations of a sequence view
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceDiagram {
+    /// Lifelines in order of their first message
+    pub lifelines: Vec<Lifeline>,
+    /// Messages in sequence order
+    pub messages: Vec<SequenceMessage>,
+    pub activations: Vec<Activation>,
+}
+
+/// A part taking part in messages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lifeline {
+    /// Name as written in message endpoints (e.g., "driver")
+    pub name: String,
+    /// Qualified name of the part, if it is declared in the diagram's scope
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+}
+
+/// A message from one lifeline to another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceMessage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Name of the payload feature (`ignitionCmd` in `of ignitionCmd : IgnitionCmd`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+    /// Type of the payload
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_type: Option<String>,
+    /// Sending lifeline
+    pub from: String,
+    /// Receiving lifeline
+    pub to: String,
+    /// Event on the sending lifeline (`turnOn` in `from driver.turnOn`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_event: Option<String>,
+    /// Event on the receiving lifeline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_event: Option<String>,
+}
+
+/// Consecutive messages, by index into `messages`, that a lifeline sends or receives
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Activation {
+    pub lifeline: String,
+    pub first_message: usize,
+    pub last_message: usize,
+}
+
+impl LspServer {
+    /// Sequence data for a diagram of a file or element, or of all open documents
+    pub(super) fn sequence_diagram(
+        &mut self,
+        file_path: Option<&Path>,
+        element: Option<&str>,
+        symbols: &[DiagramSymbol],
+    ) -> SequenceDiagram {
+        let mut sources: Vec<(PathBuf, Option<Position>)> = Vec::new();
+        if let Some(element) = element {
+            let analysis = self.analysis_host.analysis();
+            if let Some(symbol) = analysis.symbol_index().lookup_qualified(element)
+                && let Some(path) = analysis.get_file_path(symbol.file)
+            {
+                let position = Position::new(symbol.start_line, symbol.start_col);
+                sources.push((PathBuf::from(path), Some(position)));
+            }
+        } else if let Some(path) = file_path {
+            sources.push((path.to_path_buf(), None));
+        } else {
+            sources.extend(self.document_texts.keys().map(|path| (path.clone(), None)));
+            sources.sort();
+        }
+
+        let mut statements = Statements::default();
+        for (path, position) in sources {
+            let text = match self.document_texts.get(&path) {
+                Some(text) => text.clone(),
+                None => match self.fs.read_to_string(&path) {
+                    Ok(text) => text,
+                    Err(_) => continue,
+                },
+            };
+            let tokens = tokenize(&text);
+            let scope = match position {
+                Some(position) => body_of(&tokens, position),
+                None => &tokens[..],
+            };
+            statements.read(scope);
+        }
+        statements.into_diagram(symbols)
+    }
+}
+
+/// Tokens of the body of the element declared at `position`; empty if it has none
+fn body_of<'t, 'a>(tokens: &'t [Token<'a>], position: Position) -> &'t [Token<'a>] {
+    let start = tokens.partition_point(|token| token.start < position);
+    let Some(open) = tokens[start..]
+        .iter()
+        .position(|token| matches!(token.kind, TokenKind::LBrace | TokenKind::Semi))
+        .map(|idx| start + idx)
+        .filter(|&idx| tokens[idx].kind == TokenKind::LBrace)
+    else {
+        return &[];
+    };
+
+    let mut depth = 0;
+    for (idx, token) in tokens.iter().enumerate().skip(open) {
+        match token.kind {
+            TokenKind::LBrace => depth += 1,
+            TokenKind::RBrace => {
+                depth -= 1;
+                if depth == 0 {
+                    return &tokens[open + 1..idx];
+                }
+            }
+            _ => {}
+        }
+    }
+    &tokens[open + 1..]
+}
+
+/// The text of a name token
+fn word<'a>(token: &Token<'a>) -> Option<&'a str> {
+    (token.kind == TokenKind::Word).then_some(token.text)
+}
+
+/// Messages and successions read from source, in declaration order
+#[derive(Default)]
+struct Statements {
+    messages: Vec<SequenceMessage>,
+    /// (earlier, later) message names
+    successions: Vec<(String, String)>,
+}
+
+impl Statements {
+    fn read(&mut self, tokens: &[Token<'_>]) {
+        for (idx, token) in tokens.iter().enumerate() {
+            let rest = &tokens[idx + 1..];
+            let end = rest
+                .iter()
+                .position(|token| {
+                    matches!(
+                        token.kind,
+                        TokenKind::Semi | TokenKind::LBrace | TokenKind::RBrace
+                    )
+                })
+                .unwrap_or(rest.len());
+            match word(token) {
+                Some("message") => {
+                    if let Some(message) = parse_message(&rest[..end]) {
+                        self.messages.push(message);
+                    }
+                }
+                Some("first") => {
+                    if let [earlier, then, later] = &rest[..end]
+                        && then.text == "then"
+                        && let (Some(earlier), Some(later)) = (word(earlier), word(later))
+                    {
+                        self.successions
+                            .push((earlier.to_string(), later.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn into_diagram(self, symbols: &[DiagramSymbol]) -> SequenceDiagram {
+        let messages = order_messages(self.messages, &self.successions);
+
+        let mut lifelines: Vec<Lifeline> = Vec::new();
+        for name in messages
+            .iter()
+            .flat_map(|message| [&message.from, &message.to])
+        {
+            if lifelines.iter().all(|lifeline| &lifeline.name != name) {
+                let qualified_name = symbols
+                    .iter()
+                    .find(|sym| &sym.name == name && sym.node_type.ends_with("Usage"))
+                    .map(|sym| sym.qualified_name.clone());
+                lifelines.push(Lifeline {
+                    name: name.clone(),
+                    qualified_name,
+                });
+            }
+        }
+
+        let mut activations = Vec::new();
+        for lifeline in &lifelines {
+            let takes_part = |message: &SequenceMessage| {
+                message.from == lifeline.name || message.to == lifeline.name
+            };
+            let mut idx = 0;
+            while idx < messages.len() {
+                if !takes_part(&messages[idx]) {
+                    idx += 1;
+                    continue;
+                }
+                let first_message = idx;
+                while idx + 1 < messages.len() && takes_part(&messages[idx + 1]) {
+                    idx += 1;
+                }
+                activations.push(Activation {
+                    lifeline: lifeline.name.clone(),
+                    first_message,
+                    last_message: idx,
+                });
+                idx += 1;
+            }
+        }
+        activations.sort_by_key(|activation| activation.first_message);
+
+        SequenceDiagram {
+            lifelines,
+            messages,
+            activations,
+        }
+    }
+}
+
+/// A message statement after the `message` keyword; `None` unless it has both ends
+fn parse_message(tokens: &[Token<'_>]) -> Option<SequenceMessage> {
+    let mut tokens = tokens.iter().peekable();
+    let mut name = None;
+    let mut payload = None;
+    let mut payload_type = None;
+
+    if let Some(first) = tokens.peek().and_then(|token| word(token))
+        && !matches!(first, "def" | "of" | "from" | "to")
+    {
+        name = Some(first.to_string());
+        tokens.next();
+    }
+    if tokens.next_if(|token| token.text == ":").is_some() {
+        payload_type = Some(path(&mut tokens, "::")?);
+    }
+    if tokens.next_if(|token| token.text == "of").is_some() {
+        let written = path(&mut tokens, "::")?;
+        if tokens.next_if(|token| token.text == ":").is_some() {
+            payload = Some(written);
+            payload_type = Some(path(&mut tokens, "::")?);
+        } else {
+            payload_type = Some(written);
+        }
+    }
+
+    tokens.next_if(|token| token.text == "from")?;
+    let from = path(&mut tokens, ".")?;
+    tokens.next_if(|token| token.text == "to")?;
+    let to = path(&mut tokens, ".")?;
+
+    let (from, from_event) = split_endpoint(from);
+    let (to, to_event) = split_endpoint(to);
+    Some(SequenceMessage {
+        name,
+        payload,
+        payload_type,
+        from,
+        to,
+        from_event,
+        to_event,
+    })
+}
+
+/// Names joined by `separator` tokens
+fn path<'t, 'a: 't>(
+    tokens: &mut std::iter::Peekable<impl Iterator<Item = &'t Token<'a>>>,
+    separator: &str,
+) -> Option<String> {
+    let mut path = word(tokens.next()?)?.to_string();
+    while tokens.next_if(|token| token.text == separator).is_some() {
+        path.push_str(separator);
+        path.push_str(word(tokens.next()?)?);
+    }
+    Some(path)
+}
+
+/// `driver.turnOn` is the event `turnOn` on the lifeline `driver`
+fn split_endpoint(endpoint: String) -> (String, Option<String>) {
+    match endpoint.split_once('.') {
+        Some((lifeline, event)) => (lifeline.to_string(), Some(event.to_string())),
+        None => (endpoint, None),
+    }
+}
+
+/// Declaration order, with named messages moved after the messages they succeed.
+///
+/// Successions that form a cycle are ignored for the messages in the cycle.
+fn order_messages(
+    messages: Vec<SequenceMessage>,
+    successions: &[(String, String)],
+) -> Vec<SequenceMessage> {
+    let index_of = |name: &str| {
+        messages
+            .iter()
+            .position(|message| message.name.as_deref() == Some(name))
+    };
+    let edges: Vec<(usize, usize)> = successions
+        .iter()
+        .filter_map(|(earlier, later)| Some((index_of(earlier)?, index_of(later)?)))
+        .filter(|(earlier, later)| earlier != later)
+        .collect();
+
+    let mut placed = vec![false; messages.len()];
+    let mut order = Vec::with_capacity(messages.len());
+    loop {
+        let ready = (0..messages.len()).find(|&idx| {
+            !placed[idx]
+                && edges
+                    .iter()
+                    .all(|&(earlier, later)| later != idx || placed[earlier])
+        });
+        // In a cycle: fall back to declaration order
+        let Some(next) = ready.or_else(|| (0..messages.len()).find(|&idx| !placed[idx])) else {
+            break;
+        };
+        placed[next] = true;
+        order.push(next);
+    }
+
+    let mut messages: Vec<Option<SequenceMessage>> = messages.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .filter_map(|idx| messages[idx].take())
+        .collect()
+}
@@ -0,0 +1,369 @@
+//! Diagram export to PlantUML and Mermaid text.
+//!
+//! Renders the same data as `syster/getDiagram` as text that wikis and
+//! documentation tools render natively, so users can paste diagrams without
+//! the webview. Each view type has its own template:
+//!
+//! - `InterconnectionView`: elements nested inside their owners
+//! - `BrowserView`: the ownership tree as a mind map
+//! - everything else (`GeneralView`): definitions and usages as nodes with
+//!   ownership and typing edges
+
+use super::LspServer;
+use super::diagram::{DiagramData, DiagramSymbol, default_view_type};
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::request::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Custom LSP request: syster/exportDiagramText
+pub enum ExportDiagramTextRequest {}
+
+impl Request for ExportDiagramTextRequest {
+    type Params = ExportDiagramTextParams;
+    type Result = ExportDiagramTextResult;
+    const METHOD: &'static str = "syster/exportDiagramText";
+}
+
+/// Text format of an exported diagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagramTextFormat {
+    PlantUml,
+    Mermaid,
+}
+
+/// Request parameters for syster/exportDiagramText
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportDiagramTextParams {
+    /// URI of the file to export (optional - if None, exports the whole workspace)
+    pub uri: Option<String>,
+
+    /// Qualified name of an element to scope the diagram to (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element: Option<String>,
+
+    /// View type to render, defaults to "GeneralView"
+    #[serde(default = "default_view_type")]
+    pub view_type: String,
+
+    /// Output format: "plantuml" or "mermaid"
+    pub format: DiagramTextFormat,
+}
+
+/// Response for syster/exportDiagramText
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportDiagramTextResult {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    pub text: String,
+}
+
+impl LspServer {
+    /// Render the diagram for the workspace, a file or an element as text
+    pub fn export_diagram_text(
+        &mut self,
+        file_path: Option<&Path>,
+        element: Option<&str>,
+        view_type: &str,
+        format: DiagramTextFormat,
+    ) -> ExportDiagramTextResult {
+        let data = self.get_diagram(file_path, element, None, view_type);
+        ExportDiagramTextResult {
+            schema_version: SCHEMA_VERSION,
+            text: render_diagram_text(&data, format),
+        }
+    }
+}
+
+/// Render diagram data with the template for its view type
+pub fn render_diagram_text(data: &DiagramData, format: DiagramTextFormat) -> String {
+    let diagram = Diagram::new(data);
+    match (data.view_type.as_str(), format) {
+        ("InterconnectionView", DiagramTextFormat::PlantUml) => diagram.plantuml_nested(),
+        ("InterconnectionView", DiagramTextFormat::Mermaid) => diagram.mermaid_nested(),
+        ("BrowserView", DiagramTextFormat::PlantUml) => diagram.plantuml_tree(),
+        ("BrowserView", DiagramTextFormat::Mermaid) => diagram.mermaid_tree(),
+        (_, DiagramTextFormat::PlantUml) => diagram.plantuml_general(),
+        (_, DiagramTextFormat::Mermaid) => diagram.mermaid_general(),
+    }
+}
+
+/// Diagram symbols indexed by ownership
+struct Diagram<'a> {
+    data: &'a DiagramData,
+    /// Symbols whose owner isn't in the diagram, in input order
+    roots: Vec<&'a DiagramSymbol>,
+    /// Owned symbols by owner qualified name, in input order
+    children: BTreeMap<&'a str, Vec<&'a DiagramSymbol>>,
+    names: HashSet<&'a str>,
+}
+
+impl<'a> Diagram<'a> {
+    fn new(data: &'a DiagramData) -> Self {
+        let names: HashSet<&str> = data
+            .symbols
+            .iter()
+            .map(|sym| sym.qualified_name.as_str())
+            .collect();
+        let mut roots = Vec::new();
+        let mut children: BTreeMap<&str, Vec<&DiagramSymbol>> = BTreeMap::new();
+        for sym in &data.symbols {
+            match sym
+                .parent
+                .as_deref()
+                .filter(|parent| names.contains(parent))
+            {
+                Some(parent) => children.entry(parent).or_default().push(sym),
+                None => roots.push(sym),
+            }
+        }
+        Self {
+            data,
+            roots,
+            children,
+            names,
+        }
+    }
+
+    fn children_of(&self, sym: &DiagramSymbol) -> &[&'a DiagramSymbol] {
+        self.children
+            .get(sym.qualified_name.as_str())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The symbol a type reference names, which may be written unqualified
+    fn lookup(&self, target: &str) -> Option<&'a str> {
+        if let Some(&name) = self.names.get(target) {
+            return Some(name);
+        }
+        let suffix = format!("::{target}");
+        let mut matches = self
+            .data
+            .symbols
+            .iter()
+            .filter(|sym| sym.qualified_name.ends_with(&suffix));
+        let found = matches.next()?;
+        // An ambiguous name gets no edge
+        matches
+            .next()
+            .is_none()
+            .then_some(found.qualified_name.as_str())
+    }
+
+    /// Ownership edges, then typing edges between symbols in the diagram
+    fn edges(&self) -> (Vec<(&'a str, &'a str)>, Vec<(&'a str, &'a str)>) {
+        let owned = self
+            .data
+            .symbols
+            .iter()
+            .filter_map(|sym| {
+                let parent = sym.parent.as_deref()?;
+                self.names
+                    .contains(parent)
+                    .then_some((parent, sym.qualified_name.as_str()))
+            })
+            .collect();
+        let typed = self
+            .data
+            .relationships
+            .iter()
+            .filter(|rel| rel.rel_type == "typing" && self.names.contains(rel.source.as_str()))
+            .filter_map(|rel| Some((rel.source.as_str(), self.lookup(&rel.target)?)))
+            .collect();
+        (owned, typed)
+    }
+
+    fn plantuml_general(&self) -> String {
+        let mut out = String::from("@startuml\n");
+        for sym in &self.data.symbols {
+            let kind = keyword(&sym.node_type);
+            let shape = if is_definition(sym) {
+                "class"
+            } else {
+                "object"
+            };
+            let _ = writeln!(
+                out,
+                "{shape} \"{}\" as {} <<{kind}>>",
+                label(sym),
+                node_id(&sym.qualified_name)
+            );
+        }
+        let (owned, typed) = self.edges();
+        for (owner, member) in owned {
+            let _ = writeln!(out, "{} *-- {}", node_id(owner), node_id(member));
+        }
+        for (usage, definition) in typed {
+            let _ = writeln!(
+                out,
+                "{} ..> {} : typed by",
+                node_id(usage),
+                node_id(definition)
+            );
+        }
+        out.push_str("@enduml\n");
+        out
+    }
+
+    fn mermaid_general(&self) -> String {
+        let mut out = String::from("classDiagram\n");
+        for sym in &self.data.symbols {
+            let id = node_id(&sym.qualified_name);
+            let _ = writeln!(out, "    class {id}[\"{}\"]", label(sym));
+            let _ = writeln!(out, "    <<{}>> {id}", keyword(&sym.node_type));
+        }
+        let (owned, typed) = self.edges();
+        for (owner, member) in owned {
+            let _ = writeln!(out, "    {} *-- {}", node_id(owner), node_id(member));
+        }
+        for (usage, definition) in typed {
+            let _ = writeln!(
+                out,
+                "    {} ..> {} : typed by",
+                node_id(usage),
+                node_id(definition)
+            );
+        }
+        out
+    }
+
+    fn plantuml_nested(&self) -> String {
+        let mut out = String::from("@startuml\n");
+        for sym in &self.roots {
+            self.write_plantuml_nested(&mut out, sym, 0);
+        }
+        out.push_str("@enduml\n");
+        out
+    }
+
+    fn write_plantuml_nested(&self, out: &mut String, sym: &DiagramSymbol, depth: usize) {
+        let indent = "  ".repeat(depth);
+        let _ = write!(
+            out,
+            "{indent}rectangle \"{}\" as {}",
+            label(sym),
+            node_id(&sym.qualified_name)
+        );
+        let children = self.children_of(sym);
+        if children.is_empty() {
+            out.push('\n');
+            return;
+        }
+        out.push_str(" {\n");
+        for child in children {
+            self.write_plantuml_nested(out, child, depth + 1);
+        }
+        let _ = writeln!(out, "{indent}}}");
+    }
+
+    fn mermaid_nested(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for sym in &self.roots {
+            self.write_mermaid_nested(&mut out, sym, 1);
+        }
+        out
+    }
+
+    fn write_mermaid_nested(&self, out: &mut String, sym: &DiagramSymbol, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let id = node_id(&sym.qualified_name);
+        let children = self.children_of(sym);
+        if children.is_empty() {
+            let _ = writeln!(out, "{indent}{id}[\"{}\"]", label(sym));
+            return;
+        }
+        let _ = writeln!(out, "{indent}subgraph {id}[\"{}\"]", label(sym));
+        for child in children {
+            self.write_mermaid_nested(out, child, depth + 1);
+        }
+        let _ = writeln!(out, "{indent}end");
+    }
+
+    fn plantuml_tree(&self) -> String {
+        let mut out = String::from("@startmindmap\n");
+        for sym in &self.roots {
+            self.write_plantuml_tree(&mut out, sym, 1);
+        }
+        out.push_str("@endmindmap\n");
+        out
+    }
+
+    fn write_plantuml_tree(&self, out: &mut String, sym: &DiagramSymbol, depth: usize) {
+        let _ = writeln!(out, "{} {}", "*".repeat(depth), label(sym));
+        for child in self.children_of(sym) {
+            self.write_plantuml_tree(out, child, depth + 1);
+        }
+    }
+
+    fn mermaid_tree(&self) -> String {
+        // A mind map has a single root
+        let mut out = String::from("mindmap\n  root((Model))\n");
+        for sym in &self.roots {
+            self.write_mermaid_tree(&mut out, sym, 2);
+        }
+        out
+    }
+
+    fn write_mermaid_tree(&self, out: &mut String, sym: &DiagramSymbol, depth: usize) {
+        let _ = writeln!(
+            out,
+            "{}{}[{}]",
+            "  ".repeat(depth),
+            node_id(&sym.qualified_name),
+            label(sym)
+        );
+        for child in self.children_of(sym) {
+            self.write_mermaid_tree(out, child, depth + 1);
+        }
+    }
+}
+
+fn is_definition(sym: &DiagramSymbol) -> bool {
+    sym.node_type.ends_with("Def") || sym.node_type == "Package"
+}
+
+/// `name : Type` for typed usages, the name otherwise
+fn label(sym: &DiagramSymbol) -> String {
+    let name = sym.name.replace('"', "'");
+    match &sym.typed_by {
+        Some(typed_by) if !is_definition(sym) => {
+            let type_name = typed_by.rsplit("::").next().unwrap_or(typed_by);
+            format!("{name} : {}", type_name.replace('"', "'"))
+        }
+        _ => name,
+    }
+}
+
+/// SysML keyword of a node type (`PartDef` is `part def`, `UseCaseDef` is `use case def`)
+fn keyword(node_type: &str) -> String {
+    let (kind, def) = match node_type.strip_suffix("Def") {
+        Some(kind) => (kind, true),
+        None => (node_type.strip_suffix("Usage").unwrap_or(node_type), false),
+    };
+    let mut keyword = String::new();
+    for (i, ch) in kind.chars().enumerate() {
+        if ch.is_uppercase() && i > 0 {
+            keyword.push(' ');
+        }
+        keyword.extend(ch.to_lowercase());
+    }
+    if def {
+        keyword.push_str(" def");
+    }
+    keyword
+}
+
+/// Identifier for a qualified name that both formats accept
+fn node_id(qualified_name: &str) -> String {
+    qualified_name
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .collect()
+}
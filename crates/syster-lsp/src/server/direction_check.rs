@@ -0,0 +1,299 @@
+//! Parameter direction checks for action bodies
+//!
+//! Actions, their parameters and the parameters' directions come from the
+//! symbol index. The AST doesn't keep the ends of `flow`, `bind` and `assign`
+//! statements, so those are read from the document's tokens, in order, to
+//! find reads from `out` parameters before they are produced and writes to
+//! `in` parameters.
+
+use std::collections::HashSet;
+
+use async_lsp::lsp_types::{DiagnosticSeverity, Range};
+use syster::hir::{Direction, SymbolIndex, SymbolKind};
+
+use super::completion_context::declared_name;
+use super::tokens::{Token, TokenKind, tokenize, unquote};
+use super::type_hierarchy::general_types;
+
+/// Diagnostic code for parameter direction misuse
+pub const DIRECTION_MISUSE_CODE: &str = "direction-misuse";
+
+/// A direction misuse found in a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectionIssue {
+    pub range: Range,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+/// Check all action bodies in `text` for parameter direction misuse
+pub fn check_directions(index: &SymbolIndex, text: &str) -> Vec<DirectionIssue> {
+    let tokens = tokenize(text);
+    let checker = Checker {
+        index,
+        events: events(index, &tokens),
+    };
+    checker.check()
+}
+
+/// A reference to a parameter, either `name` or `action.name`
+#[derive(Debug, Clone)]
+struct Endpoint<'a> {
+    segments: Vec<&'a str>,
+    range: Range,
+}
+
+#[derive(Debug)]
+enum Event<'a> {
+    /// Data moves from `source` to `target` (flows and assignments)
+    Flow {
+        source: Option<Endpoint<'a>>,
+        target: Endpoint<'a>,
+    },
+    /// Both ends are bound to each other
+    Bind(Endpoint<'a>, Endpoint<'a>),
+}
+
+/// The flows, bindings and assignments of action bodies in document order,
+/// with the qualified name of the action they occur in
+fn events<'a>(index: &SymbolIndex, tokens: &[Token<'a>]) -> Vec<(String, Event<'a>)> {
+    let mut events = Vec::new();
+    // Names declared by the open braces, `None` for anonymous ones
+    let mut open: Vec<Option<String>> = Vec::new();
+    let mut statement_start = 0;
+
+    for (idx, token) in tokens.iter().enumerate() {
+        if !matches!(
+            token.kind,
+            TokenKind::LBrace | TokenKind::RBrace | TokenKind::Semi
+        ) {
+            continue;
+        }
+        let statement = &tokens[statement_start..idx];
+        statement_start = idx + 1;
+        if token.kind == TokenKind::LBrace {
+            open.push(declared_name(&statement.iter().collect::<Vec<_>>()));
+            continue;
+        }
+
+        let scope = open
+            .iter()
+            .map_while(|name| name.as_deref())
+            .collect::<Vec<_>>()
+            .join("::");
+        if is_action(index, &scope)
+            && let Some(event) = event(statement)
+        {
+            events.push((scope, event));
+        }
+        if token.kind == TokenKind::RBrace {
+            open.pop();
+        }
+    }
+    events
+}
+
+fn is_action(index: &SymbolIndex, qualified_name: &str) -> bool {
+    index
+        .lookup_qualified(qualified_name)
+        .is_some_and(|sym| matches!(sym.kind, SymbolKind::ActionDef | SymbolKind::ActionUsage))
+}
+
+/// The flow, binding or assignment a statement makes, if any
+fn event<'a>(statement: &[Token<'a>]) -> Option<Event<'a>> {
+    match statement.first()?.text {
+        "bind" => {
+            let eq = statement.iter().position(|t| t.text == "=")?;
+            Some(Event::Bind(
+                endpoint(&statement[1..eq])?,
+                endpoint(&statement[eq + 1..])?,
+            ))
+        }
+        "assign" => {
+            let assign = statement.iter().position(|t| t.text == ":=")?;
+            Some(Event::Flow {
+                source: None,
+                target: endpoint(&statement[1..assign])?,
+            })
+        }
+        _ if statement.iter().take(2).any(|t| t.text == "flow") => flow_event(statement),
+        _ => None,
+    }
+}
+
+struct Checker<'i, 'a> {
+    index: &'i SymbolIndex,
+    events: Vec<(String, Event<'a>)>,
+}
+
+impl<'a> Checker<'_, 'a> {
+    /// Direction of a parameter of `scope`, declared by it or by its types
+    fn own_param(&self, scope: &str, name: &str) -> Option<Direction> {
+        let owner = self.index.lookup_qualified(scope)?;
+        let mut visited = HashSet::new();
+        let mut pending = vec![owner];
+        while let Some(owner) = pending.pop() {
+            if !visited.insert(owner.qualified_name.clone()) {
+                continue;
+            }
+            let param = format!("{}::{name}", owner.qualified_name);
+            if let Some(direction) = self
+                .index
+                .lookup_qualified(&param)
+                .and_then(|param| param.direction)
+            {
+                return Some(direction);
+            }
+            pending.extend(general_types(self.index, owner));
+        }
+        None
+    }
+
+    /// Direction of a parameter of a nested action
+    fn child_param(&self, scope: &str, child: &str, name: &str) -> Option<Direction> {
+        self.own_param(&format!("{scope}::{child}"), name)
+    }
+
+    fn resolve(&self, scope: &str, endpoint: &Endpoint<'a>) -> Option<Resolved<'a>> {
+        match *endpoint.segments.as_slice() {
+            [name] => Some(Resolved::Own(name, self.own_param(scope, name)?)),
+            [child, name] => Some(Resolved::Child(self.child_param(scope, child, name)?)),
+            _ => None,
+        }
+    }
+
+    fn check(&self) -> Vec<DirectionIssue> {
+        let mut issues = Vec::new();
+        let mut produced: HashSet<(&str, &str)> = HashSet::new();
+
+        for (scope, event) in &self.events {
+            match event {
+                Event::Flow { source, target } => {
+                    if let Some(source) = source {
+                        self.check_read(scope, source, &produced, &mut issues);
+                    }
+                    self.check_write(scope, target, &mut produced, &mut issues);
+                }
+                Event::Bind(left, right) => {
+                    let left_resolved = self.resolve(scope, left);
+                    let right_resolved = self.resolve(scope, right);
+                    // A binding between an own parameter and a nested action's
+                    // parameter delegates data in the direction of the nested one
+                    for (own, other) in [
+                        (left, right_resolved.as_ref()),
+                        (right, left_resolved.as_ref()),
+                    ] {
+                        match other {
+                            Some(Resolved::Child(Direction::Out)) => {
+                                self.check_write(scope, own, &mut produced, &mut issues)
+                            }
+                            Some(Resolved::Child(Direction::In)) => {
+                                self.check_read(scope, own, &produced, &mut issues)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        issues
+    }
+
+    fn check_read<'s>(
+        &self,
+        scope: &'s str,
+        endpoint: &Endpoint<'a>,
+        produced: &HashSet<(&'s str, &'a str)>,
+        issues: &mut Vec<DirectionIssue>,
+    ) {
+        match self.resolve(scope, endpoint) {
+            Some(Resolved::Own(name, Direction::Out)) if !produced.contains(&(scope, name)) => {
+                issues.push(DirectionIssue {
+                    range: endpoint.range,
+                    severity: DiagnosticSeverity::WARNING,
+                    message: format!(
+                        "'{name}' is an out parameter and is read before it is produced"
+                    ),
+                });
+            }
+            Some(Resolved::Child(Direction::In)) => issues.push(DirectionIssue {
+                range: endpoint.range,
+                severity: DiagnosticSeverity::ERROR,
+                message: format!(
+                    "Cannot read from in parameter '{}'",
+                    endpoint.segments.join(".")
+                ),
+            }),
+            _ => {}
+        }
+    }
+
+    fn check_write<'s>(
+        &self,
+        scope: &'s str,
+        endpoint: &Endpoint<'a>,
+        produced: &mut HashSet<(&'s str, &'a str)>,
+        issues: &mut Vec<DirectionIssue>,
+    ) {
+        match self.resolve(scope, endpoint) {
+            Some(Resolved::Own(name, Direction::In)) => issues.push(DirectionIssue {
+                range: endpoint.range,
+                severity: DiagnosticSeverity::ERROR,
+                message: format!("Cannot write to in parameter '{name}'"),
+            }),
+            Some(Resolved::Own(name, _)) => {
+                produced.insert((scope, name));
+            }
+            Some(Resolved::Child(Direction::Out)) => issues.push(DirectionIssue {
+                range: endpoint.range,
+                severity: DiagnosticSeverity::ERROR,
+                message: format!(
+                    "Cannot write to out parameter '{}'",
+                    endpoint.segments.join(".")
+                ),
+            }),
+            _ => {}
+        }
+    }
+}
+
+enum Resolved<'a> {
+    Own(&'a str, Direction),
+    Child(Direction),
+}
+
+/// Parse `a` or `a.b` spanning the whole token slice
+fn endpoint<'a>(tokens: &[Token<'a>]) -> Option<Endpoint<'a>> {
+    let (first, last) = (tokens.first()?, tokens.last()?);
+    let mut segments = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let expect_word = i % 2 == 0;
+        match token.kind {
+            TokenKind::Word if expect_word => segments.push(unquote(token.text)),
+            TokenKind::Punct if !expect_word && token.text == "." => {}
+            _ => return None,
+        }
+    }
+    (tokens.len() % 2 == 1).then(|| Endpoint {
+        segments,
+        range: Range::new(first.start, last.end),
+    })
+}
+
+/// `flow [name] [of T] from a to b` or `flow a to b`
+fn flow_event<'a>(stmt: &[Token<'a>]) -> Option<Event<'a>> {
+    let to = stmt.iter().rposition(|t| t.text == "to")?;
+    let target = endpoint(&stmt[to + 1..])?;
+    let source_tokens = match stmt.iter().position(|t| t.text == "from") {
+        Some(from) => &stmt[from + 1..to],
+        None => {
+            let flow = stmt.iter().position(|t| t.text == "flow")?;
+            &stmt[flow + 1..to]
+        }
+    };
+    Some(Event::Flow {
+        source: Some(endpoint(source_tokens)?),
+        target,
+    })
+}
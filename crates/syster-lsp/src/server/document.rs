@@ -1,7 +1,11 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use super::LspServer;
+use super::error::LspError;
 use super::helpers::apply_text_edit;
+use super::language::Language;
+use super::parse_cache::CachedParse;
 use async_lsp::lsp_types::{TextDocumentContentChangeEvent, Url};
 use syster::core::constants::is_supported_extension;
 
@@ -14,10 +18,10 @@ impl LspServer {
         &mut self,
         uri: &Url,
         change: &TextDocumentContentChangeEvent,
-    ) -> Result<(), String> {
+    ) -> Result<(), LspError> {
         let path = uri
             .to_file_path()
-            .map_err(|_| format!("Invalid file URI: {uri}"))?;
+            .map_err(|_| LspError::InvalidUri(uri.clone()))?;
 
         // Get current document text, or empty string if document not yet opened
         let current_text = self.document_texts.get(&path).cloned().unwrap_or_default();
@@ -37,6 +41,7 @@ impl LspServer {
         };
 
         // Update text buffer only - parsing happens later via parse_document
+        self.health.document_pending(path.clone());
         self.document_texts.insert(path, new_text);
         Ok(())
     }
@@ -44,14 +49,20 @@ impl LspServer {
     /// Close a document - optionally remove from workspace
     /// For now, we keep documents in workspace even after close
     /// to maintain cross-file references
-    pub fn close_document(&mut self, _uri: &Url) -> Result<(), String> {
-        // We don't remove from workspace to keep cross-file references working
-        // In the future, might want to track "open" vs "workspace" files separately
+    pub fn close_document(&mut self, uri: &Url) -> Result<(), LspError> {
+        // We don't remove from workspace to keep cross-file references working,
+        // but the file on disk is authoritative again for watched file changes
+        if let Ok(path) = uri.to_file_path() {
+            self.document_versions.remove(&path);
+            self.formatted_hashes.remove(&path);
+            self.semantic_tokens_cache.remove(&path);
+            self.diagnostics_store.remove(&path);
+        }
         Ok(())
     }
 
     /// Open a document and add it to the workspace
-    pub fn open_document(&mut self, uri: &Url, text: &str) -> Result<(), String> {
+    pub fn open_document(&mut self, uri: &Url, text: &str) -> Result<(), LspError> {
         self.ensure_workspace_loaded()?;
         let path = self.uri_to_model_path(uri)?;
         self.document_texts.insert(path.clone(), text.to_string());
@@ -62,6 +73,11 @@ impl LspServer {
     /// Parse a document that already has updated text
     /// Called after debounce delay
     pub fn parse_document(&mut self, uri: &Url) {
+        // The edit leaves the pipeline here, even if the document can't be parsed
+        if let Ok(path) = uri.to_file_path() {
+            self.health.document_settled(&path);
+        }
+
         // Validate file extension before parsing
         let path = match self.uri_to_model_path(uri) {
             Ok(p) => p,
@@ -73,19 +89,48 @@ impl LspServer {
             return;
         }
 
-        // Get current text and parse it
+        // Get current text and parse it, re-parsing only the members that changed
         if let Some(text) = self.document_texts.get(&path).cloned() {
-            self.parse_into_workspace(&path, &text);
+            self.track_definitions(&path);
+            let incremental = &mut self.incremental_parser;
+            let parse_result = self.parse_cache.get_or_parse(&path, &text, || {
+                incremental
+                    .parse(&path, &text)
+                    .unwrap_or_else(|| CachedParse::parse(&path, &text))
+            });
+            self.store_parse_result(&path, &text, parse_result);
+            // Files using what this one defines are re-validated with it
+            self.schedule_dependents(&path);
         }
     }
 
     /// Parse text and update workspace
     fn parse_into_workspace(&mut self, path: &std::path::Path, text: &str) {
-        let parse_result = syster::project::file_loader::parse_with_result(text, path);
+        // Identical content (reopened file, undo, branch switch) reuses the cached parse
+        let parse_result = self
+            .parse_cache
+            .get_or_parse(path, text, || CachedParse::parse(path, text));
+        self.store_parse_result(path, text, parse_result);
+    }
+
+    /// Record parse errors and put the parsed file into the workspace
+    fn store_parse_result(
+        &mut self,
+        path: &std::path::Path,
+        text: &str,
+        parse_result: Arc<CachedParse>,
+    ) {
+        let parse_result = Arc::unwrap_or_clone(parse_result);
+        if !parse_result.errors.is_empty() {
+            self.health.parse_failed();
+        }
         self.parse_errors
             .insert(path.to_path_buf(), parse_result.errors);
+        self.reference_index.note_edit(path, text);
+        self.symbol_lookup.note_edit(path);
 
         if let Some(file) = parse_result.content {
+            self.root_libraries.set_file(path, &file);
             // Use set_file which handles update vs add
             self.analysis_host.set_file(path.to_path_buf(), file);
             // Index is automatically marked dirty by AnalysisHost
@@ -93,19 +138,18 @@ impl LspServer {
             // Parse failed completely - still add an empty file so the file_id exists
             // This allows completions/hover to work even with invalid syntax
             let empty_file = Self::create_empty_syntax_file(path);
+            self.root_libraries.set_file(path, &empty_file);
             self.analysis_host.set_file(path.to_path_buf(), empty_file);
         }
     }
 
     /// Create an empty SyntaxFile based on file extension
-    fn create_empty_syntax_file(path: &std::path::Path) -> syster::syntax::SyntaxFile {
+    pub(super) fn create_empty_syntax_file(path: &std::path::Path) -> syster::syntax::SyntaxFile {
         use syster::syntax::SyntaxFile;
         use syster::syntax::kerml::KerMLFile;
         use syster::syntax::sysml::ast::SysMLFile;
 
-        let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("sysml");
-
-        if ext == "kerml" {
+        if Language::of(path) == Some(Language::KerML) {
             SyntaxFile::KerML(KerMLFile {
                 namespace: None,
                 elements: Vec::new(),
@@ -120,20 +164,20 @@ impl LspServer {
     }
 
     /// Convert URI to path and validate extension is SysML or KerML
-    fn uri_to_model_path(&self, uri: &Url) -> Result<PathBuf, String> {
+    fn uri_to_model_path(&self, uri: &Url) -> Result<PathBuf, LspError> {
         let path = uri
             .to_file_path()
-            .map_err(|_| format!("Invalid file URI: {uri}"))?;
+            .map_err(|_| LspError::InvalidUri(uri.clone()))?;
 
-        let ext = path
+        let supported = path
             .extension()
             .and_then(|s| s.to_str())
-            .ok_or_else(|| "File has no extension".to_string())?;
+            .is_some_and(is_supported_extension);
 
-        if is_supported_extension(ext) {
+        if supported {
             Ok(path)
         } else {
-            Err(format!("Unsupported file extension: {ext}"))
+            Err(LspError::UnsupportedFile(path))
         }
     }
 }
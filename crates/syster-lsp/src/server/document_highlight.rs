@@ -0,0 +1,108 @@
+use super::LspServer;
+use super::helpers::uri_to_path;
+use async_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind, Position, Range, Url};
+
+impl LspServer {
+    /// Highlight all occurrences of the symbol at the given position in the same file
+    ///
+    /// The declaration is highlighted as `Write` and references as `Read`. When the
+    /// position isn't on a resolvable symbol, identical identifiers in the document
+    /// are highlighted as `Text`.
+    pub fn get_document_highlights(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> Option<Vec<DocumentHighlight>> {
+        let path = uri_to_path(uri)?;
+        let path_str = path.to_string_lossy();
+
+        let analysis = self.analysis_host.analysis();
+        let file_id = analysis.get_file_id(&path_str)?;
+
+        let result = analysis.find_references(file_id, position.line, position.character, true);
+
+        let mut highlights: Vec<DocumentHighlight> = result
+            .references
+            .into_iter()
+            .filter(|reference| reference.file == file_id)
+            .map(|reference| DocumentHighlight {
+                range: Range {
+                    start: Position::new(reference.start_line, reference.start_col),
+                    end: Position::new(reference.end_line, reference.end_col),
+                },
+                kind: Some(if reference.is_definition {
+                    DocumentHighlightKind::WRITE
+                } else {
+                    DocumentHighlightKind::READ
+                }),
+            })
+            .collect();
+
+        if highlights.is_empty() {
+            let text = self.document_texts.get(&path)?;
+            highlights = text_highlights(text, position);
+        }
+
+        highlights.sort_by_key(|h| (h.range.start.line, h.range.start.character));
+        highlights.dedup_by_key(|h| h.range);
+
+        if highlights.is_empty() {
+            None
+        } else {
+            Some(highlights)
+        }
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Highlight every whole-word occurrence of the identifier under the cursor
+fn text_highlights(text: &str, position: Position) -> Vec<DocumentHighlight> {
+    let Some(line) = text.lines().nth(position.line as usize) else {
+        return Vec::new();
+    };
+    // The cursor's column counts UTF-16 code units
+    let cursor = line
+        .char_indices()
+        .map(|(i, _)| i)
+        .find(|&i| line[..i].encode_utf16().count() >= position.character as usize)
+        .unwrap_or(line.len());
+
+    let start = line[..cursor]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_identifier_char(c))
+        .last()
+        .map_or(cursor, |(i, _)| i);
+    let end = line[cursor..]
+        .char_indices()
+        .find(|&(_, c)| !is_identifier_char(c))
+        .map_or(line.len(), |(i, _)| cursor + i);
+    let word = &line[start..end];
+    if word.is_empty() {
+        return Vec::new();
+    }
+
+    let mut highlights = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let column = |offset: usize| line[..offset].encode_utf16().count() as u32;
+        for (col, _) in line.match_indices(word) {
+            let after = col + word.len();
+            let before_ok = !line[..col].ends_with(is_identifier_char);
+            let after_ok = !line[after..].starts_with(is_identifier_char);
+            if before_ok && after_ok {
+                highlights.push(DocumentHighlight {
+                    range: Range {
+                        start: Position::new(line_idx as u32, column(col)),
+                        end: Position::new(line_idx as u32, column(after)),
+                    },
+                    kind: Some(DocumentHighlightKind::TEXT),
+                });
+            }
+        }
+    }
+
+    highlights
+}
@@ -1,15 +1,18 @@
 use super::LspServer;
-use super::helpers::uri_to_path;
+use super::helpers::{position_to_byte_offset, uri_to_path};
 use async_lsp::lsp_types::{DocumentLink, Position, Range, Url};
+use syster::hir::{HirSymbol, SymbolIndex, SymbolKind};
 
 impl LspServer {
     /// Get document links for imports and qualified references in the document
     ///
     /// Returns a list of clickable links that navigate to:
-    /// 1. Import statements - links to the definition of the imported symbol
+    /// 1. Import statements - each segment of the import path links to the
+    ///    package or element it names (`import Pkg::Name` gives `Pkg` and `Name`)
     /// 2. Type references - links to specialized types, typed definitions, etc.
     ///
-    /// Uses the new HIR-based IDE layer.
+    /// Uses the new HIR-based IDE layer, falling back to its whole-import link
+    /// when the path can't be found in the document text.
     pub fn get_document_links(&mut self, uri: &Url) -> Vec<DocumentLink> {
         let path = match uri_to_path(uri) {
             Some(p) => p,
@@ -24,38 +27,148 @@ impl LspServer {
             None => return Vec::new(),
         };
 
-        // Use the Analysis document_links method
+        let index = analysis.symbol_index();
+        let mut links = Vec::new();
+        // Lines of the imports whose segments were linked
+        let mut linked_imports = Vec::new();
+
+        if let Some(text) = self.document_texts.get(&path) {
+            for import in index
+                .symbols_in_file(file_id)
+                .into_iter()
+                .filter(|sym| sym.kind == SymbolKind::Import)
+            {
+                let Some(segments) = import_segments(text, import) else {
+                    continue;
+                };
+                linked_imports.push(import.start_line..=import.end_line);
+                for (range, prefix) in segments {
+                    let Some(target) = resolve_import(index, import, &prefix) else {
+                        continue;
+                    };
+                    let Some(target_uri) = analysis
+                        .get_file_path(target.file)
+                        .and_then(|target_path| Url::from_file_path(target_path).ok())
+                        .and_then(|target_uri| {
+                            Url::parse(&format!("{}#L{}", target_uri, target.start_line + 1)).ok()
+                        })
+                    else {
+                        continue;
+                    };
+                    links.push(DocumentLink {
+                        range,
+                        target: Some(target_uri),
+                        tooltip: Some(format!("Go to {}", target.qualified_name)),
+                        data: None,
+                    });
+                }
+            }
+        }
+
+        // Use the Analysis document_links method for everything else
         let ide_links = analysis.document_links(file_id);
 
         // Convert to LSP DocumentLinks
-        ide_links
-            .into_iter()
-            .filter_map(|link| {
-                // Convert target FileId to URI
-                let target_path = analysis.get_file_path(link.target_file)?;
-                let target_uri = Url::from_file_path(target_path).ok()?;
-
-                // Add line number to URI fragment for jump-to-line
-                let target_line = link.target_line + 1; // 1-indexed
-                let target_uri_with_line =
-                    Url::parse(&format!("{}#L{}", target_uri, target_line)).ok()?;
-
-                Some(DocumentLink {
-                    range: Range {
-                        start: Position {
-                            line: link.start_line,
-                            character: link.start_col,
-                        },
-                        end: Position {
-                            line: link.end_line,
-                            character: link.end_col,
-                        },
-                    },
-                    target: Some(target_uri_with_line),
-                    tooltip: Some(link.tooltip.to_string()),
-                    data: None,
+        links.extend(
+            ide_links
+                .into_iter()
+                .filter(|link| {
+                    !linked_imports
+                        .iter()
+                        .any(|lines| lines.contains(&link.start_line))
                 })
-            })
-            .collect()
+                .filter_map(|link| {
+                    // Convert target FileId to URI
+                    let target_path = analysis.get_file_path(link.target_file)?;
+                    let target_uri = Url::from_file_path(target_path).ok()?;
+
+                    // Add line number to URI fragment for jump-to-line
+                    let target_line = link.target_line + 1; // 1-indexed
+                    let target_uri_with_line =
+                        Url::parse(&format!("{}#L{}", target_uri, target_line)).ok()?;
+
+                    Some(DocumentLink {
+                        range: Range {
+                            start: Position {
+                                line: link.start_line,
+                                character: link.start_col,
+                            },
+                            end: Position {
+                                line: link.end_line,
+                                character: link.end_col,
+                            },
+                        },
+                        target: Some(target_uri_with_line),
+                        tooltip: Some(link.tooltip.to_string()),
+                        data: None,
+                    })
+                }),
+        );
+
+        links.sort_by_key(|link| (link.range.start.line, link.range.start.character));
+        links
     }
 }
+
+/// Span and path prefix of each named segment of an import path
+///
+/// `import Pkg::Name::*` gives `Pkg` (prefix `Pkg`) and `Name` (prefix
+/// `Pkg::Name`). Returns `None` if the path isn't written as-is after the
+/// import's start.
+fn import_segments(text: &str, import: &HirSymbol) -> Option<Vec<(Range, String)>> {
+    let path = import.name.as_ref();
+    let start =
+        position_to_byte_offset(text, Position::new(import.start_line, import.start_col)).ok()?;
+    let statement = text.get(start..)?;
+    let statement = &statement[..statement.find(';').unwrap_or(statement.len())];
+    let mut offset = start + statement.find(path)?;
+
+    let mut segments = Vec::new();
+    let mut prefix = String::new();
+    for segment in path.split("::") {
+        if !prefix.is_empty() {
+            prefix.push_str("::");
+        }
+        prefix.push_str(segment);
+        if !segment.is_empty() && segment != "*" && segment != "**" {
+            segments.push((
+                Range {
+                    start: byte_offset_to_position(text, offset),
+                    end: byte_offset_to_position(text, offset + segment.len()),
+                },
+                prefix.clone(),
+            ));
+        }
+        offset += segment.len() + "::".len();
+    }
+    Some(segments)
+}
+
+/// The element named by an import path prefix, resolved from the import's scope
+pub(super) fn resolve_import<'a>(
+    index: &'a SymbolIndex,
+    import: &HirSymbol,
+    prefix: &str,
+) -> Option<&'a HirSymbol> {
+    // Import symbols are named `<scope>::import:<path>`
+    let qualified_name = import.qualified_name.as_ref();
+    let scope = qualified_name
+        .find("::import:")
+        .map_or("", |idx| &qualified_name[..idx]);
+    let target = index
+        .resolver_for_scope(scope)
+        .resolve(prefix)
+        .symbol()
+        .map(|sym| sym.qualified_name.to_string())
+        .unwrap_or_else(|| prefix.to_string());
+    index.lookup_qualified(&target)
+}
+
+pub(super) fn byte_offset_to_position(text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position::new(
+        before.matches('\n').count() as u32,
+        before[line_start..].encode_utf16().count() as u32,
+    )
+}
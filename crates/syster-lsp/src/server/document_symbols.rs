@@ -1,8 +1,10 @@
 use super::LspServer;
-use async_lsp::lsp_types::{DocumentSymbol, Position, Range, SymbolKind};
-use std::collections::HashMap;
+use super::hover::FeatureModifiers;
+use super::symbol_lookup::symbol;
+use async_lsp::lsp_types::{DocumentSymbol, Position, Range, SymbolKind, SymbolTag};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use syster::hir::SymbolKind as HirSymbolKind;
+use syster::hir::{HirSymbol, RefKind, SymbolKind as HirSymbolKind};
 
 impl LspServer {
     /// Get all symbols in a document for the outline view.
@@ -17,12 +19,28 @@ impl LspServer {
             None => return Vec::new(),
         };
 
-        // Use the Analysis document_symbols method
-        let symbols = analysis.document_symbols(file_id);
+        // Walk the file's symbols so imports (skipped by the IDE outline) are
+        // included; the lookup keeps them in document order
+        self.symbol_lookup.sync(&analysis);
+        let symbols: Vec<&HirSymbol> = self
+            .symbol_lookup
+            .in_file(file_path)
+            .iter()
+            .filter_map(|entry| symbol(analysis.symbol_index(), file_id, entry))
+            .filter(|sym| sym.kind != HirSymbolKind::Comment)
+            .collect();
+        let text = self.document_texts.get(file_path).map(String::as_str);
+        // Features owned directly by an enumeration are its literals
+        let enumerations: HashSet<&str> = symbols
+            .iter()
+            .filter(|sym| sym.kind == HirSymbolKind::EnumerationDef)
+            .map(|sym| sym.qualified_name.as_ref())
+            .collect();
 
-        let flat_symbols: Vec<(String, DocumentSymbol)> = symbols
+        let flat_symbols: Vec<(String, Option<String>, DocumentSymbol)> = symbols
             .into_iter()
             .map(|sym| {
+                let modifiers = text.and_then(|text| FeatureModifiers::of(text, sym));
                 let range = Range {
                     start: Position {
                         line: sym.start_line,
@@ -34,19 +52,46 @@ impl LspServer {
                     },
                 };
 
+                let (name, detail) = match sym.kind {
+                    // Imports are named after their path, show the full statement instead
+                    HirSymbolKind::Import => (format!("import {}", sym.name), sym.name.to_string()),
+                    // Aliases show what they point at
+                    HirSymbolKind::Alias => (
+                        sym.name.to_string(),
+                        sym.supertypes
+                            .first()
+                            .map(|target| target.to_string())
+                            .unwrap_or_else(|| sym.qualified_name.to_string()),
+                    ),
+                    _ => (
+                        sym.name.to_string(),
+                        declaration_detail(sym, modifiers.as_ref())
+                            .unwrap_or_else(|| sym.qualified_name.to_string()),
+                    ),
+                };
+                let kind = match container_name(sym) {
+                    Some(owner) if enumerations.contains(owner.as_str()) => SymbolKind::ENUM_MEMBER,
+                    _ => convert_symbol_kind(sym.kind),
+                };
+                let deprecated = modifiers.as_ref().is_some_and(|m| m.is_deprecated);
+
                 let doc_symbol = DocumentSymbol {
-                    name: sym.name.to_string(),
-                    detail: Some(sym.qualified_name.to_string()),
-                    kind: convert_symbol_kind(sym.kind),
+                    name,
+                    detail: Some(detail),
+                    kind,
                     range,
                     selection_range: range,
                     children: Some(Vec::new()),
-                    tags: None,
+                    tags: deprecated.then(|| vec![SymbolTag::DEPRECATED]),
                     #[allow(deprecated)]
                     deprecated: None,
                 };
 
-                (sym.qualified_name.to_string(), doc_symbol)
+                (
+                    sym.qualified_name.to_string(),
+                    container_name(sym),
+                    doc_symbol,
+                )
             })
             .collect();
 
@@ -57,12 +102,16 @@ impl LspServer {
     /// Build a hierarchical structure from flat symbols using qualified names
     fn build_symbol_hierarchy(
         &self,
-        flat_symbols: Vec<(String, DocumentSymbol)>,
+        flat_symbols: Vec<(String, Option<String>, DocumentSymbol)>,
     ) -> Vec<DocumentSymbol> {
         let mut symbol_map: HashMap<String, DocumentSymbol> = HashMap::new();
+        let mut parents: HashMap<String, String> = HashMap::new();
 
         // First, add all symbols to the map
-        for (qualified_name, symbol) in flat_symbols {
+        for (qualified_name, parent, symbol) in flat_symbols {
+            if let Some(parent) = parent {
+                parents.insert(qualified_name.clone(), parent);
+            }
             symbol_map.insert(qualified_name, symbol);
         }
 
@@ -76,8 +125,8 @@ impl LspServer {
 
         // Build hierarchy by moving children into parents, starting from deepest
         for qualified_name in &all_names {
-            if let Some(last_separator) = qualified_name.rfind("::") {
-                let parent_name = &qualified_name[..last_separator];
+            if let Some(parent_name) = parents.get(qualified_name) {
+                let parent_name = parent_name.as_str();
 
                 // Check if parent exists and child hasn't been moved yet
                 if symbol_map.contains_key(parent_name) && symbol_map.contains_key(qualified_name) {
@@ -101,50 +150,84 @@ impl LspServer {
     }
 }
 
-fn convert_symbol_kind(kind: HirSymbolKind) -> SymbolKind {
+/// Get the qualified name of the scope that owns a symbol.
+///
+/// Import qualified names embed the imported path (`Pkg::import:Other::*`), so
+/// the owning scope is everything before the `import:` marker.
+fn container_name(sym: &HirSymbol) -> Option<String> {
+    let qname = sym.qualified_name.as_ref();
+    if sym.kind == HirSymbolKind::Import {
+        return qname.find("::import:").map(|idx| qname[..idx].to_string());
+    }
+    qname.rfind("::").map(|idx| qname[..idx].to_string())
+}
+
+/// Detail shown next to a definition or usage: `abstract` if it is, then its
+/// types and supertypes as declared (`: Vehicle`, `: ~FuelPort`, `:> Base`, `:>> mass`)
+fn declaration_detail(sym: &HirSymbol, modifiers: Option<&FeatureModifiers>) -> Option<String> {
+    let mut parts = Vec::new();
+    if modifiers.is_some_and(|m| m.is_abstract) {
+        parts.push("abstract".to_string());
+    }
+    for type_ref in sym.type_refs.iter().flat_map(|trk| trk.as_refs()) {
+        let (operator, conjugated) = match type_ref.kind {
+            RefKind::TypedBy => (":", modifiers.is_some_and(|m| m.is_conjugated)),
+            RefKind::Specializes | RefKind::Subsets => (":>", false),
+            RefKind::Redefines => (":>>", false),
+            _ => continue,
+        };
+        let conjugate = if conjugated { "~" } else { "" };
+        parts.push(format!("{operator} {conjugate}{}", type_ref.target));
+    }
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+pub(super) fn convert_symbol_kind(kind: HirSymbolKind) -> SymbolKind {
     match kind {
         HirSymbolKind::Package => SymbolKind::NAMESPACE,
 
-        // Definitions are classes
+        // Structural definitions are classes
         HirSymbolKind::PartDef
         | HirSymbolKind::ItemDef
-        | HirSymbolKind::ActionDef
-        | HirSymbolKind::PortDef
-        | HirSymbolKind::AttributeDef
         | HirSymbolKind::ConnectionDef
-        | HirSymbolKind::InterfaceDef
         | HirSymbolKind::AllocationDef
-        | HirSymbolKind::RequirementDef
-        | HirSymbolKind::ConstraintDef
         | HirSymbolKind::StateDef
+        | HirSymbolKind::ViewDef
+        | HirSymbolKind::ViewpointDef
+        | HirSymbolKind::RenderingDef => SymbolKind::CLASS,
+        HirSymbolKind::PortDef | HirSymbolKind::InterfaceDef => SymbolKind::INTERFACE,
+        HirSymbolKind::AttributeDef => SymbolKind::STRUCT,
+        HirSymbolKind::EnumerationDef => SymbolKind::ENUM,
+
+        // Behavior is functions, whether defined or used
+        HirSymbolKind::ActionDef
         | HirSymbolKind::CalculationDef
         | HirSymbolKind::UseCaseDef
         | HirSymbolKind::AnalysisCaseDef
+        | HirSymbolKind::ActionUsage
+        | HirSymbolKind::CalculationUsage => SymbolKind::FUNCTION,
+
+        // Requirements and constraints keep their kind as usages
+        HirSymbolKind::RequirementDef
         | HirSymbolKind::ConcernDef
-        | HirSymbolKind::ViewDef
-        | HirSymbolKind::ViewpointDef
-        | HirSymbolKind::RenderingDef
-        | HirSymbolKind::EnumerationDef => SymbolKind::CLASS,
+        | HirSymbolKind::RequirementUsage => SymbolKind::OBJECT,
+        HirSymbolKind::ConstraintDef | HirSymbolKind::ConstraintUsage => SymbolKind::BOOLEAN,
 
-        // Usages are properties
+        // Other usages are properties
         HirSymbolKind::PartUsage
         | HirSymbolKind::ItemUsage
-        | HirSymbolKind::ActionUsage
         | HirSymbolKind::PortUsage
         | HirSymbolKind::AttributeUsage
         | HirSymbolKind::ConnectionUsage
         | HirSymbolKind::InterfaceUsage
         | HirSymbolKind::AllocationUsage
-        | HirSymbolKind::RequirementUsage
-        | HirSymbolKind::ConstraintUsage
         | HirSymbolKind::StateUsage
-        | HirSymbolKind::CalculationUsage
         | HirSymbolKind::ReferenceUsage
         | HirSymbolKind::OccurrenceUsage
         | HirSymbolKind::FlowUsage => SymbolKind::PROPERTY,
 
         HirSymbolKind::Alias => SymbolKind::VARIABLE,
-        HirSymbolKind::Import => SymbolKind::NAMESPACE,
+        HirSymbolKind::Import => SymbolKind::MODULE,
         HirSymbolKind::Comment => SymbolKind::STRING,
         HirSymbolKind::Dependency => SymbolKind::VARIABLE,
         HirSymbolKind::Other => SymbolKind::VARIABLE,
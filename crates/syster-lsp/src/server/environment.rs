@@ -0,0 +1,151 @@
+//! File system injection point
+//!
+//! The server reads files through the [`FileSystem`] trait instead of calling
+//! `std` directly, so tests can swap in a [`MemoryFileSystem`] whose
+//! modification times only move when the test advances them, driving cache
+//! invalidation and file watching deterministically. The test double is
+//! compiled for this crate's tests and, for downstream contributors, behind
+//! the `test-support` feature.
+//!
+//! Workspace scanning and loading go through syster-base and still use the
+//! real file system.
+//!
+//! The debounce task sleeps on tokio's clock; tests pause and advance it with
+//! `#[tokio::test(start_paused = true)]`.
+
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Size and modification time of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileMetadata {
+    pub len: u64,
+    pub modified: SystemTime,
+}
+
+/// File operations performed by the server
+pub trait FileSystem: Send + Sync {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()>;
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+}
+
+/// The real file system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFileSystem;
+
+impl FileSystem for RealFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(FileMetadata {
+            len: metadata.len(),
+            modified: metadata.modified()?,
+        })
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        std::fs::write(path, contents)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        std::fs::rename(from, to)
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+pub use test_support::MemoryFileSystem;
+
+#[cfg(any(test, feature = "test-support"))]
+mod test_support {
+    use super::{FileMetadata, FileSystem};
+    use std::collections::HashMap;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+    use std::time::{Duration, SystemTime};
+
+    /// An in-memory file system stamping writes with a time that only moves
+    /// when told to, starting at the Unix epoch.
+    ///
+    /// Directories aren't tracked: every directory exists.
+    #[derive(Debug)]
+    pub struct MemoryFileSystem {
+        now: Mutex<SystemTime>,
+        files: Mutex<HashMap<PathBuf, (String, SystemTime)>>,
+    }
+
+    impl Default for MemoryFileSystem {
+        fn default() -> Self {
+            Self {
+                now: Mutex::new(SystemTime::UNIX_EPOCH),
+                files: Mutex::new(HashMap::new()),
+            }
+        }
+    }
+
+    impl MemoryFileSystem {
+        /// Move the time later writes are stamped with
+        pub fn advance(&self, duration: Duration) {
+            *self.now.lock().unwrap() += duration;
+        }
+
+        /// Delete a file, returning whether it existed
+        pub fn remove(&self, path: &Path) -> bool {
+            self.files.lock().unwrap().remove(path).is_some()
+        }
+
+        fn not_found(path: &Path) -> io::Error {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not found", path.display()),
+            )
+        }
+    }
+
+    impl FileSystem for MemoryFileSystem {
+        fn read_to_string(&self, path: &Path) -> io::Result<String> {
+            let files = self.files.lock().unwrap();
+            let (text, _) = files.get(path).ok_or_else(|| Self::not_found(path))?;
+            Ok(text.clone())
+        }
+
+        fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+            let files = self.files.lock().unwrap();
+            let (text, modified) = files.get(path).ok_or_else(|| Self::not_found(path))?;
+            Ok(FileMetadata {
+                len: text.len() as u64,
+                modified: *modified,
+            })
+        }
+
+        fn create_dir_all(&self, _path: &Path) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+            self.files.lock().unwrap().insert(
+                path.to_path_buf(),
+                (contents.to_string(), *self.now.lock().unwrap()),
+            );
+            Ok(())
+        }
+
+        fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+            let mut files = self.files.lock().unwrap();
+            let file = files.remove(from).ok_or_else(|| Self::not_found(from))?;
+            files.insert(to.to_path_buf(), file);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Errors returned by the server's public APIs
+//!
+//! Document, change and query APIs report failures as an [`LspError`], so
+//! callers can tell a bad request (an unsupported file, an out of range edit)
+//! from a workspace problem (the stdlib failing to load) without matching on
+//! message text. The `Display` output is what the server logs and returns to
+//! clients.
+
+use async_lsp::lsp_types::Url;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Failure of a server API
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LspError {
+    /// The URI doesn't name a local file
+    InvalidUri(Url),
+    /// The file isn't a SysML or KerML file
+    UnsupportedFile(PathBuf),
+    /// The document isn't open where the change was sent
+    FileNotOpen(Url),
+    /// A position or range lies outside the document
+    InvalidRange(String),
+    /// The standard library couldn't be loaded
+    StdlibUnavailable(String),
+    /// A rename would produce an invalid or conflicting name
+    InvalidRename(String),
+    /// A workspace snapshot can't be loaded
+    InvalidSnapshot(String),
+    /// A model corpus or its expectations can't be read
+    InvalidCorpus(String),
+}
+
+impl fmt::Display for LspError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUri(uri) => write!(f, "Invalid file URI: {uri}"),
+            Self::UnsupportedFile(path) => match path.extension() {
+                Some(ext) => write!(f, "Unsupported file extension: {}", ext.to_string_lossy()),
+                None => write!(f, "File has no extension"),
+            },
+            Self::FileNotOpen(uri) => write!(f, "Document not open: {uri}"),
+            Self::StdlibUnavailable(message) => {
+                write!(f, "Standard library unavailable: {message}")
+            }
+            Self::InvalidRange(message) | Self::InvalidRename(message) => write!(f, "{message}"),
+            Self::InvalidSnapshot(message) => write!(f, "Invalid workspace snapshot: {message}"),
+            Self::InvalidCorpus(message) => write!(f, "Invalid corpus: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for LspError {}
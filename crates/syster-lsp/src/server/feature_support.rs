@@ -0,0 +1,132 @@
+//! Feature support matrix request handler for LSP.
+//!
+//! Lists which SysML constructs have working hover, go-to-definition, semantic
+//! tokens and diagnostics, so extension docs and users can see what a release
+//! implements without reading the source. The matrix comes from the registry
+//! below, which is updated alongside the features themselves.
+
+use super::LspServer;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::request::Request;
+use serde::{Deserialize, Serialize};
+use syster::core::constants::LSP_SERVER_VERSION;
+
+/// Custom LSP request: syster/getFeatureSupportMatrix
+pub enum GetFeatureSupportMatrixRequest {}
+
+impl Request for GetFeatureSupportMatrixRequest {
+    type Params = GetFeatureSupportMatrixParams;
+    type Result = FeatureSupportMatrix;
+    const METHOD: &'static str = "syster/getFeatureSupportMatrix";
+}
+
+/// Request parameters for syster/getFeatureSupportMatrix
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFeatureSupportMatrixParams {}
+
+/// Support for a single construct
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstructSupport {
+    /// Name of the construct (e.g., "Part definition")
+    pub construct: String,
+    /// How the construct is written (e.g., "part def")
+    pub syntax: String,
+    pub hover: bool,
+    pub definition: bool,
+    pub semantic_tokens: bool,
+    pub diagnostics: bool,
+}
+
+/// Result of the syster/getFeatureSupportMatrix request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureSupportMatrix {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Version of the server the matrix describes
+    pub server_version: String,
+    pub constructs: Vec<ConstructSupport>,
+}
+
+const HOVER: u8 = 1 << 0;
+const DEFINITION: u8 = 1 << 1;
+const SEMANTIC_TOKENS: u8 = 1 << 2;
+const DIAGNOSTICS: u8 = 1 << 3;
+/// Hover, definition and tokens, without construct-specific diagnostics
+const NAVIGATION: u8 = HOVER | DEFINITION | SEMANTIC_TOKENS;
+const ALL: u8 = NAVIGATION | DIAGNOSTICS;
+
+/// Constructs and the features that currently handle them
+const REGISTRY: &[(&str, &str, u8)] = &[
+    ("Package", "package", ALL),
+    (
+        "Import",
+        "import",
+        DEFINITION | SEMANTIC_TOKENS | DIAGNOSTICS,
+    ),
+    ("Alias", "alias ... for", ALL),
+    ("Part definition", "part def", ALL),
+    ("Part", "part", ALL),
+    ("Item definition", "item def", ALL),
+    ("Item", "item", ALL),
+    ("Attribute definition", "attribute def", ALL),
+    ("Attribute", "attribute", ALL),
+    ("Port definition", "port def", ALL),
+    ("Port", "port", ALL),
+    ("Action definition", "action def", ALL),
+    ("Action", "action", ALL),
+    ("Directed parameter", "in / out / inout", ALL),
+    ("State definition", "state def", ALL),
+    ("State", "state", ALL),
+    ("Calculation definition", "calc def", ALL),
+    ("Calculation", "calc", ALL),
+    ("Constraint definition", "constraint def", ALL),
+    ("Constraint", "constraint", ALL),
+    ("Requirement definition", "requirement def", ALL),
+    ("Requirement", "requirement", ALL),
+    ("Concern definition", "concern def", ALL),
+    ("Use case definition", "use case def", ALL),
+    ("Analysis case definition", "analysis def", ALL),
+    ("Connection definition", "connection def", ALL),
+    ("Connection", "connection", ALL),
+    ("Interface definition", "interface def", ALL),
+    ("Interface", "interface", ALL),
+    ("Allocation definition", "allocation def", ALL),
+    ("Allocation", "allocation", ALL),
+    ("Flow", "flow", NAVIGATION),
+    ("Enumeration definition", "enum def", ALL),
+    ("Enumeration literal", "enum", NAVIGATION),
+    ("Occurrence", "occurrence", ALL),
+    ("Reference", "ref", ALL),
+    ("View definition", "view def", NAVIGATION),
+    ("Viewpoint definition", "viewpoint def", NAVIGATION),
+    ("Rendering definition", "rendering def", NAVIGATION),
+    ("Dependency", "dependency", SEMANTIC_TOKENS),
+    ("Comment", "comment / doc", 0),
+];
+
+impl LspServer {
+    /// Get the constructs supported by hover, definition, tokens and diagnostics.
+    pub fn feature_support_matrix() -> FeatureSupportMatrix {
+        let constructs = REGISTRY
+            .iter()
+            .map(|&(construct, syntax, features)| ConstructSupport {
+                construct: construct.to_string(),
+                syntax: syntax.to_string(),
+                hover: features & HOVER != 0,
+                definition: features & DEFINITION != 0,
+                semantic_tokens: features & SEMANTIC_TOKENS != 0,
+                diagnostics: features & DIAGNOSTICS != 0,
+            })
+            .collect();
+
+        FeatureSupportMatrix {
+            schema_version: SCHEMA_VERSION,
+            server_version: LSP_SERVER_VERSION.to_string(),
+            constructs,
+        }
+    }
+}
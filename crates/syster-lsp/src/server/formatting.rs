@@ -1,71 +1,247 @@
+use super::tokens::{TokenKind, tokenize};
 use crate::server::LspServer;
 use crate::server::helpers::{position_to_byte_offset, uri_to_path};
-use async_lsp::ResponseError;
+use crate::server::incremental_parse::{find_body, split_members};
+use crate::server::pragmas::{overlaps_format_off, restore_format_off_regions};
 use async_lsp::lsp_types::*;
+use async_lsp::{ErrorCode, ResponseError};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
 use syster::syntax::formatter;
 use tokio_util::sync::CancellationToken;
 
+/// Formatting option property setting how many consecutive blank lines are kept
+pub const PROP_MAX_BLANK_LINES: &str = "maxBlankLines";
+
+/// Consecutive blank lines kept when `maxBlankLines` is not set
+pub const DEFAULT_MAX_BLANK_LINES: usize = 1;
+
+/// Documents larger than this many bytes are formatted in chunks
+pub const CHUNKED_FORMAT_THRESHOLD: usize = 64 * 1024;
+
+/// Bytes of consecutive elements formatted together when formatting in chunks
+const FORMAT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// The editor's version of a document, shared with in-flight formatting
+#[derive(Debug, Clone, Default)]
+pub struct DocumentVersion(Arc<AtomicI32>);
+
+impl DocumentVersion {
+    pub fn get(&self) -> i32 {
+        self.0.load(Ordering::Acquire)
+    }
+
+    fn set(&self, version: i32) {
+        self.0.store(version, Ordering::Release);
+    }
+}
+
+/// Hash of the text and options a document was last formatted to, shared
+/// with in-flight formatting
+#[derive(Debug, Clone, Default)]
+pub struct FormattedHash(Arc<Mutex<Option<u64>>>);
+
+impl FormattedHash {
+    /// Whether formatting `text` with `options` is known to change nothing
+    pub fn is_formatted(&self, text: &str, options: &FormattingOptions) -> bool {
+        *self.0.lock().unwrap_or_else(|err| err.into_inner()) == Some(format_hash(text, options))
+    }
+
+    /// Remember that `text` is formatted for `options`
+    fn record(&self, text: &str, options: &FormattingOptions) {
+        *self.0.lock().unwrap_or_else(|err| err.into_inner()) = Some(format_hash(text, options));
+    }
+}
+
+/// Hash of a text together with the options that change how it is formatted
+fn format_hash(text: &str, options: &FormattingOptions) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    options.tab_size.hash(&mut hasher);
+    options.insert_spaces.hash(&mut hasher);
+    max_blank_lines(options).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Document text together with the version it was taken at
+#[derive(Debug, Clone)]
+pub struct DocumentSnapshot {
+    pub text: String,
+    pub version: i32,
+    current: DocumentVersion,
+    /// What the document was last formatted to
+    pub formatted: FormattedHash,
+}
+
+impl DocumentSnapshot {
+    /// Whether the document has been edited since the snapshot was taken
+    pub fn is_stale(&self) -> bool {
+        self.current.get() != self.version
+    }
+}
+
 impl LspServer {
     /// Get a snapshot of the document text for async formatting
     pub fn get_document_text(&self, uri: &Url) -> Option<String> {
         let path = uri_to_path(uri)?;
         self.document_texts.get(&path).cloned()
     }
+
+    /// Get a snapshot of the document text tied to its current version
+    pub fn get_document_snapshot(&self, uri: &Url) -> Option<DocumentSnapshot> {
+        let path = uri_to_path(uri)?;
+        let text = self.document_texts.get(&path)?.clone();
+        let current = self
+            .document_versions
+            .get(&path)
+            .cloned()
+            .unwrap_or_default();
+        let formatted = self
+            .formatted_hashes
+            .get(&path)
+            .cloned()
+            .unwrap_or_default();
+        Some(DocumentSnapshot {
+            text,
+            version: current.get(),
+            current,
+            formatted,
+        })
+    }
+
+    /// Record the editor's version of a document (from didOpen/didChange)
+    pub fn set_document_version(&mut self, uri: &Url, version: i32) {
+        if let Some(path) = uri_to_path(uri) {
+            self.formatted_hashes.entry(path.clone()).or_default();
+            self.document_versions.entry(path).or_default().set(version);
+        }
+    }
+}
+
+/// Error returned when the document was edited while it was being formatted
+fn content_modified(snapshot: &DocumentSnapshot) -> ResponseError {
+    ResponseError::new(
+        ErrorCode::CONTENT_MODIFIED,
+        format!(
+            "Document changed while formatting (version {} is now {}); format again",
+            snapshot.version,
+            snapshot.current.get()
+        ),
+    )
 }
 
 /// Handle document formatting request asynchronously
 ///
 /// This function takes snapshots of the required data and returns a future
 /// that can be awaited. The formatting work runs on a blocking thread pool
-/// and respects cancellation.
+/// and respects cancellation. Edits are only returned if the document is
+/// still at the snapshot's version; otherwise a `ContentModified` error asks
+/// the client to format again.
+///
+/// The text the document is formatted to is remembered, so formatting it
+/// again before it changes, as rapid format-on-save does, returns right away.
 pub async fn format_document(
-    text_snapshot: Option<String>,
+    snapshot: Option<DocumentSnapshot>,
     options: FormattingOptions,
     cancel_token: CancellationToken,
 ) -> Result<Option<Vec<TextEdit>>, ResponseError> {
-    let result = match text_snapshot {
-        Some(text) => {
-            let cancel_for_select = cancel_token.clone();
-
-            // Run formatting on the blocking thread pool.
-            // Use select! to race the work against cancellation.
-            let format_task =
-                tokio::task::spawn_blocking(move || format_text(&text, options, &cancel_token));
-
-            tokio::select! {
-                result = format_task => result.unwrap_or(None),
-                _ = cancel_for_select.cancelled() => None,
-            }
+    let Some(snapshot) = snapshot else {
+        return Ok(None);
+    };
+    if snapshot.formatted.is_formatted(&snapshot.text, &options) {
+        return Ok(None);
+    }
+    let cancel_for_select = cancel_token.clone();
+
+    // Run formatting on the blocking thread pool, a chunk at a time for large
+    // documents. Use select! to race the work against cancellation.
+    let text: Arc<str> = Arc::from(snapshot.text.as_str());
+    let format_options = options.clone();
+    let format_task = async move {
+        match chunk_plan(&text) {
+            Some(chunks) => format_in_chunks(text, chunks, format_options, cancel_token).await,
+            None => tokio::task::spawn_blocking(move || {
+                format_text(&text, format_options, &cancel_token)
+            })
+            .await
+            .unwrap_or(None),
         }
-        None => None,
     };
 
+    let result = tokio::select! {
+        result = format_task => result,
+        _ = cancel_for_select.cancelled() => None,
+    };
+
+    if snapshot.is_stale() {
+        return Err(content_modified(&snapshot));
+    }
+    match result.as_deref() {
+        Some([edit]) => snapshot.formatted.record(&edit.new_text, &options),
+        None if !cancel_for_select.is_cancelled() => {
+            snapshot.formatted.record(&snapshot.text, &options)
+        }
+        _ => {}
+    }
     Ok(result)
 }
 
 /// Handle range formatting request asynchronously
 pub async fn format_range_document(
-    text_snapshot: Option<String>,
+    snapshot: Option<DocumentSnapshot>,
     options: FormattingOptions,
     cancel_token: CancellationToken,
     range: Range,
 ) -> Result<Option<Vec<TextEdit>>, ResponseError> {
-    let result = match text_snapshot {
-        Some(text) => {
-            let cancel_for_select = cancel_token.clone();
+    let Some(snapshot) = snapshot else {
+        return Ok(None);
+    };
+    let cancel_for_select = cancel_token.clone();
 
-            let format_task = tokio::task::spawn_blocking(move || {
-                format_range_text(&text, options, &cancel_token, range)
-            });
+    let text = snapshot.text.clone();
+    let format_task = tokio::task::spawn_blocking(move || {
+        format_range_text(&text, options, &cancel_token, range)
+    });
 
-            tokio::select! {
-                result = format_task => result.unwrap_or(None),
-                _ = cancel_for_select.cancelled() => None,
-            }
-        }
-        None => None,
+    let result = tokio::select! {
+        result = format_task => result.unwrap_or(None),
+        _ = cancel_for_select.cancelled() => None,
     };
 
+    if snapshot.is_stale() {
+        return Err(content_modified(&snapshot));
+    }
+    Ok(result)
+}
+
+/// Handle on-type formatting request asynchronously
+pub async fn format_on_type_document(
+    snapshot: Option<DocumentSnapshot>,
+    options: FormattingOptions,
+    cancel_token: CancellationToken,
+    position: Position,
+    ch: String,
+) -> Result<Option<Vec<TextEdit>>, ResponseError> {
+    let Some(snapshot) = snapshot else {
+        return Ok(None);
+    };
+    let cancel_for_select = cancel_token.clone();
+
+    let text = snapshot.text.clone();
+    let format_task = tokio::task::spawn_blocking(move || {
+        format_on_type_text(&text, options, &cancel_token, position, &ch)
+    });
+
+    let result = tokio::select! {
+        result = format_task => result.unwrap_or(None),
+        _ = cancel_for_select.cancelled() => None,
+    };
+
+    if snapshot.is_stale() {
+        return Err(content_modified(&snapshot));
+    }
     Ok(result)
 }
 
@@ -81,16 +257,63 @@ pub fn format_text(
         return None;
     }
 
-    // Convert LSP options to formatter options
-    let format_options = formatter::FormatOptions {
-        tab_size: options.tab_size as usize,
-        insert_spaces: options.insert_spaces,
-        print_width: 80, // Default print width
-    };
-
     // Use the Rowan-based formatter that preserves comments
     // The formatter checks the cancellation token periodically
-    let formatted = formatter::format_async(text, &format_options, cancel)?;
+    let formatted = match chunk_plan(text) {
+        Some(chunks) => {
+            let mut pieces = Vec::with_capacity(chunks.len());
+            for chunk in &chunks {
+                if cancel.is_cancelled() {
+                    return None;
+                }
+                pieces.push(format_chunk(text, chunk, &options, cancel)?);
+            }
+            join_chunks(text, pieces)
+        }
+        None => formatter::format_async(text, &formatter_options(&options), cancel)?,
+    };
+    finish_format(text, formatted, &options, cancel)
+}
+
+/// Format a large text a chunk at a time, each on the blocking thread pool.
+///
+/// The task yields between chunks, so other requests are served while a
+/// large document is formatted, and a format superseded by an edit stops
+/// within one chunk instead of running over the whole document.
+async fn format_in_chunks(
+    text: Arc<str>,
+    chunks: Vec<Chunk>,
+    options: FormattingOptions,
+    cancel: CancellationToken,
+) -> Option<Vec<TextEdit>> {
+    let mut pieces = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        if cancel.is_cancelled() {
+            return None;
+        }
+        let (text, options, cancel) = (text.clone(), options.clone(), cancel.clone());
+        let piece =
+            tokio::task::spawn_blocking(move || format_chunk(&text, &chunk, &options, &cancel));
+        pieces.push(piece.await.ok()??);
+    }
+    let formatted = join_chunks(&text, pieces);
+    tokio::task::spawn_blocking(move || finish_format(&text, formatted, &options, &cancel))
+        .await
+        .ok()?
+}
+
+/// Restore what the formatter drops and build the edit to the formatted text.
+///
+/// Returns None if cancelled or if no changes needed.
+fn finish_format(
+    text: &str,
+    formatted: String,
+    options: &FormattingOptions,
+    cancel: &CancellationToken,
+) -> Option<Vec<TextEdit>> {
+    let formatted = preserve_blank_lines(text, &formatted, max_blank_lines(options));
+    // Regions after `// syster:format off` keep their original lines
+    let formatted = restore_format_off_regions(text, &formatted)?;
 
     // Check cancellation before building result
     if cancel.is_cancelled() {
@@ -107,8 +330,161 @@ pub fn format_text(
     }])
 }
 
+/// Part of a large document formatted on its own
+#[derive(Debug, Clone, Copy)]
+struct Chunk {
+    start: usize,
+    end: usize,
+    /// Blocks the chunk sits in
+    depth: usize,
+    kind: ChunkKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkKind {
+    /// Whole members
+    Members,
+    /// The text of a block element up to and including its `{`
+    Header,
+    /// The `}` closing a block element
+    Footer,
+}
+
+/// Split a text larger than [`CHUNKED_FORMAT_THRESHOLD`] into chunks of
+/// whole members of about [`FORMAT_CHUNK_SIZE`] bytes.
+///
+/// Members are found the way the incremental parser splits a document. A
+/// member too large for one chunk (typically the single package of a library
+/// file) has its header and closing brace formatted on their own and its body
+/// chunked one level deeper. Returns None for smaller texts and for texts
+/// whose braces don't balance, which are formatted in one go.
+fn chunk_plan(text: &str) -> Option<Vec<Chunk>> {
+    if text.len() <= CHUNKED_FORMAT_THRESHOLD {
+        return None;
+    }
+    let mut chunks = Vec::new();
+    plan_members(text, 0..text.len(), 0, &mut chunks)?;
+    Some(chunks)
+}
+
+/// Add the chunks of `range` of `text`, which sits `depth` blocks deep
+fn plan_members(
+    text: &str,
+    range: std::ops::Range<usize>,
+    depth: usize,
+    chunks: &mut Vec<Chunk>,
+) -> Option<()> {
+    let push = |chunks: &mut Vec<Chunk>, start: usize, end: usize, kind: ChunkKind| {
+        if kind != ChunkKind::Members || !text[start..end].trim().is_empty() {
+            chunks.push(Chunk {
+                start,
+                end,
+                depth,
+                kind,
+            });
+        }
+    };
+    // Members from `start` to `end` are waiting to fill a chunk
+    let (mut start, mut end) = (range.start, range.start);
+    for member in split_members(text, range.clone())? {
+        let member_end = line_end(text, member.end, range.end);
+        if member.len() > FORMAT_CHUNK_SIZE
+            && let Some((open, close)) = find_body(text, member.clone())
+        {
+            push(chunks, start, end, ChunkKind::Members);
+            push(chunks, end, open + 1, ChunkKind::Header);
+            plan_members(text, open + 1..close, depth + 1, chunks)?;
+            push(chunks, close, member_end, ChunkKind::Footer);
+            (start, end) = (member_end, member_end);
+            continue;
+        }
+        end = member_end;
+        if end - start >= FORMAT_CHUNK_SIZE {
+            push(chunks, start, end, ChunkKind::Members);
+            start = end;
+        }
+    }
+    push(chunks, start, range.end, ChunkKind::Members);
+    Some(())
+}
+
+/// Where a chunk ending with a member at `end` stops: at the end of its line
+/// if only a line comment follows, so the comment stays with the member
+fn line_end(text: &str, end: usize, limit: usize) -> usize {
+    let eol = text[end..limit].find('\n').map_or(limit, |i| end + i);
+    let rest = text[end..eol].trim_start();
+    if rest.is_empty() || rest.starts_with("//") {
+        eol
+    } else {
+        end
+    }
+}
+
+/// Format one chunk, indented to the depth it sits at
+fn format_chunk(
+    text: &str,
+    chunk: &Chunk,
+    options: &FormattingOptions,
+    cancel: &CancellationToken,
+) -> Option<String> {
+    let source = &text[chunk.start..chunk.end];
+    let indent = indent_unit(options).repeat(chunk.depth);
+    let format_options = formatter_options(options);
+    match chunk.kind {
+        ChunkKind::Members => {
+            let formatted = formatter::format_async(source, &format_options, cancel)?;
+            Some(indent_lines(formatted.trim(), &indent))
+        }
+        ChunkKind::Header => {
+            let header = formatter::format_async(&format!("{source}}}"), &format_options, cancel)?;
+            let header = header.trim_end().strip_suffix('}')?.trim_end();
+            Some(indent_lines(header, &indent))
+        }
+        ChunkKind::Footer => Some(indent_lines(source.trim(), &indent)),
+    }
+}
+
+/// Put formatted chunks back together. Blank lines between chunks are
+/// restored afterwards like any others.
+fn join_chunks(text: &str, pieces: Vec<String>) -> String {
+    let mut formatted = pieces.join("\n");
+    if text.ends_with('\n') {
+        formatted.push('\n');
+    }
+    formatted
+}
+
+/// Format the region affected by a character just typed before `position`.
+///
+/// `}` re-indents the block it closes and `;` normalizes the spacing of the
+/// current line. Returns None for other characters or if no changes needed.
+pub fn format_on_type_text(
+    text: &str,
+    options: FormattingOptions,
+    cancel: &CancellationToken,
+    position: Position,
+    ch: &str,
+) -> Option<Vec<TextEdit>> {
+    let line = match ch {
+        ";" => position.line,
+        "}" => {
+            let offset = position_to_byte_offset(text, position).ok()?;
+            let close = text[..offset].rfind('}')?;
+            open_blocks(&text[..close])?.last()?.line
+        }
+        _ => return None,
+    };
+    let range = Range::new(Position::new(line, 0), position);
+    format_range_text(text, options, cancel, range)
+}
+
 /// Format text for a given range with cancellation support
-/// Returns None if cancelled, range is invalid, or if no changes needed
+///
+/// The range is widened to whole lines, formatted on its own, and re-indented
+/// to the depth of the enclosing block so it lines up with the surrounding
+/// text, which is left untouched.
+/// Returns None if cancelled, range is invalid, doesn't hold whole elements or
+/// touches a `// syster:format off` region, or if no changes needed
 pub fn format_range_text(
     text: &str,
     options: FormattingOptions,
@@ -119,6 +495,10 @@ pub fn format_range_text(
         return None;
     }
 
+    let range = whole_lines(text, range)?;
+    if overlaps_format_off(text, range) {
+        return None;
+    }
     let start_byte = position_to_byte_offset(text, range.start).ok()?;
     let end_byte = position_to_byte_offset(text, range.end).ok()?;
     if start_byte > end_byte || end_byte > text.len() {
@@ -126,14 +506,19 @@ pub fn format_range_text(
     }
 
     let selected = &text[start_byte..end_byte];
+    if selected.trim().is_empty() {
+        return None;
+    }
+    // Formatting a selection that opens or closes a block would mangle it
+    if block_depth(selected) != Some(0) {
+        return None;
+    }
+    let depth = block_depth(&text[..start_byte])?;
+    let max_blank_lines = max_blank_lines(&options);
 
-    let format_options = formatter::FormatOptions {
-        tab_size: options.tab_size as usize,
-        insert_spaces: options.insert_spaces,
-        print_width: 80, // Default print width
-    };
-
-    let formatted = formatter::format_async(selected, &format_options, cancel)?;
+    let formatted = formatter::format_async(selected, &formatter_options(&options), cancel)?;
+    let formatted = preserve_blank_lines(selected, &formatted, max_blank_lines);
+    let formatted = indent_lines(formatted.trim(), &indent_unit(&options).repeat(depth));
 
     if cancel.is_cancelled() {
         return None;
@@ -149,6 +534,81 @@ pub fn format_range_text(
     }])
 }
 
+/// Widen a range to cover its lines in full, without the final line break.
+///
+/// A range ending at the start of a line doesn't include that line.
+fn whole_lines(text: &str, range: Range) -> Option<Range> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut end_line = (range.end.line as usize).min(lines.len() - 1);
+    if range.end.character == 0 && end_line > range.start.line as usize {
+        end_line -= 1;
+    }
+    if range.start.line as usize > end_line {
+        return None;
+    }
+    let end_character = lines[end_line].trim_end_matches('\r').chars().count();
+    Some(Range {
+        start: Position::new(range.start.line, 0),
+        end: Position::new(end_line as u32, end_character as u32),
+    })
+}
+
+/// Number of blocks left open at the end of `text`.
+///
+/// Braces inside comments, strings and quoted names don't count. Returns None
+/// if the text closes more blocks than it opens.
+fn block_depth(text: &str) -> Option<usize> {
+    open_blocks(text).map(|blocks| blocks.len())
+}
+
+/// Positions of the `{` of each block left open at the end of `text`,
+/// outermost first
+fn open_blocks(text: &str) -> Option<Vec<Position>> {
+    let mut blocks = Vec::new();
+    for token in tokenize(text) {
+        match token.kind {
+            TokenKind::LBrace => blocks.push(token.start),
+            TokenKind::RBrace => {
+                blocks.pop()?;
+            }
+            _ => {}
+        }
+    }
+    Some(blocks)
+}
+
+/// Convert LSP options to formatter options
+fn formatter_options(options: &FormattingOptions) -> formatter::FormatOptions {
+    formatter::FormatOptions {
+        tab_size: options.tab_size as usize,
+        insert_spaces: options.insert_spaces,
+        print_width: 80, // Default print width
+    }
+}
+
+/// One level of indentation as configured by the formatting options
+fn indent_unit(options: &FormattingOptions) -> String {
+    if options.insert_spaces {
+        " ".repeat(options.tab_size as usize)
+    } else {
+        "\t".to_string()
+    }
+}
+
+/// Prefix every non-blank line with `indent`
+fn indent_lines(text: &str, indent: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                String::new()
+            } else {
+                format!("{indent}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Calculate the range that covers the entire document
 fn full_document_range(text: &str) -> Range {
     let line_count = text.lines().count().saturating_sub(1) as u32;
@@ -159,3 +619,107 @@ fn full_document_range(text: &str) -> Range {
         end: Position::new(line_count, last_char),
     }
 }
+
+/// Read the `maxBlankLines` property from the formatting options
+fn max_blank_lines(options: &FormattingOptions) -> usize {
+    match options.properties.get(PROP_MAX_BLANK_LINES) {
+        Some(FormattingProperty::Number(n)) if *n >= 0 => *n as usize,
+        _ => DEFAULT_MAX_BLANK_LINES,
+    }
+}
+
+/// Restore blank-line groups that the formatter collapsed.
+///
+/// The formatter keeps every non-whitespace character, so gaps between them are
+/// matched up by counting non-whitespace characters. Where the original gap held
+/// more blank lines than the formatted one, up to `max_blank_lines` are restored.
+/// Blank lines adjacent to doc comments are always restored in full. Blank lines
+/// are only ever added back, never removed, and only where the formatter kept a
+/// line break.
+pub fn preserve_blank_lines(original: &str, formatted: &str, max_blank_lines: usize) -> String {
+    let wanted = blank_line_groups(original, max_blank_lines);
+    if wanted.is_empty() {
+        return formatted.to_string();
+    }
+
+    let mut output = String::with_capacity(formatted.len());
+    let mut significant = 0;
+    let mut gap_start = None;
+
+    for (offset, ch) in formatted.char_indices() {
+        if ch.is_whitespace() {
+            gap_start.get_or_insert(offset);
+            continue;
+        }
+        if let Some(start) = gap_start.take() {
+            let gap = &formatted[start..offset];
+            match wanted.get(&significant) {
+                Some(&blank_lines) if gap.contains('\n') && blank_lines_in(gap) < blank_lines => {
+                    let indent = &gap[gap.rfind('\n').map_or(0, |i| i + 1)..];
+                    output.extend(std::iter::repeat_n('\n', blank_lines + 1));
+                    output.push_str(indent);
+                }
+                _ => output.push_str(gap),
+            }
+        }
+        output.push(ch);
+        significant += 1;
+    }
+    if let Some(start) = gap_start {
+        output.push_str(&formatted[start..]);
+    }
+
+    output
+}
+
+/// Map each gap (keyed by the number of non-whitespace characters before it)
+/// to the number of blank lines it should keep
+fn blank_line_groups(text: &str, max_blank_lines: usize) -> HashMap<usize, usize> {
+    let mut groups = HashMap::new();
+    let mut significant = 0;
+    let mut gap_start = None;
+
+    for (offset, ch) in text.char_indices() {
+        if ch.is_whitespace() {
+            gap_start.get_or_insert(offset);
+            continue;
+        }
+        if let Some(start) = gap_start.take()
+            && significant > 0
+        {
+            let blank_lines = blank_lines_in(&text[start..offset]);
+            if blank_lines > 0 {
+                let before = text[..start].rsplit('\n').next().unwrap_or("");
+                let after = text[offset..].split('\n').next().unwrap_or("");
+                let keep = if is_doc_comment_boundary(before, after) {
+                    blank_lines
+                } else {
+                    blank_lines.min(max_blank_lines)
+                };
+                groups.insert(significant, keep);
+            }
+        }
+        significant += 1;
+    }
+
+    groups
+}
+
+/// Number of blank lines in a whitespace gap
+fn blank_lines_in(gap: &str) -> usize {
+    gap.matches('\n').count().saturating_sub(1)
+}
+
+/// Whether a blank-line group ends a doc comment or precedes one
+fn is_doc_comment_boundary(line_before: &str, line_after: &str) -> bool {
+    let after = line_after.trim_start();
+    let starts_with_keyword = |keyword: &str| {
+        after.strip_prefix(keyword).is_some_and(|rest| {
+            rest.is_empty() || rest.starts_with(|c: char| c.is_whitespace() || c == '/')
+        })
+    };
+    line_before.trim_end().ends_with("*/")
+        || after.starts_with("/*")
+        || starts_with_keyword("doc")
+        || starts_with_keyword("comment")
+}
@@ -0,0 +1,103 @@
+//! Health check request handler for LSP.
+//!
+//! Reports whether the server is ready to answer requests with complete
+//! results, so the extension can hold back features and show a spinner while
+//! the stdlib and workspace load instead of returning empty hovers.
+
+use super::LspServer;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::request::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Custom LSP request: syster/healthCheck
+pub enum HealthCheckRequest {}
+
+impl Request for HealthCheckRequest {
+    type Params = HealthCheckParams;
+    type Result = HealthStatus;
+    const METHOD: &'static str = "syster/healthCheck";
+}
+
+/// Request parameters for syster/healthCheck
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheckParams {}
+
+/// Errors seen since the server started
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorCounts {
+    /// Parses that reported syntax errors
+    pub parse_errors: u64,
+    /// Documents that failed to open, change or close
+    pub document_errors: u64,
+}
+
+/// Result of the syster/healthCheck request
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStatus {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// The workspace is loaded and no indexing is running
+    pub ready: bool,
+    /// The stdlib has been loaded into the workspace
+    pub stdlib_loaded: bool,
+    /// A background indexing job is running
+    pub indexing: bool,
+    /// Edited documents waiting to be re-parsed
+    pub pending_documents: usize,
+    pub errors: ErrorCounts,
+}
+
+/// Pending documents and error counts tracked for the health check
+#[derive(Debug, Default)]
+pub struct HealthTracker {
+    pending_documents: HashSet<PathBuf>,
+    errors: ErrorCounts,
+}
+
+impl HealthTracker {
+    /// Record that a document was edited and awaits parsing
+    pub(super) fn document_pending(&mut self, path: PathBuf) {
+        self.pending_documents.insert(path);
+    }
+
+    /// Record that an edited document left the parse pipeline
+    pub(super) fn document_settled(&mut self, path: &Path) {
+        self.pending_documents.remove(path);
+    }
+
+    /// Whether an edited document hasn't been parsed yet
+    pub(super) fn is_pending(&self, path: &Path) -> bool {
+        self.pending_documents.contains(path)
+    }
+
+    /// Record a parse that reported syntax errors
+    pub(super) fn parse_failed(&mut self) {
+        self.errors.parse_errors += 1;
+    }
+}
+
+impl LspServer {
+    /// Get the readiness, pending work and error counts of the server.
+    pub fn health_check(&self) -> HealthStatus {
+        let indexing = self.is_indexing();
+        HealthStatus {
+            schema_version: SCHEMA_VERSION,
+            ready: self.is_workspace_loaded() && !indexing,
+            stdlib_loaded: self.is_stdlib_loaded(),
+            indexing,
+            pending_documents: self.health.pending_documents.len(),
+            errors: self.health.errors.clone(),
+        }
+    }
+
+    /// Record that a document failed to open, change or close
+    pub fn record_document_error(&mut self) {
+        self.health.errors.document_errors += 1;
+    }
+}
@@ -1,3 +1,4 @@
+use super::error::LspError;
 use async_lsp::lsp_types::{Position, Range, Url};
 use percent_encoding::percent_decode_str;
 use std::path::PathBuf;
@@ -7,6 +8,18 @@ pub fn uri_to_path(uri: &Url) -> Option<PathBuf> {
     uri.to_file_path().ok()
 }
 
+/// The qualified name a prepare request stored on a call or type hierarchy
+/// item: its `data`, or its `detail` if the client didn't keep the data
+pub fn item_qualified_name(
+    data: Option<&serde_json::Value>,
+    detail: Option<&String>,
+) -> Option<String> {
+    match data {
+        Some(serde_json::Value::String(name)) => Some(name.clone()),
+        _ => detail.cloned(),
+    }
+}
+
 /// Decode percent-encoded strings (e.g., "my%20file.txt" -> "my file.txt")
 ///
 /// Used to display file names to users with proper formatting instead of URL encoding.
@@ -35,7 +48,7 @@ pub fn char_offset_to_byte(line: &str, char_offset: usize) -> usize {
 ///
 /// Handles multi-line documents by calculating line offsets and character positions
 /// Note: Treats position.character as character count (not strict UTF-16 code units)
-pub fn position_to_byte_offset(text: &str, pos: Position) -> Result<usize, String> {
+pub fn position_to_byte_offset(text: &str, pos: Position) -> Result<usize, LspError> {
     let line_idx = pos.line as usize;
     let char_offset = pos.character as usize;
 
@@ -43,11 +56,11 @@ pub fn position_to_byte_offset(text: &str, pos: Position) -> Result<usize, Strin
     let lines: Vec<&str> = text.split('\n').collect();
 
     if line_idx > lines.len() {
-        return Err(format!(
+        return Err(LspError::InvalidRange(format!(
             "Line {} out of bounds (total lines: {})",
             line_idx,
             lines.len()
-        ));
+        )));
     }
 
     if line_idx == lines.len() {
@@ -71,22 +84,22 @@ pub fn position_to_byte_offset(text: &str, pos: Position) -> Result<usize, Strin
 }
 
 /// Apply a text edit to a string based on LSP range
-pub fn apply_text_edit(text: &str, range: &Range, new_text: &str) -> Result<String, String> {
+pub fn apply_text_edit(text: &str, range: &Range, new_text: &str) -> Result<String, LspError> {
     let start_byte = position_to_byte_offset(text, range.start)?;
     let end_byte = position_to_byte_offset(text, range.end)?;
 
     if start_byte > end_byte {
-        return Err(format!(
+        return Err(LspError::InvalidRange(format!(
             "Invalid range: start ({start_byte}) > end ({end_byte})"
-        ));
+        )));
     }
 
     if end_byte > text.len() {
-        return Err(format!(
+        return Err(LspError::InvalidRange(format!(
             "Range end ({}) exceeds text length ({})",
             end_byte,
             text.len()
-        ));
+        )));
     }
 
     let mut result = String::with_capacity(text.len() + new_text.len());
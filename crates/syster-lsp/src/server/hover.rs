@@ -1,9 +1,30 @@
 use super::LspServer;
+use super::client_support::markdown_to_plain_text;
+use super::completion_context::analyze;
+use super::connector_ends::typed_by_conjugate;
+use super::constant_eval::{Quantity, evaluate, value_expression};
+use super::document_links::resolve_import;
 use super::helpers::{decode_uri_component, uri_to_path};
+use super::keyword_hover::{keyword_at, keyword_hover_contents};
+use super::organize_imports::{import_scope, in_scope};
+use super::reference_index::ReferenceIndex;
+use super::references::aliases_of;
+use super::symbol_lookup::{SymbolLookup, symbol};
+use super::type_hierarchy::general_types;
+use super::unit_resolution::{UnitResolution, quantity_literal_at, resolve_unit};
 use async_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position, Range, Url};
-use syster::hir::RelationshipKind;
+use std::collections::HashSet;
+use std::path::Path;
+use syster::base::FileId;
+use syster::hir::{
+    Direction, HirSymbol, Multiplicity, RelationshipKind, SymbolIndex, SymbolKind as HirSymbolKind,
+};
 use syster::ide::ResolvedRelationship;
 
+/// Appended to hovers answered while the workspace is still being indexed
+pub const PARTIAL_RESULTS_NOTE: &str =
+    "\n\n---\n_Partial results: the workspace is still being indexed._\n";
+
 impl LspServer {
     /// Get hover information for a symbol at the given position
     ///
@@ -12,32 +33,150 @@ impl LspServer {
         let path = uri_to_path(uri)?;
 
         let path_str = path.to_string_lossy();
-        let analysis = self.analysis_host.analysis();
+        let indexing = self.is_indexing();
+        let max_depth = self.hover_max_depth;
+        let markdown = self.client_support.markdown_hover;
+        // The lookups hold every root's files, so they follow the shared index
+        {
+            let shared = self.analysis_host.analysis();
+            self.reference_index.sync(&shared);
+            self.symbol_lookup.sync(&shared);
+        }
+        let analysis = self
+            .root_libraries
+            .host(&path, &mut self.analysis_host)
+            .analysis();
 
         // Get file ID for the new HIR layer
         let file_id = analysis.get_file_id(&path_str)?;
 
-        // Use the Analysis hover method
-        let result = analysis.hover(file_id, position.line, position.character)?;
+        // Quantities like `10 [km/h]` explain their unit
+        if let Some(text) = self.document_texts.get(&path)
+            && let Some((range, quantity)) = quantity_literal_at(text, position)
+        {
+            let document_texts = &self.document_texts;
+            let texts = |sym: &HirSymbol| {
+                let path = analysis.get_file_path(sym.file)?;
+                document_texts
+                    .get(std::path::Path::new(path))
+                    .map(String::as_str)
+            };
+            let scope = analyze(text, position).scope;
+            if let Some(resolution) =
+                resolve_unit(analysis.symbol_index(), &texts, &scope, &quantity.unit)
+            {
+                let contents = quantity_hover(&analysis, &quantity, &resolution);
+                return Some(Hover {
+                    contents: HoverContents::Markup(markup(contents, markdown)),
+                    range: Some(range),
+                });
+            }
+        }
+
+        // The `package` keyword hovers as the package it declares
+        let header = package_header(
+            &self.symbol_lookup,
+            analysis.symbol_index(),
+            &path,
+            file_id,
+            position,
+        );
+        let result = match analysis.hover(file_id, position.line, position.character) {
+            Some(result) => result,
+            None => match header {
+                Some(package) => analysis.hover(file_id, package.start_line, package.start_col)?,
+                // Other keywords explain the construct they introduce
+                None => {
+                    let text = self.document_texts.get(&path)?;
+                    let (keyword, range) = keyword_at(text, position)?;
+                    let contents = keyword_hover_contents(keyword)?;
+                    return Some(Hover {
+                        contents: HoverContents::Markup(markup(contents, markdown)),
+                        range: Some(range),
+                    });
+                }
+            },
+        };
 
-        // Get the qualified name from the result to find references
-        let mut contents = result.contents.clone();
+        let index = analysis.symbol_index();
+        let symbol = result
+            .qualified_name
+            .as_ref()
+            .and_then(|qualified_name| index.lookup_qualified(qualified_name));
+        let symbol_text = symbol.and_then(|symbol| {
+            let symbol_path = analysis.get_file_path(symbol.file)?;
+            self.document_texts.get(Path::new(symbol_path))
+        });
+
+        // Documentation comes first, inherited from a supertype if the element has none
+        let doc = symbol.and_then(|symbol| documentation(index, symbol));
+        let mut contents = HoverSections::new(&result.contents, doc);
+
+        if let Some(symbol) = symbol {
+            // Direction, multiplicity and modifiers declared on the symbol
+            if let Some(text) = symbol_text
+                && let Some(modifiers) = FeatureModifiers::of(text, symbol)
+            {
+                contents.details.push(modifiers.hover_line());
+            }
+
+            // Features bound to a constant expression show its value
+            if let Some(text) = symbol_text
+                && let Some((expression, _)) = value_expression(text, symbol)
+                && let Some(value) = evaluate(expression)
+            {
+                contents.details.push(format!("**Value:** `{value}`"));
+            }
+
+            // Enum literals show their enumeration and sibling literals
+            if let Some(literal) = EnumLiteral::from_index(index, symbol) {
+                contents.sections.push(literal.hover_section(&analysis));
+            }
+        }
+
+        // Each segment of the qualified name links to the namespace it names
+        if let Some(qualified_name) = result.qualified_name.as_ref() {
+            contents.link_qualified_name(&analysis, qualified_name);
+        }
 
         // Add relationships section with clickable links
-        contents = Self::add_relationships_section(&analysis, &contents, &result.relationships);
+        contents.sections.extend(Self::relationships_section(
+            &analysis,
+            &result.relationships,
+        ));
+
+        // Add the transitive supertypes, up to the configured depth
+        if let Some(symbol) = symbol {
+            contents
+                .sections
+                .extend(Self::inheritance_section(&analysis, symbol, max_depth));
+        }
 
         // Add "Referenced by:" section with clickable links
         if let Some(qualified_name) = result.qualified_name.as_ref() {
-            contents =
-                Self::add_references_section_from_analysis(&analysis, &contents, qualified_name);
+            contents.sections.extend(Self::references_section(
+                &analysis,
+                &self.reference_index,
+                &self.symbol_lookup,
+                qualified_name,
+            ));
+        }
+
+        // Package headers summarize how the package's imports resolved
+        if let Some(package) = header
+            && let Some(imports) = PackageImports::from_index(index, package)
+        {
+            contents.sections.push(imports.hover_section(&analysis));
+        }
+
+        // Only the documents opened so far are loaded while indexing runs
+        if indexing {
+            contents.sections.push(PARTIAL_RESULTS_NOTE.to_string());
         }
 
         // Convert to LSP Hover
         Some(Hover {
-            contents: HoverContents::Markup(MarkupContent {
-                kind: MarkupKind::Markdown,
-                value: contents,
-            }),
+            contents: HoverContents::Markup(markup(contents.render(), markdown)),
             range: Some(Range {
                 start: Position {
                     line: result.start_line,
@@ -51,16 +190,15 @@ impl LspServer {
         })
     }
 
-    /// Add relationships section with clickable links to definitions.
-    fn add_relationships_section(
+    /// Relationships section with clickable links to definitions.
+    fn relationships_section(
         analysis: &syster::ide::Analysis<'_>,
-        content: &str,
         relationships: &[ResolvedRelationship],
-    ) -> String {
+    ) -> Option<String> {
         use std::collections::HashMap;
 
         if relationships.is_empty() {
-            return content.to_string();
+            return None;
         }
 
         // Group relationships by kind
@@ -84,7 +222,7 @@ impl LspServer {
             RelationshipKind::Verifies,
         ];
 
-        let mut result = content.to_string();
+        let mut result = String::new();
 
         for kind in order {
             if let Some(rels) = by_kind.get(&kind) {
@@ -115,57 +253,120 @@ impl LspServer {
             }
         }
 
-        result
+        Some(result)
+    }
+
+    /// The supertypes of a symbol level by level, up to `max_depth` levels.
+    ///
+    /// Deeper levels are cut off with a "…" marker, so long stdlib hierarchies
+    /// keep hovers short. There is no section when only direct supertypes
+    /// exist, since the relationships section lists those, or when
+    /// `max_depth` is 0.
+    fn inheritance_section(
+        analysis: &syster::ide::Analysis<'_>,
+        symbol: &HirSymbol,
+        max_depth: usize,
+    ) -> Option<String> {
+        if max_depth == 0 {
+            return None;
+        }
+        let index = analysis.symbol_index();
+        let mut visited: HashSet<&str> = HashSet::from([symbol.qualified_name.as_ref()]);
+        let mut levels: Vec<Vec<&HirSymbol>> = Vec::new();
+        let mut truncated = false;
+        let mut level = vec![symbol];
+        loop {
+            let next: Vec<&HirSymbol> = level
+                .iter()
+                .flat_map(|sym| general_types(index, sym))
+                .filter(|supertype| visited.insert(supertype.qualified_name.as_ref()))
+                .collect();
+            if next.is_empty() {
+                break;
+            }
+            if levels.len() == max_depth {
+                truncated = true;
+                break;
+            }
+            levels.push(next.clone());
+            level = next;
+        }
+
+        if levels.is_empty() || (levels.len() == 1 && !truncated) {
+            return None;
+        }
+
+        let mut chain: Vec<String> = levels
+            .iter()
+            .map(|level| {
+                level
+                    .iter()
+                    .map(|sym| symbol_link(analysis, sym, &sym.name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect();
+        if truncated {
+            chain.push("…".to_string());
+        }
+
+        Some(format!("\n**Inheritance:** {}\n", chain.join(" → ")))
     }
 
-    /// Add "Referenced by:" section with clickable file links.
-    fn add_references_section_from_analysis(
+    /// "Referenced by:" section with clickable file links.
+    fn references_section(
         analysis: &syster::ide::Analysis<'_>,
-        content: &str,
+        reference_index: &ReferenceIndex,
+        symbol_lookup: &SymbolLookup,
         qualified_name: &str,
-    ) -> String {
+    ) -> Option<String> {
         // Get the simple name from qualified name for matching type_refs
         // type_refs store simple names like "Base", not "Test::Base"
         let simple_name = qualified_name.rsplit("::").next().unwrap_or(qualified_name);
+        let mut names = vec![qualified_name];
+        if simple_name != qualified_name {
+            names.push(simple_name);
+        }
 
-        // Collect references to this symbol
-        // Match both the simple name and qualified name since type_refs may use either
-        // type_refs are now TypeRefKind (Simple or Chain), so we flatten with as_refs()
-        let mut references: Vec<_> = analysis
-            .symbol_index()
-            .all_symbols()
-            .filter(|sym| {
-                sym.type_refs
-                    .iter()
-                    .flat_map(|trk| trk.as_refs())
-                    .any(|tr| {
-                        tr.target.as_ref() == qualified_name || tr.target.as_ref() == simple_name
-                    })
-            })
-            .flat_map(|sym| {
-                sym.type_refs
-                    .iter()
-                    .flat_map(|trk| trk.as_refs())
-                    .filter(|tr| {
-                        tr.target.as_ref() == qualified_name || tr.target.as_ref() == simple_name
-                    })
-                    .map(move |tr| (sym.file, tr.start_line, tr.start_col))
+        // Usages written with an alias count as references too
+        let index = analysis.symbol_index();
+        for alias in aliases_of(symbol_lookup, analysis, qualified_name) {
+            names.push(alias.qualified_name.as_ref());
+            if alias.name != alias.qualified_name {
+                names.push(alias.name.as_ref());
+            }
+        }
+
+        // Collect references to this symbol, looked up by the name they were
+        // written with rather than by scanning every symbol
+        let mut references: Vec<_> = names
+            .into_iter()
+            .flat_map(|name| reference_index.references_to(name))
+            .filter_map(|(path, entry)| {
+                let file = analysis.get_file_id(&path.to_string_lossy())?;
+                let owner = index.lookup_qualified(&entry.owner)?;
+                Some((
+                    file,
+                    entry.range.start.line,
+                    entry.range.start.character,
+                    owner,
+                ))
             })
             .collect();
 
         if references.is_empty() {
-            return content.to_string();
+            return None;
         }
 
         // Sort for deterministic output
-        references.sort_by_key(|(file, line, col)| (*file, *line, *col));
+        references.sort_by_key(|(file, line, col, _)| (*file, *line, *col));
 
-        let mut result = content.to_string();
+        let mut result = String::new();
         let count = references.len();
         let plural = if count == 1 { "" } else { "s" };
         result.push_str(&format!("\n**Referenced by:** ({count} usage{plural})\n"));
 
-        for (file_id, line, col) in references {
+        for (file_id, line, col, sym) in references {
             if let Some(path) = analysis.get_file_path(file_id)
                 && let Ok(uri) = Url::from_file_path(path)
             {
@@ -177,12 +378,514 @@ impl LspServer {
                 let display_line = line + 1; // 1-indexed for display
                 let display_col = col + 1;
                 result.push_str(&format!(
-                    "- [{decoded_file_name}:{display_line}:{display_col}]({}#L{display_line})\n",
+                    "- [{decoded_file_name}:{display_line}:{display_col}]({}#L{display_line})",
                     uri
                 ));
+                // Name the referencing element, linked to its declaration
+                if !sym.name.starts_with('<') {
+                    result.push_str(" in ");
+                    result.push_str(&symbol_link(analysis, sym, &sym.qualified_name));
+                }
+                result.push('\n');
+            }
+        }
+
+        Some(result)
+    }
+}
+
+/// Hover contents in markdown, or its plain text for clients that can't render it
+fn markup(contents: String, markdown: bool) -> MarkupContent {
+    if markdown {
+        MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: contents,
+        }
+    } else {
+        MarkupContent {
+            kind: MarkupKind::PlainText,
+            value: markdown_to_plain_text(&contents),
+        }
+    }
+}
+
+/// A markdown link labelled `text` to where a symbol is declared, or `text`
+/// as code when its file has no URI
+fn symbol_link(analysis: &syster::ide::Analysis<'_>, sym: &HirSymbol, text: &str) -> String {
+    match analysis
+        .get_file_path(sym.file)
+        .and_then(|path| Url::from_file_path(path).ok())
+    {
+        Some(uri) => format!("[{text}]({uri}#L{})", sym.start_line + 1),
+        None => format!("`{text}`"),
+    }
+}
+
+/// Hover contents, built up section by section and rendered once
+///
+/// The IDE layer's hover is split around its qualified name line, so details
+/// of the element go just before that line and further sections after the
+/// hover.
+#[derive(Debug, Default)]
+struct HoverSections {
+    /// Documentation, shown before everything else
+    documentation: Option<String>,
+    /// The IDE layer's hover up to its qualified name line
+    head: String,
+    /// Lines shown just before the qualified name
+    details: Vec<String>,
+    /// The qualified name as shown, and the IDE layer's hover after it
+    qualified_name: Option<(String, String)>,
+    /// Sections shown after the IDE layer's hover
+    sections: Vec<String>,
+}
+
+impl HoverSections {
+    const QUALIFIED_NAME_LABEL: &str = "\n**Qualified Name:**";
+
+    /// Split the IDE layer's hover, moving the element's own documentation
+    /// out of it to the top
+    fn new(base: &str, documentation: Option<(&str, Option<&HirSymbol>)>) -> Self {
+        let base = match documentation {
+            Some((doc, None)) => without_copy(base, doc.trim()),
+            _ => base.to_string(),
+        };
+        let (head, qualified_name) = match base.find(Self::QUALIFIED_NAME_LABEL) {
+            Some(idx) => {
+                let start = idx + Self::QUALIFIED_NAME_LABEL.len();
+                let end = base[start..]
+                    .find('\n')
+                    .map_or(base.len(), |idx| start + idx);
+                let line = (base[start..end].to_string(), base[end..].to_string());
+                (base[..idx].to_string(), Some(line))
+            }
+            None => (base, None),
+        };
+        Self {
+            documentation: documentation.map(|(doc, source)| documentation_section(doc, source)),
+            head,
+            qualified_name,
+            ..Default::default()
+        }
+    }
+
+    /// Show each segment of the qualified name as a link to the namespace it
+    /// names (`[P](…#L1)::[Vehicle](…#L2)`)
+    fn link_qualified_name(&mut self, analysis: &syster::ide::Analysis<'_>, qualified_name: &str) {
+        let Some((shown, _)) = self.qualified_name.as_mut() else {
+            return;
+        };
+        if shown.trim().trim_matches('`') != qualified_name {
+            return;
+        }
+
+        let index = analysis.symbol_index();
+        let mut prefix = String::new();
+        let mut segments = Vec::new();
+        for segment in qualified_name.split("::") {
+            if !prefix.is_empty() {
+                prefix.push_str("::");
+            }
+            prefix.push_str(segment);
+            segments.push(match index.lookup_qualified(&prefix) {
+                Some(sym) => symbol_link(analysis, sym, segment),
+                None => format!("`{segment}`"),
+            });
+        }
+        *shown = format!(" {}", segments.join("::"));
+    }
+
+    /// The hover as markdown
+    fn render(self) -> String {
+        let mut body = self.head;
+        for detail in &self.details {
+            body.push_str(&format!("\n{detail}\n"));
+        }
+        if let Some((shown, rest)) = &self.qualified_name {
+            body.push_str(Self::QUALIFIED_NAME_LABEL);
+            body.push_str(shown);
+            body.push_str(rest);
+        }
+        for section in &self.sections {
+            body.push_str(section);
+        }
+        match self.documentation {
+            Some(documentation) => format!("{documentation}\n\n---\n\n{body}"),
+            None => body,
+        }
+    }
+}
+
+/// The documentation of a symbol, or of the nearest element it specializes
+/// that has some, with that element when the documentation is inherited
+fn documentation<'a>(
+    index: &'a SymbolIndex,
+    symbol: &'a HirSymbol,
+) -> Option<(&'a str, Option<&'a HirSymbol>)> {
+    if let Some(doc) = symbol.doc.as_deref().filter(|doc| !doc.trim().is_empty()) {
+        return Some((doc, None));
+    }
+
+    let mut visited: HashSet<&str> = HashSet::from([symbol.qualified_name.as_ref()]);
+    let mut queue: std::collections::VecDeque<&HirSymbol> = general_types(index, symbol).into();
+    while let Some(supertype) = queue.pop_front() {
+        if !visited.insert(supertype.qualified_name.as_ref()) {
+            continue;
+        }
+        if let Some(doc) = supertype
+            .doc
+            .as_deref()
+            .filter(|doc| !doc.trim().is_empty())
+        {
+            return Some((doc, Some(supertype)));
+        }
+        queue.extend(general_types(index, supertype));
+    }
+    None
+}
+
+/// `content` with its first copy of `text` removed
+fn without_copy(content: &str, text: &str) -> String {
+    let Some(idx) = content.find(text) else {
+        return content.to_string();
+    };
+    let before = content[..idx].trim_end();
+    let after = content[idx + text.len()..].trim_start();
+    if before.is_empty() {
+        after.to_string()
+    } else {
+        format!("{before}\n\n{after}")
+    }
+}
+
+/// Documentation as shown at the top of a hover, naming the element it's
+/// inherited from
+fn documentation_section(doc: &str, source: Option<&HirSymbol>) -> String {
+    let mut section = doc_markdown(doc);
+    if let Some(source) = source {
+        section.push_str(&format!("\n\n_Inherited from `{}`_", source.qualified_name));
+    }
+    section
+}
+
+/// The text of a `doc /* ... */` body with its comment markers and leading
+/// `*` of each line removed
+fn doc_markdown(doc: &str) -> String {
+    let body = doc.trim();
+    let body = body.strip_prefix("/*").unwrap_or(body);
+    let body = body.strip_suffix("*/").unwrap_or(body);
+    let lines: Vec<&str> = body
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            line.strip_prefix("* ")
+                .or_else(|| line.strip_prefix('*'))
+                .unwrap_or(line)
+        })
+        .collect();
+    let start = lines.iter().position(|line| !line.is_empty()).unwrap_or(0);
+    let end = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |idx| idx + 1);
+    lines[start..end.max(start)].join("\n")
+}
+
+/// Modifiers written on a feature or definition declaration
+///
+/// An element is deprecated if it carries `#deprecated` or `@Deprecated`
+/// metadata.
+#[derive(Debug, Default, PartialEq)]
+pub struct FeatureModifiers {
+    pub direction: Option<String>,
+    pub multiplicity: Option<String>,
+    pub is_abstract: bool,
+    pub is_derived: bool,
+    pub is_readonly: bool,
+    pub is_deprecated: bool,
+    /// Typed by a conjugated port definition (`: ~FuelPort`)
+    pub is_conjugated: bool,
+}
+
+impl FeatureModifiers {
+    /// The modifiers of `symbol`, declared in `text`
+    pub fn of(text: &str, symbol: &HirSymbol) -> Option<Self> {
+        if matches!(
+            symbol.kind,
+            HirSymbolKind::Package
+                | HirSymbolKind::Import
+                | HirSymbolKind::Alias
+                | HirSymbolKind::Comment
+        ) {
+            return None;
+        }
+
+        let modifiers = FeatureModifiers {
+            direction: symbol.direction.map(|direction| {
+                match direction {
+                    Direction::In => "in",
+                    Direction::Out => "out",
+                    Direction::InOut => "inout",
+                }
+                .to_string()
+            }),
+            multiplicity: symbol.multiplicity.as_ref().map(multiplicity_text),
+            is_abstract: symbol.is_abstract,
+            is_derived: symbol.is_derived,
+            is_readonly: symbol.is_readonly,
+            is_deprecated: symbol.metadata_annotations.iter().any(|metadata| {
+                let name = metadata.rsplit("::").next().unwrap_or(metadata);
+                name.eq_ignore_ascii_case("deprecated")
+            }),
+            is_conjugated: typed_by_conjugate(text, symbol),
+        };
+        (modifiers != FeatureModifiers::default()).then_some(modifiers)
+    }
+
+    /// The modifiers line of a hover
+    fn hover_line(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(direction) = &self.direction {
+            parts.push(format!("**Direction:** `{direction}`"));
+        }
+        if let Some(multiplicity) = &self.multiplicity {
+            parts.push(format!("**Multiplicity:** `{multiplicity}`"));
+        }
+        let flags: Vec<&str> = [
+            (self.is_abstract, "abstract"),
+            (self.is_derived, "derived"),
+            (self.is_readonly, "readonly"),
+            (self.is_deprecated, "deprecated"),
+            (self.is_conjugated, "conjugated"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect();
+        if !flags.is_empty() {
+            parts.push(format!("**Modifiers:** {}", flags.join(", ")));
+        }
+
+        parts.join(" · ")
+    }
+}
+
+/// A multiplicity as written, like `[1..*]` or `[4]`
+fn multiplicity_text(multiplicity: &Multiplicity) -> String {
+    let bound = |bound: Option<u64>| bound.map_or("*".to_string(), |n| n.to_string());
+    match (multiplicity.lower, multiplicity.upper) {
+        (Some(lower), upper) if Some(lower) != upper => format!("[{lower}..{}]", bound(upper)),
+        (_, upper) => format!("[{}]", bound(upper)),
+    }
+}
+
+/// Hover contents for a quantity literal: its kind, its value in SI units and
+/// the library units it is written in
+fn quantity_hover(
+    analysis: &syster::ide::Analysis<'_>,
+    quantity: &Quantity,
+    resolution: &UnitResolution<'_>,
+) -> String {
+    let mut contents = format!("**Quantity:** `{quantity}`\n");
+    if let Some(kind) = &resolution.quantity_kind {
+        contents.push_str(&format!("\n**Quantity Kind:** {kind}\n"));
+    }
+    if let Some(si) = &resolution.si {
+        let value = Quantity {
+            value: quantity.value * si.value,
+            unit: si.unit.clone(),
+        };
+        contents.push_str(&format!("\n**SI Value:** `{value}`\n"));
+    }
+    contents.push_str("\n**Units:**\n");
+    for resolved in &resolution.units {
+        let link = symbol_link(analysis, resolved.unit, &resolved.unit.qualified_name);
+        match &resolved.unit_type {
+            Some(unit_type) => contents.push_str(&format!(
+                "- `{}`: {link} : `{unit_type}`\n",
+                resolved.symbol
+            )),
+            None => contents.push_str(&format!("- `{}`: {link}\n", resolved.symbol)),
+        }
+    }
+    contents
+}
+
+/// A literal of an enumeration definition
+///
+/// The HIR doesn't give enum literals a kind of their own, so a literal is
+/// any feature owned directly by an `enum def`. Literals are ordered by their
+/// declaration.
+pub struct EnumLiteral<'a> {
+    pub enumeration: &'a HirSymbol,
+    /// Zero-based position among the enumeration's literals
+    pub index: usize,
+    pub literals: Vec<&'a HirSymbol>,
+}
+
+impl<'a> EnumLiteral<'a> {
+    /// The enumeration owning `symbol`, if `symbol` is one of its literals
+    pub fn from_index(index: &'a SymbolIndex, symbol: &HirSymbol) -> Option<Self> {
+        let (owner, _) = symbol.qualified_name.rsplit_once("::")?;
+        let enumeration = index
+            .lookup_qualified(owner)
+            .filter(|sym| sym.kind == HirSymbolKind::EnumerationDef)?;
+
+        let mut literals: Vec<&HirSymbol> = index
+            .symbols_in_file(enumeration.file)
+            .into_iter()
+            .filter(|sym| {
+                sym.qualified_name
+                    .rsplit_once("::")
+                    .is_some_and(|(parent, _)| parent == owner)
+                    && !sym.kind.is_definition()
+                    && !matches!(
+                        sym.kind,
+                        HirSymbolKind::Import | HirSymbolKind::Alias | HirSymbolKind::Comment
+                    )
+            })
+            .collect();
+        literals.sort_by_key(|sym| (sym.start_line, sym.start_col));
+
+        let index = literals
+            .iter()
+            .position(|sym| sym.qualified_name == symbol.qualified_name)?;
+        Some(Self {
+            enumeration,
+            index,
+            literals,
+        })
+    }
+
+    /// Hover section naming the enumeration and its literals
+    fn hover_section(&self, analysis: &syster::ide::Analysis<'_>) -> String {
+        let name = &self.enumeration.name;
+        let enumeration = match analysis
+            .get_file_path(self.enumeration.file)
+            .and_then(|path| Url::from_file_path(path).ok())
+        {
+            Some(uri) => format!("[{name}]({uri}#L{})", self.enumeration.start_line + 1),
+            None => format!("`{name}`"),
+        };
+        let literals: Vec<String> = self
+            .literals
+            .iter()
+            .enumerate()
+            .map(|(i, literal)| {
+                if i == self.index {
+                    format!("**`{}`**", literal.name)
+                } else {
+                    format!("`{}`", literal.name)
+                }
+            })
+            .collect();
+
+        format!(
+            "\n**Enumeration:** {enumeration} · literal {} of {}\n**Literals:** {}\n",
+            self.index + 1,
+            self.literals.len(),
+            literals.join(", ")
+        )
+    }
+}
+
+/// The package whose header line the position is on, up to the end of its name
+fn package_header<'a>(
+    lookup: &SymbolLookup,
+    index: &'a SymbolIndex,
+    path: &Path,
+    file: FileId,
+    position: Position,
+) -> Option<&'a HirSymbol> {
+    lookup
+        .on_line(path, position.line)
+        .iter()
+        .filter(|entry| position.character <= entry.range.end.character)
+        .filter_map(|entry| symbol(index, file, entry))
+        .find(|sym| sym.kind == HirSymbolKind::Package)
+}
+
+/// The imports of a package and its members, and which of them resolve
+pub struct PackageImports<'a> {
+    /// Number of elements declared in the package in any file, imports excluded
+    pub members: usize,
+    /// Imports of the hovered declaration in declaration order, with whether
+    /// each resolves
+    pub imports: Vec<(&'a HirSymbol, bool)>,
+}
+
+impl<'a> PackageImports<'a> {
+    /// The imports of `package`, if it has any
+    pub fn from_index(index: &'a SymbolIndex, package: &HirSymbol) -> Option<Self> {
+        let scope = package.qualified_name.as_ref();
+        let mut imports: Vec<&HirSymbol> = index
+            .symbols_in_file(package.file)
+            .into_iter()
+            .filter(|sym| sym.kind == HirSymbolKind::Import && in_scope(import_scope(sym), scope))
+            .collect();
+        if imports.is_empty() {
+            return None;
+        }
+        // A package can be declared again in other files, which add members too
+        let members = index
+            .all_symbols()
+            .filter(|sym| {
+                sym.kind != HirSymbolKind::Import
+                    && sym.qualified_name.as_ref() != scope
+                    && in_scope(&sym.qualified_name, scope)
+            })
+            .count();
+        imports.sort_by_key(|sym| (sym.start_line, sym.start_col));
+
+        let imports = imports
+            .into_iter()
+            .map(|import| {
+                let path = import.name.as_ref();
+                let target = path
+                    .strip_suffix("::**")
+                    .or_else(|| path.strip_suffix("::*"))
+                    .unwrap_or(path);
+                (import, resolve_import(index, import, target).is_some())
+            })
+            .collect();
+        Some(Self { members, imports })
+    }
+
+    pub fn failed(&self) -> usize {
+        self.imports
+            .iter()
+            .filter(|(_, resolved)| !resolved)
+            .count()
+    }
+
+    /// Hover section with the import statistics, linking to the first
+    /// failing import
+    fn hover_section(&self, analysis: &syster::ide::Analysis<'_>) -> String {
+        let total = self.imports.len();
+        let failed = self.failed();
+        let mut section = format!(
+            "\n**Members:** {} · **Imports:** {} of {total} resolved",
+            self.members,
+            total - failed
+        );
+
+        if let Some((first, _)) = self.imports.iter().find(|(_, resolved)| !resolved) {
+            section.push_str(&format!(", {failed} failed"));
+            if let Some(uri) = analysis
+                .get_file_path(first.file)
+                .and_then(|path| Url::from_file_path(path).ok())
+            {
+                section.push_str(&format!(
+                    " · [first failure]({uri}#L{})",
+                    first.start_line + 1
+                ));
             }
         }
+        section.push('\n');
 
-        result
+        for (import, resolved) in &self.imports {
+            let status = if *resolved { "resolved" } else { "unresolved" };
+            section.push_str(&format!("- `{}` {status}\n", import.name));
+        }
+        section
     }
 }
@@ -0,0 +1,60 @@
+//! textDocument/implementation handler.
+//!
+//! Navigates from an element to the elements that implement it: from a
+//! definition to the usages typed by it and the definitions specializing it,
+//! and from a usage to the features that subset or redefine it. This is the
+//! reverse of `textDocument/typeDefinition` and the type hierarchy's supertypes.
+
+use super::LspServer;
+use super::type_hierarchy::hierarchy_targets;
+use async_lsp::lsp_types::{Location, Position, Range, Url};
+use syster::hir::{HirSymbol, RefKind, SymbolIndex};
+
+impl LspServer {
+    /// Get the elements typed by, specializing, subsetting or redefining the
+    /// element at the given position, ordered by file and position
+    pub fn get_implementations(&mut self, uri: &Url, position: Position) -> Vec<Location> {
+        let _ = self.ensure_workspace_loaded();
+        let Some(qualified_name) = self.hierarchy_element_at(uri, position) else {
+            return Vec::new();
+        };
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+
+        let mut implementations: Vec<(_, &HirSymbol)> = index
+            .all_symbols()
+            .filter(|sym| implements(index, sym, &qualified_name))
+            .filter_map(|sym| Some((analysis.get_file_path(sym.file)?, sym)))
+            .collect();
+        implementations.sort_by_key(|(path, sym)| (*path, sym.start_line, sym.start_col));
+
+        implementations
+            .into_iter()
+            .filter_map(|(path, sym)| {
+                Some(Location {
+                    uri: Url::from_file_path(path).ok()?,
+                    range: Range::new(
+                        Position::new(sym.start_line, sym.start_col),
+                        Position::new(sym.end_line, sym.end_col),
+                    ),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Whether `symbol` is typed by, specializes, subsets or redefines `target`
+fn implements(index: &SymbolIndex, symbol: &HirSymbol, target: &str) -> bool {
+    let typed_by = symbol
+        .type_refs
+        .iter()
+        .flat_map(|trk| trk.as_refs())
+        .any(|type_ref| {
+            type_ref.kind == RefKind::TypedBy && type_ref.resolved_target.as_deref() == Some(target)
+        });
+    typed_by
+        || hierarchy_targets(index, symbol)
+            .iter()
+            .any(|supertype| supertype.qualified_name.as_ref() == target)
+}
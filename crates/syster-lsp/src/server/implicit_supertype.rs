@@ -0,0 +1,140 @@
+//! Implicit library supertype request handler for LSP.
+//!
+//! Every definition and usage implicitly specializes a standard library
+//! element chosen by its kind: `part def Vehicle;` is a `Parts::Part` and
+//! `part engine;` is one of `Parts::parts`. The `syster/getImplicitSupertype`
+//! request exposes that element, so clients can offer "Go to Implicit
+//! Supertype" and users can learn the library structure from their own models.
+
+use super::LspServer;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::request::Request;
+use async_lsp::lsp_types::{LocationLink, Position, Range, Url};
+use serde::{Deserialize, Serialize};
+use syster::hir::SymbolKind;
+
+/// Custom LSP request: syster/getImplicitSupertype
+///
+/// Returns the library element implicitly specialized by the element declared
+/// or referenced at a position.
+pub enum ImplicitSupertypeRequest {}
+
+impl Request for ImplicitSupertypeRequest {
+    type Params = ImplicitSupertypeParams;
+    type Result = Option<ImplicitSupertypeResult>;
+    const METHOD: &'static str = "syster/getImplicitSupertype";
+}
+
+/// Request parameters for syster/getImplicitSupertype
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImplicitSupertypeParams {
+    /// URI of the document
+    pub uri: String,
+    /// Cursor position
+    pub position: Position,
+}
+
+/// Result of the syster/getImplicitSupertype request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImplicitSupertypeResult {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Qualified name of the element at the position
+    pub qualified_name: String,
+
+    /// Qualified name of its implicit library supertype (e.g., "Parts::Part")
+    pub supertype: String,
+
+    /// Where the supertype is declared; `null` when the stdlib isn't loaded
+    pub link: Option<LocationLink>,
+}
+
+impl LspServer {
+    /// Get the implicit library supertype of the element at a position
+    pub fn get_implicit_supertype(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> Option<ImplicitSupertypeResult> {
+        let qualified_name = self.get_qualified_name_at(uri, position)?.qualified_name;
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+        let symbol = index.lookup_qualified(&qualified_name)?;
+        let supertype = implicit_supertype(symbol.kind)?;
+
+        let link = index.lookup_qualified(supertype).and_then(|target| {
+            let range = Range::new(
+                Position::new(target.start_line, target.start_col),
+                Position::new(target.end_line, target.end_col),
+            );
+            Some(LocationLink {
+                origin_selection_range: None,
+                target_uri: Url::from_file_path(analysis.get_file_path(target.file)?).ok()?,
+                target_range: range,
+                target_selection_range: range,
+            })
+        });
+
+        Some(ImplicitSupertypeResult {
+            schema_version: SCHEMA_VERSION,
+            qualified_name,
+            supertype: supertype.to_string(),
+            link,
+        })
+    }
+}
+
+/// The library element that elements of a kind implicitly specialize
+pub fn implicit_supertype(kind: SymbolKind) -> Option<&'static str> {
+    let supertype = match kind {
+        // Definitions
+        SymbolKind::PartDef => "Parts::Part",
+        SymbolKind::ItemDef => "Items::Item",
+        SymbolKind::ActionDef => "Actions::Action",
+        SymbolKind::PortDef => "Ports::Port",
+        SymbolKind::AttributeDef | SymbolKind::EnumerationDef => "Base::DataValue",
+        SymbolKind::ConnectionDef => "Connections::Connection",
+        SymbolKind::InterfaceDef => "Interfaces::Interface",
+        SymbolKind::AllocationDef => "Allocations::Allocation",
+        SymbolKind::RequirementDef => "Requirements::RequirementCheck",
+        SymbolKind::ConstraintDef => "Constraints::ConstraintCheck",
+        SymbolKind::StateDef => "States::StateAction",
+        SymbolKind::CalculationDef => "Calculations::Calculation",
+        SymbolKind::UseCaseDef => "UseCases::UseCase",
+        SymbolKind::AnalysisCaseDef => "AnalysisCases::AnalysisCase",
+        SymbolKind::ConcernDef => "Requirements::ConcernCheck",
+        SymbolKind::ViewDef => "Views::View",
+        SymbolKind::ViewpointDef => "Views::ViewpointCheck",
+        SymbolKind::RenderingDef => "Views::Rendering",
+
+        // Usages
+        SymbolKind::PartUsage => "Parts::parts",
+        SymbolKind::ItemUsage => "Items::items",
+        SymbolKind::ActionUsage => "Actions::actions",
+        SymbolKind::PortUsage => "Ports::ports",
+        SymbolKind::AttributeUsage => "Base::dataValues",
+        SymbolKind::ConnectionUsage => "Connections::connections",
+        SymbolKind::InterfaceUsage => "Interfaces::interfaces",
+        SymbolKind::AllocationUsage => "Allocations::allocations",
+        SymbolKind::RequirementUsage => "Requirements::requirementChecks",
+        SymbolKind::ConstraintUsage => "Constraints::constraintChecks",
+        SymbolKind::StateUsage => "States::stateActions",
+        SymbolKind::CalculationUsage => "Calculations::calculations",
+        SymbolKind::ReferenceUsage => "Base::things",
+        SymbolKind::OccurrenceUsage => "Occurrences::occurrences",
+        SymbolKind::FlowUsage => "Flows::flows",
+
+        // Other
+        SymbolKind::Package
+        | SymbolKind::Alias
+        | SymbolKind::Import
+        | SymbolKind::Comment
+        | SymbolKind::Dependency
+        | SymbolKind::Other => return None,
+    };
+    Some(supertype)
+}
@@ -0,0 +1,521 @@
+//! Incremental reparsing for SysML documents.
+//!
+//! The parser only works on whole files, so instead of re-parsing the whole
+//! document after each keystroke we split it into members and parse each one
+//! on its own, indented to its original column. Member results are cached by
+//! their text and column and moved to their current line afterwards, so an edit
+//! only re-parses the member it touched, even when it shifted later lines.
+//!
+//! Two layouts are handled:
+//! - several top-level elements: each element is parsed on its own
+//! - a single top-level package with a body (e.g. `ISQ.sysml`): the package
+//!   header is parsed once with an empty body and each body member is parsed
+//!   inside a copy of that header
+//!
+//! Anything else, or any member that fails to parse, returns `None` so the
+//! caller falls back to a full parse with its error recovery.
+
+use super::language::Language;
+use super::parse_cache::{CachedParse, ParseCache};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use syster::syntax::sysml::ast::{
+    Comment, Definition, DefinitionMember, Element, ExtractedRef, Import, NamespaceDeclaration,
+    Relationships, SysMLFile, Usage, UsageMember,
+};
+use syster::syntax::{Span, SyntaxFile};
+
+/// Number of member parse results kept between edits
+const SEGMENT_CACHE_CAPACITY: usize = 4096;
+
+/// The text a cached member result was parsed from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum SegmentKey {
+    /// A top-level element, indented to its column
+    TopLevel { col: usize, member: Arc<str> },
+    /// A package header with an empty body
+    Skeleton(Arc<str>),
+    /// A body member inside a copy of its package header
+    Member {
+        header: Arc<str>,
+        same_line: bool,
+        col: usize,
+        member: Arc<str>,
+    },
+}
+
+/// Parses SysML documents member-by-member, reusing unchanged members
+#[derive(Debug)]
+pub struct IncrementalParser {
+    segments: ParseCache<SegmentKey>,
+}
+
+impl Default for IncrementalParser {
+    fn default() -> Self {
+        Self {
+            segments: ParseCache::with_capacity(SEGMENT_CACHE_CAPACITY),
+        }
+    }
+}
+
+impl IncrementalParser {
+    /// Parse `text` reusing cached member results.
+    ///
+    /// Returns `None` when the document can't be split safely or a member has
+    /// errors; callers should then run a full parse.
+    pub fn parse(&mut self, path: &Path, text: &str) -> Option<CachedParse> {
+        if Language::of(path) != Some(Language::SysML) {
+            return None;
+        }
+
+        let top_level = split_members(text, 0..text.len())?;
+        let lines = LineIndex::new(text);
+        let file = match top_level.as_slice() {
+            [] => return None,
+            [single] => {
+                let (open, close) = find_body(text, single.clone())?;
+                self.parse_package_members(path, text, &lines, open, close)?
+            }
+            segments => self.parse_top_level(path, text, &lines, segments)?,
+        };
+
+        Some(CachedParse {
+            content: Some(SyntaxFile::SysML(file)),
+            errors: Vec::new(),
+        })
+    }
+
+    /// Number of member results currently cached
+    pub fn cached_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Drop all cached member results. Returns the number removed.
+    pub fn clear(&mut self) -> usize {
+        self.segments.clear()
+    }
+
+    /// Parse each top-level element on its own and concatenate the results
+    fn parse_top_level(
+        &mut self,
+        path: &Path,
+        text: &str,
+        lines: &LineIndex,
+        segments: &[Range<usize>],
+    ) -> Option<SysMLFile> {
+        let mut merged = SysMLFile {
+            namespace: None,
+            namespaces: Vec::new(),
+            elements: Vec::new(),
+        };
+
+        for segment in segments {
+            let (line, col) = lines.line_col(text, segment.start);
+            let member = &text[segment.clone()];
+            // Parsed on line 0, then moved to the member's real line
+            let key = SegmentKey::TopLevel {
+                col,
+                member: Arc::from(member),
+            };
+            let parsed = self.segments.get_or_parse_keyed(key, member.len(), || {
+                CachedParse::parse(path, &format!("{}{}", " ".repeat(col), member))
+            });
+            let mut elements = successful_sysml(&parsed)?.elements;
+            elements.shift_lines(line);
+            merged.elements.extend(elements);
+        }
+
+        Some(with_namespaces(merged))
+    }
+
+    /// Parse the single top-level package's header once and each body member inside it
+    fn parse_package_members(
+        &mut self,
+        path: &Path,
+        text: &str,
+        lines: &LineIndex,
+        open: usize,
+        close: usize,
+    ) -> Option<SysMLFile> {
+        let header = &text[..=open];
+        let (header_line, header_col) = lines.line_col(text, open + 1);
+        let members = split_members(text, open + 1..close)?;
+
+        // The package with an empty body; its name span lies in the header
+        let skeleton_text = format!("{header}}}");
+        let key = SegmentKey::Skeleton(Arc::from(skeleton_text.as_str()));
+        let mut skeleton = successful_sysml(&self.segments.get_or_parse_keyed(
+            key,
+            skeleton_text.len(),
+            || CachedParse::parse(path, &skeleton_text),
+        ))?;
+        let shared_header: Arc<str> = Arc::from(header);
+
+        let mut elements = Vec::new();
+        for member in members {
+            let (line, col) = lines.line_col(text, member.start);
+            let member_text = &text[member.clone()];
+            let same_line = line == header_line;
+            // Members on their own line are parsed on the line after the header,
+            // then moved to their real line
+            let key = SegmentKey::Member {
+                header: Arc::clone(&shared_header),
+                same_line,
+                col,
+                member: Arc::from(member_text),
+            };
+            let parsed = self
+                .segments
+                .get_or_parse_keyed(key, member_text.len(), || {
+                    let gap = if same_line {
+                        " ".repeat(col - header_col)
+                    } else {
+                        format!("\n{}", " ".repeat(col))
+                    };
+                    CachedParse::parse(path, &format!("{header}{gap}{member_text}\n}}"))
+                });
+            let parsed = successful_sysml(&parsed)?;
+            let mut package_elements = match parsed.elements.into_iter().next() {
+                Some(Element::Package(package)) => package.elements,
+                _ => return None,
+            };
+            if !same_line {
+                package_elements.shift_lines(line - header_line - 1);
+            }
+            elements.extend(package_elements);
+        }
+
+        match skeleton.elements.as_mut_slice() {
+            [Element::Package(package)] if package.elements.is_empty() => {
+                package.elements = elements;
+                Some(with_namespaces(skeleton))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Recompute namespace declarations the way the parser does for a whole file:
+/// every top-level package without members declares a namespace
+fn with_namespaces(mut file: SysMLFile) -> SysMLFile {
+    file.namespaces = file
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            Element::Package(package) if package.elements.is_empty() => {
+                Some(NamespaceDeclaration {
+                    name: package.name.clone()?,
+                    span: package.span,
+                })
+            }
+            _ => None,
+        })
+        .collect();
+    file.namespace = file.namespaces.first().cloned();
+    file
+}
+
+/// A copy of the SysML file of an error-free parse
+fn successful_sysml(parsed: &CachedParse) -> Option<SysMLFile> {
+    if !parsed.errors.is_empty() {
+        return None;
+    }
+    match parsed.content.as_ref()? {
+        SyntaxFile::SysML(file) => Some(file.clone()),
+        SyntaxFile::KerML(_) => None,
+    }
+}
+
+/// Split `range` of `text` into members, each ending in `;` or a balanced `}`.
+///
+/// Comments and whitespace before a member belong to it; trailing text without
+/// a terminator becomes the last member. Returns `None` on unbalanced braces.
+pub(super) fn split_members(text: &str, range: Range<usize>) -> Option<Vec<Range<usize>>> {
+    let bytes = text.as_bytes();
+    let mut members = Vec::new();
+    let mut depth = 0usize;
+    let mut start: Option<usize> = None;
+    let mut i = range.start;
+
+    while i < range.end {
+        let b = bytes[i];
+        if start.is_none() && !b.is_ascii_whitespace() {
+            start = Some(i);
+        }
+        match b {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = find_from(text, i, "\n").unwrap_or(range.end);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = find_from(text, i + 2, "*/")? + 2;
+                continue;
+            }
+            b'"' => {
+                i = find_from(text, i + 1, "\"")? + 1;
+                continue;
+            }
+            b'\'' => {
+                i = skip_quoted_name(bytes, i + 1)?;
+                continue;
+            }
+            b'{' => depth += 1,
+            b'}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    members.push(start.take()?..i + 1);
+                }
+            }
+            b';' if depth == 0 => members.push(start.take()?..i + 1),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if depth != 0 {
+        return None;
+    }
+    if let Some(start) = start {
+        members.push(start..range.end);
+    }
+    Some(members)
+}
+
+/// Find the body braces of a member (`{` at depth 0 and its closing `}` at the end)
+pub(super) fn find_body(text: &str, member: Range<usize>) -> Option<(usize, usize)> {
+    let close = member.end.checked_sub(1)?;
+    if text.as_bytes().get(close) != Some(&b'}') {
+        return None;
+    }
+    // The first member-level split point of the header is the opening brace
+    let bytes = text.as_bytes();
+    let mut i = member.start;
+    while i < close {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = find_from(text, i, "\n")?;
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = find_from(text, i + 2, "*/")? + 2;
+                continue;
+            }
+            b'"' => {
+                i = find_from(text, i + 1, "\"")? + 1;
+                continue;
+            }
+            b'\'' => {
+                i = skip_quoted_name(bytes, i + 1)?;
+                continue;
+            }
+            b'{' => return Some((i, close)),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Skip a quoted name body (after the opening quote), honouring backslash escapes
+fn skip_quoted_name(bytes: &[u8], mut i: usize) -> Option<usize> {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\'' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+fn find_from(text: &str, from: usize, needle: &str) -> Option<usize> {
+    text.get(from..)?.find(needle).map(|idx| from + idx)
+}
+
+/// Byte offsets of line starts, for converting offsets to line/column
+pub(super) struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub(super) fn new(text: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self { starts }
+    }
+
+    /// Line and column (in chars) of a byte offset
+    pub(super) fn line_col(&self, text: &str, offset: usize) -> (usize, usize) {
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        (line, text[self.starts[line]..offset].chars().count())
+    }
+}
+
+/// Move every span in a parsed element down by a number of lines
+trait ShiftLines {
+    fn shift_lines(&mut self, delta: usize);
+}
+
+impl ShiftLines for Span {
+    fn shift_lines(&mut self, delta: usize) {
+        self.start.line += delta;
+        self.end.line += delta;
+    }
+}
+
+impl<T: ShiftLines> ShiftLines for Option<T> {
+    fn shift_lines(&mut self, delta: usize) {
+        if let Some(inner) = self {
+            inner.shift_lines(delta);
+        }
+    }
+}
+
+impl<T: ShiftLines> ShiftLines for Vec<T> {
+    fn shift_lines(&mut self, delta: usize) {
+        if delta == 0 {
+            return;
+        }
+        for item in self {
+            item.shift_lines(delta);
+        }
+    }
+}
+
+impl<T: ShiftLines> ShiftLines for Box<T> {
+    fn shift_lines(&mut self, delta: usize) {
+        (**self).shift_lines(delta);
+    }
+}
+
+impl ShiftLines for Element {
+    fn shift_lines(&mut self, delta: usize) {
+        match self {
+            Element::Package(package) => {
+                package.span.shift_lines(delta);
+                package.elements.shift_lines(delta);
+            }
+            Element::Definition(definition) => definition.shift_lines(delta),
+            Element::Usage(usage) => usage.shift_lines(delta),
+            Element::Comment(comment) => comment.shift_lines(delta),
+            Element::Import(import) => import.shift_lines(delta),
+            Element::Alias(alias) => {
+                alias.target_span.shift_lines(delta);
+                alias.span.shift_lines(delta);
+            }
+            Element::Dependency(dependency) => {
+                dependency.name_span.shift_lines(delta);
+                for reference in dependency
+                    .sources
+                    .iter_mut()
+                    .chain(dependency.targets.iter_mut())
+                {
+                    reference.span.shift_lines(delta);
+                }
+                dependency.span.shift_lines(delta);
+            }
+            Element::Filter(filter) => {
+                for meta in &mut filter.meta_refs {
+                    meta.extracted.shift_lines(delta);
+                }
+                filter.expression_refs.shift_lines(delta);
+                filter.span.shift_lines(delta);
+            }
+        }
+    }
+}
+
+impl ShiftLines for Definition {
+    fn shift_lines(&mut self, delta: usize) {
+        self.short_name_span.shift_lines(delta);
+        self.relationships.shift_lines(delta);
+        self.body.shift_lines(delta);
+        self.span.shift_lines(delta);
+    }
+}
+
+impl ShiftLines for DefinitionMember {
+    fn shift_lines(&mut self, delta: usize) {
+        match self {
+            DefinitionMember::Comment(comment) => comment.shift_lines(delta),
+            DefinitionMember::Usage(usage) => usage.shift_lines(delta),
+            DefinitionMember::Import(import) => import.shift_lines(delta),
+        }
+    }
+}
+
+impl ShiftLines for Usage {
+    fn shift_lines(&mut self, delta: usize) {
+        self.short_name_span.shift_lines(delta);
+        self.relationships.shift_lines(delta);
+        self.body.shift_lines(delta);
+        self.span.shift_lines(delta);
+        self.expression_refs.shift_lines(delta);
+    }
+}
+
+impl ShiftLines for UsageMember {
+    fn shift_lines(&mut self, delta: usize) {
+        match self {
+            UsageMember::Comment(comment) => comment.shift_lines(delta),
+            UsageMember::Usage(usage) => usage.shift_lines(delta),
+        }
+    }
+}
+
+impl ShiftLines for Comment {
+    fn shift_lines(&mut self, delta: usize) {
+        self.name_span.shift_lines(delta);
+        for about in &mut self.about {
+            about.span.shift_lines(delta);
+        }
+        self.span.shift_lines(delta);
+    }
+}
+
+impl ShiftLines for Import {
+    fn shift_lines(&mut self, delta: usize) {
+        self.path_span.shift_lines(delta);
+        self.span.shift_lines(delta);
+    }
+}
+
+impl ShiftLines for Relationships {
+    fn shift_lines(&mut self, delta: usize) {
+        self.typed_by_span.shift_lines(delta);
+        let extracted = self
+            .specializes
+            .iter_mut()
+            .map(|r| &mut r.extracted)
+            .chain(self.redefines.iter_mut().map(|r| &mut r.extracted))
+            .chain(self.subsets.iter_mut().map(|r| &mut r.extracted))
+            .chain(self.references.iter_mut().map(|r| &mut r.extracted))
+            .chain(self.crosses.iter_mut().map(|r| &mut r.extracted))
+            .chain(self.satisfies.iter_mut().map(|r| &mut r.extracted))
+            .chain(self.performs.iter_mut().map(|r| &mut r.extracted))
+            .chain(self.exhibits.iter_mut().map(|r| &mut r.extracted))
+            .chain(self.includes.iter_mut().map(|r| &mut r.extracted))
+            .chain(self.asserts.iter_mut().map(|r| &mut r.extracted))
+            .chain(self.verifies.iter_mut().map(|r| &mut r.extracted))
+            .chain(self.meta.iter_mut().map(|r| &mut r.extracted));
+        for reference in extracted {
+            reference.shift_lines(delta);
+        }
+    }
+}
+
+impl ShiftLines for ExtractedRef {
+    fn shift_lines(&mut self, delta: usize) {
+        match self {
+            ExtractedRef::Simple { span, .. } => span.shift_lines(delta),
+            ExtractedRef::Chain(chain) => {
+                for part in &mut chain.parts {
+                    part.span.shift_lines(delta);
+                }
+                chain.span.shift_lines(delta);
+            }
+        }
+    }
+}
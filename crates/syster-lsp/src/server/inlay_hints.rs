@@ -1,6 +1,7 @@
 //! Inlay hint support for the LSP server
 
 use super::LspServer;
+use super::constant_eval::{evaluate, is_literal, value_expression};
 use super::helpers::uri_to_path;
 use async_lsp::lsp_types::{
     InlayHint, InlayHintKind, InlayHintLabel, InlayHintParams, Position as LspPosition,
@@ -35,7 +36,7 @@ impl LspServer {
         let hints = analysis.inlay_hints(file_id, range);
 
         // Convert IDE hints to LSP hints
-        hints
+        let mut lsp_hints: Vec<InlayHint> = hints
             .into_iter()
             .map(|hint| InlayHint {
                 position: LspPosition {
@@ -53,6 +54,32 @@ impl LspServer {
                 padding_right: Some(hint.padding_right),
                 data: None,
             })
-            .collect()
+            .collect();
+
+        // Values of features bound to constant expressions, after the expression
+        if let Some(text) = self.document_texts.get(&path) {
+            for symbol in analysis.symbol_index().symbols_in_file(file_id) {
+                let Some((expression, end)) = value_expression(text, symbol) else {
+                    continue;
+                };
+                if end < params.range.start || end > params.range.end || is_literal(expression) {
+                    continue;
+                }
+                if let Some(value) = evaluate(expression) {
+                    lsp_hints.push(InlayHint {
+                        position: end,
+                        label: InlayHintLabel::String(format!("= {value}")),
+                        kind: None,
+                        text_edits: None,
+                        tooltip: None,
+                        padding_left: Some(true),
+                        padding_right: Some(false),
+                        data: None,
+                    });
+                }
+            }
+        }
+        lsp_hints.sort_by_key(|hint| (hint.position.line, hint.position.character));
+        lsp_hints
     }
 }
@@ -0,0 +1,177 @@
+//! Inline (ghost text) completion of structural boilerplate
+//!
+//! After typing the `{` that opens an element's body, the client can show the
+//! usual skeleton of that kind of element as ghost text: an entry action and
+//! first state for `state def Engine {`, a doc comment for
+//! `requirement def X {`, and so on. The suggestion closes the block too, and
+//! replaces a `}` the editor closed automatically.
+//!
+//! `textDocument/inlineCompletion` is new in LSP 3.18, so neither the request
+//! nor its client capability exists in the protocol types. Clients opt in with
+//! the `inlineCompletion` experimental capability and the request is
+//! registered dynamically, like type hierarchy.
+
+use super::LspServer;
+use super::helpers::{char_offset_to_utf16, position_to_byte_offset, uri_to_path};
+use async_lsp::lsp_types::request::Request;
+use async_lsp::lsp_types::{Position, Range, Registration, TextDocumentIdentifier, Url};
+use serde::{Deserialize, Serialize};
+
+/// Method name of the inline completion request
+pub const INLINE_COMPLETION_METHOD: &str = "textDocument/inlineCompletion";
+
+/// Experimental client capability that enables inline completion
+pub const INLINE_COMPLETION_CAPABILITY: &str = "inlineCompletion";
+
+/// LSP 3.18 request: textDocument/inlineCompletion
+pub enum InlineCompletionRequest {}
+
+impl Request for InlineCompletionRequest {
+    type Params = InlineCompletionParams;
+    type Result = Option<InlineCompletionList>;
+    const METHOD: &'static str = INLINE_COMPLETION_METHOD;
+}
+
+/// Request parameters for textDocument/inlineCompletion
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCompletionParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
+
+/// Response for textDocument/inlineCompletion
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCompletionList {
+    pub items: Vec<InlineCompletionItem>,
+}
+
+/// Ghost text offered at the cursor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCompletionItem {
+    pub insert_text: String,
+    /// Text replaced by the completion, if not just the cursor position
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<Range>,
+}
+
+/// Keywords that may precede the kind of a declaration
+const MODIFIERS: &[&str] = &[
+    "abstract",
+    "derived",
+    "in",
+    "individual",
+    "inout",
+    "out",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "ref",
+    "variation",
+];
+
+/// Body lines suggested for an element, by the keywords that declare it
+fn template(keywords: &[&str]) -> Option<&'static [&'static str]> {
+    let template: &[&str] = match keywords {
+        ["state" | "exhibit", ..] => &["entry; then idle;", "", "state idle;"],
+        ["requirement" | "concern", ..] | ["satisfy", "requirement", ..] => &["doc /* */"],
+        ["use", "case", ..] => &["subject;", "objective {", "    doc /* */", "}"],
+        ["action" | "perform", ..] => &["first start;", "then done;"],
+        ["constraint" | "assert", ..] => &["doc /* */"],
+        _ => return None,
+    };
+    Some(template)
+}
+
+impl LspServer {
+    /// Whether the client asked for inline completions
+    pub fn set_inline_completion_support(&mut self, supported: bool) {
+        self.client_support.inline_completion = supported;
+    }
+
+    /// Registration for inline completion, or `None` if the client doesn't support it
+    pub fn inline_completion_registration(&self) -> Option<Registration> {
+        self.client_support.inline_completion.then(|| Registration {
+            id: INLINE_COMPLETION_METHOD.to_string(),
+            method: INLINE_COMPLETION_METHOD.to_string(),
+            register_options: Some(serde_json::json!({
+                "documentSelector": [
+                    { "language": "sysml" },
+                    { "language": "kerml" },
+                    { "pattern": "**/*.{sysml,kerml}" }
+                ]
+            })),
+        })
+    }
+
+    /// Skeleton for the body of the element whose `{` was just typed before `position`
+    pub fn get_inline_completions(
+        &self,
+        uri: &Url,
+        position: Position,
+    ) -> Option<InlineCompletionList> {
+        let path = uri_to_path(uri)?;
+        let text = self.document_texts.get(&path)?;
+        let item = body_skeleton(text, position)?;
+        Some(InlineCompletionList { items: vec![item] })
+    }
+}
+
+/// The body skeleton to offer after an opening brace at the end of a line
+fn body_skeleton(text: &str, position: Position) -> Option<InlineCompletionItem> {
+    let offset = position_to_byte_offset(text, position).ok()?;
+    let line_start = text[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let line_end = text[offset..]
+        .find('\n')
+        .map_or(text.len(), |idx| offset + idx);
+    let line = text[line_start..line_end].trim_end_matches('\r');
+    let (before, after) = line.split_at((offset - line_start).min(line.len()));
+
+    // Only an empty block: nothing after the cursor but a brace the editor added
+    let before = before.trim_end().strip_suffix('{')?;
+    let after = after.trim();
+    if !(after.is_empty() || after == "}") || before.contains("//") {
+        return None;
+    }
+
+    let declaration = before
+        .rsplit([';', '{', '}'])
+        .next()
+        .unwrap_or(before)
+        .split(|c: char| c == ':' || c == '[')
+        .next()
+        .unwrap_or_default();
+    let words: Vec<&str> = declaration.split_whitespace().collect();
+    let keywords = &words[words.iter().position(|w| !MODIFIERS.contains(w))?..];
+    let body = template(keywords)?;
+
+    let outer: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+    let unit = if outer.contains('\t') { "\t" } else { "    " };
+    let mut insert_text = String::new();
+    for body_line in body {
+        insert_text.push('\n');
+        if !body_line.is_empty() {
+            // Templates nest with four spaces
+            let content = body_line.trim_start();
+            let depth = (body_line.len() - content.len()) / 4 + 1;
+            insert_text.push_str(&outer);
+            insert_text.push_str(&unit.repeat(depth));
+            insert_text.push_str(content);
+        }
+    }
+    insert_text.push('\n');
+    insert_text.push_str(&outer);
+    insert_text.push('}');
+
+    let end = Position::new(
+        position.line,
+        char_offset_to_utf16(line, line.chars().count()),
+    );
+    Some(InlineCompletionItem {
+        insert_text,
+        range: Some(Range::new(position, end)),
+    })
+}
@@ -0,0 +1,202 @@
+//! Hover explanations for SysML keywords and relationship operators
+//!
+//! Keywords aren't symbols, so the IDE layer has nothing to say about them.
+//! Hovering `redefines`, `perform`, `:>` and the like instead explains what
+//! the construct means, with a pointer to the clause of the SysML v2
+//! specification that defines it.
+
+use super::tokens::{Token, TokenKind, tokenize};
+use async_lsp::lsp_types::{Position, Range};
+
+/// The SysML v2 specification the explanations refer to
+pub const SPEC_URL: &str = "https://www.omg.org/spec/SysML/2.0/";
+
+/// Keyword or operator, the spec clause defining it, and what it means
+const KEYWORDS: &[(&str, &str, &str)] = &[
+    (
+        ":",
+        "7.6 Definition and Usage",
+        "Typing: the usage is defined by the definitions after `:` (`defined by`). Its values are instances of all of them.",
+    ),
+    (
+        ":>",
+        "7.6 Definition and Usage",
+        "Specialization: a definition after `:>` (`specializes`) is a supertype, whose features are inherited; a usage after `:>` (`subsets`) is a feature whose values include this one's.",
+    ),
+    (
+        ":>>",
+        "7.6 Definition and Usage",
+        "Redefinition: the feature replaces the inherited feature after `:>>` (`redefines`), narrowing its type, multiplicity or value. The redefined feature is no longer inherited separately.",
+    ),
+    (
+        "specializes",
+        "7.6 Definition and Usage",
+        "Specialization: every instance of this definition is an instance of the general one, and inherits its features. Also written `:>`.",
+    ),
+    (
+        "subsets",
+        "7.6 Definition and Usage",
+        "Subsetting: every value of this feature is also a value of the subsetted feature. Also written `:>`.",
+    ),
+    (
+        "redefines",
+        "7.6 Definition and Usage",
+        "Redefinition: this feature replaces an inherited feature, narrowing its type, multiplicity or value. The redefined feature is no longer inherited separately. Also written `:>>`.",
+    ),
+    (
+        "abstract",
+        "7.6 Definition and Usage",
+        "Abstract: every instance must also be an instance of a more specialized element; an abstract definition can't be instantiated directly.",
+    ),
+    (
+        "ref",
+        "7.6 Definition and Usage",
+        "Referential usage: the feature refers to values owned elsewhere, rather than composing them into its owner.",
+    ),
+    (
+        "variation",
+        "7.6 Definition and Usage",
+        "Variation point: the element can only be one of its `variant`s.",
+    ),
+    (
+        "import",
+        "7.5 Namespaces and Packages",
+        "Import: makes the members of another namespace visible here by their simple names. `::*` imports all members, `::**` members recursively; a public import also re-exports them.",
+    ),
+    (
+        "connect",
+        "7.13 Connections",
+        "Connection: links the features at its ends, within the context of the owning element.",
+    ),
+    (
+        "bind",
+        "7.13 Connections",
+        "Binding connector: the features at both ends have equal values.",
+    ),
+    (
+        "flow",
+        "7.13 Connections",
+        "Flow connection: something flows from the source feature to the target feature.",
+    ),
+    (
+        "allocate",
+        "7.15 Allocations",
+        "Allocation: assigns responsibility for the source element to the target element, for example a function to a component.",
+    ),
+    (
+        "perform",
+        "7.16 Actions",
+        "Perform: the owner carries out the referenced action, which becomes one of its behaviors.",
+    ),
+    (
+        "first",
+        "7.16 Actions",
+        "Succession: the occurrence after `first` must end before the one after `then` starts.",
+    ),
+    (
+        "then",
+        "7.16 Actions",
+        "Succession: the preceding occurrence must end before the one after `then` starts.",
+    ),
+    (
+        "accept",
+        "7.16 Actions",
+        "Accept action: waits for an item of the given type to arrive, optionally via a port. As a transition trigger, the transition fires when it arrives.",
+    ),
+    (
+        "exhibit",
+        "7.17 States",
+        "Exhibit: the owner exhibits the referenced state behavior, being in its states over time.",
+    ),
+    (
+        "entry",
+        "7.17 States",
+        "Entry action: performed when the state is entered. In a state definition, `entry; then s;` makes `s` the initial state.",
+    ),
+    (
+        "exit",
+        "7.17 States",
+        "Exit action: performed when the state is left.",
+    ),
+    (
+        "do",
+        "7.17 States",
+        "Do action: performed while in the state, after its entry action.",
+    ),
+    (
+        "transition",
+        "7.17 States",
+        "Transition: moves from the `first` state to the `then` state when its trigger is accepted and its guard holds, performing its effect.",
+    ),
+    (
+        "require",
+        "7.20 Requirements",
+        "Required constraint: must hold for the requirement's subject to satisfy the requirement.",
+    ),
+    (
+        "assume",
+        "7.20 Requirements",
+        "Assumed constraint: the requirement only applies while it holds.",
+    ),
+    (
+        "satisfy",
+        "7.20 Requirements",
+        "Satisfy: asserts that the subject meets the requirement, i.e. its required constraints hold whenever its assumed ones do.",
+    ),
+    (
+        "subject",
+        "7.20 Requirements",
+        "Subject: the element a requirement or case is about.",
+    ),
+    (
+        "objective",
+        "7.21 Cases",
+        "Objective: the requirement the case is meant to satisfy, such as what an analysis determines.",
+    ),
+    (
+        "include",
+        "7.24 Use Cases",
+        "Include: the use case is performed as part of the including use case.",
+    ),
+];
+
+/// The keyword or operator at `position` in `text`, with its span
+pub(super) fn keyword_at(text: &str, position: Position) -> Option<(&'static str, Range)> {
+    let tokens = tokenize(text);
+    let idx = tokens
+        .iter()
+        .position(|token| token.start <= position && position < token.end)?;
+    let token = &tokens[idx];
+
+    // `:>>` is tokenized as `:>` followed by `>`
+    let adjacent = |first: &Token<'_>, second: &Token<'_>| {
+        first.text == ":>" && second.text == ">" && first.end == second.start
+    };
+    let (text, range) = match token.text {
+        ":>" if tokens
+            .get(idx + 1)
+            .is_some_and(|next| adjacent(token, next)) =>
+        {
+            (":>>", Range::new(token.start, tokens[idx + 1].end))
+        }
+        ">" if idx > 0 && adjacent(&tokens[idx - 1], token) => {
+            (":>>", Range::new(tokens[idx - 1].start, token.end))
+        }
+        text if token.kind == TokenKind::Word || matches!(text, ":" | ":>") => {
+            (text, Range::new(token.start, token.end))
+        }
+        _ => return None,
+    };
+    KEYWORDS
+        .iter()
+        .find(|(keyword, _, _)| *keyword == text)
+        .map(|(keyword, _, _)| (*keyword, range))
+}
+
+/// Markdown explaining `keyword`, linking to the spec clause defining it
+pub(super) fn keyword_hover_contents(keyword: &str) -> Option<String> {
+    let (_, clause, explanation) = KEYWORDS.iter().find(|(k, _, _)| *k == keyword)?;
+    Some(format!(
+        "**`{keyword}`**\n\n{explanation}\n\nSee [SysML v2 §{clause}]({SPEC_URL})"
+    ))
+}
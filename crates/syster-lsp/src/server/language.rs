@@ -0,0 +1,27 @@
+//! Language of a model file
+//!
+//! SysML and KerML files go through the same pipeline; the extension only
+//! selects the grammar and the keywords the text-based checks know about.
+//! Passes that only apply to SysML (action parameter checks, the incremental
+//! parser, which splits SysML ASTs) ask for the language rather than matching
+//! extensions.
+
+use std::path::Path;
+
+/// Language of a model file, from its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Language {
+    SysML,
+    KerML,
+}
+
+impl Language {
+    /// Language of the file at `path`, or `None` if it isn't a model file
+    pub(super) fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "sysml" => Some(Self::SysML),
+            "kerml" => Some(Self::KerML),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Linked editing of element names
+//!
+//! `textDocument/linkedEditingRange` lets the editor change a name in every
+//! place it is written in the document at once, as the user types, without
+//! going through a rename. The linked ranges are the element's declared name,
+//! its short name when that is written the same, and the references in the
+//! same file that resolve to it. A qualified reference (`P::Engine`) links
+//! only its last segment.
+//!
+//! Every range has to hold the same text for the editor to keep them in step,
+//! so written names that differ from the declared one are left out. So is a
+//! same-named redefinition's reference (`part engine :>> engine`), which names
+//! the inherited feature rather than the declaration.
+
+use super::LspServer;
+use super::helpers::{position_to_byte_offset, uri_to_path};
+use super::qualified_name::element_at;
+use super::type_hierarchy::redefined_feature;
+use async_lsp::lsp_types::{LinkedEditingRanges, Position, Range, Url};
+use std::collections::HashMap;
+use syster::base::FileId;
+use syster::hir::{RefKind, SymbolIndex};
+
+/// What the editor may type into a linked range and keep it linked
+pub const NAME_WORD_PATTERN: &str = "[A-Za-z_][A-Za-z0-9_]*";
+
+impl LspServer {
+    /// Ranges to edit together with the name at a position
+    pub fn get_linked_editing_ranges(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> Option<LinkedEditingRanges> {
+        let path = uri_to_path(uri)?;
+        let text = self.document_texts.get(&path)?;
+        let analysis = self.analysis_host.analysis();
+        self.reference_index.sync(&analysis);
+        let index = analysis.symbol_index();
+        let file_id = analysis.get_file_id(&path.to_string_lossy())?;
+        let (symbol, _) = element_at(index, file_id, position)?;
+        let name = symbol.name.as_ref();
+
+        let mut ranges = Vec::new();
+        if symbol.file == file_id {
+            ranges.push(Range::new(
+                Position::new(symbol.start_line, symbol.start_col),
+                Position::new(symbol.end_line, symbol.end_col),
+            ));
+            if let (Some(start_line), Some(start_col), Some(end_line), Some(end_col)) = (
+                symbol.short_name_start_line,
+                symbol.short_name_start_col,
+                symbol.short_name_end_line,
+                symbol.short_name_end_col,
+            ) {
+                ranges.push(Range::new(
+                    Position::new(start_line, start_col),
+                    Position::new(end_line, end_col),
+                ));
+            }
+        }
+
+        let resolved = resolved_references(index, file_id);
+        let target = Some(symbol.qualified_name.to_string());
+        for entry in self.reference_index.entries(&path) {
+            let last_segment = entry.target.rsplit(['.', ':']).next();
+            if last_segment != Some(name)
+                || resolved.get(&(entry.range.start.line, entry.range.start.character))
+                    != Some(&target)
+            {
+                continue;
+            }
+            let end = entry.range.end;
+            let width = name.chars().count() as u32;
+            ranges.push(Range::new(
+                Position::new(end.line, end.character.saturating_sub(width)),
+                end,
+            ));
+        }
+
+        // Spans that don't hold the name as written can't be linked to it
+        ranges.retain(|range| range_text(text, range) == Some(name));
+        ranges.sort_by_key(|range| (range.start.line, range.start.character));
+        ranges.dedup();
+
+        let at_cursor = |range: &Range| range.start <= position && position <= range.end;
+        if !ranges.iter().any(at_cursor) {
+            return None;
+        }
+        Some(LinkedEditingRanges {
+            ranges,
+            word_pattern: Some(NAME_WORD_PATTERN.to_string()),
+        })
+    }
+}
+
+/// The element each reference in a file resolves to, keyed by where the
+/// reference starts
+fn resolved_references(
+    index: &SymbolIndex,
+    file_id: FileId,
+) -> HashMap<(u32, u32), Option<String>> {
+    let mut resolved = HashMap::new();
+    for symbol in index.symbols_in_file(file_id) {
+        for type_ref in symbol.type_refs.iter().flat_map(|trk| trk.as_refs()) {
+            // A same-named `:>> name` resolves to the redefining feature, but
+            // names the inherited one, which keeps its name
+            let redefined = matches!(type_ref.kind, RefKind::Redefines)
+                .then(|| redefined_feature(index, symbol, &type_ref.target))
+                .flatten();
+            let target = match (redefined, &type_ref.resolved_target) {
+                (Some(redefined), _) => Some(redefined.qualified_name.to_string()),
+                (None, Some(target)) => Some(target.to_string()),
+                (None, None) => index
+                    .resolver_for_scope(&symbol.qualified_name)
+                    .resolve(&type_ref.target)
+                    .symbol()
+                    .map(|target| target.qualified_name.to_string()),
+            };
+            resolved.insert((type_ref.start_line, type_ref.start_col), target);
+        }
+    }
+    resolved
+}
+
+fn range_text<'a>(text: &'a str, range: &Range) -> Option<&'a str> {
+    let start = position_to_byte_offset(text, range.start).ok()?;
+    let end = position_to_byte_offset(text, range.end).ok()?;
+    text.get(start..end)
+}
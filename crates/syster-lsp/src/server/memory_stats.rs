@@ -0,0 +1,196 @@
+//! Memory statistics request handler for LSP.
+//!
+//! Reports approximate sizes of the server's in-memory structures so users can
+//! tune the server on memory-constrained machines, and optionally trims caches
+//! that no longer correspond to loaded files.
+
+use super::LspServer;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::request::Request;
+use serde::{Deserialize, Serialize};
+use std::mem::size_of;
+use syster::hir::HirSymbol;
+
+/// Custom LSP request: syster/getMemoryStats
+pub enum GetMemoryStatsRequest {}
+
+impl Request for GetMemoryStatsRequest {
+    type Params = GetMemoryStatsParams;
+    type Result = MemoryStats;
+    const METHOD: &'static str = "syster/getMemoryStats";
+}
+
+/// Request parameters for syster/getMemoryStats
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMemoryStatsParams {
+    /// Trim stale caches before collecting statistics
+    #[serde(default)]
+    pub trim_caches: bool,
+}
+
+/// Size of a single in-memory structure
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructureStats {
+    /// Number of entries held by the structure
+    pub entries: usize,
+    /// Rough estimate of the heap bytes used by the entries
+    pub estimated_bytes: usize,
+}
+
+/// Parse cache size and effectiveness
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseCacheStats {
+    /// Number of cached parse results
+    pub entries: usize,
+    /// Number of cached member results used for incremental reparsing
+    pub segment_entries: usize,
+    /// Maximum number of cached parse results
+    pub capacity: usize,
+    /// Source bytes covered by the cached parse results
+    pub source_bytes: usize,
+    /// Lookups served from the cache
+    pub hits: u64,
+    /// Lookups that required a parse
+    pub misses: u64,
+    /// hits / (hits + misses)
+    pub hit_rate: f64,
+}
+
+/// Result of the syster/getMemoryStats request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryStats {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Number of files loaded into the analysis host
+    pub file_count: usize,
+    /// Symbol table (all HIR symbols across loaded files)
+    pub symbols: StructureStats,
+    /// Type references tracked on symbols (the reference index)
+    pub references: StructureStats,
+    /// Document texts kept for hover and other features
+    pub document_texts: StructureStats,
+    /// Parse errors cached per file
+    pub parse_errors: StructureStats,
+    /// Per-document cancellation tokens
+    pub cancel_tokens: StructureStats,
+    /// Content-addressed parse cache
+    pub parse_cache: ParseCacheStats,
+    /// Number of cache entries removed by this request (when `trimCaches` was set)
+    pub trimmed_entries: usize,
+}
+
+impl LspServer {
+    /// Collect memory statistics, trimming caches first when requested.
+    pub fn get_memory_stats(&mut self, trim_caches: bool) -> MemoryStats {
+        let trimmed_entries = if trim_caches { self.trim_caches() } else { 0 };
+
+        let document_texts = StructureStats {
+            entries: self.document_texts.len(),
+            estimated_bytes: self
+                .document_texts
+                .iter()
+                .map(|(path, text)| path.as_os_str().len() + text.capacity())
+                .sum(),
+        };
+
+        let parse_errors = StructureStats {
+            entries: self.parse_errors.values().map(Vec::len).sum(),
+            estimated_bytes: self
+                .parse_errors
+                .iter()
+                .map(|(path, errors)| {
+                    path.as_os_str().len()
+                        + errors
+                            .iter()
+                            .map(|e| size_of_val(e) + e.message.capacity())
+                            .sum::<usize>()
+                })
+                .sum(),
+        };
+
+        let cancel_tokens = StructureStats {
+            entries: self.document_cancel_tokens.len(),
+            estimated_bytes: self
+                .document_cancel_tokens
+                .keys()
+                .map(|path| path.as_os_str().len() + size_of::<usize>())
+                .sum(),
+        };
+
+        let parse_cache = ParseCacheStats {
+            entries: self.parse_cache.len(),
+            segment_entries: self.incremental_parser.cached_segments(),
+            capacity: self.parse_cache.capacity(),
+            source_bytes: self.parse_cache.cached_source_bytes(),
+            hits: self.parse_cache.hits(),
+            misses: self.parse_cache.misses(),
+            hit_rate: self.parse_cache.hit_rate(),
+        };
+
+        let file_count = self.analysis_host.file_count();
+        let analysis = self.analysis_host.analysis();
+        let mut symbols = StructureStats::default();
+        let mut references = StructureStats::default();
+        for symbol in analysis.symbol_index().all_symbols() {
+            symbols.entries += 1;
+            symbols.estimated_bytes += estimate_symbol_bytes(symbol);
+            for type_ref in symbol.type_refs.iter().flat_map(|trk| trk.as_refs()) {
+                references.entries += 1;
+                references.estimated_bytes += size_of_val(type_ref) + type_ref.target.len();
+            }
+        }
+
+        MemoryStats {
+            schema_version: SCHEMA_VERSION,
+            file_count,
+            symbols,
+            references,
+            document_texts,
+            parse_errors,
+            cancel_tokens,
+            parse_cache,
+            trimmed_entries,
+        }
+    }
+
+    /// Drop cached state for files that are no longer loaded, empty the parse
+    /// cache and release spare capacity. Returns the number of entries removed.
+    pub fn trim_caches(&mut self) -> usize {
+        let files = self.analysis_host.files();
+        let before =
+            self.document_texts.len() + self.parse_errors.len() + self.document_cancel_tokens.len();
+
+        self.document_texts
+            .retain(|path, _| files.contains_key(path));
+        self.parse_errors.retain(|path, _| files.contains_key(path));
+        self.document_cancel_tokens
+            .retain(|path, token| files.contains_key(path) && !token.is_cancelled());
+
+        self.document_texts.shrink_to_fit();
+        self.parse_errors.shrink_to_fit();
+        self.document_cancel_tokens.shrink_to_fit();
+
+        let cleared = self.parse_cache.clear() + self.incremental_parser.clear();
+
+        cleared + before
+            - (self.document_texts.len()
+                + self.parse_errors.len()
+                + self.document_cancel_tokens.len())
+    }
+}
+
+/// Estimate the bytes used by a symbol, including its owned strings.
+fn estimate_symbol_bytes(symbol: &HirSymbol) -> usize {
+    size_of::<HirSymbol>()
+        + symbol.name.len()
+        + symbol.qualified_name.len()
+        + symbol.short_name.as_ref().map_or(0, |s| s.len())
+        + symbol.doc.as_ref().map_or(0, |d| d.len())
+        + symbol.supertypes.iter().map(|s| s.len()).sum::<usize>()
+        + symbol.relationships.capacity() * size_of::<syster::hir::HirRelationship>()
+}
@@ -0,0 +1,288 @@
+//! Export of the resolved model as JSON
+//!
+//! `syster/exportModel` serializes what the analysis knows about the model:
+//! every element with its qualified name, kind and source span, and the
+//! typing, specialization, subsetting and redefinition relationships it
+//! declares, resolved to qualified names where resolution succeeded. Report
+//! generators and CI checks can consume the export instead of parsing SysML
+//! themselves. Elements of the standard library are left out unless asked for.
+
+use super::LspServer;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::request::Request;
+use async_lsp::lsp_types::{Position, Range, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use syster::core::constants::LSP_SERVER_VERSION;
+use syster::hir::{HirSymbol, RefKind, SymbolKind};
+
+/// Custom LSP request: syster/exportModel
+pub enum ExportModelRequest {}
+
+impl Request for ExportModelRequest {
+    type Params = ExportModelParams;
+    type Result = ModelExport;
+    const METHOD: &'static str = "syster/exportModel";
+}
+
+/// Request parameters for syster/exportModel
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportModelParams {
+    /// URI of the file to export (optional - if None, exports the whole workspace)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    /// Include the elements of the standard library
+    #[serde(default)]
+    pub include_stdlib: bool,
+}
+
+/// The resolved model of a file or the whole workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelExport {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Version of the server that exported the model
+    pub server_version: String,
+    /// Exported files, ordered by URI
+    pub files: Vec<ModelFile>,
+}
+
+/// The elements declared in one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelFile {
+    pub uri: String,
+    /// Elements in source order
+    pub elements: Vec<ModelElement>,
+}
+
+/// An element of the model; imports and comments aren't exported
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelElement {
+    pub name: String,
+    pub qualified_name: String,
+    pub kind: ElementKind,
+    /// Qualified name of the owning namespace, if not top level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Span of the element's name
+    pub range: Range,
+    /// Relationships the element declares, in source order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relationships: Vec<ModelRelationship>,
+}
+
+/// A typing, specialization, subsetting or redefinition relationship
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelRelationship {
+    pub kind: ExportedRelationshipKind,
+    /// Target as written in the source
+    pub target: String,
+    /// Qualified name of the target, if it resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<String>,
+    /// Span of the target reference
+    pub range: Range,
+}
+
+/// Kind of an exported element
+///
+/// Serialized by name (`"partDef"`), independent of how hovers display kinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ElementKind {
+    Package,
+    PartDef,
+    ItemDef,
+    ConnectionDef,
+    AllocationDef,
+    StateDef,
+    ViewDef,
+    ViewpointDef,
+    RenderingDef,
+    PortDef,
+    InterfaceDef,
+    AttributeDef,
+    EnumerationDef,
+    ActionDef,
+    CalculationDef,
+    UseCaseDef,
+    AnalysisCaseDef,
+    RequirementDef,
+    ConcernDef,
+    ConstraintDef,
+    PartUsage,
+    ItemUsage,
+    PortUsage,
+    AttributeUsage,
+    ConnectionUsage,
+    InterfaceUsage,
+    AllocationUsage,
+    StateUsage,
+    ReferenceUsage,
+    OccurrenceUsage,
+    FlowUsage,
+    ActionUsage,
+    CalculationUsage,
+    RequirementUsage,
+    ConstraintUsage,
+    Alias,
+    Dependency,
+    Other,
+}
+
+impl From<SymbolKind> for ElementKind {
+    fn from(kind: SymbolKind) -> Self {
+        match kind {
+            SymbolKind::Package => Self::Package,
+            SymbolKind::PartDef => Self::PartDef,
+            SymbolKind::ItemDef => Self::ItemDef,
+            SymbolKind::ConnectionDef => Self::ConnectionDef,
+            SymbolKind::AllocationDef => Self::AllocationDef,
+            SymbolKind::StateDef => Self::StateDef,
+            SymbolKind::ViewDef => Self::ViewDef,
+            SymbolKind::ViewpointDef => Self::ViewpointDef,
+            SymbolKind::RenderingDef => Self::RenderingDef,
+            SymbolKind::PortDef => Self::PortDef,
+            SymbolKind::InterfaceDef => Self::InterfaceDef,
+            SymbolKind::AttributeDef => Self::AttributeDef,
+            SymbolKind::EnumerationDef => Self::EnumerationDef,
+            SymbolKind::ActionDef => Self::ActionDef,
+            SymbolKind::CalculationDef => Self::CalculationDef,
+            SymbolKind::UseCaseDef => Self::UseCaseDef,
+            SymbolKind::AnalysisCaseDef => Self::AnalysisCaseDef,
+            SymbolKind::RequirementDef => Self::RequirementDef,
+            SymbolKind::ConcernDef => Self::ConcernDef,
+            SymbolKind::ConstraintDef => Self::ConstraintDef,
+            SymbolKind::PartUsage => Self::PartUsage,
+            SymbolKind::ItemUsage => Self::ItemUsage,
+            SymbolKind::PortUsage => Self::PortUsage,
+            SymbolKind::AttributeUsage => Self::AttributeUsage,
+            SymbolKind::ConnectionUsage => Self::ConnectionUsage,
+            SymbolKind::InterfaceUsage => Self::InterfaceUsage,
+            SymbolKind::AllocationUsage => Self::AllocationUsage,
+            SymbolKind::StateUsage => Self::StateUsage,
+            SymbolKind::ReferenceUsage => Self::ReferenceUsage,
+            SymbolKind::OccurrenceUsage => Self::OccurrenceUsage,
+            SymbolKind::FlowUsage => Self::FlowUsage,
+            SymbolKind::ActionUsage => Self::ActionUsage,
+            SymbolKind::CalculationUsage => Self::CalculationUsage,
+            SymbolKind::RequirementUsage => Self::RequirementUsage,
+            SymbolKind::ConstraintUsage => Self::ConstraintUsage,
+            SymbolKind::Alias => Self::Alias,
+            SymbolKind::Dependency => Self::Dependency,
+            // Imports and comments aren't exported
+            SymbolKind::Import | SymbolKind::Comment | SymbolKind::Other => Self::Other,
+        }
+    }
+}
+
+/// Kind of an exported relationship
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportedRelationshipKind {
+    TypedBy,
+    Specializes,
+    Subsets,
+    Redefines,
+    Other,
+}
+
+impl From<RefKind> for ExportedRelationshipKind {
+    fn from(kind: RefKind) -> Self {
+        match kind {
+            RefKind::TypedBy => Self::TypedBy,
+            RefKind::Specializes => Self::Specializes,
+            RefKind::Subsets => Self::Subsets,
+            RefKind::Redefines => Self::Redefines,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl LspServer {
+    /// Export the model of one file, or of the whole workspace if `file_path`
+    /// is `None`
+    pub fn export_model(&mut self, file_path: Option<&Path>, include_stdlib: bool) -> ModelExport {
+        let _ = self.ensure_workspace_loaded();
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+
+        let symbols: Box<dyn Iterator<Item = &HirSymbol>> = match file_path {
+            Some(path) => match analysis.get_file_id(&path.to_string_lossy()) {
+                Some(file_id) => Box::new(index.symbols_in_file(file_id).into_iter()),
+                None => Box::new(std::iter::empty()),
+            },
+            None => Box::new(index.all_symbols()),
+        };
+
+        let mut by_file: BTreeMap<PathBuf, Vec<((u32, u32), ModelElement)>> = BTreeMap::new();
+        for symbol in symbols
+            .filter(|symbol| !matches!(symbol.kind, SymbolKind::Import | SymbolKind::Comment))
+        {
+            if let Some(path) = analysis.get_file_path(symbol.file) {
+                by_file
+                    .entry(PathBuf::from(path))
+                    .or_default()
+                    .push(((symbol.start_line, symbol.start_col), model_element(symbol)));
+            }
+        }
+
+        let files = by_file
+            .into_iter()
+            .filter(|(path, _)| include_stdlib || !self.is_stdlib_path(path))
+            .filter_map(|(path, mut elements)| {
+                elements.sort_by_key(|(start, _)| *start);
+                Some(ModelFile {
+                    uri: Url::from_file_path(&path).ok()?.to_string(),
+                    elements: elements.into_iter().map(|(_, element)| element).collect(),
+                })
+            })
+            .collect();
+
+        ModelExport {
+            schema_version: SCHEMA_VERSION,
+            server_version: LSP_SERVER_VERSION.to_string(),
+            files,
+        }
+    }
+}
+
+/// The exported form of a symbol
+fn model_element(symbol: &HirSymbol) -> ModelElement {
+    let relationships = symbol
+        .type_refs
+        .iter()
+        .flat_map(|trk| trk.as_refs())
+        .map(|type_ref| ModelRelationship {
+            kind: type_ref.kind.into(),
+            target: type_ref.target.to_string(),
+            resolved: type_ref.resolved_target.as_deref().map(str::to_string),
+            range: Range::new(
+                Position::new(type_ref.start_line, type_ref.start_col),
+                Position::new(type_ref.end_line, type_ref.end_col),
+            ),
+        })
+        .collect();
+
+    ModelElement {
+        name: symbol.name.to_string(),
+        qualified_name: symbol.qualified_name.to_string(),
+        kind: symbol.kind.into(),
+        owner: symbol
+            .qualified_name
+            .rsplit_once("::")
+            .map(|(owner, _)| owner.to_string()),
+        range: Range::new(
+            Position::new(symbol.start_line, symbol.start_col),
+            Position::new(symbol.end_line, symbol.end_col),
+        ),
+        relationships,
+    }
+}
@@ -0,0 +1,59 @@
+//! Monikers for linking elements across tools
+//!
+//! `textDocument/moniker` gives the element declared or referenced at a
+//! position an identifier that other tools - requirement tracing, model
+//! repositories - can store and match later: `<qualified name>#<kind>`, with
+//! `@<version>` appended for standard library elements. The bundled library
+//! is versioned with the server; a custom library has no version the server
+//! could vouch for, so its elements are only unique within the project.
+//!
+//! Workspace elements are exported, library elements imported.
+
+use super::LspServer;
+use super::helpers::uri_to_path;
+use super::qualified_name::element_at;
+use async_lsp::lsp_types::{Moniker, MonikerKind, Position, UniquenessLevel, Url};
+use std::path::PathBuf;
+use syster::core::constants::LSP_SERVER_VERSION;
+
+/// Scheme of the monikers this server produces
+pub const MONIKER_SCHEME: &str = "sysml";
+
+impl LspServer {
+    /// Moniker of the element at a position
+    pub fn get_monikers(&mut self, uri: &Url, position: Position) -> Option<Vec<Moniker>> {
+        let path = uri_to_path(uri)?;
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+        let file_id = analysis.get_file_id(&path.to_string_lossy())?;
+        let (symbol, _) = element_at(index, file_id, position)?;
+        let identifier = format!("{}#{:?}", symbol.qualified_name, symbol.kind);
+        let symbol_path = PathBuf::from(analysis.get_file_path(symbol.file)?);
+
+        let moniker = if self.is_stdlib_path(&symbol_path) {
+            // Only the bundled library has a known version
+            let version = self.stdlib_path.is_none().then_some(LSP_SERVER_VERSION);
+            Moniker {
+                scheme: MONIKER_SCHEME.to_string(),
+                identifier: match version {
+                    Some(version) => format!("{identifier}@{version}"),
+                    None => identifier,
+                },
+                unique: if version.is_some() {
+                    UniquenessLevel::Global
+                } else {
+                    UniquenessLevel::Project
+                },
+                kind: Some(MonikerKind::Import),
+            }
+        } else {
+            Moniker {
+                scheme: MONIKER_SCHEME.to_string(),
+                identifier,
+                unique: UniquenessLevel::Project,
+                kind: Some(MonikerKind::Export),
+            }
+        };
+        Some(vec![moniker])
+    }
+}
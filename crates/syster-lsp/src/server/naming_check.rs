@@ -0,0 +1,132 @@
+//! Naming convention checks for declared names
+//!
+//! Definitions are named in UpperCamelCase (`part def FuelTank`) and usages in
+//! lowerCamelCase (`part fuelTank`). Packages, quoted unrestricted names and
+//! anonymous elements are not checked.
+//!
+//! Each issue carries the conventional spelling, which the quick fix applies
+//! with a workspace rename so references are updated too.
+
+use async_lsp::lsp_types::{Position, Range};
+use syster::base::FileId;
+use syster::hir::{SymbolIndex, SymbolKind};
+
+/// Diagnostic code for a name that doesn't follow the naming convention
+pub const NAMING_CONVENTION_CODE: &str = "naming-convention";
+
+/// Casing style a name should follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameCase {
+    UpperCamel,
+    LowerCamel,
+}
+
+impl NameCase {
+    /// The convention for elements of a kind, if names of that kind are checked
+    pub fn for_kind(kind: SymbolKind) -> Option<Self> {
+        match kind {
+            SymbolKind::Package => None,
+            kind if kind.is_definition() => Some(Self::UpperCamel),
+            kind if kind.is_usage() => Some(Self::LowerCamel),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::UpperCamel => "UpperCamelCase",
+            Self::LowerCamel => "lowerCamelCase",
+        }
+    }
+
+    /// Respell a name in this case (`engine_rpm` is `EngineRpm` or `engineRpm`)
+    pub fn convert(self, name: &str) -> String {
+        let mut converted = String::with_capacity(name.len());
+        for word in words(name) {
+            let mut chars = word.chars();
+            let Some(first) = chars.next() else {
+                continue;
+            };
+            if converted.is_empty() && self == Self::LowerCamel {
+                converted.extend(first.to_lowercase());
+            } else {
+                converted.extend(first.to_uppercase());
+            }
+            // Words written in capitals (`MAX_SPEED`) keep only their initial
+            if word.chars().all(|c| !c.is_lowercase()) {
+                converted.extend(chars.flat_map(char::to_lowercase));
+            } else {
+                converted.push_str(chars.as_str());
+            }
+        }
+        converted
+    }
+}
+
+/// Words of a name, split at underscores and case changes (`HTTPServer_port`
+/// is `HTTP`, `Server`, `port`)
+fn words(name: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let chars: Vec<(usize, char)> = part.char_indices().collect();
+        let mut start = 0;
+        for (i, &(idx, ch)) in chars.iter().enumerate().skip(1) {
+            let prev = chars[i - 1].1;
+            let next = chars.get(i + 1).map(|&(_, next)| next);
+            // A capital after a lowercase letter or digit, or the last capital
+            // of a run that starts a new word
+            let boundary = ch.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_ascii_digit()
+                    || prev.is_uppercase() && next.is_some_and(char::is_lowercase));
+            if boundary && idx > start {
+                words.push(&part[start..idx]);
+                start = idx;
+            }
+        }
+        words.push(&part[start..]);
+    }
+    words
+}
+
+/// A declared name that doesn't follow its convention
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamingIssue {
+    /// Span of the declared name
+    pub range: Range,
+    pub message: String,
+    /// The name as declared
+    pub name: String,
+    pub case: NameCase,
+    /// The name spelled in `case`
+    pub replacement: String,
+}
+
+/// Check the names of every definition and usage declared in `file`
+pub fn check_naming(index: &SymbolIndex, file: FileId) -> Vec<NamingIssue> {
+    let mut issues = Vec::new();
+    for symbol in index.symbols_in_file(file) {
+        let name = symbol.name.as_ref();
+        if name.starts_with(['<', '\'']) {
+            continue;
+        }
+        let Some(case) = NameCase::for_kind(symbol.kind) else {
+            continue;
+        };
+        let replacement = case.convert(name);
+        if replacement == name || replacement.is_empty() {
+            continue;
+        }
+        issues.push(NamingIssue {
+            range: Range::new(
+                Position::new(symbol.start_line, symbol.start_col),
+                Position::new(symbol.end_line, symbol.end_col),
+            ),
+            message: format!("'{name}' should be {}", case.label()),
+            name: name.to_string(),
+            case,
+            replacement,
+        });
+    }
+    issues
+}
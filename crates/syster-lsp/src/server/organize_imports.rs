@@ -0,0 +1,305 @@
+//! Organize imports source actions.
+//!
+//! The imports of each package in a document are deduplicated, sorted by path
+//! with public imports first, and moved to where the package's first import
+//! was. Private imports that no reference in the package uses are removed;
+//! public imports re-export names to other files and are always kept.
+//!
+//! A second action also replaces used private wildcard imports with explicit
+//! imports of the names the package refers to.
+//!
+//! Packages whose imports share a line with other code are left alone.
+
+use super::LspServer;
+use super::code_lens::wildcard_import_names;
+use super::helpers::uri_to_path;
+use async_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionParams, Position, Range, TextEdit, Url, WorkspaceEdit,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use syster::hir::{HirSymbol, SymbolIndex, SymbolKind};
+
+/// Kind of the action that also expands wildcard imports
+pub const ORGANIZE_IMPORTS_EXPAND_WILDCARDS: &str = "source.organizeImports.expandWildcards";
+
+/// An import statement occupying whole lines
+#[derive(Debug, Clone)]
+pub(super) struct ImportStatement {
+    /// Lines spanned by the statement
+    pub lines: std::ops::RangeInclusive<u32>,
+    /// The statement, on one line without indentation
+    pub text: String,
+    /// The imported path as written (`Lib::Engine`, `Lib::*`)
+    pub path: String,
+    pub is_public: bool,
+}
+
+impl LspServer {
+    /// Organize imports actions for a document, if they would change it
+    pub(super) fn organize_imports_actions(
+        &mut self,
+        params: &CodeActionParams,
+    ) -> Vec<CodeAction> {
+        let wanted = |kind: &CodeActionKind| {
+            params.context.only.as_ref().is_none_or(|only| {
+                only.iter()
+                    .any(|prefix| kind.as_str().starts_with(prefix.as_str()))
+            })
+        };
+        let organize = CodeActionKind::SOURCE_ORGANIZE_IMPORTS;
+        let expand = CodeActionKind::new(ORGANIZE_IMPORTS_EXPAND_WILDCARDS);
+
+        let mut actions = Vec::new();
+        let uri = &params.text_document.uri;
+        if wanted(&organize)
+            && let Some(edits) = self.organize_imports(uri, false)
+        {
+            actions.push(source_action("Organize imports", organize, uri, edits));
+        }
+        if wanted(&expand)
+            && let Some(edits) = self.organize_imports(uri, true)
+        {
+            actions.push(source_action(
+                "Organize imports and expand wildcards",
+                expand,
+                uri,
+                edits,
+            ));
+        }
+        // Expanding changes nothing more when there are no wildcards to expand
+        if actions.len() == 2 && actions[0].edit == actions[1].edit {
+            actions.pop();
+        }
+        actions
+    }
+
+    /// Edits organizing the imports of every package in a document, or
+    /// `None` if they are already organized
+    fn organize_imports(&mut self, uri: &Url, expand_wildcards: bool) -> Option<Vec<TextEdit>> {
+        let path = uri_to_path(uri)?;
+        let text = self.document_texts.get(&path)?;
+        let lines: Vec<&str> = text.split('\n').collect();
+
+        let analysis = self.analysis_host.analysis();
+        self.reference_index.sync(&analysis);
+        let file_id = analysis.get_file_id(&path.to_string_lossy())?;
+        let index = analysis.symbol_index();
+
+        // Imports grouped by the package they belong to
+        let mut scopes: BTreeMap<&str, Vec<&HirSymbol>> = BTreeMap::new();
+        for import in index
+            .symbols_in_file(file_id)
+            .into_iter()
+            .filter(|sym| sym.kind == SymbolKind::Import)
+        {
+            scopes.entry(import_scope(import)).or_default().push(import);
+        }
+
+        let mut edits = Vec::new();
+        for (scope, imports) in scopes {
+            let Some(statements) = imports
+                .iter()
+                .map(|import| import_statement(&lines, import))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            // References made by the package and its members
+            let used: BTreeSet<&str> = self
+                .reference_index
+                .entries(&path)
+                .iter()
+                .filter(|entry| in_scope(&entry.owner, scope))
+                .map(|entry| first_segment(&entry.target))
+                .chain(
+                    statements
+                        .iter()
+                        .map(|statement| first_segment(&statement.path)),
+                )
+                .collect();
+
+            let organized = organize(index, &imports, &statements, &used, expand_wildcards);
+            edits.extend(scope_edits(&lines, &statements, organized));
+        }
+
+        (!edits.is_empty()).then_some(edits)
+    }
+}
+
+/// Read an import's statement, if nothing else shares its lines
+pub(super) fn import_statement(lines: &[&str], import: &HirSymbol) -> Option<ImportStatement> {
+    let statement = lines
+        .get(import.start_line as usize..=import.end_line as usize)?
+        .iter()
+        .map(|line| line.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let rest = statement
+        .strip_prefix("public ")
+        .or_else(|| statement.strip_prefix("private "))
+        .unwrap_or(&statement);
+    if !rest.starts_with("import ") || !statement.ends_with(';') || statement.contains("//") {
+        return None;
+    }
+    Some(ImportStatement {
+        lines: import.start_line..=import.end_line,
+        text: statement.clone(),
+        path: import.name.to_string(),
+        is_public: import.is_public,
+    })
+}
+
+/// Deduplicated, sorted statements with unused private imports dropped
+fn organize(
+    index: &SymbolIndex,
+    imports: &[&HirSymbol],
+    statements: &[ImportStatement],
+    used: &BTreeSet<&str>,
+    expand_wildcards: bool,
+) -> Vec<String> {
+    let mut kept: Vec<(bool, String, String)> = Vec::new();
+    for (import, statement) in imports.iter().zip(statements) {
+        if statement.is_public {
+            kept.push((false, statement.path.clone(), statement.text.clone()));
+            continue;
+        }
+        match wildcard_import_names(index, import) {
+            // An import that can't be resolved may still be needed
+            Some(names) if names.is_empty() => {
+                kept.push((true, statement.path.clone(), statement.text.clone()));
+            }
+            Some(names) => {
+                let used_names: Vec<&String> = names
+                    .keys()
+                    .filter(|name| used.contains(name.as_str()))
+                    .collect();
+                let namespace = statement
+                    .path
+                    .strip_suffix("::**")
+                    .or_else(|| statement.path.strip_suffix("::*"))
+                    .unwrap_or(&statement.path);
+                let expandable = expand_wildcards
+                    && !statement.path.ends_with("::**")
+                    && statement
+                        .text
+                        .ends_with(&format!("import {};", statement.path));
+                if expandable {
+                    for name in used_names {
+                        let path = format!("{namespace}::{name}");
+                        let text = format!("private import {path};");
+                        kept.push((true, path, text));
+                    }
+                } else if !used_names.is_empty() {
+                    kept.push((true, statement.path.clone(), statement.text.clone()));
+                }
+            }
+            None => {
+                let name = statement
+                    .path
+                    .rsplit("::")
+                    .next()
+                    .unwrap_or(&statement.path);
+                if used.contains(name) {
+                    kept.push((true, statement.path.clone(), statement.text.clone()));
+                }
+            }
+        }
+    }
+
+    // A path imported both publicly and privately only needs the public import
+    kept.sort();
+    let public: BTreeSet<String> = kept
+        .iter()
+        .filter(|(private, ..)| !private)
+        .map(|(_, path, _)| path.clone())
+        .collect();
+    kept.retain(|(private, path, _)| !private || !public.contains(path));
+    kept.dedup_by(|a, b| a.2 == b.2);
+
+    kept.into_iter().map(|(_, _, text)| text).collect()
+}
+
+/// Edits replacing a package's import lines with the organized statements,
+/// placed where its first import was
+fn scope_edits(
+    lines: &[&str],
+    statements: &[ImportStatement],
+    organized: Vec<String>,
+) -> Vec<TextEdit> {
+    let mut spans: Vec<&std::ops::RangeInclusive<u32>> = statements
+        .iter()
+        .map(|statement| &statement.lines)
+        .collect();
+    spans.sort_by_key(|span| *span.start());
+    spans.dedup();
+
+    let first = *spans[0].start() as usize;
+    let indent = &lines[first][..lines[first].len() - lines[first].trim_start().len()];
+    let current: Vec<String> = spans
+        .iter()
+        .flat_map(|span| *span.start()..=*span.end())
+        .map(|line| lines[line as usize].trim().to_string())
+        .collect();
+    let contiguous = spans
+        .windows(2)
+        .all(|pair| *pair[0].end() + 1 == *pair[1].start());
+    if contiguous && current == organized {
+        return Vec::new();
+    }
+
+    let mut edits: Vec<TextEdit> = spans
+        .iter()
+        .map(|span| TextEdit {
+            range: Range::new(
+                Position::new(*span.start(), 0),
+                Position::new(*span.end() + 1, 0),
+            ),
+            new_text: String::new(),
+        })
+        .collect();
+    edits[0].new_text = organized
+        .iter()
+        .map(|statement| format!("{indent}{statement}\n"))
+        .collect();
+    edits
+}
+
+/// The package an import belongs to: import symbols are named `<scope>::import:<path>`
+pub(super) fn import_scope(import: &HirSymbol) -> &str {
+    let qualified_name = import.qualified_name.as_ref();
+    qualified_name
+        .find("::import:")
+        .map_or("", |idx| &qualified_name[..idx])
+}
+
+/// Whether an element is the package `scope` or one of its members
+pub(super) fn in_scope(owner: &str, scope: &str) -> bool {
+    scope.is_empty()
+        || owner == scope
+        || owner
+            .strip_prefix(scope)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+/// The name a reference starts with (`Engine` for `Engine::power` or `engine.power`)
+pub(super) fn first_segment(target: &str) -> &str {
+    let end = target
+        .find("::")
+        .into_iter()
+        .chain(target.find('.'))
+        .min()
+        .unwrap_or(target.len());
+    target[..end].trim_matches('\'')
+}
+
+fn source_action(title: &str, kind: CodeActionKind, uri: &Url, edits: Vec<TextEdit>) -> CodeAction {
+    CodeAction {
+        title: title.to_string(),
+        kind: Some(kind),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), edits)])),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
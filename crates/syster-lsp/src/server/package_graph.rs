@@ -0,0 +1,244 @@
+//! Package-level import graph
+//!
+//! `syster/getDependencyGraph` returns which packages import which, so the
+//! extension can draw the import graph and users can spot dependencies on the
+//! standard library or across subsystems they didn't intend. An import of a
+//! package member (`import Parts::Engine;`) is a dependency on the package
+//! owning it, and an import inside a definition belongs to the package around
+//! the definition.
+//!
+//! Public imports re-export what they import, so a package also depends on
+//! whatever the packages it imports publicly import in turn. Those transitive
+//! dependencies are listed with the packages they go through. Dependencies of
+//! standard library packages are left out unless asked for; dependencies on
+//! them, including those re-exported by the library, never are.
+
+use super::LspServer;
+use super::organize_imports::import_scope;
+use super::project_validation::imported_namespace;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::Url;
+use async_lsp::lsp_types::request::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::path::PathBuf;
+use syster::hir::{HirSymbol, SymbolIndex, SymbolKind};
+
+/// Custom LSP request: syster/getDependencyGraph
+pub enum GetDependencyGraphRequest {}
+
+impl Request for GetDependencyGraphRequest {
+    type Params = GetDependencyGraphParams;
+    type Result = DependencyGraphData;
+    const METHOD: &'static str = "syster/getDependencyGraph";
+}
+
+/// Request parameters for syster/getDependencyGraph
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetDependencyGraphParams {
+    /// Include the imports of standard library packages
+    #[serde(default)]
+    pub include_stdlib: bool,
+}
+
+/// Packages and the imports between them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyGraphData {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Packages importing or imported, ordered by qualified name
+    pub packages: Vec<PackageNode>,
+    /// Dependencies ordered by source and target
+    pub imports: Vec<PackageImport>,
+}
+
+/// A package in the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageNode {
+    pub qualified_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    /// Whether the package is part of the standard library
+    #[serde(default)]
+    pub stdlib: bool,
+}
+
+/// A package depending on another through its imports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageImport {
+    /// Qualified name of the importing package
+    pub source: String,
+    /// Qualified name of the imported package
+    pub target: String,
+    /// Whether `source` re-exports what it gets from `target`
+    pub public: bool,
+    /// Packages whose public imports lead to `target`; empty for a direct import
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub via: Vec<String>,
+}
+
+/// An import statement resolved to the packages on both sides
+#[derive(Debug, Clone)]
+pub(super) struct DirectImport {
+    pub source: String,
+    pub target: String,
+    pub public: bool,
+}
+
+impl LspServer {
+    /// The package dependency graph of the workspace
+    pub fn get_dependency_graph(&mut self, include_stdlib: bool) -> DependencyGraphData {
+        let (imports, package_files) = self.direct_imports();
+        let in_stdlib = |name: &str| {
+            package_files
+                .get(name)
+                .is_some_and(|path| self.is_stdlib_path(path))
+        };
+
+        // Direct imports, with repeated imports of a package merged
+        let mut direct: BTreeMap<&str, BTreeMap<&str, bool>> = BTreeMap::new();
+        for import in &imports {
+            *direct
+                .entry(&import.source)
+                .or_default()
+                .entry(&import.target)
+                .or_default() |= import.public;
+        }
+
+        let mut edges = Vec::new();
+        for (&source, targets) in &direct {
+            if !include_stdlib && in_stdlib(source) {
+                continue;
+            }
+            for (&target, &public) in targets {
+                edges.push(PackageImport {
+                    source: source.to_string(),
+                    target: target.to_string(),
+                    public,
+                    via: Vec::new(),
+                });
+            }
+            edges.extend(reexports(&direct, source));
+        }
+        edges.sort_by(|a, b| (&a.source, &a.target).cmp(&(&b.source, &b.target)));
+
+        let names: BTreeSet<&str> = edges
+            .iter()
+            .flat_map(|edge| [edge.source.as_str(), edge.target.as_str()])
+            .collect();
+        let packages = names
+            .into_iter()
+            .map(|name| PackageNode {
+                qualified_name: name.to_string(),
+                uri: package_files
+                    .get(name)
+                    .and_then(|path| Url::from_file_path(path).ok())
+                    .map(|uri| uri.to_string()),
+                stdlib: in_stdlib(name),
+            })
+            .collect();
+
+        DependencyGraphData {
+            schema_version: SCHEMA_VERSION,
+            packages,
+            imports: edges,
+        }
+    }
+
+    /// Every import statement resolved to packages, and the file declaring
+    /// each package
+    pub(super) fn direct_imports(&mut self) -> (Vec<DirectImport>, BTreeMap<String, PathBuf>) {
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+
+        let mut imports = Vec::new();
+        let mut package_files = BTreeMap::new();
+        for symbol in index.all_symbols() {
+            match symbol.kind {
+                SymbolKind::Package => {
+                    if let Some(path) = analysis.get_file_path(symbol.file) {
+                        package_files.insert(symbol.qualified_name.to_string(), path.into());
+                    }
+                }
+                SymbolKind::Import => {
+                    imports.extend(resolve_package_import(index, symbol));
+                }
+                _ => {}
+            }
+        }
+        (imports, package_files)
+    }
+}
+
+/// The packages on both sides of an import, unless it stays within one
+pub(super) fn resolve_package_import(
+    index: &SymbolIndex,
+    import: &HirSymbol,
+) -> Option<DirectImport> {
+    let owner = import_scope(import);
+    let source = enclosing_package(index, owner)?;
+    let namespace = imported_namespace(index, owner, &import.name)?;
+    let target = enclosing_package(index, &namespace)?;
+    (source != target).then(|| DirectImport {
+        source: source.to_string(),
+        target: target.to_string(),
+        public: import.is_public,
+    })
+}
+
+/// The package that is or owns the element named `qualified_name`
+fn enclosing_package<'a>(index: &SymbolIndex, qualified_name: &'a str) -> Option<&'a str> {
+    let mut name = qualified_name;
+    loop {
+        if index
+            .lookup_qualified(name)
+            .is_some_and(|symbol| symbol.kind == SymbolKind::Package)
+        {
+            return Some(name);
+        }
+        name = name.rsplit_once("::")?.0;
+    }
+}
+
+/// Packages `source` depends on through the public imports of the packages
+/// it imports, each with the shortest chain of packages leading to it
+fn reexports(direct: &BTreeMap<&str, BTreeMap<&str, bool>>, source: &str) -> Vec<PackageImport> {
+    let Some(imported) = direct.get(source) else {
+        return Vec::new();
+    };
+    let mut seen: HashSet<&str> = imported.keys().copied().collect();
+    seen.insert(source);
+
+    // Breadth first from each directly imported package, following public
+    // imports; `seen` keeps cycles from being walked again
+    let mut queue: VecDeque<(&str, bool, Vec<&str>)> = imported
+        .iter()
+        .map(|(&target, &public)| (target, public, vec![target]))
+        .collect();
+    let mut edges = Vec::new();
+    while let Some((package, public, via)) = queue.pop_front() {
+        let Some(targets) = direct.get(package) else {
+            continue;
+        };
+        for (&target, _) in targets.iter().filter(|(_, public)| **public) {
+            if !seen.insert(target) {
+                continue;
+            }
+            edges.push(PackageImport {
+                source: source.to_string(),
+                target: target.to_string(),
+                public,
+                via: via.iter().map(|name| name.to_string()).collect(),
+            });
+            let mut next = via.clone();
+            next.push(target);
+            queue.push_back((target, public, next));
+        }
+    }
+    edges
+}
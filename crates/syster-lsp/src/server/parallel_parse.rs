@@ -0,0 +1,120 @@
+//! Parsing many files at once on all cores
+//!
+//! Loading the standard library parses about a hundred files before the first
+//! request can be answered. Parsing is independent per file, so the files are
+//! read and parsed on a scoped thread per core, and only the results are put
+//! into the `AnalysisHost` on the calling thread. The host keys files by path,
+//! so the symbol tables come out the same whatever order the workers finish in.
+
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use syster::core::constants::STDLIB_DIR;
+use syster::ide::AnalysisHost;
+
+use super::parse_cache::CachedParse;
+use super::workspace_filter::WorkspaceFilter;
+
+/// Read and parse `paths` in parallel, returning the results in path order
+pub(super) fn parse_files(paths: &[PathBuf]) -> Vec<(PathBuf, std::io::Result<CachedParse>)> {
+    let workers = std::thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(paths.len());
+    if workers <= 1 {
+        return paths
+            .iter()
+            .map(|path| (path.clone(), parse_file(path)))
+            .collect();
+    }
+
+    // Workers take the next file as they finish one, so a large file doesn't
+    // hold up a whole share of the others
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, std::io::Result<CachedParse>)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut parsed = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = paths.get(idx) else {
+                            break;
+                        };
+                        parsed.push((idx, parse_file(path)));
+                    }
+                    parsed
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    });
+    results.sort_by_key(|(idx, _)| *idx);
+    results
+        .into_iter()
+        .map(|(idx, result)| (paths[idx].clone(), result))
+        .collect()
+}
+
+fn parse_file(path: &Path) -> std::io::Result<CachedParse> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(CachedParse::parse(path, &text))
+}
+
+/// Parse `paths` in parallel and add them to `host`.
+///
+/// Files that can't be read or parsed are logged and skipped; returns how
+/// many were loaded.
+pub(super) fn load_files(paths: &[PathBuf], host: &mut AnalysisHost) -> usize {
+    let mut loaded = 0;
+    for (path, result) in parse_files(paths) {
+        match result {
+            Ok(parse) => {
+                if !parse.errors.is_empty() {
+                    tracing::warn!(
+                        path = %path.display(),
+                        "{} parse errors",
+                        parse.errors.len()
+                    );
+                }
+                if let Some(file) = parse.content {
+                    host.set_file(path, file);
+                    loaded += 1;
+                }
+            }
+            Err(err) => tracing::warn!(path = %path.display(), "Failed to read file: {err}"),
+        }
+    }
+    loaded
+}
+
+/// The stdlib directory to load: the configured one, or a `sysml.library`
+/// next to the executable or in the working directory
+///
+/// `None` leaves discovery to the `StdLibLoader`, which parses sequentially.
+pub(super) fn stdlib_dir(configured: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = configured {
+        return Some(path.to_path_buf());
+    }
+    let beside_exe = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join(STDLIB_DIR)));
+    let in_cwd = std::env::current_dir().ok().map(|dir| dir.join(STDLIB_DIR));
+    beside_exe
+        .into_iter()
+        .chain(in_cwd)
+        .find(|dir| dir.is_dir())
+}
+
+/// Parse the stdlib files under `dir` in parallel and add them to `host`.
+///
+/// Returns how many files were loaded, or an error if there were none.
+pub(super) fn load_stdlib(dir: &Path, host: &mut AnalysisHost) -> Result<usize, String> {
+    let paths = WorkspaceFilter::for_folder(dir, &[]).collect_files()?;
+    match load_files(&paths, host) {
+        0 => Err(format!("No stdlib files found in {}", dir.display())),
+        loaded => Ok(loaded),
+    }
+}
@@ -0,0 +1,175 @@
+//! Content-addressed cache of parse results.
+//!
+//! Re-opening an unchanged file, undoing an edit, or switching git branches back
+//! and forth produces text we have already parsed. Results are keyed by a hash
+//! of the file extension and content so those cases skip the parser entirely.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{OsStr, OsString};
+use std::hash::Hash;
+use std::path::Path;
+use std::sync::Arc;
+use syster::core::ParseError;
+use syster::syntax::SyntaxFile;
+
+/// Default number of parse results kept in the cache
+pub const DEFAULT_PARSE_CACHE_CAPACITY: usize = 128;
+
+/// A cached parse result
+#[derive(Debug, Clone)]
+pub struct CachedParse {
+    /// Parsed syntax tree (None if parsing failed completely)
+    pub content: Option<SyntaxFile>,
+    /// Parse errors reported for the content
+    pub errors: Vec<ParseError>,
+}
+
+impl CachedParse {
+    /// Run the full-file parser for the path's language
+    pub fn parse(path: &Path, text: &str) -> Self {
+        let result = syster::project::file_loader::parse_with_result(text, path);
+        Self {
+            content: result.content,
+            errors: result.errors,
+        }
+    }
+}
+
+/// Text parsed with the grammar its file extension selects
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceKey {
+    extension: Option<OsString>,
+    text: Arc<str>,
+}
+
+/// LRU-bounded cache of parse results keyed by the source they were parsed from
+///
+/// Keys are compared in full, so texts whose hashes collide never share a
+/// result. Results are shared through an `Arc` rather than copied on each hit.
+#[derive(Debug)]
+pub struct ParseCache<K = SourceKey> {
+    /// Cached results with the tick of their most recent use and their source length
+    entries: HashMap<K, (Arc<CachedParse>, u64, usize)>,
+    /// Keys ordered from least to most recently used
+    order: BTreeMap<u64, K>,
+    /// Monotonic use counter
+    tick: u64,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl<K: Clone + Eq + Hash> Default for ParseCache<K> {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_PARSE_CACHE_CAPACITY)
+    }
+}
+
+impl ParseCache<SourceKey> {
+    /// Return the cached parse for this content, or parse it with `parse` and cache the result
+    pub fn get_or_parse<F>(&mut self, path: &Path, text: &str, parse: F) -> Arc<CachedParse>
+    where
+        F: FnOnce() -> CachedParse,
+    {
+        // The extension selects the SysML or KerML grammar
+        let key = SourceKey {
+            extension: path.extension().map(OsStr::to_os_string),
+            text: Arc::from(text),
+        };
+        self.get_or_parse_keyed(key, text.len(), parse)
+    }
+}
+
+impl<K: Clone + Eq + Hash> ParseCache<K> {
+    /// Create a cache holding at most `capacity` parse results
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Like `get_or_parse`, but keyed by any value that fully determines the
+    /// parsed text of `len` bytes.
+    ///
+    /// Lets callers skip building the text to parse when the result is already cached.
+    pub fn get_or_parse_keyed<F>(&mut self, key: K, len: usize, parse: F) -> Arc<CachedParse>
+    where
+        F: FnOnce() -> CachedParse,
+    {
+        self.tick += 1;
+        let tick = self.tick;
+
+        if let Some((cached, last_used, _)) = self.entries.get_mut(&key) {
+            self.hits += 1;
+            self.order.remove(last_used);
+            *last_used = tick;
+            self.order.insert(tick, key);
+            return Arc::clone(cached);
+        }
+
+        self.misses += 1;
+        let parsed = Arc::new(parse());
+        if self.capacity > 0 {
+            while self.entries.len() >= self.capacity {
+                match self.order.pop_first() {
+                    Some((_, oldest)) => {
+                        self.entries.remove(&oldest);
+                    }
+                    None => break,
+                }
+            }
+            self.order.insert(tick, key.clone());
+            self.entries.insert(key, (Arc::clone(&parsed), tick, len));
+        }
+        parsed
+    }
+
+    /// Number of cached parse results
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Maximum number of cached parse results
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of lookups served from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of lookups that required a parse
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Fraction of lookups served from the cache (0.0 when nothing was looked up)
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+
+    /// Total source bytes covered by the cached entries
+    pub fn cached_source_bytes(&self) -> usize {
+        self.entries.values().map(|(_, _, len)| len).sum()
+    }
+
+    /// Drop all cached entries, keeping the hit/miss counters. Returns the number removed.
+    pub fn clear(&mut self) -> usize {
+        let removed = self.entries.len();
+        self.entries.clear();
+        self.order.clear();
+        self.entries.shrink_to_fit();
+        removed
+    }
+}
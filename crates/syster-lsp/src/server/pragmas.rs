@@ -0,0 +1,135 @@
+//! Pragma comments that opt a file or region out of formatting and validation
+//!
+//! Generated and third-party models can be excluded without workspace
+//! configuration by writing line comments of their own:
+//! - `// syster:format off` leaves the lines after it as written, up to a
+//!   `// syster:format on` or the end of the file
+//! - `// syster:lint off` hides the file's diagnostics, except syntax errors
+//! - `// syster:lint off relationship-kind naming-convention` hides only the
+//!   diagnostics with those codes
+
+use async_lsp::lsp_types::{Diagnostic, NumberOrString, Range};
+use std::collections::HashSet;
+
+/// Source of syntax error diagnostics, which pragmas never hide
+const PARSE_SOURCE: &str = "syster-parse";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Pragma<'a> {
+    FormatOff,
+    FormatOn,
+    LintOff(Vec<&'a str>),
+}
+
+/// The pragma a line holds, if it is nothing but a pragma comment
+fn pragma(line: &str) -> Option<Pragma<'_>> {
+    let comment = line.trim().strip_prefix("//")?;
+    let mut words = comment.split_whitespace();
+    let pragma = match (words.next()?, words.next()?) {
+        ("syster:format", "off") => Pragma::FormatOff,
+        ("syster:format", "on") => Pragma::FormatOn,
+        ("syster:lint", "off") => return Some(Pragma::LintOff(words.collect())),
+        _ => return None,
+    };
+    words.next().is_none().then_some(pragma)
+}
+
+/// Lines excluded from formatting: those after each `format off` pragma, up
+/// to the matching `format on` pragma or the end of the text. The pragmas
+/// themselves are formatted like other comments.
+pub fn format_off_regions(text: &str) -> Vec<std::ops::Range<usize>> {
+    let mut regions = Vec::new();
+    let mut start = None;
+    let mut count = 0;
+    for (idx, line) in text.split('\n').enumerate() {
+        count = idx + 1;
+        match pragma(line) {
+            Some(Pragma::FormatOff) if start.is_none() => start = Some(idx + 1),
+            Some(Pragma::FormatOn) => {
+                if let Some(start) = start.take() {
+                    regions.push(start..idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = start {
+        regions.push(start..count);
+    }
+    regions
+}
+
+/// Whether a range touches a line excluded from formatting
+pub fn overlaps_format_off(text: &str, range: Range) -> bool {
+    let (first, last) = (range.start.line as usize, range.end.line as usize);
+    format_off_regions(text)
+        .iter()
+        .any(|region| region.start <= last && first < region.end)
+}
+
+/// Put the lines of `original`'s format-off regions back into its formatted text.
+///
+/// The formatter keeps comments, so the formatted text has the same pragmas
+/// in the same order. Returns None if it doesn't, since the regions can't be
+/// matched up.
+pub fn restore_format_off_regions(original: &str, formatted: &str) -> Option<String> {
+    let original_regions = format_off_regions(original);
+    if original_regions.is_empty() {
+        return Some(formatted.to_string());
+    }
+    let formatted_regions = format_off_regions(formatted);
+    if formatted_regions.len() != original_regions.len() {
+        return None;
+    }
+
+    let original_lines: Vec<&str> = original.split('\n').collect();
+    let formatted_lines: Vec<&str> = formatted.split('\n').collect();
+    let mut lines: Vec<&str> = Vec::with_capacity(formatted_lines.len());
+    let mut next = 0;
+    for (kept, replaced) in original_regions.iter().zip(&formatted_regions) {
+        lines.extend(&formatted_lines[next..replaced.start]);
+        lines.extend(&original_lines[kept.clone()]);
+        next = replaced.end;
+    }
+    lines.extend(formatted_lines.get(next..).unwrap_or_default());
+    Some(lines.join("\n"))
+}
+
+/// Diagnostics hidden by the `lint off` pragmas of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintSuppression {
+    /// Every diagnostic except syntax errors
+    All,
+    /// Diagnostics with these codes
+    Rules(HashSet<String>),
+}
+
+impl LintSuppression {
+    /// Read the `lint off` pragmas of a file
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut rules = HashSet::new();
+        for line in text.lines() {
+            if let Some(Pragma::LintOff(ids)) = pragma(line) {
+                if ids.is_empty() {
+                    return Some(Self::All);
+                }
+                rules.extend(ids.into_iter().map(str::to_string));
+            }
+        }
+        (!rules.is_empty()).then_some(Self::Rules(rules))
+    }
+
+    pub fn suppresses(&self, diagnostic: &Diagnostic) -> bool {
+        if diagnostic.source.as_deref() == Some(PARSE_SOURCE) {
+            return false;
+        }
+        match (self, &diagnostic.code) {
+            (Self::All, _) => true,
+            (Self::Rules(rules), Some(NumberOrString::String(code))) => rules.contains(code),
+            (Self::Rules(rules), Some(NumberOrString::Number(code))) => {
+                rules.contains(&code.to_string())
+            }
+            (Self::Rules(_), None) => false,
+        }
+    }
+}
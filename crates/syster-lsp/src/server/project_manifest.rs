@@ -0,0 +1,159 @@
+//! Project manifest (`syster.toml`)
+//!
+//! A manifest at the root of a workspace folder configures how the project is
+//! loaded:
+//!
+//! ```toml
+//! [project]
+//! sources = ["models", "lib"]   # directories scanned instead of the whole folder
+//! exclude = ["generated/"]      # gitignore-style globs, on top of `excludeGlobs`
+//!
+//! [stdlib]
+//! path = "vendor/sysml.library" # overrides `stdlibPath`
+//! enabled = true                # overrides `stdlibEnabled`
+//!
+//! [format]
+//! tab_size = 2                  # override the editor's formatting options
+//! insert_spaces = true
+//! ```
+//!
+//! Relative paths are resolved against the manifest's directory. Unknown keys
+//! and tables are ignored.
+
+use super::LspServer;
+use super::environment::FileSystem;
+use async_lsp::lsp_types::{FormattingOptions, Url};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// File name of the project manifest
+pub const MANIFEST_FILE: &str = "syster.toml";
+
+/// Settings read from a `syster.toml`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectManifest {
+    /// Directory holding the manifest
+    pub root: PathBuf,
+    /// Source directories to scan (the whole root when empty)
+    pub sources: Vec<PathBuf>,
+    /// Globs excluded when scanning the source directories
+    pub exclude: Vec<String>,
+    pub stdlib_path: Option<PathBuf>,
+    pub stdlib_enabled: Option<bool>,
+    pub tab_size: Option<u32>,
+    pub insert_spaces: Option<bool>,
+}
+
+impl ProjectManifest {
+    /// Read the manifest at the root of `folder`, if there is one
+    pub fn find(fs: &dyn FileSystem, folder: &Path) -> Option<Result<Self, String>> {
+        let text = fs.read_to_string(&folder.join(MANIFEST_FILE)).ok()?;
+        Some(Self::parse(folder, &text))
+    }
+
+    /// Parse manifest text, resolving relative paths against `root`
+    pub fn parse(root: &Path, text: &str) -> Result<Self, String> {
+        let file: ManifestFile = toml::from_str(text).map_err(|err| match err.span() {
+            Some(span) => {
+                let line = text[..span.start].matches('\n').count() + 1;
+                format!("line {line}: {}", err.message())
+            }
+            None => err.message().to_string(),
+        })?;
+
+        let tab_size = file
+            .format
+            .tab_size
+            .map(|size| {
+                u32::try_from(size)
+                    .ok()
+                    .filter(|size| *size > 0)
+                    .ok_or("'format.tab_size' must be a positive integer")
+            })
+            .transpose()?;
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            sources: file
+                .project
+                .sources
+                .iter()
+                .map(|dir| root.join(dir))
+                .collect(),
+            exclude: file.project.exclude,
+            stdlib_path: file.stdlib.path.map(|path| root.join(path)),
+            stdlib_enabled: file.stdlib.enabled,
+            tab_size,
+            insert_spaces: file.format.insert_spaces,
+        })
+    }
+
+    /// Directories scanned for model files
+    pub fn source_folders(&self) -> Vec<PathBuf> {
+        if self.sources.is_empty() {
+            vec![self.root.clone()]
+        } else {
+            self.sources.clone()
+        }
+    }
+}
+
+impl LspServer {
+    /// Project manifests read from the workspace folders
+    pub fn project_manifests(&self) -> &[ProjectManifest] {
+        &self.project_manifests
+    }
+
+    /// Apply the formatting options of the project a document belongs to
+    pub fn project_formatting_options(
+        &self,
+        uri: &Url,
+        mut options: FormattingOptions,
+    ) -> FormattingOptions {
+        let Ok(path) = uri.to_file_path() else {
+            return options;
+        };
+        if let Some(manifest) = self
+            .workspace_root(&path)
+            .and_then(|root| self.root_manifest(root))
+        {
+            if let Some(tab_size) = manifest.tab_size {
+                options.tab_size = tab_size;
+            }
+            if let Some(insert_spaces) = manifest.insert_spaces {
+                options.insert_spaces = insert_spaces;
+            }
+        }
+        options
+    }
+}
+
+/// The tables of a `syster.toml`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ManifestFile {
+    project: ProjectTable,
+    stdlib: StdlibTable,
+    format: FormatTable,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ProjectTable {
+    sources: Vec<PathBuf>,
+    exclude: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StdlibTable {
+    path: Option<PathBuf>,
+    enabled: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FormatTable {
+    tab_size: Option<i64>,
+    insert_spaces: Option<bool>,
+}
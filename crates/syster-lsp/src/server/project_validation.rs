@@ -0,0 +1,431 @@
+//! Debounced validation of the whole project
+//!
+//! Document diagnostics are computed as each document is edited, but some
+//! problems only show across files: an element declared under the same
+//! qualified name in two files, packages that publicly import each other in a
+//! cycle, or - when enabled - definitions nothing outside their file refers to.
+//! Once edits have paused for the configured delay, the server checks the
+//! whole project for those, then re-validates every project file
+//! (unresolved references included) a few files per step, so requests queued
+//! in between are answered without waiting for the whole run. An edit cancels
+//! its document's token, which abandons the run; the next pause starts over.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, Url};
+use syster::hir::{HirSymbol, SymbolIndex, SymbolKind};
+use tokio_util::sync::CancellationToken;
+
+use super::LspServer;
+use super::scope_check::DUPLICATE_NAME_CODE;
+
+/// Diagnostic code for a public import that is part of an import cycle
+pub const IMPORT_CYCLE_CODE: &str = "import-cycle";
+
+/// Files re-validated per step of a validation run
+pub const VALIDATION_CHUNK_SIZE: usize = 16;
+
+/// Findings of the last project-wide checks and the run in progress
+#[derive(Debug, Default)]
+pub(super) struct ProjectValidation {
+    /// Cross-file findings, per file
+    findings: HashMap<PathBuf, Vec<Diagnostic>>,
+    run: Option<ValidationRun>,
+    /// Incremented by every run, so steps of a replaced run are ignored
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct ValidationRun {
+    generation: u64,
+    /// Files still to re-validate
+    pending: VecDeque<PathBuf>,
+    /// Tokens of the open documents when the run started
+    tokens: Vec<CancellationToken>,
+}
+
+impl ProjectValidation {
+    /// Cross-file findings of the last run for a file
+    pub(super) fn findings(&self, path: &Path) -> &[Diagnostic] {
+        self.findings.get(path).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Diagnostics from one step of a validation run
+#[derive(Debug, Default)]
+pub struct ValidationStep {
+    /// Documents whose diagnostics changed, to publish
+    pub diagnostics: Vec<(Url, Vec<Diagnostic>)>,
+    /// Every file has been re-validated
+    pub finished: bool,
+}
+
+impl LspServer {
+    /// Check the whole project for cross-file problems and start re-validating
+    /// its files, abandoning any run in progress. Returns the run's generation.
+    pub fn start_project_validation(&mut self) -> u64 {
+        let project: HashSet<PathBuf> = self
+            .document_texts
+            .keys()
+            .filter(|path| {
+                self.document_versions.contains_key(*path) || self.is_workspace_file(path)
+            })
+            .cloned()
+            .collect();
+        let unused_elements = if self.unused_elements {
+            self.unused_element_findings()
+        } else {
+            Vec::new()
+        };
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+        let path_of = |sym: &HirSymbol| analysis.get_file_path(sym.file).map(PathBuf::from);
+        let mut findings: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+        for (path, diagnostic) in duplicate_definitions(index, &path_of)
+            .into_iter()
+            .chain(import_cycles(index, &path_of))
+            .chain(unused_elements)
+            .filter(|(path, _)| project.contains(path))
+        {
+            findings.entry(path).or_default().push(diagnostic);
+        }
+
+        // Open documents first, then the rest of the project; files that had
+        // findings are re-validated too so stale ones are cleared
+        let mut files: Vec<PathBuf> = project
+            .into_iter()
+            .chain(self.project_validation.findings.keys().cloned())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        files.sort_by(|a, b| {
+            let closed = |path: &PathBuf| !self.document_versions.contains_key(path);
+            (closed(a), a).cmp(&(closed(b), b))
+        });
+        if self.client_support.pull_diagnostics {
+            // Pulling clients ask for what they show once the run finishes
+            files.clear();
+        }
+
+        let tokens = self
+            .document_versions
+            .keys()
+            .map(|path| {
+                self.document_cancel_tokens
+                    .entry(path.clone())
+                    .or_default()
+                    .clone()
+            })
+            .collect();
+
+        let validation = &mut self.project_validation;
+        validation.findings = findings;
+        validation.generation += 1;
+        validation.run = Some(ValidationRun {
+            generation: validation.generation,
+            pending: files.into(),
+            tokens,
+        });
+        validation.generation
+    }
+
+    /// Re-validate up to `budget` more files of the run `generation`
+    ///
+    /// Returns `None` if that run was replaced, finished already, or abandoned
+    /// because a document was edited since it started.
+    pub fn continue_project_validation(
+        &mut self,
+        generation: u64,
+        budget: usize,
+    ) -> Option<ValidationStep> {
+        let run = self
+            .project_validation
+            .run
+            .as_mut()
+            .filter(|run| run.generation == generation)?;
+        if run.tokens.iter().any(CancellationToken::is_cancelled) {
+            self.project_validation.run = None;
+            return None;
+        }
+
+        let batch: Vec<PathBuf> = run.pending.drain(..budget.min(run.pending.len())).collect();
+        let finished = run.pending.is_empty();
+        if finished {
+            self.project_validation.run = None;
+        }
+
+        let mut step = ValidationStep {
+            diagnostics: Vec::new(),
+            finished,
+        };
+        for path in batch {
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            if let Some(diagnostics) = self.diagnostics_to_publish(&uri) {
+                step.diagnostics.push((uri, diagnostics));
+            }
+        }
+        Some(step)
+    }
+}
+
+/// Range of a symbol's name
+fn name_range(sym: &HirSymbol) -> Range {
+    Range::new(
+        Position::new(sym.start_line, sym.start_col),
+        Position::new(sym.end_line, sym.end_col),
+    )
+}
+
+/// Elements declared under the same qualified name in more than one file
+///
+/// Packages may be spread over several files, so only the outermost
+/// duplicated element below them is reported, not each of its members.
+fn duplicate_definitions(
+    index: &SymbolIndex,
+    path_of: &dyn Fn(&HirSymbol) -> Option<PathBuf>,
+) -> Vec<(PathBuf, Diagnostic)> {
+    let mut declarations: HashMap<&str, Vec<(&HirSymbol, PathBuf)>> = HashMap::new();
+    for sym in index.all_symbols().filter(|sym| {
+        !sym.name.is_empty()
+            && !sym.name.starts_with('<')
+            && !matches!(
+                sym.kind,
+                SymbolKind::Package
+                    | SymbolKind::Import
+                    | SymbolKind::Comment
+                    | SymbolKind::Dependency
+            )
+    }) {
+        if let Some(path) = path_of(sym) {
+            declarations
+                .entry(sym.qualified_name.as_ref())
+                .or_default()
+                .push((sym, path));
+        }
+    }
+    declarations.retain(|_, declared| {
+        let files: HashSet<&PathBuf> = declared.iter().map(|(_, path)| path).collect();
+        files.len() > 1
+    });
+
+    let mut duplicates = Vec::new();
+    for (qualified_name, declared) in &declarations {
+        let owner_duplicated = qualified_name
+            .rsplit_once("::")
+            .is_some_and(|(owner, _)| declarations.contains_key(owner));
+        if owner_duplicated {
+            continue;
+        }
+        for (sym, path) in declared {
+            let mut others: Vec<String> = declared
+                .iter()
+                .filter(|(_, other)| other != path)
+                .filter_map(|(_, other)| other.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+                .collect();
+            others.sort();
+            others.dedup();
+            duplicates.push((
+                path.clone(),
+                Diagnostic {
+                    range: name_range(sym),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(DUPLICATE_NAME_CODE.to_string())),
+                    message: format!(
+                        "'{qualified_name}' is also declared in {}",
+                        others.join(", ")
+                    ),
+                    source: Some("syster-semantic".to_string()),
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+    duplicates
+}
+
+/// Public imports that are part of a cycle of namespaces re-exporting each other
+///
+/// Private imports don't re-export, so they can't close a cycle. The
+/// namespaces are split into strongly connected components in one walk that
+/// visits each namespace once; an import is part of a cycle when both its
+/// namespaces are in the same component, and the way back is only searched
+/// within it.
+fn import_cycles(
+    index: &SymbolIndex,
+    path_of: &dyn Fn(&HirSymbol) -> Option<PathBuf>,
+) -> Vec<(PathBuf, Diagnostic)> {
+    // Namespace importing and namespace imported by each import
+    let imports: Vec<(&HirSymbol, &str, String)> = index
+        .all_symbols()
+        .filter(|sym| sym.kind == SymbolKind::Import && sym.is_public)
+        .filter_map(|import| {
+            let (owner, _) = import.qualified_name.split_once("::import:")?;
+            let target = imported_namespace(index, owner, &import.name)?;
+            (target != owner).then_some((import, owner, target))
+        })
+        .collect();
+    let mut graph: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (_, owner, target) in &imports {
+        graph.entry(*owner).or_default().insert(target.as_str());
+    }
+    let components = strongly_connected(&graph);
+
+    let mut cycles = Vec::new();
+    for (import, owner, target) in &imports {
+        let Some(back) = shortest_path(&graph, &components, target, *owner) else {
+            continue;
+        };
+        let Some(path) = path_of(import) else {
+            continue;
+        };
+        let mut names = vec![*owner];
+        names.extend(back);
+        cycles.push((
+            path,
+            Diagnostic {
+                range: name_range(import),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(IMPORT_CYCLE_CODE.to_string())),
+                message: format!(
+                    "public import of '{target}' forms an import cycle: {}",
+                    names.join(" -> ")
+                ),
+                source: Some("syster-semantic".to_string()),
+                ..Default::default()
+            },
+        ));
+    }
+    cycles
+}
+
+/// Namespace whose members an import brings in: `P` for `P::*`, `P::**` and `P::X`
+pub(super) fn imported_namespace(index: &SymbolIndex, owner: &str, path: &str) -> Option<String> {
+    let wildcard = path
+        .strip_suffix("::**")
+        .or_else(|| path.strip_suffix("::*"));
+    let target = wildcard.unwrap_or(path);
+    let resolved = index
+        .resolver_for_scope(owner)
+        .resolve(target)
+        .symbol()
+        .map(|sym| (sym.qualified_name.to_string(), sym.kind))
+        .or_else(|| {
+            index
+                .lookup_qualified(target)
+                .map(|sym| (sym.qualified_name.to_string(), sym.kind))
+        })?;
+    match resolved {
+        (name, SymbolKind::Package) if wildcard.is_some() => Some(name),
+        (name, _) if wildcard.is_none() => name.rsplit_once("::").map(|(ns, _)| ns.to_string()),
+        _ => None,
+    }
+}
+
+/// The strongly connected component of each namespace of the import graph
+fn strongly_connected<'a>(graph: &BTreeMap<&'a str, BTreeSet<&'a str>>) -> HashMap<&'a str, usize> {
+    let mut walk = ComponentWalk {
+        graph,
+        order: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: HashMap::new(),
+    };
+    for &namespace in graph.keys() {
+        if !walk.order.contains_key(namespace) {
+            walk.visit(namespace);
+        }
+    }
+    walk.components
+}
+
+/// Depth-first walk of the import graph finding its strongly connected
+/// components (Tarjan's algorithm)
+struct ComponentWalk<'g, 'a> {
+    graph: &'g BTreeMap<&'a str, BTreeSet<&'a str>>,
+    /// The order namespaces were first reached in, which marks them visited
+    order: HashMap<&'a str, usize>,
+    /// The earliest namespace still on the stack reachable from each namespace
+    low: HashMap<&'a str, usize>,
+    stack: Vec<&'a str>,
+    on_stack: HashSet<&'a str>,
+    components: HashMap<&'a str, usize>,
+}
+
+impl<'a> ComponentWalk<'_, 'a> {
+    fn visit(&mut self, namespace: &'a str) {
+        let order = self.order.len();
+        self.order.insert(namespace, order);
+        self.low.insert(namespace, order);
+        self.stack.push(namespace);
+        self.on_stack.insert(namespace);
+
+        let graph = self.graph;
+        for &next in graph.get(namespace).into_iter().flatten() {
+            let reached = if !self.order.contains_key(next) {
+                self.visit(next);
+                self.low[next]
+            } else if self.on_stack.contains(next) {
+                self.order[next]
+            } else {
+                continue;
+            };
+            let low = self.low[namespace].min(reached);
+            self.low.insert(namespace, low);
+        }
+
+        // The namespace is the first of its component reached: pop the component
+        if self.low[namespace] == order {
+            let component = self.order[namespace];
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(member);
+                self.components.insert(member, component);
+                if member == namespace {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// The namespaces on the shortest path of imports from `from` to `to`, both
+/// included, if they are in the same component
+fn shortest_path<'a>(
+    graph: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+    components: &HashMap<&'a str, usize>,
+    from: &'a str,
+    to: &'a str,
+) -> Option<Vec<&'a str>> {
+    let component = components.get(from)?;
+    if components.get(to) != Some(component) {
+        return None;
+    }
+
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    let mut visited = HashSet::from([from]);
+    while let Some(namespace) = queue.pop_front() {
+        if namespace == to {
+            let mut chain = vec![to];
+            let mut current = to;
+            while let Some(&before) = previous.get(current) {
+                chain.push(before);
+                current = before;
+            }
+            chain.reverse();
+            return Some(chain);
+        }
+        for &next in graph.get(namespace).into_iter().flatten() {
+            if components.get(next) == Some(component) && visited.insert(next) {
+                previous.insert(next, namespace);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,154 @@
+//! Qualified name request handler for LSP.
+//!
+//! Resolves the element at a cursor position - either a declaration or a
+//! reference - to its fully qualified name, so clients can implement commands
+//! like "Copy Qualified Name".
+
+use super::LspServer;
+use super::helpers::uri_to_path;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::request::Request;
+use async_lsp::lsp_types::{Position, Url};
+use serde::{Deserialize, Serialize};
+use syster::base::FileId;
+use syster::hir::{HirSymbol, Resolver, SymbolIndex};
+
+/// Custom LSP request: syster/getQualifiedNameAt
+///
+/// Returns the qualified name of the element declared or referenced at a position.
+pub enum QualifiedNameAtRequest {}
+
+impl Request for QualifiedNameAtRequest {
+    type Params = QualifiedNameAtParams;
+    type Result = Option<QualifiedNameAtResult>;
+    const METHOD: &'static str = "syster/getQualifiedNameAt";
+}
+
+/// Request parameters for syster/getQualifiedNameAt
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualifiedNameAtParams {
+    /// URI of the document
+    pub uri: String,
+    /// Cursor position
+    pub position: Position,
+}
+
+/// Result of the syster/getQualifiedNameAt request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualifiedNameAtResult {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// The fully qualified name (e.g., "Vehicles::Car::engine")
+    pub qualified_name: String,
+
+    /// The shortest name that resolves to the same element from the cursor's scope
+    pub minimal_name: String,
+
+    /// The kind of the element (e.g., "Part def", "Part")
+    pub kind: String,
+}
+
+impl LspServer {
+    /// Get the qualified name of the element at a position.
+    ///
+    /// On a type reference this is the resolved target; on a declaration it is
+    /// the declared element itself.
+    pub fn get_qualified_name_at(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> Option<QualifiedNameAtResult> {
+        let path = uri_to_path(uri)?;
+        let path_str = path.to_string_lossy();
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+        let file_id = analysis.get_file_id(&path_str)?;
+        let (symbol, scope) = element_at(index, file_id, position)?;
+
+        Some(QualifiedNameAtResult {
+            schema_version: SCHEMA_VERSION,
+            qualified_name: symbol.qualified_name.to_string(),
+            minimal_name: minimal_name(index, symbol, &scope),
+            kind: symbol.kind.display().to_string(),
+        })
+    }
+}
+
+/// The element referenced or declared at a position in a file, with the scope
+/// the position is in
+pub(super) fn element_at(
+    index: &SymbolIndex,
+    file_id: FileId,
+    position: Position,
+) -> Option<(&HirSymbol, String)> {
+    let symbols = index.symbols_in_file(file_id);
+
+    // References first: a type reference can sit inside a declaration's span
+    let reference = symbols.iter().find_map(|sym| {
+        sym.type_refs.iter().find_map(|trk| {
+            let (_, type_ref) = trk.part_at(position.line, position.character)?;
+            let resolved = match &type_ref.resolved_target {
+                Some(resolved) => resolved.clone(),
+                None => index
+                    .resolver_for_scope(&sym.qualified_name)
+                    .resolve(&type_ref.target)
+                    .symbol()?
+                    .qualified_name
+                    .clone(),
+            };
+            let target = index.lookup_qualified(&resolved)?;
+            Some((target, sym.qualified_name.to_string()))
+        })
+    });
+    if reference.is_some() {
+        return reference;
+    }
+
+    // The innermost declaration containing the position
+    let symbol = symbols
+        .into_iter()
+        .filter(|sym| contains(sym, position))
+        .min_by_key(|sym| {
+            (
+                sym.end_line - sym.start_line,
+                sym.end_col.abs_diff(sym.start_col),
+            )
+        })?;
+    Some((symbol, parent_scope(&symbol.qualified_name).to_string()))
+}
+
+fn contains(sym: &HirSymbol, position: Position) -> bool {
+    let start = (sym.start_line, sym.start_col);
+    let end = (sym.end_line, sym.end_col);
+    let pos = (position.line, position.character);
+    start <= pos && pos <= end
+}
+
+fn parent_scope(qualified_name: &str) -> &str {
+    qualified_name
+        .rsplit_once("::")
+        .map_or("", |(parent, _)| parent)
+}
+
+/// The shortest suffix of the qualified name that resolves back to `symbol` from `scope`
+pub(super) fn minimal_name(index: &SymbolIndex, symbol: &HirSymbol, scope: &str) -> String {
+    shortest_name(&index.resolver_for_scope(scope), symbol)
+}
+
+/// The shortest suffix of the qualified name that `resolver` resolves back to `symbol`
+pub(super) fn shortest_name(resolver: &Resolver<'_>, symbol: &HirSymbol) -> String {
+    let segments: Vec<&str> = symbol.qualified_name.split("::").collect();
+    (1..segments.len())
+        .rev()
+        .map(|start| segments[start..].join("::"))
+        .find(|candidate| {
+            resolver
+                .resolve(candidate)
+                .symbol()
+                .is_some_and(|found| found.qualified_name == symbol.qualified_name)
+        })
+        .unwrap_or_else(|| symbol.qualified_name.to_string())
+}
@@ -0,0 +1,399 @@
+//! Per-file index of the references made by each element.
+//!
+//! Entries record what a reference says (its owner, written target and span),
+//! not what it resolves to, so edits never invalidate other files' entries.
+//! When an edited document is re-indexed, only the entries inside the members
+//! touched by the edit are replaced: entries before them are kept as they are
+//! and entries after them are moved by the edit's line and column shift. Edits
+//! that change the document's member structure replace the whole file.
+//!
+//! Owner and target names are interned: every entry naming the same element
+//! shares one allocation, whether it came from the symbol index or from the
+//! stdlib cache. A lookup by target goes through a map from each target to
+//! the files referring to it, kept in step as files are re-indexed, and only
+//! compares pointers within those files.
+
+use super::incremental_parse::{LineIndex, find_body, split_members};
+use async_lsp::lsp_types::{Position, Range};
+use std::collections::{HashMap, HashSet};
+use std::ops;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use syster::base::FileId;
+use syster::hir::SymbolIndex;
+use syster::ide::Analysis;
+
+/// A single reference made by an element
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceEntry {
+    /// Qualified name of the element making the reference
+    pub owner: Arc<str>,
+    /// The referenced name as written
+    pub target: Arc<str>,
+    /// Span of the reference
+    pub range: Range,
+}
+
+#[derive(Debug, Default)]
+struct FileReferences {
+    /// Text the entries were extracted from, kept for documents edited in the editor
+    text: Option<String>,
+    /// Shared so searches can scan a snapshot off the main loop
+    entries: Arc<[ReferenceEntry]>,
+}
+
+/// References per file, updated span by span as documents are edited
+#[derive(Debug, Default)]
+pub struct ReferenceIndex {
+    files: HashMap<PathBuf, FileReferences>,
+    /// Documents re-parsed since the last sync, with their new text
+    pending: HashMap<PathBuf, String>,
+    /// The owner and target names of all entries, one allocation per name
+    names: HashSet<Arc<str>>,
+    /// Files with entries for each written target, so lookups by target only
+    /// visit the files that make such references
+    by_target: HashMap<Arc<str>, HashSet<PathBuf>>,
+}
+
+impl ReferenceIndex {
+    /// Record that a document was re-parsed from `text`.
+    ///
+    /// The entries are updated on the next `sync`, once the symbol index has
+    /// been rebuilt.
+    pub fn note_edit(&mut self, path: &Path, text: &str) {
+        self.pending.insert(path.to_path_buf(), text.to_string());
+    }
+
+    /// Bring the index up to date with the files loaded in `analysis`.
+    ///
+    /// Edited documents get span-scoped updates, newly loaded files are
+    /// indexed in full and files no longer loaded are dropped.
+    ///
+    /// Files that weren't edited keep their entries as they are: nothing is
+    /// extracted or copied again for them, and snapshots taken before the
+    /// sync still share their entries with the index.
+    pub fn sync(&mut self, analysis: &Analysis<'_>) {
+        let index = analysis.symbol_index();
+
+        let unloaded: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| analysis.get_file_id(&path.to_string_lossy()).is_none())
+            .cloned()
+            .collect();
+        for path in unloaded {
+            self.unlink(&path);
+            self.files.remove(&path);
+        }
+
+        let edited = !self.pending.is_empty();
+        for (path, text) in std::mem::take(&mut self.pending) {
+            let Some(file) = analysis.get_file_id(&path.to_string_lossy()) else {
+                continue;
+            };
+            let fresh = intern_entries(&mut self.names, file_entries(index, file));
+            let updated = self.files.get(&path).and_then(|references| {
+                let old = references.text.as_deref()?;
+                update_entries(old, &text, &references.entries, &fresh)
+            });
+            self.set_file(
+                path,
+                FileReferences {
+                    text: Some(text),
+                    entries: updated.unwrap_or(fresh).into(),
+                },
+            );
+        }
+
+        for (path, &file) in analysis.file_id_map().iter() {
+            if !self.files.contains_key(Path::new(path)) {
+                let entries = intern_entries(&mut self.names, file_entries(index, file));
+                self.set_file(
+                    PathBuf::from(path),
+                    FileReferences {
+                        text: None,
+                        entries: entries.into(),
+                    },
+                );
+            }
+        }
+
+        // Names nothing but the index holds belonged to replaced entries
+        if edited {
+            self.names.retain(|name| Arc::strong_count(name) > 1);
+        }
+    }
+
+    /// Record the entries of a file indexed elsewhere (e.g. restored from a
+    /// cache), so `sync` doesn't extract them again while the file is loaded
+    pub fn seed(&mut self, path: &Path, entries: Vec<ReferenceEntry>) {
+        if self.files.contains_key(path) {
+            return;
+        }
+        let entries = intern_entries(&mut self.names, entries);
+        self.set_file(
+            path.to_path_buf(),
+            FileReferences {
+                text: None,
+                entries: entries.into(),
+            },
+        );
+    }
+
+    /// Replace the entries of a file, keeping the by-target lookup in step
+    fn set_file(&mut self, path: PathBuf, references: FileReferences) {
+        self.unlink(&path);
+        for entry in references.entries.iter() {
+            self.by_target
+                .entry(entry.target.clone())
+                .or_default()
+                .insert(path.clone());
+        }
+        self.files.insert(path, references);
+    }
+
+    /// Remove a file's entries from the by-target lookup
+    fn unlink(&mut self, path: &Path) {
+        let Some(references) = self.files.get(path) else {
+            return;
+        };
+        for entry in references.entries.iter() {
+            if let Some(files) = self.by_target.get_mut(&entry.target) {
+                files.remove(path);
+                if files.is_empty() {
+                    self.by_target.remove(&entry.target);
+                }
+            }
+        }
+    }
+
+    /// The references made in a file, in document order
+    pub fn entries(&self, path: &Path) -> &[ReferenceEntry] {
+        self.files
+            .get(path)
+            .map_or(&[], |references| &references.entries[..])
+    }
+
+    /// The entries of every file, shared rather than copied
+    pub fn snapshot(&self) -> impl Iterator<Item = (&Path, Arc<[ReferenceEntry]>)> {
+        self.files
+            .iter()
+            .map(|(path, references)| (path.as_path(), references.entries.clone()))
+    }
+
+    /// The reference of a file whose span contains `position`
+    pub fn reference_at(&self, path: &Path, position: Position) -> Option<&ReferenceEntry> {
+        let entries = self.entries(path);
+        let after = entries
+            .partition_point(|entry| position_key(entry.range.start) <= position_key(position));
+        // References are names, which don't span lines
+        entries[..after]
+            .iter()
+            .rev()
+            .take_while(|entry| entry.range.start.line == position.line)
+            .find(|entry| position_key(position) <= position_key(entry.range.end))
+    }
+
+    /// Every reference whose written target is `target`
+    pub fn references_to<'a>(
+        &'a self,
+        target: &str,
+    ) -> impl Iterator<Item = (&'a Path, &'a ReferenceEntry)> + 'a {
+        self.by_target
+            .get_key_value(target)
+            .into_iter()
+            .flat_map(move |(target, paths)| {
+                paths
+                    .iter()
+                    .filter_map(move |path| Some((path.as_path(), self.files.get(path)?)))
+                    .flat_map(move |(path, references)| {
+                        references
+                            .entries
+                            .iter()
+                            .filter(move |entry| Arc::ptr_eq(&entry.target, target))
+                            .map(move |entry| (path, entry))
+                    })
+            })
+    }
+
+    /// Number of distinct owner and target names held by the index
+    pub fn interned_names(&self) -> usize {
+        self.names.len()
+    }
+}
+
+/// The shared allocation of `name`, adding it if it is new
+fn intern(names: &mut HashSet<Arc<str>>, name: &Arc<str>) -> Arc<str> {
+    match names.get(name) {
+        Some(interned) => interned.clone(),
+        None => {
+            names.insert(name.clone());
+            name.clone()
+        }
+    }
+}
+
+fn intern_entries(
+    names: &mut HashSet<Arc<str>>,
+    entries: Vec<ReferenceEntry>,
+) -> Vec<ReferenceEntry> {
+    entries
+        .into_iter()
+        .map(|entry| ReferenceEntry {
+            owner: intern(names, &entry.owner),
+            target: intern(names, &entry.target),
+            range: entry.range,
+        })
+        .collect()
+}
+
+/// All references made by the symbols of a file, in document order
+fn file_entries(index: &SymbolIndex, file: FileId) -> Vec<ReferenceEntry> {
+    let mut entries: Vec<ReferenceEntry> = index
+        .symbols_in_file(file)
+        .into_iter()
+        .flat_map(|sym| {
+            sym.type_refs
+                .iter()
+                .flat_map(|trk| trk.as_refs())
+                .map(|type_ref| ReferenceEntry {
+                    owner: sym.qualified_name.clone(),
+                    target: type_ref.target.clone(),
+                    range: Range::new(
+                        Position::new(type_ref.start_line, type_ref.start_col),
+                        Position::new(type_ref.end_line, type_ref.end_col),
+                    ),
+                })
+        })
+        .collect();
+    entries.sort_by_key(|entry| position_key(entry.range.start));
+    entries
+}
+
+/// Replace only the entries inside the members an edit touched.
+///
+/// Returns `None` when the edit changed the member structure, or the kept
+/// entries don't line up with a fresh extraction, so the caller replaces the
+/// whole file.
+fn update_entries(
+    old: &str,
+    new: &str,
+    entries: &[ReferenceEntry],
+    fresh: &[ReferenceEntry],
+) -> Option<Vec<ReferenceEntry>> {
+    let (old_edit, new_edit) = edited_bytes(old, new);
+    let old_region = affected_region(old, old_edit)?;
+    let new_region = affected_region(new, new_edit)?;
+    // Outside the touched members, both texts must be split the same way
+    if old_region.start != new_region.start
+        || old.len() - old_region.end != new.len() - new_region.end
+    {
+        return None;
+    }
+
+    let old_lines = LineIndex::new(old);
+    let new_lines = LineIndex::new(new);
+    let start = position_at(&old_lines, old, old_region.start);
+    let old_end = position_at(&old_lines, old, old_region.end);
+    let new_end = position_at(&new_lines, new, new_region.end);
+
+    let shift = |position: Position| {
+        if position.line == old_end.line {
+            Position::new(
+                new_end.line,
+                position.character - old_end.character + new_end.character,
+            )
+        } else {
+            Position::new(
+                position.line - old_end.line + new_end.line,
+                position.character,
+            )
+        }
+    };
+
+    let before = entries
+        .iter()
+        .filter(|entry| position_key(entry.range.start) < position_key(start));
+    let after = entries
+        .iter()
+        .filter(|entry| position_key(entry.range.start) >= position_key(old_end))
+        .map(|entry| ReferenceEntry {
+            range: Range::new(shift(entry.range.start), shift(entry.range.end)),
+            ..entry.clone()
+        });
+    let inside = fresh.iter().filter(|entry| {
+        let key = position_key(entry.range.start);
+        position_key(start) <= key && key < position_key(new_end)
+    });
+
+    let updated: Vec<ReferenceEntry> = before
+        .cloned()
+        .chain(inside.cloned())
+        .chain(after)
+        .collect();
+    (updated.len() == fresh.len()).then_some(updated)
+}
+
+/// The differing bytes of the old and new text, after the common prefix and suffix
+fn edited_bytes(old: &str, new: &str) -> (ops::Range<usize>, ops::Range<usize>) {
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    (prefix..old.len() - suffix, prefix..new.len() - suffix)
+}
+
+/// Widen an edited byte range to the innermost members that contain it.
+///
+/// Descends into the body of a member while the edit lies within it; at the
+/// level where it doesn't, every member overlapping the edit is included.
+fn affected_region(text: &str, edit: ops::Range<usize>) -> Option<ops::Range<usize>> {
+    let mut level = 0..text.len();
+    loop {
+        let members = split_members(text, level.clone())?;
+        let overlapping: Vec<&ops::Range<usize>> = members
+            .iter()
+            .filter(|member| member.start <= edit.end && edit.start <= member.end)
+            .collect();
+
+        if let [member] = overlapping.as_slice()
+            && let Some((open, close)) = find_body(text, (*member).clone())
+            && open < edit.start
+            && edit.end <= close
+        {
+            level = open + 1..close;
+            continue;
+        }
+
+        let start = overlapping
+            .iter()
+            .map(|m| m.start)
+            .fold(edit.start, usize::min);
+        let end = overlapping.iter().map(|m| m.end).fold(edit.end, usize::max);
+        return Some(start..end);
+    }
+}
+
+fn position_at(lines: &LineIndex, text: &str, offset: usize) -> Position {
+    let (line, col) = lines.line_col(text, offset);
+    Position::new(line as u32, col as u32)
+}
+
+fn position_key(position: Position) -> (u32, u32) {
+    (position.line, position.character)
+}
@@ -0,0 +1,321 @@
+//! Find-references sharded per file
+//!
+//! On a large workspace, checking every reference of every file against the
+//! element under the cursor keeps the main loop busy for the whole search. A
+//! reference search instead takes the reference index's entries of each file
+//! as a shard. Background workers scan the shards for references whose
+//! written name could name the element or one of its aliases, and only those
+//! candidates come back to the main loop. The main loop checks what each
+//! candidate resolves to in the symbol index, so only real references are
+//! reported. Verified locations are streamed to clients that sent a
+//! `partialResultToken` as each shard finishes. Editing any open document
+//! cancels the search, since its snapshot no longer matches the documents,
+//! and so does the client cancelling the request.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_lsp::lsp_types::notification::Notification;
+use async_lsp::lsp_types::{Location, Position, ProgressToken, Range, Url};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+use super::LspServer;
+use super::helpers::uri_to_path;
+use super::reference_index::ReferenceEntry;
+use super::references::aliases_of;
+
+/// Loaded files from which a reference search is sharded; smaller workspaces
+/// are searched directly
+pub const PARALLEL_SEARCH_MIN_FILES: usize = 64;
+
+/// `$/progress` carrying a batch of references, which `ProgressParamsValue`
+/// can't express
+pub enum ReferencesProgress {}
+
+impl Notification for ReferencesProgress {
+    type Params = ReferencesProgressParams;
+    const METHOD: &'static str = "$/progress";
+}
+
+/// A partial result of `textDocument/references`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferencesProgressParams {
+    /// The request's `partialResultToken`
+    pub token: ProgressToken,
+    /// References to append to the result
+    pub value: Vec<Location>,
+}
+
+/// The references made in one file, as indexed when the search started
+#[derive(Debug, Clone)]
+pub struct ReferenceShard {
+    pub path: PathBuf,
+    pub entries: Arc<[ReferenceEntry]>,
+}
+
+impl ReferenceShard {
+    /// Entries whose written target ends in one of `names`, by simple name or
+    /// the last segment of a qualified name or feature chain
+    pub fn candidates(&self, names: &[Arc<str>]) -> Vec<ReferenceEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| {
+                let last = entry.target.rsplit([':', '.']).next().unwrap_or_default();
+                names.iter().any(|name| name.as_ref() == last)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// A search handed to the background workers
+#[derive(Debug)]
+pub struct ReferenceSearchJob {
+    pub id: u64,
+    /// Simple names of the searched element and of its aliases
+    pub names: Vec<Arc<str>>,
+    /// Largest files first, so no worker is left with a big file at the end
+    pub shards: Vec<ReferenceShard>,
+    cancel: CancellationToken,
+    /// Tokens of the open documents when the search started
+    tokens: Vec<CancellationToken>,
+}
+
+impl ReferenceSearchJob {
+    /// Whether the search was abandoned or a document was edited since it started
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled() || self.tokens.iter().any(CancellationToken::is_cancelled)
+    }
+
+    /// A guard that cancels the search when dropped, held by the request's
+    /// response so that cancelling the request stops the workers
+    pub fn cancel_on_drop(&self) -> DropGuard {
+        self.cancel.clone().drop_guard()
+    }
+}
+
+/// Reference searches in progress
+#[derive(Debug, Default)]
+pub(super) struct ReferenceSearches {
+    searches: HashMap<u64, PendingSearch>,
+    next_id: u64,
+}
+
+#[derive(Debug)]
+struct PendingSearch {
+    /// Qualified names of the searched element and of its aliases
+    targets: Vec<Arc<str>>,
+    /// Verified references, in the order they were found
+    locations: Vec<Location>,
+    /// How many of `locations` have been taken as partial results
+    reported: usize,
+    cancel: CancellationToken,
+    tokens: Vec<CancellationToken>,
+}
+
+impl PendingSearch {
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled() || self.tokens.iter().any(CancellationToken::is_cancelled)
+    }
+}
+
+impl LspServer {
+    /// Whether the workspace is large enough for references to be searched in shards
+    pub fn parallel_reference_search(&self) -> bool {
+        self.analysis_host.files().len() >= PARALLEL_SEARCH_MIN_FILES
+    }
+
+    /// Start searching for references to the element at `position`.
+    ///
+    /// Returns `None` if there is no resolved element there. The declaration,
+    /// if included, is the first location found; the rest come from the
+    /// returned job's shards.
+    pub fn start_reference_search(
+        &mut self,
+        uri: &Url,
+        position: Position,
+        include_declaration: bool,
+    ) -> Option<ReferenceSearchJob> {
+        let _ = self.ensure_workspace_loaded();
+        let path = uri_to_path(uri)?;
+        let analysis = self.analysis_host.analysis();
+        self.reference_index.sync(&analysis);
+        self.symbol_lookup.sync(&analysis);
+        let index = analysis.symbol_index();
+        let file_id = analysis.get_file_id(&path.to_string_lossy())?;
+
+        // A reference under the cursor searches for what it resolves to,
+        // otherwise the symbol declared there is searched for
+        let referenced = index
+            .symbols_in_file(file_id)
+            .into_iter()
+            .flat_map(|sym| &sym.type_refs)
+            .find_map(|trk| trk.part_at(position.line, position.character))
+            .map(|(_, type_ref)| type_ref.resolved_target.clone());
+        let target = match referenced {
+            Some(resolved) => resolved?,
+            None => self
+                .symbol_lookup
+                .at(&path, position)?
+                .qualified_name
+                .clone(),
+        };
+        let symbol = index.lookup_qualified(&target)?;
+
+        let declaration = include_declaration
+            .then(|| {
+                Some(Location {
+                    uri: Url::from_file_path(analysis.get_file_path(symbol.file)?).ok()?,
+                    range: Range::new(
+                        Position::new(symbol.start_line, symbol.start_col),
+                        Position::new(symbol.end_line, symbol.end_col),
+                    ),
+                })
+            })
+            .flatten();
+        let aliases = aliases_of(&self.symbol_lookup, &analysis, &target);
+        let names = std::iter::once(symbol)
+            .chain(aliases.iter().copied())
+            .map(|sym| Arc::from(sym.name.as_ref()))
+            .collect();
+        let targets = std::iter::once(target)
+            .chain(aliases.iter().map(|alias| alias.qualified_name.clone()))
+            .collect();
+
+        let mut shards: Vec<ReferenceShard> = self
+            .reference_index
+            .snapshot()
+            .filter(|(path, entries)| {
+                !entries.is_empty()
+                    && !(self.references_exclude_stdlib() && self.is_stdlib_path(path))
+            })
+            .map(|(path, entries)| ReferenceShard {
+                path: path.to_path_buf(),
+                entries,
+            })
+            .collect();
+        shards.sort_by_key(|shard| std::cmp::Reverse(shard.entries.len()));
+
+        let tokens: Vec<CancellationToken> = self
+            .document_versions
+            .keys()
+            .map(|path| {
+                self.document_cancel_tokens
+                    .entry(path.clone())
+                    .or_default()
+                    .clone()
+            })
+            .collect();
+        let cancel = CancellationToken::new();
+        let searches = &mut self.reference_searches;
+        let id = searches.next_id;
+        searches.next_id += 1;
+        searches.searches.insert(
+            id,
+            PendingSearch {
+                targets,
+                locations: declaration.into_iter().collect(),
+                reported: 0,
+                cancel: cancel.clone(),
+                tokens: tokens.clone(),
+            },
+        );
+
+        Some(ReferenceSearchJob {
+            id,
+            names,
+            shards,
+            cancel,
+            tokens,
+        })
+    }
+
+    /// Keep the candidates from one file that resolve to the searched element.
+    ///
+    /// Returns `false` once the search is cancelled or unknown.
+    pub fn accept_reference_candidates(
+        &mut self,
+        id: u64,
+        path: &Path,
+        candidates: &[ReferenceEntry],
+    ) -> bool {
+        let Some(search) = self.reference_searches.searches.get_mut(&id) else {
+            return false;
+        };
+        if search.is_cancelled() {
+            return false;
+        }
+
+        let analysis = self.analysis_host.analysis();
+        let Some(file_id) = analysis.get_file_id(&path.to_string_lossy()) else {
+            return true;
+        };
+        let Ok(uri) = Url::from_file_path(path) else {
+            return true;
+        };
+
+        let starts: HashSet<Position> = candidates.iter().map(|entry| entry.range.start).collect();
+        for sym in analysis.symbol_index().symbols_in_file(file_id) {
+            for type_ref in sym.type_refs.iter().flat_map(|trk| trk.as_refs()) {
+                let start = Position::new(type_ref.start_line, type_ref.start_col);
+                if starts.contains(&start)
+                    && type_ref
+                        .resolved_target
+                        .as_ref()
+                        .is_some_and(|resolved| search.targets.contains(resolved))
+                {
+                    search.locations.push(Location {
+                        uri: uri.clone(),
+                        range: Range::new(
+                            start,
+                            Position::new(type_ref.end_line, type_ref.end_col),
+                        ),
+                    });
+                }
+            }
+        }
+        true
+    }
+
+    /// References found since the last call, to stream as a partial result
+    pub fn take_unreported_references(&mut self, id: u64) -> Vec<Location> {
+        let Some(search) = self.reference_searches.searches.get_mut(&id) else {
+            return Vec::new();
+        };
+        if search.is_cancelled() {
+            return Vec::new();
+        }
+        let unreported = search.locations[search.reported..].to_vec();
+        search.reported = search.locations.len();
+        unreported
+    }
+
+    /// Stop a search, e.g. because the client cancelled the request
+    pub fn cancel_reference_search(&mut self, id: u64) {
+        if let Some(search) = self.reference_searches.searches.remove(&id) {
+            search.cancel.cancel();
+        }
+    }
+
+    /// End a search once every shard has been scanned, with all the references
+    /// found ordered by file and position.
+    ///
+    /// Returns `None` if the search was cancelled.
+    pub fn finish_reference_search(&mut self, id: u64) -> Option<Vec<Location>> {
+        let search = self.reference_searches.searches.remove(&id)?;
+        if search.is_cancelled() {
+            return None;
+        }
+        let mut locations = search.locations;
+        locations.sort_by(|a, b| {
+            (a.uri.as_str(), a.range.start.line, a.range.start.character).cmp(&(
+                b.uri.as_str(),
+                b.range.start.line,
+                b.range.start.character,
+            ))
+        });
+        Some(locations)
+    }
+}
@@ -1,21 +1,40 @@
 use super::LspServer;
+use super::completion::alias_target;
 use super::helpers::uri_to_path;
+use super::symbol_lookup::{SymbolLookup, symbol};
 use async_lsp::lsp_types::{Location, Position, Range, Url};
+use syster::hir::HirSymbol;
+use syster::ide::Analysis;
 
 impl LspServer {
     /// Find all references to a symbol at the given position
     ///
-    /// Uses the new HIR-based IDE layer for find-references.
+    /// Uses the new HIR-based IDE layer for find-references. Locations are
+    /// collected from every loaded file (open documents, workspace files and
+    /// stdlib), unless the server is configured to exclude stdlib files.
     pub fn get_references(
         &mut self,
         uri: &Url,
         position: Position,
         include_declaration: bool,
+    ) -> Option<Vec<Location>> {
+        let exclude_stdlib = self.references_exclude_stdlib();
+        self.get_references_with_options(uri, position, include_declaration, exclude_stdlib)
+    }
+
+    /// Find all references across the workspace with explicit stdlib filtering
+    pub fn get_references_with_options(
+        &mut self,
+        uri: &Url,
+        position: Position,
+        include_declaration: bool,
+        exclude_stdlib: bool,
     ) -> Option<Vec<Location>> {
         let path = uri_to_path(uri)?;
         let path_str = path.to_string_lossy();
 
         let analysis = self.analysis_host.analysis();
+        self.symbol_lookup.sync(&analysis);
 
         // Get file ID for the new HIR layer
         let file_id = analysis.get_file_id(&path_str)?;
@@ -28,10 +47,30 @@ impl LspServer {
             include_declaration,
         );
 
+        // Usages written with an alias of the symbol refer to it as well
+        let index = analysis.symbol_index();
+        let aliases = analysis
+            .goto_definition(file_id, position.line, position.character)
+            .targets
+            .first()
+            .and_then(|target| {
+                index.symbols_in_file(target.file).into_iter().find(|sym| {
+                    sym.start_line == target.start_line && sym.start_col == target.start_col
+                })
+            })
+            .map(|symbol| aliases_of(&self.symbol_lookup, &analysis, &symbol.qualified_name))
+            .unwrap_or_default();
+        let through_aliases = aliases.iter().flat_map(|alias| {
+            analysis
+                .find_references(alias.file, alias.start_line, alias.start_col, false)
+                .references
+        });
+
         // Convert to LSP Locations
-        let locations: Vec<Location> = result
+        let mut locations: Vec<Location> = result
             .references
             .into_iter()
+            .chain(through_aliases)
             .filter_map(|reference| {
                 let ref_path = analysis.get_file_path(reference.file)?;
                 let ref_uri = Url::from_file_path(ref_path).ok()?;
@@ -50,7 +89,40 @@ impl LspServer {
                 })
             })
             .collect();
+        let mut seen = std::collections::HashSet::new();
+        locations.retain(|location| seen.insert((location.uri.clone(), location.range)));
+
+        if exclude_stdlib {
+            locations.retain(|location| {
+                location
+                    .uri
+                    .to_file_path()
+                    .map(|path| !self.is_stdlib_path(&path))
+                    .unwrap_or(true)
+            });
+        }
 
         Some(locations)
     }
 }
+
+/// Aliases whose target is the symbol named `qualified_name`, looked up by
+/// the target's simple name
+pub(super) fn aliases_of<'a>(
+    lookup: &SymbolLookup,
+    analysis: &'a Analysis<'_>,
+    qualified_name: &str,
+) -> Vec<&'a HirSymbol> {
+    let index = analysis.symbol_index();
+    let simple_name = qualified_name.rsplit("::").next().unwrap_or(qualified_name);
+    lookup
+        .aliases_naming(simple_name)
+        .filter_map(|(path, entry)| {
+            symbol(index, analysis.get_file_id(&path.to_string_lossy())?, entry)
+        })
+        .filter(|alias| {
+            alias_target(index, alias)
+                .is_some_and(|target| target.qualified_name.as_ref() == qualified_name)
+        })
+        .collect()
+}
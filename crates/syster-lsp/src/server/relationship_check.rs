@@ -0,0 +1,188 @@
+//! Relationship keyword checks for usages
+//!
+//! Flags usages whose relationship keyword doesn't fit its target, where the
+//! legal keyword is clear from the target:
+//! - subsetting a feature of the same name that's inherited from a supertype
+//!   (which subsets the usage itself) should redefine it
+//! - subsetting a definition should be typing
+//! - redefining a sibling feature rather than an inherited one should subset it
+//!
+//! Each issue carries the operator's span and its replacement, which the quick
+//! fix applies.
+
+use async_lsp::lsp_types::{Position, Range};
+use syster::base::FileId;
+use syster::hir::{HirSymbol, RefKind, SymbolIndex, SymbolKind, TypeRef};
+
+use super::completion_context::inherited_features;
+use super::tokens::{Token, TokenKind, tokenize};
+use super::type_hierarchy::redefined_feature;
+
+/// Diagnostic code for a relationship keyword that doesn't fit its target
+pub const RELATIONSHIP_KIND_CODE: &str = "relationship-kind";
+
+/// A relationship keyword that should be replaced
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelationshipIssue {
+    /// Span of the relationship operator or keyword
+    pub range: Range,
+    pub message: String,
+    /// The operator as written
+    pub operator: String,
+    /// The operator to use instead
+    pub replacement: String,
+}
+
+/// Check the relationships of every usage declared in `file`
+pub fn check_relationships(
+    index: &SymbolIndex,
+    file: FileId,
+    text: &str,
+) -> Vec<RelationshipIssue> {
+    let tokens = tokenize(text);
+    let mut issues = Vec::new();
+
+    for symbol in index.symbols_in_file(file) {
+        if !symbol.kind.is_usage() {
+            continue;
+        }
+        for type_ref in symbol
+            .type_refs
+            .iter()
+            .filter_map(|trk| trk.as_refs().first().copied())
+        {
+            let Some((range, operator)) = operator_before(&tokens, type_ref) else {
+                continue;
+            };
+            let Some((message, replacement)) =
+                check_relationship(index, symbol, type_ref, &operator)
+            else {
+                continue;
+            };
+            issues.push(RelationshipIssue {
+                range,
+                message,
+                operator,
+                replacement: replacement.to_string(),
+            });
+        }
+    }
+
+    issues.sort_by_key(|issue| (issue.range.start.line, issue.range.start.character));
+    issues
+}
+
+/// The message and replacement operator for a relationship that doesn't fit
+fn check_relationship(
+    index: &SymbolIndex,
+    symbol: &HirSymbol,
+    type_ref: &TypeRef,
+    operator: &str,
+) -> Option<(String, &'static str)> {
+    let owner = symbol
+        .qualified_name
+        .rsplit_once("::")
+        .map_or("", |(owner, _)| owner);
+    let inherited = |name: &str| {
+        inherited_features(index, owner)
+            .into_iter()
+            .find(|feature| feature.name.as_ref() == name)
+    };
+    let target = type_ref
+        .resolved_target
+        .as_deref()
+        .and_then(|target| index.lookup_qualified(target));
+
+    match type_ref.kind {
+        RefKind::Subsets if type_ref.target == symbol.name => {
+            let feature = inherited(&type_ref.target)?;
+            let replacement = if operator == "subsets" {
+                "redefines"
+            } else {
+                ":>>"
+            };
+            Some((
+                format!(
+                    "'{}' subsets itself; use '{replacement}' to redefine the inherited '{}'",
+                    symbol.name, feature.qualified_name
+                ),
+                replacement,
+            ))
+        }
+        RefKind::Subsets => {
+            let target = target?;
+            if !target.kind.is_definition() || target.kind == SymbolKind::Package {
+                return None;
+            }
+            Some((
+                format!(
+                    "'{}' subsets the definition '{}'; use ':' to type it",
+                    symbol.name, target.name
+                ),
+                ":",
+            ))
+        }
+        RefKind::Redefines => {
+            // Redefining an inherited feature is what redefinition is for, even
+            // when a sibling or the usage itself has the same name
+            if redefined_feature(index, symbol, &type_ref.target).is_some() {
+                return None;
+            }
+            let target = target?;
+            let target_owner = target
+                .qualified_name
+                .rsplit_once("::")
+                .map_or("", |(owner, _)| owner);
+            if target.qualified_name == symbol.qualified_name
+                || target_owner != owner
+                || !target.kind.is_usage()
+                || inherited(&target.name).is_some()
+            {
+                return None;
+            }
+            let replacement = if operator == "redefines" {
+                "subsets"
+            } else {
+                ":>"
+            };
+            Some((
+                format!(
+                    "'{}' redefines '{}', which isn't inherited; use '{replacement}' to subset it",
+                    symbol.name, target.name
+                ),
+                replacement,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// The relationship operator or keyword that introduces a reference
+///
+/// Walks back over the earlier references of a comma-separated list.
+pub(super) fn operator_before(tokens: &[Token<'_>], type_ref: &TypeRef) -> Option<(Range, String)> {
+    let start = (type_ref.start_line, type_ref.start_col);
+    let end = tokens.partition_point(|token| (token.start.line, token.start.character) < start);
+
+    let mut i = end;
+    while i > 0 {
+        i -= 1;
+        let token = &tokens[i];
+        match (token.kind, token.text) {
+            (TokenKind::Punct, ">") if i > 0 && tokens[i - 1].text == ":>" => {
+                return Some((range(tokens[i - 1].start, token.end), ":>>".to_string()));
+            }
+            (TokenKind::Punct, ":" | ":>")
+            | (TokenKind::Word, "subsets" | "redefines" | "specializes") => {
+                return Some((range(token.start, token.end), token.text.to_string()));
+            }
+            (TokenKind::Word, _) | (TokenKind::Punct, "," | "::" | ".") => continue,
+            _ => return None,
+        }
+    }
+    None
+}
+
+fn range(start: Position, end: Position) -> Range {
+    Range { start, end }
+}
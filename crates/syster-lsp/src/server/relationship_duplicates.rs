@@ -0,0 +1,150 @@
+//! Duplicate relationship checks
+//!
+//! An element that declares the same typing, specialization, subsetting or
+//! redefinition twice (`part def C :> A, A`) gains nothing from the second
+//! one, and features that show every relationship show it twice. Each
+//! relationship is keyed by its kind and target, the resolved target where
+//! there is one, so `A` and `P::A` naming the same element are duplicates.
+//! Every declaration after the first with the same key is reported, with the
+//! span that deleting it removes.
+
+use async_lsp::lsp_types::{Position, Range};
+use syster::base::FileId;
+use syster::hir::{RefKind, SymbolIndex, TypeRef, TypeRefKind};
+
+use super::relationship_check::operator_before;
+use super::tokens::{Token, TokenKind, tokenize};
+
+/// Diagnostic code for a relationship an element already declares
+pub const DUPLICATE_RELATIONSHIP_CODE: &str = "duplicate-relationship";
+
+/// A relationship declared again by the same element
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateRelationship {
+    /// Span of the repeated target
+    pub range: Range,
+    pub message: String,
+    /// The target as written
+    pub target: String,
+    /// Span to delete: the target with its separating comma or operator
+    pub removal: Range,
+}
+
+/// What makes two relationships of an element the same
+#[derive(PartialEq)]
+struct RelationshipKey<'a> {
+    kind: &'a RefKind,
+    target: &'a str,
+}
+
+impl<'a> RelationshipKey<'a> {
+    /// Relationships other than typing, specialization, subsetting and
+    /// redefinition have no key
+    fn of(type_ref: &'a TypeRef) -> Option<Self> {
+        if !matches!(
+            type_ref.kind,
+            RefKind::TypedBy | RefKind::Specializes | RefKind::Subsets | RefKind::Redefines
+        ) {
+            return None;
+        }
+        Some(Self {
+            kind: &type_ref.kind,
+            target: type_ref
+                .resolved_target
+                .as_deref()
+                .unwrap_or(&*type_ref.target),
+        })
+    }
+}
+
+/// Check the relationships of every element declared in `file`
+pub fn check_duplicate_relationships(
+    index: &SymbolIndex,
+    file: FileId,
+    text: &str,
+) -> Vec<DuplicateRelationship> {
+    let tokens = tokenize(text);
+    let mut duplicates = Vec::new();
+
+    for symbol in index.symbols_in_file(file) {
+        let mut seen: Vec<RelationshipKey<'_>> = Vec::new();
+        // Feature chains have no single target to compare
+        for type_ref in symbol.type_refs.iter().filter_map(|trk| match trk {
+            TypeRefKind::Simple(type_ref) => Some(type_ref),
+            TypeRefKind::Chain(_) => None,
+        }) {
+            let Some(key) = RelationshipKey::of(type_ref) else {
+                continue;
+            };
+            if !seen.contains(&key) {
+                seen.push(key);
+                continue;
+            }
+            let Some(removal) = removal_range(&tokens, type_ref) else {
+                continue;
+            };
+            duplicates.push(DuplicateRelationship {
+                range: Range::new(
+                    Position::new(type_ref.start_line, type_ref.start_col),
+                    Position::new(type_ref.end_line, type_ref.end_col),
+                ),
+                message: format!(
+                    "'{}' already {} '{}'",
+                    symbol.name,
+                    verb(&type_ref.kind),
+                    type_ref.target
+                ),
+                target: type_ref.target.to_string(),
+                removal,
+            });
+        }
+    }
+
+    duplicates
+        .sort_by_key(|duplicate| (duplicate.range.start.line, duplicate.range.start.character));
+    duplicates
+}
+
+fn verb(kind: &RefKind) -> &'static str {
+    match kind {
+        RefKind::TypedBy => "is typed by",
+        RefKind::Specializes => "specializes",
+        RefKind::Subsets => "subsets",
+        _ => "redefines",
+    }
+}
+
+/// The span that removes a repeated target from its declaration
+///
+/// In a list, the target goes with the comma before it. A target with its own
+/// operator goes with the operator, unless more targets follow it, in which
+/// case it goes with the comma after it.
+fn removal_range(tokens: &[Token<'_>], type_ref: &TypeRef) -> Option<Range> {
+    let start = (type_ref.start_line, type_ref.start_col);
+    let end = (type_ref.end_line, type_ref.end_col);
+    let first = tokens.partition_point(|token| (token.start.line, token.start.character) < start);
+    let after = tokens.partition_point(|token| (token.start.line, token.start.character) < end);
+    let ref_end = Position::new(type_ref.end_line, type_ref.end_col);
+
+    let previous = tokens.get(first.checked_sub(1)?)?;
+    if previous.kind == TokenKind::Punct && previous.text == "," {
+        let before = tokens.get(first.checked_sub(2)?)?;
+        return Some(Range::new(before.end, ref_end));
+    }
+
+    let (operator, _) = operator_before(tokens, type_ref)?;
+    if let Some(next) = tokens.get(after)
+        && next.text == ","
+        && let Some(following) = tokens.get(after + 1)
+    {
+        return Some(Range::new(
+            Position::new(type_ref.start_line, type_ref.start_col),
+            following.start,
+        ));
+    }
+    let operator_index = tokens.partition_point(|token| {
+        (token.start.line, token.start.character) < (operator.start.line, operator.start.character)
+    });
+    let before = tokens.get(operator_index.checked_sub(1)?)?;
+    Some(Range::new(before.end, ref_end))
+}
@@ -1,7 +1,15 @@
 use super::LspServer;
+use super::error::LspError;
 use super::helpers::uri_to_path;
+use super::symbol_lookup::{SymbolLookup, symbol};
+use super::type_hierarchy::redefined_feature;
 use async_lsp::lsp_types::{Position, PrepareRenameResponse, Range, TextEdit, Url, WorkspaceEdit};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use syster::base::FileId;
+use syster::hir::{HirSymbol, RefKind, SymbolIndex};
+use syster::ide::Analysis;
 
 impl LspServer {
     /// Prepare rename: validate that the symbol at the position can be renamed
@@ -39,22 +47,96 @@ impl LspServer {
         })
     }
 
+    /// Check that renaming the symbol at the position to `new_name` keeps the model valid
+    ///
+    /// Rejects names that are not valid identifiers and names that already exist
+    /// in the scope that owns the symbol. Positions without a renameable symbol
+    /// are accepted here and left for `get_rename_edits` to report.
+    pub fn validate_rename(
+        &mut self,
+        uri: &Url,
+        position: Position,
+        new_name: &str,
+    ) -> Result<(), LspError> {
+        if !is_valid_name(new_name) {
+            return Err(LspError::InvalidRename(format!(
+                "'{new_name}' is not a valid SysML name"
+            )));
+        }
+
+        let Some(path) = uri_to_path(uri) else {
+            return Ok(());
+        };
+        let path_str = path.to_string_lossy();
+        let analysis = self.analysis_host.analysis();
+        let Some(file_id) = analysis.get_file_id(&path_str) else {
+            return Ok(());
+        };
+
+        let result = analysis.find_references(file_id, position.line, position.character, true);
+        let Some(declaration) = result.references.iter().find(|r| r.is_definition) else {
+            return Ok(());
+        };
+
+        self.symbol_lookup.sync(&analysis);
+        let index = analysis.symbol_index();
+        let Some(symbol) = symbol_at(
+            &self.symbol_lookup,
+            &analysis,
+            declaration.file,
+            Position::new(declaration.start_line, declaration.start_col),
+        ) else {
+            return Ok(());
+        };
+
+        if symbol.name.as_ref() == new_name {
+            return Ok(());
+        }
+
+        let qualified_name = symbol.qualified_name.as_ref();
+        let candidate = match qualified_name.rfind("::") {
+            Some(idx) => format!("{}::{}", &qualified_name[..idx], new_name),
+            None => new_name.to_string(),
+        };
+
+        match index.lookup_qualified(&candidate) {
+            Some(existing) if existing.qualified_name.as_ref() != qualified_name => {
+                Err(LspError::InvalidRename(format!(
+                    "Cannot rename '{}' to '{}': a {} named '{}' already exists in this scope",
+                    symbol.name,
+                    new_name,
+                    existing.kind.display(),
+                    new_name
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Rename a symbol at the given position
     ///
-    /// Finds all references to the symbol and generates a WorkspaceEdit
-    /// to rename them all to the new name.
+    /// Finds all references to the symbol across every loaded file and generates
+    /// a WorkspaceEdit to rename them all to the new name. Returns None when the
+    /// rename would conflict (see `validate_rename`).
+    ///
+    /// With `renameRedefinitions`, features redefining or subsetting the symbol
+    /// under the same name (`part :>> wheel`), in subtypes and their subtypes,
+    /// are renamed along with their references.
     pub fn get_rename_edits(
         &mut self,
         uri: &Url,
         position: Position,
         new_name: &str,
     ) -> Option<WorkspaceEdit> {
+        self.validate_rename(uri, position, new_name).ok()?;
+
         let path = uri_to_path(uri)?;
         let path_str = path.to_string_lossy();
         let (_element_name, _) = self.find_symbol_at_position(&path, position)?;
 
         let analysis = self.analysis_host.analysis();
         let file_id = analysis.get_file_id(&path_str)?;
+        self.symbol_lookup.sync(&analysis);
 
         // Use find_references to get all locations (with include_declaration=true)
         let result = analysis.find_references(
@@ -68,10 +150,30 @@ impl LspServer {
             return None;
         }
 
+        let mut references = result.references;
+        if self.rename_redefinitions
+            && let Some(declaration) = references.iter().find(|r| r.is_definition)
+            && let Some(symbol) = symbol_at(
+                &self.symbol_lookup,
+                &analysis,
+                declaration.file,
+                Position::new(declaration.start_line, declaration.start_col),
+            )
+        {
+            for feature in same_named_redefinitions(&self.symbol_lookup, &analysis, symbol) {
+                references.extend(
+                    analysis
+                        .find_references(feature.file, feature.start_line, feature.start_col, true)
+                        .references,
+                );
+            }
+        }
+
         // Convert to WorkspaceEdit
         let mut edits_by_file: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        let mut seen = HashSet::new();
 
-        for reference in result.references {
+        for reference in references {
             if let Some(ref_path) = analysis.get_file_path(reference.file)
                 && let Ok(file_uri) = Url::from_file_path(ref_path)
             {
@@ -85,6 +187,10 @@ impl LspServer {
                         character: reference.end_col,
                     },
                 };
+                // A redefinition's name is also its reference to the original
+                if !seen.insert((file_uri.clone(), range)) {
+                    continue;
+                }
                 edits_by_file.entry(file_uri).or_default().push(TextEdit {
                     range,
                     new_text: new_name.to_string(),
@@ -99,3 +205,95 @@ impl LspServer {
         })
     }
 }
+
+/// The symbol whose name starts at `start` in `file`
+fn symbol_at<'a>(
+    lookup: &SymbolLookup,
+    analysis: &'a Analysis<'_>,
+    file: FileId,
+    start: Position,
+) -> Option<&'a HirSymbol> {
+    let path = analysis.get_file_path(file)?;
+    lookup
+        .on_line(Path::new(path), start.line)
+        .iter()
+        .filter(|entry| entry.range.start == start)
+        .find_map(|entry| symbol(analysis.symbol_index(), file, entry))
+}
+
+/// Features that redefine or subset `feature`, or one of those, under the
+/// same name
+fn same_named_redefinitions<'a>(
+    lookup: &SymbolLookup,
+    analysis: &'a Analysis<'_>,
+    feature: &HirSymbol,
+) -> Vec<&'a HirSymbol> {
+    let index = analysis.symbol_index();
+    let candidates: Vec<&HirSymbol> = lookup
+        .named(&feature.name)
+        .filter_map(|(path, entry)| {
+            symbol(index, analysis.get_file_id(&path.to_string_lossy())?, entry)
+        })
+        .filter(|sym| sym.kind.is_usage() && sym.name == feature.name)
+        .collect();
+
+    let mut found = Vec::new();
+    let mut visited: HashSet<Arc<str>> = HashSet::from([feature.qualified_name.clone()]);
+    let mut pending = vec![feature.qualified_name.clone()];
+    while let Some(target) = pending.pop() {
+        for &candidate in &candidates {
+            if visited.contains(&candidate.qualified_name) || !refines(index, candidate, &target) {
+                continue;
+            }
+            visited.insert(candidate.qualified_name.clone());
+            pending.push(candidate.qualified_name.clone());
+            found.push(candidate);
+        }
+    }
+    found
+}
+
+/// Whether `feature` redefines or subsets the feature named `target`
+fn refines(index: &SymbolIndex, feature: &HirSymbol, target: &str) -> bool {
+    let scope = feature
+        .qualified_name
+        .rsplit_once("::")
+        .map_or("", |(scope, _)| scope);
+    feature
+        .type_refs
+        .iter()
+        .flat_map(|trk| trk.as_refs())
+        .filter(|type_ref| matches!(type_ref.kind, RefKind::Redefines | RefKind::Subsets))
+        .any(|type_ref| {
+            // A same-named `x :>> x` resolves to the feature itself
+            if let Some(redefined) = redefined_feature(index, feature, &type_ref.target) {
+                return redefined.qualified_name.as_ref() == target;
+            }
+            match &type_ref.resolved_target {
+                Some(resolved) => resolved.as_ref() == target,
+                None => index
+                    .resolver_for_scope(scope)
+                    .resolve(&type_ref.target)
+                    .symbol()
+                    .is_some_and(|sym| sym.qualified_name.as_ref() == target),
+            }
+        })
+}
+
+/// Check that a name is a plain identifier or a quoted unrestricted name
+fn is_valid_name(name: &str) -> bool {
+    if let Some(inner) = name
+        .strip_prefix('\'')
+        .and_then(|rest| rest.strip_suffix('\''))
+    {
+        return !inner.is_empty() && !inner.contains('\'');
+    }
+
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,103 @@
+//! Requirement status decorations
+//!
+//! `syster/requirementDecorations` tells the editor how to mark each
+//! requirement declared in a document: verified, satisfied or still open,
+//! along with whether it is satisfied and verified on their own.
+//! The status comes from the traceability report of `requirement_trace`, so
+//! a requirement definition counts as satisfied or verified when one of its
+//! usages is. The extension only picks the colors.
+
+use super::LspServer;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::Range;
+use async_lsp::lsp_types::request::Request;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Custom LSP request: syster/requirementDecorations
+pub enum RequirementDecorationsRequest {}
+
+impl Request for RequirementDecorationsRequest {
+    type Params = RequirementDecorationsParams;
+    type Result = Option<RequirementDecorations>;
+    const METHOD: &'static str = "syster/requirementDecorations";
+}
+
+/// Request parameters for syster/requirementDecorations
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementDecorationsParams {
+    /// URI of the document to decorate
+    pub uri: String,
+}
+
+/// Decorations for the requirements of a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementDecorations {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Ordered by position
+    pub decorations: Vec<RequirementDecoration>,
+}
+
+/// How far a requirement is traced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequirementStatus {
+    /// Nothing satisfies or verifies it
+    Open,
+    /// Satisfied, but no verification case verifies it
+    Satisfied,
+    /// A verification case verifies it
+    Verified,
+}
+
+/// The status of one requirement, to decorate its line with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementDecoration {
+    pub qualified_name: String,
+    /// Line of the requirement's name
+    pub line: u32,
+    /// Span of the requirement's name
+    pub range: Range,
+    pub status: RequirementStatus,
+    /// Whether something satisfies it
+    pub satisfied: bool,
+    /// Whether a verification case verifies it
+    pub verified: bool,
+}
+
+impl LspServer {
+    /// Status decorations for the requirements declared in a file
+    pub fn requirement_decorations(&mut self, file: &Path) -> RequirementDecorations {
+        let report = self.validate_requirements(Some(file));
+        let decorations = report
+            .requirements
+            .into_iter()
+            .map(|trace| {
+                let satisfied = !report.unsatisfied.contains(&trace.qualified_name);
+                let verified = !report.unverified.contains(&trace.qualified_name);
+                let status = match (satisfied, verified) {
+                    (_, true) => RequirementStatus::Verified,
+                    (true, false) => RequirementStatus::Satisfied,
+                    (false, false) => RequirementStatus::Open,
+                };
+                RequirementDecoration {
+                    line: trace.range.start.line,
+                    range: trace.range,
+                    qualified_name: trace.qualified_name,
+                    status,
+                    satisfied,
+                    verified,
+                }
+            })
+            .collect();
+        RequirementDecorations {
+            schema_version: SCHEMA_VERSION,
+            decorations,
+        }
+    }
+}
@@ -0,0 +1,459 @@
+//! Requirement traceability report
+//!
+//! `syster/validateRequirements` lists the requirement definitions and usages
+//! of the project with what satisfies, verifies and derives from each, and
+//! the requirements nothing satisfies or verifies, for a requirements
+//! dashboard.
+//!
+//! Like parameter directions, the `satisfy` and `verify` statements and the
+//! ends of `#derivation` connections are read from the document text; their
+//! names are resolved from the element enclosing the statement. A `satisfy`
+//! without `by` is satisfied by that element, and a `verify` by the
+//! verification case whose objective it is. A requirement definition also
+//! counts as satisfied or verified when one of its usages is.
+
+use super::LspServer;
+use super::connector_ends::resolve_chain;
+use super::schema::SCHEMA_VERSION;
+use super::tokens::{Token, TokenKind, tokenize, unquote};
+use async_lsp::lsp_types::request::Request;
+use async_lsp::lsp_types::{Position, Range, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use syster::hir::{HirSymbol, RefKind, SymbolIndex, SymbolKind};
+
+/// Custom LSP request: syster/validateRequirements
+pub enum ValidateRequirementsRequest {}
+
+impl Request for ValidateRequirementsRequest {
+    type Params = ValidateRequirementsParams;
+    type Result = RequirementsReport;
+    const METHOD: &'static str = "syster/validateRequirements";
+}
+
+/// Request parameters for syster/validateRequirements
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateRequirementsParams {
+    /// Only report requirements declared in this document; the whole
+    /// workspace if omitted
+    #[serde(default)]
+    pub uri: Option<String>,
+}
+
+/// Traceability of the requirements of the project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementsReport {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Ordered by document and position
+    pub requirements: Vec<RequirementTrace>,
+    /// Qualified names of the requirements nothing satisfies
+    pub unsatisfied: Vec<String>,
+    /// Qualified names of the requirements no verification case verifies
+    pub unverified: Vec<String>,
+}
+
+/// A requirement and the elements tracing to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementTrace {
+    pub qualified_name: String,
+    pub kind: String,
+    pub uri: String,
+    /// Span of the requirement's name
+    pub range: Range,
+    pub satisfied_by: Vec<TraceLink>,
+    pub verified_by: Vec<TraceLink>,
+    /// Requirements this one is derived from
+    pub derived_from: Vec<String>,
+    /// Requirements derived from this one
+    pub derived: Vec<String>,
+}
+
+/// An element satisfying or verifying a requirement, and where it says so
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceLink {
+    /// Qualified name of the element, or its name as written if it
+    /// doesn't resolve
+    pub element: String,
+    pub uri: String,
+    /// Span of the `satisfy` or `verify` statement
+    pub range: Range,
+}
+
+/// Satisfactions, verifications and derivations found in the project
+#[derive(Default)]
+struct Traces {
+    satisfied_by: HashMap<String, Vec<TraceLink>>,
+    verified_by: HashMap<String, Vec<TraceLink>>,
+    derived_from: HashMap<String, Vec<String>>,
+    derived: HashMap<String, Vec<String>>,
+    /// Name positions of the requirements the statements refer to, which
+    /// may be declared as requirement usages of their own
+    statement_names: HashSet<(PathBuf, Position)>,
+}
+
+impl LspServer {
+    /// Traceability of the requirements of the workspace, or of those
+    /// declared in one file
+    pub fn validate_requirements(&mut self, file: Option<&Path>) -> RequirementsReport {
+        let project = self.project_files();
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+
+        let mut paths: Vec<&PathBuf> = project.iter().collect();
+        paths.sort();
+        let mut traces = Traces::default();
+        for path in paths {
+            let (Some(text), Some(file_id)) = (
+                self.document_texts.get(path),
+                analysis.get_file_id(&path.to_string_lossy()),
+            ) else {
+                continue;
+            };
+            let Ok(uri) = Url::from_file_path(path) else {
+                continue;
+            };
+            let symbols = index.symbols_in_file(file_id);
+            let mut scanner = Scanner {
+                index,
+                symbols: &symbols,
+                path,
+                uri: uri.as_str(),
+                traces: &mut traces,
+            };
+            scanner.scan(&tokenize(text));
+        }
+
+        let mut requirements: Vec<(PathBuf, RequirementTrace)> = Vec::new();
+        let mut satisfied: HashSet<String> = traces.satisfied_by.keys().cloned().collect();
+        let mut verified: HashSet<String> = traces.verified_by.keys().cloned().collect();
+        for symbol in index.all_symbols().filter(|sym| is_requirement(sym)) {
+            let Some(path) = analysis.get_file_path(symbol.file).map(PathBuf::from) else {
+                continue;
+            };
+            let start = Position::new(symbol.start_line, symbol.start_col);
+            if !project.contains(&path)
+                || symbol.name.starts_with('<')
+                || traces.statement_names.contains(&(path.clone(), start))
+            {
+                continue;
+            }
+            let qualified_name = symbol.qualified_name.to_string();
+
+            // A satisfied or verified usage covers its definition too
+            if let Some(definition) = typed_by(symbol) {
+                if satisfied.contains(&qualified_name) {
+                    satisfied.insert(definition.clone());
+                }
+                if verified.contains(&qualified_name) {
+                    verified.insert(definition);
+                }
+            }
+
+            if file.is_some_and(|file| path != file) {
+                continue;
+            }
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            let trace = RequirementTrace {
+                satisfied_by: traced(&traces.satisfied_by, &qualified_name),
+                verified_by: traced(&traces.verified_by, &qualified_name),
+                derived_from: traced(&traces.derived_from, &qualified_name),
+                derived: traced(&traces.derived, &qualified_name),
+                qualified_name,
+                kind: symbol.kind.display().to_string(),
+                uri: uri.to_string(),
+                range: Range::new(start, Position::new(symbol.end_line, symbol.end_col)),
+            };
+            requirements.push((path, trace));
+        }
+        requirements.sort_by(|(a, x), (b, y)| (a, x.range.start).cmp(&(b, y.range.start)));
+
+        let requirements: Vec<RequirementTrace> =
+            requirements.into_iter().map(|(_, trace)| trace).collect();
+        let missing = |covered: &HashSet<String>| -> Vec<String> {
+            requirements
+                .iter()
+                .filter(|trace| !covered.contains(&trace.qualified_name))
+                .map(|trace| trace.qualified_name.clone())
+                .collect()
+        };
+        RequirementsReport {
+            schema_version: SCHEMA_VERSION,
+            unsatisfied: missing(&satisfied),
+            unverified: missing(&verified),
+            requirements,
+        }
+    }
+}
+
+fn is_requirement(symbol: &HirSymbol) -> bool {
+    matches!(
+        symbol.kind,
+        SymbolKind::RequirementDef | SymbolKind::RequirementUsage
+    )
+}
+
+fn traced<T: Clone>(traces: &HashMap<String, Vec<T>>, qualified_name: &str) -> Vec<T> {
+    traces.get(qualified_name).cloned().unwrap_or_default()
+}
+
+/// Qualified name of the definition typing a usage
+fn typed_by(symbol: &HirSymbol) -> Option<String> {
+    symbol
+        .type_refs
+        .iter()
+        .flat_map(|trk| trk.as_refs())
+        .find(|type_ref| type_ref.kind == RefKind::TypedBy)
+        .and_then(|type_ref| type_ref.resolved_target.as_deref().map(str::to_string))
+}
+
+/// A `{ ... }` body being scanned
+struct Frame {
+    /// Qualified name of the element owning the body; empty at the top level
+    scope: String,
+    /// Whether the owner is a requirement, such as a verification objective
+    requirement: bool,
+    /// The body of a `#derivation` connection
+    derivation: bool,
+    originals: Vec<String>,
+    derived: Vec<String>,
+}
+
+/// Walks the statements of one document
+struct Scanner<'a, 'b> {
+    index: &'a SymbolIndex,
+    /// Symbols declared in the document
+    symbols: &'b [&'a HirSymbol],
+    path: &'b Path,
+    uri: &'b str,
+    traces: &'b mut Traces,
+}
+
+impl Scanner<'_, '_> {
+    fn scan(&mut self, tokens: &[Token<'_>]) {
+        let mut frames = vec![Frame {
+            scope: String::new(),
+            requirement: false,
+            derivation: false,
+            originals: Vec::new(),
+            derived: Vec::new(),
+        }];
+        let mut statement: Vec<Token<'_>> = Vec::new();
+        for token in tokens {
+            match token.kind {
+                TokenKind::LBrace => {
+                    self.statement(&statement, &mut frames);
+                    let owner = self.declared(&statement);
+                    let scope = match owner {
+                        Some(owner) => owner.qualified_name.to_string(),
+                        None => frames
+                            .last()
+                            .map(|frame| frame.scope.clone())
+                            .unwrap_or_default(),
+                    };
+                    frames.push(Frame {
+                        scope,
+                        requirement: owner.is_some_and(is_requirement),
+                        derivation: statement
+                            .windows(2)
+                            .any(|pair| pair[0].text == "#" && pair[1].text == "derivation"),
+                        originals: Vec::new(),
+                        derived: Vec::new(),
+                    });
+                    statement.clear();
+                }
+                TokenKind::Semi => {
+                    self.statement(&statement, &mut frames);
+                    statement.clear();
+                }
+                TokenKind::RBrace => {
+                    self.statement(&statement, &mut frames);
+                    statement.clear();
+                    if frames.len() > 1
+                        && let Some(frame) = frames.pop()
+                    {
+                        self.derivation(frame);
+                    }
+                }
+                TokenKind::Word | TokenKind::Punct => statement.push(token.clone()),
+            }
+        }
+    }
+
+    /// The symbol a statement declares: the first one named within it
+    fn declared(&self, statement: &[Token<'_>]) -> Option<&HirSymbol> {
+        let (first, last) = (statement.first()?, statement.last()?);
+        self.symbols
+            .iter()
+            .filter(|sym| {
+                let start = Position::new(sym.start_line, sym.start_col);
+                first.start <= start && start < last.end
+            })
+            .min_by_key(|sym| (sym.start_line, sym.start_col))
+            .copied()
+    }
+
+    fn statement(&mut self, statement: &[Token<'_>], frames: &mut [Frame]) {
+        let Some(frame) = frames.last_mut() else {
+            return;
+        };
+        let words: Vec<&str> = statement
+            .iter()
+            .filter(|token| token.kind == TokenKind::Word)
+            .map(|token| token.text)
+            .collect();
+
+        // The ends of a derivation connection: `end #original ::> r;`
+        if frame.derivation {
+            for (idx, pair) in statement.windows(2).enumerate() {
+                if pair[0].text != "#" {
+                    continue;
+                }
+                let ends = match pair[1].text {
+                    "original" => &mut frame.originals,
+                    "derive" => &mut frame.derived,
+                    _ => continue,
+                };
+                if let Some((chain, _, _)) = name_after(statement, idx + 2)
+                    && let Some(target) = resolve_chain(self.index, &frame.scope, &chain)
+                {
+                    ends.push(target.qualified_name.to_string());
+                }
+            }
+            return;
+        }
+
+        // `satisfy` and `verify`, after visibility or `assert`; a negated
+        // satisfaction satisfies nothing
+        let Some(keyword) = statement.iter().position(|token| {
+            token.kind == TokenKind::Word
+                && !matches!(token.text, "public" | "private" | "protected" | "assert")
+        }) else {
+            return;
+        };
+        let verify = match statement[keyword].text {
+            "satisfy" => false,
+            "verify" => true,
+            _ => return,
+        };
+        if words.contains(&"not") {
+            return;
+        }
+        let mut next = keyword + 1;
+        if statement
+            .get(next)
+            .is_some_and(|token| token.text == "requirement")
+        {
+            next += 1;
+        }
+        let Some((chain, name_start, next)) = name_after(statement, next) else {
+            return;
+        };
+        let scope = frame.scope.clone();
+        let Some(target) = resolve_chain(self.index, &scope, &chain) else {
+            return;
+        };
+        self.traces
+            .statement_names
+            .insert((self.path.to_path_buf(), name_start));
+
+        let element = if verify {
+            // The verification case owning the objective
+            frames
+                .iter()
+                .rev()
+                .find(|frame| !frame.requirement)
+                .map(|frame| frame.scope.clone())
+                .unwrap_or_default()
+        } else {
+            match statement.get(next) {
+                Some(token) if token.text == "by" => {
+                    let Some((subject, _, _)) = name_after(statement, next + 1) else {
+                        return;
+                    };
+                    resolve_chain(self.index, &scope, &subject)
+                        .map(|sym| sym.qualified_name.to_string())
+                        .unwrap_or_else(|| subject.join("."))
+                }
+                _ => scope,
+            }
+        };
+        if element.is_empty() {
+            return;
+        }
+
+        let (Some(first), Some(last)) = (statement.first(), statement.last()) else {
+            return;
+        };
+        let links = if verify {
+            &mut self.traces.verified_by
+        } else {
+            &mut self.traces.satisfied_by
+        };
+        links
+            .entry(target.qualified_name.to_string())
+            .or_default()
+            .push(TraceLink {
+                element,
+                uri: self.uri.to_string(),
+                range: Range::new(first.start, last.end),
+            });
+    }
+
+    /// Record the derivations of a closed `#derivation` connection body
+    fn derivation(&mut self, frame: Frame) {
+        if !frame.derivation {
+            return;
+        }
+        for original in &frame.originals {
+            for derived in &frame.derived {
+                self.traces
+                    .derived
+                    .entry(original.clone())
+                    .or_default()
+                    .push(derived.clone());
+                self.traces
+                    .derived_from
+                    .entry(derived.clone())
+                    .or_default()
+                    .push(original.clone());
+            }
+        }
+    }
+}
+
+/// The feature chain named from `start` on, skipping punctuation like `::>`
+/// before it: its segments, where it starts, and the index after it
+fn name_after(tokens: &[Token<'_>], start: usize) -> Option<(Vec<String>, Position, usize)> {
+    let first = start
+        + tokens
+            .get(start..)?
+            .iter()
+            .position(|token| token.kind == TokenKind::Word)?;
+    let mut chain = vec![unquote(tokens[first].text).to_string()];
+    let mut idx = first + 1;
+    while let (Some(separator), Some(word)) = (tokens.get(idx), tokens.get(idx + 1)) {
+        if word.kind != TokenKind::Word {
+            break;
+        }
+        match separator.text {
+            "::" => {
+                let last = chain.last_mut()?;
+                last.push_str("::");
+                last.push_str(unquote(word.text));
+            }
+            "." => chain.push(unquote(word.text).to_string()),
+            _ => break,
+        }
+        idx += 2;
+    }
+    Some((chain, tokens[first].start, idx))
+}
@@ -0,0 +1,132 @@
+//! Bulk span resolution request handler for LSP.
+//!
+//! Model-level APIs such as `syster/getDiagram` identify elements by qualified
+//! name, and syntax-level spans are byte offsets into a file. Clients that want
+//! to navigate to many such elements can resolve them all to LSP locations in
+//! one `syster/resolveSpans` request instead of one request per element.
+
+use super::LspServer;
+use super::document_links::byte_offset_to_position;
+use super::helpers::uri_to_path;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::request::Request;
+use async_lsp::lsp_types::{Location, Position, Range, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Custom LSP request: syster/resolveSpans
+pub enum ResolveSpansRequest {}
+
+impl Request for ResolveSpansRequest {
+    type Params = ResolveSpansParams;
+    type Result = ResolveSpansResult;
+    const METHOD: &'static str = "syster/resolveSpans";
+}
+
+/// Request parameters for syster/resolveSpans
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveSpansParams {
+    pub spans: Vec<ElementSpan>,
+}
+
+/// An element, or a byte span within a file, to resolve.
+///
+/// With only `qualifiedName`, the element's whole declaration is resolved.
+/// With `start` and `end`, the byte span is resolved within `uri`, or within
+/// the file declaring `qualifiedName` when no URI is given.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementSpan {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    /// Byte offset where the span starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    /// Byte offset just past the end of the span
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+}
+
+/// Response for syster/resolveSpans
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveSpansResult {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// One entry per requested span, in request order; `null` where the
+    /// element or file is unknown or the span is out of bounds
+    pub locations: Vec<Option<Location>>,
+}
+
+/// Range of a byte span of `text`; None if it is out of bounds or splits a
+/// character
+fn span_range(text: &str, start: usize, end: usize) -> Option<Range> {
+    (start <= end && text.is_char_boundary(start) && text.is_char_boundary(end)).then(|| {
+        Range::new(
+            byte_offset_to_position(text, start),
+            byte_offset_to_position(text, end),
+        )
+    })
+}
+
+impl LspServer {
+    /// Resolve element names and byte spans to LSP locations
+    pub fn resolve_spans(&mut self, params: &ResolveSpansParams) -> ResolveSpansResult {
+        // Files are read once, however many spans they hold
+        let mut texts: HashMap<PathBuf, Option<String>> = HashMap::new();
+        let locations = params
+            .spans
+            .iter()
+            .map(|span| {
+                let element = match &span.qualified_name {
+                    Some(name) => Some(self.element_location(name)?),
+                    None => None,
+                };
+                let (Some(start), Some(end)) = (span.start, span.end) else {
+                    return element;
+                };
+                let uri = match &span.uri {
+                    Some(uri) => Url::parse(uri).ok()?,
+                    None => element?.uri,
+                };
+                let path = uri_to_path(&uri)?;
+                let text = texts
+                    .entry(path)
+                    .or_insert_with_key(|path| self.span_text(path));
+                let range = span_range(text.as_ref()?, start, end)?;
+                Some(Location { uri, range })
+            })
+            .collect();
+        ResolveSpansResult {
+            schema_version: SCHEMA_VERSION,
+            locations,
+        }
+    }
+
+    /// Location of an element's declaration
+    fn element_location(&mut self, qualified_name: &str) -> Option<Location> {
+        let analysis = self.analysis_host.analysis();
+        let symbol = analysis.symbol_index().lookup_qualified(qualified_name)?;
+        let path = analysis.get_file_path(symbol.file)?;
+        Some(Location {
+            uri: Url::from_file_path(path).ok()?,
+            range: Range {
+                start: Position::new(symbol.start_line, symbol.start_col),
+                end: Position::new(symbol.end_line, symbol.end_col),
+            },
+        })
+    }
+
+    /// Text of a loaded document, or of the file on disk
+    fn span_text(&self, path: &Path) -> Option<String> {
+        match self.document_texts.get(path) {
+            Some(text) => Some(text.clone()),
+            None => self.fs.read_to_string(path).ok(),
+        }
+    }
+}
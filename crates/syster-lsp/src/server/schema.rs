@@ -0,0 +1,12 @@
+//! Versioning of the custom request payloads
+//!
+//! Every custom request's payload - diagram data, model exports, type
+//! information, health checks and the rest - carries a `schemaVersion`, so
+//! the extension can tell a payload it doesn't understand from a broken one. Adding an optional
+//! field keeps the version; renaming or removing a field, or changing its
+//! type, bumps it. Payloads from servers that predate versioning deserialize
+//! with version 0. The schema tests pin the field names of every payload, so
+//! a rename can't slip through without a version bump.
+
+/// Version of the custom request payloads' schema
+pub const SCHEMA_VERSION: u32 = 1;
@@ -0,0 +1,231 @@
+//! Name conflict checks within a scope
+//!
+//! The symbol index accepts any number of elements with the same qualified
+//! name, so this reports:
+//! - elements that share a name with an earlier element in the same scope
+//! - redefinitions whose target isn't a feature of any supertype of the owner
+//! - type references that the scope rules can't resolve, but that resolved
+//!   anyway through the global name search, so they break as soon as that
+//!   search finds a second match
+//!
+//! Redefinitions of siblings are left to the relationship check, which offers
+//! a quick fix, and unresolved targets to the semantic checker.
+
+use std::collections::HashMap;
+
+use async_lsp::lsp_types::{Position, Range};
+use syster::base::FileId;
+use syster::hir::{HirSymbol, RefKind, SymbolIndex, SymbolKind};
+
+use super::completion_context::inherited_features;
+use super::type_hierarchy::redefined_feature;
+
+/// Diagnostic code for an element whose name is already taken in its scope
+pub const DUPLICATE_NAME_CODE: &str = "duplicate-name";
+
+/// Diagnostic code for a redefinition of a feature that isn't inherited
+pub const REDEFINITION_TARGET_CODE: &str = "redefinition-target";
+
+/// Diagnostic code for a type reference resolved only by the global fallback
+pub const FALLBACK_RESOLUTION_CODE: &str = "fallback-resolution";
+
+/// A name conflict found in a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeIssue {
+    pub range: Range,
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Check the scopes of every element declared in `file`
+pub fn check_scopes(index: &SymbolIndex, file: FileId) -> Vec<ScopeIssue> {
+    let mut symbols: Vec<&HirSymbol> = index
+        .symbols_in_file(file)
+        .into_iter()
+        .filter(|sym| {
+            !sym.name.is_empty()
+                && !matches!(
+                    sym.kind,
+                    SymbolKind::Import | SymbolKind::Comment | SymbolKind::Dependency
+                )
+        })
+        .collect();
+    symbols.sort_by_key(|sym| (sym.start_line, sym.start_col));
+
+    let mut issues = duplicate_names(&symbols);
+    issues.extend(
+        symbols
+            .iter()
+            .filter(|sym| sym.kind.is_usage())
+            .flat_map(|sym| redefinition_targets(index, sym)),
+    );
+
+    issues.sort_by_key(|issue| (issue.range.start.line, issue.range.start.character));
+    issues
+}
+
+/// A type reference resolved by the global fallback instead of the scope rules
+#[derive(Debug, Clone, PartialEq)]
+pub struct FallbackResolution {
+    pub range: Range,
+    /// The reference as written
+    pub name: String,
+    /// Qualified name of the definition it resolved to
+    pub target: String,
+}
+
+/// Type references made in `file` that only the global fallback resolves
+pub fn fallback_resolutions(index: &SymbolIndex, file: FileId) -> Vec<FallbackResolution> {
+    let mut resolutions = Vec::new();
+
+    for symbol in index
+        .symbols_in_file(file)
+        .into_iter()
+        .filter(|sym| sym.kind != SymbolKind::Import)
+    {
+        let resolver = index.resolver_for_scope(&symbol.qualified_name);
+        for type_ref in symbol
+            .type_refs
+            .iter()
+            .flat_map(|trk| trk.as_refs())
+            .filter(|type_ref| matches!(type_ref.kind, RefKind::TypedBy | RefKind::Specializes))
+        {
+            let Some(target) = type_ref
+                .resolved_target
+                .as_deref()
+                .and_then(|target| index.lookup_qualified(target))
+            else {
+                continue;
+            };
+            // Fully qualified references are explicit already
+            if !target.kind.is_definition()
+                || target.qualified_name.as_ref() == type_ref.target.as_ref()
+                || resolver.resolve(&type_ref.target).symbol().is_some()
+            {
+                continue;
+            }
+            resolutions.push(FallbackResolution {
+                range: Range {
+                    start: Position::new(type_ref.start_line, type_ref.start_col),
+                    end: Position::new(type_ref.end_line, type_ref.end_col),
+                },
+                name: type_ref.target.to_string(),
+                target: target.qualified_name.to_string(),
+            });
+        }
+    }
+
+    resolutions.sort_by_key(|resolution| {
+        (
+            resolution.range.start.line,
+            resolution.range.start.character,
+        )
+    });
+    resolutions
+}
+
+/// Elements named like an earlier element of the same scope
+fn duplicate_names(symbols: &[&HirSymbol]) -> Vec<ScopeIssue> {
+    let mut first: HashMap<&str, &HirSymbol> = HashMap::new();
+    let mut issues = Vec::new();
+
+    for &symbol in symbols {
+        let Some(earlier) = first.get(symbol.qualified_name.as_ref()).copied() else {
+            first.insert(symbol.qualified_name.as_ref(), symbol);
+            continue;
+        };
+        let scope = owner_of(symbol);
+        let message = if scope.is_empty() {
+            format!(
+                "'{}' is already declared at line {}",
+                symbol.name,
+                earlier.start_line + 1
+            )
+        } else {
+            format!(
+                "'{}' is already declared in '{scope}' at line {}",
+                symbol.name,
+                earlier.start_line + 1
+            )
+        };
+        issues.push(ScopeIssue {
+            range: symbol_range(symbol),
+            code: DUPLICATE_NAME_CODE,
+            message,
+        });
+    }
+
+    issues
+}
+
+/// Redefinitions by `symbol` whose targets its owner doesn't inherit
+fn redefinition_targets(index: &SymbolIndex, symbol: &HirSymbol) -> Vec<ScopeIssue> {
+    let owner = owner_of(symbol);
+    let mut inherited = None;
+    let mut issues = Vec::new();
+
+    for type_ref in symbol
+        .type_refs
+        .iter()
+        .filter_map(|trk| trk.as_refs().first().copied())
+        .filter(|type_ref| matches!(type_ref.kind, RefKind::Redefines))
+    {
+        // A same-named redefinition resolves to the redefining feature itself,
+        // so the target is looked up in the owner's supertypes
+        let target = match redefined_feature(index, symbol, &type_ref.target) {
+            Some(target) => {
+                let inherited = inherited.get_or_insert_with(|| inherited_features(index, owner));
+                if !target.kind.is_usage()
+                    || owner_of(target) == owner
+                    || inherited
+                        .iter()
+                        .any(|feature| feature.qualified_name == target.qualified_name)
+                {
+                    continue;
+                }
+                target.qualified_name.to_string()
+            }
+            None => match type_ref
+                .resolved_target
+                .as_deref()
+                .and_then(|target| index.lookup_qualified(target))
+            {
+                Some(target) if target.qualified_name == symbol.qualified_name => {
+                    type_ref.target.to_string()
+                }
+                Some(target) if target.kind.is_usage() && owner_of(target) != owner => {
+                    target.qualified_name.to_string()
+                }
+                // Unresolved, or a sibling
+                _ => continue,
+            },
+        };
+        issues.push(ScopeIssue {
+            range: Range {
+                start: Position::new(type_ref.start_line, type_ref.start_col),
+                end: Position::new(type_ref.end_line, type_ref.end_col),
+            },
+            code: REDEFINITION_TARGET_CODE,
+            message: format!(
+                "'{}' redefines '{target}', which isn't a feature of any supertype of '{owner}'",
+                symbol.name
+            ),
+        });
+    }
+
+    issues
+}
+
+fn owner_of(symbol: &HirSymbol) -> &str {
+    symbol
+        .qualified_name
+        .rsplit_once("::")
+        .map_or("", |(owner, _)| owner)
+}
+
+fn symbol_range(symbol: &HirSymbol) -> Range {
+    Range {
+        start: Position::new(symbol.start_line, symbol.start_col),
+        end: Position::new(symbol.end_line, symbol.end_col),
+    }
+}
@@ -1,19 +1,118 @@
 use crate::server::core::LspServer;
 use crate::server::helpers::{char_offset_to_utf16, uri_to_path};
+use crate::server::hover::FeatureModifiers;
 use async_lsp::lsp_types::{
-    SemanticToken as LspSemanticToken, SemanticTokenType, SemanticTokens, SemanticTokensLegend,
-    SemanticTokensResult, Url,
+    Position, Range, SemanticToken as LspSemanticToken, SemanticTokenModifier, SemanticTokenType,
+    SemanticTokens, SemanticTokensDelta, SemanticTokensEdit, SemanticTokensFullDeltaResult,
+    SemanticTokensLegend, SemanticTokensRangeResult, SemanticTokensResult, Url,
 };
-use syster::ide::SemanticToken;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use syster::base::FileId;
+use syster::hir::{RefKind, SymbolKind};
+use syster::ide::{Analysis, SemanticToken};
 use tracing::debug;
 
+/// Modifier bits, in the order of the legend's `token_modifiers`
+pub const DECLARATION: u32 = 1 << 0;
+pub const READONLY: u32 = 1 << 1;
+pub const DEFAULT_LIBRARY: u32 = 1 << 2;
+pub const MODIFICATION: u32 = 1 << 3;
+
+/// Modifier bits of the tokens starting at each (line, column)
+type TokenModifiers = HashMap<(u32, u32), u32>;
+
+/// The tokens last sent for each document, so the next request can be
+/// answered with a delta against them
+#[derive(Debug, Default)]
+pub struct SemanticTokensCache {
+    results: HashMap<PathBuf, (String, Vec<LspSemanticToken>)>,
+    next_id: u64,
+}
+
+impl SemanticTokensCache {
+    /// Remember the tokens sent for a document, returning their result id
+    fn store(&mut self, path: PathBuf, data: Vec<LspSemanticToken>) -> String {
+        self.next_id += 1;
+        let result_id = self.next_id.to_string();
+        self.results.insert(path, (result_id.clone(), data));
+        result_id
+    }
+
+    /// The tokens sent for a document, if they are the result the client has
+    fn get(&self, path: &Path, result_id: &str) -> Option<&[LspSemanticToken]> {
+        self.results
+            .get(path)
+            .filter(|(id, _)| id == result_id)
+            .map(|(_, data)| data.as_slice())
+    }
+
+    pub(super) fn remove(&mut self, path: &Path) {
+        self.results.remove(path);
+    }
+}
+
 impl LspServer {
     /// Get semantic tokens for a document
     pub fn get_semantic_tokens(&mut self, uri: &Url) -> Option<SemanticTokensResult> {
         let path = uri_to_path(uri)?;
+        let data = self.semantic_token_data(&path, None)?;
+        let result_id = self.semantic_tokens_cache.store(path, data.clone());
+
+        Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: Some(result_id),
+            data,
+        }))
+    }
+
+    /// Get the changes to a document's semantic tokens since a previous result.
+    ///
+    /// Falls back to the full token list when the previous result is unknown.
+    pub fn get_semantic_tokens_delta(
+        &mut self,
+        uri: &Url,
+        previous_result_id: &str,
+    ) -> Option<SemanticTokensFullDeltaResult> {
+        let path = uri_to_path(uri)?;
+        let data = self.semantic_token_data(&path, None)?;
+        let edits = self
+            .semantic_tokens_cache
+            .get(&path, previous_result_id)
+            .map(|previous| token_edits(previous, &data));
+        let result_id = Some(self.semantic_tokens_cache.store(path, data.clone()));
+
+        Some(match edits {
+            Some(edits) => {
+                SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta { result_id, edits })
+            }
+            None => SemanticTokensFullDeltaResult::Tokens(SemanticTokens { result_id, data }),
+        })
+    }
+
+    /// Get semantic tokens starting inside a range of a document
+    pub fn get_semantic_tokens_range(
+        &mut self,
+        uri: &Url,
+        range: Range,
+    ) -> Option<SemanticTokensRangeResult> {
+        let path = uri_to_path(uri)?;
+        let data = self.semantic_token_data(&path, Some(range))?;
+
+        Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+            result_id: None,
+            data,
+        }))
+    }
+
+    /// Encoded tokens of a document, optionally only those starting in a range
+    fn semantic_token_data(
+        &mut self,
+        path: &Path,
+        range: Option<Range>,
+    ) -> Option<Vec<LspSemanticToken>> {
         debug!("semantic_tokens: path from URI = {:?}", path);
 
-        let document_text = self.document_texts.get(&path);
+        let document_text = self.document_texts.get(path);
         if document_text.is_none() {
             debug!(
                 "semantic_tokens: document_text NOT FOUND for path {:?}",
@@ -40,12 +139,21 @@ impl LspServer {
 
         debug!("semantic_tokens: got {} tokens", tokens.len());
 
-        let lsp_tokens = encode_tokens_as_deltas(&tokens, &lines);
+        let (mut modifiers, references) = collect_modifiers(&analysis, file_id, document_text);
 
-        Some(SemanticTokensResult::Tokens(SemanticTokens {
-            result_id: None,
-            data: lsp_tokens,
-        }))
+        // References into the standard library are styled as library names
+        let library_files: HashSet<FileId> = references
+            .iter()
+            .filter(|(_, (_, path))| self.is_stdlib_path(path))
+            .map(|(_, (file, _))| *file)
+            .collect();
+        for (position, (file, _)) in references {
+            if library_files.contains(&file) {
+                *modifiers.entry(position).or_default() |= DEFAULT_LIBRARY;
+            }
+        }
+
+        Some(encode_tokens_as_deltas(&tokens, &lines, &modifiers, range))
     }
 
     /// Get the semantic tokens legend (token types supported)
@@ -58,13 +166,69 @@ impl LspServer {
                 SemanticTokenType::PROPERTY,
                 SemanticTokenType::KEYWORD,
             ],
-            token_modifiers: vec![],
+            token_modifiers: vec![
+                SemanticTokenModifier::DECLARATION,
+                SemanticTokenModifier::READONLY,
+                SemanticTokenModifier::DEFAULT_LIBRARY,
+                SemanticTokenModifier::MODIFICATION,
+            ],
+        }
+    }
+}
+
+/// Modifiers of declared names and redefinitions in a file, and the file
+/// each resolved reference points into
+fn collect_modifiers(
+    analysis: &Analysis<'_>,
+    file_id: FileId,
+    text: &str,
+) -> (TokenModifiers, Vec<((u32, u32), (FileId, PathBuf))>) {
+    let index = analysis.symbol_index();
+    let mut modifiers = TokenModifiers::new();
+    let mut references = Vec::new();
+
+    for sym in index.symbols_in_file(file_id) {
+        if matches!(sym.kind, SymbolKind::Import | SymbolKind::Comment) {
+            continue;
         }
+
+        let mut declaration = DECLARATION;
+        if FeatureModifiers::of(text, sym).is_some_and(|found| found.is_readonly) {
+            declaration |= READONLY;
+        }
+
+        for type_ref in sym.type_refs.iter().flat_map(|trk| trk.as_refs()) {
+            let position = (type_ref.start_line, type_ref.start_col);
+            if type_ref.kind == RefKind::Redefines {
+                declaration |= MODIFICATION;
+                *modifiers.entry(position).or_default() |= MODIFICATION;
+            }
+            if let Some(target) = type_ref
+                .resolved_target
+                .as_deref()
+                .and_then(|target| index.lookup_qualified(target))
+                && let Some(path) = analysis.get_file_path(target.file)
+            {
+                references.push((position, (target.file, Path::new(path).to_path_buf())));
+            }
+        }
+
+        *modifiers
+            .entry((sym.start_line, sym.start_col))
+            .or_default() |= declaration;
     }
+
+    (modifiers, references)
 }
 
-/// Convert semantic tokens to LSP delta-encoded format with UTF-16 positions
-fn encode_tokens_as_deltas(tokens: &[SemanticToken], lines: &[&str]) -> Vec<LspSemanticToken> {
+/// Convert semantic tokens to LSP delta-encoded format with UTF-16 positions,
+/// skipping tokens that don't start inside `range`
+fn encode_tokens_as_deltas(
+    tokens: &[SemanticToken],
+    lines: &[&str],
+    modifiers: &TokenModifiers,
+    range: Option<Range>,
+) -> Vec<LspSemanticToken> {
     let mut result = Vec::with_capacity(tokens.len());
     let mut prev_line = 0u32;
     let mut prev_col_utf16 = 0u32;
@@ -75,6 +239,13 @@ fn encode_tokens_as_deltas(tokens: &[SemanticToken], lines: &[&str]) -> Vec<LspS
         let end_utf16 = char_offset_to_utf16(line_text, (token.col + token.length) as usize);
         let len_utf16 = end_utf16 - col_utf16;
 
+        if let Some(range) = range {
+            let start = Position::new(token.line, col_utf16);
+            if start < range.start || start >= range.end {
+                continue;
+            }
+        }
+
         let delta_line = token.line - prev_line;
         let delta_start = if delta_line == 0 {
             col_utf16 - prev_col_utf16
@@ -87,7 +258,10 @@ fn encode_tokens_as_deltas(tokens: &[SemanticToken], lines: &[&str]) -> Vec<LspS
             delta_start,
             length: len_utf16,
             token_type: token.token_type as u32,
-            token_modifiers_bitset: 0,
+            token_modifiers_bitset: modifiers
+                .get(&(token.line, token.col))
+                .copied()
+                .unwrap_or(0),
         });
 
         prev_line = token.line;
@@ -96,3 +270,33 @@ fn encode_tokens_as_deltas(tokens: &[SemanticToken], lines: &[&str]) -> Vec<LspS
 
     result
 }
+
+/// The edit turning the previous tokens into the new ones: everything between
+/// their common prefix and suffix is replaced. Positions count integers of the
+/// encoded array, five per token.
+fn token_edits(
+    previous: &[LspSemanticToken],
+    data: &[LspSemanticToken],
+) -> Vec<SemanticTokensEdit> {
+    let prefix = previous
+        .iter()
+        .zip(data)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(data[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    if prefix + suffix == previous.len() && previous.len() == data.len() {
+        return Vec::new();
+    }
+
+    let inserted = &data[prefix..data.len() - suffix];
+    vec![SemanticTokensEdit {
+        start: (prefix * 5) as u32,
+        delete_count: ((previous.len() - prefix - suffix) * 5) as u32,
+        data: (!inserted.is_empty()).then(|| inserted.to_vec()),
+    }]
+}
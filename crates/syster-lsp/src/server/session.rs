@@ -0,0 +1,264 @@
+//! Sessions sharing one workspace
+//!
+//! Lets several consumers - an editor connection and a webview helper, say -
+//! share one `LspServer`, so the stdlib and workspace are loaded only once.
+//! Each session keeps its own overlay of the documents it has open; the most
+//! recently used overlay of a document is the one analysed, and a session's
+//! requests switch its documents to its own overlay first. When a session
+//! closes a document (or is dropped), the next most recent overlay takes over,
+//! or the file on disk once no session has it open.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use super::LspServer;
+use super::error::LspError;
+use super::helpers::apply_text_edit;
+use async_lsp::lsp_types::{TextDocumentContentChangeEvent, Url};
+
+/// Identifies a session connected to a shared workspace
+pub type SessionId = u32;
+
+/// Open-document text per session, most recently edited last
+#[derive(Debug, Default)]
+pub struct DocumentOverlays {
+    overlays: HashMap<PathBuf, Vec<(SessionId, String)>>,
+}
+
+impl DocumentOverlays {
+    /// Set a session's text for a document and make it the active overlay
+    fn set(&mut self, session: SessionId, path: &Path, text: String) {
+        let overlays = self.overlays.entry(path.to_path_buf()).or_default();
+        overlays.retain(|(id, _)| *id != session);
+        overlays.push((session, text));
+    }
+
+    /// A session's text for a document
+    fn get(&self, session: SessionId, path: &Path) -> Option<&str> {
+        self.overlays
+            .get(path)?
+            .iter()
+            .find(|(id, _)| *id == session)
+            .map(|(_, text)| text.as_str())
+    }
+
+    /// Make a session's overlays the active ones, returning the documents
+    /// whose active text changed
+    fn activate(&mut self, session: SessionId) -> Vec<(PathBuf, String)> {
+        let mut activated = Vec::new();
+        for (path, overlays) in &mut self.overlays {
+            let Some(idx) = overlays.iter().position(|(id, _)| *id == session) else {
+                continue;
+            };
+            if idx + 1 == overlays.len() {
+                continue;
+            }
+            let overlay = overlays.remove(idx);
+            activated.push((path.clone(), overlay.1.clone()));
+            overlays.push(overlay);
+        }
+        activated
+    }
+
+    /// Remove a session's overlay, returning the text now active (if any
+    /// session still has the document open)
+    fn remove(&mut self, session: SessionId, path: &Path) -> Option<Option<String>> {
+        let overlays = self.overlays.get_mut(path)?;
+        let before = overlays.len();
+        overlays.retain(|(id, _)| *id != session);
+        if overlays.len() == before {
+            return None;
+        }
+        let active = overlays.last().map(|(_, text)| text.clone());
+        if overlays.is_empty() {
+            self.overlays.remove(path);
+        }
+        Some(active)
+    }
+
+    /// Documents a session has open
+    fn documents_of(&self, session: SessionId) -> Vec<PathBuf> {
+        self.overlays
+            .iter()
+            .filter(|(_, overlays)| overlays.iter().any(|(id, _)| *id == session))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Sessions that have a document open
+    pub fn sessions_for(&self, path: &Path) -> Vec<SessionId> {
+        self.overlays
+            .get(path)
+            .map(|overlays| overlays.iter().map(|(id, _)| *id).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl LspServer {
+    /// Open a document in a session's overlay and analyse it
+    pub fn open_session_document(
+        &mut self,
+        session: SessionId,
+        uri: &Url,
+        text: &str,
+    ) -> Result<(), LspError> {
+        let path = uri
+            .to_file_path()
+            .map_err(|_| LspError::InvalidUri(uri.clone()))?;
+        self.document_overlays.set(session, &path, text.to_string());
+        self.open_document(uri, text)
+    }
+
+    /// Apply a change to a session's overlay and analyse the result
+    pub fn change_session_document(
+        &mut self,
+        session: SessionId,
+        uri: &Url,
+        change: &TextDocumentContentChangeEvent,
+    ) -> Result<(), LspError> {
+        let path = uri
+            .to_file_path()
+            .map_err(|_| LspError::InvalidUri(uri.clone()))?;
+        let current = self
+            .document_overlays
+            .get(session, &path)
+            .ok_or_else(|| LspError::FileNotOpen(uri.clone()))?;
+        let text = match &change.range {
+            Some(range) => apply_text_edit(current, range, &change.text)?,
+            None => change.text.clone(),
+        };
+        self.document_overlays.set(session, &path, text.clone());
+        self.open_document(uri, &text)
+    }
+
+    /// Close a session's overlay, falling back to another session's text or the file on disk
+    pub fn close_session_document(
+        &mut self,
+        session: SessionId,
+        uri: &Url,
+    ) -> Result<(), LspError> {
+        let path = uri
+            .to_file_path()
+            .map_err(|_| LspError::InvalidUri(uri.clone()))?;
+        let Some(active) = self.document_overlays.remove(session, &path) else {
+            return Ok(());
+        };
+        match active.or_else(|| self.fs.read_to_string(&path).ok()) {
+            Some(text) => self.open_document(uri, &text),
+            // Nothing to fall back to - keep the last text to preserve cross-file references
+            None => self.close_document(uri),
+        }
+    }
+
+    /// Analyse a session's text of the documents it has open, where another
+    /// session's overlay is active
+    pub fn activate_session(&mut self, session: SessionId) {
+        for (path, text) in self.document_overlays.activate(session) {
+            if let Ok(uri) = Url::from_file_path(&path)
+                && let Err(err) = self.open_document(&uri, &text)
+            {
+                tracing::warn!(path = %path.display(), "Failed to switch session overlay: {err}");
+            }
+        }
+    }
+
+    /// Close every document a session has open
+    pub fn close_session(&mut self, session: SessionId) {
+        for path in self.document_overlays.documents_of(session) {
+            if let Ok(uri) = Url::from_file_path(&path)
+                && let Err(err) = self.close_session_document(session, &uri)
+            {
+                tracing::warn!(path = %path.display(), "Failed to close session document: {err}");
+            }
+        }
+    }
+
+    /// Sessions that have a document open
+    pub fn document_sessions(&self, uri: &Url) -> Vec<SessionId> {
+        uri.to_file_path()
+            .map(|path| self.document_overlays.sessions_for(&path))
+            .unwrap_or_default()
+    }
+}
+
+/// An `LspServer` shared between sessions
+#[derive(Clone)]
+pub struct SharedWorkspace {
+    server: Arc<Mutex<LspServer>>,
+    next_session: Arc<AtomicU32>,
+}
+
+impl SharedWorkspace {
+    pub fn new(server: LspServer) -> Self {
+        Self {
+            server: Arc::new(Mutex::new(server)),
+            next_session: Arc::new(AtomicU32::new(1)),
+        }
+    }
+
+    /// Start a new session on the workspace
+    pub fn connect(&self) -> Session {
+        Session {
+            id: self.next_session.fetch_add(1, Ordering::Relaxed),
+            workspace: self.clone(),
+        }
+    }
+
+    /// Lock the shared server
+    pub fn lock(&self) -> MutexGuard<'_, LspServer> {
+        // A panic in another session leaves the server usable
+        self.server
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// One consumer of a shared workspace, with its own open documents
+///
+/// Dropping the session closes its documents.
+pub struct Session {
+    id: SessionId,
+    workspace: SharedWorkspace,
+}
+
+impl Session {
+    pub fn id(&self) -> SessionId {
+        self.id
+    }
+
+    /// Lock the shared server for requests (hover, completion, ...), which
+    /// read this session's text of the documents it has open
+    pub fn server(&self) -> MutexGuard<'_, LspServer> {
+        let mut server = self.workspace.lock();
+        server.activate_session(self.id);
+        server
+    }
+
+    pub fn open_document(&self, uri: &Url, text: &str) -> Result<(), LspError> {
+        self.workspace
+            .lock()
+            .open_session_document(self.id, uri, text)
+    }
+
+    pub fn change_document(
+        &self,
+        uri: &Url,
+        change: &TextDocumentContentChangeEvent,
+    ) -> Result<(), LspError> {
+        self.workspace
+            .lock()
+            .change_session_document(self.id, uri, change)
+    }
+
+    pub fn close_document(&self, uri: &Url) -> Result<(), LspError> {
+        self.workspace.lock().close_session_document(self.id, uri)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.workspace.lock().close_session(self.id);
+    }
+}
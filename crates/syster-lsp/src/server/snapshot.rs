@@ -0,0 +1,193 @@
+//! Workspace snapshots for reproducing bug reports
+//!
+//! `syster/exportWorkspaceSnapshot` captures what the server was working
+//! with: the text and version of every open document, the configuration, and
+//! optionally a content hash of every workspace file. The snapshot is a
+//! single JSON document a user can attach to an issue; maintainers load it
+//! into a fresh server with [`LspServer::from_snapshot`] to replay hovers,
+//! resolution and diagnostics against the same documents.
+
+use super::LspServer;
+use super::error::LspError;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::Url;
+use async_lsp::lsp_types::request::Request;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use syster::core::constants::LSP_SERVER_VERSION;
+
+/// Version of the snapshot format, bumped on incompatible changes
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Custom LSP request: syster/exportWorkspaceSnapshot
+pub enum ExportWorkspaceSnapshotRequest {}
+
+impl Request for ExportWorkspaceSnapshotRequest {
+    type Params = ExportWorkspaceSnapshotParams;
+    type Result = WorkspaceSnapshot;
+    const METHOD: &'static str = "syster/exportWorkspaceSnapshot";
+}
+
+/// Request parameters for syster/exportWorkspaceSnapshot
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportWorkspaceSnapshotParams {
+    /// Include a content hash of every file in the workspace folders
+    #[serde(default)]
+    pub include_file_hashes: bool,
+}
+
+/// Open documents and configuration of a server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSnapshot {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Snapshot format version ([`SNAPSHOT_FORMAT_VERSION`])
+    pub format_version: u32,
+    /// Version of the server that took the snapshot
+    pub server_version: String,
+    pub configuration: SnapshotConfiguration,
+    pub workspace_folders: Vec<String>,
+    /// Open documents, ordered by URI
+    pub documents: Vec<SnapshotDocument>,
+    /// Workspace files and their content hashes, ordered by path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<SnapshotFile>>,
+}
+
+/// Settings that affect resolution, hovers and diagnostics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotConfiguration {
+    pub stdlib_enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdlib_path: Option<String>,
+    pub exclude_globs: Vec<String>,
+    pub references_exclude_stdlib: bool,
+    pub hover_max_depth: usize,
+    pub naming_conventions: bool,
+}
+
+/// An open document as the editor last sent it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDocument {
+    pub uri: String,
+    pub version: i32,
+    pub text: String,
+}
+
+/// A workspace file identified by its content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotFile {
+    pub path: String,
+    /// FNV-1a hash of the file text, as 16 hex digits
+    pub hash: String,
+    pub bytes: usize,
+}
+
+impl LspServer {
+    /// Capture the open documents and configuration of the server
+    pub fn export_workspace_snapshot(&self, include_file_hashes: bool) -> WorkspaceSnapshot {
+        let (stdlib_enabled, stdlib_path) = &self.configured_stdlib;
+        let mut documents: Vec<SnapshotDocument> = self
+            .document_versions
+            .iter()
+            .filter_map(|(path, version)| {
+                Some(SnapshotDocument {
+                    uri: Url::from_file_path(path).ok()?.to_string(),
+                    version: version.get(),
+                    text: self.document_texts.get(path)?.clone(),
+                })
+            })
+            .collect();
+        documents.sort_by(|a, b| a.uri.cmp(&b.uri));
+
+        let files = include_file_hashes.then(|| {
+            let mut files: Vec<SnapshotFile> = self
+                .document_texts
+                .iter()
+                .filter(|(path, _)| {
+                    self.workspace_folders
+                        .iter()
+                        .any(|folder| path.starts_with(folder))
+                })
+                .map(|(path, text)| SnapshotFile {
+                    path: path.to_string_lossy().into_owned(),
+                    hash: format!("{:016x}", fnv1a(text.as_bytes())),
+                    bytes: text.len(),
+                })
+                .collect();
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+            files
+        });
+
+        WorkspaceSnapshot {
+            schema_version: SCHEMA_VERSION,
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            server_version: LSP_SERVER_VERSION.to_string(),
+            configuration: SnapshotConfiguration {
+                stdlib_enabled: *stdlib_enabled,
+                stdlib_path: stdlib_path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().into_owned()),
+                exclude_globs: self.exclude_globs.clone(),
+                references_exclude_stdlib: self.references_exclude_stdlib,
+                hover_max_depth: self.hover_max_depth,
+                naming_conventions: self.naming_conventions,
+            },
+            workspace_folders: self
+                .workspace_folders
+                .iter()
+                .map(|folder| folder.to_string_lossy().into_owned())
+                .collect(),
+            documents,
+            files,
+        }
+    }
+
+    /// Create a server holding the documents and configuration of a snapshot.
+    ///
+    /// The snapshot's workspace folders are on another machine, so they aren't
+    /// scanned; only its documents are loaded. A custom stdlib path that
+    /// doesn't exist here falls back to the stdlib found automatically.
+    pub fn from_snapshot(snapshot: &WorkspaceSnapshot) -> Result<Self, LspError> {
+        if snapshot.format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(LspError::InvalidSnapshot(format!(
+                "format version {} is not supported (expected {SNAPSHOT_FORMAT_VERSION})",
+                snapshot.format_version
+            )));
+        }
+
+        let config = &snapshot.configuration;
+        let stdlib_path = config
+            .stdlib_path
+            .as_ref()
+            .map(PathBuf::from)
+            .filter(|path| path.exists());
+        let mut server = Self::with_config(config.stdlib_enabled, stdlib_path);
+        server.set_exclude_globs(config.exclude_globs.clone());
+        server.set_references_exclude_stdlib(config.references_exclude_stdlib);
+        server.set_hover_max_depth(config.hover_max_depth);
+        server.set_naming_conventions(config.naming_conventions);
+
+        for document in &snapshot.documents {
+            let uri = Url::parse(&document.uri).map_err(|err| {
+                LspError::InvalidSnapshot(format!("invalid URI {}: {err}", document.uri))
+            })?;
+            server.set_document_version(&uri, document.version);
+            server.open_document(&uri, &document.text)?;
+        }
+        Ok(server)
+    }
+}
+
+/// 64-bit FNV-1a hash, stable across platforms and Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
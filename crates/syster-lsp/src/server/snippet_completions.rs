@@ -0,0 +1,56 @@
+//! Snippet completions for common multi-line constructs
+//!
+//! At the start of a statement, completion offers skeletons of the elements
+//! most models are built from: a part definition with its body, a requirement
+//! with its doc comment, subject and required constraint, a state machine with
+//! its entry action and a transition, and an analysis with its subject,
+//! objective and result. Each has tab stops for the names and expressions to
+//! fill in.
+//!
+//! The snippets only make sense with their tab stops, so clients without
+//! `snippetSupport` don't get them.
+
+use async_lsp::lsp_types::{CompletionItem, CompletionItemKind, InsertTextFormat};
+
+/// Label, description and body of each snippet
+const SNIPPETS: &[(&str, &str, &str)] = &[
+    (
+        "part def",
+        "Part definition with a body",
+        "part def ${1:Name} {\n\t$0\n}",
+    ),
+    (
+        "requirement def",
+        "Requirement definition with doc, subject and constraint",
+        "requirement def ${1:Name} {\n\tdoc /* ${2:The requirement} */\n\tsubject ${3:subject} : ${4:Type};\n\trequire constraint { ${5:true} }\n\t$0\n}",
+    ),
+    (
+        "state def",
+        "State definition with entry state and transition",
+        "state def ${1:Name} {\n\tentry; then ${2:idle};\n\n\tstate ${2:idle};\n\tstate ${3:active};\n\n\ttransition first ${2:idle} accept ${4:Trigger} then ${3:active};\n\t$0\n}",
+    ),
+    (
+        "analysis",
+        "Analysis case with subject, objective and result",
+        "analysis ${1:name} {\n\tsubject ${2:subject} : ${3:Type};\n\tobjective {\n\t\tdoc /* ${4:What the analysis determines} */\n\t}\n\treturn ${5:result} : ${6:Real};\n\t$0\n}",
+    ),
+];
+
+/// Add the snippets to `items`, replacing the IDE layer's shorter snippets
+/// of the same name
+pub(super) fn add_snippet_completions(items: &mut Vec<CompletionItem>) {
+    for (label, description, body) in SNIPPETS {
+        items.retain(|item| {
+            !(item.label == *label && item.kind == Some(CompletionItemKind::SNIPPET))
+        });
+        items.push(CompletionItem {
+            label: label.to_string(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some(description.to_string()),
+            insert_text: Some(body.to_string()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            sort_text: Some(format!("080_{label}")),
+            ..Default::default()
+        });
+    }
+}
@@ -0,0 +1,200 @@
+//! On-disk cache of the stdlib reference index.
+//!
+//! Indexing the references of every stdlib file is repeated at each server
+//! start although the stdlib rarely changes. The cache stores those entries in
+//! a versioned JSON file, together with the size and modification time of each
+//! stdlib file. On startup the entries are seeded into the reference index
+//! instead of being rebuilt; any added, removed or modified stdlib file, or a
+//! different server version, invalidates the whole cache.
+//!
+//! syster-base's syntax trees and symbols can't be serialized, so stdlib files
+//! are still parsed into the analysis host.
+
+use super::LspServer;
+use super::environment::FileSystem;
+use super::reference_index::{ReferenceEntry, ReferenceIndex};
+use async_lsp::lsp_types::Range;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use syster::core::constants::LSP_SERVER_VERSION;
+
+/// Bumped whenever the cache layout changes
+pub const STDLIB_CACHE_FORMAT: u32 = 1;
+
+/// Name of the cache file inside the cache directory
+pub const STDLIB_CACHE_FILE: &str = "stdlib-index.json";
+
+/// Default cache directory: `$XDG_CACHE_HOME/syster-lsp`, or `~/.cache/syster-lsp`
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("syster-lsp"))
+}
+
+/// Size and modification time a cached file had when the cache was written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileStamp {
+    path: PathBuf,
+    size: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+}
+
+impl FileStamp {
+    fn read(fs: &dyn FileSystem, path: &Path) -> Option<Self> {
+        let metadata = fs.metadata(path).ok()?;
+        let modified = metadata.modified.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            path: path.to_path_buf(),
+            size: metadata.len,
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedReference {
+    owner: String,
+    target: String,
+    range: Range,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedFile {
+    stamp: FileStamp,
+    references: Vec<CachedReference>,
+}
+
+/// The stdlib reference index as stored on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StdlibCache {
+    format: u32,
+    server_version: String,
+    files: Vec<CachedFile>,
+}
+
+impl StdlibCache {
+    /// Capture the reference index entries of `files`
+    pub fn build(
+        fs: &dyn FileSystem,
+        files: &[PathBuf],
+        reference_index: &ReferenceIndex,
+    ) -> Option<Self> {
+        let files = files
+            .iter()
+            .map(|path| {
+                Some(CachedFile {
+                    stamp: FileStamp::read(fs, path)?,
+                    references: reference_index
+                        .entries(path)
+                        .iter()
+                        .map(|entry| CachedReference {
+                            owner: entry.owner.to_string(),
+                            target: entry.target.to_string(),
+                            range: entry.range,
+                        })
+                        .collect(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            format: STDLIB_CACHE_FORMAT,
+            server_version: LSP_SERVER_VERSION.to_string(),
+            files,
+        })
+    }
+
+    /// Load the cache if it was written by this server version for exactly
+    /// `files`, all unchanged since
+    pub fn load(fs: &dyn FileSystem, cache_file: &Path, files: &[PathBuf]) -> Option<Self> {
+        let text = fs.read_to_string(cache_file).ok()?;
+        let cache: Self = serde_json::from_str(&text).ok()?;
+        let valid =
+            cache.format == STDLIB_CACHE_FORMAT
+                && cache.server_version == LSP_SERVER_VERSION
+                && cache.files.len() == files.len()
+                && cache.files.iter().zip(files).all(|(cached, path)| {
+                    FileStamp::read(fs, path).as_ref() == Some(&cached.stamp)
+                });
+        valid.then_some(cache)
+    }
+
+    /// Write the cache, creating its directory if needed
+    pub fn save(&self, fs: &dyn FileSystem, cache_file: &Path) -> std::io::Result<()> {
+        if let Some(dir) = cache_file.parent() {
+            fs.create_dir_all(dir)?;
+        }
+        let text = serde_json::to_string(self).map_err(std::io::Error::other)?;
+        // Write then rename so a concurrent server never reads a partial file
+        let partial = cache_file.with_extension("json.partial");
+        fs.write(&partial, &text)?;
+        fs.rename(&partial, cache_file)
+    }
+
+    /// Put the cached entries into the reference index
+    pub fn seed(self, reference_index: &mut ReferenceIndex) {
+        for file in self.files {
+            let entries = file
+                .references
+                .into_iter()
+                .map(|reference| ReferenceEntry {
+                    owner: reference.owner.into(),
+                    target: reference.target.into(),
+                    range: reference.range,
+                })
+                .collect();
+            reference_index.seed(&file.stamp.path, entries);
+        }
+    }
+}
+
+impl LspServer {
+    /// Set the directory holding the stdlib cache (`None` disables caching)
+    pub fn set_stdlib_cache_dir(&mut self, dir: Option<PathBuf>) {
+        self.stdlib_cache_dir = dir;
+    }
+
+    /// Seed the stdlib references from the on-disk cache, or index them and
+    /// write the cache when it is missing or stale.
+    pub(super) fn restore_stdlib_index(&mut self) {
+        let Some(cache_file) = self
+            .stdlib_cache_dir
+            .as_ref()
+            .map(|dir| dir.join(STDLIB_CACHE_FILE))
+        else {
+            return;
+        };
+        let mut files: Vec<PathBuf> = self
+            .analysis_host
+            .files()
+            .keys()
+            .filter(|path| self.is_stdlib_path(path))
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            return;
+        }
+        files.sort();
+
+        if let Some(cache) = StdlibCache::load(self.fs.as_ref(), &cache_file, &files) {
+            cache.seed(&mut self.reference_index);
+            return;
+        }
+
+        let analysis = self.analysis_host.analysis();
+        self.reference_index.sync(&analysis);
+        if let Some(cache) = StdlibCache::build(self.fs.as_ref(), &files, &self.reference_index)
+            && let Err(err) = cache.save(self.fs.as_ref(), &cache_file)
+        {
+            tracing::warn!(path = %cache_file.display(), "Failed to write stdlib cache: {err}");
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Lookup maps over the symbols of each file, by name and by position.
+//!
+//! The symbol index lists symbols per file in no particular order, so finding
+//! the symbol under the cursor or every symbol with a name meant walking
+//! those lists. Here each file's symbols are kept in document order, which
+//! finds the name spans on a line by binary search, and a map from each name
+//! and short name to the files declaring it narrows name lookups to those
+//! files. Aliases are also found by the last segment of the target they name,
+//! the same way.
+//!
+//! Entries record what a file declares (qualified name and name span), which
+//! only changes when that file is re-parsed, so an edit re-indexes just the
+//! edited file and every other file keeps its entries.
+
+use async_lsp::lsp_types::{Position, Range};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use syster::base::FileId;
+use syster::hir::{HirSymbol, SymbolIndex, SymbolKind};
+use syster::ide::Analysis;
+
+/// A symbol declared in a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolEntry {
+    pub qualified_name: Arc<str>,
+    pub name: Arc<str>,
+    pub short_name: Option<Arc<str>>,
+    /// The target of an alias, as written
+    pub alias_target: Option<Arc<str>>,
+    /// Span of the symbol's name
+    pub range: Range,
+}
+
+/// Symbols per file, by position and by name
+#[derive(Debug, Default)]
+pub struct SymbolLookup {
+    /// Symbols of each file, in document order
+    files: HashMap<PathBuf, Vec<SymbolEntry>>,
+    /// Documents re-parsed since the last sync
+    pending: HashSet<PathBuf>,
+    /// Files declaring a symbol with each name or short name
+    by_name: HashMap<Arc<str>, HashSet<PathBuf>>,
+    /// Files declaring an alias of each target name's last segment
+    by_alias_target: HashMap<Arc<str>, HashSet<PathBuf>>,
+}
+
+impl SymbolLookup {
+    /// Record that a document was re-parsed.
+    ///
+    /// Its entries are replaced on the next `sync`, once the symbol index has
+    /// been rebuilt.
+    pub fn note_edit(&mut self, path: &Path) {
+        self.pending.insert(path.to_path_buf());
+    }
+
+    /// Bring the lookup up to date with the files loaded in `analysis`.
+    ///
+    /// Edited documents and newly loaded files are indexed, files no longer
+    /// loaded are dropped and all others are left as they are.
+    pub fn sync(&mut self, analysis: &Analysis<'_>) {
+        let index = analysis.symbol_index();
+
+        let unloaded: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| analysis.get_file_id(&path.to_string_lossy()).is_none())
+            .cloned()
+            .collect();
+        for path in unloaded {
+            self.unlink(&path);
+            self.files.remove(&path);
+        }
+
+        for path in std::mem::take(&mut self.pending) {
+            if let Some(file) = analysis.get_file_id(&path.to_string_lossy()) {
+                self.set_file(path, file_entries(index, file));
+            }
+        }
+        for (path, &file) in analysis.file_id_map().iter() {
+            if !self.files.contains_key(Path::new(path)) {
+                self.set_file(PathBuf::from(path), file_entries(index, file));
+            }
+        }
+    }
+
+    /// Replace the entries of a file, keeping the by-name lookup in step
+    fn set_file(&mut self, path: PathBuf, entries: Vec<SymbolEntry>) {
+        self.unlink(&path);
+        for entry in &entries {
+            for name in entry_names(entry) {
+                self.by_name
+                    .entry(name.clone())
+                    .or_default()
+                    .insert(path.clone());
+            }
+            if let Some(target) = alias_target_name(entry) {
+                self.by_alias_target
+                    .entry(Arc::from(target))
+                    .or_default()
+                    .insert(path.clone());
+            }
+        }
+        self.files.insert(path, entries);
+    }
+
+    /// Remove a file's entries from the by-name lookup
+    fn unlink(&mut self, path: &Path) {
+        let Some(entries) = self.files.get(path) else {
+            return;
+        };
+        for name in entries.iter().flat_map(entry_names) {
+            if let Some(files) = self.by_name.get_mut(name) {
+                files.remove(path);
+                if files.is_empty() {
+                    self.by_name.remove(name);
+                }
+            }
+        }
+        for target in entries.iter().filter_map(alias_target_name) {
+            if let Some(files) = self.by_alias_target.get_mut(target) {
+                files.remove(path);
+                if files.is_empty() {
+                    self.by_alias_target.remove(target);
+                }
+            }
+        }
+    }
+
+    /// The symbols declared in a file, in document order
+    pub fn in_file(&self, path: &Path) -> &[SymbolEntry] {
+        self.files.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// The symbols whose name starts on `line` of a file
+    pub fn on_line(&self, path: &Path, line: u32) -> &[SymbolEntry] {
+        let entries = self.in_file(path);
+        let start = entries.partition_point(|entry| entry.range.start.line < line);
+        let end = entries.partition_point(|entry| entry.range.start.line <= line);
+        &entries[start..end]
+    }
+
+    /// The symbol whose name span contains `position`
+    pub fn at(&self, path: &Path, position: Position) -> Option<&SymbolEntry> {
+        self.on_line(path, position.line).iter().find(|entry| {
+            entry.range.start.character <= position.character
+                && (position.line, position.character)
+                    <= (entry.range.end.line, entry.range.end.character)
+        })
+    }
+
+    /// Every symbol named or short-named `name`, with the file declaring it
+    pub fn named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = (&'a Path, &'a SymbolEntry)> {
+        self.by_name
+            .get(name)
+            .into_iter()
+            .flatten()
+            .flat_map(move |path| {
+                self.in_file(path)
+                    .iter()
+                    .filter(move |entry| entry_names(entry).any(|n| n.as_ref() == name))
+                    .map(move |entry| (path.as_path(), entry))
+            })
+    }
+
+    /// Every alias whose written target ends in `name`, with the file declaring it
+    pub fn aliases_naming<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = (&'a Path, &'a SymbolEntry)> {
+        self.by_alias_target
+            .get(name)
+            .into_iter()
+            .flatten()
+            .flat_map(move |path| {
+                self.in_file(path)
+                    .iter()
+                    .filter(move |entry| alias_target_name(entry) == Some(name))
+                    .map(move |entry| (path.as_path(), entry))
+            })
+    }
+}
+
+/// The symbol of `file` an entry stands for
+pub fn symbol<'a>(
+    index: &'a SymbolIndex,
+    file: FileId,
+    entry: &SymbolEntry,
+) -> Option<&'a HirSymbol> {
+    let is_entry = |sym: &&HirSymbol| {
+        sym.file == file
+            && sym.qualified_name == entry.qualified_name
+            && Position::new(sym.start_line, sym.start_col) == entry.range.start
+    };
+    // Elements declared twice share a qualified name, so fall back to the file
+    index
+        .lookup_qualified(&entry.qualified_name)
+        .filter(is_entry)
+        .or_else(|| index.symbols_in_file(file).into_iter().find(is_entry))
+}
+
+fn entry_names(entry: &SymbolEntry) -> impl Iterator<Item = &Arc<str>> {
+    std::iter::once(&entry.name).chain(entry.short_name.as_ref())
+}
+
+/// Last segment of the target an alias names
+fn alias_target_name(entry: &SymbolEntry) -> Option<&str> {
+    let target = entry.alias_target.as_deref()?;
+    target.rsplit("::").next()
+}
+
+/// The symbols of a file, in document order
+fn file_entries(index: &SymbolIndex, file: FileId) -> Vec<SymbolEntry> {
+    let mut entries: Vec<SymbolEntry> = index
+        .symbols_in_file(file)
+        .into_iter()
+        .map(|sym| SymbolEntry {
+            qualified_name: sym.qualified_name.clone(),
+            name: sym.name.clone(),
+            short_name: sym.short_name.as_deref().map(Arc::from),
+            alias_target: sym
+                .supertypes
+                .first()
+                .filter(|_| sym.kind == SymbolKind::Alias)
+                .map(|target| Arc::from(&**target)),
+            range: Range::new(
+                Position::new(sym.start_line, sym.start_col),
+                Position::new(sym.end_line, sym.end_col),
+            ),
+        })
+        .collect();
+    entries.sort_by_key(|entry| (entry.range.start.line, entry.range.start.character));
+    entries
+}
@@ -2,14 +2,75 @@
 pub mod test_helpers;
 
 // Test modules
+mod tests_alias_references;
+mod tests_call_hierarchy;
+mod tests_client_support;
+mod tests_code_actions;
 mod tests_code_lens;
+mod tests_completion_ranking;
+mod tests_configuration;
+mod tests_connection_check;
+mod tests_connector_ends;
+mod tests_constant_eval;
 mod tests_core_lspserver;
+mod tests_corpus;
+mod tests_dependency_graph;
+mod tests_diagnostics_store;
+mod tests_diagram;
+mod tests_diagram_sequence;
+mod tests_diagram_text;
+mod tests_direction_check;
+mod tests_document_highlight;
 mod tests_document_links;
+mod tests_environment;
+mod tests_feature_chain_definition;
+mod tests_feature_support;
 mod tests_formatting;
+mod tests_health;
 mod tests_helpers;
 mod tests_helpers_apply_text_edit;
 mod tests_helpers_char_offset_to_byte;
 mod tests_helpers_char_offset_to_utf16;
 mod tests_helpers_position_to_byte_offset;
+mod tests_implementation;
+mod tests_implicit_supertype;
+mod tests_incremental_parse;
+mod tests_inline_completion;
+mod tests_kerml;
+mod tests_keyword_hover;
+mod tests_linked_editing;
 mod tests_lsp_server_state;
+mod tests_member_completion;
+mod tests_memory_stats;
+mod tests_model_export;
+mod tests_moniker;
+mod tests_package_graph;
+mod tests_parallel_parse;
+mod tests_parse_cache;
+mod tests_pragmas;
+mod tests_project_manifest;
+mod tests_project_validation;
+mod tests_qualified_completion;
+mod tests_qualified_name;
+mod tests_reference_index;
+mod tests_reference_search;
+mod tests_references;
+mod tests_rename_redefinitions;
+mod tests_requirement_status;
+mod tests_requirement_trace;
+mod tests_resolve_spans;
+mod tests_schema;
+mod tests_scope_check;
 mod tests_server;
+mod tests_session;
+mod tests_snapshot;
+mod tests_snippet_completions;
+mod tests_stdlib_cache;
+mod tests_symbol_lookup;
+mod tests_type_hierarchy;
+mod tests_unit_resolution;
+mod tests_unused_elements;
+mod tests_unused_imports;
+mod tests_watched_files;
+mod tests_workspace_filter;
+mod tests_workspace_roots;
@@ -0,0 +1,70 @@
+//! Tests for references written through aliases
+
+use crate::server::LspServer;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{HoverContents, Position, Url};
+
+const MODEL: &str = "package P {
+    attribute def DurationValue;
+    alias Time for DurationValue;
+    part def Timer {
+        attribute elapsed : Time;
+        attribute limit : DurationValue;
+    }
+}";
+
+fn open() -> (LspServer, Url) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, MODEL).unwrap();
+    (server, uri)
+}
+
+#[test]
+fn test_references_include_usages_written_with_an_alias() {
+    let (mut server, uri) = open();
+
+    let locations = server
+        .get_references(&uri, Position::new(1, 20), false)
+        .unwrap();
+    let lines: Vec<u32> = locations
+        .iter()
+        .map(|location| location.range.start.line)
+        .collect();
+    // `for DurationValue`, `: Time` and `: DurationValue`
+    assert!(lines.contains(&4), "{locations:?}");
+    assert!(lines.contains(&5), "{locations:?}");
+
+    let time = locations
+        .iter()
+        .find(|location| location.range.start.line == 4)
+        .unwrap();
+    assert_eq!(time.range.start.character, 28);
+}
+
+#[test]
+fn test_alias_usages_are_reported_once() {
+    let (mut server, uri) = open();
+
+    let locations = server
+        .get_references(&uri, Position::new(1, 20), true)
+        .unwrap();
+    for (idx, location) in locations.iter().enumerate() {
+        assert!(
+            !locations[idx + 1..].contains(location),
+            "duplicate {location:?} in {locations:?}"
+        );
+    }
+}
+
+#[test]
+fn test_hover_counts_usages_written_with_an_alias() {
+    let (mut server, uri) = open();
+
+    let hover = server.get_hover(&uri, Position::new(1, 20)).unwrap();
+    let HoverContents::Markup(content) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert!(content.value.contains("test.sysml:5:"), "{}", content.value);
+    assert!(content.value.contains("test.sysml:6:"), "{}", content.value);
+}
@@ -0,0 +1,101 @@
+//! Tests for call hierarchy on actions and calculations
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, SymbolKind, Url};
+
+const SOURCE: &str = r#"package P {
+    action def Start;
+    calc def Speed;
+    action def Drive {
+        action s : Start;
+        perform action p : Start;
+        calc v : Speed;
+    }
+    part car {
+        perform action drive : Drive;
+    }
+}"#;
+
+fn open_source() -> (crate::server::LspServer, Url) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///calls.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+    (server, uri)
+}
+
+#[test]
+fn test_prepare_call_hierarchy_on_action_def() {
+    let (mut server, uri) = open_source();
+
+    let items = server
+        .prepare_call_hierarchy(&uri, Position::new(3, 16))
+        .expect("Drive should be callable");
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name, "Drive");
+    assert_eq!(items[0].kind, SymbolKind::FUNCTION);
+    assert_eq!(items[0].detail.as_deref(), Some("P::Drive"));
+}
+
+#[test]
+fn test_prepare_call_hierarchy_on_invocation_uses_target() {
+    let (mut server, uri) = open_source();
+
+    // Cursor on `Start` in `action s : Start;`
+    let items = server
+        .prepare_call_hierarchy(&uri, Position::new(4, 20))
+        .expect("type reference should resolve");
+    assert_eq!(items[0].detail.as_deref(), Some("P::Start"));
+}
+
+#[test]
+fn test_prepare_call_hierarchy_rejects_non_callables() {
+    let (mut server, uri) = open_source();
+    // Cursor on the package name
+    assert!(
+        server
+            .prepare_call_hierarchy(&uri, Position::new(0, 8))
+            .is_none()
+    );
+}
+
+#[test]
+fn test_incoming_calls() {
+    let (mut server, uri) = open_source();
+
+    let start = server
+        .prepare_call_hierarchy(&uri, Position::new(1, 16))
+        .unwrap()
+        .remove(0);
+    let incoming = server.get_incoming_calls(&start).unwrap();
+
+    assert_eq!(incoming.len(), 1, "{incoming:?}");
+    assert_eq!(incoming[0].from.name, "Drive");
+    assert_eq!(incoming[0].from_ranges.len(), 2, "action s and perform p");
+
+    let drive = server
+        .prepare_call_hierarchy(&uri, Position::new(3, 16))
+        .unwrap()
+        .remove(0);
+    let incoming = server.get_incoming_calls(&drive).unwrap();
+    assert_eq!(incoming.len(), 1);
+    assert_eq!(incoming[0].from.name, "car");
+    assert_eq!(incoming[0].from.kind, SymbolKind::PROPERTY);
+}
+
+#[test]
+fn test_outgoing_calls() {
+    let (mut server, uri) = open_source();
+
+    let drive = server
+        .prepare_call_hierarchy(&uri, Position::new(3, 16))
+        .unwrap()
+        .remove(0);
+    let outgoing = server.get_outgoing_calls(&drive).unwrap();
+
+    let names: Vec<_> = outgoing.iter().map(|c| c.to.name.as_str()).collect();
+    assert_eq!(names, vec!["Speed", "Start"]);
+    let start = outgoing.iter().find(|c| c.to.name == "Start").unwrap();
+    assert_eq!(start.from_ranges.len(), 2);
+    assert_eq!(start.from_ranges[0].start.line, 4);
+}
@@ -0,0 +1,159 @@
+//! Tests for the client capabilities the server consults
+
+use crate::server::client_support::{
+    ClientSupport, GotoRequest, markdown_to_plain_text, snippet_to_plain_text,
+};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{
+    ClientCapabilities, CompletionClientCapabilities, CompletionItemCapability, GotoCapability,
+    GotoDefinitionResponse, HoverClientCapabilities, HoverContents, Location, MarkupKind, Position,
+    Range, TextDocumentClientCapabilities, Url, WindowClientCapabilities,
+};
+
+#[test]
+fn test_minimal_client_supports_nothing_optional() {
+    let support = ClientSupport::from_capabilities(&ClientCapabilities::default());
+
+    assert!(!support.work_done_progress);
+    assert!(!support.pull_diagnostics);
+    assert!(!support.definition_links);
+    assert!(!support.type_definition_links);
+    assert!(!support.implementation_links);
+    assert!(!support.markdown_hover);
+    assert!(!support.snippets);
+}
+
+#[test]
+fn test_client_support_reads_declared_capabilities() {
+    let capabilities = ClientCapabilities {
+        window: Some(WindowClientCapabilities {
+            work_done_progress: Some(true),
+            ..Default::default()
+        }),
+        text_document: Some(TextDocumentClientCapabilities {
+            definition: Some(GotoCapability {
+                link_support: Some(true),
+                ..Default::default()
+            }),
+            implementation: Some(GotoCapability {
+                link_support: Some(true),
+                ..Default::default()
+            }),
+            hover: Some(HoverClientCapabilities {
+                content_format: Some(vec![MarkupKind::Markdown, MarkupKind::PlainText]),
+                ..Default::default()
+            }),
+            completion: Some(CompletionClientCapabilities {
+                completion_item: Some(CompletionItemCapability {
+                    snippet_support: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let support = ClientSupport::from_capabilities(&capabilities);
+
+    assert!(support.work_done_progress);
+    assert!(support.definition_links);
+    assert!(!support.type_definition_links);
+    assert!(support.implementation_links);
+    assert!(support.markdown_hover);
+    assert!(support.snippets);
+    // Declaring text document capabilities doesn't mean pulling diagnostics
+    assert!(!support.pull_diagnostics);
+}
+
+#[test]
+fn test_hover_is_plain_text_without_markdown_support() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server
+        .open_document(&uri, "part def Vehicle;\npart car : Vehicle;")
+        .unwrap();
+    server.set_client_support(ClientSupport::from_capabilities(
+        &ClientCapabilities::default(),
+    ));
+
+    let hover = server.get_hover(&uri, Position::new(0, 10)).unwrap();
+    let HoverContents::Markup(content) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert_eq!(content.kind, MarkupKind::PlainText);
+    assert!(content.value.contains("Vehicle"), "{}", content.value);
+    assert!(!content.value.contains("**"), "{}", content.value);
+}
+
+#[test]
+fn test_goto_response_uses_links_only_when_supported() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server
+        .open_document(&uri, "part def Vehicle;\npart car : Vehicle;")
+        .unwrap();
+    let target = Location {
+        uri: uri.clone(),
+        range: Range::new(Position::new(0, 9), Position::new(0, 16)),
+    };
+    let position = Position::new(1, 13);
+
+    let response = GotoDefinitionResponse::Scalar(target.clone());
+    let definition = GotoRequest::Definition;
+    assert_eq!(
+        server.goto_response(definition, &uri, position, response.clone()),
+        response
+    );
+
+    let mut support = server.client_support().clone();
+    support.definition_links = true;
+    server.set_client_support(support);
+    // Each request has its own capability
+    assert_eq!(
+        server.goto_response(
+            GotoRequest::TypeDefinition,
+            &uri,
+            position,
+            response.clone()
+        ),
+        response
+    );
+    let GotoDefinitionResponse::Link(links) =
+        server.goto_response(definition, &uri, position, response)
+    else {
+        panic!("Expected location links");
+    };
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].target_uri, uri);
+    assert_eq!(links[0].target_selection_range, target.range);
+    assert_eq!(
+        links[0].origin_selection_range,
+        Some(Range::new(Position::new(1, 11), Position::new(1, 18)))
+    );
+}
+
+#[test]
+fn test_markdown_to_plain_text() {
+    let markdown = "```sysml\npart def Vehicle\n```\n**Multiplicity:** `[0..*]`\n\
+                    **Qualified Name:** [P](file:///p.sysml#L1)::[Vehicle](file:///p.sysml#L2)\n\
+                    ---\n_Partial results: the workspace is still being indexed._";
+
+    assert_eq!(
+        markdown_to_plain_text(markdown),
+        "part def Vehicle\nMultiplicity: [0..*]\nQualified Name: P::Vehicle\n\
+         Partial results: the workspace is still being indexed."
+    );
+}
+
+#[test]
+fn test_snippet_to_plain_text() {
+    assert_eq!(
+        snippet_to_plain_text("part def ${1:Name} {\n\t$0\n}"),
+        "part def Name {\n\t\n}"
+    );
+    assert_eq!(
+        snippet_to_plain_text("attribute ${1} : \\$Real$2;"),
+        "attribute  : $Real;"
+    );
+}
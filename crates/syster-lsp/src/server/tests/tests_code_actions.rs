@@ -0,0 +1,447 @@
+//! Tests for code actions
+
+use crate::server::LspServer;
+use crate::server::configuration::ServerSettings;
+use crate::server::helpers::apply_text_edit;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{
+    CodeActionContext, CodeActionOrCommand, CodeActionParams, Diagnostic, NumberOrString,
+    PartialResultParams, Position, Range, TextDocumentIdentifier, Url, WorkDoneProgressParams,
+};
+
+fn relationship_diagnostics(server: &mut LspServer, uri: &Url) -> Vec<Diagnostic> {
+    server
+        .get_diagnostics(uri)
+        .into_iter()
+        .filter(|d| d.code == Some(NumberOrString::String("relationship-kind".to_string())))
+        .collect()
+}
+
+fn code_actions(
+    server: &mut LspServer,
+    uri: &Url,
+    diagnostics: Vec<Diagnostic>,
+) -> Vec<CodeActionOrCommand> {
+    let range = diagnostics.first().map(|d| d.range).unwrap_or_default();
+    server.get_code_actions(&CodeActionParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        range,
+        context: CodeActionContext {
+            diagnostics,
+            only: None,
+            trigger_kind: None,
+        },
+        work_done_progress_params: WorkDoneProgressParams::default(),
+        partial_result_params: PartialResultParams::default(),
+    })
+}
+
+/// The title and single text edit of a code action
+fn only_edit(action: &CodeActionOrCommand, uri: &Url) -> (String, Range, String) {
+    let CodeActionOrCommand::CodeAction(action) = action else {
+        panic!("Expected a code action");
+    };
+    let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[uri];
+    assert_eq!(edits.len(), 1);
+    (
+        action.title.clone(),
+        edits[0].range,
+        edits[0].new_text.clone(),
+    )
+}
+
+#[test]
+fn test_subsetting_inherited_name_offers_redefinition() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def V { part e; }\n    part def C :> V {\n        part e :> e;\n    }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = relationship_diagnostics(&mut server, &uri);
+    assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+    assert!(diagnostics[0].message.contains("subsets itself"));
+
+    let actions = code_actions(&mut server, &uri, diagnostics);
+    assert_eq!(actions.len(), 1);
+    let (title, range, new_text) = only_edit(&actions[0], &uri);
+    assert_eq!(title, "Replace ':>' with ':>>'");
+    assert_eq!(
+        range,
+        Range::new(Position::new(3, 15), Position::new(3, 17))
+    );
+    assert_eq!(new_text, ":>>");
+
+    let fixed = text.replace("part e :> e", "part e :>> e");
+    server.open_document(&uri, &fixed).unwrap();
+    assert!(relationship_diagnostics(&mut server, &uri).is_empty());
+}
+
+#[test]
+fn test_subsetting_definition_offers_typing() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def Vehicle;\n    part car subsets Vehicle;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = relationship_diagnostics(&mut server, &uri);
+    let actions = code_actions(&mut server, &uri, diagnostics);
+    assert_eq!(actions.len(), 1);
+    let (title, range, new_text) = only_edit(&actions[0], &uri);
+    assert_eq!(title, "Replace 'subsets' with ':'");
+    assert_eq!(
+        range,
+        Range::new(Position::new(2, 13), Position::new(2, 20))
+    );
+    assert_eq!(new_text, ":");
+}
+
+#[test]
+fn test_redefining_sibling_offers_subsetting() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def C {\n        part wheels;\n        part front redefines wheels;\n        part rear :>> wheels;\n    }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = relationship_diagnostics(&mut server, &uri);
+    assert_eq!(diagnostics.len(), 2, "got {diagnostics:?}");
+    let replacements: Vec<String> = code_actions(&mut server, &uri, diagnostics)
+        .iter()
+        .map(|action| only_edit(action, &uri).2)
+        .collect();
+    assert_eq!(replacements, vec!["subsets", ":>"]);
+}
+
+#[test]
+fn test_legal_relationships_have_no_quick_fix() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def V { part e; }\n    part def C :> V {\n        part e :>> e;\n        part f :> e;\n    }\n    part v : V;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = relationship_diagnostics(&mut server, &uri);
+    assert!(diagnostics.is_empty(), "got {diagnostics:?}");
+    let all = server.get_diagnostics(&uri);
+    assert!(code_actions(&mut server, &uri, all).is_empty());
+}
+
+#[test]
+fn test_redefining_inherited_feature_shadowed_by_sibling_has_no_quick_fix() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def V { part e; }\n    part def C :> V {\n        part e :>> e;\n        part front :>> e;\n    }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = relationship_diagnostics(&mut server, &uri);
+    assert!(diagnostics.is_empty(), "got {diagnostics:?}");
+}
+
+fn duplicate_diagnostics(server: &mut LspServer, uri: &Url) -> Vec<Diagnostic> {
+    server
+        .get_diagnostics(uri)
+        .into_iter()
+        .filter(|d| d.code == Some(NumberOrString::String("duplicate-relationship".to_string())))
+        .collect()
+}
+
+/// The document after applying the single edit of the only quick fix
+fn apply_only_fix(server: &mut LspServer, uri: &Url, text: &str) -> (String, String) {
+    let diagnostics = duplicate_diagnostics(server, uri);
+    assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+    let actions = code_actions(server, uri, diagnostics);
+    assert_eq!(actions.len(), 1);
+    let (title, range, new_text) = only_edit(&actions[0], uri);
+    (title, apply_text_edit(text, &range, &new_text).unwrap())
+}
+
+#[test]
+fn test_duplicate_specialization_in_list_is_removed() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def A;\n    part def B;\n    part def C :> A, B, A;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = duplicate_diagnostics(&mut server, &uri);
+    assert_eq!(
+        diagnostics[0].range,
+        Range::new(Position::new(3, 24), Position::new(3, 25))
+    );
+    assert!(diagnostics[0].message.contains("already specializes 'A'"));
+
+    let (title, fixed) = apply_only_fix(&mut server, &uri, text);
+    assert_eq!(title, "Remove duplicate 'A'");
+    assert!(fixed.contains("part def C :> A, B;"), "{fixed}");
+    server.open_document(&uri, &fixed).unwrap();
+    assert!(duplicate_diagnostics(&mut server, &uri).is_empty());
+}
+
+#[test]
+fn test_duplicate_subsetting_through_qualified_name_is_removed() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def V { part e; part f; }\n    part def C :> V {\n        part g subsets e subsets V::e, f;\n    }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let (_, fixed) = apply_only_fix(&mut server, &uri, text);
+    assert!(fixed.contains("part g subsets e subsets f;"), "{fixed}");
+}
+
+#[test]
+fn test_repeated_target_of_another_kind_is_no_duplicate() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def V { part e; }\n    part def C :> V {\n        part e :>> e;\n        part f : V :> e;\n        part g : V;\n    }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    assert!(duplicate_diagnostics(&mut server, &uri).is_empty());
+}
+
+fn unresolved_diagnostics(server: &mut LspServer, uri: &Url) -> Vec<Diagnostic> {
+    server
+        .get_diagnostics(uri)
+        .into_iter()
+        .filter(|d| d.message.starts_with("unresolved reference"))
+        .collect()
+}
+
+#[test]
+fn test_unresolved_reference_offers_import() {
+    let mut server = create_server();
+    let lib_uri = Url::parse("file:///lib.sysml").unwrap();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server
+        .open_document(&lib_uri, "package Lib {\n    part def Engine;\n}")
+        .unwrap();
+    let text = "package App {\n    part def Car;\n    part e : Engine;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = unresolved_diagnostics(&mut server, &uri);
+    assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+
+    let actions = code_actions(&mut server, &uri, diagnostics);
+    let (title, range, new_text) = only_edit(&actions[0], &uri);
+    assert_eq!(title, "Import 'Lib::Engine'");
+    assert_eq!(range, Range::new(Position::new(1, 0), Position::new(1, 0)));
+    assert_eq!(new_text, "    private import Lib::Engine;\n");
+}
+
+#[test]
+fn test_unresolved_reference_offers_closest_names() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def Vehicle;\n    part def Vessel;\n    part vehicle;\n    part x : Vehcle;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = unresolved_diagnostics(&mut server, &uri);
+    let actions = code_actions(&mut server, &uri, diagnostics);
+    let fixes: Vec<(String, Range, String)> = actions
+        .iter()
+        .map(|action| only_edit(action, &uri))
+        .collect();
+
+    // Only definitions can type `x`, and `Vessel` is too far off
+    assert_eq!(fixes.len(), 1, "got {fixes:?}");
+    assert_eq!(fixes[0].0, "Change to 'Vehicle'");
+    assert_eq!(
+        fixes[0].1,
+        Range::new(Position::new(4, 13), Position::new(4, 19))
+    );
+    assert_eq!(fixes[0].2, "Vehicle");
+}
+
+#[test]
+fn test_fallback_resolution_offers_import() {
+    let mut server = create_server();
+    let parts_uri = Url::parse("file:///parts.sysml").unwrap();
+    let uri = Url::parse("file:///car.sysml").unwrap();
+    server
+        .open_document(&parts_uri, "package Parts {\n    part def Engine;\n}")
+        .unwrap();
+    let text = "package Car {\n    part engine : Engine;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostic = Diagnostic {
+        range: Range::new(Position::new(1, 18), Position::new(1, 24)),
+        code: Some(NumberOrString::String("fallback-resolution".to_string())),
+        message:
+            "'Engine' resolved by fallback to 'Parts::Engine'; add an import to make this explicit"
+                .to_string(),
+        data: Some(serde_json::json!({ "target": "Parts::Engine" })),
+        ..Default::default()
+    };
+    let actions = code_actions(&mut server, &uri, vec![diagnostic]);
+    let fixes: Vec<(String, Range, String)> = actions
+        .iter()
+        .map(|action| only_edit(action, &uri))
+        .filter(|(title, _, _)| title == "Import 'Parts::Engine'")
+        .collect();
+    assert_eq!(fixes.len(), 1, "got {fixes:?}");
+    assert_eq!(
+        fixes[0].1,
+        Range::new(Position::new(1, 0), Position::new(1, 0))
+    );
+    assert_eq!(fixes[0].2, "    private import Parts::Engine;\n");
+}
+
+const LIB: &str =
+    "package Lib {\n    part def Engine;\n    part def Wheel;\n    part def Unused;\n}";
+
+/// Open the library and a document, and return its organize imports actions by kind
+fn organize_imports_actions(text: &str) -> Vec<(String, String)> {
+    let mut server = create_server();
+    let lib_uri = Url::parse("file:///lib.sysml").unwrap();
+    let uri = Url::parse("file:///app.sysml").unwrap();
+    server.open_document(&lib_uri, LIB).unwrap();
+    server.open_document(&uri, text).unwrap();
+
+    code_actions(&mut server, &uri, Vec::new())
+        .into_iter()
+        .map(|action| {
+            let CodeActionOrCommand::CodeAction(action) = action else {
+                panic!("Expected a code action");
+            };
+            let mut edits = action.edit.unwrap().changes.unwrap()[&uri].clone();
+            // Apply from the end so earlier ranges stay valid
+            edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+            let organized = edits.iter().fold(text.to_string(), |text, edit| {
+                apply_text_edit(&text, &edit.range, &edit.new_text).unwrap()
+            });
+            (action.kind.unwrap().as_str().to_string(), organized)
+        })
+        .collect()
+}
+
+#[test]
+fn test_organize_imports_sorts_dedupes_and_removes_unused() {
+    let text = "package App {\n    private import Lib::Wheel;\n    private import Lib::Engine;\n    private import Lib::Unused;\n    private import Lib::Engine;\n    part e : Engine;\n    part w : Wheel;\n}";
+    let actions = organize_imports_actions(text);
+
+    assert_eq!(actions.len(), 1, "got {actions:?}");
+    assert_eq!(actions[0].0, "source.organizeImports");
+    assert_eq!(
+        actions[0].1,
+        "package App {\n    private import Lib::Engine;\n    private import Lib::Wheel;\n    part e : Engine;\n    part w : Wheel;\n}"
+    );
+}
+
+#[test]
+fn test_organize_imports_keeps_public_imports_first() {
+    let text = "package App {\n    private import Lib::Engine;\n    part e : Engine;\n    public import Lib::Unused;\n}";
+    let actions = organize_imports_actions(text);
+
+    assert_eq!(
+        actions[0].1,
+        "package App {\n    public import Lib::Unused;\n    private import Lib::Engine;\n    part e : Engine;\n}"
+    );
+}
+
+#[test]
+fn test_organize_imports_expands_wildcards() {
+    let text =
+        "package App {\n    private import Lib::*;\n    part e : Engine;\n    part w : Wheel;\n}";
+    let actions = organize_imports_actions(text);
+
+    // The wildcard is used, so only expanding it changes anything
+    assert_eq!(actions.len(), 1, "got {actions:?}");
+    assert_eq!(actions[0].0, "source.organizeImports.expandWildcards");
+    assert_eq!(
+        actions[0].1,
+        "package App {\n    private import Lib::Engine;\n    private import Lib::Wheel;\n    part e : Engine;\n    part w : Wheel;\n}"
+    );
+}
+
+#[test]
+fn test_organized_imports_offer_no_action() {
+    let text = "package App {\n    private import Lib::Engine;\n    private import Lib::Wheel;\n    part e : Engine;\n    part w : Wheel;\n}";
+    let actions = organize_imports_actions(text);
+    assert!(actions.is_empty(), "got {actions:?}");
+}
+
+fn naming_diagnostics(server: &mut LspServer, uri: &Url) -> Vec<Diagnostic> {
+    server
+        .get_diagnostics(uri)
+        .into_iter()
+        .filter(|d| d.code == Some(NumberOrString::String("naming-convention".to_string())))
+        .collect()
+}
+
+#[test]
+fn test_naming_conventions_are_opt_in() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server
+        .open_document(&uri, "package P {\n    part def fuel_tank;\n}")
+        .unwrap();
+    assert!(naming_diagnostics(&mut server, &uri).is_empty());
+
+    let options = serde_json::json!({ "namingConventions": true });
+    assert!(ServerSettings::from_options(Some(&options)).naming_conventions);
+    assert!(!ServerSettings::from_options(None).naming_conventions);
+}
+
+#[test]
+fn test_naming_convention_fix_renames_references() {
+    let mut server = create_server();
+    server.set_naming_conventions(true);
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package my_pkg {\n    part def fuel_tank;\n    part def Car {\n        part Tank : fuel_tank;\n    }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = naming_diagnostics(&mut server, &uri);
+    let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "'fuel_tank' should be UpperCamelCase",
+            "'Tank' should be lowerCamelCase"
+        ]
+    );
+    assert_eq!(
+        diagnostics[0].range,
+        Range::new(Position::new(1, 13), Position::new(1, 22))
+    );
+
+    let actions = code_actions(&mut server, &uri, vec![diagnostics[0].clone()]);
+    assert_eq!(actions.len(), 1, "got {actions:?}");
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("Expected a code action");
+    };
+    assert_eq!(
+        action.title,
+        "Convert 'fuel_tank' to UpperCamelCase ('FuelTank')"
+    );
+    let mut edits = action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri].clone();
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+    let renamed = edits.iter().fold(text.to_string(), |text, edit| {
+        apply_text_edit(&text, &edit.range, &edit.new_text).unwrap()
+    });
+    assert_eq!(
+        renamed,
+        "package my_pkg {\n    part def FuelTank;\n    part def Car {\n        part Tank : FuelTank;\n    }\n}"
+    );
+}
+
+#[test]
+fn test_naming_convention_fix_skipped_on_conflict() {
+    let mut server = create_server();
+    server.set_naming_conventions(true);
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def Engine;\n    part def engine;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = naming_diagnostics(&mut server, &uri);
+    assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+    assert!(code_actions(&mut server, &uri, diagnostics).is_empty());
+}
+
+#[test]
+fn test_name_case_conversion() {
+    use crate::server::naming_check::NameCase;
+
+    assert_eq!(NameCase::UpperCamel.convert("engine_rpm"), "EngineRpm");
+    assert_eq!(NameCase::LowerCamel.convert("engine_rpm"), "engineRpm");
+    assert_eq!(NameCase::LowerCamel.convert("FuelTank"), "fuelTank");
+    assert_eq!(NameCase::UpperCamel.convert("MAX_SPEED"), "MaxSpeed");
+    assert_eq!(NameCase::LowerCamel.convert("HTTPServer"), "httpServer");
+    assert_eq!(NameCase::UpperCamel.convert("sensor2Input"), "Sensor2Input");
+    assert_eq!(NameCase::LowerCamel.convert("wheelHub"), "wheelHub");
+}
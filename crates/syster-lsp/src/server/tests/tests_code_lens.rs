@@ -1,7 +1,16 @@
 //! Tests for code lens functionality
 
 use crate::server::tests::test_helpers::create_server;
-use async_lsp::lsp_types::Url;
+use async_lsp::lsp_types::{CodeLens, Command, Url};
+
+/// Commands of the "N references" lenses
+fn reference_lenses(lenses: &[CodeLens]) -> Vec<&Command> {
+    lenses
+        .iter()
+        .filter_map(|lens| lens.command.as_ref())
+        .filter(|cmd| cmd.command == "syster.showReferences")
+        .collect()
+}
 
 #[test]
 fn test_code_lens_basic() {
@@ -17,11 +26,9 @@ part car : Vehicle;
     let lenses = server.get_code_lenses(&uri);
 
     // Should have one code lens for Vehicle showing 1 reference
-    assert_eq!(lenses.len(), 1);
-    assert!(lenses[0].command.is_some());
-    let cmd = lenses[0].command.as_ref().unwrap();
-    assert_eq!(cmd.title, "1 reference");
-    assert_eq!(cmd.command, "syster.showReferences");
+    let references = reference_lenses(&lenses);
+    assert_eq!(references.len(), 1);
+    assert_eq!(references[0].title, "1 reference");
 }
 
 #[test]
@@ -40,9 +47,9 @@ part bike : Vehicle;
     let lenses = server.get_code_lenses(&uri);
 
     // Should have one code lens for Vehicle showing 3 references
-    assert_eq!(lenses.len(), 1);
-    let cmd = lenses[0].command.as_ref().unwrap();
-    assert_eq!(cmd.title, "3 references");
+    let references = reference_lenses(&lenses);
+    assert_eq!(references.len(), 1);
+    assert_eq!(references[0].title, "3 references");
 }
 
 #[test]
@@ -58,8 +65,8 @@ part def Bike;
 
     let lenses = server.get_code_lenses(&uri);
 
-    // Should have no code lenses since there are no references
-    assert_eq!(lenses.len(), 0);
+    // Should have no reference lenses since there are no references
+    assert!(reference_lenses(&lenses).is_empty());
 }
 
 #[test]
@@ -76,9 +83,9 @@ classifier Car specializes Vehicle;
     let lenses = server.get_code_lenses(&uri);
 
     // Should have one code lens for Vehicle showing the specialization reference
-    assert_eq!(lenses.len(), 1);
-    let cmd = lenses[0].command.as_ref().unwrap();
-    assert_eq!(cmd.title, "1 reference");
+    let references = reference_lenses(&lenses);
+    assert_eq!(references.len(), 1);
+    assert_eq!(references[0].title, "1 reference");
 }
 
 #[test]
@@ -141,3 +148,100 @@ part anotherRef : myCar;
     // Should have code lenses for both Vehicle (typed by myCar) and myCar (typed by anotherRef)
     assert!(!lenses.is_empty(), "Should have at least one code lens");
 }
+
+#[test]
+fn test_code_lens_wildcard_import_expansion() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package Defs {
+    part def Engine;
+    part def Wheel;
+}
+package Uses {
+    import Defs::*;
+    import Defs::Engine;
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let lenses = server.get_code_lenses(&uri);
+    let import_lenses: Vec<_> = lenses
+        .iter()
+        .filter_map(|lens| lens.command.as_ref())
+        .filter(|cmd| cmd.command == "syster.showImportedNames")
+        .collect();
+
+    // Only the wildcard import gets a lens
+    assert_eq!(import_lenses.len(), 1);
+    let cmd = import_lenses[0];
+    let names = cmd.arguments.as_ref().unwrap()[2].as_array().unwrap();
+    let names: Vec<_> = names
+        .iter()
+        .map(|entry| entry["qualifiedName"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"Defs::Engine"));
+    assert!(names.contains(&"Defs::Wheel"));
+    assert_eq!(
+        cmd.title,
+        format!("expands to {} visible names", names.len())
+    );
+}
+
+#[test]
+fn test_code_lens_open_diagram_resolves_to_scoped_request() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package Pkg {
+    part def Vehicle {
+        part engine;
+    }
+    part car;
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let lenses = server.get_code_lenses(&uri);
+    // Only definitions get a diagram lens, and it is resolved lazily
+    let unresolved: Vec<_> = lenses.iter().filter(|l| l.command.is_none()).collect();
+    assert_eq!(unresolved.len(), 1);
+
+    let lens = server.resolve_code_lens(unresolved[0].clone());
+    let cmd = lens.command.expect("resolved lens should have a command");
+    assert_eq!(cmd.title, "Open diagram");
+    assert_eq!(cmd.command, "syster.showDiagram");
+    let params = &cmd.arguments.unwrap()[0];
+    assert_eq!(params["uri"], "file:///test.sysml");
+    assert_eq!(params["element"], "Pkg::Vehicle");
+
+    // The arguments are a syster/getDiagram request scoped to the definition
+    let diagram = server.get_diagram(
+        Some(&uri.to_file_path().unwrap()),
+        Some("Pkg::Vehicle"),
+        None,
+        "GeneralView",
+    );
+    let mut names: Vec<_> = diagram
+        .symbols
+        .iter()
+        .map(|sym| sym.qualified_name.as_str())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["Pkg::Vehicle", "Pkg::Vehicle::engine"]);
+}
+
+#[test]
+fn test_code_lens_resolve_keeps_resolved_lens() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server
+        .open_document(&uri, "part def Vehicle;\npart car : Vehicle;")
+        .unwrap();
+
+    let lenses = server.get_code_lenses(&uri);
+    let resolved = lenses.iter().find(|l| l.command.is_some()).unwrap().clone();
+    assert_eq!(server.resolve_code_lens(resolved.clone()), resolved);
+}
@@ -0,0 +1,98 @@
+//! Tests for ranking completion candidates
+
+use crate::server::LspServer;
+use crate::server::completion_ranking::{Proximity, match_quality, proximity_of};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{CompletionItem, CompletionResponse, Position, Url};
+use std::path::Path;
+
+const LIBRARY: &str = "package Parts {
+    part def Engine;
+    part def Wheel;
+    part def Seat;
+}";
+
+const MODEL: &str = "package Garage {
+    private import Parts::*;
+    part def Tool;
+    part def Car {
+        part def Bench;
+        part front : Wheel;
+        part s : 
+    }
+}";
+
+fn completions(model: &str, position: Position) -> Vec<CompletionItem> {
+    let mut server: LspServer = create_server();
+    let library = Url::parse("file:///sysml.library/Parts.sysml").unwrap();
+    server.open_document(&library, LIBRARY).unwrap();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, model).unwrap();
+    let CompletionResponse::Array(mut items) =
+        server.get_completions(Path::new("/test.sysml"), position)
+    else {
+        panic!("Expected completion array");
+    };
+    items.sort_by(|a, b| a.sort_text.cmp(&b.sort_text));
+    items
+}
+
+fn rank(items: &[CompletionItem], label: &str) -> usize {
+    items
+        .iter()
+        .position(|item| item.label == label)
+        .unwrap_or_else(|| panic!("no {label} in {items:?}"))
+}
+
+#[test]
+fn test_body_and_package_symbols_come_before_the_stdlib() {
+    let items = completions(MODEL, Position::new(6, 17));
+    assert!(rank(&items, "Bench") < rank(&items, "Tool"), "{items:?}");
+    assert!(rank(&items, "Tool") < rank(&items, "Engine"), "{items:?}");
+}
+
+#[test]
+fn test_names_used_in_the_file_come_first() {
+    let items = completions(MODEL, Position::new(6, 17));
+    // Both from the stdlib, but only Wheel is written in the file
+    assert!(rank(&items, "Wheel") < rank(&items, "Engine"), "{items:?}");
+}
+
+#[test]
+fn test_matching_prefix_comes_first() {
+    let model = MODEL.replace("part s : ", "part s : Se");
+    let items = completions(&model, Position::new(6, 19));
+    assert!(rank(&items, "Seat") < rank(&items, "Engine"), "{items:?}");
+}
+
+#[test]
+fn test_match_quality() {
+    assert_eq!(match_quality("Engine", ""), 0);
+    assert_eq!(match_quality("Engine", "Eng"), 0);
+    assert_eq!(match_quality("Engine", "eng"), 1);
+    assert_eq!(match_quality("drivePowerOutput", "dpo"), 2);
+    assert_eq!(match_quality("drivePowerOutput", "power"), 2);
+    assert_eq!(match_quality("Engine", "whe"), 3);
+}
+
+#[test]
+fn test_proximity_of_declarations() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, MODEL).unwrap();
+    let analysis = server.analysis_host.analysis();
+    let index = analysis.symbol_index();
+    let proximity = |name: &str| {
+        proximity_of(
+            index.lookup_qualified(name).unwrap(),
+            "Garage::Car",
+            "Garage",
+        )
+    };
+
+    assert_eq!(proximity("Garage::Car::Bench"), Proximity::Body);
+    // The enclosing element is in the package, not the body
+    assert_eq!(proximity("Garage::Car"), Proximity::Package);
+    assert_eq!(proximity("Garage::Tool"), Proximity::Package);
+    assert_eq!(proximity("Garage"), Proximity::Project);
+}
@@ -0,0 +1,151 @@
+//! Tests for changing the server configuration while it runs
+
+use crate::server::LspServer;
+use crate::server::configuration::{DEFAULT_HOVER_MAX_DEPTH, ServerSettings};
+use crate::server::tests::test_helpers::{LspServerTestExt, create_server};
+use async_lsp::lsp_types::Url;
+use std::path::{Path, PathBuf};
+
+/// Create an empty scratch folder
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("syster-config-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(path: &Path, text: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+fn load_workspace(folder: &Path) -> LspServer {
+    let mut server = create_server();
+    server.set_workspace_folders(vec![folder.to_path_buf()]);
+    server.ensure_workspace_loaded().unwrap();
+    server
+}
+
+#[test]
+fn test_settings_default_without_options() {
+    let settings = ServerSettings::from_options(None);
+    assert!(settings.stdlib_enabled);
+    assert_eq!(settings.stdlib_path, None);
+    assert_eq!(settings.hover_max_depth, DEFAULT_HOVER_MAX_DEPTH);
+    assert!(!settings.naming_conventions);
+    assert!(settings.exclude_globs.is_empty());
+}
+
+#[test]
+fn test_changed_settings_are_read_from_the_section() {
+    let options = serde_json::json!({
+        "hoverMaxDepth": 2,
+        "namingConventions": true,
+        "excludeGlobs": ["build/"],
+    });
+    let expected = ServerSettings::from_options(Some(&options));
+    assert_eq!(expected.hover_max_depth, 2);
+
+    assert_eq!(
+        ServerSettings::from_change(&serde_json::json!({ "syster": options })),
+        Some(expected.clone())
+    );
+    assert_eq!(ServerSettings::from_change(&options), Some(expected));
+}
+
+#[test]
+fn test_changes_without_server_options_are_ignored() {
+    assert_eq!(ServerSettings::from_change(&serde_json::Value::Null), None);
+    assert_eq!(
+        ServerSettings::from_change(&serde_json::json!({ "editor": { "tabSize": 2 } })),
+        None
+    );
+}
+
+#[test]
+fn test_settings_round_trip() {
+    let mut server = create_server();
+    let options = serde_json::json!({
+        "stdlibEnabled": false,
+        "referencesExcludeStdlib": true,
+        "unusedElements": true,
+        "entryPoints": ["App::main"],
+        "renameRedefinitions": true,
+    });
+    let settings = ServerSettings::from_options(Some(&options));
+
+    let update = server.apply_settings(settings.clone());
+
+    // Nothing is loaded yet, so nothing needs publishing
+    assert_eq!(update, Default::default());
+    assert_eq!(server.settings(), settings);
+    assert!(server.references_exclude_stdlib());
+}
+
+#[test]
+fn test_diagnostic_settings_republish_open_documents() {
+    let dir = scratch_dir("diagnostics");
+    let mut server = load_workspace(&dir);
+    let uri = Url::from_file_path(dir.join("Open.sysml")).unwrap();
+    server.set_document_version(&uri, 1);
+    server
+        .open_document(&uri, "package P {\n    part def fuel_tank;\n}")
+        .unwrap();
+
+    let mut settings = server.settings();
+    settings.hover_max_depth = 1;
+    assert!(server.apply_settings(settings.clone()).changed.is_empty());
+
+    settings.naming_conventions = true;
+    let update = server.apply_settings(settings);
+    assert_eq!(update.changed, [uri.clone()]);
+    assert!(
+        server
+            .get_diagnostics(&uri)
+            .iter()
+            .any(|d| d.message.contains("fuel_tank"))
+    );
+}
+
+#[test]
+fn test_new_stdlib_path_reloads_the_library() {
+    let dir = scratch_dir("stdlib-workspace");
+    let library = scratch_dir("stdlib-library");
+    write(
+        &library.join("Lib.sysml"),
+        "package Lib { part def Engine; }",
+    );
+    let mut server = load_workspace(&dir);
+    let uri = Url::from_file_path(dir.join("Open.sysml")).unwrap();
+    server.set_document_version(&uri, 1);
+    server
+        .open_document(&uri, "package Open { part def Car; }")
+        .unwrap();
+    assert!(!server.has_qualified_symbol("Lib::Engine"));
+
+    let mut settings = server.settings();
+    settings.stdlib_enabled = true;
+    settings.stdlib_path = Some(library);
+    let update = server.apply_settings(settings);
+
+    assert!(server.has_qualified_symbol("Lib::Engine"));
+    // The open document survives the reload
+    assert!(server.has_qualified_symbol("Open::Car"));
+    assert_eq!(update.changed, [uri]);
+}
+
+#[test]
+fn test_new_exclude_globs_reload_the_workspace() {
+    let dir = scratch_dir("exclude");
+    write(&dir.join("A.sysml"), "package A { part def X; }");
+    write(&dir.join("build/B.sysml"), "package B { part def Y; }");
+    let mut server = load_workspace(&dir);
+    assert!(server.has_qualified_symbol("B::Y"));
+
+    let mut settings = server.settings();
+    settings.exclude_globs = vec!["build/".to_string()];
+    server.apply_settings(settings);
+
+    assert!(server.has_qualified_symbol("A::X"));
+    assert!(!server.has_qualified_symbol("B::Y"));
+}
@@ -0,0 +1,100 @@
+//! Tests for port conjugation checks on connections
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Url};
+
+const MODEL: &str = "package Fuel {
+    port def FuelPort;
+    port def DataPort;
+    part def Tank {
+        port fuelOut : ~FuelPort;
+        port level : DataPort;
+    }
+    part def Engine {
+        port fuelIn : FuelPort;
+        port data : DataPort;
+    }
+    part def Car {
+        port fuelCap : ~FuelPort;
+        part tank : Tank;
+        part engine : Engine;
+        part spare : Engine;
+        CONNECTION
+    }
+}";
+
+fn conjugation_issues(connection: &str) -> Vec<Diagnostic> {
+    let mut server = create_server();
+    let uri = Url::parse("file:///fuel.sysml").unwrap();
+    server
+        .open_document(&uri, &MODEL.replace("CONNECTION", connection))
+        .unwrap();
+    server
+        .get_diagnostics(&uri)
+        .into_iter()
+        .filter(|d| d.code == Some(NumberOrString::String("port-conjugation".to_string())))
+        .collect()
+}
+
+#[test]
+fn test_connecting_port_to_its_conjugate_is_legal() {
+    assert!(conjugation_issues("connect tank.fuelOut to engine.fuelIn;").is_empty());
+    assert!(
+        conjugation_issues("connection feed connect engine.fuelIn to tank.fuelOut;").is_empty()
+    );
+}
+
+#[test]
+fn test_same_direction_connection_is_flagged() {
+    let issues = conjugation_issues("connect engine.fuelIn to spare.fuelIn;");
+    assert_eq!(issues.len(), 1, "{issues:?}");
+    assert_eq!(issues[0].severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(
+        issues[0].message,
+        "'engine.fuelIn' (FuelPort) and 'spare.fuelIn' (FuelPort) have the same direction; connect a port to its conjugate"
+    );
+    // The diagnostic spans the connect clause
+    assert_eq!(issues[0].range.start.line, 16);
+    assert_eq!(issues[0].range.start.character, 8);
+}
+
+#[test]
+fn test_incompatible_port_types_are_errors() {
+    let issues = conjugation_issues("connect tank.level to engine.fuelIn;");
+    assert_eq!(issues.len(), 1, "{issues:?}");
+    assert_eq!(issues[0].severity, Some(DiagnosticSeverity::ERROR));
+}
+
+#[test]
+fn test_delegation_keeps_direction() {
+    assert!(conjugation_issues("connect fuelCap to tank.fuelOut;").is_empty());
+
+    let issues = conjugation_issues("connect fuelCap to engine.fuelIn;");
+    assert_eq!(issues.len(), 1, "{issues:?}");
+    assert!(issues[0].message.contains("opposite directions"));
+}
+
+#[test]
+fn test_connections_between_parts_are_not_checked() {
+    assert!(conjugation_issues("connect tank to engine;").is_empty());
+}
+
+#[test]
+fn test_conjugation_of_qualified_port_type() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///fuel.sysml").unwrap();
+    let model = MODEL
+        .replace(
+            "port fuelOut : ~FuelPort;",
+            "port fuelOut : ~ Fuel::FuelPort;",
+        )
+        .replace("CONNECTION", "connect tank.fuelOut to engine.fuelIn;");
+    server.open_document(&uri, &model).unwrap();
+
+    assert!(
+        !server
+            .get_diagnostics(&uri)
+            .iter()
+            .any(|d| d.code == Some(NumberOrString::String("port-conjugation".to_string())))
+    );
+}
@@ -0,0 +1,91 @@
+//! Tests for type-aware completion of connector ends
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{CompletionItem, CompletionResponse, Position, Url};
+use std::path::Path;
+
+const MODEL: &str = "package Fuel {
+    port def FuelPort;
+    port def DataPort;
+    part def Tank {
+        port fuelOut : ~FuelPort;
+        port data : DataPort;
+    }
+    part def Engine {
+        port fuelIn : FuelPort;
+        port fuelReturn : FuelPort;
+        port data : DataPort;
+    }
+    part def Car {
+        port fuelCap : ~FuelPort;
+        part tank : Tank;
+        part engine : Engine;
+        CONNECTION
+    }
+}";
+
+fn completions(connection: &str) -> Vec<CompletionItem> {
+    let mut server = create_server();
+    let uri = Url::parse("file:///fuel.sysml").unwrap();
+    let text = MODEL.replace("CONNECTION", connection);
+    server.open_document(&uri, &text).unwrap();
+
+    let position = Position::new(16, 8 + connection.len() as u32);
+    match server.get_completions(Path::new("/fuel.sysml"), position) {
+        CompletionResponse::Array(items) => items,
+        _ => panic!("Expected completion array"),
+    }
+}
+
+fn labels(items: &[CompletionItem]) -> Vec<&str> {
+    items.iter().map(|item| item.label.as_str()).collect()
+}
+
+#[test]
+fn test_connector_end_offers_conjugate_ports_of_nested_parts() {
+    let items = completions("connect tank.fuelOut to ");
+    let labels = labels(&items);
+
+    assert!(labels.contains(&"engine.fuelIn"), "{labels:?}");
+    assert!(labels.contains(&"engine.fuelReturn"), "{labels:?}");
+    // Other port types and the first end itself aren't offered
+    assert!(!labels.contains(&"engine.data"), "{labels:?}");
+    assert!(!labels.contains(&"tank.data"), "{labels:?}");
+    assert!(!labels.contains(&"tank.fuelOut"), "{labels:?}");
+    // A port of the same direction isn't offered between nested parts
+    assert!(!labels.contains(&"fuelCap"), "{labels:?}");
+
+    let fuel_in = items.iter().find(|item| item.label == "engine.fuelIn");
+    assert_eq!(
+        fuel_in.and_then(|item| item.detail.as_deref()),
+        Some(": FuelPort")
+    );
+}
+
+#[test]
+fn test_connector_end_after_prefix_offers_its_features() {
+    let items = completions("connect tank.fuelOut to engine.");
+    let labels = labels(&items);
+
+    assert!(labels.contains(&"fuelIn"), "{labels:?}");
+    assert!(!labels.contains(&"data"), "{labels:?}");
+}
+
+#[test]
+fn test_connector_end_delegation_keeps_direction() {
+    // The boundary port delegates to a nested port of the same direction
+    let items = completions("connect fuelCap to ");
+    let labels = labels(&items);
+
+    assert!(labels.contains(&"tank.fuelOut"), "{labels:?}");
+    assert!(!labels.contains(&"engine.fuelIn"), "{labels:?}");
+}
+
+#[test]
+fn test_connector_end_unresolved_first_end_offers_all_features() {
+    let items = completions("connect spare to ");
+    let labels = labels(&items);
+
+    assert!(labels.contains(&"engine"), "{labels:?}");
+    assert!(labels.contains(&"engine.data"), "{labels:?}");
+}
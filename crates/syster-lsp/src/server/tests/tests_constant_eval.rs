@@ -0,0 +1,157 @@
+//! Tests for evaluating constant value expressions
+
+use crate::server::constant_eval::evaluate;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{
+    HoverContents, InlayHintLabel, InlayHintParams, Position, Range, TextDocumentIdentifier, Url,
+};
+
+const MODEL: &str = "package P {
+    part def Wheel {
+        attribute wheelDiameter = 0.5 [m] * 2;
+        attribute spokes = 32;
+        attribute rimWidth = spokes * 2;
+        attribute speed := (10 [m] + 5 [m]) / 3 [s];
+    }
+}";
+
+fn value(expression: &str) -> Option<String> {
+    evaluate(expression).map(|value| value.to_string())
+}
+
+#[test]
+fn test_evaluate_arithmetic() {
+    assert_eq!(value("1 + 2 * 3").as_deref(), Some("7"));
+    assert_eq!(value("(1 + 2) * 3").as_deref(), Some("9"));
+    assert_eq!(value("-2 ^ 2").as_deref(), Some("4"));
+    assert_eq!(value("2 ** 3 ** 2").as_deref(), Some("512"));
+    assert_eq!(value("0.1 + 0.2").as_deref(), Some("0.3"));
+    assert_eq!(value("1.5e3 / 3").as_deref(), Some("500"));
+}
+
+#[test]
+fn test_evaluate_units() {
+    assert_eq!(value("0.5 [m] * 2").as_deref(), Some("1 [m]"));
+    assert_eq!(value("3 [m] + 4 [m]").as_deref(), Some("7 [m]"));
+    assert_eq!(
+        value("9.81 [m/s^2] * 2 [s]").as_deref(),
+        Some("19.62 [m/s]")
+    );
+    assert_eq!(value("2 [m] * 3 [m]").as_deref(), Some("6 [m^2]"));
+    assert_eq!(value("6 [m] / 2 [m]").as_deref(), Some("3"));
+    assert_eq!(value("1 / 4 [s]").as_deref(), Some("0.25 [1/s]"));
+}
+
+#[test]
+fn test_non_constant_expressions_have_no_value() {
+    // Different units don't add up
+    assert_eq!(value("1 [m] + 1 [s]"), None);
+    assert_eq!(value("spokes * 2"), None);
+    assert_eq!(value("1 / 0"), None);
+    assert_eq!(value("(1 + 2"), None);
+    assert_eq!(value("2 [m] ^ 0.5"), None);
+    assert_eq!(value(""), None);
+}
+
+#[test]
+fn test_hover_shows_evaluated_value() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, MODEL).unwrap();
+
+    let hover = server.get_hover(&uri, Position::new(2, 22)).unwrap();
+    let HoverContents::Markup(content) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert!(
+        content.value.contains("**Value:** `1 [m]`"),
+        "{}",
+        content.value
+    );
+
+    // A reference to another feature isn't evaluated
+    let hover = server.get_hover(&uri, Position::new(4, 20)).unwrap();
+    let HoverContents::Markup(content) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert!(!content.value.contains("**Value:**"), "{}", content.value);
+}
+
+#[test]
+fn test_inlay_hints_show_values_of_computed_expressions() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, MODEL).unwrap();
+
+    let params = InlayHintParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        range: Range::new(Position::new(0, 0), Position::new(10, 0)),
+        work_done_progress_params: Default::default(),
+    };
+    let values: Vec<(Position, String)> = server
+        .get_inlay_hints(&params)
+        .into_iter()
+        .filter_map(|hint| match hint.label {
+            InlayHintLabel::String(label) if label.starts_with("= ") => {
+                Some((hint.position, label))
+            }
+            _ => None,
+        })
+        .collect();
+    // Plain literals like `32` already show their value
+    assert_eq!(
+        values,
+        [
+            (Position::new(2, 45), "= 1 [m]".to_string()),
+            (Position::new(5, 51), "= 5 [m/s]".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_skips_comments_and_line_breaks() {
+    assert_eq!(value("1 +\n    2").as_deref(), Some("3"));
+    assert_eq!(value("1 + /* two */ 2").as_deref(), Some("3"));
+    assert_eq!(value("2 [m] // metres\n    * 3").as_deref(), Some("6 [m]"));
+    assert_eq!(value("1 /* unclosed"), None);
+}
+
+#[test]
+fn test_expression_spanning_lines_with_comments() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {
+    part def Beam {
+        attribute length = 2 [m] // doubled; see below
+            * /* factor = */ 3;
+    }
+}";
+    server.open_document(&uri, text).unwrap();
+
+    let hover = server.get_hover(&uri, Position::new(2, 20)).unwrap();
+    let HoverContents::Markup(content) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert!(
+        content.value.contains("**Value:** `6 [m]`"),
+        "{}",
+        content.value
+    );
+
+    let params = InlayHintParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        range: Range::new(Position::new(0, 0), Position::new(6, 0)),
+        work_done_progress_params: Default::default(),
+    };
+    let values: Vec<(Position, String)> = server
+        .get_inlay_hints(&params)
+        .into_iter()
+        .filter_map(|hint| match hint.label {
+            InlayHintLabel::String(label) if label.starts_with("= ") => {
+                Some((hint.position, label))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(values, [(Position::new(3, 30), "= 6 [m]".to_string())]);
+}
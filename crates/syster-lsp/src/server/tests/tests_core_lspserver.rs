@@ -3,14 +3,17 @@
 //! This module provides extensive test coverage for the following LspServer methods:
 //! - get_folding_ranges
 //! - semantic_tokens_legend
-//! - get_semantic_tokens
+//! - get_semantic_tokens, get_semantic_tokens_delta, get_semantic_tokens_range
 //! - get_selection_ranges, build_selection_range_chain, default_selection_range
 //! - get_inlay_hints
 //!
 //! Tests cover both success and edge cases through the public API.
 
 use crate::server::LspServer;
-use crate::server::tests::test_helpers::create_server;
+use crate::server::semantic_tokens::{DECLARATION, DEFAULT_LIBRARY, MODIFICATION, READONLY};
+use crate::server::tests::test_helpers::{
+    LspServerTestExt, create_server, create_server_with_stdlib,
+};
 use async_lsp::lsp_types::*;
 use std::path::Path;
 
@@ -274,14 +277,21 @@ fn test_semantic_tokens_legend_consistent() {
 }
 
 #[test]
-fn test_semantic_tokens_legend_no_modifiers() {
+fn test_semantic_tokens_legend_modifiers() {
     let legend = LspServer::semantic_tokens_legend();
 
-    // Current implementation has no modifiers
-    assert!(
-        legend.token_modifiers.is_empty(),
-        "Current implementation has no token modifiers"
+    // Bit positions used by the token collector
+    assert_eq!(
+        legend.token_modifiers,
+        vec![
+            SemanticTokenModifier::DECLARATION,
+            SemanticTokenModifier::READONLY,
+            SemanticTokenModifier::DEFAULT_LIBRARY,
+            SemanticTokenModifier::MODIFICATION,
+        ]
     );
+    assert_eq!(DECLARATION, 1 << 0);
+    assert_eq!(MODIFICATION, 1 << 3);
 }
 
 // ============================================================================
@@ -459,6 +469,210 @@ fn test_semantic_tokens_multiline_structure() {
     assert!(has_multiline, "Should have tokens on multiple lines");
 }
 
+/// Modifier bits of the token starting at each (line, UTF-16 column)
+fn token_modifiers_at(server: &mut LspServer, uri: &Url) -> Vec<((u32, u32), u32)> {
+    let Some(SemanticTokensResult::Tokens(tokens)) = server.get_semantic_tokens(uri) else {
+        panic!("Expected SemanticTokens result");
+    };
+    let (mut line, mut col) = (0, 0);
+    tokens
+        .data
+        .iter()
+        .map(|token| {
+            if token.delta_line > 0 {
+                col = 0;
+            }
+            line += token.delta_line;
+            col += token.delta_start;
+            ((line, col), token.token_modifiers_bitset)
+        })
+        .collect()
+}
+
+fn modifiers_at(tokens: &[((u32, u32), u32)], line: u32, col: u32) -> u32 {
+    tokens
+        .iter()
+        .find(|(position, _)| *position == (line, col))
+        .map_or_else(
+            || panic!("No token at {line}:{col}: {tokens:?}"),
+            |(_, bits)| *bits,
+        )
+}
+
+#[test]
+fn test_semantic_tokens_declaration_and_modification_modifiers() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "part def Vehicle {\n    readonly attribute mass;\n}\npart def Car :> Vehicle {\n    attribute :>> mass;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let tokens = token_modifiers_at(&mut server, &uri);
+
+    // Declared names
+    assert_eq!(modifiers_at(&tokens, 0, 9), DECLARATION);
+    assert_eq!(modifiers_at(&tokens, 1, 23), DECLARATION | READONLY);
+    // A reference to a user definition has no modifiers
+    assert_eq!(modifiers_at(&tokens, 3, 16), 0);
+    // The redefined feature
+    assert_ne!(modifiers_at(&tokens, 4, 18) & MODIFICATION, 0);
+}
+
+#[test]
+fn test_semantic_tokens_default_library_modifier() {
+    let mut server = create_server_with_stdlib();
+    if !server.has_stdlib_loaded() {
+        return;
+    }
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text =
+        "package P {\n    private import ScalarValues::*;\n    attribute def Mass :> Real;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let tokens = token_modifiers_at(&mut server, &uri);
+
+    assert_eq!(modifiers_at(&tokens, 2, 18), DECLARATION);
+    assert_eq!(modifiers_at(&tokens, 2, 26), DEFAULT_LIBRARY);
+}
+
+fn full_tokens(server: &mut LspServer, uri: &Url) -> SemanticTokens {
+    match server.get_semantic_tokens(uri) {
+        Some(SemanticTokensResult::Tokens(tokens)) => tokens,
+        other => panic!("Expected SemanticTokens result, got {other:?}"),
+    }
+}
+
+/// Apply delta edits to the integers of an encoded token array
+fn apply_token_edits(data: &[SemanticToken], edits: &[SemanticTokensEdit]) -> Vec<SemanticToken> {
+    let mut data = data.to_vec();
+    for edit in edits.iter().rev() {
+        assert_eq!(edit.start % 5, 0, "Edits should replace whole tokens");
+        assert_eq!(
+            edit.delete_count % 5,
+            0,
+            "Edits should replace whole tokens"
+        );
+        let start = edit.start as usize / 5;
+        let end = start + edit.delete_count as usize / 5;
+        data.splice(start..end, edit.data.clone().unwrap_or_default());
+    }
+    data
+}
+
+#[test]
+fn test_semantic_tokens_delta_unchanged_document() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server
+        .open_document(&uri, "part def Vehicle;\npart car : Vehicle;")
+        .unwrap();
+
+    let full = full_tokens(&mut server, &uri);
+    let previous_id = full
+        .result_id
+        .expect("Full results should have a result id");
+
+    let Some(SemanticTokensFullDeltaResult::TokensDelta(delta)) =
+        server.get_semantic_tokens_delta(&uri, &previous_id)
+    else {
+        panic!("Expected a delta");
+    };
+    assert!(delta.edits.is_empty(), "Nothing changed: {:?}", delta.edits);
+    assert_ne!(delta.result_id, Some(previous_id));
+}
+
+#[test]
+fn test_semantic_tokens_delta_after_edit() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server
+        .open_document(&uri, "part def Vehicle;\npart car : Vehicle;")
+        .unwrap();
+    let before = full_tokens(&mut server, &uri);
+
+    server
+        .open_document(
+            &uri,
+            "part def Vehicle;\npart def Engine;\npart car : Vehicle;",
+        )
+        .unwrap();
+    let Some(SemanticTokensFullDeltaResult::TokensDelta(delta)) =
+        server.get_semantic_tokens_delta(&uri, before.result_id.as_deref().unwrap())
+    else {
+        panic!("Expected a delta");
+    };
+    assert_eq!(delta.edits.len(), 1);
+
+    // The edits turn the previous tokens into the current ones
+    let after = full_tokens(&mut server, &uri);
+    assert_eq!(apply_token_edits(&before.data, &delta.edits), after.data);
+    assert!(
+        delta.edits[0].delete_count < before.data.len() as u32 * 5,
+        "Unchanged tokens should be kept"
+    );
+}
+
+#[test]
+fn test_semantic_tokens_delta_unknown_result_id() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, "part def Vehicle;").unwrap();
+    let full = full_tokens(&mut server, &uri);
+
+    // An outdated or unknown result gets the full tokens
+    let Some(SemanticTokensFullDeltaResult::Tokens(tokens)) =
+        server.get_semantic_tokens_delta(&uri, "unknown")
+    else {
+        panic!("Expected full tokens");
+    };
+    assert_eq!(tokens.data, full.data);
+    assert!(tokens.result_id.is_some());
+
+    // Closing the document forgets its tokens
+    let previous_id = tokens.result_id.unwrap();
+    server.close_document(&uri).unwrap();
+    assert!(matches!(
+        server.get_semantic_tokens_delta(&uri, &previous_id),
+        Some(SemanticTokensFullDeltaResult::Tokens(_))
+    ));
+}
+
+#[test]
+fn test_semantic_tokens_range() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "part def Vehicle;\npart def Engine;\npart car : Vehicle;";
+    server.open_document(&uri, text).unwrap();
+    let full = full_tokens(&mut server, &uri);
+
+    let range = Range::new(Position::new(1, 0), Position::new(2, 0));
+    let Some(SemanticTokensRangeResult::Tokens(tokens)) =
+        server.get_semantic_tokens_range(&uri, range)
+    else {
+        panic!("Expected SemanticTokens result");
+    };
+
+    assert!(!tokens.data.is_empty());
+    assert!(tokens.data.len() < full.data.len());
+    // Positions are encoded from the start of the document
+    assert_eq!(tokens.data[0].delta_line, 1);
+    assert!(tokens.data[1..].iter().all(|token| token.delta_line == 0));
+}
+
+#[test]
+fn test_semantic_tokens_capabilities() {
+    let capabilities = LspServer::server_capabilities();
+    let Some(SemanticTokensServerCapabilities::SemanticTokensOptions(options)) =
+        capabilities.semantic_tokens_provider
+    else {
+        panic!("Expected semantic tokens options");
+    };
+    assert_eq!(options.range, Some(true));
+    assert_eq!(
+        options.full,
+        Some(SemanticTokensFullOptions::Delta { delta: Some(true) })
+    );
+}
+
 // ============================================================================
 // Tests for get_selection_ranges, build_selection_range_chain,
 // default_selection_range (#71-91)
@@ -930,17 +1144,17 @@ fn test_folding_ranges_kerml_file() {
     feature speed : Real;
 }"#;
 
-    // KerML files not yet fully supported, so we expect an error
-    let result = server.open_document(&uri, text);
-    if result.is_err() {
-        // Expected - KerML not fully supported yet
-        return;
-    }
+    server.open_document(&uri, text).unwrap();
 
     let path = Path::new(uri.path());
     let ranges = server.get_folding_ranges(path);
 
-    // Should handle KerML files gracefully if opened
+    // The class body folds like a SysML definition body
+    assert!(
+        ranges
+            .iter()
+            .any(|range| range.start_line == 0 && range.end_line > 0)
+    );
     for range in &ranges {
         assert!(range.end_line >= range.start_line);
     }
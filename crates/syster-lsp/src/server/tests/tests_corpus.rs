@@ -0,0 +1,186 @@
+//! Tests for corpus regression runs
+
+use crate::server::LspError;
+use crate::server::corpus::{
+    CorpusCheck, CorpusExpectations, EXPECTATIONS_FILE, FileExpectations, Probe,
+};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::Position;
+use std::path::{Path, PathBuf};
+
+/// Create an empty scratch corpus
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("syster-corpus-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(path: &Path, text: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+/// A corpus of two files, one referring to the other
+fn vehicles_corpus(name: &str) -> PathBuf {
+    let dir = scratch_dir(name);
+    write(
+        &dir.join("parts/Engine.sysml"),
+        "package Parts {\n    part def Engine;\n}\n",
+    );
+    write(
+        &dir.join("Vehicle.sysml"),
+        "package Vehicles {\n    import Parts::*;\n    part def Vehicle {\n        part engine : Engine;\n    }\n}\n",
+    );
+    dir
+}
+
+#[test]
+fn test_recorded_corpus_passes() {
+    let dir = vehicles_corpus("recorded");
+    let expectations = create_server().record_corpus(&dir).unwrap();
+
+    let paths: Vec<&str> = expectations
+        .files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect();
+    assert_eq!(paths, ["Vehicle.sysml", "parts/Engine.sysml"]);
+    let engine = expectations.files[0]
+        .probes
+        .iter()
+        .find(|probe| probe.position == Position::new(3, 22))
+        .expect("probe at the reference to Engine");
+    let definition = engine.definition.as_ref().unwrap();
+    assert_eq!(definition.path, "parts/Engine.sysml");
+    assert_eq!(definition.range.start, Position::new(1, 13));
+    let hover = engine.hover.as_deref().unwrap();
+    assert!(!hover.contains("file://"), "{hover}");
+
+    expectations.save(&dir).unwrap();
+    assert_eq!(CorpusExpectations::load(&dir).unwrap(), expectations);
+    let report = create_server().check_corpus(&dir).unwrap();
+    assert!(report.passed(), "{:?}", report.regressions);
+    assert_eq!(report.files, 2);
+    assert_eq!(report.probes, expectations.files[0].probes.len());
+    assert!(report.unrecorded_files.is_empty());
+}
+
+#[test]
+fn test_moved_corpus_passes() {
+    let dir = vehicles_corpus("moved-from");
+    create_server()
+        .record_corpus(&dir)
+        .unwrap()
+        .save(&dir)
+        .unwrap();
+
+    let moved = std::env::temp_dir().join(format!("syster-corpus-moved-to-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&moved);
+    std::fs::rename(&dir, &moved).unwrap();
+    let report = create_server().check_corpus(&moved).unwrap();
+    assert!(report.passed(), "{:?}", report.regressions);
+}
+
+#[test]
+fn test_changed_answers_are_regressions() {
+    let dir = vehicles_corpus("changed");
+    write(&dir.join("notes/Draft.sysml"), "package Draft;\n");
+    create_server()
+        .record_corpus(&dir)
+        .unwrap()
+        .save(&dir)
+        .unwrap();
+
+    // Engine moves down a line and the draft gains a syntax error
+    write(
+        &dir.join("parts/Engine.sysml"),
+        "package Parts {\n    part def Spare;\n    part def Engine;\n}\n",
+    );
+    write(
+        &dir.join("notes/Draft.sysml"),
+        "package Draft {\n    part def ;\n}\n",
+    );
+    let report = create_server().check_corpus(&dir).unwrap();
+
+    assert!(!report.passed());
+    let parse_errors = report
+        .regressions
+        .iter()
+        .find(|regression| regression.check == CorpusCheck::ParseErrors)
+        .unwrap();
+    assert_eq!(parse_errors.path, "notes/Draft.sysml");
+    assert_eq!(parse_errors.expected, "0");
+
+    let definition = report
+        .regressions
+        .iter()
+        .find(|regression| regression.check == CorpusCheck::Definition)
+        .unwrap();
+    assert_eq!(definition.path, "Vehicle.sysml");
+    assert_eq!(definition.position, Some(Position::new(3, 22)));
+    assert_eq!(definition.expected, "parts/Engine.sysml:2:14");
+    assert_eq!(definition.actual, "parts/Engine.sysml:3:14");
+    assert_eq!(
+        definition.to_string(),
+        "Vehicle.sysml:4:23: Definition expected \"parts/Engine.sysml:2:14\", got \"parts/Engine.sysml:3:14\""
+    );
+}
+
+#[test]
+fn test_missing_and_unrecorded_files() {
+    let dir = vehicles_corpus("missing");
+    CorpusExpectations {
+        files: vec![
+            FileExpectations {
+                path: "Removed.sysml".to_string(),
+                parse_errors: 0,
+                probes: Vec::new(),
+            },
+            FileExpectations {
+                path: "Vehicle.sysml".to_string(),
+                parse_errors: 0,
+                probes: vec![Probe {
+                    position: Position::new(3, 22),
+                    hover: None,
+                    definition: None,
+                }],
+            },
+        ],
+    }
+    .save(&dir)
+    .unwrap();
+
+    let report = create_server().check_corpus(&dir).unwrap();
+    assert_eq!(report.regressions[0].path, "Removed.sysml");
+    assert_eq!(report.regressions[0].check, CorpusCheck::MissingFile);
+    assert_eq!(report.unrecorded_files, ["parts/Engine.sysml"]);
+    // The reference to Engine has a hover and a definition, neither expected
+    let checks: Vec<CorpusCheck> = report.regressions[1..]
+        .iter()
+        .map(|regression| regression.check)
+        .collect();
+    assert_eq!(checks, [CorpusCheck::Hover, CorpusCheck::Definition]);
+}
+
+#[test]
+fn test_corpus_without_expectations() {
+    let dir = vehicles_corpus("unrecorded");
+    let report = create_server().check_corpus(&dir).unwrap();
+    assert!(report.passed());
+    assert_eq!(report.probes, 0);
+    assert_eq!(report.unrecorded_files.len(), 2);
+}
+
+#[test]
+fn test_invalid_expectations() {
+    let dir = vehicles_corpus("invalid");
+    write(&dir.join(EXPECTATIONS_FILE), "{ \"files\": 3 }");
+    let err = create_server().check_corpus(&dir).unwrap_err();
+    assert!(matches!(err, LspError::InvalidCorpus(_)), "{err}");
+
+    let err = create_server()
+        .check_corpus(&dir.join("missing"))
+        .unwrap_err();
+    assert!(matches!(err, LspError::InvalidCorpus(_)), "{err}");
+}
@@ -0,0 +1,106 @@
+//! Tests for re-validating files that depend on an edited document
+
+use crate::server::LspServer;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+
+const VEHICLES: &str = "package Vehicles {
+    part def Vehicle;
+}";
+
+const GARAGE: &str = "package Garage {
+    import Vehicles::*;
+    part car : Vehicle;
+}";
+
+fn open_workspace() -> (LspServer, Url, Url) {
+    let mut server = create_server();
+    let vehicles = Url::parse("file:///vehicles.sysml").unwrap();
+    let garage = Url::parse("file:///garage.sysml").unwrap();
+    // Dependents are re-validated if they are open in the editor
+    for (uri, text) in [(&vehicles, VEHICLES), (&garage, GARAGE)] {
+        server.set_document_version(uri, 1);
+        server.open_document(uri, text).unwrap();
+    }
+    (server, vehicles, garage)
+}
+
+fn edit(server: &mut LspServer, uri: &Url, range: Range, text: &str) {
+    let change = TextDocumentContentChangeEvent {
+        range: Some(range),
+        range_length: None,
+        text: text.to_string(),
+    };
+    server.apply_text_change_only(uri, &change).unwrap();
+    server.parse_document(uri);
+}
+
+/// Range of `Vehicle` in `part def Vehicle;`
+fn definition_name() -> Range {
+    Range::new(Position::new(1, 13), Position::new(1, 20))
+}
+
+#[test]
+fn test_renaming_definition_schedules_dependents() {
+    let (mut server, vehicles, garage) = open_workspace();
+    assert!(server.get_diagnostics(&garage).is_empty());
+
+    edit(&mut server, &vehicles, definition_name(), "Car");
+    assert_eq!(server.take_dependent_documents(), vec![garage.clone()]);
+    // Taking the dependents clears them
+    assert!(server.take_dependent_documents().is_empty());
+
+    // The dependent's diagnostics reflect the rename
+    assert!(!server.get_diagnostics(&garage).is_empty());
+}
+
+#[test]
+fn test_renaming_definition_back_resolves_dependents() {
+    let (mut server, vehicles, garage) = open_workspace();
+    server.diagnostics_to_publish(&garage);
+
+    edit(&mut server, &vehicles, definition_name(), "Car");
+    server.take_dependent_documents();
+    assert!(server.diagnostics_to_publish(&garage).is_some());
+
+    edit(
+        &mut server,
+        &vehicles,
+        Range::new(Position::new(1, 13), Position::new(1, 16)),
+        "Vehicle",
+    );
+    assert_eq!(server.take_dependent_documents(), vec![garage.clone()]);
+    assert_eq!(server.diagnostics_to_publish(&garage), Some(Vec::new()));
+}
+
+#[test]
+fn test_edit_without_definition_changes_schedules_nothing() {
+    let (mut server, vehicles, _) = open_workspace();
+
+    // A doc comment doesn't change what the file defines
+    edit(
+        &mut server,
+        &vehicles,
+        Range::new(Position::new(1, 21), Position::new(1, 21)),
+        " // the vehicle",
+    );
+    assert!(server.take_dependent_documents().is_empty());
+}
+
+#[test]
+fn test_unrelated_files_are_not_scheduled() {
+    let (mut server, vehicles, _) = open_workspace();
+    let other = Url::parse("file:///other.sysml").unwrap();
+    server.set_document_version(&other, 1);
+    server
+        .open_document(
+            &other,
+            "package Other { part def Engine; part e : Engine; }",
+        )
+        .unwrap();
+
+    edit(&mut server, &vehicles, definition_name(), "Car");
+    let dependents = server.take_dependent_documents();
+    assert!(!dependents.contains(&other));
+    assert!(!dependents.contains(&vehicles));
+}
@@ -0,0 +1,226 @@
+//! Tests for deduplicated, ordered diagnostics and pull diagnostics
+
+use crate::server::diagnostics_store::{DiagnosticSet, DiagnosticsStore};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{
+    Diagnostic, DiagnosticSeverity, DocumentDiagnosticReport, DocumentDiagnosticReportResult,
+    FullDocumentDiagnosticReport, NumberOrString, Position, PreviousResultId, Range,
+    TextDocumentContentChangeEvent, Url, WorkspaceDiagnosticReportResult,
+    WorkspaceDocumentDiagnosticReport,
+};
+use std::path::PathBuf;
+
+fn diagnostic(line: u32, severity: DiagnosticSeverity, code: &str, message: &str) -> Diagnostic {
+    Diagnostic {
+        range: Range::new(Position::new(line, 0), Position::new(line, 4)),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        message: message.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_diagnostic_set_deduplicates_findings() {
+    let mut set = DiagnosticSet::default();
+    assert!(set.insert(diagnostic(1, DiagnosticSeverity::WARNING, "a", "m")));
+    assert!(!set.insert(diagnostic(1, DiagnosticSeverity::WARNING, "a", "m")));
+    // A different code or message is a different finding
+    assert!(set.insert(diagnostic(1, DiagnosticSeverity::WARNING, "b", "m")));
+    assert!(set.insert(diagnostic(1, DiagnosticSeverity::WARNING, "a", "n")));
+    assert_eq!(set.as_slice().len(), 3);
+
+    // The most severe copy of a finding is kept
+    assert!(!set.insert(diagnostic(1, DiagnosticSeverity::ERROR, "a", "m")));
+    assert!(!set.insert(diagnostic(1, DiagnosticSeverity::HINT, "a", "m")));
+    assert_eq!(set.as_slice().len(), 3);
+    assert_eq!(set.as_slice()[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(set.as_slice()[0].message, "m");
+}
+
+#[test]
+fn test_diagnostic_set_orders_by_range_then_severity() {
+    let set: DiagnosticSet = [
+        diagnostic(3, DiagnosticSeverity::ERROR, "x", "third"),
+        diagnostic(1, DiagnosticSeverity::HINT, "x", "hint"),
+        diagnostic(1, DiagnosticSeverity::ERROR, "x", "error"),
+        diagnostic(1, DiagnosticSeverity::WARNING, "x", "warning"),
+    ]
+    .into_iter()
+    .collect();
+
+    let messages: Vec<&str> = set.as_slice().iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(messages, vec!["error", "warning", "hint", "third"]);
+
+    // Insertion order doesn't change the result
+    let reversed: DiagnosticSet = set.as_slice().iter().rev().cloned().collect();
+    assert_eq!(reversed, set);
+}
+
+#[test]
+fn test_diagnostics_store_reports_changes_only() {
+    let mut store = DiagnosticsStore::default();
+    let path = PathBuf::from("/test.sysml");
+    let set: DiagnosticSet = [diagnostic(0, DiagnosticSeverity::ERROR, "x", "m")]
+        .into_iter()
+        .collect();
+
+    assert_eq!(
+        store.update(path.clone(), set.clone()).map(|d| d.len()),
+        Some(1)
+    );
+    assert_eq!(store.update(path.clone(), set.clone()), None);
+    assert_eq!(
+        store.update(path.clone(), DiagnosticSet::default()),
+        Some(Vec::new())
+    );
+
+    // A forgotten document is published again
+    store.remove(&path);
+    assert!(store.update(path, DiagnosticSet::default()).is_some());
+}
+
+#[test]
+fn test_diagnostics_to_publish_skips_unchanged_documents() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, "part def A {").unwrap();
+
+    let published = server.diagnostics_to_publish(&uri).unwrap();
+    assert!(!published.is_empty());
+    assert_eq!(published, server.get_diagnostics(&uri));
+    assert_eq!(server.diagnostics_to_publish(&uri), None);
+
+    // Reopening a closed document publishes its diagnostics again
+    server.close_document(&uri).unwrap();
+    server.open_document(&uri, "part def A {").unwrap();
+    assert_eq!(server.diagnostics_to_publish(&uri), Some(published));
+}
+
+fn full_report(result: DocumentDiagnosticReportResult) -> FullDocumentDiagnosticReport {
+    match result {
+        DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(report)) => {
+            report.full_document_diagnostic_report
+        }
+        other => panic!("expected a full report, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_pull_diagnostics_reports_unchanged_result_ids() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, "part def A {").unwrap();
+
+    let first = full_report(server.get_document_diagnostic_report(&uri, None));
+    assert!(!first.items.is_empty());
+    let result_id = first.result_id.unwrap();
+
+    match server.get_document_diagnostic_report(&uri, Some(&result_id)) {
+        DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Unchanged(report)) => {
+            assert_eq!(
+                report.unchanged_document_diagnostic_report.result_id,
+                result_id
+            );
+        }
+        other => panic!("expected an unchanged report, got {other:?}"),
+    }
+
+    // Edits still waiting for the debounce are parsed before reporting
+    let change = TextDocumentContentChangeEvent {
+        range: None,
+        range_length: None,
+        text: "part def A;".to_string(),
+    };
+    server.apply_text_change_only(&uri, &change).unwrap();
+    let fixed = full_report(server.get_document_diagnostic_report(&uri, Some(&result_id)));
+    assert!(fixed.items.is_empty(), "{:?}", fixed.items);
+    assert_ne!(fixed.result_id, Some(result_id));
+}
+
+#[test]
+fn test_pull_diagnostics_disable_publishing() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, "part def A {").unwrap();
+
+    server.set_pull_diagnostics(true);
+    assert_eq!(server.diagnostics_to_publish(&uri), None);
+    assert!(!server.diagnostic_refresh_support());
+    server.set_diagnostic_refresh_support(true);
+    assert!(server.diagnostic_refresh_support());
+}
+
+#[test]
+fn test_workspace_diagnostics_cover_open_documents() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.set_document_version(&uri, 3);
+    server.open_document(&uri, "part def A {").unwrap();
+
+    let WorkspaceDiagnosticReportResult::Report(report) =
+        server.get_workspace_diagnostic_report(&[])
+    else {
+        panic!("expected a report");
+    };
+    let result_id = match &report.items[..] {
+        [WorkspaceDocumentDiagnosticReport::Full(full)] => {
+            assert_eq!(full.uri, uri);
+            assert_eq!(full.version, Some(3));
+            full.full_document_diagnostic_report
+                .result_id
+                .clone()
+                .unwrap()
+        }
+        other => panic!("expected one full report, got {other:?}"),
+    };
+
+    let previous = [PreviousResultId {
+        uri: uri.clone(),
+        value: result_id,
+    }];
+    let WorkspaceDiagnosticReportResult::Report(report) =
+        server.get_workspace_diagnostic_report(&previous)
+    else {
+        panic!("expected a report");
+    };
+    assert!(matches!(
+        &report.items[..],
+        [WorkspaceDocumentDiagnosticReport::Unchanged(_)]
+    ));
+}
+
+#[test]
+fn test_unchanged_workspace_diagnostics_are_held() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, "part def A;").unwrap();
+
+    // A first pull is always answered
+    let Some(WorkspaceDiagnosticReportResult::Report(report)) =
+        server.changed_workspace_diagnostics(&[])
+    else {
+        panic!("expected a report");
+    };
+    let [WorkspaceDocumentDiagnosticReport::Full(full)] = &report.items[..] else {
+        panic!("expected one full report");
+    };
+    let previous = [PreviousResultId {
+        uri: uri.clone(),
+        value: full
+            .full_document_diagnostic_report
+            .result_id
+            .clone()
+            .unwrap(),
+    }];
+    assert!(server.changed_workspace_diagnostics(&previous).is_none());
+
+    let change = TextDocumentContentChangeEvent {
+        range: None,
+        range_length: None,
+        text: "part def A {".to_string(),
+    };
+    server.apply_text_change_only(&uri, &change).unwrap();
+    server.parse_document(&uri);
+    assert!(server.changed_workspace_diagnostics(&previous).is_some());
+}
@@ -0,0 +1,172 @@
+//! Tests for the relationships of syster/getDiagram
+
+use crate::server::diagram::{DiagramData, GetDiagramParams};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::Url;
+
+const SOURCE: &str = "package Vehicles {
+    part def Engine { part cylinders[4]; }
+    part def Vehicle { part engine : Engine; }
+    part def Car :> Vehicle {
+        part engine :>> engine;
+        part sportEngine : Engine :> engine;
+        part frontCylinders :> engine.cylinders;
+    }
+}";
+
+fn diagram() -> DiagramData {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Vehicles.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+    server.get_diagram(
+        Some(&uri.to_file_path().unwrap()),
+        None,
+        None,
+        "GeneralView",
+    )
+}
+
+fn edges<'a>(data: &'a DiagramData, rel_type: &str) -> Vec<(&'a str, &'a str)> {
+    let mut edges: Vec<_> = data
+        .relationships
+        .iter()
+        .filter(|rel| rel.rel_type == rel_type)
+        .map(|rel| (rel.source.as_str(), rel.target.as_str()))
+        .collect();
+    edges.sort();
+    edges
+}
+
+#[test]
+fn test_diagram_typing_edges() {
+    let data = diagram();
+    assert_eq!(
+        edges(&data, "typing"),
+        [
+            ("Vehicles::Car::sportEngine", "Vehicles::Engine"),
+            ("Vehicles::Vehicle::engine", "Vehicles::Engine"),
+        ]
+    );
+}
+
+#[test]
+fn test_diagram_specialization_edges() {
+    let data = diagram();
+    // Specialization isn't typing
+    assert_eq!(
+        edges(&data, "specialization"),
+        [("Vehicles::Car", "Vehicles::Vehicle")]
+    );
+}
+
+#[test]
+fn test_diagram_redefinition_and_subsetting_edges() {
+    let data = diagram();
+    assert_eq!(
+        edges(&data, "redefinition"),
+        [("Vehicles::Car::engine", "Vehicles::Vehicle::engine")]
+    );
+    // A chain's edge goes to the feature at its end
+    let subsetting = edges(&data, "subsetting");
+    assert!(
+        subsetting.iter().any(|(source, target)| {
+            *source == "Vehicles::Car::frontCylinders" && target.ends_with("cylinders")
+        }),
+        "got {subsetting:?}"
+    );
+}
+
+#[test]
+fn test_diagram_has_no_redefinition_edge_to_itself() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Lone.sysml").unwrap();
+    server
+        .open_document(
+            &uri,
+            "package Lone {\n    part def A {\n        part x :>> x;\n    }\n}",
+        )
+        .unwrap();
+    let data = server.get_diagram(
+        Some(&uri.to_file_path().unwrap()),
+        None,
+        None,
+        "GeneralView",
+    );
+
+    assert!(
+        data.relationships
+            .iter()
+            .all(|rel| rel.source != rel.target),
+        "got {:?}",
+        data.relationships
+    );
+}
+
+#[test]
+fn test_diagram_composition_edges() {
+    let data = diagram();
+    let composition = edges(&data, "composition");
+    assert!(composition.contains(&("Vehicles::Vehicle", "Vehicles::Vehicle::engine")));
+    assert!(composition.contains(&("Vehicles::Engine", "Vehicles::Engine::cylinders")));
+    // Package members are nested, not composed
+    assert!(
+        composition.iter().all(|(owner, _)| *owner != "Vehicles"),
+        "got {composition:?}"
+    );
+}
+
+fn scoped_names(root: &str, depth: Option<usize>) -> Vec<String> {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Vehicles.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+    let data = server.get_diagram(
+        Some(&uri.to_file_path().unwrap()),
+        Some(root),
+        depth,
+        "GeneralView",
+    );
+    let mut names: Vec<String> = data
+        .symbols
+        .into_iter()
+        .map(|symbol| symbol.qualified_name)
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_diagram_scoped_to_element_by_depth() {
+    assert_eq!(scoped_names("Vehicles", Some(0)), ["Vehicles"]);
+    assert_eq!(
+        scoped_names("Vehicles", Some(1)),
+        [
+            "Vehicles",
+            "Vehicles::Car",
+            "Vehicles::Engine",
+            "Vehicles::Vehicle"
+        ]
+    );
+    assert!(scoped_names("Vehicles", Some(2)).contains(&"Vehicles::Engine::cylinders".to_string()));
+    assert_eq!(
+        scoped_names("Vehicles", None),
+        scoped_names("Vehicles", Some(2))
+    );
+    // A feature without members is shown on its own
+    assert_eq!(
+        scoped_names("Vehicles::Car::engine", None),
+        ["Vehicles::Car::engine"]
+    );
+}
+
+#[test]
+fn test_diagram_params_accept_root_qualified_name() {
+    let params: GetDiagramParams = serde_json::from_value(serde_json::json!({
+        "uri": "file:///test/Vehicles.sysml",
+        "rootQualifiedName": "Vehicles::Car",
+        "depth": 1
+    }))
+    .unwrap();
+    assert_eq!(params.element.as_deref(), Some("Vehicles::Car"));
+    assert_eq!(params.depth, Some(1));
+    assert_eq!(params.view_type, "GeneralView");
+}
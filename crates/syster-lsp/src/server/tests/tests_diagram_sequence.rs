@@ -0,0 +1,109 @@
+//! Tests for sequence view data in syster/getDiagram
+
+use crate::server::diagram_sequence::{Activation, SEQUENCE_VIEW, SequenceDiagram};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::Url;
+
+const SOURCE: &str = r#"package Interaction {
+    part def Driver;
+    part def Vehicle;
+    part startSequence {
+        part driver : Driver;
+        part vehicle : Vehicle;
+        // message of ignored from a.b to c.d;
+        message ignite of ignitionCmd : IgnitionCmd from driver.turnVehicleOn to vehicle.trigger1;
+        message report of es : EngineStatus from vehicle.sendStatus to driver.trigger2;
+        message honk of Honk from driver.horn to vehicle.speaker;
+        first honk then report;
+    }
+    part other {
+        message of Ignored from x.a to y.b;
+    }
+}"#;
+
+fn sequence(element: Option<&str>, view_type: &str) -> Option<SequenceDiagram> {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Interaction.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+    let path = uri.to_file_path().unwrap();
+    server
+        .get_diagram(Some(&path), element, None, view_type)
+        .sequence
+}
+
+#[test]
+fn test_sequence_only_for_sequence_view() {
+    assert!(sequence(None, "GeneralView").is_none());
+    assert!(sequence(None, SEQUENCE_VIEW).is_some());
+}
+
+#[test]
+fn test_sequence_messages_follow_successions() {
+    let sequence = sequence(Some("Interaction::startSequence"), SEQUENCE_VIEW).unwrap();
+
+    let names: Vec<_> = sequence
+        .messages
+        .iter()
+        .map(|message| message.name.as_deref().unwrap())
+        .collect();
+    assert_eq!(names, ["ignite", "honk", "report"]);
+
+    let ignite = &sequence.messages[0];
+    assert_eq!(ignite.payload.as_deref(), Some("ignitionCmd"));
+    assert_eq!(ignite.payload_type.as_deref(), Some("IgnitionCmd"));
+    assert_eq!(ignite.from, "driver");
+    assert_eq!(ignite.from_event.as_deref(), Some("turnVehicleOn"));
+    assert_eq!(ignite.to, "vehicle");
+    assert_eq!(ignite.to_event.as_deref(), Some("trigger1"));
+    assert_eq!(sequence.messages[1].payload, None);
+    assert_eq!(sequence.messages[1].payload_type.as_deref(), Some("Honk"));
+}
+
+#[test]
+fn test_sequence_lifelines_and_activations() {
+    let sequence = sequence(Some("Interaction::startSequence"), SEQUENCE_VIEW).unwrap();
+
+    let lifelines: Vec<_> = sequence
+        .lifelines
+        .iter()
+        .map(|lifeline| (lifeline.name.as_str(), lifeline.qualified_name.as_deref()))
+        .collect();
+    assert_eq!(
+        lifelines,
+        [
+            ("driver", Some("Interaction::startSequence::driver")),
+            ("vehicle", Some("Interaction::startSequence::vehicle")),
+        ]
+    );
+
+    // Both lifelines take part in every message
+    assert_eq!(
+        sequence.activations,
+        [
+            Activation {
+                lifeline: "driver".to_string(),
+                first_message: 0,
+                last_message: 2,
+            },
+            Activation {
+                lifeline: "vehicle".to_string(),
+                first_message: 0,
+                last_message: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_sequence_of_whole_file() {
+    let sequence = sequence(None, SEQUENCE_VIEW).unwrap();
+
+    // Messages of every part, and lifelines without declared parts
+    assert_eq!(sequence.messages.len(), 4);
+    let x = sequence
+        .lifelines
+        .iter()
+        .find(|lifeline| lifeline.name == "x")
+        .unwrap();
+    assert_eq!(x.qualified_name, None);
+}
@@ -0,0 +1,112 @@
+//! Tests for the syster/exportDiagramText request
+
+use crate::server::LspServer;
+use crate::server::diagram_text::{DiagramTextFormat, ExportDiagramTextParams};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::Url;
+use std::path::PathBuf;
+
+const SOURCE: &str = "package Vehicles {\n    part def Engine;\n    part def Vehicle {\n        part engine : Engine;\n    }\n}\npackage Other {\n    part def Wheel;\n}";
+
+fn open_model() -> (LspServer, PathBuf) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Vehicles.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+    (server, uri.to_file_path().unwrap())
+}
+
+fn export(view_type: &str, format: DiagramTextFormat) -> String {
+    let (mut server, path) = open_model();
+    server
+        .export_diagram_text(Some(&path), Some("Vehicles"), view_type, format)
+        .text
+}
+
+#[test]
+fn test_export_params_deserialize() {
+    let json = r#"{"uri": "file:///test.sysml", "format": "plantuml"}"#;
+    let params: ExportDiagramTextParams = serde_json::from_str(json).unwrap();
+    assert_eq!(params.format, DiagramTextFormat::PlantUml);
+    assert_eq!(params.view_type, "GeneralView");
+
+    let json = r#"{"format": "mermaid", "viewType": "BrowserView"}"#;
+    let params: ExportDiagramTextParams = serde_json::from_str(json).unwrap();
+    assert_eq!(params.format, DiagramTextFormat::Mermaid);
+    assert_eq!(params.uri, None);
+}
+
+#[test]
+fn test_export_general_view_plantuml() {
+    let text = export("GeneralView", DiagramTextFormat::PlantUml);
+
+    assert!(text.starts_with("@startuml\n"), "{text}");
+    assert!(text.ends_with("@enduml\n"), "{text}");
+    assert!(
+        text.contains("class \"Vehicle\" as Vehicles__Vehicle <<part def>>"),
+        "{text}"
+    );
+    assert!(
+        text.contains("object \"engine : Engine\" as Vehicles__Vehicle__engine <<part>>"),
+        "{text}"
+    );
+    assert!(
+        text.contains("Vehicles__Vehicle *-- Vehicles__Vehicle__engine"),
+        "{text}"
+    );
+    assert!(
+        text.contains("Vehicles__Vehicle__engine ..> Vehicles__Engine : typed by"),
+        "{text}"
+    );
+    // Elements outside the scope are left out
+    assert!(!text.contains("Wheel"), "{text}");
+}
+
+#[test]
+fn test_export_general_view_mermaid() {
+    let text = export("GeneralView", DiagramTextFormat::Mermaid);
+
+    assert!(text.starts_with("classDiagram\n"), "{text}");
+    assert!(
+        text.contains("    class Vehicles__Engine[\"Engine\"]\n    <<part def>> Vehicles__Engine"),
+        "{text}"
+    );
+    assert!(
+        text.contains("    Vehicles__Vehicle__engine ..> Vehicles__Engine : typed by"),
+        "{text}"
+    );
+}
+
+#[test]
+fn test_export_interconnection_view_nests_members() {
+    let text = export("InterconnectionView", DiagramTextFormat::PlantUml);
+    assert!(
+        text.contains(
+            "  rectangle \"Vehicle\" as Vehicles__Vehicle {\n    rectangle \"engine : Engine\" as Vehicles__Vehicle__engine\n  }"
+        ),
+        "{text}"
+    );
+
+    let text = export("InterconnectionView", DiagramTextFormat::Mermaid);
+    assert!(text.starts_with("flowchart LR\n"), "{text}");
+    assert!(
+        text.contains(
+            "        subgraph Vehicles__Vehicle[\"Vehicle\"]\n            Vehicles__Vehicle__engine[\"engine : Engine\"]\n        end"
+        ),
+        "{text}"
+    );
+}
+
+#[test]
+fn test_export_browser_view_as_mind_map() {
+    let text = export("BrowserView", DiagramTextFormat::PlantUml);
+    assert_eq!(
+        text,
+        "@startmindmap\n* Vehicles\n** Engine\n** Vehicle\n*** engine : Engine\n@endmindmap\n"
+    );
+
+    let text = export("BrowserView", DiagramTextFormat::Mermaid);
+    assert!(
+        text.starts_with("mindmap\n  root((Model))\n    Vehicles[Vehicles]\n"),
+        "{text}"
+    );
+}
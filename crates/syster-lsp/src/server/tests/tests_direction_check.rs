@@ -0,0 +1,119 @@
+//! Tests for parameter direction diagnostics in action bodies
+
+use crate::server::direction_check::{DIRECTION_MISUSE_CODE, DirectionIssue, check_directions};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{DiagnosticSeverity, NumberOrString, Position, Url};
+
+/// Direction issues of `text`, checked against its symbols
+fn direction_issues(text: &str) -> Vec<DirectionIssue> {
+    let mut server = create_server();
+    let uri = Url::parse("file:///actions.sysml").unwrap();
+    server.open_document(&uri, text).unwrap();
+    let analysis = server.analysis_host.analysis();
+    check_directions(analysis.symbol_index(), text)
+}
+
+#[test]
+fn test_valid_flows_have_no_issues() {
+    let text = r#"package P {
+    action def Provide { out item fuel; }
+    action def Consume { in item fuel; }
+    action def Pipeline {
+        in item source;
+        out item result;
+        action provide : Provide;
+        action consume : Consume;
+        flow provide.fuel to consume.fuel;
+        bind source = consume.fuel;
+        bind result = provide.fuel;
+        flow result to consume.fuel;
+    }
+}"#;
+    assert_eq!(direction_issues(text), vec![]);
+}
+
+#[test]
+fn test_write_to_in_parameter() {
+    let text = "action def A {\n    in x;\n    out y;\n    flow y to x;\n}";
+    let issues = direction_issues(text);
+
+    let write = issues
+        .iter()
+        .find(|i| i.message.contains("Cannot write to in parameter 'x'"))
+        .expect("write to in parameter should be reported");
+    assert_eq!(write.severity, DiagnosticSeverity::ERROR);
+    assert_eq!(write.range.start, Position::new(3, 14));
+    assert_eq!(write.range.end, Position::new(3, 15));
+}
+
+#[test]
+fn test_read_out_parameter_before_produced() {
+    let text = r#"action def Step { in a; out b; }
+action def A {
+    in x;
+    out y;
+    action step : Step;
+    flow y to step.a;
+    flow step.b to y;
+    flow y to step.a;
+}"#;
+    let issues = direction_issues(text);
+
+    assert_eq!(
+        issues.len(),
+        1,
+        "Only the first read is too early: {issues:?}"
+    );
+    assert_eq!(issues[0].severity, DiagnosticSeverity::WARNING);
+    assert!(issues[0].message.contains("'y'"));
+    assert_eq!(issues[0].range.start.line, 5);
+}
+
+#[test]
+fn test_nested_action_parameter_directions() {
+    let text = r#"action def A {
+    action step {
+        in a;
+        out b;
+    }
+    action other {
+        in c;
+    }
+    flow step.a to other.c;
+    flow other.c to step.b;
+    assign step.b := 1;
+}"#;
+    let messages: Vec<_> = direction_issues(text)
+        .into_iter()
+        .map(|i| i.message)
+        .collect();
+
+    assert!(messages.contains(&"Cannot read from in parameter 'step.a'".to_string()));
+    assert!(messages.contains(&"Cannot read from in parameter 'other.c'".to_string()));
+    assert!(messages.contains(&"Cannot write to out parameter 'step.b'".to_string()));
+    assert_eq!(messages.len(), 4, "{messages:?}");
+}
+
+#[test]
+fn test_comments_and_strings_are_ignored() {
+    let text = "action def A {\n    in x;\n    // flow x to x;\n    /* assign x := 1; */\n    doc /* flow a to x; */\n}";
+    assert!(direction_issues(text).is_empty());
+}
+
+#[test]
+fn test_direction_misuse_reported_as_diagnostic() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///actions.sysml").unwrap();
+    let text = "package P {\n    action def A {\n        in x;\n        assign x := 1;\n    }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = server.get_diagnostics(&uri);
+    let direction: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| d.code == Some(NumberOrString::String(DIRECTION_MISUSE_CODE.to_string())))
+        .collect();
+
+    assert_eq!(direction.len(), 1, "{diagnostics:?}");
+    assert_eq!(direction[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(direction[0].range.start, Position::new(3, 15));
+}
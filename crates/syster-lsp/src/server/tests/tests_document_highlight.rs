@@ -0,0 +1,107 @@
+//! Tests for textDocument/documentHighlight
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{DocumentHighlightKind, Position, Url};
+
+#[test]
+fn test_highlights_definition_and_references_in_file() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text =
+        "package P {\n    part def Wheel;\n    part front : Wheel;\n    part rear : Wheel;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    // Cursor on the `Wheel` definition
+    let highlights = server
+        .get_document_highlights(&uri, Position::new(1, 14))
+        .expect("should highlight Wheel");
+
+    let writes: Vec<_> = highlights
+        .iter()
+        .filter(|h| h.kind == Some(DocumentHighlightKind::WRITE))
+        .collect();
+    let reads: Vec<_> = highlights
+        .iter()
+        .filter(|h| h.kind == Some(DocumentHighlightKind::READ))
+        .collect();
+
+    assert_eq!(writes.len(), 1, "{highlights:?}");
+    assert_eq!(writes[0].range.start.line, 1);
+    assert_eq!(reads.len(), 2, "{highlights:?}");
+    assert!(reads.iter().any(|h| h.range.start == Position::new(2, 17)));
+    assert!(reads.iter().any(|h| h.range.start == Position::new(3, 16)));
+}
+
+#[test]
+fn test_highlights_only_current_file() {
+    let mut server = create_server();
+    let def_uri = Url::parse("file:///def.sysml").unwrap();
+    let use_uri = Url::parse("file:///use.sysml").unwrap();
+    server
+        .open_document(&def_uri, "package Defs {\n    part def Engine;\n}")
+        .unwrap();
+    server
+        .open_document(
+            &use_uri,
+            "package Uses {\n    import Defs::*;\n    part e : Engine;\n}",
+        )
+        .unwrap();
+
+    let highlights = server
+        .get_document_highlights(&use_uri, Position::new(2, 14))
+        .expect("should highlight Engine usage");
+
+    assert_eq!(highlights.len(), 1, "{highlights:?}");
+    assert_eq!(highlights[0].kind, Some(DocumentHighlightKind::READ));
+    assert_eq!(highlights[0].range.start, Position::new(2, 13));
+}
+
+#[test]
+fn test_highlights_fall_back_to_text() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    // speed matters\n    attribute speed;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    // Cursor inside the comment, which isn't a symbol
+    let highlights = server
+        .get_document_highlights(&uri, Position::new(1, 9))
+        .expect("should highlight matching words");
+
+    assert!(
+        highlights
+            .iter()
+            .all(|h| h.kind == Some(DocumentHighlightKind::TEXT))
+    );
+    assert_eq!(highlights.len(), 2, "{highlights:?}");
+}
+
+#[test]
+fn test_highlights_none_for_unknown_file() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///missing.sysml").unwrap();
+    assert!(
+        server
+            .get_document_highlights(&uri, Position::new(0, 0))
+            .is_none()
+    );
+}
+
+#[test]
+fn test_text_highlights_use_utf16_columns() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    // The emoji takes two UTF-16 code units
+    let text = "package P;\n// 😀 speed and speed";
+    server.open_document(&uri, text).unwrap();
+
+    let highlights = server
+        .get_document_highlights(&uri, Position::new(1, 7))
+        .expect("should highlight matching words");
+
+    let ranges: Vec<_> = highlights
+        .iter()
+        .map(|h| (h.range.start.character, h.range.end.character))
+        .collect();
+    assert_eq!(ranges, vec![(6, 11), (16, 21)]);
+}
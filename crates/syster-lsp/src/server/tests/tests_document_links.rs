@@ -1,6 +1,6 @@
 use crate::server::LspServer;
 use crate::server::tests::test_helpers::create_server;
-use async_lsp::lsp_types::Url;
+use async_lsp::lsp_types::{Position, Url};
 
 #[test]
 fn test_document_links_empty_file() {
@@ -97,15 +97,15 @@ classifier TestClass {
 
     let links = server.get_document_links(&test_uri);
 
-    // Should have a link for the import
+    // Should have a link for each segment of the import path
     assert_eq!(
         links.len(),
-        1,
-        "File with one import should have one document link"
+        2,
+        "Import of Base::DataValue should link Base and DataValue"
     );
 
     // Verify the link points to the base file
-    let target_path = links[0]
+    let target_path = links[1]
         .target
         .as_ref()
         .and_then(|u| u.to_file_path().ok())
@@ -141,18 +141,27 @@ package TestPkg {
 
     let links = server.get_document_links(&test_uri);
 
-    // Should have a link for the import
+    // Should have a link for each segment of the import path
     assert_eq!(
         links.len(),
-        1,
-        "File with one import should have one document link"
+        2,
+        "Import of Base::Vehicle should link Base and Vehicle"
     );
 
-    // Verify the link has a tooltip
+    // Each segment spans its own name and links to what it names
+    assert_eq!(links[0].range.start, Position::new(2, 11));
+    assert_eq!(links[0].range.end, Position::new(2, 15));
+    assert!(links[0].tooltip.as_ref().unwrap().ends_with("Base"));
+    assert_eq!(links[1].range.start, Position::new(2, 17));
+    assert_eq!(links[1].range.end, Position::new(2, 24));
     assert!(
-        links[0].tooltip.as_ref().unwrap().contains("Base::Vehicle"),
+        links[1].tooltip.as_ref().unwrap().contains("Base::Vehicle"),
         "Tooltip should mention the import path"
     );
+    assert!(
+        links[1].target.as_ref().unwrap().as_str().ends_with("#L3"),
+        "Link should jump to the Vehicle definition"
+    );
 }
 
 #[test]
@@ -238,11 +247,11 @@ classifier TestClass {
 
     let links = server.get_document_links(&test_uri);
 
-    // Should have links for both imports
+    // Should have links for both segments of both imports
     assert_eq!(
         links.len(),
-        2,
-        "File with two imports should have two document links"
+        4,
+        "File with two imports should have four document links"
     );
 }
 
@@ -0,0 +1,86 @@
+//! Tests for the file system injection point
+
+use crate::server::environment::{FileSystem, MemoryFileSystem};
+use crate::server::reference_index::ReferenceIndex;
+use crate::server::stdlib_cache::StdlibCache;
+use crate::server::tests::test_helpers::{LspServerTestExt, create_server};
+use async_lsp::lsp_types::{FileChangeType, FileEvent, FormattingOptions, Url};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn memory_fs() -> Arc<MemoryFileSystem> {
+    Arc::new(MemoryFileSystem::default())
+}
+
+#[test]
+fn test_memory_fs_stamps_writes_with_its_time() {
+    let fs = memory_fs();
+    let path = Path::new("/virtual/Model.sysml");
+    fs.write(path, "part def A;").unwrap();
+    let first = fs.metadata(path).unwrap();
+    assert_eq!(first.len, 11);
+
+    fs.advance(Duration::from_secs(1));
+    fs.write(path, "part def A;").unwrap();
+    assert_eq!(
+        fs.metadata(path).unwrap().modified,
+        first.modified + Duration::from_secs(1)
+    );
+
+    assert!(fs.remove(path));
+    assert!(fs.read_to_string(path).is_err());
+}
+
+#[test]
+fn test_stdlib_cache_invalidated_by_modification_time() {
+    let fs = memory_fs();
+    let files = vec![PathBuf::from("/virtual/stdlib/Base.sysml")];
+    let cache_file = Path::new("/virtual/cache/stdlib-index.json");
+    fs.write(&files[0], "package Base;").unwrap();
+
+    let cache = StdlibCache::build(fs.as_ref(), &files, &ReferenceIndex::default()).unwrap();
+    cache.save(fs.as_ref(), cache_file).unwrap();
+    assert!(StdlibCache::load(fs.as_ref(), cache_file, &files).is_some());
+
+    // Same contents, rewritten later
+    fs.advance(Duration::from_secs(1));
+    fs.write(&files[0], "package Base;").unwrap();
+    assert!(StdlibCache::load(fs.as_ref(), cache_file, &files).is_none());
+}
+
+#[test]
+fn test_watched_file_read_through_file_system() {
+    let fs = memory_fs();
+    let dir = PathBuf::from("/virtual/workspace");
+    let path = dir.join("Engine.sysml");
+    fs.write(&path, "package Engines { part def Engine; }")
+        .unwrap();
+
+    let mut server = create_server();
+    server.set_file_system(fs);
+    server.set_workspace_folders(vec![dir]);
+    server.apply_watched_file_changes(&[FileEvent {
+        uri: Url::from_file_path(&path).unwrap(),
+        typ: FileChangeType::CREATED,
+    }]);
+
+    assert!(server.has_qualified_symbol("Engines::Engine"));
+}
+
+#[test]
+fn test_project_manifest_read_through_file_system() {
+    let fs = memory_fs();
+    let dir = PathBuf::from("/virtual/project");
+    fs.write(&dir.join("syster.toml"), "[format]\ntab_size = 3\n")
+        .unwrap();
+
+    let mut server = create_server();
+    server.set_file_system(fs);
+    server.set_workspace_folders(vec![dir.clone()]);
+    server.ensure_workspace_loaded().unwrap();
+
+    let uri = Url::from_file_path(dir.join("Model.sysml")).unwrap();
+    let options = server.project_formatting_options(&uri, FormattingOptions::default());
+    assert_eq!(options.tab_size, 3);
+}
@@ -0,0 +1,71 @@
+//! Tests for go-to-definition on the segments of feature chains
+
+use crate::server::LspServer;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, Url};
+
+const MODEL: &str = "package P {
+    port def Plug;
+    part def Driver {
+        port p1 : Plug;
+    }
+    part def Person {
+        port hand : Plug;
+    }
+    action def TurnOn;
+    action def Start {
+        action turnVehicleOn : TurnOn;
+    }
+    part def Vehicle {
+        part driver : Driver;
+        part owner : Person;
+        part passenger :> owner;
+        port p2 : Plug;
+        action startVehicle : Start;
+        connect driver.p1 to p2;
+        connect passenger.hand to p2;
+        perform startVehicle.turnVehicleOn;
+    }
+}";
+
+fn definition(position: Position) -> Option<Range> {
+    let mut server: LspServer = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, MODEL).unwrap();
+    let location = server.get_definition(&uri, position)?;
+    assert_eq!(location.uri, uri);
+    Some(location.range)
+}
+
+fn name(line: u32, col: u32, len: u32) -> Option<Range> {
+    Some(Range::new(
+        Position::new(line, col),
+        Position::new(line, col + len),
+    ))
+}
+
+#[test]
+fn test_first_segment_goes_to_the_owned_feature() {
+    // `driver` in `connect driver.p1`
+    assert_eq!(definition(Position::new(18, 18)), name(13, 13, 6));
+}
+
+#[test]
+fn test_later_segment_follows_the_typing() {
+    // `p1` in `connect driver.p1` is declared by Driver
+    assert_eq!(definition(Position::new(18, 24)), name(3, 13, 2));
+    // `turnVehicleOn` in `perform startVehicle.turnVehicleOn` by Start
+    assert_eq!(definition(Position::new(20, 30)), name(10, 15, 13));
+}
+
+#[test]
+fn test_later_segment_follows_the_subsetting() {
+    // `passenger` has no type of its own; `hand` comes from owner's Person
+    assert_eq!(definition(Position::new(19, 27)), name(6, 13, 4));
+}
+
+#[test]
+fn test_names_outside_chains_are_unchanged() {
+    // `p2` after `to` is a plain name
+    assert_eq!(definition(Position::new(18, 30)), name(16, 13, 2));
+}
@@ -0,0 +1,54 @@
+//! Tests for the syster/getFeatureSupportMatrix request
+
+use crate::server::LspServer;
+use crate::server::feature_support::GetFeatureSupportMatrixParams;
+
+#[test]
+fn test_matrix_lists_each_construct_once() {
+    let matrix = LspServer::feature_support_matrix();
+
+    assert!(!matrix.server_version.is_empty());
+    let mut names: Vec<&str> = matrix
+        .constructs
+        .iter()
+        .map(|c| c.construct.as_str())
+        .collect();
+    let count = names.len();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), count);
+}
+
+#[test]
+fn test_matrix_reports_partial_support() {
+    let matrix = LspServer::feature_support_matrix();
+    let find = |name: &str| {
+        matrix
+            .constructs
+            .iter()
+            .find(|c| c.construct == name)
+            .unwrap_or_else(|| panic!("{name} missing from matrix"))
+    };
+
+    let part_def = find("Part definition");
+    assert_eq!(part_def.syntax, "part def");
+    assert!(part_def.hover && part_def.definition && part_def.semantic_tokens);
+    assert!(part_def.diagnostics);
+
+    let comment = find("Comment");
+    assert!(!comment.hover && !comment.definition && !comment.diagnostics);
+}
+
+#[test]
+fn test_matrix_serializes_in_camel_case() {
+    let json = serde_json::to_value(LspServer::feature_support_matrix()).unwrap();
+    assert!(json.get("serverVersion").is_some());
+    assert!(json["constructs"][0].get("semanticTokens").is_some());
+}
+
+#[test]
+fn test_matrix_params_accept_empty_object() {
+    let params: Result<GetFeatureSupportMatrixParams, _> =
+        serde_json::from_value(serde_json::json!({}));
+    assert!(params.is_ok());
+}
@@ -155,3 +155,307 @@ fn test_lsp_range_format_normalizes_whitespace() {
         edits[0].new_text
     );
 }
+
+fn spaces(tab_size: u32) -> FormattingOptions {
+    FormattingOptions {
+        tab_size,
+        insert_spaces: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_range_format_aligns_with_enclosing_block() {
+    let source = "package Test {\n    part def Vehicle {\npart   engine : Engine;\n  part wheels;\n    }\n    part def   Other;\n}";
+    // Selection starts mid-line and ends at the start of the next line
+    let range = Range::new(Position::new(2, 4), Position::new(4, 0));
+
+    let edits = format_range_text(source, spaces(4), &CancellationToken::new(), range).unwrap();
+
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].range,
+        Range::new(Position::new(2, 0), Position::new(3, 14))
+    );
+    assert_eq!(
+        edits[0].new_text,
+        "        part engine : Engine;\n        part wheels;"
+    );
+}
+
+#[test]
+fn test_range_format_whole_definition() {
+    let source =
+        "package Test {\n  part def Vehicle {\n part engine;\n }\n    part def   Other;\n}";
+    let range = Range::new(Position::new(1, 0), Position::new(3, 2));
+
+    let edits = format_range_text(source, spaces(2), &CancellationToken::new(), range).unwrap();
+
+    assert_eq!(
+        edits[0].new_text,
+        "  part def Vehicle {\n    part engine;\n  }"
+    );
+    assert_eq!(edits[0].range.end, Position::new(3, 2));
+}
+
+#[test]
+fn test_range_format_uses_tabs() {
+    let source = "package Test {\npart   a;\n}";
+    let options = FormattingOptions {
+        tab_size: 4,
+        insert_spaces: false,
+        ..Default::default()
+    };
+    let range = Range::new(Position::new(1, 0), Position::new(1, 9));
+
+    let edits = format_range_text(source, options, &CancellationToken::new(), range).unwrap();
+
+    assert_eq!(edits[0].new_text, "\tpart a;");
+}
+
+#[test]
+fn test_range_format_ignores_braces_in_comments() {
+    let source = "package Test {\n    // not a block {\n    doc /* } */\npart   a;\n}";
+    let range = Range::new(Position::new(3, 0), Position::new(3, 9));
+
+    let edits = format_range_text(source, spaces(4), &CancellationToken::new(), range).unwrap();
+
+    assert_eq!(edits[0].new_text, "    part a;");
+}
+
+#[test]
+fn test_range_format_skips_partial_blocks() {
+    let source = "package Test {\n    part def Vehicle {\n        part engine;\n    }\n}";
+    // Only the opening line of a block
+    let range = Range::new(Position::new(1, 0), Position::new(2, 0));
+
+    assert!(format_range_text(source, spaces(4), &CancellationToken::new(), range).is_none());
+}
+
+#[test]
+fn test_range_format_already_formatted() {
+    let source = "package Test {\n    part a;\n}";
+    let range = Range::new(Position::new(1, 0), Position::new(1, 11));
+
+    assert!(format_range_text(source, spaces(4), &CancellationToken::new(), range).is_none());
+}
+
+#[test]
+fn test_on_type_close_brace_reindents_block() {
+    let source = "package Test {\n    part def Vehicle {\npart engine;\n      part   wheels;\n}\n}";
+    let edits = format_on_type_text(
+        source,
+        spaces(4),
+        &CancellationToken::new(),
+        Position::new(4, 1),
+        "}",
+    )
+    .unwrap();
+
+    assert_eq!(
+        edits[0].range,
+        Range::new(Position::new(1, 0), Position::new(4, 1))
+    );
+    assert_eq!(
+        edits[0].new_text,
+        "    part def Vehicle {\n        part engine;\n        part wheels;\n    }"
+    );
+}
+
+#[test]
+fn test_on_type_semicolon_normalizes_line() {
+    let source = "package Test {\n    part a;\n  part   b :   B;\n}";
+    let edits = format_on_type_text(
+        source,
+        spaces(4),
+        &CancellationToken::new(),
+        Position::new(2, 17),
+        ";",
+    )
+    .unwrap();
+
+    assert_eq!(
+        edits[0].range,
+        Range::new(Position::new(2, 0), Position::new(2, 17))
+    );
+    assert_eq!(edits[0].new_text, "    part b : B;");
+}
+
+#[test]
+fn test_on_type_leaves_other_lines_alone() {
+    let source = "package Test {\n    part   a;\n    part b;\n}";
+    let result = format_on_type_text(
+        source,
+        spaces(4),
+        &CancellationToken::new(),
+        Position::new(2, 11),
+        ";",
+    );
+    assert!(result.is_none(), "Current line is already formatted");
+
+    let result = format_on_type_text(
+        source,
+        spaces(4),
+        &CancellationToken::new(),
+        Position::new(2, 11),
+        "x",
+    );
+    assert!(result.is_none());
+}
+
+fn blank_line_options(max_blank_lines: Option<i32>) -> FormattingOptions {
+    let mut options = FormattingOptions {
+        tab_size: 4,
+        insert_spaces: true,
+        ..Default::default()
+    };
+    if let Some(n) = max_blank_lines {
+        options.properties.insert(
+            PROP_MAX_BLANK_LINES.to_string(),
+            async_lsp::lsp_types::FormattingProperty::Number(n),
+        );
+    }
+    options
+}
+
+fn format_with_blank_lines(source: &str, max_blank_lines: Option<i32>) -> String {
+    format_text(
+        source,
+        blank_line_options(max_blank_lines),
+        &CancellationToken::new(),
+    )
+    .map(|edits| edits[0].new_text.clone())
+    .unwrap_or_else(|| source.to_string())
+}
+
+#[test]
+fn test_format_preserves_blank_line_groups_up_to_limit() {
+    let source = "package Test {\n    part a;\n\n\n\n    part b;\n}";
+
+    let default = format_with_blank_lines(source, None);
+    assert!(
+        default.contains("part a;\n\n    part b;"),
+        "Default keeps one blank line. Got: |{default}|"
+    );
+
+    let two = format_with_blank_lines(source, Some(2));
+    assert!(
+        two.contains("part a;\n\n\n    part b;"),
+        "Should keep two blank lines. Got: |{two}|"
+    );
+    assert!(!two.contains("\n\n\n\n"), "Should cap at two. Got: |{two}|");
+}
+
+#[test]
+fn test_format_keeps_blank_lines_around_doc_comments() {
+    let source = "package Test {\n    part a;\n\n\n\n    doc /* About b */\n    part b;\n}";
+
+    let result = format_with_blank_lines(source, Some(1));
+    assert!(
+        result.contains("part a;\n\n\n\n    doc /* About b */"),
+        "Blank lines before a doc comment are never collapsed. Got: |{result}|"
+    );
+}
+
+#[test]
+fn test_preserve_blank_lines_never_removes_or_joins_lines() {
+    // Gaps the formatter joined (e.g. before `{`) stay joined
+    let original = "part def A\n\n\n{\n}";
+    let formatted = "part def A {\n}";
+    assert_eq!(preserve_blank_lines(original, formatted, 3), formatted);
+
+    // Existing blank lines in the formatted text are left alone
+    let formatted = "part a;\n\n\npart b;";
+    assert_eq!(
+        preserve_blank_lines("part a;\npart b;", formatted, 0),
+        formatted
+    );
+}
+
+#[test]
+fn test_format_keeps_format_off_region() {
+    let source = "package Test {\n// syster:format off\npart   a;\n  part    b;\n// syster:format on\npart   c;\n}";
+
+    let edits = format_text(source, spaces(4), &CancellationToken::new()).unwrap();
+    let result = &edits[0].new_text;
+    assert!(
+        result.contains("\npart   a;\n  part    b;\n"),
+        "The region keeps its lines. Got: |{result}|"
+    );
+    assert!(
+        result.contains("    part c;"),
+        "Lines after the region are formatted. Got: |{result}|"
+    );
+}
+
+#[test]
+fn test_format_off_without_on_skips_rest_of_file() {
+    let source = "// syster:format off\npackage   Generated {\npart   a;\n}";
+    assert!(format_text(source, spaces(4), &CancellationToken::new()).is_none());
+}
+
+#[test]
+fn test_range_format_skips_format_off_region() {
+    let source = "package Test {\n    // syster:format off\npart   a;\n    // syster:format on\npart   b;\n}";
+    let cancel = CancellationToken::new();
+
+    let inside = Range::new(Position::new(2, 0), Position::new(2, 9));
+    assert!(format_range_text(source, spaces(4), &cancel, inside).is_none());
+
+    let outside = Range::new(Position::new(4, 0), Position::new(4, 9));
+    let edits = format_range_text(source, spaces(4), &cancel, outside).unwrap();
+    assert_eq!(edits[0].new_text, "    part b;");
+}
+
+#[test]
+fn test_format_large_file_in_chunks() {
+    let count = CHUNKED_FORMAT_THRESHOLD / 10;
+    let mut source = String::from("package   Big {\n");
+    for i in 0..count {
+        source.push_str(&format!("part   a{i} ;\n"));
+        if i % 100 == 0 {
+            source.push('\n');
+        }
+    }
+    source.push_str("}\npart def   After;\n");
+    assert!(source.len() > CHUNKED_FORMAT_THRESHOLD);
+
+    let edits = format_text(&source, spaces(4), &CancellationToken::new()).unwrap();
+    let result = &edits[0].new_text;
+    assert!(
+        result.starts_with("package Big {\n    part a0;\n\n    part a1;\n    part a2;\n"),
+        "Members are formatted inside their package. Got: |{}|",
+        &result[..80]
+    );
+    assert!(result.contains(&format!("    part a{};\n}}\n", count - 1)));
+    assert!(result.ends_with("}\npart def After;\n"));
+    assert_eq!(result.lines().count(), source.lines().count());
+}
+
+#[test]
+fn test_format_large_file_cancelled() {
+    let source = "part   a;\n".repeat(CHUNKED_FORMAT_THRESHOLD / 8);
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    assert!(format_text(&source, spaces(4), &cancel).is_none());
+}
+
+#[test]
+fn test_format_large_file_keeps_trailing_comments_with_members() {
+    let mut source = String::from("package Big {\n");
+    for i in 0..CHUNKED_FORMAT_THRESHOLD / 20 {
+        source.push_str(&format!("part   a{i} ; // note {i}\n"));
+    }
+    source.push_str("}\n");
+
+    let edits = format_text(&source, spaces(4), &CancellationToken::new()).unwrap();
+    let result = &edits[0].new_text;
+    assert_eq!(result.lines().count(), source.lines().count());
+    for line in result.lines().filter(|line| line.contains("// note")) {
+        let i = line.rsplit(' ').next().unwrap();
+        assert!(
+            line.starts_with(&format!("    part a{i};")),
+            "Comment moved off its member: |{line}|"
+        );
+    }
+}
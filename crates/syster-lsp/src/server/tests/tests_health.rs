@@ -0,0 +1,103 @@
+//! Tests for the syster/healthCheck request
+
+use crate::server::LspServer;
+use crate::server::health::HealthCheckParams;
+use crate::server::tests::test_helpers::{create_server, create_server_with_stdlib};
+use async_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+
+#[test]
+fn test_not_ready_before_workspace_loads() {
+    let server = create_server();
+    let status = server.health_check();
+
+    assert!(!status.ready);
+    assert!(!status.stdlib_loaded);
+    assert!(!status.indexing);
+    assert_eq!(status.pending_documents, 0);
+}
+
+#[test]
+fn test_not_ready_while_indexing() {
+    let mut server = create_server();
+    let _job = server.begin_background_indexing().unwrap();
+
+    let status = server.health_check();
+    assert!(!status.ready);
+    assert!(status.indexing);
+}
+
+#[test]
+fn test_ready_once_workspace_loaded() {
+    let mut server = create_server();
+    server.ensure_workspace_loaded().unwrap();
+
+    let status = server.health_check();
+    assert!(status.ready);
+    // The stdlib is disabled for this server
+    assert!(!status.stdlib_loaded);
+}
+
+#[test]
+fn test_stdlib_reported_loaded() {
+    assert!(
+        !LspServer::with_config(true, None)
+            .health_check()
+            .stdlib_loaded
+    );
+
+    let status = create_server_with_stdlib().health_check();
+    assert!(status.stdlib_loaded);
+    assert!(status.ready);
+}
+
+#[test]
+fn test_edited_documents_pending_until_parsed() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, "part def A;").unwrap();
+
+    let change = TextDocumentContentChangeEvent {
+        range: Some(Range::new(Position::new(0, 9), Position::new(0, 10))),
+        range_length: None,
+        text: "B".to_string(),
+    };
+    server.apply_text_change_only(&uri, &change).unwrap();
+    server.apply_text_change_only(&uri, &change).unwrap();
+    assert_eq!(server.health_check().pending_documents, 1);
+
+    server.parse_document(&uri);
+    assert_eq!(server.health_check().pending_documents, 0);
+}
+
+#[test]
+fn test_errors_counted_since_start() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, "part def A;").unwrap();
+    server.open_document(&uri, "part def {").unwrap();
+    server.record_document_error();
+
+    let errors = server.health_check().errors;
+    assert_eq!(errors.parse_errors, 1);
+    assert_eq!(errors.document_errors, 1);
+
+    // Fixing the document doesn't reset the counts
+    server.open_document(&uri, "part def A;").unwrap();
+    assert_eq!(server.health_check().errors.parse_errors, 1);
+}
+
+#[test]
+fn test_params_accept_empty_object() {
+    let params: Result<HealthCheckParams, _> = serde_json::from_value(serde_json::json!({}));
+    assert!(params.is_ok());
+}
+
+#[test]
+fn test_status_serializes_camel_case() {
+    let status = create_server().health_check();
+    let json = serde_json::to_value(status).unwrap();
+
+    assert!(json.get("stdlibLoaded").is_some());
+    assert!(json.get("pendingDocuments").is_some());
+    assert!(json["errors"].get("parseErrors").is_some());
+}
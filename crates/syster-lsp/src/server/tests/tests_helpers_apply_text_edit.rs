@@ -5,6 +5,7 @@
 //! Tests the application of text edits based on LSP Range.
 //! The function converts LSP Position (line, character) to byte offset and performs the edit.
 
+use crate::server::LspError;
 use crate::server::helpers::apply_text_edit;
 use async_lsp::lsp_types::{Position, Range};
 
@@ -158,8 +159,9 @@ fn test_apply_text_edit_invalid_range_start_after_end() {
     // Start after end
     let range = Range::new(Position::new(0, 5), Position::new(0, 3));
     let result = apply_text_edit(text, &range, "test");
-    assert!(result.is_err());
-    assert!(result.unwrap_err().contains("Invalid range"));
+    let err = result.unwrap_err();
+    assert!(matches!(err, LspError::InvalidRange(_)));
+    assert!(err.to_string().contains("Invalid range"));
 }
 
 #[test]
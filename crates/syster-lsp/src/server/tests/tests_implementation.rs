@@ -0,0 +1,91 @@
+//! Tests for go to implementation and go to type definition
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Location, Position, Range, Url};
+
+const SOURCE: &str = r#"package P {
+    part def Vehicle;
+    part def Car :> Vehicle;
+    part car : Vehicle;
+    part def Fleet {
+        part vehicles : Vehicle[*];
+    }
+    part def CarFleet :> Fleet {
+        part :>> vehicles : Car;
+    }
+}"#;
+
+fn open_source() -> (crate::server::LspServer, Url) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///implementation.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+    (server, uri)
+}
+
+fn lines(locations: &[Location]) -> Vec<u32> {
+    locations
+        .iter()
+        .map(|location| location.range.start.line)
+        .collect()
+}
+
+#[test]
+fn test_implementations_of_definition() {
+    let (mut server, uri) = open_source();
+
+    // `Vehicle` is specialized by Car and types car and vehicles
+    let locations = server.get_implementations(&uri, Position::new(1, 14));
+    assert_eq!(lines(&locations), vec![2, 3, 5], "got {locations:?}");
+    assert!(locations.iter().all(|location| location.uri == uri));
+    assert_eq!(
+        locations[0].range,
+        Range::new(Position::new(2, 13), Position::new(2, 16))
+    );
+}
+
+#[test]
+fn test_implementations_from_reference() {
+    let (mut server, uri) = open_source();
+
+    // On the `Vehicle` reference in `part def Car :> Vehicle;`
+    let locations = server.get_implementations(&uri, Position::new(2, 22));
+    assert_eq!(lines(&locations), vec![2, 3, 5], "got {locations:?}");
+}
+
+#[test]
+fn test_implementations_of_usage() {
+    let (mut server, uri) = open_source();
+
+    let locations = server.get_implementations(&uri, Position::new(5, 14));
+    assert_eq!(lines(&locations), vec![8], "got {locations:?}");
+}
+
+#[test]
+fn test_no_implementations() {
+    let (mut server, uri) = open_source();
+
+    assert!(
+        server
+            .get_implementations(&uri, Position::new(3, 10))
+            .is_empty()
+    );
+    assert!(
+        server
+            .get_implementations(&uri, Position::new(0, 9))
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_type_definition_of_usage() {
+    let (mut server, uri) = open_source();
+
+    let location = server
+        .get_type_definition(&uri, Position::new(3, 10))
+        .expect("type definition expected");
+    assert_eq!(location.uri, uri);
+    assert_eq!(
+        location.range,
+        Range::new(Position::new(1, 13), Position::new(1, 20))
+    );
+}
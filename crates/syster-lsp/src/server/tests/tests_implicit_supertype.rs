@@ -0,0 +1,70 @@
+//! Tests for the syster/getImplicitSupertype request
+
+use crate::server::implicit_supertype::implicit_supertype;
+use crate::server::tests::test_helpers::{
+    LspServerTestExt, create_server, create_server_with_stdlib,
+};
+use async_lsp::lsp_types::{Position, Url};
+use syster::hir::SymbolKind;
+
+const SOURCE: &str = "package Vehicles {\n    part def Vehicle;\n    part car : Vehicle;\n    attribute def Mass;\n}";
+
+#[test]
+fn test_implicit_supertype_by_kind() {
+    assert_eq!(implicit_supertype(SymbolKind::PartDef), Some("Parts::Part"));
+    assert_eq!(
+        implicit_supertype(SymbolKind::PartUsage),
+        Some("Parts::parts")
+    );
+    assert_eq!(
+        implicit_supertype(SymbolKind::EnumerationDef),
+        Some("Base::DataValue")
+    );
+    assert_eq!(implicit_supertype(SymbolKind::Package), None);
+}
+
+#[test]
+fn test_implicit_supertype_of_declarations_and_references() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Vehicles.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+
+    let result = server
+        .get_implicit_supertype(&uri, Position::new(1, 15))
+        .unwrap();
+    assert_eq!(result.qualified_name, "Vehicles::Vehicle");
+    assert_eq!(result.supertype, "Parts::Part");
+    // Without the stdlib there is nothing to link to
+    assert!(result.link.is_none());
+
+    // On the declaration of a usage, and on a reference to a definition
+    let usage = server
+        .get_implicit_supertype(&uri, Position::new(2, 10))
+        .unwrap();
+    assert_eq!(usage.supertype, "Parts::parts");
+    let reference = server
+        .get_implicit_supertype(&uri, Position::new(2, 17))
+        .unwrap();
+    assert_eq!(reference.qualified_name, "Vehicles::Vehicle");
+
+    let attribute = server
+        .get_implicit_supertype(&uri, Position::new(3, 19))
+        .unwrap();
+    assert_eq!(attribute.supertype, "Base::DataValue");
+}
+
+#[test]
+fn test_implicit_supertype_links_to_stdlib() {
+    let mut server = create_server_with_stdlib();
+    if !server.has_stdlib_loaded() {
+        return;
+    }
+    let uri = Url::parse("file:///test/Vehicles.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+
+    let result = server
+        .get_implicit_supertype(&uri, Position::new(1, 15))
+        .unwrap();
+    let link = result.link.unwrap();
+    assert!(link.target_uri.path().ends_with("Parts.sysml"), "{link:?}");
+}
@@ -0,0 +1,142 @@
+//! Tests for member-level incremental reparsing
+
+use crate::server::incremental_parse::IncrementalParser;
+use crate::server::parse_cache::CachedParse;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+use std::path::{Path, PathBuf};
+
+fn assert_same_as_full_parse(path: &Path, text: &str) {
+    let mut parser = IncrementalParser::default();
+    let incremental = parser
+        .parse(path, text)
+        .expect("document should be parsed incrementally");
+    let full = CachedParse::parse(path, text);
+
+    assert!(full.errors.is_empty());
+    assert_eq!(incremental.content, full.content);
+}
+
+#[test]
+fn test_incremental_parse_matches_full_parse_for_package_body() {
+    let text = r#"// Vehicle model
+package Vehicles {
+    doc /* Vehicles and their parts */
+    import ScalarValues::*;
+
+    part def Engine {
+        attribute power : Real;
+    }
+    part def Car :> Vehicle {
+        part engine : Engine[1];
+        part 'front wheel' : Wheel[2];
+    }
+    alias Auto for Car;
+    part myCar : Car; part other : Car;
+    // trailing comment
+}
+"#;
+    assert_same_as_full_parse(Path::new("/vehicles.sysml"), text);
+}
+
+#[test]
+fn test_incremental_parse_matches_full_parse_for_top_level_elements() {
+    let text = r#"
+package A {
+    part def X;
+}
+part def Y { /* { not a brace } */ }
+package B;
+"#;
+    assert_same_as_full_parse(Path::new("/top.sysml"), text);
+}
+
+#[test]
+fn test_incremental_parse_matches_full_parse_for_stdlib_files() {
+    let stdlib = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("sysml.library");
+    for file in [
+        "Systems Library/Parts.sysml",
+        "Systems Library/Requirements.sysml",
+        "Systems Library/StandardViewDefinitions.sysml",
+    ] {
+        let path = stdlib.join(file);
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        assert_same_as_full_parse(&path, &text);
+    }
+}
+
+#[test]
+fn test_incremental_parse_moves_reused_members_to_new_lines() {
+    let path = Path::new("/shift.sysml");
+    let before =
+        "package P {\n    part def A :> B;\n    part def C {\n        part d : A;\n    }\n}\n";
+    let after = "package P {\n    part def A :> B;\n\n\n    // new\n    part def C {\n        part d : A;\n    }\n}\n";
+
+    let mut parser = IncrementalParser::default();
+    parser.parse(path, before).unwrap();
+    let reused = parser.parse(path, after).unwrap();
+
+    assert_eq!(reused.content, CachedParse::parse(path, after).content);
+}
+
+#[test]
+fn test_incremental_parse_falls_back_on_errors() {
+    let mut parser = IncrementalParser::default();
+    let text = "package P {\n    part def A\n    part def B;\n}\n";
+    assert!(parser.parse(Path::new("/bad.sysml"), text).is_none());
+}
+
+#[test]
+fn test_incremental_parse_skips_kerml() {
+    let mut parser = IncrementalParser::default();
+    assert!(
+        parser
+            .parse(Path::new("/lib.kerml"), "package P { classifier C; }")
+            .is_none()
+    );
+}
+
+#[test]
+fn test_edit_reparses_only_changed_member() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def A;\n    part def B;\n    part def C;\n}\n";
+    server.open_document(&uri, text).unwrap();
+
+    let edit = |server: &mut crate::server::LspServer, range: Range, new_text: &str| {
+        let change = TextDocumentContentChangeEvent {
+            range: Some(range),
+            range_length: None,
+            text: new_text.to_string(),
+        };
+        server.apply_text_change_only(&uri, &change).unwrap();
+        server.parse_document(&uri);
+    };
+
+    // First edit populates the member cache (rename A to Axle)
+    edit(
+        &mut server,
+        Range::new(Position::new(1, 13), Position::new(1, 14)),
+        "Axle",
+    );
+    let before = server.get_memory_stats(false).parse_cache.segment_entries;
+
+    // Rename B to Bus on the same line
+    edit(
+        &mut server,
+        Range::new(Position::new(2, 13), Position::new(2, 14)),
+        "Bus",
+    );
+
+    let after = server.get_memory_stats(false).parse_cache.segment_entries;
+    assert_eq!(after, before + 1, "only the edited member is re-parsed");
+
+    let symbols = server.get_document_symbols(Path::new("/test.sysml"));
+    let children = symbols[0].children.as_ref().unwrap();
+    let names: Vec<&str> = children.iter().map(|s| s.name.as_str()).collect();
+    assert!(names.contains(&"Axle"));
+    assert!(names.contains(&"Bus"));
+    assert!(!names.contains(&"B"));
+}
@@ -0,0 +1,71 @@
+//! Tests for inline completion of element bodies
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, Url};
+
+fn inline_completion(text: &str, position: Position) -> Option<(String, Option<Range>)> {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, text).unwrap();
+    let mut list = server.get_inline_completions(&uri, position)?;
+    assert_eq!(list.items.len(), 1);
+    let item = list.items.remove(0);
+    Some((item.insert_text, item.range))
+}
+
+#[test]
+fn test_inline_completion_state_def_skeleton() {
+    let text = "package P {\n    state def Engine {\n}";
+    let (insert_text, range) = inline_completion(text, Position::new(1, 22)).unwrap();
+    assert_eq!(
+        insert_text,
+        "\n        entry; then idle;\n\n        state idle;\n    }"
+    );
+    assert_eq!(
+        range,
+        Some(Range::new(Position::new(1, 22), Position::new(1, 22)))
+    );
+}
+
+#[test]
+fn test_inline_completion_replaces_auto_closed_brace() {
+    let text = "\trequirement def MassLimit {}";
+    let (insert_text, range) = inline_completion(text, Position::new(0, 28)).unwrap();
+    assert_eq!(insert_text, "\n\t\tdoc /* */\n\t}");
+    assert_eq!(
+        range,
+        Some(Range::new(Position::new(0, 28), Position::new(0, 29)))
+    );
+}
+
+#[test]
+fn test_inline_completion_nested_template_lines() {
+    let text = "use case def Drive {";
+    let (insert_text, _) = inline_completion(text, Position::new(0, 20)).unwrap();
+    assert_eq!(
+        insert_text,
+        "\n    subject;\n    objective {\n        doc /* */\n    }\n}"
+    );
+}
+
+#[test]
+fn test_inline_completion_only_for_empty_blocks_of_known_kinds() {
+    // No template for parts
+    assert!(inline_completion("part def Car {", Position::new(0, 14)).is_none());
+    // The block already has content
+    assert!(inline_completion("state def S { state a; }", Position::new(0, 13)).is_none());
+    // The cursor isn't after an opening brace
+    assert!(inline_completion("state def S;", Position::new(0, 12)).is_none());
+    // Modifiers don't hide the kind
+    assert!(inline_completion("abstract action def Go {", Position::new(0, 24)).is_some());
+}
+
+#[test]
+fn test_inline_completion_registration_requires_client_support() {
+    let mut server = create_server();
+    assert!(server.inline_completion_registration().is_none());
+
+    server.set_inline_completion_support(true);
+    let registration = server.inline_completion_registration().unwrap();
+    assert_eq!(registration.method, "textDocument/inlineCompletion");
+}
@@ -0,0 +1,122 @@
+//! Tests for KerML documents going through the same pipeline as SysML ones
+
+use crate::server::LspServer;
+use crate::server::semantic_tokens::READONLY;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{
+    CompletionResponse, DiagnosticSeverity, FoldingRangeKind, HoverContents, Position,
+    SemanticTokensResult, Url,
+};
+use std::path::Path;
+
+const LIBRARY: &str = "package Kernel {
+    datatype Mass;
+    classifier Engine;
+    classifier Vehicle {
+        const feature mass : Mass;
+        feature engine : Engine;
+    }
+}";
+
+fn open_library() -> (LspServer, Url) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///Kernel.kerml").unwrap();
+    server.open_document(&uri, LIBRARY).unwrap();
+    (server, uri)
+}
+
+#[test]
+fn test_kerml_document_opens_without_errors() {
+    let (mut server, uri) = open_library();
+
+    let errors: Vec<_> = server
+        .get_diagnostics(&uri)
+        .into_iter()
+        .filter(|d| d.severity == Some(DiagnosticSeverity::ERROR))
+        .collect();
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn test_kerml_document_symbols() {
+    let (mut server, _) = open_library();
+
+    let symbols = server.get_document_symbols(Path::new("/Kernel.kerml"));
+    assert_eq!(symbols.len(), 1, "{symbols:?}");
+    assert_eq!(symbols[0].name, "Kernel");
+
+    let members = symbols[0].children.as_deref().unwrap_or_default();
+    let vehicle = members.iter().find(|sym| sym.name == "Vehicle");
+    let features: Vec<&str> = vehicle
+        .and_then(|sym| sym.children.as_deref())
+        .unwrap_or_default()
+        .iter()
+        .map(|sym| sym.name.as_str())
+        .collect();
+    assert_eq!(features, ["mass", "engine"]);
+}
+
+#[test]
+fn test_kerml_hover_shows_const_features_as_readonly() {
+    let (mut server, uri) = open_library();
+
+    let hover = server.get_hover(&uri, Position::new(4, 23)).unwrap();
+    let HoverContents::Markup(content) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert!(content.value.contains("mass"), "{}", content.value);
+    assert!(content.value.contains("readonly"), "{}", content.value);
+}
+
+#[test]
+fn test_kerml_semantic_tokens_mark_const_features_readonly() {
+    let (mut server, uri) = open_library();
+
+    let Some(SemanticTokensResult::Tokens(tokens)) = server.get_semantic_tokens(&uri) else {
+        panic!("Expected semantic tokens");
+    };
+    assert!(!tokens.data.is_empty());
+    assert!(
+        tokens
+            .data
+            .iter()
+            .any(|token| token.token_modifiers_bitset & READONLY != 0)
+    );
+}
+
+#[test]
+fn test_kerml_folding_ranges() {
+    let (mut server, _) = open_library();
+
+    let ranges = server.get_folding_ranges(Path::new("/Kernel.kerml"));
+    let regions: Vec<(u32, u32)> = ranges
+        .iter()
+        .filter(|range| range.kind == Some(FoldingRangeKind::Region))
+        .map(|range| (range.start_line, range.end_line))
+        .collect();
+    // The package and the classifier body fold
+    for line in [0, 3] {
+        assert!(
+            regions
+                .iter()
+                .any(|&(start, end)| start == line && end > start),
+            "{regions:?}"
+        );
+    }
+}
+
+#[test]
+fn test_kerml_declared_name_is_not_offered_as_its_own_supertype() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///Kernel.kerml").unwrap();
+    let text = LIBRARY.replace("classifier Engine;", "classifier Engine :> ");
+    server.open_document(&uri, &text).unwrap();
+
+    let position = Position::new(2, 25);
+    let CompletionResponse::Array(items) =
+        server.get_completions(Path::new("/Kernel.kerml"), position)
+    else {
+        panic!("Expected completion array");
+    };
+    assert!(items.iter().all(|item| item.label != "Engine"));
+}
@@ -0,0 +1,83 @@
+//! Tests for hover explanations of keywords and operators
+
+use crate::server::client_support::ClientSupport;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Hover, HoverContents, MarkupKind, Position, Range, Url};
+
+const MODEL: &str = "package P {
+    part def Vehicle {
+        part wheel;
+    }
+    part def Car :> Vehicle {
+        part redefines wheel;
+        part w :>> wheel;
+        perform drive;
+    }
+    action drive;
+}";
+
+fn hover(position: Position, markdown: bool) -> Option<Hover> {
+    let mut server = create_server();
+    server.set_client_support(ClientSupport {
+        markdown_hover: markdown,
+        ..ClientSupport::default()
+    });
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, MODEL).unwrap();
+    server.get_hover(&uri, position)
+}
+
+fn contents(hover: &Hover) -> &str {
+    match &hover.contents {
+        HoverContents::Markup(content) => &content.value,
+        _ => panic!("Expected markup hover"),
+    }
+}
+
+#[test]
+fn test_keyword_hover_explains_redefinition() {
+    let hover = hover(Position::new(5, 14), true).unwrap();
+    let value = contents(&hover);
+
+    assert!(value.starts_with("**`redefines`**"), "{value}");
+    assert!(value.contains("Redefinition"), "{value}");
+    assert!(
+        value.contains("(https://www.omg.org/spec/SysML/2.0/)"),
+        "{value}"
+    );
+    assert_eq!(
+        hover.range,
+        Some(Range::new(Position::new(5, 13), Position::new(5, 22)))
+    );
+}
+
+#[test]
+fn test_operator_hover_covers_the_whole_operator() {
+    // On the second `>` of `:>>`
+    let hover = hover(Position::new(6, 17), true).unwrap();
+    assert!(contents(&hover).starts_with("**`:>>`**"));
+    assert_eq!(
+        hover.range,
+        Some(Range::new(Position::new(6, 15), Position::new(6, 18)))
+    );
+
+    let hover = self::hover(Position::new(4, 18), true).unwrap();
+    assert!(contents(&hover).starts_with("**`:>`**"));
+}
+
+#[test]
+fn test_keyword_hover_in_plain_text() {
+    let hover = hover(Position::new(7, 9), false).unwrap();
+    let HoverContents::Markup(content) = &hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert_eq!(content.kind, MarkupKind::PlainText);
+    assert!(content.value.contains("perform"), "{}", content.value);
+    assert!(!content.value.contains("**"), "{}", content.value);
+}
+
+#[test]
+fn test_names_still_hover_as_symbols() {
+    let hover = hover(Position::new(7, 17), true).unwrap();
+    assert!(!contents(&hover).contains("See [SysML v2"));
+}
@@ -0,0 +1,128 @@
+//! Tests for linked editing ranges
+
+use crate::server::linked_editing::NAME_WORD_PATTERN;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, Url};
+
+const ENGINES: &str = "package P {
+    part def Engine;
+    part a : Engine;
+    part b : P::Engine;
+    package Q {
+        part def Engine;
+        part c : Engine;
+    }
+}";
+
+fn range(line: u32, start: u32, end: u32) -> Range {
+    Range::new(Position::new(line, start), Position::new(line, end))
+}
+
+#[test]
+fn test_linked_ranges_from_the_declaration() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///engines.sysml").unwrap();
+    server.open_document(&uri, ENGINES).unwrap();
+
+    let linked = server
+        .get_linked_editing_ranges(&uri, Position::new(1, 15))
+        .unwrap();
+    // Only the last segment of a qualified reference, and not Q::Engine's usage
+    assert_eq!(
+        linked.ranges,
+        [range(1, 13, 19), range(2, 13, 19), range(3, 16, 22)]
+    );
+    assert_eq!(linked.word_pattern.as_deref(), Some(NAME_WORD_PATTERN));
+}
+
+#[test]
+fn test_linked_ranges_from_a_reference() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///engines.sysml").unwrap();
+    server.open_document(&uri, ENGINES).unwrap();
+
+    let linked = server
+        .get_linked_editing_ranges(&uri, Position::new(6, 18))
+        .unwrap();
+    assert_eq!(linked.ranges, [range(5, 17, 23), range(6, 17, 23)]);
+}
+
+#[test]
+fn test_short_name_written_the_same_is_linked() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///short.sysml").unwrap();
+    server
+        .open_document(
+            &uri,
+            "part def <Engine> Engine;\npart def <W> Wheel;\npart w : Wheel;",
+        )
+        .unwrap();
+
+    let linked = server
+        .get_linked_editing_ranges(&uri, Position::new(0, 20))
+        .unwrap();
+    assert_eq!(linked.ranges, [range(0, 10, 16), range(0, 18, 24)]);
+
+    // A different short name keeps its own text
+    let linked = server
+        .get_linked_editing_ranges(&uri, Position::new(1, 14))
+        .unwrap();
+    assert_eq!(linked.ranges, [range(1, 13, 18), range(2, 9, 14)]);
+}
+
+#[test]
+fn test_element_declared_in_another_file_links_local_references() {
+    let mut server = create_server();
+    let base = Url::parse("file:///base.sysml").unwrap();
+    let model = Url::parse("file:///model.sysml").unwrap();
+    server
+        .open_document(&base, "package Base { part def Engine; }")
+        .unwrap();
+    server
+        .open_document(
+            &model,
+            "package Model {\n    import Base::*;\n    part a : Engine;\n    part b : Engine;\n}",
+        )
+        .unwrap();
+
+    let linked = server
+        .get_linked_editing_ranges(&model, Position::new(2, 15))
+        .unwrap();
+    assert_eq!(linked.ranges, [range(2, 13, 19), range(3, 13, 19)]);
+}
+
+#[test]
+fn test_no_linked_ranges_away_from_a_name() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///engines.sysml").unwrap();
+    server.open_document(&uri, ENGINES).unwrap();
+
+    assert!(
+        server
+            .get_linked_editing_ranges(&uri, Position::new(1, 5))
+            .is_none()
+    );
+    let unknown = Url::parse("file:///missing.sysml").unwrap();
+    assert!(
+        server
+            .get_linked_editing_ranges(&unknown, Position::new(0, 0))
+            .is_none()
+    );
+}
+
+#[test]
+fn test_same_named_redefinition_is_not_linked() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///redefines.sysml").unwrap();
+    server
+        .open_document(
+            &uri,
+            "part def V { part engine; }\npart def C :> V {\n    part engine :>> engine;\n}",
+        )
+        .unwrap();
+
+    let linked = server
+        .get_linked_editing_ranges(&uri, Position::new(2, 10))
+        .unwrap();
+    assert_eq!(linked.ranges, [range(2, 9, 15)]);
+}
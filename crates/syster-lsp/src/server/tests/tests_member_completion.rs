@@ -0,0 +1,83 @@
+//! Tests for member completion after `.` in feature chains
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{CompletionItem, CompletionResponse, Position, Url};
+use std::path::Path;
+
+const MODEL: &str = "package Garage {
+    part def Engine {
+        port fuelIn;
+        action start;
+    }
+    part def Vehicle {
+        part engine : Engine;
+        attribute mass;
+    }
+    part def Car :> Vehicle {
+        part wheel;
+        part redefines engine;
+    }
+    part def Driver {
+        action drive;
+    }
+    part def Site {
+        part car : Car;
+        part driver : Driver;
+        STATEMENT
+    }
+}";
+
+fn completions(statement: &str) -> Vec<CompletionItem> {
+    let mut server = create_server();
+    let uri = Url::parse("file:///garage.sysml").unwrap();
+    let text = MODEL.replace("STATEMENT", statement);
+    server.open_document(&uri, &text).unwrap();
+
+    let position = Position::new(19, 8 + statement.len() as u32);
+    match server.get_completions(Path::new("/garage.sysml"), position) {
+        CompletionResponse::Array(items) => items,
+        _ => panic!("Expected completion array"),
+    }
+}
+
+fn labels(items: &[CompletionItem]) -> Vec<&str> {
+    items.iter().map(|item| item.label.as_str()).collect()
+}
+
+#[test]
+fn test_members_include_inherited_features() {
+    let items = completions("attribute m = car.");
+    let labels = labels(&items);
+
+    assert!(labels.contains(&"wheel"), "{labels:?}");
+    // Inherited through `Car :> Vehicle`
+    assert!(labels.contains(&"mass"), "{labels:?}");
+    // The redefinition is offered once
+    assert_eq!(labels.iter().filter(|l| **l == "engine").count(), 1);
+    // Only members, not everything visible
+    assert!(!labels.contains(&"Vehicle"), "{labels:?}");
+    assert!(!labels.contains(&"driver"), "{labels:?}");
+}
+
+#[test]
+fn test_members_of_a_chain() {
+    let items = completions("attribute m = car.engine.");
+    let labels = labels(&items);
+
+    assert!(labels.contains(&"fuelIn"), "{labels:?}");
+    assert!(labels.contains(&"start"), "{labels:?}");
+    assert!(!labels.contains(&"wheel"), "{labels:?}");
+}
+
+#[test]
+fn test_members_after_perform() {
+    let items = completions("perform driver.");
+    let labels = labels(&items);
+
+    assert_eq!(labels, ["drive"]);
+}
+
+#[test]
+fn test_unresolved_chain_offers_nothing() {
+    assert!(completions("attribute m = missing.").is_empty());
+}
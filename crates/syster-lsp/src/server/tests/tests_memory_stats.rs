@@ -0,0 +1,66 @@
+//! Tests for the syster/getMemoryStats request
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::Url;
+
+#[test]
+fn test_memory_stats_empty_server() {
+    let mut server = create_server();
+
+    let stats = server.get_memory_stats(false);
+
+    assert_eq!(stats.file_count, 0);
+    assert_eq!(stats.symbols.entries, 0);
+    assert_eq!(stats.references.entries, 0);
+    assert_eq!(stats.document_texts.entries, 0);
+    assert_eq!(stats.trimmed_entries, 0);
+}
+
+#[test]
+fn test_memory_stats_counts_symbols_and_references() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+part def Vehicle;
+part car : Vehicle;
+part truck : Vehicle;
+    "#;
+    server.open_document(&uri, text).unwrap();
+
+    let stats = server.get_memory_stats(false);
+
+    assert_eq!(stats.file_count, 1);
+    assert_eq!(stats.symbols.entries, 3);
+    assert!(stats.symbols.estimated_bytes > 0);
+    assert_eq!(stats.references.entries, 2);
+    assert_eq!(stats.document_texts.entries, 1);
+    assert!(stats.document_texts.estimated_bytes >= text.len());
+}
+
+#[test]
+fn test_memory_stats_trim_removes_stale_entries() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, "part def Vehicle;").unwrap();
+
+    // A text for a file that was never loaded into the analysis host
+    server
+        .document_texts_mut()
+        .insert("/stale.sysml".into(), "part def Old;".to_string());
+
+    let untrimmed = server.get_memory_stats(false);
+    assert_eq!(untrimmed.document_texts.entries, 2);
+
+    let trimmed = server.get_memory_stats(true);
+    // The stale text plus the cached parse of test.sysml
+    assert_eq!(trimmed.trimmed_entries, 2);
+    assert_eq!(trimmed.document_texts.entries, 1);
+    assert_eq!(trimmed.parse_cache.entries, 0);
+}
+
+#[test]
+fn test_memory_stats_params_default_to_no_trim() {
+    let params: crate::server::memory_stats::GetMemoryStatsParams =
+        serde_json::from_value(serde_json::json!({})).unwrap();
+    assert!(!params.trim_caches);
+}
@@ -0,0 +1,103 @@
+//! Tests for the syster/exportModel request
+
+use crate::server::model_export::{ElementKind, ExportModelParams};
+use crate::server::schema::SCHEMA_VERSION;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, Url};
+use std::path::Path;
+
+const VEHICLES: &str = "package Vehicles {
+    part def Vehicle;
+    part def Car :> Vehicle {
+        part engine : Engine;
+    }
+}";
+
+#[test]
+fn test_export_model_of_a_file() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///vehicles.sysml").unwrap();
+    server.open_document(&uri, VEHICLES).unwrap();
+
+    let export = server.export_model(Some(Path::new("/vehicles.sysml")), false);
+    assert_eq!(export.schema_version, SCHEMA_VERSION);
+    assert_eq!(export.files.len(), 1);
+    assert_eq!(export.files[0].uri, uri.to_string());
+
+    let names: Vec<&str> = export.files[0]
+        .elements
+        .iter()
+        .map(|element| element.qualified_name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "Vehicles",
+            "Vehicles::Vehicle",
+            "Vehicles::Car",
+            "Vehicles::Car::engine"
+        ]
+    );
+
+    let car = &export.files[0].elements[2];
+    assert_eq!(car.owner.as_deref(), Some("Vehicles"));
+    assert_eq!(
+        car.range,
+        Range::new(Position::new(2, 13), Position::new(2, 16))
+    );
+    assert_eq!(car.relationships.len(), 1);
+    assert_eq!(car.relationships[0].target, "Vehicle");
+    assert_eq!(
+        car.relationships[0].resolved.as_deref(),
+        Some("Vehicles::Vehicle")
+    );
+
+    // Unresolved targets are exported as written
+    let engine = &export.files[0].elements[3];
+    assert_eq!(engine.relationships[0].target, "Engine");
+    assert_eq!(engine.relationships[0].resolved, None);
+}
+
+#[test]
+fn test_export_model_of_the_workspace() {
+    let mut server = create_server();
+    let b = Url::parse("file:///b.sysml").unwrap();
+    let a = Url::parse("file:///a.sysml").unwrap();
+    server.open_document(&b, "part def B;").unwrap();
+    server.open_document(&a, "part def A;\nimport B;").unwrap();
+
+    let export = server.export_model(None, false);
+    let uris: Vec<&str> = export.files.iter().map(|file| file.uri.as_str()).collect();
+    assert_eq!(uris, [a.as_str(), b.as_str()]);
+    // Imports aren't elements of the model
+    assert_eq!(export.files[0].elements.len(), 1);
+    assert_eq!(export.files[0].elements[0].kind, ElementKind::PartDef);
+}
+
+#[test]
+fn test_export_model_of_unknown_file_is_empty() {
+    let mut server = create_server();
+    let export = server.export_model(Some(Path::new("/missing.sysml")), false);
+    assert!(export.files.is_empty());
+}
+
+#[test]
+fn test_export_model_serializes_as_camel_case() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///vehicles.sysml").unwrap();
+    server.open_document(&uri, VEHICLES).unwrap();
+
+    let json = serde_json::to_value(server.export_model(None, false)).unwrap();
+    assert_eq!(json["schemaVersion"], SCHEMA_VERSION);
+    let element = &json["files"][0]["elements"][1];
+    assert_eq!(element["qualifiedName"], "Vehicles::Vehicle");
+    assert_eq!(element["kind"], "partDef");
+    let car = &json["files"][0]["elements"][2];
+    assert_eq!(car["relationships"][0]["kind"], "specializes");
+    // Elements without relationships leave them out
+    assert!(element.get("relationships").is_none());
+
+    let params: ExportModelParams = serde_json::from_value(serde_json::json!({})).unwrap();
+    assert!(params.uri.is_none());
+    assert!(!params.include_stdlib);
+}
@@ -0,0 +1,79 @@
+//! Tests for textDocument/moniker
+
+use crate::server::LspServer;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{MonikerKind, Position, UniquenessLevel, Url};
+
+const MODEL: &str = "package Vehicles {
+    part def Engine;
+    part def Vehicle {
+        part engine : Engine;
+    }
+}";
+
+fn open_model(server: &mut LspServer) -> Url {
+    let uri = Url::parse("file:///vehicles.sysml").unwrap();
+    server.open_document(&uri, MODEL).unwrap();
+    uri
+}
+
+#[test]
+fn test_moniker_of_declaration() {
+    let mut server = create_server();
+    let uri = open_model(&mut server);
+
+    let monikers = server.get_monikers(&uri, Position::new(2, 14)).unwrap();
+    assert_eq!(monikers.len(), 1);
+    assert_eq!(monikers[0].scheme, "sysml");
+    assert_eq!(monikers[0].identifier, "Vehicles::Vehicle#PartDef");
+    assert_eq!(monikers[0].unique, UniquenessLevel::Project);
+    assert_eq!(monikers[0].kind, Some(MonikerKind::Export));
+}
+
+#[test]
+fn test_moniker_of_reference_is_its_target() {
+    let mut server = create_server();
+    let uri = open_model(&mut server);
+
+    let at_reference = server.get_monikers(&uri, Position::new(3, 23)).unwrap();
+    let at_declaration = server.get_monikers(&uri, Position::new(1, 15)).unwrap();
+    assert_eq!(at_reference[0].identifier, "Vehicles::Engine#PartDef");
+    assert_eq!(at_reference, at_declaration);
+
+    // The usage itself is a different element
+    let usage = server.get_monikers(&uri, Position::new(3, 14)).unwrap();
+    assert_eq!(usage[0].identifier, "Vehicles::Vehicle::engine#PartUsage");
+}
+
+#[test]
+fn test_moniker_of_library_element() {
+    let mut server = create_server();
+    server
+        .open_document(
+            &Url::parse("file:///sysml.library/Lib.sysml").unwrap(),
+            "package Lib { attribute def Real; }",
+        )
+        .unwrap();
+    let uri = Url::parse("file:///model.sysml").unwrap();
+    server
+        .open_document(&uri, "package Model {\n    attribute mass : Lib::Real;\n}")
+        .unwrap();
+
+    let monikers = server.get_monikers(&uri, Position::new(1, 26)).unwrap();
+    let version = syster::core::constants::LSP_SERVER_VERSION;
+    assert_eq!(
+        monikers[0].identifier,
+        format!("Lib::Real#AttributeDef@{version}")
+    );
+    assert_eq!(monikers[0].unique, UniquenessLevel::Global);
+    assert_eq!(monikers[0].kind, Some(MonikerKind::Import));
+}
+
+#[test]
+fn test_no_moniker_outside_elements() {
+    let mut server = create_server();
+    let uri = open_model(&mut server);
+    assert!(server.get_monikers(&uri, Position::new(6, 0)).is_none());
+    let unknown = Url::parse("file:///unknown.sysml").unwrap();
+    assert!(server.get_monikers(&unknown, Position::new(0, 0)).is_none());
+}
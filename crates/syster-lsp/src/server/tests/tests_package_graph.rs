@@ -0,0 +1,159 @@
+//! Tests for the syster/getDependencyGraph request
+
+use crate::server::LspServer;
+use crate::server::package_graph::{GetDependencyGraphParams, PackageImport};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::Url;
+
+const MODEL: &str = "package Vehicles {
+    import Parts::Engine;
+    part def Vehicle;
+}
+package Parts {
+    public import Units::*;
+    part def Engine;
+}
+package Units {
+    public import Base::*;
+    attribute def Mass;
+}
+package Base {
+    attribute def Real;
+}
+package Unused;
+";
+
+fn server_with(uri: &str, text: &str) -> LspServer {
+    let mut server = create_server();
+    server
+        .open_document(&Url::parse(uri).unwrap(), text)
+        .unwrap();
+    server
+}
+
+fn edge(source: &str, target: &str, public: bool, via: &[&str]) -> PackageImport {
+    PackageImport {
+        source: source.to_string(),
+        target: target.to_string(),
+        public,
+        via: via.iter().map(|name| name.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_direct_and_reexported_imports() {
+    let mut server = server_with("file:///model.sysml", MODEL);
+    let graph = server.get_dependency_graph(false);
+
+    assert_eq!(
+        graph.imports,
+        [
+            edge("Parts", "Base", true, &["Units"]),
+            edge("Parts", "Units", true, &[]),
+            edge("Units", "Base", true, &[]),
+            edge("Vehicles", "Base", false, &["Parts", "Units"]),
+            edge("Vehicles", "Parts", false, &[]),
+            edge("Vehicles", "Units", false, &["Parts"]),
+        ]
+    );
+
+    // Packages without imports either way aren't in the graph
+    let names: Vec<&str> = graph
+        .packages
+        .iter()
+        .map(|package| package.qualified_name.as_str())
+        .collect();
+    assert_eq!(names, ["Base", "Parts", "Units", "Vehicles"]);
+    assert_eq!(
+        graph.packages[0].uri.as_deref(),
+        Some("file:///model.sysml")
+    );
+    assert!(!graph.packages[0].stdlib);
+}
+
+#[test]
+fn test_import_cycle_is_walked_once() {
+    let mut server = server_with(
+        "file:///cycle.sysml",
+        "package A { public import B::*; part def X; }\n\
+         package B { public import A::*; part def Y; }\n\
+         package C { import A::*; }\n",
+    );
+    let graph = server.get_dependency_graph(false);
+
+    assert_eq!(
+        graph.imports,
+        [
+            edge("A", "B", true, &[]),
+            edge("B", "A", true, &[]),
+            edge("C", "A", false, &[]),
+            edge("C", "B", false, &["A"]),
+        ]
+    );
+}
+
+#[test]
+fn test_nested_package_and_self_imports() {
+    let mut server = server_with(
+        "file:///nested.sysml",
+        "package Outer {\n    package Inner { part def P; }\n    import Inner::*;\n}\n",
+    );
+    let graph = server.get_dependency_graph(false);
+    assert_eq!(graph.imports, [edge("Outer", "Outer::Inner", false, &[])]);
+
+    // A package importing its own members doesn't depend on itself
+
+    let mut server = server_with(
+        "file:///same.sysml",
+        "package Same {\n    part def P;\n    import Same::P;\n}\n",
+    );
+    assert!(server.get_dependency_graph(false).imports.is_empty());
+}
+
+#[test]
+fn test_stdlib_imports_only_when_asked_for() {
+    let mut server = create_server();
+    server
+        .open_document(
+            &Url::parse("file:///sysml.library/Lib.sysml").unwrap(),
+            "package Lib { public import Core::*; attribute def Real; }\n\
+             package Core { attribute def Boolean; }\n",
+        )
+        .unwrap();
+    server
+        .open_document(
+            &Url::parse("file:///model.sysml").unwrap(),
+            "package Model { import Lib::Real; }\n",
+        )
+        .unwrap();
+
+    let graph = server.get_dependency_graph(false);
+    // What the stdlib re-exports is still a dependency
+    assert_eq!(
+        graph.imports,
+        [
+            edge("Model", "Core", false, &["Lib"]),
+            edge("Model", "Lib", false, &[]),
+        ]
+    );
+    let lib = graph
+        .packages
+        .iter()
+        .find(|package| package.qualified_name == "Lib")
+        .unwrap();
+    assert!(lib.stdlib);
+
+    let graph = server.get_dependency_graph(true);
+    assert!(graph.imports.contains(&edge("Lib", "Core", true, &[])));
+    assert!(
+        graph
+            .imports
+            .contains(&edge("Model", "Core", false, &["Lib"]))
+    );
+}
+
+#[test]
+fn test_params_default_to_workspace_packages() {
+    let params: GetDependencyGraphParams = serde_json::from_value(serde_json::json!({})).unwrap();
+    assert!(!params.include_stdlib);
+}
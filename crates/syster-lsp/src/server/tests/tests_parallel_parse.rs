@@ -0,0 +1,64 @@
+//! Tests for parallel parsing of the standard library
+
+use crate::server::parallel_parse::{load_stdlib, parse_files, stdlib_dir};
+use std::path::{Path, PathBuf};
+use syster::ide::AnalysisHost;
+use syster::project::StdLibLoader;
+
+fn stdlib_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("sysml.library")
+}
+
+#[test]
+fn test_parallel_stdlib_matches_sequential_loader() {
+    let mut sequential = AnalysisHost::new();
+    StdLibLoader::with_path(stdlib_path())
+        .load_into_host(&mut sequential)
+        .unwrap();
+
+    let mut parallel = AnalysisHost::new();
+    let loaded = load_stdlib(&stdlib_path(), &mut parallel).unwrap();
+
+    let mut expected: Vec<&PathBuf> = sequential.files().keys().collect();
+    let mut actual: Vec<&PathBuf> = parallel.files().keys().collect();
+    expected.sort();
+    actual.sort();
+    assert_eq!(actual, expected);
+    assert_eq!(loaded, expected.len());
+
+    let expected_symbols = sequential.analysis().symbol_index().all_symbols().count();
+    let actual_symbols = parallel.analysis().symbol_index().all_symbols().count();
+    assert_eq!(actual_symbols, expected_symbols);
+}
+
+#[test]
+fn test_parse_results_keep_path_order() {
+    let dir = std::env::temp_dir().join(format!("syster-parallel-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let paths: Vec<PathBuf> = (0..20)
+        .map(|i| {
+            let path = dir.join(format!("P{i:02}.sysml"));
+            std::fs::write(&path, format!("package P{i} {{ part def D{i}; }}")).unwrap();
+            path
+        })
+        .chain([dir.join("missing.sysml")])
+        .collect();
+
+    let results = parse_files(&paths);
+    let order: Vec<&PathBuf> = results.iter().map(|(path, _)| path).collect();
+    assert_eq!(order, paths.iter().collect::<Vec<_>>());
+    assert!(results[..20].iter().all(|(_, result)| result.is_ok()));
+    // A file that can't be read is reported, not dropped
+    assert!(results[20].1.is_err());
+}
+
+#[test]
+fn test_missing_stdlib_is_an_error() {
+    let mut host = AnalysisHost::new();
+    assert!(load_stdlib(Path::new("/nonexistent/sysml.library"), &mut host).is_err());
+    assert_eq!(
+        stdlib_dir(Some(Path::new("/custom/lib"))),
+        Some(PathBuf::from("/custom/lib"))
+    );
+}
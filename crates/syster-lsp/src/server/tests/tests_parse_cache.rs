@@ -0,0 +1,97 @@
+//! Tests for the content-addressed parse cache
+
+use crate::server::parse_cache::{CachedParse, ParseCache};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::Url;
+use std::path::Path;
+use std::sync::Arc;
+
+fn empty_parse() -> CachedParse {
+    CachedParse {
+        content: None,
+        errors: Vec::new(),
+    }
+}
+
+#[test]
+fn test_parse_cache_hits_on_identical_content() {
+    let mut cache: ParseCache = ParseCache::with_capacity(4);
+    let path = Path::new("/a.sysml");
+    let mut parses = 0;
+
+    cache.get_or_parse(path, "part def A;", || {
+        parses += 1;
+        empty_parse()
+    });
+    cache.get_or_parse(path, "part def A;", || {
+        parses += 1;
+        empty_parse()
+    });
+
+    assert_eq!(parses, 1);
+    assert_eq!(cache.hits(), 1);
+    assert_eq!(cache.misses(), 1);
+    assert_eq!(cache.hit_rate(), 0.5);
+}
+
+#[test]
+fn test_parse_cache_shares_cached_results() {
+    let mut cache: ParseCache = ParseCache::with_capacity(4);
+    let path = Path::new("/a.sysml");
+
+    let first = cache.get_or_parse(path, "part def A;", empty_parse);
+    let second = cache.get_or_parse(path, "part def A;", empty_parse);
+
+    assert!(Arc::ptr_eq(&first, &second));
+}
+
+#[test]
+fn test_parse_cache_distinguishes_extensions() {
+    let mut cache: ParseCache = ParseCache::with_capacity(4);
+
+    cache.get_or_parse(Path::new("/a.sysml"), "package P;", empty_parse);
+    cache.get_or_parse(Path::new("/a.kerml"), "package P;", empty_parse);
+
+    assert_eq!(cache.misses(), 2);
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_parse_cache_evicts_least_recently_used() {
+    let mut cache: ParseCache = ParseCache::with_capacity(2);
+    let path = Path::new("/a.sysml");
+
+    cache.get_or_parse(path, "one", empty_parse);
+    cache.get_or_parse(path, "two", empty_parse);
+    // Touch "one" so "two" becomes the oldest entry
+    cache.get_or_parse(path, "one", empty_parse);
+    cache.get_or_parse(path, "three", empty_parse);
+
+    assert_eq!(cache.len(), 2);
+
+    let misses = cache.misses();
+    cache.get_or_parse(path, "one", empty_parse);
+    assert_eq!(cache.misses(), misses, "'one' should still be cached");
+    cache.get_or_parse(path, "two", empty_parse);
+    assert_eq!(cache.misses(), misses + 1, "'two' should have been evicted");
+}
+
+#[test]
+fn test_reopening_unchanged_document_reuses_parse() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let original = "part def Vehicle;\npart car : Vehicle;";
+
+    server.open_document(&uri, original).unwrap();
+    server.open_document(&uri, "part def Changed;").unwrap();
+    server.open_document(&uri, original).unwrap();
+
+    let stats = server.get_memory_stats(false).parse_cache;
+    assert_eq!(stats.misses, 2);
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.entries, 2);
+
+    // The cached tree is used for the analysis
+    let symbols = server.get_document_symbols(Path::new("/test.sysml"));
+    assert!(symbols.iter().any(|s| s.name == "Vehicle"));
+}
@@ -0,0 +1,94 @@
+//! Tests for format and lint opt-out pragmas
+
+use crate::server::pragmas::{LintSuppression, format_off_regions, restore_format_off_regions};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{NumberOrString, Url};
+use std::collections::HashSet;
+
+#[test]
+fn test_format_off_regions() {
+    let text =
+        "a\n// syster:format off\nb\nc\n  //  syster:format   on\nd\n// syster:format off\ne";
+    assert_eq!(format_off_regions(text), vec![2..4, 7..8]);
+
+    // Only comments holding nothing but the pragma count
+    let text = "a\n// syster:format off please\n/* syster:format off */\nb";
+    assert!(format_off_regions(text).is_empty());
+}
+
+#[test]
+fn test_restore_format_off_regions() {
+    let original = "x {\n// syster:format off\n a  ;\n// syster:format on\n b  ;\n}";
+    let formatted = "x {\n    // syster:format off\n    a;\n    // syster:format on\n    b;\n}";
+    assert_eq!(
+        restore_format_off_regions(original, formatted).as_deref(),
+        Some("x {\n    // syster:format off\n a  ;\n    // syster:format on\n    b;\n}")
+    );
+
+    // Pragmas that didn't survive formatting can't be matched up
+    assert_eq!(restore_format_off_regions(original, "x {\n}"), None);
+}
+
+#[test]
+fn test_lint_suppression_from_text() {
+    assert_eq!(LintSuppression::from_text("part def A;"), None);
+    assert_eq!(
+        LintSuppression::from_text("// syster:lint off\npart def A;"),
+        Some(LintSuppression::All)
+    );
+    assert_eq!(
+        LintSuppression::from_text(
+            "// syster:lint off naming-convention\n// syster:lint off E0001 relationship-kind"
+        ),
+        Some(LintSuppression::Rules(HashSet::from([
+            "naming-convention".to_string(),
+            "E0001".to_string(),
+            "relationship-kind".to_string(),
+        ])))
+    );
+}
+
+#[test]
+fn test_lint_off_hides_listed_rules() {
+    let mut server = create_server();
+    server.set_naming_conventions(true);
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def fuel_tank;\n    part car : Missing;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let codes = |server: &mut crate::server::LspServer| -> Vec<NumberOrString> {
+        server
+            .get_diagnostics(&uri)
+            .into_iter()
+            .filter_map(|d| d.code)
+            .collect()
+    };
+    let naming = NumberOrString::String("naming-convention".to_string());
+    let all = codes(&mut server);
+    assert!(all.contains(&naming), "got {all:?}");
+    assert!(all.len() > 1, "got {all:?}");
+
+    let suppressed = format!("// syster:lint off naming-convention\n{text}");
+    server.open_document(&uri, &suppressed).unwrap();
+    let remaining = codes(&mut server);
+    assert!(!remaining.contains(&naming), "got {remaining:?}");
+    assert_eq!(remaining.len(), all.len() - 1, "got {remaining:?}");
+
+    let suppressed = format!("// syster:lint off\n{text}");
+    server.open_document(&uri, &suppressed).unwrap();
+    assert!(server.get_diagnostics(&uri).is_empty());
+}
+
+#[test]
+fn test_lint_off_keeps_syntax_errors() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let _ = server.open_document(&uri, "// syster:lint off\npart def A {");
+
+    assert!(
+        server
+            .get_diagnostics(&uri)
+            .iter()
+            .any(|d| d.source.as_deref() == Some("syster-parse"))
+    );
+}
@@ -0,0 +1,216 @@
+//! Tests for `syster.toml` project manifests
+
+use crate::server::LspServer;
+use crate::server::project_manifest::ProjectManifest;
+use crate::server::tests::test_helpers::{LspServerTestExt, create_server};
+use async_lsp::lsp_types::{FileChangeType, FileEvent, FormattingOptions, Url};
+use std::path::{Path, PathBuf};
+
+/// Create an empty scratch workspace folder
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("syster-manifest-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(path: &Path, text: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+fn load_workspace(dir: &Path) -> LspServer {
+    let mut server = create_server();
+    server.set_workspace_folders(vec![dir.to_path_buf()]);
+    server.ensure_workspace_loaded().unwrap();
+    server
+}
+
+#[test]
+fn test_parse_manifest() {
+    let root = Path::new("/project");
+    let manifest = ProjectManifest::parse(
+        root,
+        r#"
+# Project layout
+[project]
+sources = [
+    "models",   # main model
+    "lib",
+]
+exclude = ["generated/"]
+
+[stdlib]
+path = "vendor/sysml.library"
+enabled = false
+
+[format]
+tab_size = 2
+insert_spaces = true
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        manifest.sources,
+        vec![root.join("models"), root.join("lib")]
+    );
+    assert_eq!(manifest.exclude, vec!["generated/".to_string()]);
+    assert_eq!(
+        manifest.stdlib_path,
+        Some(root.join("vendor/sysml.library"))
+    );
+    assert_eq!(manifest.stdlib_enabled, Some(false));
+    assert_eq!(manifest.tab_size, Some(2));
+    assert_eq!(manifest.insert_spaces, Some(true));
+}
+
+#[test]
+fn test_parse_manifest_ignores_unknown_keys() {
+    let manifest =
+        ProjectManifest::parse(Path::new("/project"), "name = \"demo\"\n[tool]\nx = 1\n").unwrap();
+    assert_eq!(manifest.sources, Vec::<PathBuf>::new());
+    assert_eq!(manifest.source_folders(), vec![PathBuf::from("/project")]);
+}
+
+#[test]
+fn test_parse_manifest_errors() {
+    let root = Path::new("/project");
+    let err = ProjectManifest::parse(root, "[project]\nsources = \"models\"\n").unwrap_err();
+    assert!(err.starts_with("line 2"), "{err}");
+
+    let err = ProjectManifest::parse(root, "[format]\ntab_size = 0\n").unwrap_err();
+    assert!(err.contains("positive"), "{err}");
+
+    let err = ProjectManifest::parse(root, "[project]\nsources = [\"a\"\n").unwrap_err();
+    assert!(err.starts_with("line "), "{err}");
+}
+
+#[test]
+fn test_sources_limit_loaded_files() {
+    let dir = scratch_dir("sources");
+    write(
+        &dir.join("syster.toml"),
+        "[project]\nsources = [\"models\"]\n",
+    );
+    write(
+        &dir.join("models/Model.sysml"),
+        "package Model { part def A; }",
+    );
+    write(
+        &dir.join("scratch/Old.sysml"),
+        "package Old { part def B; }",
+    );
+
+    let server = load_workspace(&dir);
+
+    assert_eq!(server.project_manifests().len(), 1);
+    assert!(server.has_qualified_symbol("Model::A"));
+    assert!(!server.has_qualified_symbol("Old::B"));
+    assert!(server.is_workspace_file(&dir.join("models/New.sysml")));
+    assert!(!server.is_workspace_file(&dir.join("scratch/New.sysml")));
+}
+
+#[test]
+fn test_manifest_exclude_applies() {
+    let dir = scratch_dir("exclude");
+    write(
+        &dir.join("syster.toml"),
+        "[project]\nexclude = [\"generated/\"]\n",
+    );
+    write(&dir.join("Model.sysml"), "package Model { part def A; }");
+    write(
+        &dir.join("generated/Gen.sysml"),
+        "package Gen { part def G; }",
+    );
+
+    let server = load_workspace(&dir);
+
+    assert!(server.has_qualified_symbol("Model::A"));
+    assert!(!server.has_qualified_symbol("Gen::G"));
+}
+
+#[test]
+fn test_invalid_manifest_is_ignored() {
+    let dir = scratch_dir("invalid");
+    write(&dir.join("syster.toml"), "[project\n");
+    write(&dir.join("Model.sysml"), "package Model { part def A; }");
+
+    let server = load_workspace(&dir);
+
+    assert!(server.project_manifests().is_empty());
+    assert!(server.has_qualified_symbol("Model::A"));
+}
+
+#[test]
+fn test_manifest_stdlib_path() {
+    let dir = scratch_dir("stdlib");
+    write(
+        &dir.join("syster.toml"),
+        "[stdlib]\npath = \"lib\"\nenabled = true\n",
+    );
+    write(
+        &dir.join("lib/Base.sysml"),
+        "package Base { part def Thing; }",
+    );
+    write(&dir.join("Model.sysml"), "package Model { part def A; }");
+
+    let server = load_workspace(&dir);
+
+    assert!(server.is_stdlib_loaded());
+    assert!(server.is_stdlib_path(&dir.join("lib/Base.sysml")));
+    assert!(server.has_qualified_symbol("Base::Thing"));
+}
+
+#[test]
+fn test_manifest_formatting_options() {
+    let dir = scratch_dir("format");
+    write(
+        &dir.join("syster.toml"),
+        "[format]\ntab_size = 2\ninsert_spaces = false\n",
+    );
+    let server = load_workspace(&dir);
+
+    let editor = FormattingOptions {
+        tab_size: 4,
+        insert_spaces: true,
+        ..Default::default()
+    };
+    let inside = Url::from_file_path(dir.join("Model.sysml")).unwrap();
+    let options = server.project_formatting_options(&inside, editor.clone());
+    assert_eq!(options.tab_size, 2);
+    assert!(!options.insert_spaces);
+
+    let outside = Url::parse("file:///elsewhere/Model.sysml").unwrap();
+    assert_eq!(
+        server.project_formatting_options(&outside, editor.clone()),
+        editor
+    );
+}
+
+#[test]
+fn test_manifest_change_reloads_workspace() {
+    let dir = scratch_dir("reload");
+    let manifest = dir.join("syster.toml");
+    write(&manifest, "[project]\nsources = [\"a\"]\n");
+    write(&dir.join("a/A.sysml"), "package A { part def X; }");
+    write(&dir.join("b/B.sysml"), "package B { part def Y; }");
+    let mut server = load_workspace(&dir);
+
+    let open = Url::from_file_path(dir.join("a/Open.sysml")).unwrap();
+    server.set_document_version(&open, 1);
+    server
+        .open_document(&open, "package Open { part def Z; }")
+        .unwrap();
+    assert!(!server.reload_project_manifests());
+
+    write(&manifest, "[project]\nsources = [\"b\"]\n");
+    server.apply_watched_file_changes(&[FileEvent {
+        uri: Url::from_file_path(&manifest).unwrap(),
+        typ: FileChangeType::CHANGED,
+    }]);
+
+    assert!(server.has_qualified_symbol("B::Y"));
+    assert!(!server.has_qualified_symbol("A::X"));
+    assert!(server.has_qualified_symbol("Open::Z"));
+}
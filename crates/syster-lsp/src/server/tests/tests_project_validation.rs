@@ -0,0 +1,201 @@
+//! Tests for debounced validation of the whole project
+
+use crate::server::LspServer;
+use crate::server::project_validation::IMPORT_CYCLE_CODE;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Diagnostic, NumberOrString, Position, Range, Url};
+
+fn open(server: &mut LspServer, name: &str, text: &str) -> Url {
+    let uri = Url::parse(&format!("file:///{name}")).unwrap();
+    server.set_document_version(&uri, 1);
+    server.open_document(&uri, text).unwrap();
+    uri
+}
+
+fn with_code<'a>(diagnostics: &'a [Diagnostic], code: &str) -> Vec<&'a Diagnostic> {
+    diagnostics
+        .iter()
+        .filter(|d| d.code == Some(NumberOrString::String(code.to_string())))
+        .collect()
+}
+
+/// Run a whole validation, returning the documents it published diagnostics for
+fn validate(server: &mut LspServer) -> Vec<Url> {
+    let generation = server.start_project_validation();
+    let mut published = Vec::new();
+    while let Some(step) = server.continue_project_validation(generation, 1) {
+        published.extend(step.diagnostics.into_iter().map(|(uri, _)| uri));
+        if step.finished {
+            break;
+        }
+    }
+    published
+}
+
+#[test]
+fn test_project_validation_reports_definitions_duplicated_across_files() {
+    let mut server = create_server();
+    let a = open(
+        &mut server,
+        "a.sysml",
+        "package P { part def Vehicle { part wheel; } }",
+    );
+    let b = open(
+        &mut server,
+        "b.sysml",
+        "package P {\n    part def Vehicle { part wheel; }\n}",
+    );
+    validate(&mut server);
+
+    let diagnostics = server.get_diagnostics(&b);
+    let duplicates = with_code(&diagnostics, "duplicate-name");
+    // Only the outermost duplicate is reported, not its members
+    assert_eq!(duplicates.len(), 1, "{duplicates:?}");
+    assert_eq!(
+        duplicates[0].message,
+        "'P::Vehicle' is also declared in a.sysml"
+    );
+    assert_eq!(
+        duplicates[0].range,
+        Range::new(Position::new(1, 13), Position::new(1, 20))
+    );
+    assert_eq!(
+        with_code(&server.get_diagnostics(&a), "duplicate-name").len(),
+        1
+    );
+}
+
+#[test]
+fn test_project_validation_reports_import_cycles() {
+    let mut server = create_server();
+    let a = open(
+        &mut server,
+        "a.sysml",
+        "package A {\n    public import B::*;\n    part def X;\n}\n",
+    );
+    let b = open(
+        &mut server,
+        "b.sysml",
+        "package B {\n    public import C::*;\n    part def Y;\n}\n",
+    );
+    let c = open(
+        &mut server,
+        "c.sysml",
+        "package C {\n    public import A::*;\n    part def Z;\n}\n",
+    );
+    let d = open(&mut server, "d.sysml", "package D { public import A::*; }");
+    validate(&mut server);
+
+    let cycles = server.get_diagnostics(&a);
+    let cycles = with_code(&cycles, IMPORT_CYCLE_CODE);
+    assert_eq!(cycles.len(), 1, "{cycles:?}");
+    assert_eq!(cycles[0].range.start, Position::new(1, 4));
+    assert_eq!(
+        cycles[0].message,
+        "public import of 'B' forms an import cycle: A -> B -> C -> A"
+    );
+    let cycles = server.get_diagnostics(&c);
+    assert_eq!(
+        with_code(&cycles, IMPORT_CYCLE_CODE)[0].message,
+        "public import of 'A' forms an import cycle: C -> A -> B -> C"
+    );
+    assert_eq!(
+        with_code(&server.get_diagnostics(&b), IMPORT_CYCLE_CODE).len(),
+        1
+    );
+    // Importing a package in a cycle isn't a cycle
+    assert!(with_code(&server.get_diagnostics(&d), IMPORT_CYCLE_CODE).is_empty());
+}
+
+#[test]
+fn test_project_validation_reports_shortest_import_cycles() {
+    let mut server = create_server();
+    let uri = open(
+        &mut server,
+        "cycle.sysml",
+        "package A {\n    public import B::*;\n}\n\
+         package B {\n    public import A::*;\n    public import C::*;\n}\n\
+         package C {\n    public import A::*;\n}\n",
+    );
+    validate(&mut server);
+
+    let diagnostics = server.get_diagnostics(&uri);
+    let messages: Vec<&str> = with_code(&diagnostics, IMPORT_CYCLE_CODE)
+        .into_iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "public import of 'B' forms an import cycle: A -> B -> A",
+            "public import of 'A' forms an import cycle: B -> A -> B",
+            "public import of 'C' forms an import cycle: B -> C -> A -> B",
+            "public import of 'A' forms an import cycle: C -> A -> B -> C",
+        ]
+    );
+}
+
+#[test]
+fn test_private_imports_do_not_form_import_cycles() {
+    let mut server = create_server();
+    let uri = open(
+        &mut server,
+        "private.sysml",
+        "package A {\n    public import B::*;\n}\n\
+         package B {\n    import A::*;\n    part def Y;\n}\n\
+         package C {\n    package Inner { part def P; }\n    public import Inner::*;\n}\n",
+    );
+    validate(&mut server);
+
+    let diagnostics = server.get_diagnostics(&uri);
+    assert!(with_code(&diagnostics, IMPORT_CYCLE_CODE).is_empty());
+}
+
+#[test]
+fn test_project_validation_clears_findings_once_fixed() {
+    let mut server = create_server();
+    open(&mut server, "a.sysml", "package P { part def Vehicle; }");
+    let b = open(&mut server, "b.sysml", "package P { part def Vehicle; }");
+    assert!(validate(&mut server).contains(&b));
+
+    server
+        .open_document(&b, "package P { part def Car; }")
+        .unwrap();
+    assert!(validate(&mut server).contains(&b));
+    assert!(with_code(&server.get_diagnostics(&b), "duplicate-name").is_empty());
+}
+
+#[test]
+fn test_project_validation_steps_through_files_in_chunks() {
+    let mut server = create_server();
+    for name in ["a", "b", "c"] {
+        let text = format!("package {name} {{ part x : Missing; }}");
+        open(&mut server, &format!("{name}.sysml"), &text);
+    }
+
+    let generation = server.start_project_validation();
+    let first = server.continue_project_validation(generation, 2).unwrap();
+    assert_eq!(first.diagnostics.len(), 2);
+    assert!(!first.finished);
+    let second = server.continue_project_validation(generation, 2).unwrap();
+    assert_eq!(second.diagnostics.len(), 1);
+    assert!(second.finished);
+    assert!(server.continue_project_validation(generation, 2).is_none());
+}
+
+#[test]
+fn test_project_validation_abandoned_by_edits() {
+    let mut server = create_server();
+    let a = open(&mut server, "a.sysml", "package P { part def A; }");
+    open(&mut server, "b.sysml", "package Q { part def B; }");
+
+    let generation = server.start_project_validation();
+    server.cancel_document_operations(&a.to_file_path().unwrap());
+    assert!(server.continue_project_validation(generation, 1).is_none());
+
+    // A new run replaces an older one
+    let older = server.start_project_validation();
+    let newer = server.start_project_validation();
+    assert!(server.continue_project_validation(older, 1).is_none());
+    assert!(server.continue_project_validation(newer, 1).is_some());
+}
@@ -0,0 +1,88 @@
+//! Tests for completion of qualified name segments after `::`
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{CompletionItem, CompletionItemKind, CompletionResponse, Position, Url};
+use std::path::Path;
+
+const MODEL: &str = "package ISQBase {
+    attribute def LengthValue;
+}
+package ISQ {
+    public import ISQBase::*;
+    attribute def MassValue;
+}
+package Vehicles {
+    package Definitions {
+        part def Vehicle;
+    }
+    part def Wheel;
+    part spare : Wheel;
+}
+package App {
+    STATEMENT
+}";
+
+fn completions(statement: &str) -> Vec<CompletionItem> {
+    let mut server = create_server();
+    let uri = Url::parse("file:///app.sysml").unwrap();
+    let text = MODEL.replace("STATEMENT", statement);
+    server.open_document(&uri, &text).unwrap();
+
+    let position = Position::new(15, 4 + statement.len() as u32);
+    match server.get_completions(Path::new("/app.sysml"), position) {
+        CompletionResponse::Array(items) => items,
+        _ => panic!("Expected completion array"),
+    }
+}
+
+fn labels(items: &[CompletionItem]) -> Vec<&str> {
+    items.iter().map(|item| item.label.as_str()).collect()
+}
+
+#[test]
+fn test_package_members_include_public_imports() {
+    let items = completions("attribute mass : ISQ::");
+    let labels = labels(&items);
+
+    assert!(labels.contains(&"MassValue"), "{labels:?}");
+    assert!(labels.contains(&"LengthValue"), "{labels:?}");
+    // Names from elsewhere aren't members of ISQ
+    assert!(!labels.contains(&"Wheel"), "{labels:?}");
+}
+
+#[test]
+fn test_nested_package_members() {
+    let items = completions("part v : Vehicles::Definitions::");
+    assert_eq!(labels(&items), ["Vehicle"]);
+}
+
+#[test]
+fn test_type_position_offers_packages_and_definitions() {
+    let items = completions("part v : Vehicles::");
+    let labels = labels(&items);
+
+    assert!(labels.contains(&"Definitions"), "{labels:?}");
+    assert!(labels.contains(&"Wheel"), "{labels:?}");
+    // A usage can't type a part
+    assert!(!labels.contains(&"spare"), "{labels:?}");
+
+    let definitions = items.iter().find(|item| item.label == "Definitions");
+    assert_eq!(
+        definitions.and_then(|item| item.kind),
+        Some(CompletionItemKind::MODULE)
+    );
+}
+
+#[test]
+fn test_import_offers_all_members() {
+    let items = completions("private import Vehicles::");
+    let labels = labels(&items);
+
+    assert!(labels.contains(&"spare"), "{labels:?}");
+    assert!(labels.contains(&"Wheel"), "{labels:?}");
+}
+
+#[test]
+fn test_unresolved_prefix_offers_nothing() {
+    assert!(completions("part v : Missing::").is_empty());
+}
@@ -0,0 +1,73 @@
+//! Tests for the syster/getQualifiedNameAt request
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Url};
+
+const SOURCE: &str = r#"package Vehicles {
+    part def Engine;
+    part def Car {
+        part engine : Engine;
+    }
+}
+package Garage {
+    import Vehicles::*;
+    part myCar : Car;
+    part other : Vehicles::Car::engine;
+}"#;
+
+fn open_source() -> (crate::server::LspServer, Url) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///names.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+    (server, uri)
+}
+
+#[test]
+fn test_qualified_name_at_declaration() {
+    let (mut server, uri) = open_source();
+
+    // Cursor on `engine` in `part engine : Engine;`
+    let result = server
+        .get_qualified_name_at(&uri, Position::new(3, 14))
+        .expect("declaration should resolve");
+
+    assert_eq!(result.qualified_name, "Vehicles::Car::engine");
+    assert_eq!(result.minimal_name, "engine");
+}
+
+#[test]
+fn test_qualified_name_at_reference() {
+    let (mut server, uri) = open_source();
+
+    // Cursor on `Car` in `part myCar : Car;`
+    let result = server
+        .get_qualified_name_at(&uri, Position::new(8, 18))
+        .expect("reference should resolve");
+
+    assert_eq!(result.qualified_name, "Vehicles::Car");
+    assert_eq!(result.minimal_name, "Car");
+    assert_eq!(result.kind, "Part def");
+}
+
+#[test]
+fn test_minimal_name_keeps_needed_qualifiers() {
+    let (mut server, uri) = open_source();
+
+    // Cursor on the qualified reference to `engine`, not visible by simple name
+    let result = server
+        .get_qualified_name_at(&uri, Position::new(9, 33))
+        .expect("qualified reference should resolve");
+
+    assert_eq!(result.qualified_name, "Vehicles::Car::engine");
+    assert_eq!(result.minimal_name, "Car::engine");
+}
+
+#[test]
+fn test_qualified_name_at_whitespace_is_none() {
+    let (mut server, uri) = open_source();
+    assert!(
+        server
+            .get_qualified_name_at(&uri, Position::new(7, 0))
+            .is_none()
+    );
+}
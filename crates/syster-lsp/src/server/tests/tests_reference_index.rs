@@ -0,0 +1,245 @@
+//! Tests for span-scoped reference index updates
+
+use crate::server::LspServer;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const TEXT: &str = "package P {
+    part def A;
+    part def B;
+    part a : A;
+    part b : B;
+    part c : A;
+}";
+
+fn edit(server: &mut LspServer, uri: &Url, range: Range, text: &str) {
+    let change = TextDocumentContentChangeEvent {
+        range: Some(range),
+        range_length: None,
+        text: text.to_string(),
+    };
+    server.apply_text_change_only(uri, &change).unwrap();
+    server.parse_document(uri);
+    // Code lenses sync the reference index
+    server.get_code_lenses(uri);
+}
+
+fn snapshot(server: &LspServer, path: &Path) -> Vec<(Arc<str>, String, Range)> {
+    server
+        .reference_index
+        .entries(path)
+        .iter()
+        .map(|entry| (entry.owner.clone(), entry.target.to_string(), entry.range))
+        .collect()
+}
+
+fn open(text: &str) -> (LspServer, Url, PathBuf) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///refs.sysml").unwrap();
+    server.open_document(&uri, text).unwrap();
+    server.get_code_lenses(&uri);
+    let path = uri.to_file_path().unwrap();
+    (server, uri, path)
+}
+
+#[test]
+fn test_edit_keeps_references_outside_edited_member() {
+    let (mut server, uri, path) = open(TEXT);
+    let before = snapshot(&server, &path);
+    assert_eq!(before.len(), 3);
+
+    // Retype `b` and give it a second line
+    edit(
+        &mut server,
+        &uri,
+        Range::new(Position::new(4, 13), Position::new(4, 14)),
+        "A;\n    part b2 : B",
+    );
+
+    let after = snapshot(&server, &path);
+    assert_eq!(after.len(), 4);
+    assert!(
+        Arc::ptr_eq(&before[0].0, &after[0].0),
+        "Reference before the edit is kept"
+    );
+    assert_eq!(after[1].1, "A");
+    assert_eq!(after[2].1, "B");
+    assert!(
+        Arc::ptr_eq(&before[2].0, &after[3].0),
+        "Reference after the edit is kept"
+    );
+    assert_eq!(
+        after[3].2,
+        Range::new(Position::new(6, 13), Position::new(6, 14)),
+        "Reference after the edit moves down a line"
+    );
+}
+
+#[test]
+fn test_edit_changing_structure_matches_full_extraction() {
+    let (mut server, uri, path) = open(TEXT);
+
+    // Closing the package early moves `c` out of it
+    edit(
+        &mut server,
+        &uri,
+        Range::new(Position::new(4, 15), Position::new(4, 15)),
+        "\n}\npackage Q {",
+    );
+
+    let text = server.get_document_text(&uri).unwrap();
+    let (fresh, _, fresh_path) = open(&text);
+    let expected: Vec<(String, String, Range)> = snapshot(&fresh, &fresh_path)
+        .into_iter()
+        .map(|(owner, target, range)| (owner.to_string(), target, range))
+        .collect();
+    let actual: Vec<(String, String, Range)> = snapshot(&server, &path)
+        .into_iter()
+        .map(|(owner, target, range)| (owner.to_string(), target, range))
+        .collect();
+    assert_eq!(actual, expected);
+    assert!(actual.iter().any(|(owner, _, _)| owner == "Q::c"));
+}
+
+#[test]
+fn test_code_lens_counts_follow_edits() {
+    let text = "part def Vehicle;\npart a : Vehicle;\npart b : Vehicle;";
+    let (mut server, uri, _) = open(text);
+
+    edit(
+        &mut server,
+        &uri,
+        Range::new(Position::new(2, 9), Position::new(2, 16)),
+        "a",
+    );
+
+    let titles: Vec<String> = server
+        .get_code_lenses(&uri)
+        .into_iter()
+        .filter_map(|lens| lens.command.map(|command| command.title))
+        .collect();
+    assert_eq!(titles, vec!["1 reference"]);
+}
+
+#[test]
+fn test_equal_names_share_one_allocation() {
+    let mut index = crate::server::reference_index::ReferenceIndex::default();
+    let entry = |owner: &str, target: &str| crate::server::reference_index::ReferenceEntry {
+        owner: Arc::from(owner),
+        target: Arc::from(target),
+        range: Range::default(),
+    };
+    index.seed(Path::new("/a.sysml"), vec![entry("A::x", "Base::T")]);
+    index.seed(Path::new("/b.sysml"), vec![entry("B::y", "Base::T")]);
+
+    let a = &index.entries(Path::new("/a.sysml"))[0];
+    let b = &index.entries(Path::new("/b.sysml"))[0];
+    assert!(Arc::ptr_eq(&a.target, &b.target));
+    assert_eq!(index.interned_names(), 3);
+
+    let targets: Vec<&Path> = index
+        .references_to("Base::T")
+        .map(|(path, _)| path)
+        .collect();
+    assert_eq!(targets.len(), 2);
+    assert_eq!(index.references_to("Base::U").count(), 0);
+}
+
+#[test]
+fn test_names_of_replaced_entries_are_released() {
+    let (mut server, uri, _) = open(TEXT);
+    let names = server.reference_index.interned_names();
+
+    // `a` now refers to a name nothing else uses; then the edit is undone
+    let retype = Range::new(Position::new(3, 13), Position::new(3, 14));
+    edit(&mut server, &uri, retype, "Missing");
+    edit(
+        &mut server,
+        &uri,
+        Range::new(Position::new(3, 13), Position::new(3, 20)),
+        "A",
+    );
+
+    assert_eq!(server.reference_index.interned_names(), names);
+}
+
+#[test]
+fn test_lookup_by_target_follows_edits() {
+    let (mut server, uri, path) = open(TEXT);
+    let files_referring_to = |server: &LspServer, target: &str| {
+        server
+            .reference_index
+            .references_to(target)
+            .map(|(path, entry)| (path.to_path_buf(), entry.range.start.line))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(files_referring_to(&server, "B"), [(path.clone(), 4)]);
+
+    // Retype `b`: it no longer refers to `B`
+    edit(
+        &mut server,
+        &uri,
+        Range::new(Position::new(4, 13), Position::new(4, 14)),
+        "A",
+    );
+    assert!(files_referring_to(&server, "B").is_empty());
+    assert_eq!(files_referring_to(&server, "A").len(), 3);
+}
+
+#[test]
+fn test_edit_leaves_other_files_entries_untouched() {
+    let (mut server, uri, path) = open(TEXT);
+    let other = Url::parse("file:///other.sysml").unwrap();
+    let other_path = other.to_file_path().unwrap();
+    server
+        .open_document(
+            &other,
+            "package Q {\n    part def D :> P::A;\n    part q : P::B;\n}",
+        )
+        .unwrap();
+    let sync = |server: &mut LspServer| {
+        let analysis = server.analysis_host.analysis();
+        server.reference_index.sync(&analysis);
+        server.symbol_lookup.sync(&analysis);
+    };
+    sync(&mut server);
+    let references = server.reference_index.entries(&other_path).to_vec();
+    let symbols = server.symbol_lookup.in_file(&other_path).to_vec();
+    let allocations = |server: &LspServer| {
+        (
+            server.reference_index.entries(&other_path).as_ptr(),
+            server.symbol_lookup.in_file(&other_path).as_ptr(),
+        )
+    };
+    let before = allocations(&server);
+
+    // Retype `b` and add a definition to the edited file
+    edit(
+        &mut server,
+        &uri,
+        Range::new(Position::new(4, 13), Position::new(4, 14)),
+        "A;\n    part def E",
+    );
+    sync(&mut server);
+
+    // The edited file's symbols and references are replaced...
+    assert_eq!(snapshot(&server, &path)[1].1, "A");
+    assert!(
+        server
+            .symbol_lookup
+            .in_file(&path)
+            .iter()
+            .any(|entry| entry.name.as_ref() == "E")
+    );
+    // ...while the other file's, relationships included, are neither rebuilt nor copied
+    assert_eq!(allocations(&server), before);
+    assert_eq!(server.reference_index.entries(&other_path), references);
+    assert_eq!(server.symbol_lookup.in_file(&other_path), symbols);
+    assert!(
+        references
+            .iter()
+            .any(|entry| entry.target.as_ref() == "P::A")
+    );
+}
@@ -0,0 +1,229 @@
+//! Tests for find-references searched in shards
+
+use crate::server::LspServer;
+use crate::server::reference_search::{ReferenceSearchJob, ReferenceShard};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Location, Position, Range, Url};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn open_model(server: &mut LspServer) -> (Url, Url) {
+    let base_uri = Url::parse("file:///project/base.sysml").unwrap();
+    let model_uri = Url::parse("file:///project/model.sysml").unwrap();
+
+    server
+        .open_document(
+            &base_uri,
+            "package Base {\n    part def Engine;\n    part spare : Engine;\n}\n",
+        )
+        .unwrap();
+    server
+        .open_document(
+            &model_uri,
+            "package Model {\n    import Base::*;\n    part engine : Engine;\n}\n\
+             package Decoy {\n    part def Engine;\n    part other : Engine;\n}\n",
+        )
+        .unwrap();
+
+    (base_uri, model_uri)
+}
+
+/// Scan every shard on this thread, as the background workers would
+fn run_search(server: &mut LspServer, job: ReferenceSearchJob) -> Option<Vec<Location>> {
+    for shard in &job.shards {
+        let candidates = shard.candidates(&job.names);
+        if !candidates.is_empty() {
+            server.accept_reference_candidates(job.id, &shard.path, &candidates);
+        }
+    }
+    server.finish_reference_search(job.id)
+}
+
+#[test]
+fn test_sharded_search_matches_direct_search() {
+    let mut server = create_server();
+    let (base_uri, model_uri) = open_model(&mut server);
+    let position = Position::new(2, 19);
+
+    let job = server
+        .start_reference_search(&model_uri, position, true)
+        .unwrap();
+    assert_eq!(job.names, [Arc::<str>::from("Engine")]);
+    let locations = run_search(&mut server, job).unwrap();
+
+    let mut expected = server.get_references(&model_uri, position, true).unwrap();
+    expected.sort_by_key(|l| (l.uri.to_string(), l.range.start));
+    assert_eq!(locations, expected);
+
+    // Decoy::Engine shares the name but isn't a reference
+    assert_eq!(
+        locations,
+        [
+            Location {
+                uri: base_uri.clone(),
+                range: Range::new(Position::new(1, 13), Position::new(1, 19)),
+            },
+            Location {
+                uri: base_uri,
+                range: Range::new(Position::new(2, 17), Position::new(2, 23)),
+            },
+            Location {
+                uri: model_uri,
+                range: Range::new(Position::new(2, 18), Position::new(2, 24)),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_search_from_the_declaration() {
+    let mut server = create_server();
+    let (base_uri, model_uri) = open_model(&mut server);
+
+    let job = server
+        .start_reference_search(&base_uri, Position::new(1, 15), false)
+        .unwrap();
+    let locations = run_search(&mut server, job).unwrap();
+
+    let uris: Vec<&Url> = locations.iter().map(|l| &l.uri).collect();
+    assert_eq!(uris, [&base_uri, &model_uri]);
+}
+
+#[test]
+fn test_search_without_element_at_position() {
+    let mut server = create_server();
+    let (base_uri, _) = open_model(&mut server);
+
+    assert!(
+        server
+            .start_reference_search(&base_uri, Position::new(3, 0), true)
+            .is_none()
+    );
+}
+
+#[test]
+fn test_unreported_references_are_taken_once() {
+    let mut server = create_server();
+    let (_, model_uri) = open_model(&mut server);
+
+    let job = server
+        .start_reference_search(&model_uri, Position::new(2, 19), true)
+        .unwrap();
+    // The declaration is known before any shard is scanned
+    assert_eq!(server.take_unreported_references(job.id).len(), 1);
+    assert!(server.take_unreported_references(job.id).is_empty());
+
+    let shard = job
+        .shards
+        .iter()
+        .find(|shard| shard.path == PathBuf::from("/project/model.sysml"))
+        .unwrap();
+    let candidates = shard.candidates(&job.names);
+    assert_eq!(candidates.len(), 2);
+    assert!(server.accept_reference_candidates(job.id, &shard.path, &candidates));
+    assert_eq!(server.take_unreported_references(job.id).len(), 1);
+
+    // The final result still has every reference
+    let mut job = job;
+    job.shards
+        .retain(|shard| shard.path != PathBuf::from("/project/model.sysml"));
+    assert_eq!(run_search(&mut server, job).unwrap().len(), 3);
+}
+
+#[test]
+fn test_edit_cancels_search() {
+    let mut server = create_server();
+    let (base_uri, model_uri) = open_model(&mut server);
+
+    let job = server
+        .start_reference_search(&model_uri, Position::new(2, 19), false)
+        .unwrap();
+    server.cancel_document_operations(&base_uri.to_file_path().unwrap());
+
+    assert!(job.is_cancelled());
+    assert!(run_search(&mut server, job).is_none());
+}
+
+#[test]
+fn test_cancelled_search_is_forgotten() {
+    let mut server = create_server();
+    let (_, model_uri) = open_model(&mut server);
+
+    let job = server
+        .start_reference_search(&model_uri, Position::new(2, 19), false)
+        .unwrap();
+    server.cancel_reference_search(job.id);
+
+    assert!(job.is_cancelled());
+    assert!(server.finish_reference_search(job.id).is_none());
+}
+
+#[test]
+fn test_dropped_response_cancels_search() {
+    let mut server = create_server();
+    let (_, model_uri) = open_model(&mut server);
+
+    let job = server
+        .start_reference_search(&model_uri, Position::new(2, 19), false)
+        .unwrap();
+    // What happens to the response future on `$/cancelRequest`
+    drop(job.cancel_on_drop());
+
+    assert!(job.is_cancelled());
+    let shard = &job.shards[0];
+    let candidates = shard.candidates(&job.names);
+    assert!(!server.accept_reference_candidates(job.id, &shard.path, &candidates));
+    assert!(server.take_unreported_references(job.id).is_empty());
+    assert!(server.finish_reference_search(job.id).is_none());
+}
+
+#[test]
+fn test_search_includes_usages_written_with_an_alias() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///alias.sysml").unwrap();
+    server
+        .open_document(
+            &uri,
+            "package P {\n    attribute def DurationValue;\n    alias Time for DurationValue;\n    \
+             part def Timer {\n        attribute elapsed : Time;\n        \
+             attribute limit : DurationValue;\n    }\n}",
+        )
+        .unwrap();
+    let position = Position::new(1, 20);
+
+    let job = server
+        .start_reference_search(&uri, position, false)
+        .unwrap();
+    assert!(job.names.contains(&Arc::from("Time")));
+    let locations = run_search(&mut server, job).unwrap();
+
+    let mut expected = server.get_references(&uri, position, false).unwrap();
+    expected.sort_by_key(|l| (l.uri.to_string(), l.range.start));
+    assert_eq!(locations, expected);
+    assert!(locations.iter().any(|l| l.range.start.line == 4));
+}
+
+#[test]
+fn test_shard_candidates_match_last_segment() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///shard.sysml").unwrap();
+    server
+        .open_document(
+            &uri,
+            "package P {\n    part def Engine;\n    part def Engines;\n    \
+             part a : Engine;\n    part b : P::Engine;\n    part c : Engines;\n}\n",
+        )
+        .unwrap();
+
+    let job = server
+        .start_reference_search(&uri, Position::new(1, 15), false)
+        .unwrap();
+    let shard: &ReferenceShard = &job.shards[0];
+    let candidates = shard.candidates(&[Arc::from("Engine")]);
+    let lines: Vec<u32> = candidates
+        .iter()
+        .map(|entry| entry.range.start.line)
+        .collect();
+    // `Engines` doesn't match
+    assert_eq!(lines, [3, 4]);
+}
@@ -0,0 +1,96 @@
+//! Tests for workspace-wide Find All References
+
+use crate::server::LspServer;
+use crate::server::configuration::ServerSettings;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Url};
+use std::path::PathBuf;
+
+fn open_library_and_model(server: &mut LspServer) -> (Url, Url, Url) {
+    let lib_uri = Url::parse("file:///project/sysml.library/Base.sysml").unwrap();
+    let model_uri = Url::parse("file:///project/model.sysml").unwrap();
+    let other_uri = Url::parse("file:///project/other.sysml").unwrap();
+
+    server
+        .open_document(
+            &lib_uri,
+            "package Base {\n    part def Engine;\n    part spare : Engine;\n}\n",
+        )
+        .unwrap();
+    server
+        .open_document(
+            &model_uri,
+            "package Model {\n    import Base::*;\n    part engine : Engine;\n}\n",
+        )
+        .unwrap();
+    server
+        .open_document(
+            &other_uri,
+            "package Other {\n    import Base::*;\n    part backup : Engine;\n}\n",
+        )
+        .unwrap();
+
+    (lib_uri, model_uri, other_uri)
+}
+
+#[test]
+fn test_references_span_all_loaded_files() {
+    let mut server = create_server();
+    let (lib_uri, model_uri, other_uri) = open_library_and_model(&mut server);
+
+    // Request from the usage in model.sysml
+    let locations = server
+        .get_references(&model_uri, Position::new(2, 19), false)
+        .unwrap();
+
+    let uris: Vec<&Url> = locations.iter().map(|l| &l.uri).collect();
+    assert!(uris.contains(&&model_uri));
+    assert!(uris.contains(&&other_uri));
+    assert!(uris.contains(&&lib_uri));
+    assert_eq!(locations.len(), 3);
+}
+
+#[test]
+fn test_references_can_exclude_stdlib() {
+    let mut server = create_server();
+    let (lib_uri, model_uri, other_uri) = open_library_and_model(&mut server);
+
+    let locations = server
+        .get_references_with_options(&model_uri, Position::new(2, 19), true, true)
+        .unwrap();
+
+    assert!(locations.iter().all(|l| l.uri != lib_uri));
+    assert!(locations.iter().any(|l| l.uri == model_uri));
+    assert!(locations.iter().any(|l| l.uri == other_uri));
+}
+
+#[test]
+fn test_references_exclude_stdlib_from_server_setting() {
+    let mut server = create_server();
+    server.set_references_exclude_stdlib(true);
+    let (lib_uri, model_uri, _) = open_library_and_model(&mut server);
+
+    let locations = server
+        .get_references(&model_uri, Position::new(2, 19), true)
+        .unwrap();
+
+    assert!(!locations.is_empty());
+    assert!(locations.iter().all(|l| l.uri != lib_uri));
+}
+
+#[test]
+fn test_is_stdlib_path_matches_custom_path() {
+    let server = LspServer::with_config(false, Some(PathBuf::from("/opt/kernel")));
+
+    assert!(server.is_stdlib_path(&PathBuf::from("/opt/kernel/Base.kerml")));
+    assert!(server.is_stdlib_path(&PathBuf::from("/x/sysml.library/Parts.sysml")));
+    assert!(!server.is_stdlib_path(&PathBuf::from("/project/model.sysml")));
+}
+
+#[test]
+fn test_parse_references_exclude_stdlib_option() {
+    let opts = serde_json::json!({ "referencesExcludeStdlib": true });
+    assert!(ServerSettings::from_options(Some(&opts)).references_exclude_stdlib);
+    assert!(!ServerSettings::from_options(None).references_exclude_stdlib);
+    assert!(!ServerSettings::from_options(Some(&serde_json::json!({}))).references_exclude_stdlib);
+}
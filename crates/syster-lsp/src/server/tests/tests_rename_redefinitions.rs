@@ -0,0 +1,111 @@
+//! Tests for renaming features together with their redefinitions
+
+use crate::server::LspServer;
+use crate::server::configuration::ServerSettings;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, Url};
+
+const MODEL: &str = "package P {
+    part def Vehicle {
+        attribute drivePowerOutputRequirement;
+    }
+    part def Car :> Vehicle {
+        attribute :>> drivePowerOutputRequirement;
+    }
+    part def SportsCar :> Car {
+        attribute :>> drivePowerOutputRequirement;
+        attribute peak :>> drivePowerOutputRequirement;
+    }
+}";
+
+fn renamed_ranges(rename_redefinitions: bool) -> Vec<Range> {
+    let mut server: LspServer = create_server();
+    server.set_rename_redefinitions(rename_redefinitions);
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, MODEL).unwrap();
+
+    let edit = server
+        .get_rename_edits(&uri, Position::new(2, 20), "powerOutput")
+        .unwrap();
+    let mut ranges: Vec<Range> = edit.changes.unwrap()[&uri]
+        .iter()
+        .map(|edit| edit.range)
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    ranges
+}
+
+fn line(range: &Range) -> u32 {
+    range.start.line
+}
+
+#[test]
+fn test_rename_leaves_indirect_redefinitions_by_default() {
+    let lines: Vec<u32> = renamed_ranges(false).iter().map(line).collect();
+    assert!(lines.contains(&2), "{lines:?}");
+    assert!(!lines.contains(&8), "{lines:?}");
+}
+
+#[test]
+fn test_rename_follows_redefinitions_through_subtypes() {
+    let ranges = renamed_ranges(true);
+    let lines: Vec<u32> = ranges.iter().map(line).collect();
+    // The declaration, Car's redefinition, SportsCar's redefinition of Car's
+    // and the reference of the differently named `peak`
+    assert_eq!(lines, [2, 5, 8, 9], "{ranges:?}");
+    for range in &ranges[1..] {
+        assert_eq!(
+            range.end.character - range.start.character,
+            "drivePowerOutputRequirement".len() as u32
+        );
+    }
+}
+
+#[test]
+fn test_differently_named_redefinitions_keep_their_name() {
+    let ranges = renamed_ranges(true);
+    // `peak` itself isn't renamed, only what it redefines
+    let peak = ranges.iter().find(|range| range.start.line == 9).unwrap();
+    assert_eq!(peak.start.character, 27);
+}
+
+#[test]
+fn test_rename_follows_named_redefinitions() {
+    let mut server: LspServer = create_server();
+    server.set_rename_redefinitions(true);
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {
+    part def Vehicle {
+        attribute mass;
+    }
+    part def Car :> Vehicle {
+        attribute mass :>> mass;
+    }
+}";
+    server.open_document(&uri, text).unwrap();
+
+    let edit = server
+        .get_rename_edits(&uri, Position::new(2, 18), "weight")
+        .unwrap();
+    let mut starts: Vec<Position> = edit.changes.unwrap()[&uri]
+        .iter()
+        .map(|edit| edit.range.start)
+        .collect();
+    starts.sort();
+    // The declaration, then the redefinition's name and its reference
+    assert_eq!(
+        starts,
+        [
+            Position::new(2, 18),
+            Position::new(5, 18),
+            Position::new(5, 27)
+        ]
+    );
+}
+
+#[test]
+fn test_rename_redefinitions_option() {
+    let options = serde_json::json!({ "renameRedefinitions": true });
+    assert!(ServerSettings::from_options(Some(&options)).rename_redefinitions);
+    assert!(!ServerSettings::from_options(None).rename_redefinitions);
+}
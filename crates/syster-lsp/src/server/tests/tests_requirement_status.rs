@@ -0,0 +1,93 @@
+//! Tests for the requirement status decorations
+
+use crate::server::LspServer;
+use crate::server::requirement_status::{RequirementDecoration, RequirementStatus};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, Url};
+use std::path::Path;
+
+const MODEL: &str = "package Reqs {
+    requirement def MassLimit;
+    requirement vehicleMass : MassLimit;
+    requirement engineMass;
+    requirement cost;
+    requirement noise;
+    part def Vehicle {
+        satisfy vehicleMass;
+        satisfy engineMass;
+    }
+    verification def Tests {
+        objective {
+            verify vehicleMass;
+            verify noise;
+        }
+    }
+}";
+
+fn decorations() -> Vec<RequirementDecoration> {
+    let mut server: LspServer = create_server();
+    let uri = Url::parse("file:///reqs.sysml").unwrap();
+    server.set_document_version(&uri, 1);
+    server.open_document(&uri, MODEL).unwrap();
+    server
+        .requirement_decorations(Path::new("/reqs.sysml"))
+        .decorations
+}
+
+fn status(decorations: &[RequirementDecoration], name: &str) -> RequirementStatus {
+    decorations
+        .iter()
+        .find(|decoration| decoration.qualified_name == name)
+        .unwrap_or_else(|| panic!("no {name} in {decorations:?}"))
+        .status
+}
+
+#[test]
+fn test_decorations_report_each_requirement_line() {
+    let decorations = decorations();
+    let lines: Vec<u32> = decorations.iter().map(|d| d.line).collect();
+    assert_eq!(lines, [1, 2, 3, 4, 5]);
+    assert_eq!(
+        decorations[0].range,
+        Range::new(Position::new(1, 20), Position::new(1, 29))
+    );
+}
+
+#[test]
+fn test_requirement_statuses() {
+    let decorations = decorations();
+    assert_eq!(
+        status(&decorations, "Reqs::vehicleMass"),
+        RequirementStatus::Verified
+    );
+    // Covered by its satisfied and verified usage
+    assert_eq!(
+        status(&decorations, "Reqs::MassLimit"),
+        RequirementStatus::Verified
+    );
+    assert_eq!(
+        status(&decorations, "Reqs::engineMass"),
+        RequirementStatus::Satisfied
+    );
+    assert_eq!(status(&decorations, "Reqs::cost"), RequirementStatus::Open);
+}
+
+#[test]
+fn test_verified_requirements_may_be_unsatisfied() {
+    let decorations = decorations();
+    let noise = decorations
+        .iter()
+        .find(|decoration| decoration.qualified_name == "Reqs::noise")
+        .unwrap();
+    assert_eq!(noise.status, RequirementStatus::Verified);
+    assert!(noise.verified);
+    assert!(!noise.satisfied);
+}
+
+#[test]
+fn test_status_serializes_lowercase() {
+    assert_eq!(
+        serde_json::to_value(RequirementStatus::Satisfied).unwrap(),
+        "satisfied"
+    );
+}
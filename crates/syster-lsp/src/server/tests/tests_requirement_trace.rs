@@ -0,0 +1,176 @@
+//! Tests for the requirement traceability report
+
+use crate::server::LspServer;
+use crate::server::requirement_trace::{RequirementTrace, ValidateRequirementsParams};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, Url};
+use std::path::Path;
+
+const REQUIREMENTS: &str = "package Reqs {
+    requirement def MassLimit;
+    requirement vehicleMass : MassLimit;
+    requirement engineMass;
+    requirement cost;
+    #derivation connection {
+        end #original ::> vehicleMass;
+        end #derive ::> engineMass;
+    }
+}";
+
+const DESIGN: &str = "package Design {
+    private import Reqs::*;
+    part def Vehicle {
+        part engine;
+        satisfy vehicleMass by engine;
+    }
+    part car : Vehicle {
+        satisfy requirement engineMass;
+    }
+    verification def MassTest {
+        objective {
+            verify vehicleMass;
+        }
+    }
+}";
+
+fn open_workspace() -> LspServer {
+    let mut server = create_server();
+    let reqs = Url::parse("file:///reqs.sysml").unwrap();
+    let design = Url::parse("file:///design.sysml").unwrap();
+    for (uri, text) in [(&reqs, REQUIREMENTS), (&design, DESIGN)] {
+        server.set_document_version(uri, 1);
+        server.open_document(uri, text).unwrap();
+    }
+    server
+}
+
+fn trace<'a>(traces: &'a [RequirementTrace], name: &str) -> &'a RequirementTrace {
+    traces
+        .iter()
+        .find(|trace| trace.qualified_name == name)
+        .unwrap_or_else(|| panic!("no {name} in {traces:?}"))
+}
+
+#[test]
+fn test_report_lists_requirements_in_order() {
+    let mut server = open_workspace();
+    let report = server.validate_requirements(None);
+    let names: Vec<&str> = report
+        .requirements
+        .iter()
+        .map(|trace| trace.qualified_name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "Reqs::MassLimit",
+            "Reqs::vehicleMass",
+            "Reqs::engineMass",
+            "Reqs::cost"
+        ]
+    );
+    let mass_limit = trace(&report.requirements, "Reqs::MassLimit");
+    assert_eq!(mass_limit.uri, "file:///reqs.sysml");
+    assert_eq!(
+        mass_limit.range,
+        Range::new(Position::new(1, 20), Position::new(1, 29))
+    );
+}
+
+#[test]
+fn test_satisfy_statements_link_their_subject() {
+    let mut server = open_workspace();
+    let report = server.validate_requirements(None);
+
+    let vehicle_mass = trace(&report.requirements, "Reqs::vehicleMass");
+    let elements: Vec<&str> = vehicle_mass
+        .satisfied_by
+        .iter()
+        .map(|link| link.element.as_str())
+        .collect();
+    assert_eq!(elements, ["Design::Vehicle::engine"]);
+    assert_eq!(vehicle_mass.satisfied_by[0].uri, "file:///design.sysml");
+    assert_eq!(
+        vehicle_mass.satisfied_by[0].range,
+        Range::new(Position::new(4, 8), Position::new(4, 37))
+    );
+
+    // Without `by`, the enclosing element is the subject
+    let engine_mass = trace(&report.requirements, "Reqs::engineMass");
+    assert_eq!(engine_mass.satisfied_by[0].element, "Design::car");
+}
+
+#[test]
+fn test_verify_statements_link_the_verification_case() {
+    let mut server = open_workspace();
+    let report = server.validate_requirements(None);
+
+    let vehicle_mass = trace(&report.requirements, "Reqs::vehicleMass");
+    assert_eq!(vehicle_mass.verified_by.len(), 1);
+    assert_eq!(vehicle_mass.verified_by[0].element, "Design::MassTest");
+    assert!(
+        trace(&report.requirements, "Reqs::engineMass")
+            .verified_by
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_derivation_connections_link_both_ends() {
+    let mut server = open_workspace();
+    let report = server.validate_requirements(None);
+
+    assert_eq!(
+        trace(&report.requirements, "Reqs::vehicleMass").derived,
+        ["Reqs::engineMass"]
+    );
+    assert_eq!(
+        trace(&report.requirements, "Reqs::engineMass").derived_from,
+        ["Reqs::vehicleMass"]
+    );
+}
+
+#[test]
+fn test_unsatisfied_and_unverified_requirements() {
+    let mut server = open_workspace();
+    let report = server.validate_requirements(None);
+
+    // The definition is covered by its satisfied and verified usage
+    assert_eq!(report.unsatisfied, ["Reqs::cost"]);
+    assert_eq!(report.unverified, ["Reqs::engineMass", "Reqs::cost"]);
+}
+
+#[test]
+fn test_negated_satisfy_satisfies_nothing() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.set_document_version(&uri, 1);
+    server
+        .open_document(
+            &uri,
+            "package P {\n    requirement r;\n    part p {\n        not satisfy r;\n    }\n}",
+        )
+        .unwrap();
+
+    let report = server.validate_requirements(None);
+    assert_eq!(report.unsatisfied, ["P::r"]);
+}
+
+#[test]
+fn test_report_for_one_file() {
+    let mut server = open_workspace();
+    assert!(
+        server
+            .validate_requirements(Some(Path::new("/design.sysml")))
+            .requirements
+            .is_empty()
+    );
+
+    let report = server.validate_requirements(Some(Path::new("/reqs.sysml")));
+    assert_eq!(report.requirements.len(), 4);
+    // Links from other files are still found
+    assert_eq!(report.unsatisfied, ["Reqs::cost"]);
+
+    let params: ValidateRequirementsParams = serde_json::from_value(serde_json::json!({})).unwrap();
+    assert!(params.uri.is_none());
+}
@@ -0,0 +1,124 @@
+//! Tests for the syster/resolveSpans request
+
+use crate::server::LspServer;
+use crate::server::resolve_spans::{ElementSpan, ResolveSpansParams};
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, Url};
+
+const SOURCE: &str =
+    "package Vehicles {\n    part def Vehicle;\n    // ünïcode\n    part def Engine;\n}";
+
+fn open_model() -> (LspServer, Url) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Vehicles.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+    (server, uri)
+}
+
+fn element(name: &str) -> ElementSpan {
+    ElementSpan {
+        qualified_name: Some(name.to_string()),
+        ..Default::default()
+    }
+}
+
+fn byte_span(uri: Option<&Url>, text: &str) -> ElementSpan {
+    let start = SOURCE.find(text).unwrap();
+    ElementSpan {
+        uri: uri.map(|uri| uri.to_string()),
+        start: Some(start),
+        end: Some(start + text.len()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_resolve_elements_in_request_order() {
+    let (mut server, uri) = open_model();
+    let params = ResolveSpansParams {
+        spans: vec![
+            element("Vehicles::Engine"),
+            element("Vehicles::Missing"),
+            element("Vehicles::Vehicle"),
+        ],
+    };
+
+    let result = server.resolve_spans(&params);
+
+    assert_eq!(result.locations.len(), 3);
+    let engine = result.locations[0].as_ref().unwrap();
+    assert_eq!(engine.uri, uri);
+    assert_eq!(engine.range.start.line, 3);
+    assert!(result.locations[1].is_none());
+    assert_eq!(result.locations[2].as_ref().unwrap().range.start.line, 1);
+}
+
+#[test]
+fn test_resolve_byte_spans() {
+    let (mut server, uri) = open_model();
+    let params = ResolveSpansParams {
+        spans: vec![
+            byte_span(Some(&uri), "Vehicle;"),
+            byte_span(Some(&uri), "Engine"),
+        ],
+    };
+
+    let result = server.resolve_spans(&params);
+
+    assert_eq!(
+        result.locations[0].as_ref().unwrap().range,
+        Range::new(Position::new(1, 13), Position::new(1, 21))
+    );
+    // Offsets are bytes, characters are UTF-16 code units
+    assert_eq!(
+        result.locations[1].as_ref().unwrap().range,
+        Range::new(Position::new(3, 13), Position::new(3, 19))
+    );
+}
+
+#[test]
+fn test_resolve_byte_span_in_element_file() {
+    let (mut server, uri) = open_model();
+    let mut span = byte_span(None, "ünïcode");
+    span.qualified_name = Some("Vehicles::Engine".to_string());
+
+    let result = server.resolve_spans(&ResolveSpansParams { spans: vec![span] });
+
+    let location = result.locations[0].as_ref().unwrap();
+    assert_eq!(location.uri, uri);
+    assert_eq!(
+        location.range,
+        Range::new(Position::new(2, 7), Position::new(2, 14))
+    );
+}
+
+#[test]
+fn test_resolve_invalid_spans() {
+    let (mut server, uri) = open_model();
+    let unicode = SOURCE.find('ü').unwrap();
+    let invalid = |start, end| ElementSpan {
+        uri: Some(uri.to_string()),
+        start: Some(start),
+        end: Some(end),
+        ..Default::default()
+    };
+    let params = ResolveSpansParams {
+        spans: vec![
+            // Inside a multi-byte character
+            invalid(unicode + 1, unicode + 3),
+            // Past the end of the file
+            invalid(0, SOURCE.len() + 1),
+            // Reversed
+            invalid(10, 5),
+            // Unknown file
+            byte_span(
+                Some(&Url::parse("file:///missing/Other.sysml").unwrap()),
+                "part",
+            ),
+        ],
+    };
+
+    let result = server.resolve_spans(&params);
+
+    assert_eq!(result.locations, vec![None, None, None, None]);
+}
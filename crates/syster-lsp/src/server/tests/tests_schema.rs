@@ -0,0 +1,434 @@
+//! Tests pinning the schema of the custom request payloads
+//!
+//! A failing field list means the payload changed shape: if a field was
+//! renamed, removed or changed type, bump `SCHEMA_VERSION` along with it.
+
+use crate::server::LspServer;
+use crate::server::diagram::DiagramData;
+use crate::server::diagram_text::DiagramTextFormat;
+use crate::server::memory_stats::MemoryStats;
+use crate::server::model_export::ModelExport;
+use crate::server::resolve_spans::{ElementSpan, ResolveSpansParams};
+use crate::server::schema::SCHEMA_VERSION;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Url};
+use serde_json::{Value, json};
+
+const SOURCE: &str = "package Vehicles {
+    part def Engine;
+    part def Vehicle { part engine : Engine; }
+}";
+
+fn payloads() -> (Value, Value, Value) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Vehicles.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+
+    let diagram = server.get_diagram(
+        Some(&uri.to_file_path().unwrap()),
+        None,
+        None,
+        "GeneralView",
+    );
+    let export = server.export_model(None, false);
+    let stats = server.get_memory_stats(false);
+    (
+        serde_json::to_value(diagram).unwrap(),
+        serde_json::to_value(export).unwrap(),
+        serde_json::to_value(stats).unwrap(),
+    )
+}
+
+/// Field names of a JSON object, sorted
+fn fields(value: &Value) -> Vec<&str> {
+    let mut fields: Vec<&str> = value
+        .as_object()
+        .expect("payload object")
+        .keys()
+        .map(String::as_str)
+        .collect();
+    fields.sort_unstable();
+    fields
+}
+
+#[test]
+fn test_payloads_carry_schema_version() {
+    let (diagram, export, stats) = payloads();
+    assert_eq!(diagram["schemaVersion"], SCHEMA_VERSION);
+    assert_eq!(export["schemaVersion"], SCHEMA_VERSION);
+    assert_eq!(stats["schemaVersion"], SCHEMA_VERSION);
+}
+
+#[test]
+fn test_every_custom_payload_carries_schema_version() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Vehicles.sysml").unwrap();
+    let path = uri.to_file_path().unwrap();
+    server.set_document_version(&uri, 1);
+    server.open_document(&uri, SOURCE).unwrap();
+    // The reference to Engine in `part engine : Engine`
+    let reference = Position::new(2, 38);
+    let spans = ResolveSpansParams {
+        spans: vec![ElementSpan {
+            qualified_name: Some("Vehicles::Engine".to_string()),
+            ..Default::default()
+        }],
+    };
+
+    let payloads = [
+        (
+            "typeInfo",
+            serde_json::to_value(server.get_type_info(&uri, reference)),
+        ),
+        ("healthCheck", serde_json::to_value(server.health_check())),
+        (
+            "resolveSpans",
+            serde_json::to_value(server.resolve_spans(&spans)),
+        ),
+        (
+            "getFeatureSupportMatrix",
+            serde_json::to_value(LspServer::feature_support_matrix()),
+        ),
+        (
+            "exportDiagramText",
+            serde_json::to_value(server.export_diagram_text(
+                Some(&path),
+                None,
+                "GeneralView",
+                DiagramTextFormat::Mermaid,
+            )),
+        ),
+        (
+            "getQualifiedNameAt",
+            serde_json::to_value(server.get_qualified_name_at(&uri, reference)),
+        ),
+        (
+            "getImplicitSupertype",
+            serde_json::to_value(server.get_implicit_supertype(&uri, Position::new(1, 13))),
+        ),
+        (
+            "exportWorkspaceSnapshot",
+            serde_json::to_value(server.export_workspace_snapshot(false)),
+        ),
+        (
+            "getDiagram",
+            serde_json::to_value(server.get_diagram(Some(&path), None, None, "GeneralView")),
+        ),
+        (
+            "exportModel",
+            serde_json::to_value(server.export_model(None, false)),
+        ),
+        (
+            "getDependencyGraph",
+            serde_json::to_value(server.get_dependency_graph(false)),
+        ),
+        (
+            "unusedElements",
+            serde_json::to_value(server.get_unused_elements(None)),
+        ),
+        (
+            "validateRequirements",
+            serde_json::to_value(server.validate_requirements(None)),
+        ),
+        (
+            "requirementDecorations",
+            serde_json::to_value(server.requirement_decorations(&path)),
+        ),
+        (
+            "getMemoryStats",
+            serde_json::to_value(server.get_memory_stats(false)),
+        ),
+    ];
+    for (method, payload) in payloads {
+        let payload = payload.unwrap();
+        assert_eq!(
+            payload["schemaVersion"], SCHEMA_VERSION,
+            "syster/{method} payload has no schema version: {payload}"
+        );
+    }
+}
+
+#[test]
+fn test_diagram_fields() {
+    let (diagram, _, _) = payloads();
+    assert_eq!(
+        fields(&diagram),
+        ["relationships", "schemaVersion", "symbols", "viewType"]
+    );
+
+    let engine = diagram["symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|symbol| symbol["qualifiedName"] == "Vehicles::Vehicle::engine")
+        .unwrap();
+    assert_eq!(
+        fields(engine),
+        ["name", "nodeType", "parent", "qualifiedName", "typedBy"]
+    );
+    assert_eq!(
+        fields(&diagram["relationships"][0]),
+        ["source", "target", "type"]
+    );
+}
+
+#[test]
+fn test_model_export_fields() {
+    let (_, export, _) = payloads();
+    assert_eq!(fields(&export), ["files", "schemaVersion", "serverVersion"]);
+    assert_eq!(fields(&export["files"][0]), ["elements", "uri"]);
+
+    let engine = &export["files"][0]["elements"][3];
+    assert_eq!(engine["qualifiedName"], "Vehicles::Vehicle::engine");
+    assert_eq!(
+        fields(engine),
+        [
+            "kind",
+            "name",
+            "owner",
+            "qualifiedName",
+            "range",
+            "relationships"
+        ]
+    );
+    assert_eq!(
+        fields(&engine["relationships"][0]),
+        ["kind", "range", "resolved", "target"]
+    );
+}
+
+#[test]
+fn test_dependency_graph_fields() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Graph.sysml").unwrap();
+    server
+        .open_document(
+            &uri,
+            "package A { public import B::*; }\npackage B { public import C::*; }\npackage C;",
+        )
+        .unwrap();
+    let graph = serde_json::to_value(server.get_dependency_graph(false)).unwrap();
+
+    assert_eq!(graph["schemaVersion"], SCHEMA_VERSION);
+    assert_eq!(fields(&graph), ["imports", "packages", "schemaVersion"]);
+    assert_eq!(
+        fields(&graph["packages"][0]),
+        ["qualifiedName", "stdlib", "uri"]
+    );
+    // A -> C through B
+    assert_eq!(
+        fields(&graph["imports"][1]),
+        ["public", "source", "target", "via"]
+    );
+}
+
+#[test]
+fn test_unused_elements_fields() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Unused.sysml").unwrap();
+    server.set_document_version(&uri, 1);
+    server.open_document(&uri, "part def Orphan;").unwrap();
+    let unused = serde_json::to_value(server.get_unused_elements(None)).unwrap();
+
+    assert_eq!(unused["schemaVersion"], SCHEMA_VERSION);
+    assert_eq!(fields(&unused), ["elements", "schemaVersion"]);
+    assert_eq!(
+        fields(&unused["elements"][0]),
+        ["kind", "qualifiedName", "range", "uri"]
+    );
+}
+
+#[test]
+fn test_requirements_report_fields() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Requirements.sysml").unwrap();
+    server.set_document_version(&uri, 1);
+    server
+        .open_document(
+            &uri,
+            "package P {\n    requirement r;\n    part p { satisfy r; }\n}",
+        )
+        .unwrap();
+    let report = serde_json::to_value(server.validate_requirements(None)).unwrap();
+
+    assert_eq!(report["schemaVersion"], SCHEMA_VERSION);
+    assert_eq!(
+        fields(&report),
+        ["requirements", "schemaVersion", "unsatisfied", "unverified"]
+    );
+    let requirement = &report["requirements"][0];
+    assert_eq!(
+        fields(requirement),
+        [
+            "derived",
+            "derivedFrom",
+            "kind",
+            "qualifiedName",
+            "range",
+            "satisfiedBy",
+            "uri",
+            "verifiedBy"
+        ]
+    );
+    assert_eq!(
+        fields(&requirement["satisfiedBy"][0]),
+        ["element", "range", "uri"]
+    );
+}
+
+#[test]
+fn test_requirement_decorations_fields() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test/Requirements.sysml").unwrap();
+    server.set_document_version(&uri, 1);
+    server
+        .open_document(&uri, "package P {\n    requirement r;\n}")
+        .unwrap();
+    let decorations =
+        serde_json::to_value(server.requirement_decorations(&uri.to_file_path().unwrap())).unwrap();
+
+    assert_eq!(decorations["schemaVersion"], SCHEMA_VERSION);
+    assert_eq!(fields(&decorations), ["decorations", "schemaVersion"]);
+    assert_eq!(
+        fields(&decorations["decorations"][0]),
+        [
+            "line",
+            "qualifiedName",
+            "range",
+            "satisfied",
+            "status",
+            "verified"
+        ]
+    );
+    assert_eq!(decorations["decorations"][0]["status"], "open");
+}
+
+#[test]
+fn test_memory_stats_fields() {
+    let (_, _, stats) = payloads();
+    assert_eq!(
+        fields(&stats),
+        [
+            "cancelTokens",
+            "documentTexts",
+            "fileCount",
+            "parseCache",
+            "parseErrors",
+            "references",
+            "schemaVersion",
+            "symbols",
+            "trimmedEntries"
+        ]
+    );
+    assert_eq!(fields(&stats["symbols"]), ["entries", "estimatedBytes"]);
+    assert_eq!(
+        fields(&stats["parseCache"]),
+        [
+            "capacity",
+            "entries",
+            "hitRate",
+            "hits",
+            "misses",
+            "segmentEntries",
+            "sourceBytes"
+        ]
+    );
+}
+
+#[test]
+fn test_version_1_payloads_deserialize() {
+    let diagram: DiagramData = serde_json::from_value(json!({
+        "schemaVersion": 1,
+        "symbols": [{
+            "name": "engine",
+            "qualifiedName": "Vehicles::Vehicle::engine",
+            "nodeType": "PartUsage",
+            "parent": "Vehicles::Vehicle",
+            "typedBy": "Engine"
+        }],
+        "relationships": [{
+            "type": "typing",
+            "source": "Vehicles::Vehicle::engine",
+            "target": "Vehicles::Engine"
+        }],
+        "viewType": "GeneralView"
+    }))
+    .unwrap();
+    assert_eq!(diagram.schema_version, 1);
+    assert_eq!(diagram.symbols[0].typed_by.as_deref(), Some("Engine"));
+    assert_eq!(diagram.relationships[0].rel_type, "typing");
+
+    let export: ModelExport = serde_json::from_value(json!({
+        "schemaVersion": 1,
+        "serverVersion": "0.1.0",
+        "files": [{
+            "uri": "file:///test/Vehicles.sysml",
+            "elements": [{
+                "name": "engine",
+                "qualifiedName": "Vehicles::Vehicle::engine",
+                "kind": "Part",
+                "owner": "Vehicles::Vehicle",
+                "range": {
+                    "start": { "line": 2, "character": 28 },
+                    "end": { "line": 2, "character": 34 }
+                },
+                "relationships": [{
+                    "kind": "typed by",
+                    "target": "Engine",
+                    "resolved": "Vehicles::Engine",
+                    "range": {
+                        "start": { "line": 2, "character": 37 },
+                        "end": { "line": 2, "character": 43 }
+                    }
+                }]
+            }]
+        }]
+    }))
+    .unwrap();
+    assert_eq!(export.schema_version, 1);
+    assert_eq!(
+        export.files[0].elements[0].relationships[0]
+            .resolved
+            .as_deref(),
+        Some("Vehicles::Engine")
+    );
+
+    let structure = json!({ "entries": 1, "estimatedBytes": 64 });
+    let stats: MemoryStats = serde_json::from_value(json!({
+        "schemaVersion": 1,
+        "fileCount": 1,
+        "symbols": structure,
+        "references": structure,
+        "documentTexts": structure,
+        "parseErrors": structure,
+        "cancelTokens": structure,
+        "parseCache": {
+            "entries": 1,
+            "segmentEntries": 0,
+            "capacity": 64,
+            "sourceBytes": 80,
+            "hits": 2,
+            "misses": 1,
+            "hitRate": 0.5
+        },
+        "trimmedEntries": 0
+    }))
+    .unwrap();
+    assert_eq!(stats.schema_version, 1);
+    assert_eq!(stats.parse_cache.hits, 2);
+}
+
+#[test]
+fn test_payload_without_version_is_version_0() {
+    let diagram: DiagramData = serde_json::from_value(json!({
+        "symbols": [],
+        "relationships": [],
+        "viewType": "GeneralView"
+    }))
+    .unwrap();
+    assert_eq!(diagram.schema_version, 0);
+
+    let export: ModelExport =
+        serde_json::from_value(json!({ "serverVersion": "0.1.0", "files": [] })).unwrap();
+    assert_eq!(export.schema_version, 0);
+}
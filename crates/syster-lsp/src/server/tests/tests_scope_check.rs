@@ -0,0 +1,112 @@
+//! Tests for duplicate name, redefinition target and fallback resolution diagnostics
+
+use crate::server::LspServer;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Url};
+
+fn diagnostics_with_code(server: &mut LspServer, uri: &Url, code: &str) -> Vec<Diagnostic> {
+    server
+        .get_diagnostics(uri)
+        .into_iter()
+        .filter(|d| d.code == Some(NumberOrString::String(code.to_string())))
+        .collect()
+}
+
+#[test]
+fn test_duplicate_definition_reported_on_later_declaration() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def A;\n    part def A;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = diagnostics_with_code(&mut server, &uri, "duplicate-name");
+    assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(diagnostics[0].range.start.line, 2);
+    assert_eq!(
+        diagnostics[0].message,
+        "'A' is already declared in 'P' at line 2"
+    );
+}
+
+#[test]
+fn test_definition_and_usage_sharing_a_name_conflict() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part x;\n    part def x;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = diagnostics_with_code(&mut server, &uri, "duplicate-name");
+    assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+}
+
+#[test]
+fn test_same_name_in_different_scopes_is_allowed() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def A { part e; }\n    part def B { part e; }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = diagnostics_with_code(&mut server, &uri, "duplicate-name");
+    assert!(diagnostics.is_empty(), "got {diagnostics:?}");
+}
+
+#[test]
+fn test_redefining_feature_of_unrelated_definition() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def V { part e; }\n    part def W { part e; }\n    part def C :> V {\n        part f :>> W::e;\n    }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = diagnostics_with_code(&mut server, &uri, "redefinition-target");
+    assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+    assert_eq!(diagnostics[0].range.start, Position::new(4, 19));
+    assert!(diagnostics[0].message.contains("'P::W::e'"));
+    assert!(diagnostics[0].message.contains("supertype of 'P::C'"));
+}
+
+#[test]
+fn test_same_named_redefinition_of_non_inherited_feature() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def V { part e; }\n    part def C {\n        part e :>> e;\n    }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = diagnostics_with_code(&mut server, &uri, "redefinition-target");
+    assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+    assert_eq!(diagnostics[0].range.start, Position::new(3, 19));
+    assert_eq!(
+        diagnostics[0].message,
+        "'e' redefines 'e', which isn't a feature of any supertype of 'P::C'"
+    );
+}
+
+#[test]
+fn test_redefining_inherited_feature_is_allowed() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def V { part e; }\n    part def M :> V;\n    part def C :> M {\n        part e :>> e;\n        part f :>> V::e;\n    }\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = diagnostics_with_code(&mut server, &uri, "redefinition-target");
+    assert!(diagnostics.is_empty(), "got {diagnostics:?}");
+}
+
+#[test]
+fn test_imported_reference_is_not_a_fallback() {
+    let mut server = create_server();
+    let parts = Url::parse("file:///parts.sysml").unwrap();
+    let car = Url::parse("file:///car.sysml").unwrap();
+    server
+        .open_document(&parts, "package Parts {\n    part def Engine;\n}")
+        .unwrap();
+    server
+        .open_document(
+            &car,
+            "package Car {\n    private import Parts::*;\n    part engine : Engine;\n    part spare : Parts::Engine;\n}",
+        )
+        .unwrap();
+
+    let diagnostics = diagnostics_with_code(&mut server, &car, "fallback-resolution");
+    assert!(diagnostics.is_empty(), "got {diagnostics:?}");
+}
@@ -1,10 +1,11 @@
-use crate::server::LspServer;
+use crate::server::configuration::ServerSettings;
 use crate::server::tests::test_helpers::{
     LspServerTestExt, create_server, create_server_with_stdlib,
 };
+use crate::server::{LspError, LspServer};
 use async_lsp::lsp_types::{
     DiagnosticSeverity, HoverContents, MarkupContent, MarkupKind, Position, PrepareRenameResponse,
-    Url,
+    Range, Url,
 };
 
 #[test]
@@ -51,8 +52,26 @@ fn test_open_unsupported_extension() {
     let text = "some text";
 
     let result = server.open_document(&uri, text);
-    assert!(result.is_err());
-    assert!(result.unwrap_err().contains("Unsupported file extension"));
+    let err = result.unwrap_err();
+    assert_eq!(
+        err,
+        LspError::UnsupportedFile(std::path::PathBuf::from("/test.txt"))
+    );
+    assert_eq!(err.to_string(), "Unsupported file extension: txt");
+}
+
+#[test]
+fn test_open_document_error_kinds() {
+    let mut server = create_server();
+
+    let uri = Url::parse("file:///Makefile").unwrap();
+    let err = server.open_document(&uri, "all:").unwrap_err();
+    assert!(matches!(err, LspError::UnsupportedFile(_)));
+    assert_eq!(err.to_string(), "File has no extension");
+
+    let uri = Url::parse("untitled:Untitled-1").unwrap();
+    let err = server.open_document(&uri, "part def A;").unwrap_err();
+    assert_eq!(err, LspError::InvalidUri(uri));
 }
 
 #[test]
@@ -272,6 +291,27 @@ fn test_get_diagnostics_for_nonexistent_file() {
     );
 }
 
+#[test]
+fn test_get_diagnostics_for_unresolved_reference() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def Vehicle;\n    part x : Vehcle;\n}";
+
+    server.open_document(&uri, text).unwrap();
+
+    let diagnostics = server.get_diagnostics(&uri);
+    let unresolved = diagnostics
+        .iter()
+        .find(|d| d.message == "unresolved reference 'Vehcle'")
+        .expect("Should report the unresolved reference");
+    assert_eq!(unresolved.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(
+        unresolved.range,
+        Range::new(Position::new(2, 13), Position::new(2, 19)),
+        "Should span the reference, not the declaration"
+    );
+}
+
 #[test]
 fn test_hover_on_symbol() {
     let mut server = create_server();
@@ -1437,6 +1477,102 @@ package Electronics {
     assert_eq!(elec_children[0].name, "Sensor");
 }
 
+#[test]
+fn test_document_symbols_include_aliases_and_imports() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package Vehicles {
+    part def Car;
+}
+package Fleet {
+    import Vehicles::*;
+    alias Auto for Vehicles::Car;
+    part myCar : Auto;
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let path = std::path::Path::new("/test.sysml");
+    let symbols = server.get_document_symbols(path);
+
+    let fleet = symbols
+        .iter()
+        .find(|s| s.name == "Fleet")
+        .expect("Fleet not found");
+    let children = fleet.children.as_ref().expect("Fleet should have children");
+
+    let import = children
+        .iter()
+        .find(|s| s.name == "import Vehicles::*")
+        .expect("Import should be in the outline");
+    assert_eq!(import.kind, async_lsp::lsp_types::SymbolKind::MODULE);
+    assert_eq!(import.detail.as_deref(), Some("Vehicles::*"));
+
+    let alias = children
+        .iter()
+        .find(|s| s.name == "Auto")
+        .expect("Alias should be in the outline");
+    assert_eq!(alias.kind, async_lsp::lsp_types::SymbolKind::VARIABLE);
+    assert_eq!(alias.detail.as_deref(), Some("Vehicles::Car"));
+
+    assert!(children.iter().any(|s| s.name == "myCar"));
+}
+
+#[test]
+fn test_document_symbols_kinds_details_and_tags() {
+    use async_lsp::lsp_types::{SymbolKind, SymbolTag};
+
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package Model {
+    abstract part def Vehicle;
+    part def Car :> Vehicle;
+    part car : Car;
+    requirement def MassLimit;
+    constraint def Positive;
+    state def Engine;
+    calc def Total;
+    use case def Drive;
+    enum def Color {
+        enum red;
+    }
+    #deprecated part def Old;
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+    let symbols = server.get_document_symbols(std::path::Path::new("/test.sysml"));
+    let model = symbols.iter().find(|s| s.name == "Model").unwrap();
+    let children = model.children.as_ref().unwrap();
+    let find = |name: &str| {
+        children
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("{name} not found"))
+    };
+
+    assert_eq!(find("Vehicle").detail.as_deref(), Some("abstract"));
+    assert_eq!(find("Car").detail.as_deref(), Some(":> Vehicle"));
+    assert_eq!(find("car").detail.as_deref(), Some(": Car"));
+    assert_eq!(find("car").kind, SymbolKind::PROPERTY);
+    assert_eq!(find("MassLimit").kind, SymbolKind::OBJECT);
+    assert_eq!(find("Positive").kind, SymbolKind::BOOLEAN);
+    assert_eq!(find("Engine").kind, SymbolKind::CLASS);
+    assert_eq!(find("Total").kind, SymbolKind::FUNCTION);
+    assert_eq!(find("Drive").kind, SymbolKind::FUNCTION);
+
+    let color = find("Color");
+    assert_eq!(color.kind, SymbolKind::ENUM);
+    let red = &color.children.as_ref().unwrap()[0];
+    assert_eq!(red.kind, SymbolKind::ENUM_MEMBER);
+
+    assert_eq!(find("Old").tags, Some(vec![SymbolTag::DEPRECATED]));
+    assert_eq!(find("Car").tags, None);
+}
+
 #[test]
 fn test_semantic_tokens() {
     let mut server = create_server();
@@ -1601,6 +1737,77 @@ package Usage {
     }
 }
 
+#[test]
+fn test_completion_leaves_no_placeholder_in_the_index() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///usage.sysml").unwrap();
+    let text = r#"
+package Usage {
+    part def Engine;
+    part car : 
+}
+    "#;
+    server.open_document(&uri, text).unwrap();
+
+    let result = server.get_completions(std::path::Path::new("/usage.sysml"), Position::new(3, 15));
+    let async_lsp::lsp_types::CompletionResponse::Array(items) = result else {
+        panic!("Expected completion array");
+    };
+    assert!(items.iter().any(|item| item.label == "Engine"));
+
+    // The repaired statement was only indexed while completing
+    assert!(!server.has_qualified_symbol("Usage::car"));
+}
+
+#[test]
+fn test_completion_offers_aliases_with_target_detail() {
+    let mut server = create_server();
+
+    let lib_uri = Url::parse("file:///quantities.sysml").unwrap();
+    let lib_text = r#"
+package Quantities {
+    attribute def DurationValue;
+    alias Duration for DurationValue;
+    alias TimeValue for DurationValue;
+}
+    "#;
+
+    let uri = Url::parse("file:///usage.sysml").unwrap();
+    let text = r#"
+package Usage {
+    import Quantities::*;
+    alias DurationValue for Quantities::DurationValue;
+    attribute t : 
+}
+    "#;
+
+    server.open_document(&lib_uri, lib_text).unwrap();
+    server.open_document(&uri, text).unwrap();
+
+    let result = server.get_completions(std::path::Path::new("/usage.sysml"), Position::new(4, 18));
+    let async_lsp::lsp_types::CompletionResponse::Array(items) = result else {
+        panic!("Expected completion array");
+    };
+
+    let time = items
+        .iter()
+        .find(|i| i.label == "TimeValue")
+        .expect("alias from another file should be offered");
+    assert_eq!(
+        time.detail.as_deref(),
+        Some("alias for Quantities::DurationValue")
+    );
+    assert_eq!(
+        time.kind,
+        Some(async_lsp::lsp_types::CompletionItemKind::CLASS)
+    );
+    assert!(items.iter().any(|i| i.label == "Duration"));
+
+    // The same-named alias inserts the same reference as its target
+    let same_name = items.iter().filter(|i| i.label == "DurationValue").count();
+    assert_eq!(same_name, 1, "Alias and target shouldn't both be offered");
+}
+
 #[test]
 fn test_completion_respects_scope() {
     let mut server = create_server();
@@ -1672,6 +1879,188 @@ package Test {
     }
 }
 
+#[test]
+fn test_completion_after_colon_offers_only_types() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package Lib {
+    part def Engine;
+}
+package Hidden {
+    part def Secret;
+}
+package Test {
+    import Lib::*;
+    attribute def Speed;
+    part wheel;
+    part car : 
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let result = server.get_completions(std::path::Path::new("/test.sysml"), Position::new(11, 15));
+    let async_lsp::lsp_types::CompletionResponse::Array(items) = result else {
+        panic!("Expected completion array");
+    };
+    let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+
+    assert!(
+        labels.contains(&"Engine"),
+        "imported type missing: {labels:?}"
+    );
+    assert!(labels.contains(&"Speed"), "local type missing: {labels:?}");
+    assert!(
+        !labels.contains(&"wheel"),
+        "usage offered as a type: {labels:?}"
+    );
+    assert!(
+        !labels.contains(&"part def"),
+        "keyword offered as a type: {labels:?}"
+    );
+}
+
+#[test]
+fn test_completion_of_hidden_type_adds_import() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package Lib {
+    part def Engine;
+}
+package Hidden {
+    part def Secret;
+}
+package Test {
+    import Lib::*;
+    part car : 
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let result = server.get_completions(std::path::Path::new("/test.sysml"), Position::new(9, 15));
+    let async_lsp::lsp_types::CompletionResponse::Array(items) = result else {
+        panic!("Expected completion array");
+    };
+
+    let engine = items.iter().find(|i| i.label == "Engine").unwrap();
+    assert!(
+        engine.additional_text_edits.is_none(),
+        "visible type shouldn't add an import"
+    );
+
+    let secret = items
+        .iter()
+        .find(|i| i.label == "Secret")
+        .expect("type from another package should be offered");
+    let edits = secret.additional_text_edits.as_ref().expect("import edit");
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].range.start, Position::new(9, 0));
+    assert_eq!(edits[0].range.end, Position::new(9, 0));
+    assert_eq!(edits[0].new_text, "    private import Hidden::Secret;\n");
+    assert!(
+        secret.sort_text > engine.sort_text,
+        "visible types should sort first"
+    );
+}
+
+#[test]
+fn test_completion_import_goes_after_package_brace_without_imports() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"package Lib {
+    attribute def MassValue;
+}
+package Test {
+    part def Car {
+        attribute mass : 
+    }
+}"#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let result = server.get_completions(std::path::Path::new("/test.sysml"), Position::new(5, 25));
+    let async_lsp::lsp_types::CompletionResponse::Array(items) = result else {
+        panic!("Expected completion array");
+    };
+
+    let mass = items.iter().find(|i| i.label == "MassValue").unwrap();
+    let edits = mass.additional_text_edits.as_ref().expect("import edit");
+    assert_eq!(edits[0].range.start, Position::new(4, 0));
+    assert_eq!(edits[0].new_text, "    private import Lib::MassValue;\n");
+}
+
+#[test]
+fn test_completion_after_specializes_matches_declared_kind() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package Test {
+    part def Vehicle;
+    attribute def Speed;
+    part def Car :> 
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let result = server.get_completions(std::path::Path::new("/test.sysml"), Position::new(4, 20));
+    let async_lsp::lsp_types::CompletionResponse::Array(items) = result else {
+        panic!("Expected completion array");
+    };
+    let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+
+    assert!(labels.contains(&"Vehicle"), "part def missing: {labels:?}");
+    assert!(
+        !labels.contains(&"Speed"),
+        "attribute def offered: {labels:?}"
+    );
+    assert!(
+        !labels.contains(&"Car"),
+        "element offered as its own supertype: {labels:?}"
+    );
+}
+
+#[test]
+fn test_completion_after_redefines_offers_inherited_features() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package Test {
+    part def Vehicle {
+        part engine;
+        attribute mass;
+    }
+    part def Car :> Vehicle {
+        part :>> 
+    }
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let result = server.get_completions(std::path::Path::new("/test.sysml"), Position::new(7, 17));
+    let async_lsp::lsp_types::CompletionResponse::Array(items) = result else {
+        panic!("Expected completion array");
+    };
+    let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+
+    assert!(
+        labels.contains(&"engine"),
+        "inherited part missing: {labels:?}"
+    );
+    assert!(
+        !labels.contains(&"mass"),
+        "attribute offered for a part: {labels:?}"
+    );
+    assert!(
+        !labels.contains(&"Vehicle"),
+        "definition offered: {labels:?}"
+    );
+}
+
 #[test]
 fn test_completion_in_incomplete_expression() {
     let mut server = create_server();
@@ -2045,6 +2434,92 @@ package Test {
     );
 }
 
+#[test]
+fn test_rename_conflicting_name_is_rejected() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package Test {
+    part def Car;
+    part def Bike;
+    part myCar : Car;
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let position = Position::new(2, 14); // On "Car"
+    let err = server
+        .validate_rename(&uri, position, "Bike")
+        .expect_err("Renaming to an existing sibling must conflict");
+    assert!(
+        err.to_string().contains("Bike"),
+        "Error should name the conflict: {err}"
+    );
+
+    assert!(
+        server.get_rename_edits(&uri, position, "Bike").is_none(),
+        "Conflicting rename must not produce edits"
+    );
+}
+
+#[test]
+fn test_validate_rename_from_reference_finds_declaration() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package Test {\n    part def Car;\n    part def Bike;\n    part myCar : Car;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    // On the "Car" type reference of myCar
+    let position = Position::new(3, 18);
+    assert!(matches!(
+        server.validate_rename(&uri, position, "Bike"),
+        Err(LspError::InvalidRename(_))
+    ));
+    assert!(server.validate_rename(&uri, position, "Truck").is_ok());
+}
+
+#[test]
+fn test_rename_same_name_in_other_scope_is_allowed() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package A {
+    part def Car;
+}
+package B {
+    part def Bike;
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let position = Position::new(2, 14); // On "Car"
+    assert!(server.validate_rename(&uri, position, "Bike").is_ok());
+    assert!(server.get_rename_edits(&uri, position, "Bike").is_some());
+}
+
+#[test]
+fn test_rename_invalid_name_is_rejected() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = r#"
+package Test {
+    part def Car;
+}
+    "#;
+
+    server.open_document(&uri, text).unwrap();
+
+    let position = Position::new(2, 14);
+    assert!(matches!(
+        server.validate_rename(&uri, position, "1Car"),
+        Err(LspError::InvalidRename(_))
+    ));
+    assert!(server.validate_rename(&uri, position, "my car").is_err());
+    assert!(server.validate_rename(&uri, position, "'my car'").is_ok());
+}
+
 #[test]
 fn test_prepare_rename_on_definition() {
     let mut server = create_server();
@@ -3610,3 +4085,253 @@ fn test_semantic_tokens_simple_vehicle_model() {
     assert_eq!(*col, 8, "SimpleVehicleModel should start at col 8");
     assert_eq!(*len, 18, "SimpleVehicleModel has 18 chars");
 }
+
+fn hover_markdown(server: &mut LspServer, uri: &Url, position: Position) -> String {
+    let hover = server.get_hover(uri, position).expect("hover expected");
+    let HoverContents::Markup(MarkupContent { value, .. }) = hover.contents else {
+        panic!("Expected markup content");
+    };
+    value
+}
+
+#[test]
+fn test_hover_shows_direction_and_multiplicity() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "action def Move {\n    doc /* moves in a line */\n    in item cargo : Cargo[1..*];\n    out derived attribute distance[0 .. 1];\n}\nitem def Cargo;";
+    server.open_document(&uri, text).unwrap();
+
+    let content = hover_markdown(&mut server, &uri, Position::new(2, 13));
+    assert!(content.contains("**Direction:** `in`"), "{content}");
+    assert!(content.contains("**Multiplicity:** `[1..*]`"), "{content}");
+    assert!(!content.contains("**Modifiers:**"), "{content}");
+
+    let content = hover_markdown(&mut server, &uri, Position::new(3, 28));
+    assert!(content.contains("**Direction:** `out`"), "{content}");
+    assert!(content.contains("**Multiplicity:** `[0..1]`"), "{content}");
+    assert!(content.contains("**Modifiers:** derived"), "{content}");
+
+    // The modifiers line comes before the qualified name
+    let modifiers = content.find("**Direction:**").unwrap();
+    let qualified = content.find("**Qualified Name:**").unwrap();
+    assert!(modifiers < qualified);
+}
+
+#[test]
+fn test_hover_shows_abstract_definition() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server
+        .open_document(&uri, "abstract part def Vehicle;\npart def Car;")
+        .unwrap();
+
+    let content = hover_markdown(&mut server, &uri, Position::new(0, 19));
+    assert!(content.contains("**Modifiers:** abstract"), "{content}");
+
+    let content = hover_markdown(&mut server, &uri, Position::new(1, 10));
+    assert!(!content.contains("**Modifiers:**"), "{content}");
+    assert!(!content.contains("**Direction:**"), "{content}");
+}
+
+#[test]
+fn test_hover_enum_literal_shows_enumeration_and_siblings() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    enum def IgnitionOnOff {\n        enum on;\n        enum off;\n    }\n    attribute ignition : IgnitionOnOff;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let content = hover_markdown(&mut server, &uri, Position::new(3, 14));
+    assert!(
+        content
+            .contains("**Enumeration:** [IgnitionOnOff](file:///test.sysml#L2) · literal 2 of 2"),
+        "{content}"
+    );
+    assert!(
+        content.contains("**Literals:** `on`, **`off`**"),
+        "{content}"
+    );
+
+    // Other features don't get an enumeration section
+    let content = hover_markdown(&mut server, &uri, Position::new(5, 15));
+    assert!(!content.contains("**Enumeration:**"), "{content}");
+}
+
+#[test]
+fn test_hover_shows_documentation_first() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "part def Vehicle {\n    doc /* A road vehicle.\n     * Has wheels.\n     */\n    part wheels;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let content = hover_markdown(&mut server, &uri, Position::new(0, 10));
+    assert!(
+        content.starts_with("A road vehicle.\nHas wheels.\n\n---\n\n"),
+        "{content}"
+    );
+    assert_eq!(content.matches("A road vehicle.").count(), 1, "{content}");
+    assert!(!content.contains("Inherited from"), "{content}");
+}
+
+#[test]
+fn test_hover_shows_inherited_documentation() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def Vehicle {\n        doc /* A road vehicle. */\n    }\n    part def Car :> Vehicle;\n    part car : Car;\n    part def Plain;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    // Through the specialized definition, and through the typing definition's supertype
+    for position in [Position::new(4, 14), Position::new(5, 10)] {
+        let content = hover_markdown(&mut server, &uri, position);
+        assert!(
+            content.starts_with("A road vehicle.\n\n_Inherited from `P::Vehicle`_\n\n---"),
+            "{content}"
+        );
+    }
+
+    let content = hover_markdown(&mut server, &uri, Position::new(6, 14));
+    assert!(!content.contains("A road vehicle."), "{content}");
+}
+
+#[test]
+fn test_hover_links_qualified_name_segments() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def Vehicle;\n    part car : Vehicle;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let content = hover_markdown(&mut server, &uri, Position::new(2, 10));
+    assert!(
+        content.contains(
+            "**Qualified Name:** [P](file:///test.sysml#L1)::[car](file:///test.sysml#L3)"
+        ),
+        "{content}"
+    );
+    assert!(
+        content.contains("[Vehicle](file:///test.sysml#L2)"),
+        "{content}"
+    );
+}
+
+#[test]
+fn test_hover_references_link_referencing_element() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package P {\n    part def Vehicle;\n    part car : Vehicle;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    let content = hover_markdown(&mut server, &uri, Position::new(1, 14));
+    assert!(
+        content.contains(
+            "- [test.sysml:3:16](file:///test.sysml#L3) in [P::car](file:///test.sysml#L3)\n"
+        ),
+        "{content}"
+    );
+}
+
+#[test]
+fn test_hover_package_members_include_other_files() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let other = Url::parse("file:///other.sysml").unwrap();
+    server
+        .open_document(
+            &uri,
+            "package Vehicles {\n    private import Missing::*;\n    part def Car;\n}",
+        )
+        .unwrap();
+    server
+        .open_document(
+            &other,
+            "package Vehicles {\n    part def Truck;\n    part def Bus;\n}",
+        )
+        .unwrap();
+
+    let content = hover_markdown(&mut server, &uri, Position::new(0, 10));
+    assert!(
+        content.contains("**Members:** 3 · **Imports:** 0 of 1 resolved"),
+        "{content}"
+    );
+}
+
+#[test]
+fn test_hover_package_header_shows_import_status() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "package Lib {\n    part def Engine;\n}\npackage Vehicles {\n    private import Lib::*;\n    private import Missing::Thing;\n    part def Car;\n}";
+    server.open_document(&uri, text).unwrap();
+
+    // On the keyword and on the name
+    for position in [Position::new(3, 2), Position::new(3, 10)] {
+        let content = hover_markdown(&mut server, &uri, position);
+        assert!(
+            content.contains("**Members:** 1 · **Imports:** 1 of 2 resolved, 1 failed"),
+            "{content}"
+        );
+        assert!(
+            content.contains("[first failure](file:///test.sysml#L6)"),
+            "{content}"
+        );
+        assert!(content.contains("- `Lib::*` resolved"), "{content}");
+        assert!(
+            content.contains("- `Missing::Thing` unresolved"),
+            "{content}"
+        );
+    }
+
+    // Packages without imports and other elements don't get the section
+    let content = hover_markdown(&mut server, &uri, Position::new(0, 10));
+    assert!(!content.contains("**Imports:**"), "{content}");
+    let content = hover_markdown(&mut server, &uri, Position::new(6, 14));
+    assert!(!content.contains("**Imports:**"), "{content}");
+}
+
+#[test]
+fn test_hover_shows_inheritance_chain() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "part def A;\npart def B :> A;\npart def C :> B;\npart def D :> C;";
+    server.open_document(&uri, text).unwrap();
+
+    let content = hover_markdown(&mut server, &uri, Position::new(3, 9));
+    assert!(
+        content.contains(
+            "**Inheritance:** [C](file:///test.sysml#L3) → [B](file:///test.sysml#L2) → [A](file:///test.sysml#L1)\n"
+        ),
+        "{content}"
+    );
+
+    // Direct supertypes alone are left to the relationships section
+    let content = hover_markdown(&mut server, &uri, Position::new(1, 9));
+    assert!(!content.contains("**Inheritance:**"), "{content}");
+}
+
+#[test]
+fn test_hover_inheritance_chain_truncated_at_max_depth() {
+    let mut server = create_server();
+    server.set_hover_max_depth(2);
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    let text = "part def A;\npart def B :> A;\npart def C :> B;\npart def D :> C;";
+    server.open_document(&uri, text).unwrap();
+
+    let content = hover_markdown(&mut server, &uri, Position::new(3, 9));
+    assert!(
+        content.contains(
+            "**Inheritance:** [C](file:///test.sysml#L3) → [B](file:///test.sysml#L2) → …\n"
+        ),
+        "{content}"
+    );
+
+    server.set_hover_max_depth(0);
+    let content = hover_markdown(&mut server, &uri, Position::new(3, 9));
+    assert!(!content.contains("**Inheritance:**"), "{content}");
+}
+
+#[test]
+fn test_parse_hover_max_depth_option() {
+    let options = serde_json::json!({ "hoverMaxDepth": 3 });
+    assert_eq!(
+        ServerSettings::from_options(Some(&options)).hover_max_depth,
+        3
+    );
+    assert_eq!(ServerSettings::from_options(None).hover_max_depth, 5);
+}
@@ -0,0 +1,122 @@
+//! Tests for sessions sharing one workspace
+
+use crate::server::LspError;
+use crate::server::session::SharedWorkspace;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+
+#[test]
+fn test_sessions_share_symbols() {
+    let workspace = SharedWorkspace::new(create_server());
+    let editor = workspace.connect();
+    let webview = workspace.connect();
+    assert_ne!(editor.id(), webview.id());
+
+    let uri = Url::parse("file:///vehicle.sysml").unwrap();
+    editor.open_document(&uri, "part def Vehicle;").unwrap();
+
+    // The second session sees the first session's document without reloading it
+    let symbols = webview
+        .server()
+        .get_document_symbols(&uri.to_file_path().unwrap());
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].name, "Vehicle");
+}
+
+#[test]
+fn test_requests_read_the_sessions_overlay() {
+    let workspace = SharedWorkspace::new(create_server());
+    let editor = workspace.connect();
+    let webview = workspace.connect();
+    let uri = Url::parse("file:///model.sysml").unwrap();
+    let path = uri.to_file_path().unwrap();
+
+    editor.open_document(&uri, "part def Car;").unwrap();
+    webview.open_document(&uri, "part def Truck;").unwrap();
+    assert_eq!(
+        editor.server().get_document_text(&uri).as_deref(),
+        Some("part def Car;")
+    );
+    assert_eq!(editor.server().get_document_symbols(&path)[0].name, "Car");
+    assert_eq!(
+        webview.server().get_document_symbols(&path)[0].name,
+        "Truck"
+    );
+
+    // Edits only change the editing session's overlay
+    editor
+        .change_document(
+            &uri,
+            &TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(0, 9), Position::new(0, 12))),
+                range_length: None,
+                text: "Bus".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        editor.server().get_document_text(&uri).as_deref(),
+        Some("part def Bus;")
+    );
+    assert_eq!(
+        webview.server().get_document_text(&uri).as_deref(),
+        Some("part def Truck;")
+    );
+    assert_eq!(
+        webview.server().document_sessions(&uri),
+        vec![editor.id(), webview.id()]
+    );
+}
+
+#[test]
+fn test_closing_falls_back_to_other_session() {
+    let workspace = SharedWorkspace::new(create_server());
+    let editor = workspace.connect();
+    let webview = workspace.connect();
+    let uri = Url::parse("file:///model.sysml").unwrap();
+
+    editor.open_document(&uri, "part def Car;").unwrap();
+    webview.open_document(&uri, "part def Truck;").unwrap();
+
+    webview.close_document(&uri).unwrap();
+    assert_eq!(
+        editor.server().get_document_text(&uri).as_deref(),
+        Some("part def Car;")
+    );
+    assert_eq!(editor.server().document_sessions(&uri), vec![editor.id()]);
+}
+
+#[test]
+fn test_dropping_session_closes_its_documents() {
+    let workspace = SharedWorkspace::new(create_server());
+    let uri = Url::parse("file:///model.sysml").unwrap();
+    let editor = workspace.connect();
+    {
+        let webview = workspace.connect();
+        editor.open_document(&uri, "part def Car;").unwrap();
+        webview.open_document(&uri, "part def Truck;").unwrap();
+    }
+
+    assert_eq!(editor.server().document_sessions(&uri), vec![editor.id()]);
+    assert_eq!(
+        editor.server().get_document_text(&uri).as_deref(),
+        Some("part def Car;")
+    );
+}
+
+#[test]
+fn test_change_requires_open_document() {
+    let workspace = SharedWorkspace::new(create_server());
+    let session = workspace.connect();
+    let uri = Url::parse("file:///model.sysml").unwrap();
+
+    let result = session.change_document(
+        &uri,
+        &TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "part def Car;".to_string(),
+        },
+    );
+    assert_eq!(result, Err(LspError::FileNotOpen(uri)));
+}
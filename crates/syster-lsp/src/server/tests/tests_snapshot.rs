@@ -0,0 +1,93 @@
+//! Tests for workspace snapshot export and import
+
+use crate::server::LspServer;
+use crate::server::error::LspError;
+use crate::server::snapshot::SNAPSHOT_FORMAT_VERSION;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Url};
+use std::path::PathBuf;
+
+#[test]
+fn test_export_snapshot_holds_open_documents_and_configuration() {
+    let mut server = create_server();
+    server.set_hover_max_depth(2);
+    server.set_naming_conventions(true);
+    let b = Url::parse("file:///b.sysml").unwrap();
+    let a = Url::parse("file:///a.sysml").unwrap();
+    server.set_document_version(&b, 7);
+    server.open_document(&b, "part def B;").unwrap();
+    server.set_document_version(&a, 1);
+    server.open_document(&a, "part def A;").unwrap();
+
+    let snapshot = server.export_workspace_snapshot(false);
+    assert_eq!(snapshot.format_version, SNAPSHOT_FORMAT_VERSION);
+    assert_eq!(snapshot.configuration.hover_max_depth, 2);
+    assert!(snapshot.configuration.naming_conventions);
+    assert_eq!(snapshot.files, None);
+
+    let documents: Vec<(&str, i32, &str)> = snapshot
+        .documents
+        .iter()
+        .map(|doc| (doc.uri.as_str(), doc.version, doc.text.as_str()))
+        .collect();
+    assert_eq!(
+        documents,
+        vec![
+            ("file:///a.sysml", 1, "part def A;"),
+            ("file:///b.sysml", 7, "part def B;"),
+        ]
+    );
+}
+
+#[test]
+fn test_export_snapshot_hashes_workspace_files() {
+    let mut server = create_server();
+    server.set_workspace_folders(vec![PathBuf::from("/ws")]);
+    let uri = Url::parse("file:///ws/model.sysml").unwrap();
+    server.set_document_version(&uri, 1);
+    server.open_document(&uri, "part def A;").unwrap();
+
+    let files = server.export_workspace_snapshot(true).files.unwrap();
+    let file = files
+        .iter()
+        .find(|file| file.path == "/ws/model.sysml")
+        .unwrap();
+    assert_eq!(file.hash.len(), 16);
+    assert_eq!(file.bytes, "part def A;".len());
+
+    // Hashes depend only on the content
+    let again = server.export_workspace_snapshot(true).files.unwrap();
+    assert_eq!(files, again);
+}
+
+#[test]
+fn test_snapshot_round_trips_into_fresh_server() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///model.sysml").unwrap();
+    let text = "part def Engine;\npart def Car {\n    part engine : Engine;\n}";
+    server.set_document_version(&uri, 4);
+    server.open_document(&uri, text).unwrap();
+
+    let json = serde_json::to_string(&server.export_workspace_snapshot(false)).unwrap();
+    let snapshot = serde_json::from_str(&json).unwrap();
+    let mut loaded = LspServer::from_snapshot(&snapshot).unwrap();
+
+    assert_eq!(loaded.get_document_text(&uri).as_deref(), Some(text));
+    assert_eq!(loaded.export_workspace_snapshot(false), snapshot);
+    let definition = loaded.get_definition(&uri, Position::new(2, 20));
+    assert!(
+        definition.is_some(),
+        "references resolve in the loaded server"
+    );
+}
+
+#[test]
+fn test_snapshot_with_unknown_format_is_rejected() {
+    let server = create_server();
+    let mut snapshot = server.export_workspace_snapshot(false);
+    snapshot.format_version = SNAPSHOT_FORMAT_VERSION + 1;
+    assert!(matches!(
+        LspServer::from_snapshot(&snapshot),
+        Err(LspError::InvalidSnapshot(_))
+    ));
+}
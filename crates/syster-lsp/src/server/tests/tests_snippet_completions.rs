@@ -0,0 +1,82 @@
+//! Tests for snippet completions of multi-line constructs
+
+use crate::server::LspServer;
+use crate::server::client_support::ClientSupport;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionResponse, InsertTextFormat, Position, Url,
+};
+use std::path::Path;
+
+fn completions(server: &mut LspServer, text: &str, position: Position) -> Vec<CompletionItem> {
+    let uri = Url::parse("file:///model.sysml").unwrap();
+    server.open_document(&uri, text).unwrap();
+    match server.get_completions(Path::new("/model.sysml"), position) {
+        CompletionResponse::Array(items) => items,
+        _ => panic!("Expected completion array"),
+    }
+}
+
+fn snippet<'a>(items: &'a [CompletionItem], label: &str) -> Option<&'a CompletionItem> {
+    items
+        .iter()
+        .find(|item| item.label == label && item.kind == Some(CompletionItemKind::SNIPPET))
+}
+
+#[test]
+fn test_snippets_at_statement_start() {
+    let mut server = create_server();
+    let items = completions(
+        &mut server,
+        "package P {\n    part def A;\n    \n}",
+        Position::new(2, 4),
+    );
+
+    for label in ["part def", "requirement def", "state def", "analysis"] {
+        let item = snippet(&items, label).unwrap_or_else(|| panic!("no {label} snippet"));
+        assert_eq!(item.insert_text_format, Some(InsertTextFormat::SNIPPET));
+    }
+    // Replaces any shorter snippet of the same name
+    let part_defs = items
+        .iter()
+        .filter(|item| item.label == "part def" && item.kind == Some(CompletionItemKind::SNIPPET));
+    assert_eq!(part_defs.count(), 1);
+
+    let requirement = snippet(&items, "requirement def").unwrap();
+    let body = requirement.insert_text.as_deref().unwrap();
+    assert!(body.starts_with("requirement def ${1:Name} {"), "{body}");
+    assert!(body.contains("doc /* ${2:The requirement} */"), "{body}");
+    assert!(body.contains("require constraint"), "{body}");
+
+    let state = snippet(&items, "state def").unwrap();
+    let body = state.insert_text.as_deref().unwrap();
+    assert!(body.contains("entry; then ${2:idle};"), "{body}");
+    assert!(body.contains("transition first"), "{body}");
+}
+
+#[test]
+fn test_no_snippets_inside_a_statement() {
+    let mut server = create_server();
+    let items = completions(
+        &mut server,
+        "package P {\n    part def A;\n    part a : \n}",
+        Position::new(2, 13),
+    );
+    assert!(snippet(&items, "requirement def").is_none());
+}
+
+#[test]
+fn test_no_snippets_without_client_support() {
+    let mut server = create_server();
+    server.set_client_support(ClientSupport {
+        snippets: false,
+        ..ClientSupport::default()
+    });
+    let items = completions(
+        &mut server,
+        "package P {\n    part def A;\n    \n}",
+        Position::new(2, 4),
+    );
+    assert!(snippet(&items, "requirement def").is_none());
+    assert!(snippet(&items, "analysis").is_none());
+}
@@ -0,0 +1,133 @@
+//! Tests for the on-disk stdlib reference cache
+
+use crate::server::LspServer;
+use crate::server::environment::RealFileSystem;
+use crate::server::stdlib_cache::{STDLIB_CACHE_FILE, StdlibCache};
+use std::path::{Path, PathBuf};
+
+/// Create a scratch stdlib with two files, and an empty cache directory next to it
+fn scratch_stdlib(name: &str) -> (PathBuf, PathBuf) {
+    let dir =
+        std::env::temp_dir().join(format!("syster-stdlib-cache-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let stdlib = dir.join("sysml.library");
+    std::fs::create_dir_all(&stdlib).unwrap();
+    std::fs::write(
+        stdlib.join("Base.sysml"),
+        "package Base { part def Thing; }",
+    )
+    .unwrap();
+    std::fs::write(
+        stdlib.join("Parts.sysml"),
+        "package Parts { import Base::*; part def Part :> Thing; part p : Part; }",
+    )
+    .unwrap();
+    (stdlib, dir.join("cache"))
+}
+
+fn load_server(stdlib: &Path, cache_dir: &Path) -> LspServer {
+    let mut server = LspServer::with_config(true, Some(stdlib.to_path_buf()));
+    server.set_stdlib_cache_dir(Some(cache_dir.to_path_buf()));
+    server.ensure_workspace_loaded().unwrap();
+    server
+}
+
+fn stdlib_files(stdlib: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(stdlib)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn test_cache_written_after_stdlib_load() {
+    let (stdlib, cache_dir) = scratch_stdlib("written");
+    let server = load_server(&stdlib, &cache_dir);
+
+    let cache_file = cache_dir.join(STDLIB_CACHE_FILE);
+    assert!(cache_file.exists());
+    assert!(StdlibCache::load(&RealFileSystem, &cache_file, &stdlib_files(&stdlib)).is_some());
+
+    let parts = stdlib.join("Parts.sysml");
+    assert!(!server.reference_index.entries(&parts).is_empty());
+}
+
+#[test]
+fn test_cache_restores_same_references() {
+    let (stdlib, cache_dir) = scratch_stdlib("restore");
+    let parts = stdlib.join("Parts.sysml");
+    let indexed = load_server(&stdlib, &cache_dir)
+        .reference_index
+        .entries(&parts)
+        .to_vec();
+
+    let restored = load_server(&stdlib, &cache_dir);
+    assert_eq!(restored.reference_index.entries(&parts), indexed.as_slice());
+}
+
+#[test]
+fn test_changed_stdlib_file_invalidates_cache() {
+    let (stdlib, cache_dir) = scratch_stdlib("changed");
+    load_server(&stdlib, &cache_dir);
+    let cache_file = cache_dir.join(STDLIB_CACHE_FILE);
+
+    std::fs::write(
+        stdlib.join("Parts.sysml"),
+        "package Parts { import Base::*; part def Part :> Thing; part p : Part; part q : Part; }",
+    )
+    .unwrap();
+    assert!(StdlibCache::load(&RealFileSystem, &cache_file, &stdlib_files(&stdlib)).is_none());
+
+    // Loading again re-indexes the stdlib and rewrites the cache
+    let server = load_server(&stdlib, &cache_dir);
+    assert!(StdlibCache::load(&RealFileSystem, &cache_file, &stdlib_files(&stdlib)).is_some());
+    let targets: Vec<&str> = server
+        .reference_index
+        .entries(&stdlib.join("Parts.sysml"))
+        .iter()
+        .map(|entry| entry.target.as_ref())
+        .filter(|target| *target == "Part")
+        .collect();
+    assert_eq!(targets.len(), 2);
+}
+
+#[test]
+fn test_added_stdlib_file_invalidates_cache() {
+    let (stdlib, cache_dir) = scratch_stdlib("added");
+    load_server(&stdlib, &cache_dir);
+
+    std::fs::write(stdlib.join("Extra.sysml"), "package Extra;").unwrap();
+    assert!(
+        StdlibCache::load(
+            &RealFileSystem,
+            &cache_dir.join(STDLIB_CACHE_FILE),
+            &stdlib_files(&stdlib)
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn test_corrupt_cache_is_ignored() {
+    let (stdlib, cache_dir) = scratch_stdlib("corrupt");
+    std::fs::create_dir_all(&cache_dir).unwrap();
+    std::fs::write(cache_dir.join(STDLIB_CACHE_FILE), "not json").unwrap();
+
+    let server = load_server(&stdlib, &cache_dir);
+    assert!(
+        !server
+            .reference_index
+            .entries(&stdlib.join("Parts.sysml"))
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_no_cache_dir_writes_nothing() {
+    let (stdlib, cache_dir) = scratch_stdlib("disabled");
+    let mut server = LspServer::with_config(true, Some(stdlib));
+    server.ensure_workspace_loaded().unwrap();
+    assert!(!cache_dir.exists());
+}
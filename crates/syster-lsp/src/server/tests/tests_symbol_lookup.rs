@@ -0,0 +1,95 @@
+//! Tests for the per-file symbol lookup by position and by name
+
+use crate::server::LspServer;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+
+const TEXT: &str = "package P {
+    part def Engine;
+    part engine : Engine;
+}";
+
+fn open() -> (LspServer, Url) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///lookup.sysml").unwrap();
+    server.open_document(&uri, TEXT).unwrap();
+    (server, uri)
+}
+
+fn named(server: &LspServer, name: &str) -> Vec<String> {
+    let mut names: Vec<String> = server
+        .symbol_lookup
+        .named(name)
+        .map(|(_, entry)| entry.qualified_name.to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_symbol_and_reference_at_position() {
+    let (mut server, uri) = open();
+    let path = uri.to_file_path().unwrap();
+
+    // On the declared name
+    let (name, range) = server
+        .find_symbol_at_position(&path, Position::new(2, 10))
+        .unwrap();
+    assert_eq!(name, "P::engine");
+    assert_eq!(range, Range::new(Position::new(2, 9), Position::new(2, 15)));
+
+    // On the type reference
+    let (name, _) = server
+        .find_symbol_at_position(&path, Position::new(2, 20))
+        .unwrap();
+    assert_eq!(name, "Engine");
+
+    assert!(
+        server
+            .find_symbol_at_position(&path, Position::new(1, 2))
+            .is_none()
+    );
+}
+
+#[test]
+fn test_names_follow_edits() {
+    let (mut server, uri) = open();
+    let path = uri.to_file_path().unwrap();
+    server.find_symbol_at_position(&path, Position::new(0, 0));
+    assert_eq!(named(&server, "engine"), vec!["P::engine"]);
+
+    let change = TextDocumentContentChangeEvent {
+        range: Some(Range::new(Position::new(2, 9), Position::new(2, 15))),
+        range_length: None,
+        text: "motor".to_string(),
+    };
+    server.apply_text_change_only(&uri, &change).unwrap();
+    server.parse_document(&uri);
+    server.find_symbol_at_position(&path, Position::new(0, 0));
+
+    assert!(named(&server, "engine").is_empty());
+    assert_eq!(named(&server, "motor"), vec!["P::motor"]);
+    assert_eq!(named(&server, "Engine"), vec!["P::Engine"]);
+}
+
+#[test]
+fn test_aliases_by_target_name() {
+    let mut server = create_server();
+    let uri = Url::parse("file:///aliases.sysml").unwrap();
+    server
+        .open_document(
+            &uri,
+            "package P {\n    part def Engine;\n    alias Motor for P::Engine;\n}",
+        )
+        .unwrap();
+    let path = uri.to_file_path().unwrap();
+    server.find_symbol_at_position(&path, Position::new(0, 0));
+
+    let aliases: Vec<String> = server
+        .symbol_lookup
+        .aliases_naming("Engine")
+        .map(|(_, entry)| entry.qualified_name.to_string())
+        .collect();
+    assert_eq!(aliases, vec!["P::Motor"]);
+    assert_eq!(server.symbol_lookup.aliases_naming("Motor").count(), 0);
+}
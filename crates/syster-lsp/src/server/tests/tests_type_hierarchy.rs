@@ -0,0 +1,108 @@
+//! Tests for the type hierarchy over specializations and redefinitions
+
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Position, Url};
+
+const SOURCE: &str = r#"package P {
+    part def Vehicle;
+    part def Car :> Vehicle;
+    part def Truck :> Vehicle;
+    part def SportsCar :> Car;
+    part def Fleet {
+        part vehicles : Vehicle;
+    }
+    part def CarFleet :> Fleet {
+        part :>> vehicles : Car;
+    }
+}"#;
+
+fn open_source() -> (crate::server::LspServer, Url) {
+    let mut server = create_server();
+    let uri = Url::parse("file:///hierarchy.sysml").unwrap();
+    server.open_document(&uri, SOURCE).unwrap();
+    (server, uri)
+}
+
+#[test]
+fn test_prepare_type_hierarchy_on_definition() {
+    let (mut server, uri) = open_source();
+
+    let items = server
+        .prepare_type_hierarchy(&uri, Position::new(2, 14))
+        .expect("Car should have a hierarchy item");
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name, "Car");
+    assert_eq!(items[0].detail.as_deref(), Some("P::Car"));
+}
+
+#[test]
+fn test_prepare_type_hierarchy_rejects_packages() {
+    let (mut server, uri) = open_source();
+    assert!(
+        server
+            .prepare_type_hierarchy(&uri, Position::new(0, 8))
+            .is_none()
+    );
+}
+
+#[test]
+fn test_supertypes_follow_specialization() {
+    let (mut server, uri) = open_source();
+    let item = server
+        .prepare_type_hierarchy(&uri, Position::new(4, 14))
+        .unwrap()
+        .remove(0);
+
+    let supertypes = server.get_supertypes(&item).unwrap();
+    let names: Vec<_> = supertypes.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, vec!["Car"]);
+
+    let supertypes = server.get_supertypes(&supertypes[0]).unwrap();
+    let names: Vec<_> = supertypes.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, vec!["Vehicle"]);
+}
+
+#[test]
+fn test_supertypes_follow_redefinition() {
+    let (mut server, uri) = open_source();
+    let item = server
+        .prepare_type_hierarchy(&uri, Position::new(9, 17))
+        .expect("redefining usage should have a hierarchy item")
+        .remove(0);
+
+    let supertypes = server.get_supertypes(&item).unwrap();
+    let details: Vec<_> = supertypes
+        .iter()
+        .filter_map(|i| i.detail.as_deref())
+        .collect();
+    assert!(
+        details.contains(&"P::Fleet::vehicles"),
+        "expected redefined feature in {details:?}"
+    );
+}
+
+#[test]
+fn test_subtypes_list_specializations() {
+    let (mut server, uri) = open_source();
+    let item = server
+        .prepare_type_hierarchy(&uri, Position::new(1, 14))
+        .unwrap()
+        .remove(0);
+
+    let subtypes = server.get_subtypes(&item).unwrap();
+    let names: Vec<_> = subtypes.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, vec!["Car", "Truck"]);
+}
+
+#[test]
+fn test_type_hierarchy_registration_requires_client_support() {
+    let mut server = create_server();
+    assert!(server.type_hierarchy_registration().is_none());
+
+    server.set_type_hierarchy_dynamic_registration(true);
+    let registration = server
+        .type_hierarchy_registration()
+        .expect("registration when the client supports it");
+    assert_eq!(registration.method, "textDocument/prepareTypeHierarchy");
+}
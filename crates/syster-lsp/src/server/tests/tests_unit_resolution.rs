@@ -0,0 +1,134 @@
+//! Tests for hovering quantities with units
+
+use crate::server::LspServer;
+use crate::server::constant_eval::evaluate_unit_expression;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{Hover, HoverContents, Position, Range, Url};
+
+/// The shape of the Quantities and Units library packages
+const LIBRARY: &str = "package ISQ {
+    attribute def LengthUnit;
+    attribute def DurationUnit;
+    attribute def MassUnit;
+    attribute def ForceUnit;
+    attribute def SpeedUnit;
+}
+package SIPrefixes {
+    attribute def UnitPrefix;
+    attribute kilo : UnitPrefix {
+        attribute :>> conversionFactor = 1E3;
+    }
+}
+package SI {
+    private import ISQ::*;
+    private import SIPrefixes::*;
+    attribute <m> metre : LengthUnit;
+    attribute <s> second : DurationUnit;
+    attribute <kg> kilogram : MassUnit;
+    attribute <N> newton : ForceUnit = kg*m/s^2;
+    attribute <km> kilometre : LengthUnit {
+        attribute :>> unitConversion {
+            attribute :>> prefix = kilo;
+            attribute :>> referenceUnit = m;
+        }
+    }
+    attribute <h> hour : DurationUnit {
+        attribute :>> unitConversion {
+            attribute :>> referenceUnit = s;
+            attribute :>> conversionFactor = 3600;
+        }
+    }
+    attribute <mps> 'metre per second' : SpeedUnit = m/s;
+}";
+
+const MODEL: &str = "package Vehicles {
+    private import SI::*;
+    part def Car {
+        attribute topSpeed = 180 [km/h];
+        attribute thrust = 2 [N];
+        attribute wheelbase = 2.5 [m];
+        attribute distance = 10 [mi];
+    }
+}";
+
+fn open() -> (LspServer, Url) {
+    let mut server = create_server();
+    let library = Url::parse("file:///Library.sysml").unwrap();
+    server.open_document(&library, LIBRARY).unwrap();
+    let uri = Url::parse("file:///test.sysml").unwrap();
+    server.open_document(&uri, MODEL).unwrap();
+    (server, uri)
+}
+
+fn hover_text(hover: &Hover) -> &str {
+    let HoverContents::Markup(content) = &hover.contents else {
+        panic!("Expected markup hover");
+    };
+    &content.value
+}
+
+#[test]
+fn test_unit_expressions_read_names_as_units() {
+    let newton = evaluate_unit_expression("kg*m/s^2").unwrap();
+    assert_eq!(newton.unit_text().as_deref(), Some("kg*m/s^2"));
+    let scaled = evaluate_unit_expression("1000 * m").unwrap();
+    assert_eq!(scaled.to_string(), "1000 [m]");
+}
+
+#[test]
+fn test_hover_shows_quantity_kind_and_si_value() {
+    let (mut server, uri) = open();
+
+    let hover = server.get_hover(&uri, Position::new(3, 30)).unwrap();
+    let text = hover_text(&hover);
+    assert!(text.contains("**Quantity:** `180 [km/h]`"), "{text}");
+    assert!(text.contains("**Quantity Kind:** speed"), "{text}");
+    assert!(text.contains("**SI Value:** `50 [m/s]`"), "{text}");
+    assert_eq!(
+        hover.range,
+        Some(Range::new(Position::new(3, 29), Position::new(3, 39)))
+    );
+}
+
+#[test]
+fn test_hover_links_the_unit_definitions() {
+    let (mut server, uri) = open();
+
+    // On the unit in brackets
+    let hover = server.get_hover(&uri, Position::new(3, 35)).unwrap();
+    let text = hover_text(&hover);
+    assert!(
+        text.contains("- `km`: [SI::kilometre](file:///Library.sysml#L21) : `ISQ::LengthUnit`"),
+        "{text}"
+    );
+    assert!(
+        text.contains("- `h`: [SI::hour](file:///Library.sysml#L27)"),
+        "{text}"
+    );
+}
+
+#[test]
+fn test_derived_units_convert_through_their_definition() {
+    let (mut server, uri) = open();
+
+    let hover = server.get_hover(&uri, Position::new(4, 27)).unwrap();
+    let text = hover_text(&hover);
+    assert!(text.contains("**Quantity Kind:** force"), "{text}");
+    assert!(text.contains("**SI Value:** `2 [kg*m/s^2]`"), "{text}");
+
+    let hover = server.get_hover(&uri, Position::new(5, 31)).unwrap();
+    assert!(hover_text(&hover).contains("**Quantity Kind:** length"));
+}
+
+#[test]
+fn test_unknown_units_are_not_explained() {
+    let (mut server, uri) = open();
+
+    let hover = server.get_hover(&uri, Position::new(6, 29));
+    assert!(
+        hover
+            .as_ref()
+            .is_none_or(|hover| !hover_text(hover).contains("**Quantity:**")),
+        "{hover:?}"
+    );
+}
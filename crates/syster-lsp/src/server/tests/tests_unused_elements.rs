@@ -0,0 +1,135 @@
+//! Tests for unreferenced definition detection
+
+use crate::server::LspServer;
+use crate::server::configuration::ServerSettings;
+use crate::server::tests::test_helpers::create_server;
+use crate::server::unused_elements::{GetUnusedElementsParams, UNUSED_ELEMENT_CODE};
+use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, Url};
+use std::path::Path;
+
+const VEHICLES: &str = "package V {
+    part def Vehicle;
+    part def Wheel;
+    part def Local;
+    part l : Local;
+}";
+
+const GARAGE: &str = "package G {
+    private import V::Wheel;
+    part car : V::Vehicle;
+}";
+
+fn open_workspace() -> (LspServer, Url) {
+    let mut server = create_server();
+    let vehicles = Url::parse("file:///vehicles.sysml").unwrap();
+    let garage = Url::parse("file:///garage.sysml").unwrap();
+    for (uri, text) in [(&vehicles, VEHICLES), (&garage, GARAGE)] {
+        server.set_document_version(uri, 1);
+        server.open_document(uri, text).unwrap();
+    }
+    (server, vehicles)
+}
+
+fn unused_diagnostics(server: &mut LspServer, uri: &Url) -> Vec<Diagnostic> {
+    server
+        .get_diagnostics(uri)
+        .into_iter()
+        .filter(|d| d.code == Some(NumberOrString::String(UNUSED_ELEMENT_CODE.to_string())))
+        .collect()
+}
+
+fn validate(server: &mut LspServer) {
+    let generation = server.start_project_validation();
+    while let Some(step) = server.continue_project_validation(generation, 16) {
+        if step.finished {
+            break;
+        }
+    }
+}
+
+#[test]
+fn test_definitions_only_used_in_their_own_file_are_unused() {
+    let (mut server, vehicles) = open_workspace();
+
+    let data = server.get_unused_elements(None);
+    assert_eq!(data.elements.len(), 1, "{:?}", data.elements);
+    let local = &data.elements[0];
+    // Typing and importing by name both count as uses
+    assert_eq!(local.qualified_name, "V::Local");
+    assert_eq!(local.kind, "Part def");
+    assert_eq!(local.uri, vehicles.to_string());
+    assert_eq!(
+        local.range,
+        Range::new(Position::new(3, 13), Position::new(3, 18))
+    );
+}
+
+#[test]
+fn test_entry_points_and_their_members_are_not_reported() {
+    let (mut server, _) = open_workspace();
+    server.set_entry_points(vec!["V".to_string()]);
+    assert!(server.get_unused_elements(None).elements.is_empty());
+
+    server.set_entry_points(vec!["V::Local".to_string()]);
+    assert!(server.get_unused_elements(None).elements.is_empty());
+
+    // A name prefix isn't a namespace
+    server.set_entry_points(vec!["V::Loc".to_string()]);
+    assert_eq!(server.get_unused_elements(None).elements.len(), 1);
+}
+
+#[test]
+fn test_unused_elements_of_one_file() {
+    let (mut server, _) = open_workspace();
+    assert!(
+        server
+            .get_unused_elements(Some(Path::new("/garage.sysml")))
+            .elements
+            .is_empty()
+    );
+    assert_eq!(
+        server
+            .get_unused_elements(Some(Path::new("/vehicles.sysml")))
+            .elements
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_unused_element_diagnostics_are_opt_in() {
+    let (mut server, vehicles) = open_workspace();
+    validate(&mut server);
+    assert!(unused_diagnostics(&mut server, &vehicles).is_empty());
+
+    server.set_unused_elements(true);
+    validate(&mut server);
+    let diagnostics = unused_diagnostics(&mut server, &vehicles);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(
+        diagnostics[0].message,
+        "'Local' is not referenced outside this file"
+    );
+    assert_eq!(
+        diagnostics[0].severity,
+        Some(DiagnosticSeverity::INFORMATION)
+    );
+}
+
+#[test]
+fn test_unused_elements_options() {
+    let options = serde_json::json!({
+        "unusedElements": true,
+        "entryPoints": ["Vehicles", "", "Api::Service"],
+    });
+    assert!(ServerSettings::from_options(Some(&options)).unused_elements);
+    assert!(!ServerSettings::from_options(None).unused_elements);
+    assert_eq!(
+        ServerSettings::from_options(Some(&options)).entry_points,
+        ["Vehicles", "Api::Service"]
+    );
+    assert!(ServerSettings::from_options(None).entry_points.is_empty());
+
+    let params: GetUnusedElementsParams = serde_json::from_value(serde_json::json!({})).unwrap();
+    assert!(params.uri.is_none());
+}
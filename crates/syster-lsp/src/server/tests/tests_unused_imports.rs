@@ -0,0 +1,129 @@
+//! Tests for unused import diagnostics
+
+use crate::server::LspServer;
+use crate::server::helpers::apply_text_edit;
+use crate::server::tests::test_helpers::create_server;
+use async_lsp::lsp_types::{
+    CodeActionContext, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic,
+    DiagnosticSeverity, DiagnosticTag, NumberOrString, PartialResultParams, Position, Range,
+    TextDocumentIdentifier, Url, WorkDoneProgressParams,
+};
+
+const LIB: &str = "package Lib {\n    part def Engine;\n    part def Wheel;\n}";
+
+fn open(text: &str) -> (LspServer, Url) {
+    let mut server = create_server();
+    let lib = Url::parse("file:///lib.sysml").unwrap();
+    let uri = Url::parse("file:///app.sysml").unwrap();
+    server.open_document(&lib, LIB).unwrap();
+    server.open_document(&uri, text).unwrap();
+    (server, uri)
+}
+
+fn unused_diagnostics(server: &mut LspServer, uri: &Url) -> Vec<Diagnostic> {
+    server
+        .get_diagnostics(uri)
+        .into_iter()
+        .filter(|d| d.code == Some(NumberOrString::String("unused-import".to_string())))
+        .collect()
+}
+
+#[test]
+fn test_unused_import_is_reported() {
+    let (mut server, uri) = open(
+        "package App {\n    private import Lib::Engine;\n    private import Lib::Wheel;\n    part e : Engine;\n}",
+    );
+
+    let diagnostics = unused_diagnostics(&mut server, &uri);
+    assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+    assert_eq!(diagnostics[0].message, "unused import 'Lib::Wheel'");
+    assert_eq!(diagnostics[0].range.start.line, 2);
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+    assert_eq!(diagnostics[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+}
+
+#[test]
+fn test_wildcard_import_with_a_used_name_is_not_reported() {
+    let (mut server, uri) =
+        open("package App {\n    private import Lib::*;\n    part e : Engine;\n}");
+    assert!(unused_diagnostics(&mut server, &uri).is_empty());
+}
+
+#[test]
+fn test_wildcard_import_without_used_names_is_reported() {
+    let (mut server, uri) = open("package App {\n    private import Lib::*;\n    part def Car;\n}");
+
+    let diagnostics = unused_diagnostics(&mut server, &uri);
+    assert_eq!(diagnostics.len(), 1, "got {diagnostics:?}");
+    assert_eq!(
+        diagnostics[0].message,
+        "none of the names imported by 'Lib::*' are used"
+    );
+}
+
+#[test]
+fn test_public_and_unresolved_imports_are_not_reported() {
+    let (mut server, uri) = open(
+        "package App {\n    public import Lib::Wheel;\n    private import Missing::*;\n    part def Car;\n}",
+    );
+    assert!(unused_diagnostics(&mut server, &uri).is_empty());
+}
+
+#[test]
+fn test_uses_in_other_packages_do_not_count() {
+    let (mut server, uri) = open(
+        "package App {\n    private import Lib::Engine;\n}\npackage Other {\n    private import Lib::Engine;\n    part e : Engine;\n}",
+    );
+
+    let diagnostics = unused_diagnostics(&mut server, &uri);
+    let lines: Vec<u32> = diagnostics.iter().map(|d| d.range.start.line).collect();
+    assert_eq!(lines, [1]);
+}
+
+#[test]
+fn test_unused_import_fix_removes_the_statement() {
+    let text = "package App {\n    private import Lib::Engine;\n    private import Lib::Wheel;\n    part e : Engine;\n}";
+    let (mut server, uri) = open(text);
+    let diagnostics = unused_diagnostics(&mut server, &uri);
+
+    let actions = server.get_code_actions(&CodeActionParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        range: diagnostics[0].range,
+        context: CodeActionContext {
+            diagnostics,
+            only: Some(vec![CodeActionKind::QUICKFIX]),
+            trigger_kind: None,
+        },
+        work_done_progress_params: WorkDoneProgressParams::default(),
+        partial_result_params: PartialResultParams::default(),
+    });
+    let quick_fixes: Vec<_> = actions
+        .iter()
+        .filter_map(|action| match action {
+            CodeActionOrCommand::CodeAction(action)
+                if action.kind == Some(CodeActionKind::QUICKFIX) =>
+            {
+                Some(action)
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(quick_fixes.len(), 1);
+    assert_eq!(quick_fixes[0].title, "Remove unused import 'Lib::Wheel'");
+
+    let edits = &quick_fixes[0]
+        .edit
+        .as_ref()
+        .unwrap()
+        .changes
+        .as_ref()
+        .unwrap()[&uri];
+    assert_eq!(
+        edits[0].range,
+        Range::new(Position::new(2, 0), Position::new(3, 0))
+    );
+    assert_eq!(
+        apply_text_edit(text, &edits[0].range, &edits[0].new_text).unwrap(),
+        "package App {\n    private import Lib::Engine;\n    part e : Engine;\n}"
+    );
+}
@@ -0,0 +1,215 @@
+//! Tests for picking up files changed outside the editor
+
+use crate::server::LspServer;
+use crate::server::tests::test_helpers::{LspServerTestExt, create_server};
+use async_lsp::lsp_types::{FileChangeType, FileEvent, Url};
+use std::path::{Path, PathBuf};
+
+/// Create an empty scratch workspace folder
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("syster-watch-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn load_workspace(dir: &Path) -> LspServer {
+    let mut server = create_server();
+    server.set_workspace_folders(vec![dir.to_path_buf()]);
+    server.ensure_workspace_loaded().unwrap();
+    server
+}
+
+fn event(path: &Path, typ: FileChangeType) -> FileEvent {
+    FileEvent {
+        uri: Url::from_file_path(path).unwrap(),
+        typ,
+    }
+}
+
+#[test]
+fn test_created_file_is_indexed() {
+    let dir = scratch_dir("created");
+    let mut server = load_workspace(&dir);
+    let path = dir.join("Engine.sysml");
+    std::fs::write(&path, "package Engines { part def Engine; }").unwrap();
+
+    let update = server.apply_watched_file_changes(&[event(&path, FileChangeType::CREATED)]);
+
+    assert!(server.has_qualified_symbol("Engines::Engine"));
+    assert!(
+        update
+            .changed
+            .contains(&Url::from_file_path(&path).unwrap())
+    );
+    assert!(update.removed.is_empty());
+}
+
+#[test]
+fn test_changed_file_is_reparsed() {
+    let dir = scratch_dir("changed");
+    let path = dir.join("Model.sysml");
+    std::fs::write(&path, "package Model { part def A; }").unwrap();
+    let mut server = load_workspace(&dir);
+    assert!(server.has_qualified_symbol("Model::A"));
+
+    std::fs::write(&path, "package Model { part def B; }").unwrap();
+    server.apply_watched_file_changes(&[event(&path, FileChangeType::CHANGED)]);
+
+    assert!(server.has_qualified_symbol("Model::B"));
+    assert!(!server.has_qualified_symbol("Model::A"));
+}
+
+#[test]
+fn test_deleted_file_symbols_removed() {
+    let dir = scratch_dir("deleted");
+    let path = dir.join("Model.sysml");
+    std::fs::write(&path, "package Model { part def A; }").unwrap();
+    let mut server = load_workspace(&dir);
+
+    std::fs::remove_file(&path).unwrap();
+    let update = server.apply_watched_file_changes(&[event(&path, FileChangeType::DELETED)]);
+
+    assert!(!server.has_qualified_symbol("Model::A"));
+    assert_eq!(update.removed, vec![Url::from_file_path(&path).unwrap()]);
+    assert!(server.get_document_text(&update.removed[0]).is_none());
+}
+
+/// A definitions file, a file using it and an unrelated file
+fn load_dependent_workspace(name: &str) -> (LspServer, [PathBuf; 3]) {
+    let dir = scratch_dir(name);
+    let defs = dir.join("Defs.sysml");
+    let user = dir.join("User.sysml");
+    let other = dir.join("Other.sysml");
+    std::fs::write(&defs, "package Defs { part def Engine; }").unwrap();
+    std::fs::write(&user, "package User { import Defs::*; part e : Engine; }").unwrap();
+    std::fs::write(&other, "package Other { part def Wheel; }").unwrap();
+    (load_workspace(&dir), [defs, user, other])
+}
+
+#[test]
+fn test_changed_file_republishes_only_its_dependents() {
+    let (mut server, [defs, user, _]) = load_dependent_workspace("dependents");
+
+    std::fs::write(&defs, "package Defs { part def Motor; }").unwrap();
+    let update = server.apply_watched_file_changes(&[event(&defs, FileChangeType::CHANGED)]);
+
+    assert_eq!(
+        update.changed,
+        vec![
+            Url::from_file_path(&defs).unwrap(),
+            Url::from_file_path(&user).unwrap()
+        ]
+    );
+}
+
+#[test]
+fn test_deleted_file_republishes_only_its_dependents() {
+    let (mut server, [defs, user, _]) = load_dependent_workspace("deleted-dependents");
+
+    std::fs::remove_file(&defs).unwrap();
+    let update = server.apply_watched_file_changes(&[event(&defs, FileChangeType::DELETED)]);
+
+    assert_eq!(update.removed, vec![Url::from_file_path(&defs).unwrap()]);
+    assert_eq!(update.changed, vec![Url::from_file_path(&user).unwrap()]);
+    assert!(!server.has_file_path(&defs));
+}
+
+#[test]
+fn test_renamed_file_keeps_symbols() {
+    let dir = scratch_dir("renamed");
+    let old = dir.join("Old.sysml");
+    let new = dir.join("New.sysml");
+    std::fs::write(&old, "package Model { part def A; }").unwrap();
+    let mut server = load_workspace(&dir);
+
+    std::fs::rename(&old, &new).unwrap();
+    let update = server.apply_watched_file_changes(&[
+        event(&old, FileChangeType::DELETED),
+        event(&new, FileChangeType::CREATED),
+    ]);
+
+    assert!(server.has_qualified_symbol("Model::A"));
+    assert_eq!(update.removed, vec![Url::from_file_path(&old).unwrap()]);
+    assert!(update.changed.contains(&Url::from_file_path(&new).unwrap()));
+}
+
+#[test]
+fn test_deleted_directory_removes_its_files() {
+    let dir = scratch_dir("deleted-dir");
+    let sub = dir.join("parts");
+    std::fs::create_dir_all(&sub).unwrap();
+    std::fs::write(sub.join("A.sysml"), "package A;").unwrap();
+    std::fs::write(sub.join("B.sysml"), "package B;").unwrap();
+    let mut server = load_workspace(&dir);
+
+    std::fs::remove_dir_all(&sub).unwrap();
+    let update = server.apply_watched_file_changes(&[event(&sub, FileChangeType::DELETED)]);
+
+    assert_eq!(update.removed.len(), 2);
+    assert!(!server.has_qualified_symbol("A"));
+    assert!(!server.has_qualified_symbol("B"));
+}
+
+#[test]
+fn test_document_open_in_editor_is_left_alone() {
+    let dir = scratch_dir("open");
+    let path = dir.join("Model.sysml");
+    std::fs::write(&path, "package Model { part def A; }").unwrap();
+    let mut server = load_workspace(&dir);
+    let uri = Url::from_file_path(&path).unwrap();
+    server.set_document_version(&uri, 1);
+    server
+        .open_document(&uri, "package Model { part def Edited; }")
+        .unwrap();
+
+    std::fs::write(&path, "package Model { part def OnDisk; }").unwrap();
+    server.apply_watched_file_changes(&[event(&path, FileChangeType::CHANGED)]);
+    assert!(server.has_qualified_symbol("Model::Edited"));
+    assert!(!server.has_qualified_symbol("Model::OnDisk"));
+
+    // Once closed, the file on disk is picked up again
+    server.close_document(&uri).unwrap();
+    server.apply_watched_file_changes(&[event(&path, FileChangeType::CHANGED)]);
+    assert!(server.has_qualified_symbol("Model::OnDisk"));
+}
+
+#[test]
+fn test_files_outside_workspace_or_excluded_are_ignored() {
+    let dir = scratch_dir("ignored");
+    let mut server = create_server();
+    server.set_workspace_folders(vec![dir.join("src")]);
+    server.set_exclude_globs(vec!["generated/".to_string()]);
+    server.ensure_workspace_loaded().unwrap();
+
+    let outside = dir.join("Outside.sysml");
+    let excluded = dir.join("src/generated/Gen.sysml");
+    let notes = dir.join("src/notes.txt");
+    std::fs::create_dir_all(excluded.parent().unwrap()).unwrap();
+    std::fs::write(&outside, "package Outside;").unwrap();
+    std::fs::write(&excluded, "package Gen;").unwrap();
+    std::fs::write(&notes, "package Notes;").unwrap();
+
+    server.apply_watched_file_changes(&[
+        event(&outside, FileChangeType::CREATED),
+        event(&excluded, FileChangeType::CREATED),
+        event(&notes, FileChangeType::CREATED),
+    ]);
+
+    assert!(!server.has_file_path(&outside));
+    assert!(!server.has_file_path(&excluded));
+    assert!(!server.has_file_path(&notes));
+}
+
+#[test]
+fn test_registration_requires_client_support() {
+    let mut server = create_server();
+    assert!(server.watched_files_registration().is_none());
+
+    server.set_watched_files_dynamic_registration(true);
+    let registration = server.watched_files_registration().unwrap();
+    assert_eq!(registration.method, "workspace/didChangeWatchedFiles");
+    let options = registration.register_options.unwrap();
+    assert_eq!(options["watchers"][0]["globPattern"], "**/*.{sysml,kerml}");
+    assert_eq!(options["watchers"][1]["globPattern"], "**/syster.toml");
+}
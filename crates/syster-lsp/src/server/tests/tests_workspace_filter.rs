@@ -0,0 +1,140 @@
+//! Tests for excluding files from workspace indexing
+
+use crate::server::LspServer;
+use crate::server::configuration::ServerSettings;
+use crate::server::workspace_filter::WorkspaceFilter;
+use std::path::{Path, PathBuf};
+
+/// Create an empty scratch directory for filter tests
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("syster-filter-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(path: &Path, content: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+fn relative(root: &Path, paths: Vec<PathBuf>) -> Vec<String> {
+    paths
+        .iter()
+        .map(|p| {
+            p.strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect()
+}
+
+#[test]
+fn test_exclude_globs_match_gitignore_style() {
+    let root = Path::new("/ws");
+    let globs: Vec<String> = [
+        "build/",
+        "*.gen.sysml",
+        "/vendor",
+        "**/generated/**",
+        "!keep.gen.sysml",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    let filter = WorkspaceFilter::for_folder(root, &globs);
+
+    assert!(filter.is_excluded(Path::new("/ws/build"), true));
+    assert!(filter.is_excluded(Path::new("/ws/models/build"), true));
+    assert!(
+        !filter.is_excluded(Path::new("/ws/build"), false),
+        "dir-only pattern"
+    );
+    assert!(filter.is_excluded(Path::new("/ws/a/b.gen.sysml"), false));
+    assert!(
+        !filter.is_excluded(Path::new("/ws/a/keep.gen.sysml"), false),
+        "negated"
+    );
+    assert!(filter.is_excluded(Path::new("/ws/vendor"), true));
+    assert!(
+        !filter.is_excluded(Path::new("/ws/models/vendor"), true),
+        "anchored"
+    );
+    assert!(filter.is_excluded(Path::new("/ws/x/generated/y.sysml"), false));
+    assert!(!filter.is_excluded(Path::new("/ws/models/car.sysml"), false));
+    assert!(!filter.is_excluded(Path::new("/elsewhere/build"), true));
+}
+
+#[test]
+fn test_collect_files_respects_ignore_files() {
+    let dir = scratch_dir("ignore-files");
+    write(&dir.join(".gitignore"), "# build output\nout/\n");
+    write(&dir.join(".systerignore"), "third_party\n");
+    write(&dir.join("model.sysml"), "part def A;");
+    write(&dir.join("out/copy.sysml"), "part def A;");
+    write(&dir.join("lib/third_party/vendored.kerml"), "classifier V;");
+    write(&dir.join("lib/own.kerml"), "classifier O;");
+    write(&dir.join("notes.txt"), "not a model");
+
+    let files = WorkspaceFilter::for_folder(&dir, &[])
+        .collect_files()
+        .unwrap();
+    assert_eq!(relative(&dir, files), vec!["lib/own.kerml", "model.sysml"]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_parse_exclude_globs_option() {
+    let options = serde_json::json!({ "excludeGlobs": ["gen/", 3, "*.tmp.sysml"] });
+    assert_eq!(
+        ServerSettings::from_options(Some(&options)).exclude_globs,
+        vec!["gen/".to_string(), "*.tmp.sysml".to_string()]
+    );
+    assert!(ServerSettings::from_options(None).exclude_globs.is_empty());
+}
+
+#[test]
+fn test_workspace_loading_skips_excluded_files() {
+    let dir = scratch_dir("workspace");
+    write(&dir.join("model.sysml"), "part def Model;");
+    write(&dir.join("generated/big.sysml"), "part def Generated;");
+
+    let mut server = LspServer::with_config(false, None);
+    server.set_workspace_folders(vec![dir.clone()]);
+    server.set_exclude_globs(vec!["generated/".to_string()]);
+    server.ensure_workspace_loaded().unwrap();
+
+    assert_eq!(server.file_count(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_workspace_files_resolve_without_being_opened() {
+    let dir = scratch_dir("discovery");
+    let lib_path = dir.join("lib/parts/engine.sysml");
+    let app_path = dir.join("app.kerml");
+    write(&lib_path, "package Lib {\n    part def Engine;\n}");
+    write(
+        &dir.join("app.sysml"),
+        "package App {\n    private import Lib::*;\n    part e : Engine;\n}",
+    );
+    write(&app_path, "package Kernel;");
+
+    let mut server = LspServer::with_config(false, None);
+    server.set_workspace_folders(vec![dir.clone()]);
+    server.ensure_workspace_loaded().unwrap();
+    assert_eq!(server.file_count(), 3);
+
+    // Nothing was opened in the editor, yet the reference resolves across files
+    let uri = async_lsp::lsp_types::Url::from_file_path(dir.join("app.sysml")).unwrap();
+    let location = server
+        .get_definition(&uri, async_lsp::lsp_types::Position::new(2, 14))
+        .expect("definition in an unopened file");
+    assert_eq!(location.uri.to_file_path().unwrap(), lib_path);
+    assert_eq!(location.range.start.line, 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
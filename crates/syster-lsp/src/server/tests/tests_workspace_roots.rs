@@ -0,0 +1,238 @@
+//! Tests for multi-root workspaces
+
+use crate::server::LspServer;
+use crate::server::tests::test_helpers::{LspServerTestExt, create_server};
+use async_lsp::lsp_types::{FormattingOptions, Url};
+use std::path::{Path, PathBuf};
+
+/// Create an empty scratch workspace folder
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("syster-roots-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(path: &Path, text: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+fn load_workspace(folders: &[&Path]) -> LspServer {
+    let mut server = create_server();
+    server.set_workspace_folders(folders.iter().map(|f| f.to_path_buf()).collect());
+    server.ensure_workspace_loaded().unwrap();
+    server
+}
+
+#[test]
+fn test_file_belongs_to_innermost_root() {
+    let dir = scratch_dir("innermost");
+    let nested = dir.join("nested");
+    std::fs::create_dir_all(&nested).unwrap();
+    let server = load_workspace(&[&dir, &nested]);
+
+    assert_eq!(
+        server.workspace_root(&dir.join("A.sysml")),
+        Some(dir.as_path())
+    );
+    assert_eq!(
+        server.workspace_root(&nested.join("B.sysml")),
+        Some(nested.as_path())
+    );
+    assert_eq!(server.workspace_root(Path::new("/elsewhere/C.sysml")), None);
+}
+
+#[test]
+fn test_added_root_is_loaded() {
+    let a = scratch_dir("add-a");
+    let b = scratch_dir("add-b");
+    write(&a.join("A.sysml"), "package A { part def X; }");
+    write(&b.join("B.sysml"), "package B { part def Y; }");
+    let mut server = load_workspace(&[&a]);
+    assert!(!server.has_qualified_symbol("B::Y"));
+
+    let update = server.change_workspace_folders(vec![b.clone()], Vec::new());
+
+    assert_eq!(server.workspace_folders(), [a, b.clone()]);
+    assert!(server.has_qualified_symbol("B::Y"));
+    assert!(
+        update
+            .changed
+            .contains(&Url::from_file_path(b.join("B.sysml")).unwrap())
+    );
+    assert!(update.removed.is_empty());
+}
+
+#[test]
+fn test_removed_root_is_unloaded() {
+    let a = scratch_dir("remove-a");
+    let b = scratch_dir("remove-b");
+    write(&a.join("A.sysml"), "package A { part def X; }");
+    write(&b.join("B.sysml"), "package B { part def Y; }");
+    let mut server = load_workspace(&[&a, &b]);
+
+    let open = Url::from_file_path(b.join("Open.sysml")).unwrap();
+    server.set_document_version(&open, 1);
+    server
+        .open_document(&open, "package Open { part def Z; }")
+        .unwrap();
+
+    let update = server.change_workspace_folders(Vec::new(), vec![b.clone()]);
+
+    assert_eq!(server.workspace_folders(), [a]);
+    assert!(server.has_qualified_symbol("A::X"));
+    assert!(!server.has_qualified_symbol("B::Y"));
+    // Documents open in the editor stay loaded
+    assert!(server.has_qualified_symbol("Open::Z"));
+    assert_eq!(
+        update.removed,
+        [Url::from_file_path(b.join("B.sysml")).unwrap()]
+    );
+}
+
+#[test]
+fn test_removing_outer_root_keeps_nested_root() {
+    let dir = scratch_dir("nested");
+    let nested = dir.join("nested");
+    write(&dir.join("Outer.sysml"), "package Outer { part def X; }");
+    write(&nested.join("Inner.sysml"), "package Inner { part def Y; }");
+    let mut server = load_workspace(&[&dir, &nested]);
+
+    server.change_workspace_folders(Vec::new(), vec![dir.clone()]);
+
+    assert!(!server.has_qualified_symbol("Outer::X"));
+    assert!(server.has_qualified_symbol("Inner::Y"));
+}
+
+#[test]
+fn test_changes_before_loading_only_update_folders() {
+    let a = scratch_dir("early-a");
+    write(&a.join("A.sysml"), "package A { part def X; }");
+    let mut server = create_server();
+
+    let update = server.change_workspace_folders(vec![a.clone()], Vec::new());
+
+    assert!(update.changed.is_empty());
+    assert!(!server.has_qualified_symbol("A::X"));
+    server.ensure_workspace_loaded().unwrap();
+    assert!(server.has_qualified_symbol("A::X"));
+}
+
+#[test]
+fn test_formatting_options_per_root() {
+    let a = scratch_dir("format-a");
+    let b = scratch_dir("format-b");
+    write(&a.join("syster.toml"), "[format]\ntab_size = 2\n");
+    write(&b.join("syster.toml"), "[format]\ntab_size = 8\n");
+    let server = load_workspace(&[&a, &b]);
+
+    let editor = FormattingOptions {
+        tab_size: 4,
+        insert_spaces: true,
+        ..Default::default()
+    };
+    let in_a = Url::from_file_path(a.join("Model.sysml")).unwrap();
+    let in_b = Url::from_file_path(b.join("Model.sysml")).unwrap();
+    assert_eq!(
+        server
+            .project_formatting_options(&in_a, editor.clone())
+            .tab_size,
+        2
+    );
+    assert_eq!(server.project_formatting_options(&in_b, editor).tab_size, 8);
+}
+
+#[test]
+fn test_stdlib_per_root() {
+    let a = scratch_dir("stdlib-a");
+    let b = scratch_dir("stdlib-b");
+    write(&a.join("syster.toml"), "[stdlib]\npath = \"lib\"\n");
+    write(
+        &a.join("lib/Base.sysml"),
+        "package Base { part def Thing; }",
+    );
+    write(&b.join("syster.toml"), "[stdlib]\nenabled = false\n");
+    let server = load_workspace(&[&a, &b]);
+
+    assert_eq!(server.root_stdlib(&a), (false, Some(a.join("lib"))));
+    assert_eq!(server.root_stdlib(&b), (false, None));
+    assert!(server.is_stdlib_path(&a.join("lib/Base.sysml")));
+    assert!(!server.is_stdlib_path(&b.join("Model.sysml")));
+}
+
+#[test]
+fn test_library_of_another_root_is_recognized() {
+    let a = scratch_dir("library-a");
+    let b = scratch_dir("library-b");
+    write(
+        &a.join("syster.toml"),
+        "[stdlib]\npath = \"lib\"\nenabled = true\n",
+    );
+    write(
+        &a.join("lib/Base.sysml"),
+        "package Base { part def Thing; }",
+    );
+    write(
+        &b.join("syster.toml"),
+        "[stdlib]\npath = \"vendor\"\nenabled = true\n",
+    );
+    write(
+        &b.join("vendor/Other.sysml"),
+        "package Other { part def T; }",
+    );
+    let mut server = load_workspace(&[&a, &b]);
+
+    // The first root's library is the one loaded
+    assert!(server.is_stdlib_loaded());
+    assert!(server.has_qualified_symbol("Base::Thing"));
+    assert!(server.is_stdlib_path(&b.join("vendor/Other.sysml")));
+}
+
+#[test]
+fn test_root_resolves_against_its_own_library() {
+    let a = scratch_dir("own-library-a");
+    let b = scratch_dir("own-library-b");
+    let vendor = scratch_dir("own-library-vendor");
+    write(
+        &a.join("syster.toml"),
+        "[stdlib]\npath = \"lib\"\nenabled = true\n",
+    );
+    write(
+        &a.join("lib/Base.sysml"),
+        "package Base { part def Thing; }",
+    );
+    write(
+        &b.join("syster.toml"),
+        &format!(
+            "[stdlib]\npath = {:?}\nenabled = true\n",
+            vendor.display().to_string()
+        ),
+    );
+    write(&vendor.join("Other.sysml"), "package Other { part def T; }");
+    let model = b.join("Model.sysml");
+    write(
+        &model,
+        "package M {\n    private import Other::*;\n    part t : T;\n}",
+    );
+    let mut server = load_workspace(&[&a, &b]);
+
+    // The shared index has the first root's library only
+    assert!(!server.has_qualified_symbol("Other::T"));
+
+    let uri = Url::from_file_path(&model).unwrap();
+    let diagnostics = server.get_diagnostics(&uri);
+    assert!(
+        diagnostics
+            .iter()
+            .all(|diag| !diag.message.contains("unresolved")),
+        "{diagnostics:?}"
+    );
+    let definition = server
+        .get_definition(&uri, async_lsp::lsp_types::Position::new(2, 13))
+        .unwrap();
+    assert_eq!(
+        definition.uri,
+        Url::from_file_path(vendor.join("Other.sysml")).unwrap()
+    );
+}
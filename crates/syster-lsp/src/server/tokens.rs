@@ -0,0 +1,134 @@
+//! Tokens of SysML and KerML text
+//!
+//! Several checks and editor features need the shape of a document's
+//! statements where the AST doesn't keep it: the ends of `flow` and `bind`
+//! statements, the braces around the cursor, the operators of an expression.
+//! They read the document as a flat list of words and punctuation, without
+//! comments and strings, with each token's span in editor coordinates.
+
+use async_lsp::lsp_types::Position;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TokenKind {
+    Word,
+    Punct,
+    LBrace,
+    RBrace,
+    Semi,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Token<'a> {
+    pub(super) kind: TokenKind,
+    pub(super) text: &'a str,
+    pub(super) start: Position,
+    pub(super) end: Position,
+}
+
+/// Split text into words and punctuation, skipping comments and strings.
+/// Columns are in UTF-16 code units.
+pub(super) fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    let mut line = 0u32;
+    let mut col = 0u32;
+
+    let advance = |ch: char, line: &mut u32, col: &mut u32| {
+        if ch == '\n' {
+            *line += 1;
+            *col = 0;
+        } else {
+            *col += ch.len_utf16() as u32;
+        }
+    };
+
+    while let Some((offset, ch)) = chars.next() {
+        let start = Position::new(line, col);
+        advance(ch, &mut line, &mut col);
+
+        if ch.is_whitespace() {
+            continue;
+        }
+
+        let rest = &text[offset..];
+        if rest.starts_with("//") || rest.starts_with("/*") {
+            let block = rest.starts_with("/*");
+            while let Some(&(i, c)) = chars.peek() {
+                if !block && c == '\n' {
+                    break;
+                }
+                chars.next();
+                advance(c, &mut line, &mut col);
+                if block && c == '/' && i > offset + 1 && text[..i].ends_with('*') {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        let (kind, end_offset) = if ch == '"' || ch == '\'' {
+            // Strings are skipped; quoted names are kept as words
+            let mut end = offset + ch.len_utf8();
+            while let Some((i, c)) = chars.next() {
+                advance(c, &mut line, &mut col);
+                end = i + c.len_utf8();
+                if c == '\\' {
+                    if let Some((j, escaped)) = chars.next() {
+                        advance(escaped, &mut line, &mut col);
+                        end = j + escaped.len_utf8();
+                    }
+                } else if c == ch {
+                    break;
+                }
+            }
+            if ch == '"' {
+                continue;
+            }
+            (TokenKind::Word, end)
+        } else if ch.is_alphanumeric() || ch == '_' {
+            let mut end = offset + ch.len_utf8();
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                chars.next();
+                advance(c, &mut line, &mut col);
+                end = i + c.len_utf8();
+            }
+            (TokenKind::Word, end)
+        } else {
+            let kind = match ch {
+                '{' => TokenKind::LBrace,
+                '}' => TokenKind::RBrace,
+                ';' => TokenKind::Semi,
+                _ => TokenKind::Punct,
+            };
+            let mut end = offset + ch.len_utf8();
+            // Keep `::` and `:=` together so they aren't mistaken for typing
+            if ch == ':'
+                && let Some(&(i, c @ (':' | '=' | '>'))) = chars.peek()
+            {
+                chars.next();
+                advance(c, &mut line, &mut col);
+                end = i + 1;
+            }
+            (kind, end)
+        };
+
+        tokens.push(Token {
+            kind,
+            text: &text[offset..end_offset],
+            start,
+            end: Position::new(line, col),
+        });
+    }
+
+    tokens
+}
+
+/// A name without the quotes of an unrestricted name
+pub(super) fn unquote(name: &str) -> &str {
+    name.strip_prefix('\'')
+        .and_then(|n| n.strip_suffix('\''))
+        .unwrap_or(name)
+}
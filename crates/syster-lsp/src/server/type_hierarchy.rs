@@ -0,0 +1,208 @@
+//! Type hierarchy over specialization (`:>`) and redefinition (`:>>`)
+//!
+//! Supertypes of an element are the elements it specializes, subsets or
+//! redefines; subtypes are the elements that specialize, subset or redefine it.
+
+use std::collections::HashSet;
+
+use super::LspServer;
+use super::document_symbols::convert_symbol_kind;
+use super::helpers::{item_qualified_name, uri_to_path};
+use async_lsp::lsp_types::{Position, Range, Registration, TypeHierarchyItem, Url};
+use syster::hir::{HirSymbol, RefKind, SymbolIndex, SymbolKind as HirSymbolKind};
+use syster::ide::Analysis;
+
+/// Method name used to register type hierarchy support dynamically
+pub const PREPARE_TYPE_HIERARCHY_METHOD: &str = "textDocument/prepareTypeHierarchy";
+
+impl LspServer {
+    /// Registration for type hierarchy support.
+    ///
+    /// The server capabilities type has no `typeHierarchyProvider` field, so the
+    /// feature is registered dynamically once the client is initialized. Returns
+    /// `None` if the client doesn't support dynamic registration for it.
+    pub fn type_hierarchy_registration(&self) -> Option<Registration> {
+        self.client_support
+            .type_hierarchy_registration
+            .then(|| Registration {
+                id: PREPARE_TYPE_HIERARCHY_METHOD.to_string(),
+                method: PREPARE_TYPE_HIERARCHY_METHOD.to_string(),
+                register_options: Some(serde_json::json!({
+                    "documentSelector": [
+                        { "language": "sysml" },
+                        { "language": "kerml" },
+                        { "pattern": "**/*.{sysml,kerml}" }
+                    ]
+                })),
+            })
+    }
+
+    /// Resolve the element at the given position to a type hierarchy item
+    pub fn prepare_type_hierarchy(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> Option<Vec<TypeHierarchyItem>> {
+        let qualified_name = self.hierarchy_element_at(uri, position)?;
+
+        let analysis = self.analysis_host.analysis();
+        let symbol = analysis.symbol_index().lookup_qualified(&qualified_name)?;
+        Some(vec![type_hierarchy_item(&analysis, symbol)?])
+    }
+
+    /// Qualified name of the element declared or referenced at a position,
+    /// unless it is a package, import or comment
+    pub(super) fn hierarchy_element_at(&mut self, uri: &Url, position: Position) -> Option<String> {
+        let path = uri_to_path(uri)?;
+        let (name, _) = self.find_symbol_at_position(&path, position)?;
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+        let file_id = analysis.get_file_id(&path.to_string_lossy());
+
+        // Type reference targets are written as in source; prefer definitions in this file
+        let symbol = index.lookup_qualified(&name).or_else(|| {
+            index
+                .lookup_simple(&name)
+                .into_iter()
+                .max_by_key(|sym| (sym.kind.is_definition(), Some(sym.file) == file_id))
+        })?;
+
+        if matches!(
+            symbol.kind,
+            HirSymbolKind::Package | HirSymbolKind::Import | HirSymbolKind::Comment
+        ) {
+            return None;
+        }
+        Some(symbol.qualified_name.to_string())
+    }
+
+    /// Elements the given item specializes, subsets or redefines
+    pub fn get_supertypes(&mut self, item: &TypeHierarchyItem) -> Option<Vec<TypeHierarchyItem>> {
+        let qualified_name = item_qualified_name(item.data.as_ref(), item.detail.as_ref())?;
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+        let symbol = index.lookup_qualified(&qualified_name)?;
+
+        let mut seen = HashSet::new();
+        Some(
+            hierarchy_targets(index, symbol)
+                .into_iter()
+                .filter(|sym| seen.insert(sym.qualified_name.clone()))
+                .filter_map(|sym| type_hierarchy_item(&analysis, sym))
+                .collect(),
+        )
+    }
+
+    /// Elements that specialize, subset or redefine the given item
+    pub fn get_subtypes(&mut self, item: &TypeHierarchyItem) -> Option<Vec<TypeHierarchyItem>> {
+        let _ = self.ensure_workspace_loaded();
+        let qualified_name = item_qualified_name(item.data.as_ref(), item.detail.as_ref())?;
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+
+        let mut subtypes: Vec<&HirSymbol> = index
+            .all_symbols()
+            .filter(|sym| {
+                hierarchy_targets(index, sym)
+                    .iter()
+                    .any(|target| target.qualified_name.as_ref() == qualified_name)
+            })
+            .collect();
+        subtypes.sort_by(|a, b| a.qualified_name.cmp(&b.qualified_name));
+
+        Some(
+            subtypes
+                .into_iter()
+                .filter_map(|sym| type_hierarchy_item(&analysis, sym))
+                .collect(),
+        )
+    }
+}
+
+/// Resolved targets of the specialization and redefinition references of `symbol`
+pub(super) fn hierarchy_targets<'a>(
+    index: &'a SymbolIndex,
+    symbol: &'a HirSymbol,
+) -> Vec<&'a HirSymbol> {
+    targets(index, symbol, false)
+}
+
+/// Like [`hierarchy_targets`], together with the definitions typing `symbol`
+pub(super) fn general_types<'a>(
+    index: &'a SymbolIndex,
+    symbol: &'a HirSymbol,
+) -> Vec<&'a HirSymbol> {
+    targets(index, symbol, true)
+}
+
+fn targets<'a>(index: &'a SymbolIndex, symbol: &'a HirSymbol, typing: bool) -> Vec<&'a HirSymbol> {
+    symbol
+        .type_refs
+        .iter()
+        .flat_map(|trk| trk.as_refs())
+        .filter_map(|type_ref| match type_ref.kind {
+            RefKind::Specializes | RefKind::Subsets => {
+                index.lookup_qualified(type_ref.resolved_target.as_deref()?)
+            }
+            RefKind::TypedBy if typing => {
+                index.lookup_qualified(type_ref.resolved_target.as_deref()?)
+            }
+            RefKind::Redefines => redefined_feature(index, symbol, type_ref.target.as_ref()),
+            _ => None,
+        })
+        .filter(|target| target.qualified_name != symbol.qualified_name)
+        .collect()
+}
+
+/// The feature redefined by `symbol`, looked up in the supertypes of its owner
+///
+/// A redefinition names a feature of the same name, so resolving it from the
+/// redefining feature's own scope finds the feature itself.
+pub(super) fn redefined_feature<'a>(
+    index: &'a SymbolIndex,
+    symbol: &HirSymbol,
+    target: &str,
+) -> Option<&'a HirSymbol> {
+    if target.contains("::") {
+        return index.lookup_qualified(target);
+    }
+    let (owner, _) = symbol.qualified_name.rsplit_once("::")?;
+    let owner = index.lookup_qualified(owner)?;
+
+    let mut visited = HashSet::new();
+    let mut pending: Vec<&HirSymbol> = hierarchy_targets(index, owner);
+    while let Some(supertype) = pending.pop() {
+        if !visited.insert(supertype.qualified_name.clone()) {
+            continue;
+        }
+        let candidate = format!("{}::{target}", supertype.qualified_name);
+        if let Some(feature) = index.lookup_qualified(&candidate) {
+            return Some(feature);
+        }
+        pending.extend(hierarchy_targets(index, supertype));
+    }
+    None
+}
+
+fn type_hierarchy_item(analysis: &Analysis<'_>, symbol: &HirSymbol) -> Option<TypeHierarchyItem> {
+    let path = analysis.get_file_path(symbol.file)?;
+    let uri = Url::from_file_path(path).ok()?;
+    let range = Range {
+        start: Position::new(symbol.start_line, symbol.start_col),
+        end: Position::new(symbol.end_line, symbol.end_col),
+    };
+
+    Some(TypeHierarchyItem {
+        name: symbol.name.to_string(),
+        kind: convert_symbol_kind(symbol.kind),
+        tags: None,
+        detail: Some(symbol.qualified_name.to_string()),
+        uri,
+        range,
+        selection_range: range,
+        data: Some(serde_json::Value::String(symbol.qualified_name.to_string())),
+    })
+}
@@ -5,6 +5,7 @@
 
 use super::LspServer;
 use super::helpers::uri_to_path;
+use super::schema::SCHEMA_VERSION;
 use async_lsp::lsp_types::request::Request;
 use async_lsp::lsp_types::{Position, Url};
 use serde::{Deserialize, Serialize};
@@ -34,6 +35,9 @@ pub struct TypeInfoParams {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TypeInfoResult {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
     /// The type name as written in source (may be simple or qualified)
     pub target_name: String,
 
@@ -78,6 +82,7 @@ impl LspServer {
         let info = analysis.type_info_at(file_id, position.line, position.character)?;
 
         Some(TypeInfoResult {
+            schema_version: SCHEMA_VERSION,
             target_name: info.target_name.to_string(),
             resolved_name: info
                 .resolved_symbol
@@ -0,0 +1,286 @@
+//! Resolution of unit expressions against the Quantities and Units library
+//!
+//! In `10 [km/h]`, each unit symbol names a unit declared in a library
+//! package like `SI`, e.g. `attribute <km> kilometre : LengthUnit { ... }`.
+//! The unit's type gives its quantity kind, and its declaration says how it
+//! converts to SI: a binding to other units (`newton : ForceUnit = kg*m/s^2`)
+//! or a `unitConversion` with a `referenceUnit`, scaled by a
+//! `conversionFactor` or the one of a `prefix`. Units with neither are base
+//! units. Following those down gives the factor and base units to convert a
+//! value to SI with.
+//!
+//! The conversion features are looked up in the index under the unit, and
+//! what they are bound to is read like the value expressions of
+//! `constant_eval`. Units of other kinds than those of their types, like
+//! `km/h`, take the kind of a library unit with the same base units.
+
+use std::collections::BTreeMap;
+
+use async_lsp::lsp_types::{Position, Range};
+use syster::hir::{HirSymbol, SymbolIndex};
+
+use super::connector_ends::end_type;
+use super::constant_eval::{Quantity, evaluate, evaluate_unit_expression, value_expression};
+use super::helpers::position_to_byte_offset;
+
+/// How deep unit definitions are followed, against cycles in broken libraries
+const MAX_CONVERSION_DEPTH: usize = 16;
+
+/// A unit symbol of an expression and the unit it names
+#[derive(Debug, Clone)]
+pub(super) struct ResolvedUnit<'a> {
+    /// The symbol as written, e.g. `km`
+    pub(super) symbol: String,
+    pub(super) unit: &'a HirSymbol,
+    /// Qualified name of the unit's type, e.g. `ISQBase::LengthUnit`
+    pub(super) unit_type: Option<String>,
+}
+
+/// A resolved unit expression
+#[derive(Debug, Clone)]
+pub(super) struct UnitResolution<'a> {
+    /// The units its symbols name, leaving out those naming none
+    pub(super) units: Vec<ResolvedUnit<'a>>,
+    /// Quantity kind of the expression, e.g. `speed`
+    pub(super) quantity_kind: Option<String>,
+    /// What one of the unit is in SI base units, e.g. `0.2777… [m/s]` for `km/h`
+    pub(super) si: Option<Quantity>,
+}
+
+/// The literal with a unit (`10 [km/h]`) at `position`, its range and value
+pub(super) fn quantity_literal_at(text: &str, position: Position) -> Option<(Range, Quantity)> {
+    let line = text.lines().nth(position.line as usize)?;
+    let line_start = position_to_byte_offset(text, Position::new(position.line, 0)).ok()?;
+    let cursor = position_to_byte_offset(text, position).ok()? - line_start;
+    let column = |offset: usize| line[..offset].encode_utf16().count() as u32;
+
+    for (open, _) in line.match_indices('[') {
+        let Some(close) = line[open..].find(']').map(|idx| open + idx + 1) else {
+            break;
+        };
+        let number = line[..open].trim_end();
+        let start = number
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-')))
+            .map_or(0, |idx| idx + 1);
+        // `1.5e-3` but not a name, nor the sign of an operation before it
+        let start = start
+            + number[start..]
+                .find(|c: char| c.is_ascii_digit() || c == '.')
+                .unwrap_or(number.len() - start);
+        if !(start..close).contains(&cursor) || start == number.len() {
+            continue;
+        }
+        let literal = &line[start..close];
+        if let Some(value) = evaluate(literal)
+            && !value.unit.is_empty()
+        {
+            let range = Range::new(
+                Position::new(position.line, column(start)),
+                Position::new(position.line, column(close)),
+            );
+            return Some((range, value));
+        }
+    }
+    None
+}
+
+/// Resolve the unit of a quantity from `scope`, or `None` if none of its
+/// symbols names a unit
+pub(super) fn resolve_unit<'a>(
+    index: &'a SymbolIndex,
+    texts: &dyn Fn(&HirSymbol) -> Option<&'a str>,
+    scope: &str,
+    unit: &BTreeMap<String, i32>,
+) -> Option<UnitResolution<'a>> {
+    let units: Vec<ResolvedUnit<'a>> = unit
+        .keys()
+        .filter_map(|symbol| {
+            let unit = lookup_unit(index, scope, symbol)?;
+            Some(ResolvedUnit {
+                symbol: symbol.clone(),
+                unit,
+                unit_type: end_type(index, unit, &|_| false).map(|end| end.definition.to_string()),
+            })
+        })
+        .collect();
+    if units.is_empty() {
+        return None;
+    }
+
+    let written = Quantity {
+        value: 1.0,
+        unit: unit.clone(),
+    };
+    let si = in_base_units(index, texts, scope, &written, 0);
+
+    // A single unit has the kind of its type, others that of their base units
+    let quantity_kind = match units.as_slice() {
+        [single] if unit.len() == 1 && unit[&single.symbol] == 1 => {
+            single.unit_type.as_deref().and_then(kind_name)
+        }
+        _ => None,
+    }
+    .or_else(|| library_kind(index, texts, &units, &si.as_ref()?.unit));
+
+    Some(UnitResolution {
+        units,
+        quantity_kind,
+        si,
+    })
+}
+
+/// The unit a symbol names from `scope`, by name or short name, or from
+/// the `SI` package for models not importing it
+fn lookup_unit<'a>(index: &'a SymbolIndex, scope: &str, symbol: &str) -> Option<&'a HirSymbol> {
+    [scope, "SI"].into_iter().find_map(|scope| {
+        index
+            .resolver_for_scope(scope)
+            .resolve(symbol)
+            .symbol()
+            .and_then(|sym| index.lookup_qualified(&sym.qualified_name))
+            .filter(|sym| is_unit(index, sym))
+    })
+}
+
+/// Whether `symbol` is a unit, a usage typed by a `*Unit` definition
+fn is_unit(index: &SymbolIndex, symbol: &HirSymbol) -> bool {
+    symbol.kind.is_usage()
+        && end_type(index, symbol, &|_| false).is_some_and(|end| end.definition.ends_with("Unit"))
+}
+
+/// A quantity with its unit symbols, named from `scope`, in base units
+fn in_base_units<'a>(
+    index: &'a SymbolIndex,
+    texts: &dyn Fn(&HirSymbol) -> Option<&'a str>,
+    scope: &str,
+    written: &Quantity,
+    depth: usize,
+) -> Option<Quantity> {
+    let mut si = Quantity {
+        value: written.value,
+        unit: BTreeMap::new(),
+    };
+    for (symbol, exponent) in &written.unit {
+        let unit = lookup_unit(index, scope, symbol)?;
+        let base = to_si(index, texts, unit, depth)?;
+        si.value *= base.value.powi(*exponent);
+        for (symbol, power) in base.unit {
+            *si.unit.entry(symbol).or_default() += power * exponent;
+        }
+    }
+    si.unit.retain(|_, power| *power != 0);
+    Some(si)
+}
+
+/// What one of `unit` is in base units, following its definition
+fn to_si<'a>(
+    index: &'a SymbolIndex,
+    texts: &dyn Fn(&HirSymbol) -> Option<&'a str>,
+    unit: &HirSymbol,
+    depth: usize,
+) -> Option<Quantity> {
+    if depth > MAX_CONVERSION_DEPTH {
+        return None;
+    }
+    let text = texts(unit)?;
+    let owner = unit
+        .qualified_name
+        .rsplit_once("::")
+        .map_or("", |(owner, _)| owner);
+    let expand = |expression: &str| {
+        let written = evaluate_unit_expression(expression)?;
+        in_base_units(index, texts, owner, &written, depth + 1)
+    };
+
+    // `newton : ForceUnit = kg*m/s^2`
+    if let Some((expression, _)) = value_expression(text, unit) {
+        return expand(expression);
+    }
+    // `:>> unitConversion : ConversionByPrefix { :>> prefix = kilo; :>> referenceUnit = m; }`
+    let bound = |feature: &str| bound_value(index, texts, unit, feature);
+    if let Some(reference) = bound("referenceUnit") {
+        let factor = match (bound("conversionFactor"), bound("prefix")) {
+            (Some(factor), _) => evaluate(factor)?.value,
+            (None, Some(prefix)) => prefix_factor(index, texts, owner, prefix)?,
+            (None, None) => 1.0,
+        };
+        let mut si = expand(reference)?;
+        si.value *= factor;
+        return Some(si);
+    }
+
+    // A base unit stands for itself
+    let symbol = unit.short_name.as_deref().unwrap_or(unit.name.as_ref());
+    Some(Quantity {
+        value: 1.0,
+        unit: BTreeMap::from([(symbol.to_string(), 1)]),
+    })
+}
+
+/// The conversion factor of a unit prefix like `kilo`, named from `scope`
+fn prefix_factor<'a>(
+    index: &'a SymbolIndex,
+    texts: &dyn Fn(&HirSymbol) -> Option<&'a str>,
+    scope: &str,
+    prefix: &str,
+) -> Option<f64> {
+    let prefix = index
+        .resolver_for_scope(scope)
+        .resolve(prefix)
+        .symbol()
+        .and_then(|sym| index.lookup_qualified(&sym.qualified_name))?;
+    Some(evaluate(bound_value(index, texts, prefix, "conversionFactor")?)?.value)
+}
+
+/// What the feature `name` owned somewhere in `owner`'s body is bound to,
+/// as `referenceUnit` in `:>> unitConversion { :>> referenceUnit = m; }`
+fn bound_value<'a>(
+    index: &'a SymbolIndex,
+    texts: &dyn Fn(&HirSymbol) -> Option<&'a str>,
+    owner: &HirSymbol,
+    name: &str,
+) -> Option<&'a str> {
+    let prefix = format!("{}::", owner.qualified_name);
+    index
+        .symbols_in_file(owner.file)
+        .into_iter()
+        .filter(|sym| sym.name.as_ref() == name && sym.qualified_name.starts_with(&prefix))
+        .find_map(|sym| Some(value_expression(texts(sym)?, sym)?.0))
+}
+
+/// `speed` for `ISQSpaceTime::SpeedUnit`
+fn kind_name(unit_type: &str) -> Option<String> {
+    let name = unit_type.rsplit("::").next()?.strip_suffix("Unit")?;
+    let mut kind = String::new();
+    for ch in name.chars() {
+        if ch.is_uppercase() && !kind.is_empty() {
+            kind.push(' ');
+        }
+        kind.extend(ch.to_lowercase());
+    }
+    (!kind.is_empty()).then_some(kind)
+}
+
+/// The kind of the library unit in the packages of `units` that is made of
+/// the same base units, e.g. `speed` for `m/s`
+fn library_kind<'a>(
+    index: &'a SymbolIndex,
+    texts: &dyn Fn(&HirSymbol) -> Option<&'a str>,
+    units: &[ResolvedUnit<'a>],
+    base: &BTreeMap<String, i32>,
+) -> Option<String> {
+    let mut files = Vec::new();
+    for resolved in units {
+        if !files.contains(&resolved.unit.file) {
+            files.push(resolved.unit.file);
+        }
+    }
+    files
+        .into_iter()
+        .flat_map(|file| index.symbols_in_file(file))
+        .filter(|sym| is_unit(index, sym))
+        .find_map(|sym| {
+            let kind = kind_name(&end_type(index, sym, &|_| false)?.definition)?;
+            (to_si(index, texts, sym, 0)?.unit == *base).then_some(kind)
+        })
+}
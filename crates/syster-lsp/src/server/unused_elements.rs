@@ -0,0 +1,180 @@
+//! Definitions nothing outside their file refers to
+//!
+//! A definition that only its own file uses - or nothing uses at all - is
+//! often a leftover of a refactoring, or part of the model nobody wired in.
+//! `syster/unusedElements` lists them for reports, and with the
+//! `unusedElements` option each one is flagged as part of project
+//! validation. Typing, specialization and the other relationships count as
+//! uses, and so does importing the definition by name.
+//!
+//! Standard library definitions are never reported. Neither are the
+//! `entryPoints` the client configures, nor their members: elements the
+//! workspace exists to provide to tools or other projects.
+
+use super::LspServer;
+use super::document_links::resolve_import;
+use super::organize_imports::in_scope;
+use super::schema::SCHEMA_VERSION;
+use async_lsp::lsp_types::request::Request;
+use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use syster::base::FileId;
+use syster::hir::SymbolKind;
+
+/// Diagnostic code for a definition nothing outside its file refers to
+pub const UNUSED_ELEMENT_CODE: &str = "unused-element";
+
+/// Custom LSP request: syster/unusedElements
+pub enum GetUnusedElementsRequest {}
+
+impl Request for GetUnusedElementsRequest {
+    type Params = GetUnusedElementsParams;
+    type Result = UnusedElementsData;
+    const METHOD: &'static str = "syster/unusedElements";
+}
+
+/// Request parameters for syster/unusedElements
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetUnusedElementsParams {
+    /// Only report definitions in this document; the whole workspace if omitted
+    #[serde(default)]
+    pub uri: Option<String>,
+}
+
+/// Definitions nothing outside their file refers to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnusedElementsData {
+    /// Payload schema version ([`SCHEMA_VERSION`]); 0 from servers predating it
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Ordered by document and position
+    pub elements: Vec<UnusedElement>,
+}
+
+/// A definition nothing outside its file refers to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnusedElement {
+    pub qualified_name: String,
+    pub kind: String,
+    pub uri: String,
+    /// Span of the definition's name
+    pub range: Range,
+}
+
+impl LspServer {
+    /// Definitions of the workspace, or of one file, that nothing outside
+    /// their own file refers to
+    pub fn get_unused_elements(&mut self, file: Option<&Path>) -> UnusedElementsData {
+        let elements = self
+            .unused_definitions()
+            .into_iter()
+            .filter(|(path, _)| file.is_none_or(|file| path == file))
+            .map(|(_, element)| element)
+            .collect();
+        UnusedElementsData {
+            schema_version: SCHEMA_VERSION,
+            elements,
+        }
+    }
+
+    /// Diagnostics for the unreferenced definitions of the project, by file
+    pub(super) fn unused_element_findings(&mut self) -> Vec<(PathBuf, Diagnostic)> {
+        self.unused_definitions()
+            .into_iter()
+            .map(|(path, element)| {
+                let name = element
+                    .qualified_name
+                    .rsplit("::")
+                    .next()
+                    .unwrap_or(&element.qualified_name)
+                    .to_string();
+                let diagnostic = Diagnostic {
+                    range: element.range,
+                    severity: Some(DiagnosticSeverity::INFORMATION),
+                    code: Some(NumberOrString::String(UNUSED_ELEMENT_CODE.to_string())),
+                    message: format!("'{name}' is not referenced outside this file"),
+                    source: Some("syster-semantic".to_string()),
+                    ..Default::default()
+                };
+                (path, diagnostic)
+            })
+            .collect()
+    }
+
+    /// Unreferenced definitions of project files, with the file declaring each
+    fn unused_definitions(&mut self) -> Vec<(PathBuf, UnusedElement)> {
+        let project = self.project_files();
+
+        let analysis = self.analysis_host.analysis();
+        let index = analysis.symbol_index();
+
+        // Files referring to each element
+        let mut referenced_from: HashMap<String, Vec<FileId>> = HashMap::new();
+        let mut refer = |target: String, file: FileId| {
+            let files = referenced_from.entry(target).or_default();
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        };
+        for symbol in index.all_symbols() {
+            if symbol.kind == SymbolKind::Import && !symbol.name.ends_with('*') {
+                if let Some(target) = resolve_import(index, symbol, &symbol.name) {
+                    refer(target.qualified_name.to_string(), symbol.file);
+                }
+                continue;
+            }
+            for type_ref in symbol.type_refs.iter().flat_map(|trk| trk.as_refs()) {
+                let target = match &type_ref.resolved_target {
+                    Some(target) => Some(target.to_string()),
+                    None => index
+                        .resolver_for_scope(&symbol.qualified_name)
+                        .resolve(&type_ref.target)
+                        .symbol()
+                        .map(|target| target.qualified_name.to_string()),
+                };
+                if let Some(target) = target {
+                    refer(target, symbol.file);
+                }
+            }
+        }
+
+        let mut unused = Vec::new();
+        for symbol in index.all_symbols().filter(|sym| sym.kind.is_definition()) {
+            let Some(path) = analysis.get_file_path(symbol.file).map(PathBuf::from) else {
+                continue;
+            };
+            let entry_point = self
+                .entry_points
+                .iter()
+                .any(|entry_point| in_scope(&symbol.qualified_name, entry_point));
+            let used_elsewhere = referenced_from
+                .get(symbol.qualified_name.as_ref())
+                .is_some_and(|files| files.iter().any(|file| *file != symbol.file));
+            if !project.contains(&path) || entry_point || used_elsewhere {
+                continue;
+            }
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            unused.push((
+                path,
+                UnusedElement {
+                    qualified_name: symbol.qualified_name.to_string(),
+                    kind: symbol.kind.display().to_string(),
+                    uri: uri.to_string(),
+                    range: Range::new(
+                        Position::new(symbol.start_line, symbol.start_col),
+                        Position::new(symbol.end_line, symbol.end_col),
+                    ),
+                },
+            ));
+        }
+        unused.sort_by(|(a, x), (b, y)| (a, x.range.start).cmp(&(b, y.range.start)));
+        unused
+    }
+}
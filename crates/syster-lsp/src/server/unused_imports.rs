@@ -0,0 +1,103 @@
+//! Unused import checks
+//!
+//! A private import is unused when no reference made by its package or the
+//! package's members starts with a name it brings in. A wildcard import is
+//! unused only when none of the names it brings in are referenced. Public
+//! imports re-export names to other files and are never reported, and
+//! neither are wildcard imports whose namespace doesn't resolve.
+//!
+//! The references come from the per-file `ReferenceIndex`, the same ones
+//! organize imports uses to drop unused imports.
+
+use async_lsp::lsp_types::{Position, Range};
+use std::collections::BTreeSet;
+use syster::base::FileId;
+use syster::hir::{SymbolIndex, SymbolKind};
+
+use super::code_lens::wildcard_import_names;
+use super::organize_imports::{first_segment, import_scope, import_statement, in_scope};
+use super::reference_index::ReferenceEntry;
+
+/// Diagnostic code for an import nothing refers to
+pub const UNUSED_IMPORT_CODE: &str = "unused-import";
+
+/// An import whose names are never referenced
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnusedImport {
+    /// Span of the import
+    pub range: Range,
+    pub message: String,
+    /// The imported path as written
+    pub path: String,
+    /// Lines to delete, when the statement has them to itself
+    pub removal: Option<Range>,
+}
+
+/// Check the private imports declared in `file` against the references made there
+pub fn check_unused_imports(
+    index: &SymbolIndex,
+    file: FileId,
+    text: &str,
+    entries: &[ReferenceEntry],
+) -> Vec<UnusedImport> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let imports: Vec<_> = index
+        .symbols_in_file(file)
+        .into_iter()
+        .filter(|sym| sym.kind == SymbolKind::Import)
+        .collect();
+
+    let mut unused = Vec::new();
+    for import in imports.iter().filter(|import| !import.is_public) {
+        let scope = import_scope(import);
+        // Other imports of the package can go through the names this one brings in
+        let used: BTreeSet<&str> = entries
+            .iter()
+            .filter(|entry| in_scope(&entry.owner, scope))
+            .map(|entry| first_segment(&entry.target))
+            .chain(
+                imports
+                    .iter()
+                    .filter(|other| other.qualified_name != import.qualified_name)
+                    .filter(|other| import_scope(other) == scope)
+                    .map(|other| first_segment(&other.name)),
+            )
+            .collect();
+
+        let path = import.name.as_ref();
+        let message = match wildcard_import_names(index, import) {
+            Some(names) if names.is_empty() => continue,
+            Some(names) => {
+                if names.keys().any(|name| used.contains(name.as_str())) {
+                    continue;
+                }
+                format!("none of the names imported by '{path}' are used")
+            }
+            None => {
+                let name = path.rsplit("::").next().unwrap_or(path);
+                if used.contains(name.trim_matches('\'')) {
+                    continue;
+                }
+                format!("unused import '{path}'")
+            }
+        };
+
+        unused.push(UnusedImport {
+            range: Range::new(
+                Position::new(import.start_line, import.start_col),
+                Position::new(import.end_line, import.end_col),
+            ),
+            message,
+            path: path.to_string(),
+            removal: import_statement(&lines, import).map(|statement| {
+                Range::new(
+                    Position::new(*statement.lines.start(), 0),
+                    Position::new(*statement.lines.end() + 1, 0),
+                )
+            }),
+        });
+    }
+
+    unused.sort_by_key(|import| (import.range.start.line, import.range.start.character));
+    unused
+}
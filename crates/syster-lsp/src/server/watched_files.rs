@@ -0,0 +1,148 @@
+//! Workspace file watching
+//!
+//! Files created, changed or deleted outside the editor are picked up through
+//! `workspace/didChangeWatchedFiles`. Documents open in the editor are left
+//! alone, since their buffer is newer than the file on disk. A deleted file is
+//! removed from the analysis host, which drops its symbols from the index.
+//! Only the changed files and the documents depending on their definitions
+//! are republished. Changes to a project manifest reload the workspace.
+
+use super::LspServer;
+use super::helpers::uri_to_path;
+use super::project_manifest::MANIFEST_FILE;
+use async_lsp::lsp_types::{
+    DidChangeWatchedFilesRegistrationOptions, FileChangeType, FileEvent, FileSystemWatcher,
+    GlobPattern, Registration, Url,
+};
+use std::path::PathBuf;
+use syster::core::constants::is_supported_extension;
+
+/// Method name used to register the file watcher dynamically
+pub const DID_CHANGE_WATCHED_FILES_METHOD: &str = "workspace/didChangeWatchedFiles";
+
+/// Documents affected by a batch of file changes
+#[derive(Debug, Default, PartialEq)]
+pub struct WatchedFilesUpdate {
+    /// Documents whose diagnostics should be republished
+    pub changed: Vec<Url>,
+    /// Deleted documents whose diagnostics should be cleared
+    pub removed: Vec<Url>,
+}
+
+impl LspServer {
+    /// Registration of a watcher for SysML/KerML files and project manifests
+    /// in the workspace.
+    ///
+    /// Returns `None` if the client can't register it dynamically.
+    pub fn watched_files_registration(&self) -> Option<Registration> {
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![
+                FileSystemWatcher {
+                    glob_pattern: GlobPattern::String("**/*.{sysml,kerml}".to_string()),
+                    kind: None,
+                },
+                FileSystemWatcher {
+                    glob_pattern: GlobPattern::String(format!("**/{MANIFEST_FILE}")),
+                    kind: None,
+                },
+            ],
+        };
+        self.client_support
+            .watched_files_registration
+            .then(|| Registration {
+                id: DID_CHANGE_WATCHED_FILES_METHOD.to_string(),
+                method: DID_CHANGE_WATCHED_FILES_METHOD.to_string(),
+                register_options: serde_json::to_value(options).ok(),
+            })
+    }
+
+    /// Bring the workspace up to date with files changed outside the editor.
+    ///
+    /// Renames arrive as a deletion and a creation. Deleting a directory
+    /// removes every loaded file under it.
+    pub fn apply_watched_file_changes(&mut self, changes: &[FileEvent]) -> WatchedFilesUpdate {
+        let mut update = WatchedFilesUpdate::default();
+
+        let manifest_changed = changes.iter().any(|change| {
+            uri_to_path(&change.uri)
+                .is_some_and(|path| path.file_name().is_some_and(|name| name == MANIFEST_FILE))
+        });
+        if manifest_changed {
+            self.reload_project_manifests();
+        }
+
+        for change in changes {
+            let Some(path) = uri_to_path(&change.uri) else {
+                continue;
+            };
+            if change.typ == FileChangeType::DELETED {
+                let mut deleted: Vec<PathBuf> = self
+                    .analysis_host
+                    .files()
+                    .keys()
+                    .filter(|loaded| loaded.starts_with(&path))
+                    .cloned()
+                    .collect();
+                deleted.sort();
+                for path in deleted {
+                    if self.document_versions.contains_key(&path) {
+                        continue;
+                    }
+                    self.track_definitions(&path);
+                    self.remove_file(&path);
+                    // Files using what it defined lose those references
+                    self.schedule_dependents(&path);
+                    self.dependency_graph.remove(&path);
+                    update.removed.extend(Url::from_file_path(&path).ok());
+                }
+                continue;
+            }
+
+            let supported = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(is_supported_extension);
+            let tracked = self.analysis_host.has_file_path(&path) || self.is_workspace_file(&path);
+            if !supported || !tracked || self.document_versions.contains_key(&path) {
+                continue;
+            }
+            let Ok(text) = self.fs.read_to_string(&path) else {
+                continue;
+            };
+            self.track_definitions(&path);
+            if let Err(err) = self.open_document(&change.uri, &text) {
+                tracing::warn!(path = %path.display(), "Failed to load changed file: {err}");
+                continue;
+            }
+            self.schedule_dependents(&path);
+            update.removed.retain(|uri| uri != &change.uri);
+            update.changed.push(change.uri.clone());
+        }
+
+        update.changed.extend(self.take_dependent_documents());
+        if manifest_changed {
+            // The reloaded project can resolve anything differently
+            update.changed = self
+                .parse_errors
+                .keys()
+                .filter_map(|path| Url::from_file_path(path).ok())
+                .collect();
+        }
+        update.changed.retain(|uri| !update.removed.contains(uri));
+        update.changed.sort();
+        update.changed.dedup();
+        update
+    }
+
+    /// Drop a deleted file's text and symbols. Its recorded definitions are
+    /// kept, so the caller can schedule the files that depended on them.
+    pub(super) fn remove_file(&mut self, path: &std::path::Path) {
+        self.document_texts.remove(path);
+        self.parse_errors.remove(path);
+        self.document_cancel_tokens.remove(path);
+        self.semantic_tokens_cache.remove(path);
+        self.diagnostics_store.remove(path);
+        self.analysis_host.remove_file(path);
+        self.root_libraries.remove_file(path);
+    }
+}
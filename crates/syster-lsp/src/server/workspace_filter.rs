@@ -0,0 +1,221 @@
+//! Exclusion of generated and vendored files from workspace indexing
+//!
+//! Workspace folders are walked with `.gitignore`/`.systerignore` patterns from
+//! the folder root and the configured exclude globs applied. Patterns follow
+//! gitignore syntax: `*`, `?` and `[...]` match within a path segment, `**`
+//! matches any number of segments, a leading or inner `/` anchors a pattern to
+//! the folder, a trailing `/` only matches directories and `!` re-includes.
+//! Ignore files in subdirectories are not read.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use syster::core::constants::SUPPORTED_EXTENSIONS;
+
+/// Ignore files read from the root of each workspace folder, in order
+pub const IGNORE_FILES: &[&str] = &[".gitignore", ".systerignore"];
+
+/// A single gitignore-style pattern
+#[derive(Debug, Clone)]
+struct Pattern {
+    segments: Vec<String>,
+    negated: bool,
+    dir_only: bool,
+    /// Whether the pattern matches from the folder root rather than any level
+    anchored: bool,
+}
+
+impl Pattern {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.trim_start_matches('/');
+        if line.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            segments: line.split('/').map(str::to_string).collect(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, components: &[&str], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            match_segments(&self.segments, components)
+        } else {
+            components
+                .last()
+                .is_some_and(|name| match_segment(&self.segments[0], name))
+        }
+    }
+}
+
+/// Decides which files and directories under a workspace folder are indexed
+#[derive(Debug, Clone)]
+pub struct WorkspaceFilter {
+    root: PathBuf,
+    patterns: Vec<Pattern>,
+}
+
+impl WorkspaceFilter {
+    /// Build the filter for a folder from its ignore files and the exclude globs.
+    ///
+    /// Exclude globs are applied after the ignore files, so they take precedence.
+    pub fn for_folder(root: &Path, exclude_globs: &[String]) -> Self {
+        let ignore_lines: Vec<String> = IGNORE_FILES
+            .iter()
+            .filter_map(|name| fs::read_to_string(root.join(name)).ok())
+            .flat_map(|content| content.lines().map(str::to_string).collect::<Vec<_>>())
+            .collect();
+
+        Self {
+            root: root.to_path_buf(),
+            patterns: ignore_lines
+                .iter()
+                .chain(exclude_globs)
+                .filter_map(|line| Pattern::parse(line))
+                .collect(),
+        }
+    }
+
+    /// Whether a path under the folder is excluded. Paths outside it never are.
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let components: Vec<&str> = relative
+            .components()
+            .filter_map(|component| component.as_os_str().to_str())
+            .collect();
+        if components.is_empty() {
+            return false;
+        }
+
+        // The last matching pattern decides
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.matches(&components, is_dir))
+            .is_some_and(|pattern| !pattern.negated)
+    }
+
+    /// Whether a file is excluded, either itself or through one of its directories
+    pub fn excludes_file(&self, path: &Path) -> bool {
+        path.ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.root))
+            .any(|dir| self.is_excluded(dir, true))
+            || self.is_excluded(path, false)
+    }
+
+    /// Collect the SysML/KerML files under the folder, skipping excluded paths.
+    ///
+    /// Excluded directories aren't descended into.
+    pub fn collect_files(&self) -> Result<Vec<PathBuf>, String> {
+        let mut paths = Vec::new();
+        self.collect_recursive(&self.root, &mut paths)?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn collect_recursive(&self, dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), String> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read directory entry: {e}"))?;
+            let path = entry.path();
+            let is_dir = path.is_dir();
+            if self.is_excluded(&path, is_dir) {
+                continue;
+            }
+
+            if is_dir {
+                self.collect_recursive(&path, paths)?;
+            } else if path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext))
+            {
+                paths.push(path);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Match pattern segments against path components, with `**` spanning components
+fn match_segments(pattern: &[String], components: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => components.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=components.len()).any(|skip| match_segments(rest, &components[skip..]))
+        }
+        Some((first, rest)) => components
+            .split_first()
+            .is_some_and(|(component, remaining)| {
+                match_segment(first, component) && match_segments(rest, remaining)
+            }),
+    }
+}
+
+/// Match a single path segment against `*`, `?` and `[...]` wildcards
+fn match_segment(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    match_chars(&pattern, &name)
+}
+
+fn match_chars(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| match_chars(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && match_chars(&pattern[1..], &name[1..]),
+        Some('[') => {
+            let Some(close) = pattern.iter().position(|&c| c == ']') else {
+                return name.first() == Some(&'[') && match_chars(&pattern[1..], &name[1..]);
+            };
+            let Some(&c) = name.first() else {
+                return false;
+            };
+            let class = &pattern[1..close];
+            let (negated, class) = match class.first() {
+                Some('!' | '^') => (true, &class[1..]),
+                _ => (false, class),
+            };
+            let mut matched = false;
+            let mut i = 0;
+            while i < class.len() {
+                if i + 2 < class.len() && class[i + 1] == '-' {
+                    matched |= class[i] <= c && c <= class[i + 2];
+                    i += 3;
+                } else {
+                    matched |= class[i] == c;
+                    i += 1;
+                }
+            }
+            matched != negated && match_chars(&pattern[close + 1..], &name[1..])
+        }
+        Some('\\') if pattern.len() > 1 => {
+            name.first() == Some(&pattern[1]) && match_chars(&pattern[2..], &name[1..])
+        }
+        Some(&p) => name.first() == Some(&p) && match_chars(&pattern[1..], &name[1..]),
+    }
+}
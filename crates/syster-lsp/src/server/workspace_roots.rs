@@ -0,0 +1,229 @@
+//! Multi-root workspaces
+//!
+//! Every workspace folder is a root, configured by its own `syster.toml`. A
+//! file belongs to the innermost root containing it, and takes its formatting
+//! options and standard library from that root's manifest.
+//!
+//! The shared symbol index holds every root's files and one library: that of
+//! the first root whose manifest configures one, or the client's. Each root
+//! configuring another library gets an index of its own, with that library
+//! and the root's files, which diagnostics, hover, go-to-definition and
+//! completion in the root's files resolve against. Files under the library
+//! of any root are treated as library files.
+//!
+//! Roots come and go with `workspace/didChangeWorkspaceFolders`. An added
+//! root's files are loaded; a removed root's files are unloaded unless they
+//! are open in the editor or belong to a remaining root. When the change
+//! switches the library, the whole workspace is reloaded.
+
+use super::LspServer;
+use super::parallel_parse;
+use super::project_manifest::ProjectManifest;
+use super::watched_files::WatchedFilesUpdate;
+use async_lsp::lsp_types::Url;
+use std::path::{Path, PathBuf};
+use syster::ide::AnalysisHost;
+use syster::project::StdLibLoader;
+use syster::syntax::SyntaxFile;
+
+/// Indices of the roots whose library differs from the shared index's
+#[derive(Default)]
+pub struct RootLibraries {
+    /// Each root with its library and files
+    hosts: Vec<(PathBuf, AnalysisHost)>,
+}
+
+impl RootLibraries {
+    /// The index a file resolves against: its root's, or the shared one
+    pub fn host<'a>(
+        &'a mut self,
+        path: &Path,
+        shared: &'a mut AnalysisHost,
+    ) -> &'a mut AnalysisHost {
+        match self.root_of(path) {
+            Some(idx) => &mut self.hosts[idx].1,
+            None => shared,
+        }
+    }
+
+    /// Put a re-parsed file into the index of its root, if it has one
+    pub fn set_file(&mut self, path: &Path, file: &SyntaxFile) {
+        if let Some(idx) = self.root_of(path) {
+            self.hosts[idx].1.set_file(path.to_path_buf(), file.clone());
+        }
+    }
+
+    /// Take a deleted file out of the index of its root, if it has one
+    pub fn remove_file(&mut self, path: &Path) {
+        if let Some(idx) = self.root_of(path) {
+            self.hosts[idx].1.remove_file(path);
+        }
+    }
+
+    /// The innermost root with an index of its own containing `path`
+    fn root_of(&self, path: &Path) -> Option<usize> {
+        self.hosts
+            .iter()
+            .enumerate()
+            .filter(|(_, (root, _))| path.starts_with(root))
+            .max_by_key(|(_, (root, _))| root.components().count())
+            .map(|(idx, _)| idx)
+    }
+}
+
+impl LspServer {
+    /// The workspace folders, in the order the client listed them
+    pub fn workspace_folders(&self) -> &[PathBuf] {
+        &self.workspace_folders
+    }
+
+    /// The workspace folder a file belongs to: the innermost one containing it
+    pub fn workspace_root(&self, path: &Path) -> Option<&Path> {
+        self.workspace_folders
+            .iter()
+            .filter(|folder| path.starts_with(folder))
+            .max_by_key(|folder| folder.components().count())
+            .map(PathBuf::as_path)
+    }
+
+    /// The project manifest of a workspace folder, if it has a valid one
+    pub fn root_manifest(&self, root: &Path) -> Option<&ProjectManifest> {
+        self.project_manifests
+            .iter()
+            .find(|manifest| manifest.root == root)
+    }
+
+    /// Whether the stdlib is enabled for a workspace folder, and where it is:
+    /// the client's settings, overridden by the folder's manifest
+    pub fn root_stdlib(&self, root: &Path) -> (bool, Option<PathBuf>) {
+        let (enabled, path) = self.configured_stdlib.clone();
+        match self.root_manifest(root) {
+            Some(manifest) => (
+                manifest.stdlib_enabled.unwrap_or(enabled),
+                manifest.stdlib_path.clone().or(path),
+            ),
+            None => (enabled, path),
+        }
+    }
+
+    /// The stdlib settings of the library loaded into the shared index: that
+    /// of the first root whose manifest configures one, or the client's
+    pub(super) fn library_stdlib(&self) -> (bool, Option<PathBuf>) {
+        self.workspace_folders
+            .iter()
+            .find(|folder| {
+                self.root_manifest(folder).is_some_and(|manifest| {
+                    manifest.stdlib_enabled.is_some() || manifest.stdlib_path.is_some()
+                })
+            })
+            .map_or_else(
+                || self.configured_stdlib.clone(),
+                |root| self.root_stdlib(root),
+            )
+    }
+
+    /// Give every root configuring another library than the shared index's an
+    /// index of its own, with that library and the root's files
+    pub(super) fn load_root_libraries(&mut self) {
+        let shared = (self.stdlib_enabled, self.stdlib_path.clone());
+        let mut hosts = Vec::new();
+        for root in &self.workspace_folders {
+            let (enabled, stdlib_path) = self.root_stdlib(root);
+            if (enabled, stdlib_path.clone()) == shared {
+                continue;
+            }
+            let mut host = AnalysisHost::new();
+            if enabled {
+                let loaded = match parallel_parse::stdlib_dir(stdlib_path.as_deref()) {
+                    Some(dir) => parallel_parse::load_stdlib(&dir, &mut host).map(|_| ()),
+                    None => StdLibLoader::new()
+                        .ensure_loaded_into_host(&mut host)
+                        .map_err(|err| err.to_string()),
+                };
+                if let Err(err) = loaded {
+                    tracing::warn!(root = %root.display(), "Failed to load stdlib: {err}");
+                }
+            }
+            for (path, file) in self.analysis_host.files() {
+                if self.workspace_root(path) == Some(root.as_path()) && !self.is_stdlib_path(path) {
+                    host.set_file(path.clone(), file.clone());
+                }
+            }
+            hosts.push((root.clone(), host));
+        }
+        self.root_libraries = RootLibraries { hosts };
+    }
+
+    /// Folders scanned for the model files of a workspace folder: the source
+    /// directories of its manifest, or the folder itself
+    pub(super) fn root_source_folders(&self, root: &Path) -> Vec<PathBuf> {
+        match self.root_manifest(root) {
+            Some(manifest) => manifest.source_folders(),
+            None => vec![root.to_path_buf()],
+        }
+    }
+
+    /// Add and remove workspace folders, loading and unloading their files.
+    ///
+    /// Before the workspace is loaded this only updates the folder list.
+    /// Returns the documents whose diagnostics should be republished or cleared.
+    pub fn change_workspace_folders(
+        &mut self,
+        added: Vec<PathBuf>,
+        removed: Vec<PathBuf>,
+    ) -> WatchedFilesUpdate {
+        let mut update = WatchedFilesUpdate::default();
+        self.workspace_folders
+            .retain(|folder| !removed.contains(folder));
+        let added: Vec<PathBuf> = added
+            .into_iter()
+            .filter(|folder| !self.workspace_folders.contains(folder))
+            .collect();
+        self.workspace_folders.extend(added.iter().cloned());
+        if !self.is_workspace_loaded() {
+            return update;
+        }
+
+        let library = (self.stdlib_enabled, self.stdlib_path.clone());
+        self.project_manifests = self.read_project_manifests();
+        if self.library_stdlib() != library {
+            self.reload_workspace();
+        } else {
+            let mut unloaded: Vec<PathBuf> = self
+                .analysis_host
+                .files()
+                .keys()
+                .filter(|path| {
+                    removed.iter().any(|folder| path.starts_with(folder))
+                        && !self.document_versions.contains_key(*path)
+                        && !self.is_workspace_file(path)
+                        && !self.is_stdlib_path(path)
+                })
+                .cloned()
+                .collect();
+            unloaded.sort();
+            for path in unloaded {
+                self.remove_file(&path);
+                self.dependency_graph.remove(&path);
+                update.removed.extend(Url::from_file_path(&path).ok());
+            }
+
+            let folders: Vec<PathBuf> = added
+                .iter()
+                .flat_map(|root| self.root_source_folders(root))
+                .collect();
+            self.load_folders(&folders);
+            self.sync_document_texts_from_files();
+            self.analysis_host.mark_dirty();
+            self.load_root_libraries();
+        }
+
+        update.changed = self
+            .parse_errors
+            .keys()
+            .filter_map(|path| Url::from_file_path(path).ok())
+            .collect();
+        update.changed.sort();
+        update
+    }
+}